@@ -7,8 +7,7 @@ use bincode::{
 };
 use js_sys::Error;
 use rand_core::OsRng;
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
-use wasm_bindgen_derive::TryFromJsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
 
 use synedrion::TestParams;
 
@@ -17,31 +16,23 @@ extern crate alloc;
 /// Max message length allowed to be (de)serialized
 const MAX_MSG_LEN: u64 = 1000 * 1000; // 1 MB
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(typescript_type = "SigningKey | undefined")]
-    pub type OptionalSigningKey;
-}
-
 /// Secp256k1 signing key.
-#[derive(TryFromJsValue)]
 #[wasm_bindgen]
 #[derive(Clone)]
-pub struct SigningKey(synedrion::k256::ecdsa::SigningKey);
+pub struct SigningKey(synedrion::ecdsa::SigningKey);
 
 #[wasm_bindgen]
 impl SigningKey {
     /// Creates the object from the serialized big-endian scalar
     #[wasm_bindgen(js_name = fromBeBytes)]
     pub fn from_be_bytes(bytes: &[u8]) -> Result<SigningKey, Error> {
-        synedrion::k256::ecdsa::SigningKey::from_slice(bytes)
+        synedrion::ecdsa::SigningKey::from_slice(bytes)
             .map(Self)
             .map_err(|err| Error::new(&format!("{}", err)))
     }
 }
 
 /// Synedrion key share.
-#[derive(TryFromJsValue)]
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct KeyShare(synedrion::KeyShare<TestParams>);
@@ -62,21 +53,12 @@ impl KeyShare {
     #[wasm_bindgen(js_name = newCentralized)]
     pub fn new_centralized(
         num_parties: usize,
-        signing_key: &OptionalSigningKey,
+        signing_key: Option<SigningKey>,
     ) -> Result<Vec<KeyShare>, Error> {
-        let sk_js: &JsValue = signing_key.as_ref();
-        let typed_sk: Option<SigningKey> = if sk_js.is_undefined() {
-            None
-        } else {
-            Some(SigningKey::try_from(sk_js).map_err(|err| Error::new(&err))?)
-        };
-
-        let backend_sk = typed_sk.map(|sk| sk.0);
-
         let shares = synedrion::KeyShare::<TestParams>::new_centralized(
             &mut OsRng,
             num_parties,
-            backend_sk.as_ref(),
+            signing_key.as_ref().map(|sk| &sk.0),
         );
         Ok(shares.into_vec().into_iter().map(KeyShare).collect())
     }
@@ -1,27 +1,58 @@
-use wasm_bindgen::{JsCast, JsValue};
+use k256::ecdsa::{Signature, SigningKey};
+use rand_core::OsRng;
 use wasm_bindgen_test::wasm_bindgen_test;
 
-use synedrion_wasm::{KeyShare, SigningKey};
-
-fn into_js_option<T, U>(val: Option<U>) -> T
-where
-    JsValue: From<U>,
-    T: JsCast,
-{
-    let js_val = match val {
-        None => JsValue::UNDEFINED,
-        Some(val) => val.into(),
-    };
-    js_val.unchecked_into::<T>()
-}
+use synedrion::{
+    ecdsa::signature::hazmat::PrehashVerifier,
+    make_interactive_signing_session,
+    sessions::Bincode,
+    testnet::{make_signers, TestNetwork},
+    TestParams,
+};
+use synedrion_wasm::KeyShare;
 
 #[wasm_bindgen_test]
 fn test_make_key_shares() {
-    let sk: Option<SigningKey> = None;
-    let shares: Vec<KeyShare> = KeyShare::new_centralized(3, &into_js_option(sk)).unwrap();
+    let shares: Vec<KeyShare> = KeyShare::new_centralized(3, None).unwrap();
     let _shares_serialized = shares
         .iter()
         .map(|share| share.to_bytes())
         .collect::<Vec<_>>();
     // TODO (#84): some assertions needed here. Serialize synedrion::KeyShare and compare sizes?
 }
+
+#[wasm_bindgen_test]
+fn test_interactive_signing() {
+    let num_parties = 2;
+
+    let (signers, verifiers) = make_signers(num_parties, || SigningKey::random(&mut OsRng));
+
+    let key_shares =
+        synedrion::KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let outcomes = TestNetwork::new(sessions).run(&mut OsRng);
+    assert_eq!(outcomes.len(), num_parties);
+
+    let vkey = key_shares[0].verifying_key();
+    for outcome in outcomes.into_values() {
+        let (sig, _rec_id) = outcome.unwrap().to_backend();
+        vkey.verify_prehash(message, &sig).unwrap();
+    }
+}
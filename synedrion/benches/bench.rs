@@ -1,7 +1,10 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use rand_core::OsRng;
 
-use synedrion::{cggmp21::benches, KeyShare, PresigningData, TestParams};
+use synedrion::{
+    cggmp21::benches::{self, SigmaSetup},
+    KeyShare, PresigningData, TestParams,
+};
 
 fn bench_happy_paths(c: &mut Criterion) {
     let mut group = c.benchmark_group("happy path");
@@ -30,6 +33,104 @@ fn bench_happy_paths(c: &mut Criterion) {
     group.finish()
 }
 
-criterion_group!(benches, bench_happy_paths);
+fn bench_sigma_protocols(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sigma protocols");
+
+    type Params = TestParams;
+    let setup = SigmaSetup::<Params>::new(&mut OsRng);
+
+    group.bench_function("enc", |b| b.iter(|| benches::enc_proof(&mut OsRng, &setup)));
+    group.bench_function("log*", |b| {
+        b.iter(|| benches::log_star_proof(&mut OsRng, &setup))
+    });
+    group.bench_function("aff-g", |b| {
+        b.iter(|| benches::aff_g_proof(&mut OsRng, &setup))
+    });
+    group.bench_function("mul", |b| b.iter(|| benches::mul_proof(&mut OsRng, &setup)));
+
+    group.sample_size(10);
+    group.bench_function("fac", |b| b.iter(|| benches::fac_proof(&mut OsRng, &setup)));
+    group.bench_function("mod", |b| b.iter(|| benches::mod_proof(&mut OsRng, &setup)));
+    group.bench_function("prm", |b| b.iter(|| benches::prm_proof(&mut OsRng, &setup)));
+
+    group.finish()
+}
+
+fn bench_paillier(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Paillier");
+
+    type Params = TestParams;
+    let setup = SigmaSetup::<Params>::new(&mut OsRng);
+
+    group.bench_function("encryption", |b| {
+        b.iter(|| benches::paillier_encrypt(&mut OsRng, &setup))
+    });
+    group.bench_function("decryption", |b| {
+        b.iter(|| benches::paillier_decrypt(&setup))
+    });
+    group.bench_function("decryption without CRT", |b| {
+        b.iter(|| benches::paillier_decrypt_without_crt(&setup))
+    });
+
+    group.finish()
+}
+
+fn bench_round_finalization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("round finalization");
+
+    type Params = TestParams;
+    let key_shares = KeyShare::new_centralized(&mut OsRng, 2, None);
+    let presigning_datas = PresigningData::new_centralized(&mut OsRng, &key_shares);
+
+    group.sample_size(10);
+
+    for round in 1..=3 {
+        group.bench_function(format!("KeyGen, round {round}, 2 parties"), |b| {
+            b.iter_batched(
+                || benches::key_init_round::<Params>(&mut OsRng, 2, round),
+                |pending| pending.finalize(&mut OsRng),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    for round in 1..=3 {
+        group.bench_function(format!("KeyRefresh, round {round}, 2 parties"), |b| {
+            b.iter_batched(
+                || benches::key_refresh_round::<Params>(&mut OsRng, 2, round),
+                |pending| pending.finalize(&mut OsRng),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    for round in 1..=3 {
+        group.bench_function(format!("Presigning, round {round}, 2 parties"), |b| {
+            b.iter_batched(
+                || benches::presigning_round::<Params>(&mut OsRng, &key_shares, round),
+                |pending| pending.finalize(&mut OsRng),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    group.bench_function("Signing, round 1, 2 parties", |b| {
+        b.iter_batched(
+            || benches::signing_round::<Params>(&mut OsRng, &key_shares, &presigning_datas),
+            |pending| pending.finalize(&mut OsRng),
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.finish()
+}
+
+criterion_group!(
+    benches,
+    bench_happy_paths,
+    bench_sigma_protocols,
+    bench_paillier,
+    bench_round_finalization
+);
 
 criterion_main!(benches);
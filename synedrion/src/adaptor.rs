@@ -0,0 +1,239 @@
+//! ECDSA adaptor signatures, for the scriptless-script protocols (e.g. atomic swaps).
+//!
+//! In the Presigning protocol the nonce point is assembled as $R = \delta^{-1} \sum_j \Gamma_j$,
+//! where $\Gamma_j = \gamma_j G$ are the public masks of the parties and $\delta = k \gamma$.
+//! Given an adaptor point $T = t G$, each party additionally publishes $A_j = \gamma_j T$
+//! with a Chaum-Pedersen proof that $\log_G \Gamma_j = \log_T A_j$,
+//! and the signature is created for the nonce point $R' = \delta^{-1} \sum_j A_j = t R$.
+//! The resulting pre-signature $s'$ is $t$ times the actual $s$,
+//! so it only becomes a valid signature after it is divided by $t$;
+//! conversely, the holder of the pre-signature can extract $t$ once the signature is published.
+
+use alloc::boxed::Box;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::{Point, RecoverableSignature, Scalar, SigningKey, VerifyingKey};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, HashInto, XofHash};
+use crate::tools::serialized_size::MaxSerializedSize;
+use crate::PrehashedMessage;
+
+/// A Chaum-Pedersen proof that $\log_G \Gamma = \log_T A$.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DleqProof {
+    e: Scalar,
+    z: Scalar,
+}
+
+impl DleqProof {
+    fn challenge(
+        adaptor_point: &Point,
+        cap_gamma: &Point,
+        cap_a: &Point,
+        commitments: &(Point, Point),
+    ) -> Scalar {
+        let mut reader = XofHash::new_with_dst(b"AdaptorNonce")
+            .chain(adaptor_point)
+            .chain(cap_gamma)
+            .chain(cap_a)
+            .chain(&commitments.0)
+            .chain(&commitments.1)
+            .finalize_to_reader();
+        Scalar::from_reader(&mut reader)
+    }
+
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        gamma: &Scalar,
+        adaptor_point: &Point,
+        cap_gamma: &Point,
+        cap_a: &Point,
+    ) -> Self {
+        let w = Scalar::random_nonzero(rng);
+        let commitments = (w.mul_by_generator(), adaptor_point * &w);
+        let e = Self::challenge(adaptor_point, cap_gamma, cap_a, &commitments);
+        let z = w + e * gamma;
+        Self { e, z }
+    }
+
+    fn verify(&self, adaptor_point: &Point, cap_gamma: &Point, cap_a: &Point) -> bool {
+        let commitments = (
+            self.z.mul_by_generator() - cap_gamma * &self.e,
+            adaptor_point * &self.z - cap_a * &self.e,
+        );
+        Self::challenge(adaptor_point, cap_gamma, cap_a, &commitments) == self.e
+    }
+}
+
+/// A party's contribution to the adaptor nonce point: $\Gamma_j$, $A_j = \gamma_j T$,
+/// and the proof that they have the same discrete log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct AdaptorNonceShare {
+    cap_gamma: Point,
+    cap_a: Point,
+    proof: DleqProof,
+}
+
+impl AdaptorNonceShare {
+    pub(crate) fn new(rng: &mut impl CryptoRngCore, gamma: &Scalar, adaptor_point: &Point) -> Self {
+        let cap_gamma = gamma.mul_by_generator();
+        let cap_a = adaptor_point * gamma;
+        let proof = DleqProof::new(rng, gamma, adaptor_point, &cap_gamma, &cap_a);
+        Self {
+            cap_gamma,
+            cap_a,
+            proof,
+        }
+    }
+
+    /// Checks that the share was created for the mask `cap_gamma`.
+    pub(crate) fn verify(&self, adaptor_point: &Point, cap_gamma: &Point) -> bool {
+        &self.cap_gamma == cap_gamma && self.proof.verify(adaptor_point, cap_gamma, &self.cap_a)
+    }
+
+    pub(crate) fn cap_a(&self) -> &Point {
+        &self.cap_a
+    }
+}
+
+impl MaxSerializedSize for AdaptorNonceShare {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        2 * Point::max_serialized_size(num_parties) + 2 * Scalar::max_serialized_size(num_parties)
+    }
+}
+
+/// The data of the Presigning protocol executed with an adaptor point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct AdaptorPresigningData {
+    pub(crate) adaptor_point: Point,
+    /// The assembled $\delta$.
+    pub(crate) delta: Scalar,
+    /// The nonce shares of all the parties.
+    pub(crate) shares: Box<[AdaptorNonceShare]>,
+}
+
+/// An ECDSA adaptor signature (a pre-signature) for an adaptor point $T$.
+///
+/// Created by the session returned by
+/// [`make_adaptor_signing_session`](crate::make_adaptor_signing_session).
+/// It can be checked by anyone with [`verify`](Self::verify),
+/// and turned into a regular signature with [`complete`](Self::complete)
+/// by the holder of the discrete log of $T$.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptorSignature {
+    adaptor_point: Point,
+    delta: Scalar,
+    shares: Box<[AdaptorNonceShare]>,
+    s: Scalar,
+}
+
+impl AdaptorSignature {
+    pub(crate) fn new(data: &AdaptorPresigningData, s: Scalar) -> Self {
+        Self {
+            adaptor_point: data.adaptor_point,
+            delta: data.delta,
+            shares: data.shares.clone(),
+            s,
+        }
+    }
+
+    /// Returns the nonce points $(R, R')$, where $R' = t R$ is the nonce point of the signature.
+    fn nonce_points(&self) -> Option<(Point, Point)> {
+        let delta_inv = Option::<Scalar>::from(self.delta.invert())?;
+        let cap_r = self
+            .shares
+            .iter()
+            .map(|share| share.cap_gamma)
+            .sum::<Point>()
+            * delta_inv;
+        let cap_r_prime = self.shares.iter().map(|share| share.cap_a).sum::<Point>() * delta_inv;
+        Some((cap_r, cap_r_prime))
+    }
+
+    /// Returns the adaptor point the signature was created for.
+    pub fn adaptor_point(&self) -> VerifyingKey {
+        self.adaptor_point
+            .to_verifying_key()
+            .expect("the adaptor point is not the identity")
+    }
+
+    /// Checks that the pre-signature of the prehashed message `prehash`
+    /// will become a valid signature for `verifying_key` when completed with the discrete log
+    /// of the adaptor point.
+    pub fn verify(&self, verifying_key: &VerifyingKey, prehash: &PrehashedMessage) -> bool {
+        if self.adaptor_point == Point::IDENTITY || self.shares.is_empty() {
+            return false;
+        }
+
+        if !self.shares.iter().all(|share| {
+            share
+                .proof
+                .verify(&self.adaptor_point, &share.cap_gamma, &share.cap_a)
+        }) {
+            return false;
+        }
+
+        let Some((cap_r, cap_r_prime)) = self.nonce_points() else {
+            return false;
+        };
+        let Some(s_inv) = Option::<Scalar>::from(self.s.invert()) else {
+            return false;
+        };
+
+        let r = cap_r_prime.x_coordinate();
+        if r == Scalar::ZERO {
+            return false;
+        }
+
+        // $s' = k (m + r x)$ where $R = k^{-1} G$.
+        let message = Scalar::from_reduced_bytes(prehash);
+        let verifying_key = Point::from_verifying_key(verifying_key);
+        (message.mul_by_generator() + verifying_key * r) * s_inv == cap_r
+    }
+
+    /// Completes the pre-signature with the discrete log of the adaptor point,
+    /// returning a signature of the prehashed message `prehash` for `verifying_key`.
+    ///
+    /// Returns `None` if `adaptor_secret` does not match the adaptor point,
+    /// or if the resulting signature is not valid.
+    pub fn complete(
+        &self,
+        verifying_key: &VerifyingKey,
+        prehash: &PrehashedMessage,
+        adaptor_secret: &SigningKey,
+    ) -> Option<RecoverableSignature> {
+        let t = Scalar::from(adaptor_secret.as_nonzero_scalar());
+        if t.mul_by_generator() != self.adaptor_point {
+            return None;
+        }
+        let (_cap_r, cap_r_prime) = self.nonce_points()?;
+        let t_inv = Option::<Scalar>::from(t.invert())?;
+        RecoverableSignature::from_scalars(
+            &cap_r_prime.x_coordinate(),
+            &(self.s * t_inv),
+            &Point::from_verifying_key(verifying_key),
+            &Scalar::from_reduced_bytes(prehash),
+        )
+    }
+
+    /// Extracts the discrete log of the adaptor point from the signature
+    /// created by completing this pre-signature.
+    ///
+    /// Returns `None` if the signature was not created from this pre-signature.
+    pub fn recover_adaptor_secret(&self, signature: &RecoverableSignature) -> Option<SigningKey> {
+        let (_cap_r, cap_r_prime) = self.nonce_points()?;
+        let (signature, _recovery_id) = signature.to_backend();
+        if Scalar::from(&signature.r()) != cap_r_prime.x_coordinate() {
+            return None;
+        }
+
+        // The completed `s` may have been negated during normalization.
+        let s_inv = Option::<Scalar>::from(Scalar::from(&signature.s()).invert())?;
+        let t = self.s * s_inv;
+        [t, -t]
+            .into_iter()
+            .find(|t| t.mul_by_generator() == self.adaptor_point)
+            .and_then(|t| SigningKey::from_bytes(&t.to_bytes()).ok())
+    }
+}
@@ -0,0 +1,180 @@
+//! Audit records of the nonce contributions in Presigning (behind the `testing` feature).
+//!
+//! The nonce point is $R = \delta^{-1} \Gamma$, where $\Gamma = \sum_j \gamma_j G$
+//! and $\delta = k \gamma$, so $R = k^{-1} G$ for $k = \sum_j k_j$.
+//! The values $\Gamma_j = \gamma_j G$ and $\Delta_j = k_j \Gamma$ are sent with the proofs
+//! binding them to the Paillier encryptions of $k_j$ and $\gamma_j$ broadcasted in Round 1,
+//! that is, before any party learns the contributions of the others.
+//!
+//! In the audit mode, each party encrypts its $k_i$ and $\gamma_i$ to the auditor key
+//! and records them along with $\Gamma_j$, $\Delta_j$ and $R$ as it received them.
+//! Given the records of all the parties, the auditor checks that they agree on the public values,
+//! that the decrypted contributions match them, and that $R$ is made from them,
+//! which shows that no party could choose its contributions depending on the others'.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::{Point, Scalar, SigningKey};
+use crate::rounds::PartyIdx;
+use crate::tools::ecies;
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+
+const ENCRYPTION_DST: &[u8] = b"NonceAuditRecord";
+
+/// Possible errors when auditing the records with [`NonceAuditRecord::audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum NonceAuditError {
+    /// The number of records does not match the number of parties.
+    WrongNumberOfRecords,
+    /// There is no record of the party {0}.
+    MissingRecord(usize),
+    /// The record of the party {0} belongs to another session, or disagrees with the others on the public values.
+    InconsistentRecord(usize),
+    /// The contributions of the party {0} cannot be decrypted with the auditor key.
+    CannotDecrypt(usize),
+    /// The contributions of the party {0} do not match the public values it sent.
+    ContributionMismatch(usize),
+    /// The nonce point is not the inverse of the sum of the nonce contributions.
+    NonceMismatch,
+}
+
+impl core::error::Error for NonceAuditError {}
+
+/// The contributions of a party to the nonce, encrypted to the auditor key
+/// when the first round is created.
+pub(crate) fn encrypt_contributions(
+    rng: &mut impl CryptoRngCore,
+    auditor_key: &Point,
+    ssid_hash: &HashOutput,
+    party_idx: PartyIdx,
+    k: &Scalar,
+    gamma: &Scalar,
+) -> Box<[u8]> {
+    let mut plaintext = Vec::with_capacity(64);
+    plaintext.extend(k.to_bytes());
+    plaintext.extend(gamma.to_bytes());
+    let associated_data = associated_data(ssid_hash, party_idx);
+    ecies::encrypt(
+        rng,
+        ENCRYPTION_DST,
+        auditor_key,
+        &associated_data,
+        &plaintext,
+    )
+}
+
+fn associated_data(ssid_hash: &HashOutput, party_idx: PartyIdx) -> HashOutput {
+    Hash::new_with_dst(ENCRYPTION_DST)
+        .chain(ssid_hash)
+        .chain(&party_idx)
+        .finalize()
+}
+
+/// A record of the nonce contributions of a party in a Presigning session,
+/// returned by [`PresigningData::audit_record`](crate::PresigningData::audit_record)
+/// if the session was created with
+/// [`make_presigning_session_with_audit`](crate::make_presigning_session_with_audit).
+///
+/// The secret contributions are encrypted to the auditor key,
+/// so the records can be collected over untrusted channels.
+/// Note though that the auditor learns the nonce of the signature made with this presigning data,
+/// and can therefore extract the signing key from the signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonceAuditRecord {
+    pub(crate) party_idx: PartyIdx,
+    pub(crate) ssid_hash: HashOutput,
+    /// $\Gamma_j$ of all the parties.
+    pub(crate) cap_gammas: Box<[Point]>,
+    /// $\Delta_j$ of all the parties.
+    pub(crate) cap_deltas: Box<[Point]>,
+    /// $R$.
+    pub(crate) nonce_point: Point,
+    pub(crate) encrypted_contributions: Box<[u8]>,
+}
+
+impl NonceAuditRecord {
+    /// The index of the party that created this record.
+    pub fn party_index(&self) -> usize {
+        self.party_idx.as_usize()
+    }
+
+    fn decrypt_contributions(&self, auditor_key: &Scalar) -> Option<(Scalar, Scalar)> {
+        let associated_data = associated_data(&self.ssid_hash, self.party_idx);
+        let plaintext = ecies::decrypt(
+            ENCRYPTION_DST,
+            auditor_key,
+            &associated_data,
+            &self.encrypted_contributions,
+        )
+        .ok()?;
+        if plaintext.len() != 64 {
+            return None;
+        }
+        let k = Scalar::try_from_bytes(&plaintext[..32]).ok()?;
+        let gamma = Scalar::try_from_bytes(&plaintext[32..]).ok()?;
+        Some((k, gamma))
+    }
+
+    /// Checks the records of all the parties of a Presigning session
+    /// with the secret key of the auditor.
+    ///
+    /// Returns an error naming the first party whose record is inconsistent
+    /// with the others or with its own contributions.
+    pub fn audit(records: &[Self], auditor_key: &SigningKey) -> Result<(), NonceAuditError> {
+        let Some(first) = records.first() else {
+            return Err(NonceAuditError::WrongNumberOfRecords);
+        };
+        let num_parties = first.cap_gammas.len();
+        if records.len() != num_parties {
+            return Err(NonceAuditError::WrongNumberOfRecords);
+        }
+
+        let mut ordered = Vec::with_capacity(num_parties);
+        for idx in 0..num_parties {
+            let record = records
+                .iter()
+                .find(|record| record.party_index() == idx)
+                .ok_or(NonceAuditError::MissingRecord(idx))?;
+            ordered.push(record);
+        }
+
+        let first = ordered[0];
+        for record in ordered.iter() {
+            if record.ssid_hash != first.ssid_hash
+                || record.cap_gammas != first.cap_gammas
+                || record.cap_deltas != first.cap_deltas
+                || record.nonce_point != first.nonce_point
+                || record.cap_deltas.len() != num_parties
+            {
+                return Err(NonceAuditError::InconsistentRecord(record.party_index()));
+            }
+        }
+
+        let auditor_key = Scalar::from(auditor_key.as_nonzero_scalar());
+        let cap_gamma = first.cap_gammas.iter().sum::<Point>();
+        let mut k = Scalar::ZERO;
+        for (idx, record) in ordered.iter().enumerate() {
+            let (k_i, gamma_i) = record
+                .decrypt_contributions(&auditor_key)
+                .ok_or(NonceAuditError::CannotDecrypt(idx))?;
+            if gamma_i.mul_by_generator() != first.cap_gammas[idx]
+                || cap_gamma * k_i != first.cap_deltas[idx]
+            {
+                return Err(NonceAuditError::ContributionMismatch(idx));
+            }
+            k = k + k_i;
+        }
+
+        let k_inv = Option::<Scalar>::from(k.invert()).ok_or(NonceAuditError::NonceMismatch)?;
+        if k_inv.mul_by_generator() != first.nonce_point {
+            return Err(NonceAuditError::NonceMismatch);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Shared randomness taken from a public randomness beacon (e.g. drand).
+//!
+//! Normally the shared randomness of a session is chosen by whoever orchestrates it,
+//! and the parties cannot tell whether it was used before.
+//! If it is instead the signed output of a beacon for some round,
+//! each party can check the signature and that the round is recent according to its own clock,
+//! so the orchestrator cannot make the parties start a session with stale randomness.
+
+use alloc::boxed::Box;
+use core::ops::RangeInclusive;
+
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::tools::{
+    hashing::{Chain, Hash},
+    serde_bytes,
+};
+
+/// Verifies the signatures of a randomness beacon.
+///
+/// For drand, this would check the BLS signature of the round with the public key of the chain.
+/// Implemented for the closures taking the round and the signature.
+pub trait BeaconVerifier {
+    /// Returns `true` if `signature` is the valid output of the beacon for `round`.
+    fn verify_round(&self, round: u64, signature: &[u8]) -> bool;
+}
+
+impl<F> BeaconVerifier for F
+where
+    F: Fn(u64, &[u8]) -> bool,
+{
+    fn verify_round(&self, round: u64, signature: &[u8]) -> bool {
+        self(round, signature)
+    }
+}
+
+/// Possible errors when checking a [`BeaconRandomness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum BeaconError {
+    /// The beacon round {round} is outside of the accepted window.
+    RoundOutsideWindow {
+        /// The round of the beacon output.
+        round: u64,
+    },
+    /// The beacon signature is invalid.
+    InvalidSignature,
+}
+
+impl core::error::Error for BeaconError {}
+
+/// The output of a randomness beacon for some round,
+/// to be used as the shared randomness of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconRandomness {
+    round: u64,
+    #[serde(with = "serde_bytes::as_base64")]
+    signature: Box<[u8]>,
+}
+
+impl BeaconRandomness {
+    /// Creates the beacon output from the round number and the beacon signature for that round.
+    pub fn new(round: u64, signature: &[u8]) -> Self {
+        Self {
+            round,
+            signature: signature.into(),
+        }
+    }
+
+    /// Returns the round of the beacon output.
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// Checks that the round is within `accepted_rounds` and the signature is valid,
+    /// and returns the shared randomness to be passed to the session constructors.
+    ///
+    /// `accepted_rounds` should be derived from the local clock of the party
+    /// (the round the beacon is expected to be at, minus the tolerated age of the output
+    /// and plus the tolerated clock skew), and not taken from the orchestrator.
+    pub fn shared_randomness(
+        &self,
+        verifier: &impl BeaconVerifier,
+        accepted_rounds: RangeInclusive<u64>,
+    ) -> Result<[u8; 32], BeaconError> {
+        if !accepted_rounds.contains(&self.round) {
+            return Err(BeaconError::RoundOutsideWindow { round: self.round });
+        }
+        if !verifier.verify_round(self.round, &self.signature) {
+            return Err(BeaconError::InvalidSignature);
+        }
+
+        let hash = Hash::new_with_dst(b"BeaconRandomness")
+            .chain(&self.round)
+            .chain(&self.signature)
+            .finalize();
+        let mut randomness = [0u8; 32];
+        randomness.copy_from_slice(hash.as_ref());
+        Ok(randomness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use signature::{Signer, Verifier};
+
+    use super::{BeaconError, BeaconRandomness};
+    use crate::curve::{BackendSignature as Signature, SigningKey};
+
+    #[test]
+    fn shared_randomness() {
+        // An ECDSA key stands in for the beacon's key.
+        let beacon_key = SigningKey::random(&mut OsRng);
+        let verifying_key = *beacon_key.verifying_key();
+        let verifier = |round: u64, signature: &[u8]| {
+            Signature::from_slice(signature).is_ok_and(|signature| {
+                verifying_key
+                    .verify(&round.to_be_bytes(), &signature)
+                    .is_ok()
+            })
+        };
+        let sign = |round: u64| {
+            let signature: Signature = beacon_key.sign(&round.to_be_bytes());
+            BeaconRandomness::new(round, &signature.to_bytes())
+        };
+
+        let beacon = sign(100);
+        let randomness = beacon.shared_randomness(&verifier, 98..=101).unwrap();
+        assert_eq!(
+            beacon.shared_randomness(&verifier, 100..=100).unwrap(),
+            randomness
+        );
+        assert_ne!(
+            sign(101).shared_randomness(&verifier, 98..=101).unwrap(),
+            randomness
+        );
+
+        // A stale output, or one from the future.
+        assert_eq!(
+            beacon.shared_randomness(&verifier, 101..=103),
+            Err(BeaconError::RoundOutsideWindow { round: 100 })
+        );
+        assert_eq!(
+            beacon.shared_randomness(&verifier, 90..=99),
+            Err(BeaconError::RoundOutsideWindow { round: 100 })
+        );
+
+        // The signature of another round.
+        let forged = BeaconRandomness::new(101, &beacon.signature);
+        assert_eq!(
+            forged.shared_randomness(&verifier, 98..=101),
+            Err(BeaconError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Non-hardened BIP32 (<https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki>)
+//! derivation of child keys.
+//!
+//! Since the parent public key is known to all the parties, a non-hardened child key
+//! can be obtained from the shared key by adding the same public tweak to it,
+//! without any interaction.
+
+use displaydoc::Display;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+use crate::curve::{Point, Scalar};
+
+/// The first index of a hardened child key.
+pub(crate) const HARDENED_BIT: u32 = 1 << 31;
+
+/// A BIP32 chain code accompanying a (shared) public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainCode(pub [u8; 32]);
+
+/// Possible errors when deriving a child key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum DeriveChildError {
+    /// Hardened derivation requires the full secret key and cannot be performed on a key share.
+    HardenedIndex,
+    /// The derived tweak or child key is invalid; the next child index should be used instead.
+    InvalidChildKey,
+}
+
+impl core::error::Error for DeriveChildError {}
+
+/// Returns the additive tweak for the child key and the child chain code
+/// (that is, the `CKDpub` function from BIP32).
+pub(crate) fn derive_tweak(
+    parent_key: &Point,
+    chain_code: &ChainCode,
+    child_number: u32,
+) -> Result<(Scalar, ChainCode), DeriveChildError> {
+    if child_number >= HARDENED_BIT {
+        return Err(DeriveChildError::HardenedIndex);
+    }
+
+    // `new_from_slice()` only fails for fixed-size key MACs, and HMAC can take a key of any size.
+    let mut mac = Hmac::<Sha512>::new_from_slice(&chain_code.0).unwrap();
+    mac.update(&parent_key.to_compressed_array());
+    mac.update(&child_number.to_be_bytes());
+    let result = mac.finalize().into_bytes();
+    let (tweak_bytes, child_chain_code) = result.split_at(32);
+
+    // BIP32 requires the tweak to be strictly less than the curve order, without reduction.
+    let tweak =
+        Scalar::try_from_bytes(tweak_bytes).map_err(|_| DeriveChildError::InvalidChildKey)?;
+    if *parent_key + tweak.mul_by_generator() == Point::IDENTITY {
+        return Err(DeriveChildError::InvalidChildKey);
+    }
+
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(child_chain_code);
+
+    Ok((tweak, ChainCode(chain_code)))
+}
+
+// The test vectors are for secp256k1.
+#[cfg(all(test, not(feature = "p256")))]
+mod tests {
+    use super::{derive_tweak, ChainCode, DeriveChildError, HARDENED_BIT};
+    use crate::curve::{Scalar, SigningKey};
+
+    fn from_hex<const N: usize>(s: &str) -> [u8; N] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn bip32_test_vector() {
+        // BIP32 test vector 1, derivation of `m/0H/1` from `m/0H`.
+        let parent_sk = SigningKey::from_slice(&from_hex::<32>(
+            "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea",
+        ))
+        .unwrap();
+        let parent_chain_code = ChainCode(from_hex(
+            "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141",
+        ));
+
+        let child_sk = SigningKey::from_slice(&from_hex::<32>(
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+        ))
+        .unwrap();
+        let child_chain_code = ChainCode(from_hex(
+            "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19",
+        ));
+
+        let parent_secret = Scalar::from(parent_sk.as_nonzero_scalar());
+        let (tweak, chain_code) =
+            derive_tweak(&parent_secret.mul_by_generator(), &parent_chain_code, 1).unwrap();
+
+        assert_eq!(
+            parent_secret + tweak,
+            Scalar::from(child_sk.as_nonzero_scalar())
+        );
+        assert_eq!(chain_code, child_chain_code);
+
+        assert_eq!(
+            derive_tweak(
+                &parent_secret.mul_by_generator(),
+                &parent_chain_code,
+                HARDENED_BIT
+            ),
+            Err(DeriveChildError::HardenedIndex)
+        );
+    }
+}
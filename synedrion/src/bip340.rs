@@ -0,0 +1,135 @@
+//! The hashes of BIP340 (<https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki>)
+//! Schnorr signatures, and the key tweak of BIP341
+//! (<https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki>) used for Taproot outputs.
+
+use sha2::{Digest, Sha256};
+
+use crate::curve::{Point, Scalar};
+
+/// The tweak applied to the x-only shared key to obtain the Taproot output key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaprootTweak {
+    /// The output can only be spent with the key path (as recommended by BIP86).
+    KeyPathOnly,
+    /// The output commits to a script tree with this Merkle root.
+    ScriptTree([u8; 32]),
+}
+
+fn tagged_hash(tag: &[u8]) -> Sha256 {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new().chain_update(tag_hash).chain_update(tag_hash)
+}
+
+/// A BIP340 Schnorr signature along with the x-only key it can be verified with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrSignature {
+    signature: [u8; 64],
+    verifying_key: [u8; 32],
+}
+
+impl SchnorrSignature {
+    /// Creates the signature `(R, s)` and checks that it is valid for the message
+    /// and the key `verifying_key`.
+    pub(crate) fn from_parts(
+        cap_r: &Point,
+        s: &Scalar,
+        verifying_key: &Point,
+        message: &[u8; 32],
+    ) -> Option<Self> {
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&cap_r.x_only_bytes());
+        signature[32..].copy_from_slice(&s.to_bytes());
+        let signature = Self {
+            signature,
+            verifying_key: verifying_key.x_only_bytes(),
+        };
+        signature.verify(message).then_some(signature)
+    }
+
+    /// Serializes the signature as the 64-byte array `R.x || s`, as defined in BIP340.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.signature
+    }
+
+    /// Returns the 32-byte x-only key the signature can be verified with
+    /// (that is, the tweaked output key if a Taproot tweak was applied).
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key
+    }
+
+    /// Verifies the signature of a 32-byte message, as defined in BIP340.
+    pub fn verify(&self, message: &[u8; 32]) -> bool {
+        let mut compressed_key = [2u8; 33];
+        compressed_key[1..].copy_from_slice(&self.verifying_key);
+        let verifying_key = match Point::try_from_compressed_bytes(&compressed_key) {
+            Ok(point) => point,
+            Err(_) => return false,
+        };
+
+        let r: [u8; 32] = self.signature[..32].try_into().unwrap();
+        let s = match Scalar::try_from_bytes(&self.signature[32..]) {
+            Ok(scalar) => scalar,
+            Err(_) => return false,
+        };
+
+        // `r` is the x-coordinate of the nonce point with an even y-coordinate.
+        let mut compressed_r = [2u8; 33];
+        compressed_r[1..].copy_from_slice(&r);
+        let cap_r = match Point::try_from_compressed_bytes(&compressed_r) {
+            Ok(point) => point,
+            Err(_) => return false,
+        };
+
+        let e = challenge(&cap_r, &verifying_key, message);
+        s.mul_by_generator() == cap_r + verifying_key * e
+    }
+}
+
+/// Returns the challenge `e` for the nonce commitment `cap_r` and the x-only key `verifying_key`.
+pub(crate) fn challenge(cap_r: &Point, verifying_key: &Point, message: &[u8; 32]) -> Scalar {
+    Scalar::from_digest(
+        tagged_hash(b"BIP0340/challenge")
+            .chain_update(cap_r.x_only_bytes())
+            .chain_update(verifying_key.x_only_bytes())
+            .chain_update(message),
+    )
+}
+
+/// Returns the scalar `t` such that the output key is `P + t * G`,
+/// where `P` is the x-only internal key.
+///
+/// Returns `None` if the hash exceeds the curve order, in which case the tweak is invalid.
+pub(crate) fn taproot_tweak(internal_key: &Point, tweak: &TaprootTweak) -> Option<Scalar> {
+    let digest = tagged_hash(b"TapTweak").chain_update(internal_key.x_only_bytes());
+    let digest = match tweak {
+        TaprootTweak::KeyPathOnly => digest,
+        TaprootTweak::ScriptTree(merkle_root) => digest.chain_update(merkle_root),
+    };
+    Scalar::try_from_bytes(&digest.finalize()).ok()
+}
+
+// BIP340 is only defined for secp256k1.
+#[cfg(all(test, not(feature = "p256")))]
+mod tests {
+    use super::SchnorrSignature;
+
+    #[test]
+    fn verify_test_vector() {
+        // Test vector 0 from BIP340.
+        let verifying_key =
+            hex::decode("F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9")
+                .unwrap();
+        let signature = hex::decode(concat![
+            "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215",
+            "25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0"
+        ])
+        .unwrap();
+        let signature = SchnorrSignature {
+            signature: signature.try_into().unwrap(),
+            verifying_key: verifying_key.try_into().unwrap(),
+        };
+
+        assert!(signature.verify(&[0u8; 32]));
+        assert!(!signature.verify(&[1u8; 32]));
+    }
+}
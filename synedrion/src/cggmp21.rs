@@ -14,10 +14,35 @@ mod sigma;
 #[cfg(feature = "bench-internals")]
 pub mod benches;
 
-pub use params::{ProductionParams, SchemeParams, TestParams};
-pub(crate) use protocols::{interactive_signing, key_gen, key_refresh};
+pub use params::{
+    validate_scheme_params, Params1536, Params4096, ProductionParams, SchemeParams, TestParams,
+    PRESET_SECURITY_LEVELS,
+};
+pub(crate) use protocols::{key_export, threshold_refresh};
+pub use protocols::{
+    ExportedKey, KeyDecryptionError, KeyExportError, KeyExportResult, ThresholdRefreshError,
+    ThresholdRefreshResult,
+};
+
+#[cfg(feature = "keygen")]
+pub(crate) use protocols::{key_gen, key_import};
+#[cfg(feature = "keygen")]
+pub use protocols::{
+    KeyGenError, KeyGenProof, KeyGenResult, KeyImportError, KeyImportResult, KeyInitError,
+    KeyInitResult,
+};
+
+#[cfg(feature = "key-refresh")]
+pub(crate) use protocols::key_refresh;
+#[cfg(feature = "key-refresh")]
+pub use protocols::KeyRefreshResult;
+
+#[cfg(feature = "signing")]
+pub(crate) use protocols::{interactive_signing, presigning, schnorr_signing, signing};
+#[cfg(feature = "signing")]
 pub use protocols::{
-    InteractiveSigningError, InteractiveSigningProof, InteractiveSigningResult, KeyGenError,
-    KeyGenProof, KeyGenResult, KeyInitError, KeyInitResult, KeyRefreshResult, PresigningError,
-    PresigningProof, PresigningResult, SigningProof, SigningResult,
+    AdaptorSigningResult, InteractiveSigningError, InteractiveSigningProof,
+    InteractiveSigningResult, PresigningError, PresigningMode, PresigningPrecompute,
+    PresigningProof, PresigningResult, SchnorrSigningError, SchnorrSigningResult, SigningError,
+    SigningProof, SigningResult,
 };
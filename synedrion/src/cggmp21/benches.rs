@@ -1,25 +1,69 @@
-//! Functions containing sequential executions of CGGMP21 protocols,
-//! intended for benchmarking.
+//! Functions containing sequential executions of CGGMP21 protocols
+//! and of their building blocks, intended for benchmarking.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 use rand_core::CryptoRngCore;
 
 use super::{
     protocols::{key_init, key_refresh, presigning, signing},
+    sigma::{AffGProof, EncProof, FacProof, LogStarProof, ModProof, MulProof, PrmProof},
     SchemeParams,
 };
 use crate::common::{KeyShare, PresigningData};
-use crate::curve::Scalar;
+use crate::curve::{Point, Scalar};
+use crate::paillier::{
+    CiphertextMod, PaillierParams, RPParamsMod, RPSecret, RandomizerMod, SecretKeyPaillier,
+    SecretKeyPaillierPrecomputed,
+};
 use crate::rounds::{
-    test_utils::{step_next_round, step_result, step_round},
-    FirstRound, PartyIdx,
+    test_utils::{step_next_round, step_result, step_round, AssembledRound},
+    FinalizableToNextRound, FinalizableToResult, FirstRound, PartyIdx,
 };
+use crate::uint::Signed;
 
-/// A sequential execution of the KeyGen protocol for all parties.
-pub fn key_init<P: SchemeParams>(rng: &mut impl CryptoRngCore, num_parties: usize) {
+/// A protocol execution for all parties, stopped after all the messages of one of the rounds
+/// were delivered, so that only the finalization of this round can be measured.
+pub struct PendingFinalization(FinalizeFn);
+
+type FinalizeFn = Box<dyn FnOnce(&mut dyn CryptoRngCore)>;
+
+impl PendingFinalization {
+    fn to_next_round<R: FinalizableToNextRound + 'static>(
+        assembled_rounds: Vec<AssembledRound<R>>,
+    ) -> Self {
+        Self(Box::new(|mut rng: &mut dyn CryptoRngCore| {
+            let _next_rounds = step_next_round(&mut rng, assembled_rounds).unwrap();
+        }))
+    }
+
+    fn to_result<R: FinalizableToResult + 'static>(
+        assembled_rounds: Vec<AssembledRound<R>>,
+    ) -> Self {
+        Self(Box::new(|mut rng: &mut dyn CryptoRngCore| {
+            let _results = step_result(&mut rng, assembled_rounds).unwrap();
+        }))
+    }
+
+    /// Finalizes the round for all parties.
+    pub fn finalize(self, rng: &mut impl CryptoRngCore) {
+        (self.0)(rng)
+    }
+}
+
+fn make_shared_randomness(rng: &mut impl CryptoRngCore) -> [u8; 32] {
     let mut shared_randomness = [0u8; 32];
     rng.fill_bytes(&mut shared_randomness);
+    shared_randomness
+}
 
-    let r1 = (0..num_parties)
+fn key_init_round1<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    num_parties: usize,
+) -> Vec<key_init::Round1<P>> {
+    let shared_randomness = make_shared_randomness(rng);
+    (0..num_parties)
         .map(|idx| {
             key_init::Round1::<P>::new(
                 rng,
@@ -30,82 +74,57 @@ pub fn key_init<P: SchemeParams>(rng: &mut impl CryptoRngCore, num_parties: usiz
             )
             .unwrap()
         })
-        .collect();
-
-    let r1a = step_round(rng, r1).unwrap();
-    let r2 = step_next_round(rng, r1a).unwrap();
-    let r2a = step_round(rng, r2).unwrap();
-    let r3 = step_next_round(rng, r2a).unwrap();
-    let r3a = step_round(rng, r3).unwrap();
-    let _shares = step_result(rng, r3a).unwrap();
+        .collect()
 }
 
-/// A sequential execution of the KeyRefresh/Auxiliary protocol for all parties.
-pub fn key_refresh<P: SchemeParams>(rng: &mut impl CryptoRngCore, num_parties: usize) {
-    let mut shared_randomness = [0u8; 32];
-    rng.fill_bytes(&mut shared_randomness);
-
-    let r1 = (0..num_parties)
+fn key_refresh_round1<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    num_parties: usize,
+) -> Vec<key_refresh::Round1<P>> {
+    let shared_randomness = make_shared_randomness(rng);
+    (0..num_parties)
         .map(|idx| {
             key_refresh::Round1::<P>::new(
                 rng,
                 &shared_randomness,
                 num_parties,
                 PartyIdx::from_usize(idx),
-                (),
+                key_refresh::Inputs::default(),
             )
             .unwrap()
         })
-        .collect();
-
-    let r1a = step_round(rng, r1).unwrap();
-    let r2 = step_next_round(rng, r1a).unwrap();
-    let r2a = step_round(rng, r2).unwrap();
-    let r3 = step_next_round(rng, r2a).unwrap();
-    let r3a = step_round(rng, r3).unwrap();
-    let _changes = step_result(rng, r3a).unwrap();
+        .collect()
 }
 
-/// A sequential execution of the Presigning protocol for all parties.
-pub fn presigning<P: SchemeParams>(rng: &mut impl CryptoRngCore, key_shares: &[KeyShare<P>]) {
-    let mut shared_randomness = [0u8; 32];
-    rng.fill_bytes(&mut shared_randomness);
-
+fn presigning_round1<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    key_shares: &[KeyShare<P>],
+) -> Vec<presigning::Round1<P>> {
+    let shared_randomness = make_shared_randomness(rng);
     let num_parties = key_shares.len();
-    let r1 = (0..num_parties)
+    (0..num_parties)
         .map(|idx| {
             presigning::Round1::<P>::new(
                 rng,
                 &shared_randomness,
                 num_parties,
                 PartyIdx::from_usize(idx),
-                key_shares[idx].clone(),
+                presigning::Inputs::new(key_shares[idx].clone()),
             )
             .unwrap()
         })
-        .collect();
-
-    let r1a = step_round(rng, r1).unwrap();
-    let r2 = step_next_round(rng, r1a).unwrap();
-    let r2a = step_round(rng, r2).unwrap();
-    let r3 = step_next_round(rng, r2a).unwrap();
-    let r3a = step_round(rng, r3).unwrap();
-    let _presigning_datas = step_result(rng, r3a).unwrap();
+        .collect()
 }
 
-/// A sequential execution of the Presigning protocol for all parties.
-pub fn signing<P: SchemeParams>(
+fn signing_round1<P: SchemeParams>(
     rng: &mut impl CryptoRngCore,
     key_shares: &[KeyShare<P>],
     presigning_datas: &[PresigningData<P>],
-) {
-    let mut shared_randomness = [0u8; 32];
-    rng.fill_bytes(&mut shared_randomness);
-
+) -> Vec<signing::Round1<P>> {
+    let shared_randomness = make_shared_randomness(rng);
     let message = Scalar::random(rng);
-
     let num_parties = presigning_datas.len();
-    let r1 = (0..num_parties)
+    (0..num_parties)
         .map(|idx| {
             signing::Round1::new(
                 rng,
@@ -114,14 +133,346 @@ pub fn signing<P: SchemeParams>(
                 PartyIdx::from_usize(idx),
                 signing::Inputs {
                     message,
-                    presigning: presigning_datas[idx].clone(),
+                    presigning: presigning_datas[idx].duplicate(),
                     key_share: key_shares[idx].to_precomputed(),
                 },
             )
             .unwrap()
         })
-        .collect();
+        .collect()
+}
+
+/// A sequential execution of the KeyGen protocol for all parties.
+pub fn key_init<P: SchemeParams>(rng: &mut impl CryptoRngCore, num_parties: usize) {
+    let r1 = key_init_round1::<P>(rng, num_parties);
+    let r1a = step_round(rng, r1).unwrap();
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    let _shares = step_result(rng, r3a).unwrap();
+}
 
+/// A sequential execution of the KeyGen protocol for all parties
+/// up to the finalization of the given round (1 to 3).
+pub fn key_init_round<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    num_parties: usize,
+    round: u8,
+) -> PendingFinalization {
+    let r1 = key_init_round1::<P>(rng, num_parties);
+    let r1a = step_round(rng, r1).unwrap();
+    if round == 1 {
+        return PendingFinalization::to_next_round(r1a);
+    }
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    if round == 2 {
+        return PendingFinalization::to_next_round(r2a);
+    }
+    assert_eq!(round, 3, "KeyGen has 3 rounds");
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    PendingFinalization::to_result(r3a)
+}
+
+/// A sequential execution of the KeyRefresh/Auxiliary protocol for all parties.
+pub fn key_refresh<P: SchemeParams>(rng: &mut impl CryptoRngCore, num_parties: usize) {
+    let r1 = key_refresh_round1::<P>(rng, num_parties);
+    let r1a = step_round(rng, r1).unwrap();
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    let _changes = step_result(rng, r3a).unwrap();
+}
+
+/// A sequential execution of the KeyRefresh/Auxiliary protocol for all parties
+/// up to the finalization of the given round (1 to 3).
+pub fn key_refresh_round<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    num_parties: usize,
+    round: u8,
+) -> PendingFinalization {
+    let r1 = key_refresh_round1::<P>(rng, num_parties);
+    let r1a = step_round(rng, r1).unwrap();
+    if round == 1 {
+        return PendingFinalization::to_next_round(r1a);
+    }
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    if round == 2 {
+        return PendingFinalization::to_next_round(r2a);
+    }
+    assert_eq!(round, 3, "KeyRefresh has 3 rounds");
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    PendingFinalization::to_result(r3a)
+}
+
+/// A sequential execution of the Presigning protocol for all parties.
+pub fn presigning<P: SchemeParams>(rng: &mut impl CryptoRngCore, key_shares: &[KeyShare<P>]) {
+    let r1 = presigning_round1(rng, key_shares);
+    let r1a = step_round(rng, r1).unwrap();
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    let _presigning_datas = step_result(rng, r3a).unwrap();
+}
+
+/// A sequential execution of the Presigning protocol for all parties
+/// up to the finalization of the given round (1 to 3).
+pub fn presigning_round<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    key_shares: &[KeyShare<P>],
+    round: u8,
+) -> PendingFinalization {
+    let r1 = presigning_round1(rng, key_shares);
+    let r1a = step_round(rng, r1).unwrap();
+    if round == 1 {
+        return PendingFinalization::to_next_round(r1a);
+    }
+    let r2 = step_next_round(rng, r1a).unwrap();
+    let r2a = step_round(rng, r2).unwrap();
+    if round == 2 {
+        return PendingFinalization::to_next_round(r2a);
+    }
+    assert_eq!(round, 3, "Presigning has 3 rounds");
+    let r3 = step_next_round(rng, r2a).unwrap();
+    let r3a = step_round(rng, r3).unwrap();
+    PendingFinalization::to_result(r3a)
+}
+
+/// A sequential execution of the Signing protocol for all parties.
+pub fn signing<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    key_shares: &[KeyShare<P>],
+    presigning_datas: &[PresigningData<P>],
+) {
+    let r1 = signing_round1(rng, key_shares, presigning_datas);
     let r1a = step_round(rng, r1).unwrap();
     let _signatures = step_result(rng, r1a).unwrap();
 }
+
+/// A sequential execution of the (single-round) Signing protocol for all parties
+/// up to the finalization.
+pub fn signing_round<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    key_shares: &[KeyShare<P>],
+    presigning_datas: &[PresigningData<P>],
+) -> PendingFinalization {
+    let r1 = signing_round1(rng, key_shares, presigning_datas);
+    let r1a = step_round(rng, r1).unwrap();
+    PendingFinalization::to_result(r1a)
+}
+
+type Uint<P> = <<P as SchemeParams>::Paillier as PaillierParams>::Uint;
+
+const AUX: &[u8] = b"benchmark";
+
+/// The Paillier keys, the ring-Pedersen parameters and the proven statements
+/// for the benchmarks of the sigma protocols and of the Paillier encryption.
+///
+/// Creating them involves finding several safe primes,
+/// so they are expected to be created once and reused.
+pub struct SigmaSetup<P: SchemeParams> {
+    // The prover's key
+    sk: SecretKeyPaillierPrecomputed<P::Paillier>,
+    // The key of another party (for П^{aff-g})
+    other_sk: SecretKeyPaillierPrecomputed<P::Paillier>,
+    // The verifier's ring-Pedersen parameters
+    aux_sk: SecretKeyPaillierPrecomputed<P::Paillier>,
+    lambda: RPSecret<P::Paillier>,
+    setup: RPParamsMod<P::Paillier>,
+    // The secrets, randomizers and the corresponding ciphertexts and points
+    x: Signed<Uint<P>>,
+    y: Signed<Uint<P>>,
+    rho_x: RandomizerMod<P::Paillier>,
+    rho_y: RandomizerMod<P::Paillier>,
+    rho: RandomizerMod<P::Paillier>,
+    cap_x_enc: CiphertextMod<P::Paillier>,
+    cap_y_enc: CiphertextMod<P::Paillier>,
+    cap_c: CiphertextMod<P::Paillier>,
+    cap_d: CiphertextMod<P::Paillier>,
+    cap_y_other: CiphertextMod<P::Paillier>,
+    g: Point,
+    cap_x: Point,
+}
+
+impl<P: SchemeParams> SigmaSetup<P> {
+    /// Creates random keys and statements.
+    pub fn new(rng: &mut impl CryptoRngCore) -> Self {
+        let sk = SecretKeyPaillier::<P::Paillier>::random(rng).to_precomputed();
+        let other_sk = SecretKeyPaillier::<P::Paillier>::random(rng).to_precomputed();
+        let aux_sk = SecretKeyPaillier::<P::Paillier>::random(rng).to_precomputed();
+        let lambda = RPSecret::random(rng, &aux_sk);
+        let setup = RPParamsMod::random_with_secret(rng, &lambda, aux_sk.public_key());
+
+        let pk = sk.public_key();
+        let other_pk = other_sk.public_key();
+
+        let x = Signed::random_bounded_bits(rng, P::L_BOUND);
+        let y = Signed::random_bounded_bits(rng, P::LP_BOUND);
+        let rho_x = RandomizerMod::random(rng, pk);
+        let rho_y = RandomizerMod::random(rng, other_pk);
+        let rho = RandomizerMod::random(rng, pk);
+
+        // `x` encrypted with `rho_x` is the statement of П^{enc} and П^{log*},
+        // and the one of П^{mul} together with `cap_y_enc` and `cap_c`.
+        let cap_x_enc = CiphertextMod::new_with_randomizer_signed(pk, &x, &rho_x.retrieve());
+        let cap_y_enc = CiphertextMod::new_signed(rng, pk, &y);
+        let cap_c = (&cap_y_enc * x).mul_randomizer(&rho.retrieve());
+
+        // The statement of П^{aff-g} (with `cap_x_enc` as the multiplied ciphertext).
+        let cap_d =
+            &cap_x_enc * x + CiphertextMod::new_with_randomizer_signed(pk, &-y, &rho.retrieve());
+        let cap_y_other =
+            CiphertextMod::new_with_randomizer_signed(other_pk, &y, &rho_y.retrieve());
+
+        let g = Point::GENERATOR * Scalar::random(rng);
+        let cap_x = g * P::scalar_from_signed(&x);
+
+        Self {
+            sk,
+            other_sk,
+            aux_sk,
+            lambda,
+            setup,
+            x,
+            y,
+            rho_x,
+            rho_y,
+            rho,
+            cap_x_enc,
+            cap_y_enc,
+            cap_c,
+            cap_d,
+            cap_y_other,
+            g,
+            cap_x,
+        }
+    }
+}
+
+/// Creates and verifies a П^{enc} proof.
+pub fn enc_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let pk = setup.sk.public_key();
+    let proof = EncProof::<P>::new(
+        rng,
+        &setup.x,
+        &setup.rho_x,
+        pk,
+        &setup.cap_x_enc,
+        &setup.setup,
+        &AUX,
+    );
+    assert!(proof.verify(pk, &setup.cap_x_enc, &setup.setup, &AUX));
+}
+
+/// Creates and verifies a П^{log*} proof.
+pub fn log_star_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let pk = setup.sk.public_key();
+    let proof = LogStarProof::<P>::new(
+        rng,
+        &setup.x,
+        &setup.rho_x,
+        pk,
+        &setup.cap_x_enc,
+        &setup.g,
+        &setup.cap_x,
+        &setup.setup,
+        &AUX,
+    );
+    assert!(proof.verify(
+        pk,
+        &setup.cap_x_enc,
+        &setup.g,
+        &setup.cap_x,
+        &setup.setup,
+        &AUX
+    ));
+}
+
+/// Creates and verifies a П^{aff-g} proof.
+pub fn aff_g_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let pk0 = setup.sk.public_key();
+    let pk1 = setup.other_sk.public_key();
+    let cap_x = P::scalar_from_signed(&setup.x).mul_by_generator();
+    let proof = AffGProof::<P>::new(
+        rng,
+        &setup.x,
+        &setup.y,
+        &setup.rho,
+        &setup.rho_y,
+        pk0,
+        pk1,
+        &setup.cap_x_enc,
+        &setup.cap_d,
+        &setup.cap_y_other,
+        &cap_x,
+        &setup.setup,
+        &AUX,
+    );
+    assert!(proof.verify(
+        pk0,
+        pk1,
+        &setup.cap_x_enc,
+        &setup.cap_d,
+        &setup.cap_y_other,
+        &cap_x,
+        &setup.setup,
+        &AUX
+    ));
+}
+
+/// Creates and verifies a П^{mul} proof.
+pub fn mul_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let pk = setup.sk.public_key();
+    let proof = MulProof::<P>::new(
+        rng,
+        &setup.x,
+        &setup.rho_x,
+        &setup.rho,
+        pk,
+        &setup.cap_x_enc,
+        &setup.cap_y_enc,
+        &setup.cap_c,
+        &AUX,
+    );
+    assert!(proof.verify(pk, &setup.cap_x_enc, &setup.cap_y_enc, &setup.cap_c, &AUX));
+}
+
+/// Creates and verifies a П^{fac} proof.
+pub fn fac_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let proof = FacProof::<P>::new(rng, &setup.sk, &setup.setup, &AUX);
+    assert!(proof.verify(setup.sk.public_key(), &setup.setup, &AUX));
+}
+
+/// Creates and verifies a П^{mod} proof.
+pub fn mod_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let proof = ModProof::<P>::new(rng, &setup.sk, &AUX);
+    assert!(proof.verify(setup.sk.public_key(), &AUX));
+}
+
+/// Creates and verifies a П^{prm} proof.
+pub fn prm_proof<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let proof = PrmProof::<P>::new(rng, &setup.aux_sk, &setup.lambda, &setup.setup, &AUX);
+    assert!(proof.verify(&setup.setup, &AUX));
+}
+
+/// Encrypts a random value with the Paillier key of the setup.
+pub fn paillier_encrypt<P: SchemeParams>(rng: &mut impl CryptoRngCore, setup: &SigmaSetup<P>) {
+    let _ciphertext = CiphertextMod::new_signed(rng, setup.sk.public_key(), &setup.y);
+}
+
+/// Decrypts a ciphertext with the Paillier key of the setup.
+pub fn paillier_decrypt<P: SchemeParams>(setup: &SigmaSetup<P>) {
+    let _plaintext = setup.cap_y_enc.decrypt_signed(&setup.sk);
+}
+
+/// Decrypts a ciphertext with the Paillier key of the setup without using the CRT,
+/// for comparison with [`paillier_decrypt`].
+pub fn paillier_decrypt_without_crt<P: SchemeParams>(setup: &SigmaSetup<P>) {
+    let _plaintext = setup.cap_y_enc.decrypt_without_crt(&setup.sk);
+}
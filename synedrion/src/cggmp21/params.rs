@@ -1,9 +1,14 @@
+use digest::{consts::U32, Digest};
+use sha2::Sha256;
+
+use crate::constructors::PrehashedMessage;
 use crate::curve::{Curve, Scalar, ORDER};
 use crate::paillier::PaillierParams;
-use crate::tools::hashing::{Chain, HashableType};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, HashableType, XofHash};
 use crate::uint::{
-    subtle::ConditionallySelectable, upcast_uint, Bounded, Encoding, NonZero, Signed, U1024Mod,
-    U2048Mod, U4096Mod, U512Mod, Zero, U1024, U2048, U4096, U512, U8192,
+    subtle::ConditionallySelectable, upcast_uint, Bounded, Encoding, Integer, NonZero, Signed,
+    U1024Mod, U1536Mod, U2048Mod, U3072Mod, U4096Mod, U512Mod, U768Mod, U8192Mod, Zero, U1024,
+    U1536, U16384, U2048, U3072, U4096, U512, U6144, U768, U8192,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -75,24 +80,86 @@ impl PaillierParams for PaillierProduction {
     type ExtraWideUint = U8192;
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Paillier1536;
+
+impl PaillierParams for Paillier1536 {
+    const PRIME_BITS: usize = 768;
+    type HalfUint = U768;
+    type HalfUintMod = U768Mod;
+    type Uint = U1536;
+    type UintMod = U1536Mod;
+    type WideUint = U3072;
+    type WideUintMod = U3072Mod;
+    type ExtraWideUint = U6144;
+}
+
+// A 3072-bit modulus would need a 12288-bit `ExtraWideUint`, which the backend does not provide,
+// so the next size after the production one is 4096 bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Paillier4096;
+
+impl PaillierParams for Paillier4096 {
+    const PRIME_BITS: usize = 2048;
+    type HalfUint = U2048;
+    type HalfUintMod = U2048Mod;
+    type Uint = U4096;
+    type UintMod = U4096Mod;
+    type WideUint = U8192;
+    type WideUintMod = U8192Mod;
+    type ExtraWideUint = U16384;
+}
+
 /// Signing scheme parameters.
 // TODO (#27): this trait can include curve scalar/point types as well,
 // but for now they are hardcoded to `k256`.
-pub trait SchemeParams: Clone + Send + PartialEq + Eq + core::fmt::Debug + 'static {
-    /// The order of the curve.
-    const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint>; // $q$
+pub trait SchemeParams: Clone + Send + Sync + PartialEq + Eq + core::fmt::Debug + 'static {
+    /// A unique name of this parameter set.
+    ///
+    /// It is recorded in the serialized key shares,
+    /// so that they cannot be loaded with different parameters.
+    const ID: &'static str;
+    /// The order of the curve ($q$ in the paper).
+    const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint>;
     /// The order of the curve as a wide integer.
     const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint>;
-    /// The scheme's statistical security parameter.
-    const SECURITY_PARAMETER: usize; // $\kappa$
-    /// The bound for secret values.
-    const L_BOUND: usize; // $\ell$, paper sets it to $\log2(q)$ (see Table 2)
-    /// The error bound for secret masks.
-    const LP_BOUND: usize; // $\ell^\prime$, in paper $= 5 \ell$ (see Table 2)
-    /// The error bound for range checks (referred to in the paper as the slackness parameter).
-    const EPS_BOUND: usize; // $\eps$, in paper $= 2 \ell$ (see Table 2)
+    /// The scheme's statistical security parameter ($\kappa$ in the paper).
+    const SECURITY_PARAMETER: usize;
+    /// The bound for secret values ($\ell$ in the paper, which sets it to $\log_2 q$, see Table 2).
+    const L_BOUND: usize;
+    /// The error bound for secret masks ($\ell^\prime$ in the paper, which sets it to $5 \ell$, see Table 2).
+    const LP_BOUND: usize;
+    /// The error bound for range checks
+    /// ($\eps$, the slackness parameter in the paper, which sets it to $2 \ell$, see Table 2).
+    const EPS_BOUND: usize;
+    /// Fails to compile if [`validate_scheme_params`] returns an error for these parameters
+    /// (e.g. when referenced as `const _: () = MyParams::BOUNDS_CHECK;`).
+    ///
+    /// It is also referenced when the key shares are prepared for a session,
+    /// so the parameters failing the validation cannot be used. It should not be overridden.
+    const BOUNDS_CHECK: () = match validate_scheme_params::<Self>() {
+        Ok(()) => (),
+        Err(message) => panic!("{}", message),
+    };
     /// The parameters of the Paillier encryption.
     type Paillier: PaillierParams;
+    /// The transcript used to derive the Fiat-Shamir challenges of the zero-knowledge proofs.
+    ///
+    /// The presets use [`XofHash`]; the applications whose verifiers assume a specific hash
+    /// can use e.g. [`Sha256Transcript`](crate::Sha256Transcript)
+    /// or [`Keccak256Transcript`](crate::Keccak256Transcript).
+    type Transcript: FiatShamirTranscript;
+    /// The hash of the messages to be signed (see [`prehash_message`](Self::prehash_message)).
+    ///
+    /// The presets use SHA-256; a chain expecting another digest to be signed
+    /// (e.g. Keccak-256) needs parameters with the corresponding hash.
+    type MessageDigest: Digest<OutputSize = U32>;
+
+    /// Hashes a message into the prehash taken by the signing sessions,
+    /// which is then reduced modulo the curve order to get the scalar being signed.
+    fn prehash_message(message: &[u8]) -> PrehashedMessage {
+        Self::MessageDigest::digest(message).into()
+    }
 
     /// Converts a curve scalar to the associated integer type.
     fn uint_from_scalar(value: &Scalar) -> <Self::Paillier as PaillierParams>::Uint {
@@ -157,6 +224,56 @@ pub trait SchemeParams: Clone + Send + PartialEq + Eq + core::fmt::Debug + 'stat
     }
 }
 
+/// Checks the bounds of the scheme parameters against the curve order and the size of the Paillier modulus,
+/// returning the description of the first requirement of the protocols that is not satisfied:
+/// - $\ell \ge \log_2 q$ and $\ell^\prime \ge \ell$;
+/// - $\eps \ge \log_2 q + \kappa$, so that an honest response fails the range check
+///   with the probability of at most $2^{-\kappa}$;
+/// - the responses of the proofs, of up to $\max(\ell, \ell^\prime) + \max(\eps, \log_2 q) + 1$ bits,
+///   have a sign bit to spare in `Paillier::Uint`;
+/// - the values encrypted in Presigning, of up to $\max(2 \ell, \ell^\prime + 2) + \log_2 q$ bits
+///   (plus one bit for every doubling of the number of parties), are smaller than $N / 2$.
+///
+/// Since this is a `const fn`, it can be checked at compile time
+/// (see [`SchemeParams::BOUNDS_CHECK`]).
+pub const fn validate_scheme_params<P: SchemeParams>() -> Result<(), &'static str> {
+    let order_bits = ORDER.bits_vartime();
+    let uint_bits = <<P::Paillier as PaillierParams>::Uint as Integer>::BITS;
+    let modulus_bits = <P::Paillier as PaillierParams>::MODULUS_BITS;
+    let max_bound = if P::L_BOUND > P::LP_BOUND {
+        P::L_BOUND
+    } else {
+        P::LP_BOUND
+    };
+    let max_eps = if P::EPS_BOUND > order_bits {
+        P::EPS_BOUND
+    } else {
+        order_bits
+    };
+    let max_presigning = if 2 * P::L_BOUND > P::LP_BOUND + 2 {
+        2 * P::L_BOUND
+    } else {
+        P::LP_BOUND + 2
+    };
+
+    if P::L_BOUND < order_bits {
+        return Err("L_BOUND is smaller than the size of the curve order");
+    }
+    if P::LP_BOUND < P::L_BOUND {
+        return Err("LP_BOUND is smaller than L_BOUND");
+    }
+    if P::EPS_BOUND < order_bits + P::SECURITY_PARAMETER {
+        return Err("EPS_BOUND does not give the statistical security of SECURITY_PARAMETER bits");
+    }
+    if max_bound + max_eps + 1 >= uint_bits - 1 {
+        return Err("The responses of the ZK proofs do not fit in the Paillier integers");
+    }
+    if max_presigning + order_bits >= modulus_bits - 2 {
+        return Err("The values encrypted in Presigning do not fit in the Paillier modulus");
+    }
+    Ok(())
+}
+
 impl<P: SchemeParams> HashableType for P {
     fn chain_type<C: Chain>(digest: C) -> C {
         digest.chain_type::<Curve>()
@@ -168,20 +285,24 @@ impl<P: SchemeParams> HashableType for P {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TestParams;
 
-// Some requirements from range proofs etc:
+// Some requirements from range proofs etc (checked in `validate_scheme_params()`):
 // - $П_{enc}$, safe two's complement representation of $\alpha$ requires
 //   `L_BOUND + EPS_BOUND + 1 < Uint::BITS - 1`
 // - $П_{enc}$, safe two's complement representation of $z_1$ requires
 //   `L_BOUND + max(EPS_BOUND, log2(q)) + 1 < Uint::BITS - 1`
 //   (where `q` is the curve order)
 // - Range checks will fail with the probability $q / 2^\eps$, so $\eps$ should be large enough.
+// Not checked, since the smaller presets do not satisfy it:
 // - P^{fac} assumes $N ~ 2^{4 \ell + 2 \eps}$
 impl SchemeParams for TestParams {
+    const ID: &'static str = "synedrion-test";
     const SECURITY_PARAMETER: usize = 10;
     const L_BOUND: usize = 256;
     const LP_BOUND: usize = 256;
     const EPS_BOUND: usize = 320;
     type Paillier = PaillierTest;
+    type Transcript = XofHash;
+    type MessageDigest = Sha256;
     const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
         NonZero::<<Self::Paillier as PaillierParams>::Uint>::const_new(upcast_uint(ORDER)).0;
     const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
@@ -193,13 +314,140 @@ impl SchemeParams for TestParams {
 pub struct ProductionParams;
 
 impl SchemeParams for ProductionParams {
+    const ID: &'static str = "synedrion-production";
     const SECURITY_PARAMETER: usize = 80; // The value is given in Table 2 in the paper
     const L_BOUND: usize = 256;
     const LP_BOUND: usize = Self::L_BOUND * 5;
     const EPS_BOUND: usize = Self::L_BOUND * 2;
     type Paillier = PaillierProduction;
+    type Transcript = XofHash;
+    type MessageDigest = Sha256;
     const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
         NonZero::<<Self::Paillier as PaillierParams>::Uint>::const_new(upcast_uint(ORDER)).0;
     const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
         NonZero::<<Self::Paillier as PaillierParams>::WideUint>::const_new(upcast_uint(ORDER)).0;
 }
+
+/// Parameters with a 1536-bit Paillier modulus,
+/// trading the security margin for speed compared to [`ProductionParams`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Params1536;
+
+// With the bounds of `ProductionParams`, the values encrypted during the presigning
+// would not fit in a 1536-bit modulus (see the comment for `PaillierTest`),
+// so $\ell^\prime$ is reduced to $3 \ell$, which still masks the $2 \ell$-bit products.
+impl SchemeParams for Params1536 {
+    const ID: &'static str = "synedrion-1536";
+    const SECURITY_PARAMETER: usize = 80;
+    const L_BOUND: usize = 256;
+    const LP_BOUND: usize = Self::L_BOUND * 3;
+    const EPS_BOUND: usize = Self::L_BOUND * 2;
+    type Paillier = Paillier1536;
+    type Transcript = XofHash;
+    type MessageDigest = Sha256;
+    const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
+        NonZero::<<Self::Paillier as PaillierParams>::Uint>::const_new(upcast_uint(ORDER)).0;
+    const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
+        NonZero::<<Self::Paillier as PaillierParams>::WideUint>::const_new(upcast_uint(ORDER)).0;
+}
+
+/// Parameters with a 4096-bit Paillier modulus,
+/// giving a larger security margin than [`ProductionParams`] at the cost of speed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Params4096;
+
+impl SchemeParams for Params4096 {
+    const ID: &'static str = "synedrion-4096";
+    const SECURITY_PARAMETER: usize = 80;
+    const L_BOUND: usize = 256;
+    const LP_BOUND: usize = Self::L_BOUND * 5;
+    const EPS_BOUND: usize = Self::L_BOUND * 2;
+    type Paillier = Paillier4096;
+    type Transcript = XofHash;
+    type MessageDigest = Sha256;
+    const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
+        NonZero::<<Self::Paillier as PaillierParams>::Uint>::const_new(upcast_uint(ORDER)).0;
+    const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
+        NonZero::<<Self::Paillier as PaillierParams>::WideUint>::const_new(upcast_uint(ORDER)).0;
+}
+
+const _: () = TestParams::BOUNDS_CHECK;
+const _: () = ProductionParams::BOUNDS_CHECK;
+const _: () = Params1536::BOUNDS_CHECK;
+const _: () = Params4096::BOUNDS_CHECK;
+
+/// The computational security levels (in bits) of the parameter presets,
+/// paired with their [`SchemeParams::ID`], in increasing order.
+///
+/// The levels are the ones NIST SP 800-57 (Part 1, Table 2) gives for the RSA moduli of the same size
+/// as the Paillier modulus, rounded down to the nearest listed size.
+/// [`TestParams`] are not included, since they are not secure.
+pub const PRESET_SECURITY_LEVELS: &[(usize, &str)] = &[
+    (80, Params1536::ID),
+    (112, ProductionParams::ID),
+    (128, Params4096::ID),
+];
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use sha2::Sha256;
+
+    use super::{
+        validate_scheme_params, Params1536, Params4096, ProductionParams, SchemeParams, TestParams,
+        PRESET_SECURITY_LEVELS,
+    };
+    use crate::curve::Scalar;
+    use crate::paillier::PaillierParams;
+    use crate::tools::hashing::XofHash;
+    use crate::uint::NonZero;
+
+    fn scalar_roundtrip<P: SchemeParams>() {
+        let scalar = Scalar::random(&mut OsRng);
+        assert_eq!(P::scalar_from_uint(&P::uint_from_scalar(&scalar)), scalar);
+        assert_eq!(
+            P::scalar_from_signed(&P::signed_from_scalar(&-scalar)),
+            -scalar
+        );
+    }
+
+    #[test]
+    fn presets() {
+        scalar_roundtrip::<Params1536>();
+        scalar_roundtrip::<Params4096>();
+
+        assert!(PRESET_SECURITY_LEVELS
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct LowSlackParams;
+
+    impl SchemeParams for LowSlackParams {
+        const ID: &'static str = "low-slack";
+        const SECURITY_PARAMETER: usize = ProductionParams::SECURITY_PARAMETER;
+        const L_BOUND: usize = ProductionParams::L_BOUND;
+        const LP_BOUND: usize = ProductionParams::LP_BOUND;
+        const EPS_BOUND: usize = ProductionParams::L_BOUND;
+        type Paillier = <ProductionParams as SchemeParams>::Paillier;
+        type Transcript = XofHash;
+        type MessageDigest = Sha256;
+        const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
+            ProductionParams::CURVE_ORDER;
+        const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
+            ProductionParams::CURVE_ORDER_WIDE;
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(validate_scheme_params::<TestParams>(), Ok(()));
+        assert_eq!(validate_scheme_params::<ProductionParams>(), Ok(()));
+        assert_eq!(validate_scheme_params::<Params1536>(), Ok(()));
+        assert_eq!(validate_scheme_params::<Params4096>(), Ok(()));
+        assert_eq!(
+            validate_scheme_params::<LowSlackParams>(),
+            Err("EPS_BOUND does not give the statistical security of SECURITY_PARAMETER bits")
+        );
+    }
+}
@@ -1,15 +1,42 @@
+#[cfg(feature = "signing")]
 pub(crate) mod interactive_signing;
+pub(crate) mod key_export;
+#[cfg(feature = "keygen")]
 pub(crate) mod key_gen;
+#[cfg(feature = "keygen")]
+pub(crate) mod key_import;
+#[cfg(feature = "keygen")]
 pub(crate) mod key_init;
+#[cfg(feature = "key-refresh")]
 pub(crate) mod key_refresh;
+#[cfg(feature = "signing")]
 pub(crate) mod presigning;
+#[cfg(feature = "signing")]
+pub(crate) mod schnorr_signing;
+#[cfg(feature = "signing")]
 pub(crate) mod signing;
+pub(crate) mod threshold_refresh;
 
+#[cfg(feature = "signing")]
 pub use interactive_signing::{
-    InteractiveSigningError, InteractiveSigningProof, InteractiveSigningResult,
+    AdaptorSigningResult, InteractiveSigningError, InteractiveSigningProof,
+    InteractiveSigningResult,
 };
+pub use key_export::{ExportedKey, KeyDecryptionError, KeyExportError, KeyExportResult};
+#[cfg(feature = "keygen")]
 pub use key_gen::{KeyGenError, KeyGenProof, KeyGenResult};
+#[cfg(feature = "keygen")]
+pub use key_import::{KeyImportError, KeyImportResult};
+#[cfg(feature = "keygen")]
 pub use key_init::{KeyInitError, KeyInitResult};
+#[cfg(feature = "key-refresh")]
 pub use key_refresh::KeyRefreshResult;
-pub use presigning::{PresigningError, PresigningProof, PresigningResult};
-pub use signing::{SigningProof, SigningResult};
+#[cfg(feature = "signing")]
+pub use presigning::{
+    PresigningError, PresigningMode, PresigningPrecompute, PresigningProof, PresigningResult,
+};
+#[cfg(feature = "signing")]
+pub use schnorr_signing::{SchnorrSigningError, SchnorrSigningResult};
+#[cfg(feature = "signing")]
+pub use signing::{SigningError, SigningProof, SigningResult};
+pub use threshold_refresh::{ThresholdRefreshError, ThresholdRefreshResult};
@@ -1,14 +1,17 @@
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
+use alloc::format;
 use core::marker::PhantomData;
 
 use rand_core::CryptoRngCore;
 
 use super::presigning::{self, PresigningResult};
 use super::signing::{self, SigningResult};
+use crate::adaptor::AdaptorSignature;
 use crate::cggmp21::params::SchemeParams;
 use crate::common::KeyShare;
-use crate::curve::{RecoverableSignature, Scalar};
+use crate::constructors::PrehashedMessage;
+use crate::curve::{Point, RecoverableSignature, Scalar, VerifyingKey};
 use crate::rounds::{
     wrap_finalize_error, CorrectnessProofWrapper, FinalizableToNextRound, FinalizableToResult,
     FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult, ProvableErrorWrapper, Round,
@@ -20,6 +23,7 @@ use crate::rounds::{
 pub struct InteractiveSigningResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for InteractiveSigningResult<P> {
+    const NAME: &'static str = "InteractiveSigning";
     type Success = RecoverableSignature;
     type ProvableError = InteractiveSigningError<P>;
     type CorrectnessProof = InteractiveSigningProof<P>;
@@ -75,24 +79,134 @@ impl<P: SchemeParams> CorrectnessProofWrapper<SigningResult<P>> for InteractiveS
     }
 }
 
+/// Possible results of the merged Presigning and Signing protocols creating an adaptor signature.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptorSigningResult<P: SchemeParams>(PhantomData<P>);
+
+impl<P: SchemeParams> ProtocolResult for AdaptorSigningResult<P> {
+    const NAME: &'static str = "AdaptorSigning";
+    type Success = AdaptorSignature;
+    type ProvableError = InteractiveSigningError<P>;
+    type CorrectnessProof = InteractiveSigningProof<P>;
+}
+
+impl<P: SchemeParams> ProvableErrorWrapper<PresigningResult<P>> for AdaptorSigningResult<P> {
+    fn wrap_error(
+        error: <PresigningResult<P> as ProtocolResult>::ProvableError,
+    ) -> Self::ProvableError {
+        InteractiveSigningError::Presigning(error)
+    }
+}
+
+impl<P: SchemeParams> CorrectnessProofWrapper<PresigningResult<P>> for AdaptorSigningResult<P> {
+    fn wrap_proof(
+        proof: <PresigningResult<P> as ProtocolResult>::CorrectnessProof,
+    ) -> Self::CorrectnessProof {
+        InteractiveSigningProof::Presigning(proof)
+    }
+}
+
+impl<P: SchemeParams> ProvableErrorWrapper<SigningResult<P>> for AdaptorSigningResult<P> {
+    fn wrap_error(
+        error: <SigningResult<P> as ProtocolResult>::ProvableError,
+    ) -> Self::ProvableError {
+        InteractiveSigningError::Signing(error)
+    }
+}
+
+impl<P: SchemeParams> CorrectnessProofWrapper<SigningResult<P>> for AdaptorSigningResult<P> {
+    fn wrap_proof(
+        proof: <SigningResult<P> as ProtocolResult>::CorrectnessProof,
+    ) -> Self::CorrectnessProof {
+        InteractiveSigningProof::Signing(proof)
+    }
+}
+
+/// The result of the merged protocols, defining what is assembled in the last round.
+pub(crate) trait SigningOutput<P: SchemeParams>:
+    ProvableErrorWrapper<PresigningResult<P>>
+    + CorrectnessProofWrapper<PresigningResult<P>>
+    + ProvableErrorWrapper<SigningResult<P>>
+    + CorrectnessProofWrapper<SigningResult<P>>
+    + Sized
+    + Send
+    + Sync
+    + 'static
+{
+    fn finalize_signing(
+        round: Round4<P, Self>,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Artifact>,
+    ) -> Result<Self::Success, FinalizeError<Self>>;
+}
+
+impl<P: SchemeParams> SigningOutput<P> for InteractiveSigningResult<P> {
+    fn finalize_signing(
+        round: Round4<P, Self>,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Artifact>,
+    ) -> Result<Self::Success, FinalizeError<Self>> {
+        let signature = round
+            .round
+            .finalize_to_result(rng, payloads, artifacts)
+            .map_err(wrap_finalize_error)?;
+
+        // The signature is assembled from the shares checked on arrival,
+        // but make sure that a corrupted result never leaves the library.
+        let prehash: PrehashedMessage = round.message.to_bytes().into();
+        if !signature.verify(&round.verifying_key, &prehash) {
+            return Err(FinalizeError::Local(format!(
+                "The assembled signature {:?} does not verify with the verifying key {:?} \
+                for the message {}",
+                signature,
+                round.verifying_key,
+                hex::encode(prehash)
+            )));
+        }
+
+        Ok(signature)
+    }
+}
+
+impl<P: SchemeParams> SigningOutput<P> for AdaptorSigningResult<P> {
+    fn finalize_signing(
+        round: Round4<P, Self>,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <signing::Round1<P> as Round>::Artifact>,
+    ) -> Result<Self::Success, FinalizeError<Self>> {
+        // The pre-signature is checked against the verifying key on assembly.
+        round
+            .round
+            .finalize_to_adaptor_signature(rng, payloads)
+            .map_err(wrap_finalize_error)
+    }
+}
+
 struct RoundContext<P: SchemeParams> {
     shared_randomness: Box<[u8]>,
     key_share: KeyShare<P>,
     message: Scalar,
 }
 
-#[derive(Clone)]
 pub(crate) struct Inputs<P: SchemeParams> {
     pub(crate) key_share: KeyShare<P>,
     pub(crate) message: Scalar,
+    /// The adaptor point, which must be given for (and only for) [`AdaptorSigningResult`].
+    pub(crate) adaptor_point: Option<Point>,
+    /// The values of the first Presigning round computed in advance for `key_share`.
+    pub(crate) presigning_precompute: Option<presigning::PresigningPrecompute<P>>,
 }
 
-pub(crate) struct Round1<P: SchemeParams> {
+pub(crate) struct Round1<P: SchemeParams, Res = InteractiveSigningResult<P>> {
     round: presigning::Round1<P>,
     context: RoundContext<P>,
+    phantom: PhantomData<fn() -> Res>,
 }
 
-impl<P: SchemeParams> FirstRound for Round1<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> FirstRound for Round1<P, Res> {
     type Inputs = Inputs<P>;
     fn new(
         rng: &mut impl CryptoRngCore,
@@ -106,20 +220,30 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
             shared_randomness,
             num_parties,
             party_idx,
-            inputs.key_share.clone(),
+            presigning::Inputs {
+                adaptor_point: inputs.adaptor_point,
+                ..match inputs.presigning_precompute {
+                    Some(precompute) => presigning::Inputs::with_precompute(precompute),
+                    None => presigning::Inputs::new(inputs.key_share.clone()),
+                }
+            },
         )?;
         let context = RoundContext {
             shared_randomness: shared_randomness.into(),
             key_share: inputs.key_share,
             message: inputs.message,
         };
-        Ok(Self { context, round })
+        Ok(Self {
+            context,
+            round,
+            phantom: PhantomData,
+        })
     }
 }
 
-impl<P: SchemeParams> RoundWrapper for Round1<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> RoundWrapper for Round1<P, Res> {
     type Type = ToNextRound;
-    type Result = InteractiveSigningResult<P>;
+    type Result = Res;
     type InnerRound = presigning::Round1<P>;
     const ROUND_NUM: u8 = 1;
     const NEXT_ROUND_NUM: Option<u8> = Some(2);
@@ -128,8 +252,8 @@ impl<P: SchemeParams> RoundWrapper for Round1<P> {
     }
 }
 
-impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
-    type NextRound = Round2<P>;
+impl<P: SchemeParams, Res: SigningOutput<P>> FinalizableToNextRound for Round1<P, Res> {
+    type NextRound = Round2<P, Res>;
     fn finalize_to_next_round(
         self,
         rng: &mut impl CryptoRngCore,
@@ -143,18 +267,20 @@ impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
         Ok(Round2 {
             round,
             context: self.context,
+            phantom: PhantomData,
         })
     }
 }
 
-pub(crate) struct Round2<P: SchemeParams> {
+pub(crate) struct Round2<P: SchemeParams, Res = InteractiveSigningResult<P>> {
     round: presigning::Round2<P>,
     context: RoundContext<P>,
+    phantom: PhantomData<fn() -> Res>,
 }
 
-impl<P: SchemeParams> RoundWrapper for Round2<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> RoundWrapper for Round2<P, Res> {
     type Type = ToNextRound;
-    type Result = InteractiveSigningResult<P>;
+    type Result = Res;
     type InnerRound = presigning::Round2<P>;
     const ROUND_NUM: u8 = 2;
     const NEXT_ROUND_NUM: Option<u8> = Some(3);
@@ -163,8 +289,8 @@ impl<P: SchemeParams> RoundWrapper for Round2<P> {
     }
 }
 
-impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
-    type NextRound = Round3<P>;
+impl<P: SchemeParams, Res: SigningOutput<P>> FinalizableToNextRound for Round2<P, Res> {
+    type NextRound = Round3<P, Res>;
     fn finalize_to_next_round(
         self,
         rng: &mut impl CryptoRngCore,
@@ -178,18 +304,20 @@ impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
         Ok(Round3 {
             round,
             context: self.context,
+            phantom: PhantomData,
         })
     }
 }
 
-pub(crate) struct Round3<P: SchemeParams> {
+pub(crate) struct Round3<P: SchemeParams, Res = InteractiveSigningResult<P>> {
     round: presigning::Round3<P>,
     context: RoundContext<P>,
+    phantom: PhantomData<fn() -> Res>,
 }
 
-impl<P: SchemeParams> RoundWrapper for Round3<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> RoundWrapper for Round3<P, Res> {
     type Type = ToNextRound;
-    type Result = InteractiveSigningResult<P>;
+    type Result = Res;
     type InnerRound = presigning::Round3<P>;
     const ROUND_NUM: u8 = 3;
     const NEXT_ROUND_NUM: Option<u8> = Some(4);
@@ -198,8 +326,8 @@ impl<P: SchemeParams> RoundWrapper for Round3<P> {
     }
 }
 
-impl<P: SchemeParams> FinalizableToNextRound for Round3<P> {
-    type NextRound = Round4<P>;
+impl<P: SchemeParams, Res: SigningOutput<P>> FinalizableToNextRound for Round3<P, Res> {
+    type NextRound = Round4<P, Res>;
     fn finalize_to_next_round(
         self,
         rng: &mut impl CryptoRngCore,
@@ -228,19 +356,23 @@ impl<P: SchemeParams> FinalizableToNextRound for Round3<P> {
 
         Ok(Round4 {
             round: signing_round,
+            verifying_key: self.context.key_share.verifying_key(),
+            message: self.context.message,
             phantom: PhantomData,
         })
     }
 }
 
-pub(crate) struct Round4<P: SchemeParams> {
+pub(crate) struct Round4<P: SchemeParams, Res = InteractiveSigningResult<P>> {
     round: signing::Round1<P>,
-    phantom: PhantomData<P>,
+    verifying_key: VerifyingKey,
+    message: Scalar,
+    phantom: PhantomData<fn() -> Res>,
 }
 
-impl<P: SchemeParams> RoundWrapper for Round4<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> RoundWrapper for Round4<P, Res> {
     type Type = ToResult;
-    type Result = InteractiveSigningResult<P>;
+    type Result = Res;
     type InnerRound = signing::Round1<P>;
     const ROUND_NUM: u8 = 4;
     const NEXT_ROUND_NUM: Option<u8> = None;
@@ -249,31 +381,31 @@ impl<P: SchemeParams> RoundWrapper for Round4<P> {
     }
 }
 
-impl<P: SchemeParams> FinalizableToResult for Round4<P> {
+impl<P: SchemeParams, Res: SigningOutput<P>> FinalizableToResult for Round4<P, Res> {
     fn finalize_to_result(
         self,
         rng: &mut impl CryptoRngCore,
         payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
         artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
     ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
-        self.round
-            .finalize_to_result(rng, payloads, artifacts)
-            .map_err(wrap_finalize_error)
+        Res::finalize_signing(self, rng, payloads, artifacts)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use k256::ecdsa::{signature::hazmat::PrehashVerifier, VerifyingKey};
+    use alloc::vec;
+
     use rand_core::{OsRng, RngCore};
+    use signature::hazmat::PrehashVerifier;
 
-    use super::{Inputs, Round1};
+    use super::{AdaptorSigningResult, Inputs, Round1};
     use crate::cggmp21::TestParams;
     use crate::common::KeyShare;
-    use crate::curve::Scalar;
+    use crate::curve::{Point, Scalar, SigningKey, VerifyingKey};
     use crate::rounds::{
         test_utils::{step_next_round, step_result, step_round},
-        FirstRound, PartyIdx,
+        FinalizeError, FirstRound, PartyIdx,
     };
 
     #[test]
@@ -295,6 +427,8 @@ mod tests {
                     Inputs {
                         message,
                         key_share: key_shares[idx].clone(),
+                        adaptor_point: None,
+                        presigning_precompute: None,
                     },
                 )
                 .unwrap()
@@ -324,4 +458,120 @@ mod tests {
             assert_eq!(recovered_key, vkey);
         }
     }
+
+    #[test]
+    fn self_check() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let message = Scalar::random(&mut OsRng);
+
+        let num_parties = 3;
+        let key_shares = KeyShare::new_centralized(&mut OsRng, num_parties, None);
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        message,
+                        key_share: key_shares[idx].clone(),
+                        adaptor_point: None,
+                        presigning_precompute: None,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let mut r4 = step_next_round(&mut OsRng, r3a).unwrap();
+
+        // Simulate a corruption of the verifying key stored by the first party.
+        let other_key_share = KeyShare::<TestParams>::new_centralized(&mut OsRng, 2, None);
+        r4[0].verifying_key = other_key_share[0].verifying_key();
+
+        let r4a = step_round(&mut OsRng, r4).unwrap();
+        // Only the party with the corrupted key fails its self-check.
+        let failed_self_check = r4a
+            .into_iter()
+            .map(|round| {
+                matches!(
+                    step_result(&mut OsRng, vec![round]),
+                    Err(FinalizeError::Local(_))
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(failed_self_check, [true, false, false]);
+    }
+
+    #[test]
+    fn execute_adaptor_signing() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let message = Scalar::random(&mut OsRng);
+        let adaptor_secret = SigningKey::random(&mut OsRng);
+        let adaptor_point = Point::from_verifying_key(adaptor_secret.verifying_key());
+
+        let num_parties = 3;
+        let key_shares = KeyShare::new_centralized(&mut OsRng, num_parties, None);
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams, AdaptorSigningResult<TestParams>>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        message,
+                        key_share: key_shares[idx].clone(),
+                        adaptor_point: Some(adaptor_point),
+                        presigning_precompute: None,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let r4 = step_next_round(&mut OsRng, r3a).unwrap();
+        let r4a = step_round(&mut OsRng, r4).unwrap();
+        let adaptor_signatures = step_result(&mut OsRng, r4a).unwrap();
+
+        let vkey = key_shares[0].verifying_key();
+        let prehash: [u8; 32] = message.to_bytes().into();
+        let wrong_secret = SigningKey::random(&mut OsRng);
+
+        for adaptor_signature in adaptor_signatures {
+            assert!(adaptor_signature.verify(&vkey, &prehash));
+            assert!(!adaptor_signature.verify(wrong_secret.verifying_key(), &prehash));
+
+            // Only the discrete log of the adaptor point completes the pre-signature
+            assert!(adaptor_signature
+                .complete(&vkey, &prehash, &wrong_secret)
+                .is_none());
+            let signature = adaptor_signature
+                .complete(&vkey, &prehash, &adaptor_secret)
+                .unwrap();
+            let (sig, _rec_id) = signature.to_backend();
+            vkey.verify_prehash(&prehash, &sig).unwrap();
+
+            // The published signature reveals the adaptor secret
+            let recovered_secret = adaptor_signature
+                .recover_adaptor_secret(&signature)
+                .unwrap();
+            assert_eq!(recovered_secret, adaptor_secret);
+        }
+    }
 }
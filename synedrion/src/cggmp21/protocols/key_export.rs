@@ -0,0 +1,409 @@
+//! Key export protocol: the parties encrypt their secret shares to a designated recipient,
+//! who can then reconstruct the full secret key.
+//!
+//! This is not a part of the CGGMP'21 paper. Each party masks its share
+//! with a value derived from a Diffie-Hellman key shared with the recipient
+//! (as in hashed ElGamal), and proves the knowledge of the mask
+//! of the difference between the masked share and its public share.
+//! Therefore every party can check that the contributions of the others are consistent
+//! with their public shares, and the recipient only has to check that it derives the same masks.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::cggmp21::{
+    sigma::{SchCommitment, SchProof, SchSecret},
+    SchemeParams,
+};
+use crate::common::KeyShare;
+use crate::curve::{Point, Scalar, SigningKey, VerifyingKey};
+use crate::rounds::{
+    all_parties_except, no_direct_messages, try_to_holevec, FinalizableToResult, FinalizeError,
+    FirstRound, InitError, PartyIdx, ProtocolResult, Round, ToResult,
+};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hash, HashInto, HashOutput, XofHash};
+use crate::tools::serialized_size::MaxSerializedSize;
+
+/// Possible results of the KeyExport protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyExportResult<P: SchemeParams>(PhantomData<P>);
+
+impl<P: SchemeParams> ProtocolResult for KeyExportResult<P> {
+    const NAME: &'static str = "KeyExport";
+    type Success = ExportedKey;
+    type ProvableError = KeyExportError;
+    type CorrectnessProof = ();
+}
+
+/// Possible verifiable errors of the KeyExport protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyExportError {
+    /// The ephemeral key sent in Round 1 is invalid.
+    R1InvalidEphemeralKey,
+    /// Failed to verify `П^{sch}` for the mask of the contribution in Round 1.
+    R1InvalidSchProof,
+}
+
+/// Possible errors when decrypting an [`ExportedKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum KeyDecryptionError {
+    /// The key was exported to a different recipient.
+    WrongRecipient,
+    /// The contribution of the party {0} is encrypted with a wrong mask.
+    InvalidContribution(usize),
+    /// The exported key is zero.
+    ZeroKey,
+}
+
+impl core::error::Error for KeyDecryptionError {}
+
+/// A secret share encrypted to the recipient.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Contribution {
+    // The ephemeral key of the sender
+    cap_r: Point,
+    // The secret share plus the mask
+    c: Scalar,
+}
+
+impl MaxSerializedSize for Contribution {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties) + Scalar::max_serialized_size(num_parties)
+    }
+}
+
+/// The secret key encrypted to a designated recipient,
+/// the result of a session created by [`make_key_export_session`](crate::make_key_export_session).
+///
+/// The contributions of all the parties have been checked against their public shares
+/// during the session; only the recipient can decrypt them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedKey {
+    recipient: Point,
+    public_shares: Vec<Point>,
+    contributions: Vec<Contribution>,
+}
+
+impl ExportedKey {
+    /// Returns the verifying key corresponding to the exported secret key.
+    pub fn verifying_key(&self) -> Option<VerifyingKey> {
+        self.public_shares.iter().sum::<Point>().to_verifying_key()
+    }
+
+    /// Decrypts the secret key with the recipient's key.
+    pub fn decrypt(&self, recipient_key: &SigningKey) -> Result<SigningKey, KeyDecryptionError> {
+        let q = Scalar::from(recipient_key.as_nonzero_scalar());
+        if q.mul_by_generator() != self.recipient {
+            return Err(KeyDecryptionError::WrongRecipient);
+        }
+
+        // A missing contribution is reported as invalid.
+        if self.contributions.len() < self.public_shares.len() {
+            return Err(KeyDecryptionError::InvalidContribution(
+                self.contributions.len(),
+            ));
+        }
+
+        let mut secret = Scalar::ZERO;
+        for (idx, (contribution, public_share)) in self
+            .contributions
+            .iter()
+            .zip(self.public_shares.iter())
+            .enumerate()
+        {
+            let mask = contribution_mask(
+                &self.recipient,
+                &contribution.cap_r,
+                &(contribution.cap_r * q),
+            );
+            let share = contribution.c - mask;
+            if &share.mul_by_generator() != public_share {
+                return Err(KeyDecryptionError::InvalidContribution(idx));
+            }
+            secret = secret + share;
+        }
+
+        let key =
+            SigningKey::from_bytes(&secret.to_bytes()).map_err(|_| KeyDecryptionError::ZeroKey);
+        #[cfg(feature = "zeroize")]
+        secret.zeroize();
+        key
+    }
+}
+
+#[derive(Clone)]
+pub struct Inputs<P: SchemeParams> {
+    pub key_share: KeyShare<P>,
+    pub recipient: Point,
+}
+
+/// Derives the mask of a contribution from the Diffie-Hellman point shared with the recipient.
+fn contribution_mask(recipient: &Point, cap_r: &Point, shared_point: &Point) -> Scalar {
+    let mut reader = XofHash::new_with_dst(b"KeyExportMask")
+        .chain(recipient)
+        .chain(cap_r)
+        .chain(shared_point)
+        .finalize_to_reader();
+    Scalar::from_reader(&mut reader)
+}
+
+struct Context {
+    num_parties: usize,
+    party_idx: PartyIdx,
+    recipient: Point,
+    public_shares: Vec<Point>,
+    contribution: Contribution,
+    // The mask of the contribution and the secret for its proof of knowledge
+    k: Scalar,
+    tau: SchSecret,
+    cap_t: SchCommitment,
+    sid_hash: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.tau.zeroize();
+    }
+}
+
+pub struct Round1<P: SchemeParams> {
+    context: Context,
+    phantom: PhantomData<P>,
+}
+
+impl<P: SchemeParams> FirstRound for Round1<P> {
+    type Inputs = Inputs<P>;
+
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        if inputs.recipient == Point::IDENTITY {
+            return Err(InitError(
+                "The recipient's key is the identity point".into(),
+            ));
+        }
+
+        let sid_hash = Hash::new_with_dst(b"SID")
+            .chain_type::<P>()
+            .chain(&shared_randomness)
+            .chain(&inputs.key_share.share_set_id)
+            .chain(&inputs.recipient)
+            .finalize();
+
+        let r = Scalar::random_nonzero(rng);
+        let cap_r = r.mul_by_generator();
+        let k = contribution_mask(&inputs.recipient, &cap_r, &(inputs.recipient * r));
+        let c = inputs.key_share.secret_share + k;
+
+        let tau = SchSecret::random(rng);
+        let cap_t = SchCommitment::new(&tau);
+
+        Ok(Self {
+            context: Context {
+                num_parties,
+                party_idx,
+                recipient: inputs.recipient,
+                public_shares: inputs.key_share.public_shares.to_vec(),
+                contribution: Contribution { cap_r, c },
+                k,
+                tau,
+                cap_t,
+                sid_hash,
+            },
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round1Message {
+    contribution: Contribution,
+    cap_t: SchCommitment,
+    psi: SchProof,
+}
+
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Contribution::max_serialized_size(num_parties)
+            + SchCommitment::max_serialized_size(num_parties)
+            + SchProof::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round1Payload {
+    contribution: Contribution,
+}
+
+impl<P: SchemeParams> Round for Round1<P> {
+    type Type = ToResult;
+    type Result = KeyExportResult<P>;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = Round1Message;
+    type DirectMessage = ();
+    type Payload = Round1Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        let contribution = self.context.contribution.clone();
+        // `c G - X_i = k G`
+        let cap_k = contribution.c.mul_by_generator()
+            - self.context.public_shares[self.party_idx().as_usize()];
+        let aux = (
+            &self.context.sid_hash,
+            &self.party_idx(),
+            &contribution.cap_r,
+        );
+        let psi = SchProof::new::<P>(
+            &self.context.tau,
+            &self.context.k,
+            &self.context.cap_t,
+            &cap_k,
+            &aux,
+        );
+        Some(Round1Message {
+            contribution,
+            cap_t: self.context.cap_t.clone(),
+            psi,
+        })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let contribution = broadcast_msg.contribution;
+        if contribution.cap_r == Point::IDENTITY {
+            return Err(KeyExportError::R1InvalidEphemeralKey);
+        }
+
+        let cap_k = contribution.c.mul_by_generator() - self.context.public_shares[from.as_usize()];
+        let aux = (&self.context.sid_hash, &from, &contribution.cap_r);
+        if !broadcast_msg
+            .psi
+            .verify::<P>(&broadcast_msg.cap_t, &cap_k, &aux)
+        {
+            return Err(KeyExportError::R1InvalidSchProof);
+        }
+
+        Ok(Round1Payload { contribution })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round1<P> {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let contributions = payloads
+            .map(|payload| payload.contribution)
+            .into_vec(self.context.contribution.clone());
+        Ok(ExportedKey {
+            recipient: self.context.recipient,
+            public_shares: self.context.public_shares.clone(),
+            contributions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{OsRng, RngCore};
+
+    use super::{Inputs, KeyDecryptionError, Round1};
+    use crate::cggmp21::TestParams;
+    use crate::common::KeyShare;
+    use crate::curve::{Point, SigningKey};
+    use crate::rounds::{
+        test_utils::{step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+
+    #[test]
+    fn execute_key_export() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let num_parties = 3;
+        let signing_key = SigningKey::random(&mut OsRng);
+        let key_shares =
+            KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, Some(&signing_key));
+
+        let recipient_key = SigningKey::random(&mut OsRng);
+        let recipient = Point::from_verifying_key(recipient_key.verifying_key());
+
+        let r1 = key_shares
+            .iter()
+            .enumerate()
+            .map(|(idx, key_share)| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        key_share: key_share.clone(),
+                        recipient,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let exported_keys = step_result(&mut OsRng, r1a).unwrap();
+
+        for exported_key in exported_keys {
+            assert_eq!(
+                exported_key.verifying_key().unwrap(),
+                *signing_key.verifying_key()
+            );
+            let decrypted = exported_key.decrypt(&recipient_key).unwrap();
+            assert_eq!(decrypted.to_bytes(), signing_key.to_bytes());
+
+            // Nobody else can decrypt it
+            assert_eq!(
+                exported_key
+                    .decrypt(&SigningKey::random(&mut OsRng))
+                    .unwrap_err(),
+                KeyDecryptionError::WrongRecipient
+            );
+        }
+    }
+}
@@ -2,19 +2,21 @@
 //! Since both take three rounds and are independent, we can execute them in parallel.
 
 use alloc::collections::BTreeMap;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use rand_core::CryptoRngCore;
 
-use super::key_init::{self, KeyInitResult};
+use super::key_init::{self, KeyInitError, KeyInitResult};
 use super::key_refresh::{self, KeyRefreshResult};
 use crate::cggmp21::SchemeParams;
 use crate::common::KeyShare;
+use crate::curve::Point;
 use crate::rounds::{
     no_direct_messages, wrap_finalize_error, CorrectnessProofWrapper, FinalizableToNextRound,
-    FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult, Round,
-    ToNextRound, ToResult,
+    FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult,
+    ProvableErrorWrapper, Round, ToNextRound, ToResult,
 };
 
 /// Possible results of the merged KeyGen and KeyRefresh protocols.
@@ -22,6 +24,7 @@ use crate::rounds::{
 pub struct KeyGenResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for KeyGenResult<P> {
+    const NAME: &'static str = "KeyGen";
     type Success = KeyShare<P>;
     type ProvableError = KeyGenError<P>;
     type CorrectnessProof = KeyGenProof<P>;
@@ -45,6 +48,20 @@ pub enum KeyGenProof<P: SchemeParams> {
     KeyRefresh(<KeyRefreshResult<P> as ProtocolResult>::CorrectnessProof),
 }
 
+impl<P: SchemeParams> ProvableErrorWrapper<KeyInitResult> for KeyGenResult<P> {
+    fn wrap_error(error: <KeyInitResult as ProtocolResult>::ProvableError) -> Self::ProvableError {
+        KeyGenError::KeyInit(error)
+    }
+}
+
+impl<P: SchemeParams> ProvableErrorWrapper<KeyRefreshResult<P>> for KeyGenResult<P> {
+    fn wrap_error(
+        error: <KeyRefreshResult<P> as ProtocolResult>::ProvableError,
+    ) -> Self::ProvableError {
+        KeyGenError::KeyRefresh(error)
+    }
+}
+
 impl<P: SchemeParams> CorrectnessProofWrapper<KeyInitResult> for KeyGenResult<P> {
     fn wrap_proof(
         proof: <KeyInitResult as ProtocolResult>::CorrectnessProof,
@@ -77,8 +94,13 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
     ) -> Result<Self, InitError> {
         let key_init_round =
             key_init::Round1::new(rng, shared_randomness, num_parties, party_idx, ())?;
-        let key_refresh_round =
-            key_refresh::Round1::new(rng, shared_randomness, num_parties, party_idx, ())?;
+        let key_refresh_round = key_refresh::Round1::new(
+            rng,
+            shared_randomness,
+            num_parties,
+            party_idx,
+            key_refresh::Inputs::default(),
+        )?;
         Ok(Self {
             key_init_round,
             key_refresh_round,
@@ -375,6 +397,23 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
             .key_refresh_round
             .finalize_to_result(rng, key_refresh_payloads, artifacts)
             .map_err(wrap_finalize_error)?;
-        Ok(KeyShare::new(keyshare_seed, keyshare_change))
+        KeyShare::new(keyshare_seed, keyshare_change)
+            .map_err(|err| FinalizeError::Init(InitError(err.to_string())))
     }
 }
+
+/// Verifies the KeyInit part of the broadcasts of all the parties (in the order of their indices)
+/// made during an execution of the protocol, and returns their public shares.
+///
+/// The KeyRefresh part is not verified, since it does not affect the resulting verifying key,
+/// and some of its checks require the direct messages.
+pub(crate) fn verify_broadcasts<P: SchemeParams>(
+    shared_randomness: &[u8],
+    round1: Vec<<Round1<P> as Round>::BroadcastMessage>,
+    round2: Vec<<Round2<P> as Round>::BroadcastMessage>,
+    round3: Vec<<Round3<P> as Round>::BroadcastMessage>,
+) -> Result<Vec<Point>, (PartyIdx, KeyInitError)> {
+    let round1 = round1.into_iter().map(|(msg, _)| msg).collect::<Vec<_>>();
+    let round2 = round2.into_iter().map(|(msg, _)| msg).collect::<Vec<_>>();
+    key_init::verify_broadcasts::<P>(shared_randomness, &round1, &round2, &round3)
+}
@@ -0,0 +1,587 @@
+//! Key import protocol: one of the parties (the dealer) holding an existing secret key
+//! splits it into shares and distributes them to the other parties.
+//!
+//! This is not a part of the CGGMP'21 paper. The dealer broadcasts the public shares
+//! along with a proof of knowledge of the key they sum up to,
+//! and sends each party its secret share encrypted with a Diffie-Hellman key
+//! derived from the ephemeral keys broadcasted in the first round.
+//! Each party checks that the received share matches the corresponding public share.
+//!
+//! The dealer knows all the resulting secret shares, so the result must be refreshed
+//! (by running the KeyRefresh protocol) before being used.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::cggmp21::{
+    sigma::{SchCommitment, SchProof, SchSecret},
+    SchemeParams,
+};
+use crate::common::KeyShareSeed;
+use crate::curve::{Point, Scalar};
+use crate::rounds::{
+    all_parties_except, no_broadcast_messages, no_direct_messages, try_to_holevec,
+    FinalizableToNextRound, FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx,
+    ProtocolResult, Round, ToNextRound, ToResult,
+};
+use crate::tools::bitvec::BitVec;
+use crate::tools::collections::HoleVec;
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hash, HashInto, HashOutput, XofHash};
+use crate::tools::serialized_size::{seq_size, MaxSerializedSize};
+
+/// Possible results of the KeyImport protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyImportResult<P: SchemeParams>(PhantomData<P>);
+
+impl<P: SchemeParams> ProtocolResult for KeyImportResult<P> {
+    const NAME: &'static str = "KeyImport";
+    type Success = KeyShareSeed;
+    type ProvableError = KeyImportError;
+    type CorrectnessProof = ();
+}
+
+/// Possible verifiable errors of the KeyImport protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyImportError {
+    /// The dealing in Round 1 is missing, malformed, is for a key other than the expected one,
+    /// or was sent by a party other than the dealer.
+    R1InvalidDealing,
+    /// Failed to verify `П^{sch}` for the imported key in Round 1.
+    R1InvalidSchProof,
+    /// The share sent in Round 2 is missing or does not match the public share.
+    R2InvalidShare,
+}
+
+#[derive(Clone)]
+pub struct Inputs {
+    pub dealer: PartyIdx,
+    pub secret_key: Option<Scalar>,
+    /// The public counterpart of the imported key, which the non-dealers must know in advance.
+    pub expected_verifying_key: Option<Point>,
+}
+
+/// The public part of the dealing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Dealing {
+    public_shares: Vec<Point>,
+    cap_t: SchCommitment,
+    psi: SchProof,
+}
+
+impl MaxSerializedSize for Dealing {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        seq_size::<Point>(num_parties, num_parties)
+            + SchCommitment::max_serialized_size(num_parties)
+            + SchProof::max_serialized_size(num_parties)
+    }
+}
+
+fn make_sid_hash<P: SchemeParams>(shared_randomness: &[u8], num_parties: usize) -> HashOutput {
+    Hash::new_with_dst(b"SID")
+        .chain_type::<P>()
+        .chain(&shared_randomness)
+        .chain(&(u32::try_from(num_parties).unwrap()))
+        .finalize()
+}
+
+/// Derives the value masking the share of `recipient` from the shared Diffie-Hellman point.
+fn share_mask(sid_hash: &HashOutput, recipient: PartyIdx, shared_point: &Point) -> Scalar {
+    let mut reader = XofHash::new_with_dst(b"KeyImportShare")
+        .chain(sid_hash)
+        .chain(&recipient)
+        .chain(shared_point)
+        .finalize_to_reader();
+    Scalar::from_reader(&mut reader)
+}
+
+struct Context {
+    num_parties: usize,
+    party_idx: PartyIdx,
+    dealer: PartyIdx,
+    expected_verifying_key: Option<Point>,
+    // The ephemeral Diffie-Hellman key
+    a: Scalar,
+    cap_a: Point,
+    rid: BitVec,
+    // The shares of all the parties (only known to the dealer)
+    shares: Option<Vec<Scalar>>,
+    dealing: Option<Dealing>,
+    sid_hash: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.a.zeroize();
+        if let Some(shares) = self.shares.as_mut() {
+            shares.zeroize();
+        }
+    }
+}
+
+pub struct Round1<P: SchemeParams> {
+    context: Context,
+    phantom: PhantomData<P>,
+}
+
+impl<P: SchemeParams> FirstRound for Round1<P> {
+    type Inputs = Inputs;
+
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        if inputs.dealer.as_usize() >= num_parties {
+            return Err(InitError("The dealer index is out of range".into()));
+        }
+
+        let sid_hash = make_sid_hash::<P>(shared_randomness, num_parties);
+
+        let (shares, dealing) = match (party_idx == inputs.dealer, inputs.secret_key) {
+            (true, Some(secret_key)) => {
+                if secret_key == Scalar::ZERO {
+                    return Err(InitError("The imported key is zero".into()));
+                }
+                if inputs
+                    .expected_verifying_key
+                    .is_some_and(|key| key != secret_key.mul_by_generator())
+                {
+                    return Err(InitError(
+                        "The secret key does not match the expected verifying key".into(),
+                    ));
+                }
+
+                let shares = secret_key.split(rng, num_parties);
+                let public_shares = shares
+                    .iter()
+                    .map(|share| share.mul_by_generator())
+                    .collect::<Vec<_>>();
+
+                let tau = SchSecret::random(rng);
+                let cap_t = SchCommitment::new(&tau);
+                let aux = (&sid_hash, &inputs.dealer, &public_shares);
+                let psi = SchProof::new::<P>(
+                    &tau,
+                    &secret_key,
+                    &cap_t,
+                    &secret_key.mul_by_generator(),
+                    &aux,
+                );
+
+                let dealing = Dealing {
+                    public_shares,
+                    cap_t,
+                    psi,
+                };
+                (Some(shares), Some(dealing))
+            }
+            (false, None) => {
+                if inputs.expected_verifying_key.is_none() {
+                    return Err(InitError(
+                        "The parties other than the dealer must provide the expected verifying key"
+                            .into(),
+                    ));
+                }
+                (None, None)
+            }
+            (true, None) => return Err(InitError("The dealer must provide the secret key".into())),
+            (false, Some(_)) => {
+                return Err(InitError(
+                    "Only the dealer can provide the secret key".into(),
+                ))
+            }
+        };
+
+        let a = Scalar::random_nonzero(rng);
+        let cap_a = a.mul_by_generator();
+        let rid = BitVec::random(rng, P::SECURITY_PARAMETER);
+
+        Ok(Self {
+            context: Context {
+                num_parties,
+                party_idx,
+                dealer: inputs.dealer,
+                expected_verifying_key: inputs.expected_verifying_key,
+                a,
+                cap_a,
+                rid,
+                shares,
+                dealing,
+                sid_hash,
+            },
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round1Message<P: SchemeParams> {
+    cap_a: Point,
+    rid: BitVec,
+    dealing: Option<Dealing>,
+    phantom: PhantomData<P>,
+}
+
+impl<P: SchemeParams> MaxSerializedSize for Round1Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties)
+            + BitVec::serialized_size(P::SECURITY_PARAMETER)
+            // The tag of the `Option`
+            + 1
+            + Dealing::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round1Payload {
+    cap_a: Point,
+    rid: BitVec,
+    dealing: Option<Dealing>,
+}
+
+impl<P: SchemeParams> Round for Round1<P> {
+    type Type = ToNextRound;
+    type Result = KeyImportResult<P>;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = Some(2);
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    // All the parties must receive the same public shares.
+    const REQUIRES_ECHO: bool = true;
+    type BroadcastMessage = Round1Message<P>;
+    type DirectMessage = ();
+    type Payload = Round1Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round1Message {
+            cap_a: self.context.cap_a,
+            rid: self.context.rid.clone(),
+            dealing: self.context.dealing.clone(),
+            phantom: PhantomData,
+        })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        if broadcast_msg.cap_a == Point::IDENTITY {
+            return Err(KeyImportError::R1InvalidDealing);
+        }
+
+        match (from == self.context.dealer, broadcast_msg.dealing.as_ref()) {
+            (true, Some(dealing)) => {
+                if dealing.public_shares.len() != self.num_parties() {
+                    return Err(KeyImportError::R1InvalidDealing);
+                }
+                let cap_y: Point = dealing.public_shares.iter().sum();
+                if cap_y == Point::IDENTITY
+                    || self
+                        .context
+                        .expected_verifying_key
+                        .is_some_and(|key| key != cap_y)
+                {
+                    return Err(KeyImportError::R1InvalidDealing);
+                }
+                let aux = (&self.context.sid_hash, &from, &dealing.public_shares);
+                if !dealing.psi.verify::<P>(&dealing.cap_t, &cap_y, &aux) {
+                    return Err(KeyImportError::R1InvalidSchProof);
+                }
+            }
+            (false, None) => {}
+            _ => return Err(KeyImportError::R1InvalidDealing),
+        }
+
+        Ok(Round1Payload {
+            cap_a: broadcast_msg.cap_a,
+            rid: broadcast_msg.rid,
+            dealing: broadcast_msg.dealing,
+        })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
+    type NextRound = Round2<P>;
+    fn finalize_to_next_round(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+
+        let mut rid = self.context.rid.clone();
+        for payload in payloads.iter() {
+            rid ^= &payload.rid;
+        }
+
+        let mut context = self.context;
+        let dealing = match context.dealing.take() {
+            Some(dealing) => dealing,
+            // The presence of the dealing was checked in `verify_message()`.
+            None => payloads
+                .get(context.dealer.as_usize())
+                .unwrap()
+                .dealing
+                .clone()
+                .unwrap(),
+        };
+
+        Ok(Round2 {
+            others_cap_a: payloads.map(|payload| payload.cap_a),
+            public_shares: dealing.public_shares,
+            rid,
+            context,
+            phantom: PhantomData,
+        })
+    }
+}
+
+pub struct Round2<P: SchemeParams> {
+    context: Context,
+    others_cap_a: HoleVec<Point>,
+    public_shares: Vec<Point>,
+    rid: BitVec,
+    phantom: PhantomData<P>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round2Message {
+    encrypted_share: Option<Scalar>,
+}
+
+impl MaxSerializedSize for Round2Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        // The tag of the `Option`
+        1 + Scalar::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round2Payload {
+    share: Option<Scalar>,
+}
+
+impl<P: SchemeParams> Round for Round2<P> {
+    type Type = ToResult;
+    type Result = KeyImportResult<P>;
+    const ROUND_NUM: u8 = 2;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = ();
+    type DirectMessage = Round2Message;
+    type Payload = Round2Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    no_broadcast_messages!();
+
+    fn make_direct_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+        destination: PartyIdx,
+    ) -> (Self::DirectMessage, Self::Artifact) {
+        // Only the dealer sends the shares; the others send empty messages
+        // so that everyone can finalize the round in the same way.
+        let encrypted_share = self.context.shares.as_ref().map(|shares| {
+            let cap_a = self.others_cap_a.get(destination.as_usize()).unwrap();
+            let mask = share_mask(
+                &self.context.sid_hash,
+                destination,
+                &(cap_a * &self.context.a),
+            );
+            shares[destination.as_usize()] + mask
+        });
+        (Round2Message { encrypted_share }, ())
+    }
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        _broadcast_msg: Self::BroadcastMessage,
+        direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let share = match (from == self.context.dealer, direct_msg.encrypted_share) {
+            (true, Some(encrypted_share)) => {
+                let cap_a = self.others_cap_a.get(from.as_usize()).unwrap();
+                let mask = share_mask(
+                    &self.context.sid_hash,
+                    self.party_idx(),
+                    &(cap_a * &self.context.a),
+                );
+                let share = encrypted_share - mask;
+                if share.mul_by_generator() != self.public_shares[self.party_idx().as_usize()] {
+                    return Err(KeyImportError::R2InvalidShare);
+                }
+                Some(share)
+            }
+            (false, None) => None,
+            _ => return Err(KeyImportError::R2InvalidShare),
+        };
+
+        Ok(Round2Payload { share })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round2<P> {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let secret_share = match self.context.shares.as_ref() {
+            Some(shares) => shares[self.party_idx().as_usize()],
+            // The presence of the share was checked in `verify_message()`.
+            None => payloads.get(&self.context.dealer).unwrap().share.unwrap(),
+        };
+
+        Ok(KeyShareSeed {
+            secret_share,
+            public_shares: self.public_shares.into(),
+            init_id: self.rid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{OsRng, RngCore};
+
+    use super::{Inputs, Round1};
+    use crate::cggmp21::TestParams;
+    use crate::curve::{Point, Scalar, SigningKey};
+    use crate::rounds::{
+        test_utils::{step_next_round, step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+
+    #[test]
+    fn execute_key_import() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let signing_key = SigningKey::random(&mut OsRng);
+        let secret_key = Scalar::from(signing_key.as_nonzero_scalar());
+
+        let num_parties = 3;
+        let dealer = PartyIdx::from_usize(1);
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                let party_idx = PartyIdx::from_usize(idx);
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    party_idx,
+                    Inputs {
+                        dealer,
+                        secret_key: (party_idx == dealer).then_some(secret_key),
+                        expected_verifying_key: Some(secret_key.mul_by_generator()),
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let seeds = step_result(&mut OsRng, r2a).unwrap();
+
+        // All the parties agree on the public shares
+        assert!(seeds[1..]
+            .iter()
+            .all(|seed| seed.public_shares == seeds[0].public_shares));
+
+        // The secret shares match the public ones and sum up to the imported key
+        for (idx, seed) in seeds.iter().enumerate() {
+            assert_eq!(
+                seed.secret_share.mul_by_generator(),
+                seed.public_shares[idx]
+            );
+        }
+        let public_key: Point = seeds[0].public_shares.iter().sum();
+        assert_eq!(public_key, secret_key.mul_by_generator());
+        let secret_sum: Scalar = seeds.iter().map(|seed| seed.secret_share).sum();
+        assert_eq!(secret_sum, secret_key);
+    }
+
+    #[test]
+    fn unexpected_key_is_rejected() {
+        let secret_key = Scalar::random_nonzero(&mut OsRng);
+        let expected_key = Scalar::random_nonzero(&mut OsRng).mul_by_generator();
+
+        let num_parties = 3;
+        let dealer = PartyIdx::from_usize(0);
+        let make_round = |party_idx: PartyIdx, expected_verifying_key| {
+            Round1::<TestParams>::new(
+                &mut OsRng,
+                b"1234567890",
+                num_parties,
+                party_idx,
+                Inputs {
+                    dealer,
+                    secret_key: (party_idx == dealer).then_some(secret_key),
+                    expected_verifying_key,
+                },
+            )
+        };
+
+        // The non-dealers must know which key is imported.
+        assert!(make_round(PartyIdx::from_usize(1), None).is_err());
+        // The dealer cannot deal a key other than the expected one.
+        assert!(make_round(dealer, Some(expected_key)).is_err());
+
+        // The other parties expect a different key than the one the dealer deals.
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                let party_idx = PartyIdx::from_usize(idx);
+                let expected_key = if party_idx == dealer {
+                    None
+                } else {
+                    Some(expected_key)
+                };
+                make_round(party_idx, expected_key).unwrap()
+            })
+            .collect();
+        assert!(step_round(&mut OsRng, r1).is_err());
+    }
+}
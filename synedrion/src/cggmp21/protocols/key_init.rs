@@ -8,9 +8,11 @@ use core::marker::PhantomData;
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use crate::cggmp21::{
-    sigma::{SchCommitment, SchProof, SchSecret},
+    sigma::{SchCommitment, SchEquation, SchProof, SchSecret},
     SchemeParams,
 };
 use crate::common::KeyShareSeed;
@@ -23,12 +25,14 @@ use crate::rounds::{
 use crate::tools::bitvec::BitVec;
 use crate::tools::collections::HoleVec;
 use crate::tools::hashing::{Chain, Hash, HashOutput, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 
 /// Possible results of the KeyGen protocol.
 #[derive(Debug, Clone, Copy)]
 pub struct KeyInitResult;
 
 impl ProtocolResult for KeyInitResult {
+    const NAME: &'static str = "KeyInit";
     type Success = KeyShareSeed;
     type ProvableError = KeyInitError;
     type CorrectnessProof = ();
@@ -72,6 +76,14 @@ impl<P: SchemeParams> PublicData<P> {
     }
 }
 
+fn make_sid_hash<P: SchemeParams>(shared_randomness: &[u8], num_parties: usize) -> HashOutput {
+    Hash::new_with_dst(b"SID")
+        .chain_type::<P>()
+        .chain(&shared_randomness)
+        .chain(&(u32::try_from(num_parties).unwrap()))
+        .finalize()
+}
+
 struct Context<P: SchemeParams> {
     num_parties: usize,
     party_idx: PartyIdx,
@@ -81,6 +93,14 @@ struct Context<P: SchemeParams> {
     sid_hash: HashOutput,
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for Context<P> {
+    fn drop(&mut self) {
+        self.x.zeroize();
+        self.tau.zeroize();
+    }
+}
+
 pub struct Round1<P: SchemeParams> {
     context: Context<P>,
 }
@@ -95,11 +115,7 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
         party_idx: PartyIdx,
         _inputs: Self::Inputs,
     ) -> Result<Self, InitError> {
-        let sid_hash = Hash::new_with_dst(b"SID")
-            .chain_type::<P>()
-            .chain(&shared_randomness)
-            .chain(&(u32::try_from(num_parties).unwrap()))
-            .finalize();
+        let sid_hash = make_sid_hash::<P>(shared_randomness, num_parties);
 
         // The secret share
         let x = Scalar::random(rng);
@@ -137,6 +153,12 @@ pub struct Round1Message {
     cap_v: HashOutput,
 }
 
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        HashOutput::max_serialized_size(num_parties)
+    }
+}
+
 pub struct Round1Payload {
     cap_v: HashOutput,
 }
@@ -221,6 +243,14 @@ pub struct Round2Message<P: SchemeParams> {
     data: PublicData<P>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round2Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties)
+            + SchCommitment::max_serialized_size(num_parties)
+            + 2 * BitVec::serialized_size(P::SECURITY_PARAMETER)
+    }
+}
+
 pub struct Round2Payload<P: SchemeParams> {
     data: PublicData<P>,
 }
@@ -315,6 +345,12 @@ pub struct Round3Message {
     psi: SchProof,
 }
 
+impl MaxSerializedSize for Round3Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        SchProof::max_serialized_size(num_parties)
+    }
+}
+
 impl<P: SchemeParams> Round for Round3<P> {
     type Type = ToResult;
     type Result = KeyInitResult;
@@ -331,7 +367,8 @@ impl<P: SchemeParams> Round for Round3<P> {
 
     type BroadcastMessage = Round3Message;
     type DirectMessage = ();
-    type Payload = ();
+    // The equation of the Schnorr proof, if it is left to be checked on finalization.
+    type Payload = Option<SchEquation>;
     type Artifact = ();
 
     fn message_destinations(&self) -> Vec<PartyIdx> {
@@ -343,7 +380,7 @@ impl<P: SchemeParams> Round for Round3<P> {
         _rng: &mut impl CryptoRngCore,
     ) -> Option<Self::BroadcastMessage> {
         let aux = (&self.context.sid_hash, &self.party_idx(), &self.rid);
-        let psi = SchProof::new(
+        let psi = SchProof::new::<P>(
             &self.context.tau,
             &self.context.x,
             &self.context.public_data.cap_a,
@@ -364,21 +401,39 @@ impl<P: SchemeParams> Round for Round3<P> {
         let data = self.others_data.get(from.as_usize()).unwrap();
 
         let aux = (&self.context.sid_hash, &from, &self.rid);
-        if !broadcast_msg.psi.verify(&data.cap_a, &data.cap_x, &aux) {
+        let equation = broadcast_msg
+            .psi
+            .verify_challenge::<P>(&data.cap_a, &data.cap_x, &aux)
+            .ok_or(KeyInitError::R3InvalidSchProof)?;
+
+        // With `batch-verify`, the equations of all the proofs are checked at once on finalization.
+        if cfg!(feature = "batch-verify") {
+            return Ok(Some(equation));
+        }
+        if !equation.verify() {
             return Err(KeyInitError::R3InvalidSchProof);
         }
-        Ok(())
+        Ok(None)
     }
 }
 
 impl<P: SchemeParams> FinalizableToResult for Round3<P> {
     fn finalize_to_result(
         self,
-        _rng: &mut impl CryptoRngCore,
-        _payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
         _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
     ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
-        let all_data = self.others_data.into_vec(self.context.public_data);
+        let equations = payloads
+            .into_iter()
+            .filter_map(|(idx, equation)| equation.map(|equation| (idx, equation)))
+            .collect::<Vec<_>>();
+        SchEquation::verify_batch(rng, &equations).map_err(|party| FinalizeError::Provable {
+            party,
+            error: KeyInitError::R3InvalidSchProof,
+        })?;
+
+        let all_data = self.others_data.into_vec(self.context.public_data.clone());
         let all_cap_x = all_data.into_iter().map(|data| data.cap_x).collect();
         Ok(KeyShareSeed {
             secret_share: self.context.x,
@@ -388,6 +443,48 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
     }
 }
 
+/// Verifies the broadcasts of all the parties (in the order of their indices)
+/// made during an execution of the protocol, and returns their public shares.
+///
+/// This is the same set of checks the parties perform during the execution,
+/// so it can be done by a third party that only observed the broadcasts.
+pub(crate) fn verify_broadcasts<P: SchemeParams>(
+    shared_randomness: &[u8],
+    round1: &[Round1Message],
+    round2: &[Round2Message<P>],
+    round3: &[Round3Message],
+) -> Result<Vec<Point>, (PartyIdx, KeyInitError)> {
+    let num_parties = round1.len();
+    assert!(round2.len() == num_parties && round3.len() == num_parties);
+
+    let sid_hash = make_sid_hash::<P>(shared_randomness, num_parties);
+
+    for (idx, (msg1, msg2)) in round1.iter().zip(round2.iter()).enumerate() {
+        let idx = PartyIdx::from_usize(idx);
+        if msg2.data.hash(&sid_hash, idx) != msg1.cap_v {
+            return Err((idx, KeyInitError::R2HashMismatch));
+        }
+    }
+
+    let mut rid = round2[0].data.rid.clone();
+    for msg2 in round2.iter().skip(1) {
+        rid ^= &msg2.data.rid;
+    }
+
+    for (idx, (msg2, msg3)) in round2.iter().zip(round3.iter()).enumerate() {
+        let idx = PartyIdx::from_usize(idx);
+        let aux = (&sid_hash, &idx, &rid);
+        if !msg3
+            .psi
+            .verify::<P>(&msg2.data.cap_a, &msg2.data.cap_x, &aux)
+        {
+            return Err((idx, KeyInitError::R3InvalidSchProof));
+        }
+    }
+
+    Ok(round2.iter().map(|msg2| msg2.data.cap_x).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use rand_core::{OsRng, RngCore};
@@ -443,5 +540,11 @@ mod tests {
             .collect();
 
         assert!(public_set == &public_from_secret);
+
+        // The verifying key is available before the auxiliary data is generated
+        let verifying_key = shares[0].verifying_key().unwrap();
+        assert!(shares
+            .iter()
+            .all(|s| s.verifying_key() == Ok(verifying_key) && s.num_parties() == num_parties));
     }
 }
@@ -5,14 +5,17 @@
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use crate::cggmp21::{
-    sigma::{FacProof, ModProof, PrmProof, SchCommitment, SchProof, SchSecret},
+    sigma::{FacProof, ModProof, PrmProof, SchCommitment, SchEquation, SchProof, SchSecret},
     SchemeParams,
 };
 use crate::common::{KeyShareChange, PublicAuxInfo, SecretAuxInfo};
@@ -29,6 +32,7 @@ use crate::rounds::{
 use crate::tools::bitvec::BitVec;
 use crate::tools::collections::HoleVec;
 use crate::tools::hashing::{Chain, Hash, HashOutput, Hashable};
+use crate::tools::serialized_size::{seq_size, MaxSerializedSize};
 use crate::uint::UintLike;
 
 /// Possible results of the KeyRefresh protocol.
@@ -36,11 +40,67 @@ use crate::uint::UintLike;
 pub struct KeyRefreshResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for KeyRefreshResult<P> {
+    const NAME: &'static str = "KeyRefresh";
     type Success = KeyShareChange<P>;
     type ProvableError = KeyRefreshError<P>;
     type CorrectnessProof = ();
 }
 
+/// What the KeyRefresh protocol updates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum KeyRefreshMode {
+    /// Re-randomizes the secret shares and replaces the auxiliary data.
+    #[default]
+    Full,
+    /// Only replaces the auxiliary data (the Paillier and El-Gamal keys
+    /// and the ring-Pedersen parameters), leaving the secret shares unchanged.
+    ///
+    /// The share changes are still exchanged, so that the messages are the same as in a full refresh,
+    /// but they are not included in the result.
+    AuxOnly,
+    /// Only re-randomizes the secret shares, with every party reusing its existing Paillier key,
+    /// which is checked by the others.
+    ///
+    /// This skips the generation of the Paillier primes, which takes most of the time of a full refresh.
+    /// The ring-Pedersen parameters and the El-Gamal keys are still replaced.
+    SharesOnly,
+}
+
+impl KeyRefreshMode {
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Full => 0,
+            Self::AuxOnly => 1,
+            Self::SharesOnly => 2,
+        }
+    }
+}
+
+/// The Paillier key and (optionally) the ring-Pedersen trapdoor generated in advance.
+pub(crate) type PaillierKeyInputs<P> = (
+    SecretKeyPaillier<<P as SchemeParams>::Paillier>,
+    Option<RPSecret<<P as SchemeParams>::Paillier>>,
+);
+
+/// The inputs of the KeyRefresh protocol.
+pub(crate) struct Inputs<P: SchemeParams> {
+    pub(crate) mode: KeyRefreshMode,
+    /// Generated in the first round if not given.
+    pub(crate) paillier_key: Option<PaillierKeyInputs<P>>,
+    /// The Paillier public keys the parties must reuse in [`KeyRefreshMode::SharesOnly`].
+    pub(crate) expected_paillier_pks: Option<Box<[PublicKeyPaillier<P::Paillier>]>>,
+}
+
+impl<P: SchemeParams> Default for Inputs<P> {
+    fn default() -> Self {
+        Self {
+            mode: KeyRefreshMode::Full,
+            paillier_key: None,
+            expected_paillier_pks: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct KeyRefreshError<P: SchemeParams>(KeyRefreshErrorEnum<P>);
 
@@ -59,6 +119,10 @@ enum KeyRefreshErrorEnum<P: SchemeParams> {
         x: Scalar,
         mu: Randomizer<P::Paillier>,
     },
+    // The ciphertext shares a factor with the Paillier modulus, which anyone can check.
+    // TODO (#43): this can be removed when error verification is added
+    #[allow(dead_code)]
+    Round3InvalidCiphertext { cap_c: Ciphertext<P::Paillier> },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +157,18 @@ struct Context<P: SchemeParams> {
     party_idx: PartyIdx,
     num_parties: usize,
     sid_hash: HashOutput,
+    mode: KeyRefreshMode,
+    expected_paillier_pks: Option<Box<[PublicKeyPaillier<P::Paillier>]>>,
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for Context<P> {
+    fn drop(&mut self) {
+        self.y.zeroize();
+        self.x_to_send.zeroize();
+        self.tau_y.zeroize();
+        self.tau_x.zeroize();
+    }
 }
 
 impl<P: SchemeParams> Hashable for PublicData1<P> {
@@ -124,23 +200,39 @@ pub struct Round1<P: SchemeParams> {
     context: Context<P>,
 }
 
+/// Checks that a proof of the modulus of `paillier_sk` being a Paillier-Blum modulus
+/// ($\Pi^{mod}$, as sent to the other parties in Round 3) can be created and verified.
+pub(crate) fn mod_self_check<P: SchemeParams>(
+    rng: &mut impl CryptoRngCore,
+    paillier_sk: &SecretKeyPaillierPrecomputed<P::Paillier>,
+) -> bool {
+    let aux = b"ModSelfCheck";
+    ModProof::<P>::new(rng, paillier_sk, aux).verify(paillier_sk.public_key(), aux)
+}
+
 impl<P: SchemeParams> FirstRound for Round1<P> {
-    type Inputs = ();
+    type Inputs = Inputs<P>;
     fn new(
         rng: &mut impl CryptoRngCore,
         shared_randomness: &[u8],
         num_parties: usize,
         party_idx: PartyIdx,
-        _inputs: Self::Inputs,
+        inputs: Self::Inputs,
     ) -> Result<Self, InitError> {
+        let () = P::BOUNDS_CHECK;
         let sid_hash = Hash::new_with_dst(b"SID")
             .chain_type::<P>()
             .chain(&shared_randomness)
             .chain(&(u32::try_from(num_parties).unwrap()))
+            .chain(&inputs.mode.as_u8())
             .finalize();
 
+        let (paillier_sk, lambda) = match inputs.paillier_key {
+            Some((paillier_sk, lambda)) => (paillier_sk, lambda),
+            None => (SecretKeyPaillier::<P::Paillier>::random(rng), None),
+        };
         // $p_i$, $q_i$
-        let paillier_sk = SecretKeyPaillier::<P::Paillier>::random(rng).to_precomputed();
+        let paillier_sk = paillier_sk.to_precomputed();
         // $N_i$
         let paillier_pk = paillier_sk.public_key();
 
@@ -161,7 +253,7 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
             .map(|x| x.mul_by_generator())
             .collect::<Vec<_>>();
 
-        let lambda = RPSecret::random(rng, &paillier_sk);
+        let lambda = lambda.unwrap_or_else(|| RPSecret::random(rng, &paillier_sk));
         // Ring-Pedersen parameters ($s$, $t$) bundled in a single object.
         let rp_params = RPParamsMod::random_with_secret(rng, &lambda, paillier_pk);
 
@@ -205,6 +297,8 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
             party_idx,
             num_parties,
             sid_hash,
+            mode: inputs.mode,
+            expected_paillier_pks: inputs.expected_paillier_pks,
         };
 
         Ok(Self { context })
@@ -216,6 +310,12 @@ pub struct Round1Message {
     cap_v: HashOutput,
 }
 
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        HashOutput::max_serialized_size(num_parties)
+    }
+}
+
 pub struct Round1Payload {
     cap_v: HashOutput,
 }
@@ -301,6 +401,19 @@ pub struct Round2Message<P: SchemeParams> {
     data: PublicData1<P>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round2Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        seq_size::<Point>(num_parties, num_parties)
+            + seq_size::<SchCommitment>(num_parties, num_parties)
+            + Point::max_serialized_size(num_parties)
+            + SchCommitment::max_serialized_size(num_parties)
+            + PublicKeyPaillier::<P::Paillier>::max_serialized_size(num_parties)
+            + RPParams::<P::Paillier>::max_serialized_size(num_parties)
+            + PrmProof::<P>::max_serialized_size(num_parties)
+            + 2 * BitVec::serialized_size(P::SECURITY_PARAMETER)
+    }
+}
+
 pub struct Round2Payload<P: SchemeParams> {
     data: PublicData1Precomp<P>,
 }
@@ -353,6 +466,14 @@ impl<P: SchemeParams> Round for Round2<P> {
             )));
         }
 
+        if let Some(expected_paillier_pks) = &self.context.expected_paillier_pks {
+            if expected_paillier_pks.get(from.as_usize()) != Some(&broadcast_msg.data.paillier_pk) {
+                return Err(KeyRefreshError(KeyRefreshErrorEnum::Round2(
+                    "Paillier key is not reused".into(),
+                )));
+            }
+        }
+
         let paillier_pk = broadcast_msg.data.paillier_pk.to_precomputed();
 
         if paillier_pk.modulus().bits_vartime() < 8 * P::SECURITY_PARAMETER {
@@ -439,7 +560,7 @@ impl<P: SchemeParams> Round3<P> {
         let aux = (&context.sid_hash, &context.party_idx, &rho);
         let psi_mod = ModProof::new(rng, &context.paillier_sk, &aux);
 
-        let pi = SchProof::new(
+        let pi = SchProof::new::<P>(
             &context.tau_y,
             &context.y,
             &context.data_precomp.data.cap_b,
@@ -464,8 +585,19 @@ pub struct Round3Message<P: SchemeParams> {
     data2: PublicData2<P>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round3Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        ModProof::<P>::max_serialized_size(num_parties)
+            + FacProof::<P>::max_serialized_size(num_parties)
+            + 2 * SchProof::max_serialized_size(num_parties)
+            + Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 pub struct Round3Payload {
     x: Scalar, // $x_j^i$, a secret share change received from the party $j$
+    // The equations of the Schnorr proofs, if they are left to be checked on finalization.
+    sch_equations: Vec<SchEquation>,
 }
 
 impl<P: SchemeParams> Round for Round3<P> {
@@ -515,7 +647,7 @@ impl<P: SchemeParams> Round for Round3<P> {
         let ciphertext =
             CiphertextMod::new(rng, &data.paillier_pk, &P::uint_from_scalar(&x_secret));
 
-        let psi_sch = SchProof::new(
+        let psi_sch = SchProof::new::<P>(
             &self.context.tau_x[idx],
             &x_secret,
             &self.context.data_precomp.data.cap_a_to_send[idx],
@@ -547,7 +679,12 @@ impl<P: SchemeParams> Round for Round3<P> {
             .paillier_enc_x
             .to_mod(self.context.paillier_sk.public_key());
 
-        let x = P::scalar_from_uint(&enc_x.decrypt(&self.context.paillier_sk));
+        let x = enc_x.decrypt(&self.context.paillier_sk).ok_or_else(|| {
+            KeyRefreshError(KeyRefreshErrorEnum::Round3InvalidCiphertext {
+                cap_c: direct_msg.data2.paillier_enc_x.clone(),
+            })
+        })?;
+        let x = P::scalar_from_uint(&x);
 
         if x.mul_by_generator() != sender_data.data.cap_x_to_send[self.context.party_idx.as_usize()]
         {
@@ -583,56 +720,104 @@ impl<P: SchemeParams> Round for Round3<P> {
             )));
         }
 
-        if !direct_msg
+        let pi_equation = direct_msg
             .data2
             .pi
-            .verify(&sender_data.data.cap_b, &sender_data.data.cap_y, &aux)
-        {
+            .verify_challenge::<P>(&sender_data.data.cap_b, &sender_data.data.cap_y, &aux)
+            .ok_or(KeyRefreshError(KeyRefreshErrorEnum::Round3(
+                "Sch proof verification (Y) failed".into(),
+            )))?;
+
+        let psi_sch_equation = direct_msg
+            .data2
+            .psi_sch
+            .verify_challenge::<P>(
+                &sender_data.data.cap_a_to_send[self.context.party_idx.as_usize()],
+                &sender_data.data.cap_x_to_send[self.context.party_idx.as_usize()],
+                &aux,
+            )
+            .ok_or(KeyRefreshError(KeyRefreshErrorEnum::Round3(
+                "Sch proof verification (X) failed".into(),
+            )))?;
+
+        // With `batch-verify`, the equations of all the proofs are checked at once on finalization.
+        if cfg!(feature = "batch-verify") {
+            return Ok(Round3Payload {
+                x,
+                sch_equations: [pi_equation, psi_sch_equation].into(),
+            });
+        }
+
+        if !pi_equation.verify() {
             return Err(KeyRefreshError(KeyRefreshErrorEnum::Round3(
                 "Sch proof verification (Y) failed".into(),
             )));
         }
-
-        if !direct_msg.data2.psi_sch.verify(
-            &sender_data.data.cap_a_to_send[self.context.party_idx.as_usize()],
-            &sender_data.data.cap_x_to_send[self.context.party_idx.as_usize()],
-            &aux,
-        ) {
+        if !psi_sch_equation.verify() {
             return Err(KeyRefreshError(KeyRefreshErrorEnum::Round3(
                 "Sch proof verification (X) failed".into(),
             )));
         }
 
-        Ok(Round3Payload { x })
+        Ok(Round3Payload {
+            x,
+            sch_equations: Vec::new(),
+        })
     }
 }
 
 impl<P: SchemeParams> FinalizableToResult for Round3<P> {
     fn finalize_to_result(
         self,
-        _rng: &mut impl CryptoRngCore,
+        rng: &mut impl CryptoRngCore,
         payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
         _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
     ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let equations = payloads
+            .iter()
+            .flat_map(|(idx, payload)| {
+                payload
+                    .sch_equations
+                    .iter()
+                    .map(|equation| (*idx, equation.clone()))
+            })
+            .collect::<Vec<_>>();
+        SchEquation::verify_batch(rng, &equations).map_err(|party| FinalizeError::Provable {
+            party,
+            error: KeyRefreshError(KeyRefreshErrorEnum::Round3(
+                "Sch proof verification failed".into(),
+            )),
+        })?;
+
         let others_x = try_to_holevec(payloads, self.num_parties(), self.party_idx())
             .unwrap()
             .map(|payload| payload.x);
 
-        // The combined secret share change
-        let x_star = others_x.iter().sum::<Scalar>()
-            + self.context.x_to_send[self.context.party_idx.as_usize()];
-
-        let all_data = self.others_data.into_vec(self.context.data_precomp);
-
-        // The combined public share changes for each node
-        let cap_x_star = (0..all_data.len())
-            .map(|idx| {
-                all_data
-                    .iter()
-                    .map(|data| data.data.cap_x_to_send[idx])
-                    .sum()
-            })
-            .collect::<Box<_>>();
+        let all_data = self.others_data.into_vec(self.context.data_precomp.clone());
+
+        let (x_star, cap_x_star) = match self.context.mode {
+            KeyRefreshMode::Full | KeyRefreshMode::SharesOnly => {
+                // The combined secret share change
+                let x_star = others_x.iter().sum::<Scalar>()
+                    + self.context.x_to_send[self.context.party_idx.as_usize()];
+
+                // The combined public share changes for each node
+                let cap_x_star = (0..all_data.len())
+                    .map(|idx| {
+                        all_data
+                            .iter()
+                            .map(|data| data.data.cap_x_to_send[idx])
+                            .sum()
+                    })
+                    .collect::<Box<_>>();
+
+                (x_star, cap_x_star)
+            }
+            KeyRefreshMode::AuxOnly => (
+                Scalar::ZERO,
+                vec![Point::IDENTITY; all_data.len()].into_boxed_slice(),
+            ),
+        };
 
         let public_aux = all_data
             .into_iter()
@@ -663,15 +848,20 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
 #[cfg(test)]
 mod tests {
 
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+
     use rand_core::{OsRng, RngCore};
 
-    use super::Round1;
-    use crate::cggmp21::TestParams;
-    use crate::curve::Scalar;
+    use super::{Inputs, KeyRefreshMode, Round1};
+    use crate::cggmp21::{SchemeParams, TestParams};
+    use crate::curve::{Point, Scalar};
+    use crate::paillier::{PaillierParams, PublicKeyPaillier, RPSecret, SecretKeyPaillier};
     use crate::rounds::{
         test_utils::{step_next_round, step_result, step_round},
         FirstRound, PartyIdx,
     };
+    use crate::uint::Bounded;
 
     #[test]
     fn execute_auxiliary() {
@@ -686,7 +876,7 @@ mod tests {
                     &shared_randomness,
                     num_parties,
                     PartyIdx::from_usize(idx),
-                    (),
+                    Inputs::default(),
                 )
                 .unwrap()
             })
@@ -721,4 +911,167 @@ mod tests {
             .sum();
         assert_eq!(mask_sum, Scalar::ZERO);
     }
+
+    #[test]
+    fn execute_auxiliary_with_pregenerated_secrets() {
+        type Paillier = <TestParams as SchemeParams>::Paillier;
+        type Uint = <Paillier as PaillierParams>::Uint;
+
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let paillier_sk = SecretKeyPaillier::<Paillier>::random(&mut OsRng);
+        let paillier_pk = paillier_sk.to_precomputed().public_key().to_minimal();
+        let lambda = Bounded::new(Uint::from_u64(12345), Paillier::MODULUS_BITS as u32).unwrap();
+        let mut paillier_key = Some((paillier_sk, Some(RPSecret::new(lambda))));
+
+        let num_parties = 3;
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        mode: KeyRefreshMode::Full,
+                        paillier_key: paillier_key.take(),
+                        expected_paillier_pks: None,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let changes = step_result(&mut OsRng, r3a).unwrap();
+
+        // The first party uses the supplied Paillier key
+        for change in changes.iter() {
+            assert_eq!(
+                change.public_aux[0].paillier_pk.modulus(),
+                paillier_pk.modulus()
+            );
+        }
+    }
+
+    #[test]
+    fn execute_auxiliary_only() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let num_parties = 3;
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        mode: KeyRefreshMode::AuxOnly,
+                        paillier_key: None,
+                        expected_paillier_pks: None,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let changes = step_result(&mut OsRng, r3a).unwrap();
+
+        // The shares are left as they are, but the auxiliary data is new.
+        for (idx, change) in changes.iter().enumerate() {
+            assert_eq!(change.secret_share_change, Scalar::ZERO);
+            assert!(change
+                .public_share_changes
+                .iter()
+                .all(|point| point == &Point::IDENTITY));
+            for other_change in changes.iter() {
+                assert_eq!(
+                    change.secret_aux.el_gamal_sk.mul_by_generator(),
+                    other_change.public_aux[idx].el_gamal_pk
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn execute_shares_only() {
+        type Paillier = <TestParams as SchemeParams>::Paillier;
+
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let num_parties = 3;
+        let paillier_sks = (0..num_parties)
+            .map(|_| SecretKeyPaillier::<Paillier>::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let paillier_pks = paillier_sks
+            .iter()
+            .map(|sk| sk.to_precomputed().public_key().to_minimal())
+            .collect::<Box<_>>();
+
+        let make_rounds = |expected_paillier_pks: &[PublicKeyPaillier<Paillier>]| {
+            paillier_sks
+                .iter()
+                .enumerate()
+                .map(|(idx, paillier_sk)| {
+                    Round1::<TestParams>::new(
+                        &mut OsRng,
+                        &shared_randomness,
+                        num_parties,
+                        PartyIdx::from_usize(idx),
+                        Inputs {
+                            mode: KeyRefreshMode::SharesOnly,
+                            paillier_key: Some((paillier_sk.clone(), None)),
+                            expected_paillier_pks: Some(expected_paillier_pks.into()),
+                        },
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let r1 = make_rounds(&paillier_pks);
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let changes = step_result(&mut OsRng, r3a).unwrap();
+
+        // The shares are re-randomized, and the Paillier keys are kept.
+        let mask_sum: Scalar = changes
+            .iter()
+            .map(|change| change.secret_share_change)
+            .sum();
+        assert_eq!(mask_sum, Scalar::ZERO);
+        for change in changes.iter() {
+            assert_ne!(change.secret_share_change, Scalar::ZERO);
+            for (aux, paillier_pk) in change.public_aux.iter().zip(paillier_pks.iter()) {
+                assert_eq!(&aux.paillier_pk, paillier_pk);
+            }
+        }
+
+        // A party that does not reuse its key is rejected.
+        let mut other_pks = paillier_pks.clone();
+        other_pks[0] = SecretKeyPaillier::<Paillier>::random(&mut OsRng)
+            .to_precomputed()
+            .public_key()
+            .to_minimal();
+        let r1 = make_rounds(&other_pks);
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        assert!(step_round(&mut OsRng, r2).is_err());
+    }
 }
@@ -1,5 +1,8 @@
 //! Presigning protocol, in the paper ECDSA Pre-Signing (Fig. 7).
 
+pub(crate) mod identifiable;
+
+use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -7,9 +10,14 @@ use core::marker::PhantomData;
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
+use crate::adaptor::{AdaptorNonceShare, AdaptorPresigningData};
+#[cfg(feature = "testing")]
+use crate::audit::{self, NonceAuditRecord};
 use crate::cggmp21::{
-    sigma::{AffGProof, DecProof, EncProof, LogStarProof, MulProof},
+    sigma::{AffGProof, DecProof, EncProof, EncProofCommitment, LogStarProof, MulProof},
     SchemeParams,
 };
 use crate::common::{KeyShare, KeySharePrecomputed, PresigningData};
@@ -23,6 +31,8 @@ use crate::rounds::{
 use crate::tools::{
     collections::{HoleRange, HoleVec},
     hashing::{Chain, Hash, HashOutput},
+    parallel::map_with_rng,
+    serialized_size::MaxSerializedSize,
 };
 use crate::uint::Signed;
 
@@ -31,6 +41,7 @@ use crate::uint::Signed;
 pub struct PresigningResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for PresigningResult<P> {
+    const NAME: &'static str = "Presigning";
     type Success = PresigningData<P>;
     type ProvableError = PresigningError;
     type CorrectnessProof = PresigningProof<P>;
@@ -45,6 +56,155 @@ pub enum PresigningError {
     Round2(String),
     /// An error in Round 3.
     Round3(String),
+    /// An error in Round 4 (only in [`PresigningMode::IdentifiableAbort`]).
+    Round4(String),
+}
+
+/// The variant of the Presigning protocol to run.
+///
+/// All the parties of a session must use the same mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PresigningMode {
+    /// The three-round protocol of the paper.
+    ///
+    /// If the assembled values turn out to be inconsistent, the session fails
+    /// with a proof of this party's correct behavior, and the culprit is not identified.
+    #[default]
+    Fast,
+    /// The protocol with identifiable abort.
+    ///
+    /// The values sent in Round 3 are echo-broadcast along with the ciphertexts sent in Round 2,
+    /// and in an additional Round 4, if the assembled values are inconsistent,
+    /// each party proves to the others that its share of them was computed correctly.
+    /// A party that cannot do so is reported as the culprit.
+    ///
+    /// This takes two more message exchanges than [`PresigningMode::Fast`] even if no one misbehaves.
+    IdentifiableAbort,
+}
+
+/// The inputs of the Presigning protocol.
+pub(crate) struct Inputs<P: SchemeParams> {
+    pub(crate) key_share: KeyShare<P>,
+    /// The adaptor point $T$, if the nonce is created for an adaptor signature
+    /// (see [`crate::adaptor`]).
+    pub(crate) adaptor_point: Option<Point>,
+    /// The key to encrypt the audit record of this party's nonce contributions to
+    /// (see [`crate::audit`]).
+    #[cfg(feature = "testing")]
+    pub(crate) auditor_key: Option<Point>,
+    /// The values of the first round computed in advance.
+    pub(crate) precompute: Option<PresigningPrecompute<P>>,
+}
+
+impl<P: SchemeParams> Inputs<P> {
+    pub(crate) fn new(key_share: KeyShare<P>) -> Self {
+        Self {
+            key_share,
+            adaptor_point: None,
+            #[cfg(feature = "testing")]
+            auditor_key: None,
+            precompute: None,
+        }
+    }
+
+    pub(crate) fn with_precompute(precompute: PresigningPrecompute<P>) -> Self {
+        let key_share = precompute.key_share.clone();
+        Self {
+            precompute: Some(precompute),
+            ..Self::new(key_share)
+        }
+    }
+}
+
+/// The values of the first round of the Presigning protocol
+/// that only depend on the key share, computed ahead of a session
+/// (see [`make_presigning_session_with_precompute`](crate::make_presigning_session_with_precompute)
+/// and [`make_interactive_signing_session_with_precompute`](crate::make_interactive_signing_session_with_precompute)).
+///
+/// This includes the shares of the nonce and its mask, their Paillier encryptions,
+/// and the commitments of the $\Pi^{enc}$ proofs for each of the other parties,
+/// which take most of the time of the first round.
+/// The work of the later rounds depends on the messages of the other parties
+/// and cannot be done in advance.
+///
+/// The values are secret, and must only be used in a single session.
+pub struct PresigningPrecompute<P: SchemeParams> {
+    key_share: KeyShare<P>,
+    key_share_precomputed: KeySharePrecomputed<P>,
+    k: Scalar,
+    gamma: Scalar,
+    rho: RandomizerMod<P::Paillier>,
+    nu: RandomizerMod<P::Paillier>,
+    cap_k: CiphertextMod<P::Paillier>,
+    cap_g: CiphertextMod<P::Paillier>,
+    enc_commitments: BTreeMap<PartyIdx, EncProofCommitment<P>>,
+}
+
+impl<P: SchemeParams> PresigningPrecompute<P> {
+    /// Computes the values for a session with the given key share.
+    pub fn new(rng: &mut impl CryptoRngCore, key_share: &KeyShare<P>) -> Self {
+        let key_share_precomputed = key_share.to_precomputed();
+
+        // The share of an ephemeral scalar
+        let k = Scalar::random(rng);
+        // The share of the mask used to generate the inverse of the ephemeral scalar
+        let gamma = Scalar::random(rng);
+
+        let pk = key_share_precomputed.secret_aux.paillier_sk.public_key();
+
+        let nu = RandomizerMod::<P::Paillier>::random(rng, pk);
+        let cap_g =
+            CiphertextMod::new_with_randomizer(pk, &P::uint_from_scalar(&gamma), &nu.retrieve());
+
+        let rho = RandomizerMod::<P::Paillier>::random(rng, pk);
+        let cap_k =
+            CiphertextMod::new_with_randomizer(pk, &P::uint_from_scalar(&k), &rho.retrieve());
+
+        let k_signed = P::signed_from_scalar(&k);
+        let enc_commitments = all_parties_except(
+            key_share_precomputed.num_parties(),
+            key_share_precomputed.party_index(),
+        )
+        .into_iter()
+        .map(|idx| {
+            let setup = &key_share_precomputed.public_aux[idx.as_usize()].rp_params;
+            (idx, EncProofCommitment::new(rng, &k_signed, pk, setup))
+        })
+        .collect();
+
+        Self {
+            key_share: key_share.clone(),
+            key_share_precomputed,
+            k,
+            gamma,
+            rho,
+            nu,
+            cap_k,
+            cap_g,
+            enc_commitments,
+        }
+    }
+
+    pub(crate) fn key_share(&self) -> &KeyShare<P> {
+        &self.key_share
+    }
+}
+
+// A custom Debug impl that skips the secret values
+impl<P: SchemeParams> core::fmt::Debug for PresigningPrecompute<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "PresigningPrecompute {{ <...> }}",)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for PresigningPrecompute<P> {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.gamma.zeroize();
+        self.rho.zeroize();
+        self.nu.zeroize();
+    }
 }
 
 struct Context<P: SchemeParams> {
@@ -54,16 +214,32 @@ struct Context<P: SchemeParams> {
     gamma: Scalar,
     rho: RandomizerMod<P::Paillier>,
     nu: RandomizerMod<P::Paillier>,
+    /// The adaptor point $T$, if the nonce is created for an adaptor signature.
+    adaptor_point: Option<Point>,
+    /// $k_i$ and $\gamma_i$ encrypted to the auditor key, in the audit mode.
+    #[cfg(feature = "testing")]
+    audit_ciphertext: Option<Box<[u8]>>,
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for Context<P> {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.gamma.zeroize();
+        self.rho.zeroize();
+        self.nu.zeroize();
+    }
 }
 
 pub struct Round1<P: SchemeParams> {
     context: Context<P>,
     cap_k: CiphertextMod<P::Paillier>,
     cap_g: CiphertextMod<P::Paillier>,
+    enc_commitments: BTreeMap<PartyIdx, EncProofCommitment<P>>,
 }
 
 impl<P: SchemeParams> FirstRound for Round1<P> {
-    type Inputs = KeyShare<P>;
+    type Inputs = Inputs<P>;
     fn new(
         rng: &mut impl CryptoRngCore,
         shared_randomness: &[u8],
@@ -71,7 +247,11 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
         _party_idx: PartyIdx,
         inputs: Self::Inputs,
     ) -> Result<Self, InitError> {
-        let key_share = inputs.to_precomputed();
+        let mut precompute = match inputs.precompute {
+            Some(precompute) => precompute,
+            None => PresigningPrecompute::new(rng, &inputs.key_share),
+        };
+        let key_share = precompute.key_share_precomputed.clone();
 
         // This includes the info of $ssid$ in the paper
         // (scheme parameters + public data from all shares - hashed in `share_set_id`),
@@ -83,32 +263,33 @@ impl<P: SchemeParams> FirstRound for Round1<P> {
 
         // TODO (#68): check that KeyShare is consistent with num_parties/party_idx
 
-        // The share of an ephemeral scalar
-        let k = Scalar::random(rng);
-        // The share of the mask used to generate the inverse of the ephemeral scalar
-        let gamma = Scalar::random(rng);
-
-        let pk = key_share.secret_aux.paillier_sk.public_key();
-
-        let nu = RandomizerMod::<P::Paillier>::random(rng, pk);
-        let cap_g =
-            CiphertextMod::new_with_randomizer(pk, &P::uint_from_scalar(&gamma), &nu.retrieve());
-
-        let rho = RandomizerMod::<P::Paillier>::random(rng, pk);
-        let cap_k =
-            CiphertextMod::new_with_randomizer(pk, &P::uint_from_scalar(&k), &rho.retrieve());
+        #[cfg(feature = "testing")]
+        let audit_ciphertext = inputs.auditor_key.map(|auditor_key| {
+            audit::encrypt_contributions(
+                rng,
+                &auditor_key,
+                &ssid_hash,
+                key_share.party_index(),
+                &precompute.k,
+                &precompute.gamma,
+            )
+        });
 
         Ok(Self {
             context: Context {
                 ssid_hash,
                 key_share,
-                k,
-                gamma,
-                rho,
-                nu,
+                k: precompute.k,
+                gamma: precompute.gamma,
+                rho: precompute.rho.clone(),
+                nu: precompute.nu.clone(),
+                adaptor_point: inputs.adaptor_point,
+                #[cfg(feature = "testing")]
+                audit_ciphertext,
             },
-            cap_k,
-            cap_g,
+            cap_k: precompute.cap_k.clone(),
+            cap_g: precompute.cap_g.clone(),
+            enc_commitments: core::mem::take(&mut precompute.enc_commitments),
         })
     }
 }
@@ -121,6 +302,12 @@ pub struct Round1BroadcastMessage<P: SchemeParams> {
     cap_g: Ciphertext<P::Paillier>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round1BroadcastMessage<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        2 * Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound(serialize = "EncProof<P>: Serialize"))]
 #[serde(bound(deserialize = "EncProof<P>: for<'x> Deserialize<'x>"))]
@@ -128,6 +315,12 @@ pub struct Round1DirectMessage<P: SchemeParams> {
     psi0: EncProof<P>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round1DirectMessage<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        EncProof::<P>::max_serialized_size(num_parties)
+    }
+}
+
 pub struct Round1Payload<P: SchemeParams> {
     cap_k: Ciphertext<P::Paillier>,
     cap_g: Ciphertext<P::Paillier>,
@@ -172,12 +365,12 @@ impl<P: SchemeParams> Round for Round1<P> {
 
     fn make_direct_message(
         &self,
-        rng: &mut impl CryptoRngCore,
+        _rng: &mut impl CryptoRngCore,
         destination: PartyIdx,
     ) -> (Self::DirectMessage, Self::Artifact) {
         let aux = (&self.context.ssid_hash, &destination);
-        let psi0 = EncProof::new(
-            rng,
+        let psi0 = EncProof::from_commitment(
+            &self.enc_commitments[&destination],
             &P::signed_from_scalar(&self.context.k),
             &self.context.rho,
             self.context.key_share.secret_aux.paillier_sk.public_key(),
@@ -272,6 +465,15 @@ pub struct Round2Message<P: SchemeParams> {
     hat_psi_prime: LogStarProof<P>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for Round2Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties)
+            + 4 * Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + 2 * AffGProof::<P>::max_serialized_size(num_parties)
+            + LogStarProof::<P>::max_serialized_size(num_parties)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Round2Artifact<P: SchemeParams> {
     beta: Signed<<P::Paillier as PaillierParams>::Uint>, // TODO (#77): secret
@@ -291,6 +493,7 @@ pub struct Round2Payload<P: SchemeParams> {
     alpha: Signed<<P::Paillier as PaillierParams>::Uint>,
     hat_alpha: Signed<<P::Paillier as PaillierParams>::Uint>,
     cap_d: CiphertextMod<P::Paillier>,
+    cap_f: CiphertextMod<P::Paillier>,
     hat_cap_d: CiphertextMod<P::Paillier>,
 }
 
@@ -445,6 +648,7 @@ impl<P: SchemeParams> Round for Round2<P> {
         let rp = &public_aux.rp_params;
 
         let cap_d = direct_msg.cap_d.to_mod(pk);
+        let cap_f = direct_msg.cap_f.to_mod(from_pk);
         let hat_cap_d = direct_msg.hat_cap_d.to_mod(pk);
 
         if !direct_msg.psi.verify(
@@ -452,7 +656,7 @@ impl<P: SchemeParams> Round for Round2<P> {
             from_pk,
             &self.all_cap_k[self.context.key_share.party_index().as_usize()],
             &cap_d,
-            &direct_msg.cap_f.to_mod(from_pk),
+            &cap_f,
             &direct_msg.cap_gamma,
             rp,
             &aux,
@@ -490,8 +694,12 @@ impl<P: SchemeParams> Round for Round2<P> {
             ));
         }
 
-        let alpha = cap_d.decrypt_signed(&self.context.key_share.secret_aux.paillier_sk);
-        let hat_alpha = hat_cap_d.decrypt_signed(&self.context.key_share.secret_aux.paillier_sk);
+        let alpha = cap_d
+            .decrypt_signed(&self.context.key_share.secret_aux.paillier_sk)
+            .ok_or_else(|| PresigningError::Round2("Invalid ciphertext (D)".into()))?;
+        let hat_alpha = hat_cap_d
+            .decrypt_signed(&self.context.key_share.secret_aux.paillier_sk)
+            .ok_or_else(|| PresigningError::Round2("Invalid ciphertext (hat D)".into()))?;
 
         // `alpha == x * y + z` where `0 <= x, y < q`, and `-2^l' <= z <= 2^l'`,
         // where `q` is the curve order.
@@ -508,6 +716,7 @@ impl<P: SchemeParams> Round for Round2<P> {
             alpha,
             hat_alpha,
             cap_d,
+            cap_f,
             hat_cap_d,
         })
     }
@@ -517,7 +726,7 @@ impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
     type NextRound = Round3<P>;
     fn finalize_to_next_round(
         self,
-        _rng: &mut impl CryptoRngCore,
+        rng: &mut impl CryptoRngCore,
         payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
         artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
     ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
@@ -546,20 +755,34 @@ impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
             + hat_alpha_sum
             + hat_beta_sum;
 
+        // The public counterpart of `chi` (`S_i` in the paper), used to verify the signature share.
+        let cap_s = cap_gamma * P::scalar_from_signed(&chi);
+
+        let cap_gammas = payloads.map_ref(|payload| payload.cap_gamma);
         let cap_ds = payloads.map_ref(|payload| payload.cap_d.clone());
+        let cap_fs = payloads.map_ref(|payload| payload.cap_f.clone());
         let hat_cap_d = payloads.map_ref(|payload| payload.hat_cap_d.clone());
 
+        let adaptor_share = self
+            .context
+            .adaptor_point
+            .map(|adaptor_point| AdaptorNonceShare::new(rng, &self.context.gamma, &adaptor_point));
+
         Ok(Round3 {
             context: self.context,
             delta,
             chi,
             cap_delta,
             cap_gamma,
+            cap_s,
             all_cap_k: self.all_cap_k,
             all_cap_g: self.all_cap_g,
+            cap_gammas,
             cap_ds,
+            cap_fs,
             hat_cap_d,
             round2_artifacts: artifacts,
+            adaptor_share,
         })
     }
 }
@@ -570,11 +793,15 @@ pub struct Round3<P: SchemeParams> {
     chi: Signed<<P::Paillier as PaillierParams>::Uint>,
     cap_delta: Point,
     cap_gamma: Point,
+    cap_s: Point,
     all_cap_k: Vec<CiphertextMod<P::Paillier>>,
     all_cap_g: Vec<CiphertextMod<P::Paillier>>,
+    cap_gammas: HoleVec<Point>,
     cap_ds: HoleVec<CiphertextMod<P::Paillier>>,
+    cap_fs: HoleVec<CiphertextMod<P::Paillier>>,
     hat_cap_d: HoleVec<CiphertextMod<P::Paillier>>,
     round2_artifacts: HoleVec<Round2Artifact<P>>,
+    adaptor_share: Option<AdaptorNonceShare>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -583,12 +810,27 @@ pub struct Round3<P: SchemeParams> {
 pub struct Round3Message<P: SchemeParams> {
     delta: Scalar,
     cap_delta: Point,
+    cap_s: Point,
     psi_pprime: LogStarProof<P>,
+    adaptor_share: Option<AdaptorNonceShare>,
+}
+
+impl<P: SchemeParams> MaxSerializedSize for Round3Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+            + 2 * Point::max_serialized_size(num_parties)
+            + LogStarProof::<P>::max_serialized_size(num_parties)
+            // The tag of the `Option`
+            + 1
+            + AdaptorNonceShare::max_serialized_size(num_parties)
+    }
 }
 
 pub struct Round3Payload {
     delta: Scalar,
     cap_delta: Point,
+    cap_s: Point,
+    adaptor_share: Option<AdaptorNonceShare>,
 }
 
 impl<P: SchemeParams> Round for Round3<P> {
@@ -648,7 +890,9 @@ impl<P: SchemeParams> Round for Round3<P> {
         let message = Round3Message {
             delta: P::scalar_from_signed(&self.delta),
             cap_delta: self.cap_delta,
+            cap_s: self.cap_s,
             psi_pprime,
+            adaptor_share: self.adaptor_share.clone(),
         };
 
         (message, ())
@@ -679,35 +923,49 @@ impl<P: SchemeParams> Round for Round3<P> {
                 "Failed to verify Log-Star proof".into(),
             ));
         }
+
+        match (&self.context.adaptor_point, &direct_msg.adaptor_share) {
+            (Some(adaptor_point), Some(share)) => {
+                if !share.verify(adaptor_point, self.cap_gammas.get(from.as_usize()).unwrap()) {
+                    return Err(PresigningError::Round3(
+                        "Failed to verify the adaptor nonce share".into(),
+                    ));
+                }
+            }
+            (None, None) => {}
+            _ => {
+                return Err(PresigningError::Round3(
+                    "The adaptor nonce share is missing or unexpected".into(),
+                ))
+            }
+        }
+
         Ok(Round3Payload {
             delta: direct_msg.delta,
             cap_delta: direct_msg.cap_delta,
+            cap_s: direct_msg.cap_s,
+            adaptor_share: direct_msg.adaptor_share,
         })
     }
 }
 
 /// A proof of a node's correct behavior for the Presigning protocol.
-#[allow(dead_code)] // TODO (#43): this can be removed when error verification is added
 #[derive(Debug, Clone)]
 pub struct PresigningProof<P: SchemeParams> {
     aff_g_proofs: Vec<(PartyIdx, PartyIdx, AffGProof<P>)>,
+    cap_h: CiphertextMod<P::Paillier>,
     mul_proof: MulProof<P>,
     dec_proofs: Vec<(PartyIdx, DecProof<P>)>,
 }
 
-impl<P: SchemeParams> FinalizableToResult for Round3<P> {
-    fn finalize_to_result(
-        self,
-        rng: &mut impl CryptoRngCore,
-        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
-        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
-    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
-        let payloads = try_to_holevec(
-            payloads,
-            self.context.key_share.num_parties(),
-            self.context.key_share.party_index(),
-        )
-        .unwrap();
+impl<P: SchemeParams> Round3<P> {
+    /// Assembles the presigning data from the values received in this round.
+    ///
+    /// Returns `None` if the assembled `delta` does not match the assembled `Delta`,
+    /// that is, if some party misbehaved.
+    fn assemble(&self, payloads: HoleVec<Round3Payload>) -> Option<PresigningData<P>> {
+        let cap_ss = payloads.map_ref(|payload| payload.cap_s);
+        let adaptor_shares = payloads.map_ref(|payload| payload.adaptor_share.clone());
         let (deltas, cap_deltas) = payloads
             .map(|payload| (payload.delta, payload.cap_delta))
             .unzip();
@@ -716,42 +974,91 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
         let assembled_delta: Scalar = scalar_delta + deltas.iter().sum::<Scalar>();
         let assembled_cap_delta: Point = self.cap_delta + cap_deltas.iter().sum::<Point>();
 
-        if assembled_delta.mul_by_generator() == assembled_cap_delta {
-            let nonce = (self.cap_gamma * assembled_delta.invert().unwrap()).x_coordinate();
-
-            let hat_beta = self.round2_artifacts.map_ref(|artifact| artifact.hat_beta);
-            let hat_r = self
-                .round2_artifacts
-                .map_ref(|artifact| artifact.hat_r.clone());
-            let hat_s = self
-                .round2_artifacts
-                .map_ref(|artifact| artifact.hat_s.clone());
-            let hat_cap_d = self
-                .round2_artifacts
-                .map_ref(|artifact| artifact.hat_cap_d.clone());
-            let hat_cap_f = self
-                .round2_artifacts
-                .map_ref(|artifact| artifact.hat_cap_f.clone());
-
-            return Ok(PresigningData {
-                nonce,
-                ephemeral_scalar_share: self.context.k,
-                product_share: P::scalar_from_signed(&self.chi),
-
-                product_share_nonreduced: self.chi,
-                hat_beta,
-                hat_r,
-                hat_s,
-                cap_k: self.all_cap_k.into_boxed_slice(),
-                hat_cap_d_received: self.hat_cap_d,
-                hat_cap_d,
-                hat_cap_f,
-            });
+        if assembled_delta.mul_by_generator() != assembled_cap_delta {
+            return None;
         }
 
-        let my_idx = self.context.key_share.party_index().as_usize();
+        let delta_inv = assembled_delta.invert().unwrap();
+
+        // With an adaptor point $T$ the nonce point is $\delta^{-1} \sum_j \gamma_j T$
+        // instead of $\delta^{-1} \Gamma$ (the shares were checked on arrival).
+        let (nonce, adaptor) = match (&self.context.adaptor_point, &self.adaptor_share) {
+            (Some(adaptor_point), Some(own_share)) => {
+                let shares = adaptor_shares
+                    .into_vec(Some(own_share.clone()))
+                    .into_iter()
+                    .collect::<Option<Box<[_]>>>()?;
+                let cap_a = shares.iter().map(|share| *share.cap_a()).sum::<Point>();
+                let adaptor = AdaptorPresigningData {
+                    adaptor_point: *adaptor_point,
+                    delta: assembled_delta,
+                    shares,
+                };
+                ((cap_a * delta_inv).x_coordinate(), Some(adaptor))
+            }
+            _ => ((self.cap_gamma * delta_inv).x_coordinate(), None),
+        };
 
-        // Construct the correctness proofs
+        #[cfg(feature = "testing")]
+        let audit_record =
+            self.context
+                .audit_ciphertext
+                .as_ref()
+                .map(|ciphertext| NonceAuditRecord {
+                    party_idx: self.context.key_share.party_index(),
+                    ssid_hash: self.context.ssid_hash,
+                    cap_gammas: self
+                        .cap_gammas
+                        .clone()
+                        .into_vec(self.context.gamma.mul_by_generator())
+                        .into_boxed_slice(),
+                    cap_deltas: cap_deltas
+                        .clone()
+                        .into_vec(self.cap_delta)
+                        .into_boxed_slice(),
+                    nonce_point: self.cap_gamma * delta_inv,
+                    encrypted_contributions: ciphertext.clone(),
+                });
+
+        let hat_beta = self.round2_artifacts.map_ref(|artifact| artifact.hat_beta);
+        let hat_r = self
+            .round2_artifacts
+            .map_ref(|artifact| artifact.hat_r.clone());
+        let hat_s = self
+            .round2_artifacts
+            .map_ref(|artifact| artifact.hat_s.clone());
+        let hat_cap_d = self
+            .round2_artifacts
+            .map_ref(|artifact| artifact.hat_cap_d.clone());
+        let hat_cap_f = self
+            .round2_artifacts
+            .map_ref(|artifact| artifact.hat_cap_f.clone());
+
+        Some(PresigningData {
+            nonce,
+            cap_gamma: self.cap_gamma,
+            cap_deltas: cap_deltas.into_vec(self.cap_delta).into_boxed_slice(),
+            cap_ss: cap_ss.into_vec(self.cap_s).into_boxed_slice(),
+            ephemeral_scalar_share: self.context.k,
+            product_share: P::scalar_from_signed(&self.chi),
+
+            product_share_nonreduced: self.chi,
+            hat_beta,
+            hat_r,
+            hat_s,
+            cap_k: self.all_cap_k.clone().into_boxed_slice(),
+            hat_cap_d_received: self.hat_cap_d.clone(),
+            hat_cap_d,
+            hat_cap_f,
+            adaptor,
+            #[cfg(feature = "testing")]
+            audit_record,
+        })
+    }
+
+    /// Creates the proof that this party's share of `delta` was computed correctly.
+    fn correctness_proof(&self, rng: &mut impl CryptoRngCore) -> PresigningProof<P> {
+        let my_idx = self.context.key_share.party_index().as_usize();
 
         let sk = &self.context.key_share.secret_aux.paillier_sk;
         let pk = sk.public_key();
@@ -764,54 +1071,52 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
 
         // Aff-g proofs
 
-        let mut aff_g_proofs = Vec::new();
-
         let beta = self.round2_artifacts.map_ref(|artifact| artifact.beta);
         let r = self.round2_artifacts.map_ref(|artifact| artifact.r.clone());
         let s = self.round2_artifacts.map_ref(|artifact| artifact.s.clone());
 
         let cap_gamma = self.context.gamma.mul_by_generator();
 
-        for j in HoleRange::new(num_parties, my_idx) {
+        let range = HoleRange::new(num_parties, my_idx);
+        let index_pairs = range
+            .flat_map(|j| range.filter(move |l| *l != j).map(move |l| (j, l)))
+            .collect::<Vec<_>>();
+
+        // The proofs are independent, so they can be created in parallel.
+        let aff_g_proofs = map_with_rng(rng, index_pairs, |mut rng, (j, l)| {
             let r2_artefacts = self.round2_artifacts.get(j).unwrap();
+            let target_pk = &self.context.key_share.public_aux[j].paillier_pk;
+            let rp = &self.context.key_share.public_aux[l].rp_params;
+
+            let p_aff_g = AffGProof::<P>::new(
+                &mut rng,
+                &P::signed_from_scalar(&self.context.gamma),
+                beta.get(j).unwrap(),
+                &s.get(j).unwrap().to_mod(target_pk),
+                &r.get(j).unwrap().to_mod(pk),
+                target_pk,
+                pk,
+                &self.all_cap_k[j],
+                &r2_artefacts.cap_d,
+                &r2_artefacts.cap_f,
+                &cap_gamma,
+                rp,
+                &aux,
+            );
 
-            for l in HoleRange::new(num_parties, my_idx) {
-                if l == j {
-                    continue;
-                }
-                let target_pk = &self.context.key_share.public_aux[j].paillier_pk;
-                let rp = &self.context.key_share.public_aux[l].rp_params;
-
-                let p_aff_g = AffGProof::<P>::new(
-                    rng,
-                    &P::signed_from_scalar(&self.context.gamma),
-                    beta.get(j).unwrap(),
-                    &s.get(j).unwrap().to_mod(target_pk),
-                    &r.get(j).unwrap().to_mod(pk),
-                    target_pk,
-                    pk,
-                    &self.all_cap_k[j],
-                    &r2_artefacts.cap_d,
-                    &r2_artefacts.cap_f,
-                    &cap_gamma,
-                    rp,
-                    &aux,
-                );
-
-                assert!(p_aff_g.verify(
-                    target_pk,
-                    pk,
-                    &self.all_cap_k[j],
-                    &r2_artefacts.cap_d,
-                    &r2_artefacts.cap_f,
-                    &cap_gamma,
-                    rp,
-                    &aux,
-                ));
-
-                aff_g_proofs.push((PartyIdx::from_usize(j), PartyIdx::from_usize(l), p_aff_g));
-            }
-        }
+            assert!(p_aff_g.verify(
+                target_pk,
+                pk,
+                &self.all_cap_k[j],
+                &r2_artefacts.cap_d,
+                &r2_artefacts.cap_f,
+                &cap_gamma,
+                rp,
+                &aux,
+            ));
+
+            (PartyIdx::from_usize(j), PartyIdx::from_usize(l), p_aff_g)
+        });
 
         // Mul proof
 
@@ -838,9 +1143,7 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
             &aux
         ));
 
-        // Dec proof
-
-        let range = HoleRange::new(self.context.key_share.num_parties(), my_idx);
+        // Dec proofs
 
         let mut ciphertext = cap_h.clone();
 
@@ -851,11 +1154,11 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
         }
 
         let rho = ciphertext.derive_randomizer(sk);
+        let scalar_delta = P::scalar_from_signed(&self.delta);
 
-        let mut dec_proofs = Vec::new();
-        for j in range {
+        let dec_proofs = map_with_rng(rng, range.collect(), |mut rng, j| {
             let p_dec = DecProof::<P>::new(
-                rng,
+                &mut rng,
                 &self.delta,
                 &rho,
                 pk,
@@ -871,14 +1174,35 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
                 &self.context.key_share.public_aux[j].rp_params,
                 &aux
             ));
-            dec_proofs.push((PartyIdx::from_usize(j), p_dec));
-        }
+            (PartyIdx::from_usize(j), p_dec)
+        });
 
-        Err(FinalizeError::Proof(PresigningProof {
+        PresigningProof {
             aff_g_proofs,
-            dec_proofs,
+            cap_h,
             mul_proof: p_mul,
-        }))
+            dec_proofs,
+        }
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round3<P> {
+    fn finalize_to_result(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(
+            payloads,
+            self.context.key_share.num_parties(),
+            self.context.key_share.party_index(),
+        )
+        .unwrap();
+
+        // Construct the correctness proofs if the presigning data cannot be assembled.
+        self.assemble(payloads)
+            .ok_or_else(|| FinalizeError::Proof(self.correctness_proof(rng)))
     }
 }
 
@@ -886,13 +1210,15 @@ impl<P: SchemeParams> FinalizableToResult for Round3<P> {
 mod tests {
     use rand_core::{OsRng, RngCore};
 
-    use super::Round1;
+    use alloc::collections::BTreeMap;
+
+    use super::{Inputs, Round1};
     use crate::cggmp21::TestParams;
     use crate::common::KeyShare;
     use crate::curve::Scalar;
     use crate::rounds::{
         test_utils::{step_next_round, step_result, step_round},
-        FirstRound, PartyIdx,
+        FinalizableToResult, FinalizeError, FirstRound, PartyIdx, Round,
     };
 
     #[test]
@@ -909,7 +1235,7 @@ mod tests {
                     &shared_randomness,
                     num_parties,
                     PartyIdx::from_usize(idx),
-                    key_shares[idx].clone(),
+                    Inputs::new(key_shares[idx].clone()),
                 )
                 .unwrap()
             })
@@ -939,4 +1265,154 @@ mod tests {
             presigning_datas[0].nonce
         );
     }
+
+    #[test]
+    fn inconsistent_delta_produces_proof() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let num_parties = 4;
+        let key_shares = KeyShare::new_centralized(&mut OsRng, num_parties, None);
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs::new(key_shares[idx].clone()),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let mut r3 = step_next_round(&mut OsRng, r2a).unwrap();
+
+        // Deliver the Round 3 messages to the party 0, with one of the deltas changed,
+        // so that the consistency check at finalization fails.
+        let receiver = r3.remove(0);
+        let receiver_idx = PartyIdx::from_usize(0);
+        let mut payloads = BTreeMap::new();
+        for (idx, sender) in r3.iter().enumerate() {
+            let sender_idx = PartyIdx::from_usize(idx + 1);
+            let (direct, _artifact) = sender.make_direct_message(&mut OsRng, receiver_idx);
+            let mut payload = receiver.verify_message(sender_idx, (), direct).unwrap();
+            if idx == 0 {
+                payload.delta = payload.delta + Scalar::ONE;
+            }
+            payloads.insert(sender_idx, payload);
+        }
+        let artifacts = receiver
+            .message_destinations()
+            .into_iter()
+            .map(|idx| (idx, ()))
+            .collect();
+
+        let result = receiver.finalize_to_result(&mut OsRng, payloads, artifacts);
+        let proof = match result {
+            Err(FinalizeError::Proof(proof)) => proof,
+            _ => panic!("expected a correctness proof"),
+        };
+
+        // An aff-g proof for each pair of other parties, and a dec proof for each other party,
+        // in the order of the indices.
+        let others = num_parties - 1;
+        assert_eq!(proof.aff_g_proofs.len(), others * (others - 1));
+        assert_eq!(proof.dec_proofs.len(), others);
+        let aff_g_indices = proof
+            .aff_g_proofs
+            .iter()
+            .map(|(j, l, _)| (j.as_usize(), l.as_usize()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            aff_g_indices,
+            [(1, 2), (1, 3), (2, 1), (2, 3), (3, 1), (3, 2)]
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn audit_nonce_contributions() {
+        use crate::audit::{NonceAuditError, NonceAuditRecord};
+        use crate::curve::{Point, SigningKey};
+
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let auditor_key = SigningKey::random(&mut OsRng);
+        let num_parties = 3;
+        let key_shares = KeyShare::new_centralized(&mut OsRng, num_parties, None);
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        auditor_key: Some(Point::from_verifying_key(auditor_key.verifying_key())),
+                        ..Inputs::new(key_shares[idx].clone())
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let presigning_datas = step_result(&mut OsRng, r3a).unwrap();
+
+        let mut records = presigning_datas
+            .iter()
+            .map(|data| data.audit_record().unwrap().clone())
+            .collect::<Vec<_>>();
+        // The order of the records does not matter
+        records.reverse();
+        assert_eq!(NonceAuditRecord::audit(&records, &auditor_key), Ok(()));
+
+        assert_eq!(
+            NonceAuditRecord::audit(&records, &SigningKey::random(&mut OsRng)),
+            Err(NonceAuditError::CannotDecrypt(0))
+        );
+        assert_eq!(
+            NonceAuditRecord::audit(&records[1..], &auditor_key),
+            Err(NonceAuditError::WrongNumberOfRecords)
+        );
+
+        // A record disagreeing with the others on the nonce
+        let mut tampered = records.clone();
+        tampered[0].nonce_point = tampered[0].nonce_point + Point::GENERATOR;
+        assert_eq!(
+            NonceAuditRecord::audit(&tampered, &auditor_key),
+            Err(NonceAuditError::InconsistentRecord(2))
+        );
+
+        // All the records agree on the nonce, but it does not match the contributions
+        let mut tampered = records.clone();
+        for record in tampered.iter_mut() {
+            record.nonce_point = record.nonce_point + Point::GENERATOR;
+        }
+        assert_eq!(
+            NonceAuditRecord::audit(&tampered, &auditor_key),
+            Err(NonceAuditError::NonceMismatch)
+        );
+
+        // The contributions of a party do not match the public values
+        let mut tampered = records.clone();
+        for record in tampered.iter_mut() {
+            let mut cap_gammas = record.cap_gammas.to_vec();
+            cap_gammas.swap(0, 1);
+            record.cap_gammas = cap_gammas.into();
+        }
+        assert_eq!(
+            NonceAuditRecord::audit(&tampered, &auditor_key),
+            Err(NonceAuditError::ContributionMismatch(0))
+        );
+    }
 }
@@ -0,0 +1,601 @@
+//! Presigning protocol with identifiable abort (see [`PresigningMode::IdentifiableAbort`](super::PresigningMode::IdentifiableAbort)).
+//!
+//! The first two rounds are the same as in the regular protocol.
+//! In Round 3 the values used to assemble the presigning data are echo-broadcast
+//! along with the ciphertexts sent in Round 2, so that all the honest parties
+//! agree on whether the assembled values are consistent and on the statements to prove.
+//! If they are not, in Round 4 each party proves the correctness of its share of `delta`
+//! (as in the correctness proof of the regular protocol) to each of the other parties,
+//! and the party whose proof fails to verify is reported.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{Inputs, PresigningData, PresigningError, PresigningProof, PresigningResult};
+use crate::cggmp21::{
+    sigma::{AffGProof, DecProof, MulProof},
+    SchemeParams,
+};
+use crate::curve::{Point, Scalar};
+use crate::paillier::{Ciphertext, CiphertextMod};
+use crate::rounds::{
+    all_parties_except, no_broadcast_messages, try_to_holevec, FinalizableToNextRound,
+    FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult,
+    ProvableErrorWrapper, Round, RoundWrapper, ToNextRound, ToResult,
+};
+use crate::tools::{
+    collections::{HoleRange, HoleVec, HoleVecAccum},
+    serialized_size::{seq_size, MaxSerializedSize},
+};
+
+impl<P: SchemeParams> ProvableErrorWrapper<PresigningResult<P>> for PresigningResult<P> {
+    fn wrap_error(error: PresigningError) -> Self::ProvableError {
+        error
+    }
+}
+
+/// Puts the values sent by the party `hole_at` to each of the other parties into a [`HoleVec`].
+fn to_holevec<T>(values: Vec<T>, num_parties: usize, hole_at: usize) -> Option<HoleVec<T>> {
+    if values.len() + 1 != num_parties {
+        return None;
+    }
+    let mut accum = HoleVecAccum::new(num_parties, hole_at);
+    for (idx, value) in HoleRange::new(num_parties, hole_at).zip(values) {
+        accum.insert(idx, value)?;
+    }
+    accum.finalize()
+}
+
+pub(crate) struct Round1<P: SchemeParams> {
+    round: super::Round1<P>,
+}
+
+impl<P: SchemeParams> FirstRound for Round1<P> {
+    type Inputs = Inputs<P>;
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        let round = super::Round1::new(rng, shared_randomness, num_parties, party_idx, inputs)?;
+        Ok(Self { round })
+    }
+}
+
+impl<P: SchemeParams> RoundWrapper for Round1<P> {
+    type Type = ToNextRound;
+    type Result = PresigningResult<P>;
+    type InnerRound = super::Round1<P>;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = Some(2);
+    fn inner_round(&self) -> &Self::InnerRound {
+        &self.round
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
+    type NextRound = Round2<P>;
+    fn finalize_to_next_round(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let round = self
+            .round
+            .finalize_to_next_round(rng, payloads, artifacts)?;
+        Ok(Round2 { round })
+    }
+}
+
+pub(crate) struct Round2<P: SchemeParams> {
+    round: super::Round2<P>,
+}
+
+impl<P: SchemeParams> RoundWrapper for Round2<P> {
+    type Type = ToNextRound;
+    type Result = PresigningResult<P>;
+    type InnerRound = super::Round2<P>;
+    const ROUND_NUM: u8 = 2;
+    const NEXT_ROUND_NUM: Option<u8> = Some(3);
+    fn inner_round(&self) -> &Self::InnerRound {
+        &self.round
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
+    type NextRound = Round3<P>;
+    fn finalize_to_next_round(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let round = self
+            .round
+            .finalize_to_next_round(rng, payloads, artifacts)?;
+        Ok(Round3 { round })
+    }
+}
+
+pub(crate) struct Round3<P: SchemeParams> {
+    round: super::Round3<P>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "Ciphertext<P::Paillier>: Serialize"))]
+#[serde(bound(deserialize = "Ciphertext<P::Paillier>: for<'x> Deserialize<'x>"))]
+pub struct Round3Broadcast<P: SchemeParams> {
+    delta: Scalar,
+    cap_delta: Point,
+    cap_s: Point,
+    cap_gamma: Point,
+    // The ciphertexts sent to each of the other parties in Round 2, in the order of their indices.
+    cap_ds: Vec<Ciphertext<P::Paillier>>,
+    cap_fs: Vec<Ciphertext<P::Paillier>>,
+}
+
+impl<P: SchemeParams> MaxSerializedSize for Round3Broadcast<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+            + 3 * Point::max_serialized_size(num_parties)
+            + 2 * seq_size::<Ciphertext<P::Paillier>>(num_parties - 1, num_parties)
+    }
+}
+
+pub struct Round3Payload<P: SchemeParams> {
+    payload: super::Round3Payload,
+    cap_ds: HoleVec<CiphertextMod<P::Paillier>>,
+    cap_fs: HoleVec<CiphertextMod<P::Paillier>>,
+}
+
+impl<P: SchemeParams> Round for Round3<P> {
+    type Type = ToNextRound;
+    type Result = PresigningResult<P>;
+    const ROUND_NUM: u8 = 3;
+    const NEXT_ROUND_NUM: Option<u8> = Some(4);
+
+    fn num_parties(&self) -> usize {
+        self.round.num_parties()
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.round.party_idx()
+    }
+
+    const REQUIRES_ECHO: bool = true;
+    type BroadcastMessage = Round3Broadcast<P>;
+    type DirectMessage = super::Round3Message<P>;
+    type Payload = Round3Payload<P>;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        self.round.message_destinations()
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        let artifacts = &self.round.round2_artifacts;
+        Some(Round3Broadcast {
+            delta: P::scalar_from_signed(&self.round.delta),
+            cap_delta: self.round.cap_delta,
+            cap_s: self.round.cap_s,
+            cap_gamma: self.round.context.gamma.mul_by_generator(),
+            cap_ds: artifacts
+                .iter()
+                .map(|artifact| artifact.cap_d.retrieve())
+                .collect(),
+            cap_fs: artifacts
+                .iter()
+                .map(|artifact| artifact.cap_f.retrieve())
+                .collect(),
+        })
+    }
+
+    fn make_direct_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        destination: PartyIdx,
+    ) -> (Self::DirectMessage, Self::Artifact) {
+        self.round.make_direct_message(rng, destination)
+    }
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let payload = self.round.verify_message(from, (), direct_msg)?;
+
+        if broadcast_msg.delta != payload.delta
+            || broadcast_msg.cap_delta != payload.cap_delta
+            || broadcast_msg.cap_s != payload.cap_s
+        {
+            return Err(PresigningError::Round3(
+                "The broadcast values differ from the ones sent directly".into(),
+            ));
+        }
+
+        let num_parties = self.num_parties();
+        let my_idx = self.party_idx().as_usize();
+        let from_idx = from.as_usize();
+        let public_aux = &self.round.context.key_share.public_aux;
+
+        let cap_ds = to_holevec(broadcast_msg.cap_ds, num_parties, from_idx)
+            .ok_or_else(|| PresigningError::Round3("Wrong number of D ciphertexts".into()))?
+            .map_enumerate(|(j, ciphertext)| ciphertext.to_mod(&public_aux[j].paillier_pk));
+        let cap_fs = to_holevec(broadcast_msg.cap_fs, num_parties, from_idx)
+            .ok_or_else(|| PresigningError::Round3("Wrong number of F ciphertexts".into()))?
+            .map(|ciphertext| ciphertext.to_mod(&public_aux[from_idx].paillier_pk));
+
+        if &broadcast_msg.cap_gamma != self.round.cap_gammas.get(from_idx).unwrap()
+            || cap_ds.get(my_idx) != self.round.cap_ds.get(from_idx)
+            || cap_fs.get(my_idx) != self.round.cap_fs.get(from_idx)
+        {
+            return Err(PresigningError::Round3(
+                "The broadcast values differ from the ones sent in Round 2".into(),
+            ));
+        }
+
+        Ok(Round3Payload {
+            payload,
+            cap_ds,
+            cap_fs,
+        })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round3<P> {
+    type NextRound = Round4<P>;
+    fn finalize_to_next_round(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let deltas = payloads.map_ref(|payload| payload.payload.delta);
+        let (payloads, ciphertexts) = payloads
+            .map(|payload| (payload.payload, (payload.cap_ds, payload.cap_fs)))
+            .unzip();
+        let (cap_ds, cap_fs) = ciphertexts.unzip();
+
+        // Since the values were echo-broadcast, either all the honest parties
+        // assemble the presigning data, or all of them prove their correctness in the next round.
+        let outcome = self
+            .round
+            .assemble(payloads)
+            .ok_or_else(|| self.round.correctness_proof(rng));
+
+        Ok(Round4 {
+            round: self.round,
+            deltas,
+            cap_ds,
+            cap_fs,
+            outcome,
+        })
+    }
+}
+
+pub(crate) struct Round4<P: SchemeParams> {
+    round: super::Round3<P>,
+    deltas: HoleVec<Scalar>,
+    // The broadcast ciphertexts sent by each of the other parties in Round 2.
+    cap_ds: HoleVec<HoleVec<CiphertextMod<P::Paillier>>>,
+    cap_fs: HoleVec<HoleVec<CiphertextMod<P::Paillier>>>,
+    outcome: Result<PresigningData<P>, PresigningProof<P>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "Round4Proof<P>: Serialize"))]
+#[serde(bound(deserialize = "Round4Proof<P>: for<'x> Deserialize<'x>"))]
+pub struct Round4Message<P: SchemeParams> {
+    // `None` if the presigning data was assembled successfully.
+    proof: Option<Round4Proof<P>>,
+}
+
+/// The part of [`PresigningProof`] that can be verified by a specific party.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "Ciphertext<P::Paillier>: Serialize,
+    AffGProof<P>: Serialize,
+    MulProof<P>: Serialize,
+    DecProof<P>: Serialize"))]
+#[serde(bound(deserialize = "Ciphertext<P::Paillier>: for<'x> Deserialize<'x>,
+    AffGProof<P>: for<'x> Deserialize<'x>,
+    MulProof<P>: for<'x> Deserialize<'x>,
+    DecProof<P>: for<'x> Deserialize<'x>"))]
+pub struct Round4Proof<P: SchemeParams> {
+    // The proofs for the ciphertexts sent to the parties other than the destination
+    // (the ones sent to the destination were already proven in Round 2),
+    // in the order of their indices.
+    aff_g_proofs: Vec<AffGProof<P>>,
+    cap_h: Ciphertext<P::Paillier>,
+    mul_proof: MulProof<P>,
+    dec_proof: DecProof<P>,
+}
+
+impl<P: SchemeParams> MaxSerializedSize for Round4Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        // The tag of the `Option`
+        1 + seq_size::<AffGProof<P>>(num_parties.saturating_sub(2), num_parties)
+            + Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + MulProof::<P>::max_serialized_size(num_parties)
+            + DecProof::<P>::max_serialized_size(num_parties)
+    }
+}
+
+impl<P: SchemeParams> Round for Round4<P> {
+    type Type = ToResult;
+    type Result = PresigningResult<P>;
+    const ROUND_NUM: u8 = 4;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.round.num_parties()
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.round.party_idx()
+    }
+
+    type BroadcastMessage = ();
+    type DirectMessage = Round4Message<P>;
+    type Payload = ();
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    no_broadcast_messages!();
+
+    fn make_direct_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+        destination: PartyIdx,
+    ) -> (Self::DirectMessage, Self::Artifact) {
+        let proof = self.outcome.as_ref().err().map(|proof| Round4Proof {
+            aff_g_proofs: proof
+                .aff_g_proofs
+                .iter()
+                .filter(|(_target, verifier, _proof)| *verifier == destination)
+                .map(|(_target, _verifier, proof)| proof.clone())
+                .collect(),
+            cap_h: proof.cap_h.retrieve(),
+            mul_proof: proof.mul_proof.clone(),
+            dec_proof: proof
+                .dec_proofs
+                .iter()
+                .find(|(verifier, _proof)| *verifier == destination)
+                .map(|(_verifier, proof)| proof.clone())
+                .unwrap(),
+        });
+
+        (Round4Message { proof }, ())
+    }
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        _broadcast_msg: Self::BroadcastMessage,
+        direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        if self.outcome.is_ok() {
+            // All the honest parties assembled the presigning data,
+            // so there is nothing to prove.
+            return Ok(());
+        }
+
+        let proof = direct_msg
+            .proof
+            .ok_or_else(|| PresigningError::Round4("The proof of correctness is missing".into()))?;
+
+        let num_parties = self.num_parties();
+        let my_idx = self.party_idx().as_usize();
+        let from_idx = from.as_usize();
+        let key_share = &self.round.context.key_share;
+
+        let aux = (&self.round.context.ssid_hash, &from);
+        let from_pk = &key_share.public_aux[from_idx].paillier_pk;
+        let rp = &key_share.public_aux[my_idx].rp_params;
+        let from_cap_ds = self.cap_ds.get(from_idx).unwrap();
+        let from_cap_fs = self.cap_fs.get(from_idx).unwrap();
+        let cap_gamma = self.round.cap_gammas.get(from_idx).unwrap();
+
+        // Aff-g proofs
+
+        let targets = HoleRange::new(num_parties, from_idx)
+            .filter(|j| *j != my_idx)
+            .collect::<Vec<_>>();
+        if proof.aff_g_proofs.len() != targets.len() {
+            return Err(PresigningError::Round4("Wrong number of AffGProofs".into()));
+        }
+        for (j, aff_g_proof) in targets.into_iter().zip(proof.aff_g_proofs.iter()) {
+            if !aff_g_proof.verify(
+                &key_share.public_aux[j].paillier_pk,
+                from_pk,
+                &self.round.all_cap_k[j],
+                from_cap_ds.get(j).unwrap(),
+                from_cap_fs.get(j).unwrap(),
+                cap_gamma,
+                rp,
+                &aux,
+            ) {
+                return Err(PresigningError::Round4("Failed to verify AffGProof".into()));
+            }
+        }
+
+        // Mul proof
+
+        let cap_h = proof.cap_h.to_mod(from_pk);
+        if !proof.mul_proof.verify(
+            from_pk,
+            &self.round.all_cap_k[from_idx],
+            &self.round.all_cap_g[from_idx],
+            &cap_h,
+            &aux,
+        ) {
+            return Err(PresigningError::Round4("Failed to verify MulProof".into()));
+        }
+
+        // Dec proof
+
+        let mut ciphertext = cap_h;
+        for j in HoleRange::new(num_parties, from_idx) {
+            let cap_d = if j == my_idx {
+                &self.round.round2_artifacts.get(from_idx).unwrap().cap_d
+            } else {
+                self.cap_ds.get(j).unwrap().get(from_idx).unwrap()
+            };
+            ciphertext = ciphertext + cap_d + from_cap_fs.get(j).unwrap();
+        }
+
+        if !proof.dec_proof.verify(
+            from_pk,
+            self.deltas.get(from_idx).unwrap(),
+            &ciphertext,
+            rp,
+            &aux,
+        ) {
+            return Err(PresigningError::Round4("Failed to verify DecProof".into()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round4<P> {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        _payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        // If the proofs of all the other parties were verified
+        // and still the presigning data could not be assembled, there is no one to blame.
+        self.outcome.map_err(FinalizeError::Proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use rand_core::{OsRng, RngCore};
+
+    use super::{super::PresigningError, Inputs, Round1};
+    use crate::cggmp21::TestParams;
+    use crate::common::KeyShare;
+    use crate::curve::Scalar;
+    use crate::rounds::{
+        test_utils::{step_next_round, step_result, step_round},
+        FinalizableToNextRound, FirstRound, PartyIdx, Round,
+    };
+
+    fn make_rounds(num_parties: usize) -> Vec<Round1<TestParams>> {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let key_shares = KeyShare::new_centralized(&mut OsRng, num_parties, None);
+        (0..num_parties)
+            .map(|idx| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs::new(key_shares[idx].clone()),
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn execute_identifiable_presigning() {
+        let r1 = make_rounds(3);
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let r4 = step_next_round(&mut OsRng, r3a).unwrap();
+        let r4a = step_round(&mut OsRng, r4).unwrap();
+        let presigning_datas = step_result(&mut OsRng, r4a).unwrap();
+
+        assert_eq!(presigning_datas[0].nonce, presigning_datas[1].nonce);
+        assert_eq!(presigning_datas[0].nonce, presigning_datas[2].nonce);
+    }
+
+    #[test]
+    fn culprit_is_identified() {
+        let num_parties = 3;
+        let r1 = make_rounds(num_parties);
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+
+        // The party 1 sends a wrong delta to everyone (consistently, as the echo round requires),
+        // and the other parties fail to assemble the presigning data.
+        let culprit = PartyIdx::from_usize(1);
+        let mut payloads = (0..num_parties)
+            .map(|_| BTreeMap::new())
+            .collect::<Vec<_>>();
+        for (from, sender) in r3.iter().enumerate() {
+            let from = PartyIdx::from_usize(from);
+            let broadcast = sender.make_broadcast_message(&mut OsRng).unwrap();
+            for to in sender.message_destinations() {
+                let (mut direct, _artifact) = sender.make_direct_message(&mut OsRng, to);
+                let mut broadcast = broadcast.clone();
+                if from == culprit || to == culprit {
+                    // Also make the culprit itself fail, so that it has to send a proof.
+                    broadcast.delta = broadcast.delta + Scalar::ONE;
+                    direct.delta = direct.delta + Scalar::ONE;
+                }
+                let payload = r3[to.as_usize()]
+                    .verify_message(from, broadcast, direct)
+                    .unwrap();
+                payloads[to.as_usize()].insert(from, payload);
+            }
+        }
+        let r4 = r3
+            .into_iter()
+            .zip(payloads)
+            .map(|(round, payloads)| {
+                let artifacts = round
+                    .message_destinations()
+                    .into_iter()
+                    .map(|idx| (idx, ()))
+                    .collect();
+                round
+                    .finalize_to_next_round(&mut OsRng, payloads, artifacts)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let receiver = &r4[0];
+        let receiver_idx = PartyIdx::from_usize(0);
+
+        // The honest party proves its correctness.
+        let (message, _artifact) = r4[2].make_direct_message(&mut OsRng, receiver_idx);
+        receiver
+            .verify_message(PartyIdx::from_usize(2), (), message)
+            .unwrap();
+
+        // The culprit cannot prove the delta it sent.
+        let (message, _artifact) = r4[1].make_direct_message(&mut OsRng, receiver_idx);
+        let error = receiver.verify_message(culprit, (), message).unwrap_err();
+        assert!(matches!(error, PresigningError::Round4(_)));
+    }
+}
@@ -0,0 +1,565 @@
+//! Threshold Schnorr signing producing BIP340 signatures with the shares of the ECDSA key.
+//!
+//! This is not a part of the CGGMP'21 paper; the parties commit to their nonces,
+//! reveal them after all the commitments are received, and sum up their partial signatures,
+//! each of which is checked against the public share of its sender.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::bip340::{challenge, taproot_tweak, SchnorrSignature, TaprootTweak};
+use crate::cggmp21::SchemeParams;
+use crate::common::KeyShare;
+use crate::curve::{Point, Scalar};
+use crate::rounds::{
+    all_parties_except, no_direct_messages, try_to_holevec, FinalizableToNextRound,
+    FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult, Round,
+    ToNextRound, ToResult,
+};
+use crate::tools::bitvec::BitVec;
+use crate::tools::collections::HoleVec;
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+use crate::tools::serialized_size::MaxSerializedSize;
+
+/// Possible results of the Schnorr signing protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct SchnorrSigningResult<P: SchemeParams>(PhantomData<P>);
+
+impl<P: SchemeParams> ProtocolResult for SchnorrSigningResult<P> {
+    const NAME: &'static str = "SchnorrSigning";
+    type Success = SchnorrSignature;
+    type ProvableError = SchnorrSigningError;
+    type CorrectnessProof = ();
+}
+
+/// Possible verifiable errors of the Schnorr signing protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum SchnorrSigningError {
+    /// The nonce revealed in Round 2 does not match the commitment sent in Round 1.
+    R2HashMismatch,
+    /// The partial signature sent in Round 3 does not match the sender's nonce and public share.
+    R3InvalidPartialSignature,
+}
+
+#[derive(Clone)]
+pub struct Inputs<P: SchemeParams> {
+    pub key_share: KeyShare<P>,
+    pub message: [u8; 32],
+    pub tweak: Option<TaprootTweak>,
+}
+
+/// The shares of the key the signature is created with:
+/// the public shares sum up to the output key with an even y-coordinate.
+struct SigningKey {
+    x: Scalar,
+    public_shares: Vec<Point>,
+    verifying_key: Point,
+}
+
+impl SigningKey {
+    fn new<P: SchemeParams>(inputs: &Inputs<P>) -> Result<Self, InitError> {
+        let mut key = Self {
+            x: inputs.key_share.secret_share,
+            public_shares: inputs.key_share.public_shares.to_vec(),
+            verifying_key: inputs.key_share.verifying_key_as_point(),
+        };
+        if key.verifying_key == Point::IDENTITY {
+            return Err(InitError("The shared key is the identity point".into()));
+        }
+
+        // The tweak is applied to the x-only internal key.
+        key.make_y_even();
+
+        if let Some(tweak) = inputs.tweak.as_ref() {
+            let t = taproot_tweak(&key.verifying_key, tweak)
+                .ok_or(InitError("The Taproot tweak is invalid".into()))?;
+            // The tweak is added to the share of the first party.
+            key.public_shares[0] = key.public_shares[0] + t.mul_by_generator();
+            if inputs.key_share.index.as_usize() == 0 {
+                key.x = key.x + t;
+            }
+            key.verifying_key = key.public_shares.iter().sum();
+            if key.verifying_key == Point::IDENTITY {
+                return Err(InitError("The tweaked key is the identity point".into()));
+            }
+            key.make_y_even();
+        }
+
+        Ok(key)
+    }
+
+    fn make_y_even(&mut self) {
+        if !self.verifying_key.has_even_y() {
+            self.x = -self.x;
+            for share in self.public_shares.iter_mut() {
+                *share = -*share;
+            }
+            self.verifying_key = -self.verifying_key;
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.x.zeroize();
+    }
+}
+
+struct Context {
+    num_parties: usize,
+    party_idx: PartyIdx,
+    message: [u8; 32],
+    key: SigningKey,
+    k: Scalar,
+    cap_r: Point,
+    u: BitVec,
+    ssid_hash: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.k.zeroize();
+    }
+}
+
+fn nonce_hash(
+    ssid_hash: &HashOutput,
+    party_idx: PartyIdx,
+    cap_r: &Point,
+    u: &BitVec,
+) -> HashOutput {
+    Hash::new_with_dst(b"SchnorrSigning")
+        .chain(ssid_hash)
+        .chain(&party_idx)
+        .chain(cap_r)
+        .chain(u)
+        .finalize()
+}
+
+pub struct Round1<P: SchemeParams> {
+    context: Context,
+    phantom: PhantomData<P>,
+}
+
+impl<P: SchemeParams> FirstRound for Round1<P> {
+    type Inputs = Inputs<P>;
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        let ssid_hash = Hash::new_with_dst(b"SSID")
+            .chain(&shared_randomness)
+            .chain(&inputs.key_share.share_set_id)
+            .finalize();
+
+        let key = SigningKey::new(&inputs)?;
+
+        let k = Scalar::random_nonzero(rng);
+        let cap_r = k.mul_by_generator();
+        let u = BitVec::random(rng, P::SECURITY_PARAMETER);
+
+        Ok(Self {
+            context: Context {
+                num_parties,
+                party_idx,
+                message: inputs.message,
+                key,
+                k,
+                cap_r,
+                u,
+                ssid_hash,
+            },
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round1Message {
+    cap_v: HashOutput,
+}
+
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        HashOutput::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round1Payload {
+    cap_v: HashOutput,
+}
+
+impl<P: SchemeParams> Round for Round1<P> {
+    type Type = ToNextRound;
+    type Result = SchnorrSigningResult<P>;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = Some(2);
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    // All the parties must receive the same commitments to agree on the nonce.
+    const REQUIRES_ECHO: bool = true;
+    type BroadcastMessage = Round1Message;
+    type DirectMessage = ();
+    type Payload = Round1Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        let cap_v = nonce_hash(
+            &self.context.ssid_hash,
+            self.party_idx(),
+            &self.context.cap_r,
+            &self.context.u,
+        );
+        Some(Round1Message { cap_v })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        _from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        Ok(Round1Payload {
+            cap_v: broadcast_msg.cap_v,
+        })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
+    type NextRound = Round2<P>;
+    fn finalize_to_next_round(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        Ok(Round2 {
+            others_cap_v: try_to_holevec(payloads, self.num_parties(), self.party_idx())
+                .unwrap()
+                .map(|payload| payload.cap_v),
+            context: self.context,
+            phantom: PhantomData,
+        })
+    }
+}
+
+pub struct Round2<P: SchemeParams> {
+    context: Context,
+    others_cap_v: HoleVec<HashOutput>,
+    phantom: PhantomData<P>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round2Message<P: SchemeParams> {
+    cap_r: Point,
+    u: BitVec,
+    phantom: PhantomData<P>,
+}
+
+impl<P: SchemeParams> MaxSerializedSize for Round2Message<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties) + BitVec::serialized_size(P::SECURITY_PARAMETER)
+    }
+}
+
+pub struct Round2Payload {
+    cap_r: Point,
+}
+
+impl<P: SchemeParams> Round for Round2<P> {
+    type Type = ToNextRound;
+    type Result = SchnorrSigningResult<P>;
+    const ROUND_NUM: u8 = 2;
+    const NEXT_ROUND_NUM: Option<u8> = Some(3);
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = Round2Message<P>;
+    type DirectMessage = ();
+    type Payload = Round2Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round2Message {
+            cap_r: self.context.cap_r,
+            u: self.context.u.clone(),
+            phantom: PhantomData,
+        })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let cap_v = nonce_hash(
+            &self.context.ssid_hash,
+            from,
+            &broadcast_msg.cap_r,
+            &broadcast_msg.u,
+        );
+        if &cap_v != self.others_cap_v.get(from.as_usize()).unwrap() {
+            return Err(SchnorrSigningError::R2HashMismatch);
+        }
+
+        Ok(Round2Payload {
+            cap_r: broadcast_msg.cap_r,
+        })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round2<P> {
+    type NextRound = Round3<P>;
+    fn finalize_to_next_round(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let mut all_cap_r = payloads
+            .map(|payload| payload.cap_r)
+            .into_vec(self.context.cap_r);
+        let mut context = self.context;
+
+        let mut cap_r: Point = all_cap_r.iter().sum();
+        if cap_r == Point::IDENTITY {
+            // The nonces were committed to before being revealed,
+            // so this can only happen with a negligible probability.
+            return Err(FinalizeError::Proof(()));
+        }
+
+        // BIP340 requires the nonce point to have an even y-coordinate.
+        if !cap_r.has_even_y() {
+            context.k = -context.k;
+            for cap_r_i in all_cap_r.iter_mut() {
+                *cap_r_i = -*cap_r_i;
+            }
+            cap_r = -cap_r;
+        }
+
+        let e = challenge(&cap_r, &context.key.verifying_key, &context.message);
+        let s = context.k + e * context.key.x;
+
+        Ok(Round3 {
+            context,
+            all_cap_r,
+            cap_r,
+            e,
+            s,
+            phantom: PhantomData,
+        })
+    }
+}
+
+pub struct Round3<P: SchemeParams> {
+    context: Context,
+    all_cap_r: Vec<Point>,
+    cap_r: Point,
+    e: Scalar,
+    s: Scalar,
+    phantom: PhantomData<P>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round3Message {
+    s: Scalar,
+}
+
+impl MaxSerializedSize for Round3Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round3Payload {
+    s: Scalar,
+}
+
+impl<P: SchemeParams> Round for Round3<P> {
+    type Type = ToResult;
+    type Result = SchnorrSigningResult<P>;
+    const ROUND_NUM: u8 = 3;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = Round3Message;
+    type DirectMessage = ();
+    type Payload = Round3Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round3Message { s: self.s })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let idx = from.as_usize();
+        let expected = self.all_cap_r[idx] + self.context.key.public_shares[idx] * self.e;
+        if broadcast_msg.s.mul_by_generator() != expected {
+            return Err(SchnorrSigningError::R3InvalidPartialSignature);
+        }
+
+        Ok(Round3Payload { s: broadcast_msg.s })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round3<P> {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let s = payloads.iter().map(|payload| payload.s).sum::<Scalar>() + self.s;
+
+        // All the partial signatures have been verified, so the signature is valid.
+        SchnorrSignature::from_parts(
+            &self.cap_r,
+            &s,
+            &self.context.key.verifying_key,
+            &self.context.message,
+        )
+        .ok_or(FinalizeError::Proof(()))
+    }
+}
+
+// The signatures are checked with the BIP340 verifier of `k256`.
+#[cfg(all(test, not(feature = "p256")))]
+mod tests {
+    use k256::schnorr::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+    use rand_core::{OsRng, RngCore};
+
+    use super::{Inputs, Round1};
+    use crate::bip340::TaprootTweak;
+    use crate::cggmp21::TestParams;
+    use crate::common::KeyShare;
+    use crate::rounds::{
+        test_utils::{step_next_round, step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+
+    #[test]
+    fn execute_schnorr_signing() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let mut message = [0u8; 32];
+        OsRng.fill_bytes(&mut message);
+
+        let num_parties = 3;
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+        let internal_key = key_shares[0].verifying_key().to_encoded_point(true);
+
+        for tweak in [
+            None,
+            Some(TaprootTweak::KeyPathOnly),
+            Some(TaprootTweak::ScriptTree([1; 32])),
+        ] {
+            let r1 = key_shares
+                .iter()
+                .enumerate()
+                .map(|(idx, key_share)| {
+                    Round1::<TestParams>::new(
+                        &mut OsRng,
+                        &shared_randomness,
+                        num_parties,
+                        PartyIdx::from_usize(idx),
+                        Inputs {
+                            key_share: key_share.clone(),
+                            message,
+                            tweak,
+                        },
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            let r1a = step_round(&mut OsRng, r1).unwrap();
+            let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+            let r2a = step_round(&mut OsRng, r2).unwrap();
+            let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+            let r3a = step_round(&mut OsRng, r3).unwrap();
+            let signatures = step_result(&mut OsRng, r3a).unwrap();
+
+            for signature in signatures {
+                assert!(signature.verify(&message));
+
+                // Check the signature with an independent implementation.
+                let vkey = VerifyingKey::from_bytes(&signature.verifying_key_bytes()).unwrap();
+                let sig = Signature::try_from(signature.to_bytes().as_ref()).unwrap();
+                vkey.verify_prehash(&message, &sig).unwrap();
+
+                // Without a tweak, the signature is made with the shared key itself.
+                assert_eq!(
+                    tweak.is_none(),
+                    signature.verifying_key_bytes() == internal_key.as_bytes()[1..]
+                );
+            }
+        }
+    }
+}
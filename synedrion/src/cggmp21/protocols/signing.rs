@@ -7,11 +7,13 @@ use core::marker::PhantomData;
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
 
+use crate::adaptor::AdaptorSignature;
 use crate::cggmp21::{
     sigma::{AffGProof, DecProof, MulStarProof},
     SchemeParams,
 };
 use crate::common::{KeySharePrecomputed, PresigningData};
+use crate::constructors::PrehashedMessage;
 use crate::curve::{RecoverableSignature, Scalar};
 use crate::paillier::RandomizerMod;
 use crate::rounds::{
@@ -21,6 +23,7 @@ use crate::rounds::{
 use crate::tools::{
     collections::HoleRange,
     hashing::{Chain, Hash, HashOutput},
+    serialized_size::MaxSerializedSize,
 };
 
 /// Possible results of the Signing protocol.
@@ -28,11 +31,20 @@ use crate::tools::{
 pub struct SigningResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for SigningResult<P> {
+    const NAME: &'static str = "Signing";
     type Success = RecoverableSignature;
-    type ProvableError = ();
+    type ProvableError = SigningError;
     type CorrectnessProof = SigningProof<P>;
 }
 
+/// Possible verifiable errors of the Signing protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum SigningError {
+    /// The signature share sent in Round 1 does not match the sender's public shares
+    /// from Presigning.
+    R1InvalidSignatureShare,
+}
+
 /// A proof of a node's correct behavior for the Signing protocol.
 #[allow(dead_code)] // TODO (#43): this can be removed when error verification is added
 #[derive(Debug, Clone)]
@@ -51,7 +63,6 @@ pub struct Round1<P: SchemeParams> {
     party_idx: PartyIdx,
 }
 
-#[derive(Clone)]
 pub struct Inputs<P: SchemeParams> {
     pub message: Scalar,
     pub presigning: PresigningData<P>,
@@ -94,6 +105,12 @@ pub struct Round1Message {
     sigma: Scalar,
 }
 
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+    }
+}
+
 pub struct Round1Payload {
     sigma: Scalar,
 }
@@ -132,10 +149,21 @@ impl<P: SchemeParams> Round for Round1<P> {
 
     fn verify_message(
         &self,
-        _from: PartyIdx,
+        from: PartyIdx,
         broadcast_msg: Self::BroadcastMessage,
         _direct_msg: Self::DirectMessage,
     ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        // `sigma_j = k_j m + r chi_j`, so it can be checked against the public counterparts
+        // of `k_j` and `chi_j` on arrival, instead of finding out that the assembled signature
+        // is invalid at finalization.
+        let presigning = &self.inputs.presigning;
+        let idx = from.as_usize();
+        if presigning.cap_gamma * broadcast_msg.sigma
+            != presigning.cap_deltas[idx] * self.inputs.message + presigning.cap_ss[idx] * self.r
+        {
+            return Err(SigningError::R1InvalidSignatureShare);
+        }
+
         Ok(Round1Payload {
             sigma: broadcast_msg.sigma,
         })
@@ -149,9 +177,7 @@ impl<P: SchemeParams> FinalizableToResult for Round1<P> {
         payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
         _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
     ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
-        let payloads = try_to_holevec(payloads, self.num_parties, self.party_idx).unwrap();
-        let others_sigma = payloads.map(|payload| payload.sigma);
-        let assembled_sigma = others_sigma.iter().sum::<Scalar>() + self.sigma;
+        let assembled_sigma = self.assembled_sigma(payloads);
 
         let signature = RecoverableSignature::from_scalars(
             &self.r,
@@ -164,6 +190,46 @@ impl<P: SchemeParams> FinalizableToResult for Round1<P> {
             return Ok(signature);
         }
 
+        Err(FinalizeError::Proof(self.correctness_proof(rng)))
+    }
+}
+
+impl<P: SchemeParams> Round1<P> {
+    fn assembled_sigma(&self, payloads: BTreeMap<PartyIdx, Round1Payload>) -> Scalar {
+        let payloads = try_to_holevec(payloads, self.num_parties, self.party_idx).unwrap();
+        let others_sigma = payloads.map(|payload| payload.sigma);
+        others_sigma.iter().sum::<Scalar>() + self.sigma
+    }
+
+    /// Finalizes the round into an adaptor signature,
+    /// if the presigning data was created for one.
+    pub(crate) fn finalize_to_adaptor_signature(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, Round1Payload>,
+    ) -> Result<AdaptorSignature, FinalizeError<SigningResult<P>>> {
+        let adaptor = self.inputs.presigning.adaptor.as_ref().ok_or_else(|| {
+            FinalizeError::Local(
+                "The presigning data was not created for an adaptor signature".into(),
+            )
+        })?;
+        let assembled_sigma = self.assembled_sigma(payloads);
+        let signature = AdaptorSignature::new(adaptor, assembled_sigma);
+
+        let verifying_key = self
+            .inputs
+            .key_share
+            .verifying_key_as_point()
+            .to_verifying_key();
+        let prehash: PrehashedMessage = self.inputs.message.to_bytes().into();
+        if verifying_key.is_some_and(|key| signature.verify(&key, &prehash)) {
+            return Ok(signature);
+        }
+
+        Err(FinalizeError::Proof(self.correctness_proof(rng)))
+    }
+
+    fn correctness_proof(&self, rng: &mut impl CryptoRngCore) -> SigningProof<P> {
         let my_idx = self.party_idx.as_usize();
         let num_parties = self.num_parties;
 
@@ -303,28 +369,28 @@ impl<P: SchemeParams> FinalizableToResult for Round1<P> {
             dec_proofs.push((PartyIdx::from_usize(l), p_dec));
         }
 
-        let proof = SigningProof {
+        SigningProof {
             aff_g_proofs,
             mul_star_proofs,
             dec_proofs,
-        };
-
-        Err(FinalizeError::Proof(proof))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use k256::ecdsa::{signature::hazmat::PrehashVerifier, VerifyingKey};
+    use alloc::vec::Vec;
+
     use rand_core::{OsRng, RngCore};
+    use signature::hazmat::PrehashVerifier;
 
-    use super::{Inputs, Round1};
+    use super::{Inputs, Round1, SigningError};
     use crate::cggmp21::TestParams;
     use crate::common::{KeyShare, PresigningData};
-    use crate::curve::Scalar;
+    use crate::curve::{Scalar, VerifyingKey};
     use crate::rounds::{
         test_utils::{step_result, step_round},
-        FirstRound, PartyIdx,
+        FirstRound, PartyIdx, Round,
     };
 
     #[test]
@@ -347,7 +413,7 @@ mod tests {
                     num_parties,
                     PartyIdx::from_usize(idx),
                     Inputs {
-                        presigning: presigning_datas[idx].clone(),
+                        presigning: presigning_datas[idx].duplicate(),
                         message,
                         key_share: key_shares[idx].to_precomputed(),
                     },
@@ -373,4 +439,43 @@ mod tests {
             assert_eq!(recovered_key, vkey);
         }
     }
+
+    #[test]
+    fn invalid_signature_share() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let num_parties = 3;
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+        let presigning_datas = PresigningData::new_centralized(&mut OsRng, &key_shares);
+        let message = Scalar::random(&mut OsRng);
+
+        let r1 = (0..num_parties)
+            .map(|idx| {
+                Round1::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        presigning: presigning_datas[idx].duplicate(),
+                        message,
+                        key_share: key_shares[idx].to_precomputed(),
+                    },
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let from = PartyIdx::from_usize(1);
+        let bcast = r1[1].make_broadcast_message(&mut OsRng).unwrap();
+        assert!(r1[0].verify_message(from, bcast.clone(), ()).is_ok());
+
+        let mut bcast = bcast;
+        bcast.sigma = bcast.sigma + Scalar::ONE;
+        assert!(matches!(
+            r1[0].verify_message(from, bcast, ()),
+            Err(SigningError::R1InvalidSignatureShare)
+        ));
+    }
 }
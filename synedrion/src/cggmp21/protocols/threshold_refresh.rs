@@ -0,0 +1,455 @@
+//! Threshold KeyRefresh protocol: the holders of a set of [`ThresholdKeyShare`]s
+//! re-randomize their Shamir shares, keeping the verifying key and the threshold.
+//!
+//! This is not a part of the CGGMP'21 paper; it is the proactive refresh from
+//! A. Herzberg, S. Jarecki, H. Krawczyk, M. Yung, "Proactive Secret Sharing Or:
+//! How to Cope With Perpetual Leakage" (CRYPTO'95), with Feldman commitments.
+//! Each party creates a random polynomial of degree `threshold - 1` with the zero constant term
+//! and broadcasts the commitments to its coefficients along with an ephemeral Diffie-Hellman key.
+//! After the broadcasts are echoed, each party sends every other party the value of its polynomial
+//! at the recipient's share index, encrypted with the key derived from the ephemeral keys.
+//! The recipients check the values against the commitments and add their sum to their shares;
+//! since all the polynomials vanish at zero, the shared secret stays the same.
+//!
+//! The auxiliary information (Paillier keys and ring-Pedersen parameters) is not changed.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::cggmp21::SchemeParams;
+use crate::curve::{Point, Scalar};
+use crate::rounds::{
+    all_parties_except, no_broadcast_messages, no_direct_messages, try_to_holevec,
+    FinalizableToNextRound, FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx,
+    ProtocolResult, Round, ToNextRound, ToResult,
+};
+use crate::threshold::{ThresholdKeyShare, ThresholdKeyShareChange};
+use crate::tools::collections::HoleVec;
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hash, HashInto, HashOutput, XofHash};
+use crate::tools::serialized_size::{seq_size, MaxSerializedSize};
+use crate::tools::sss::{Polynomial, PublicPolynomial, ShareIdx};
+
+/// Possible results of the threshold KeyRefresh protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct ThresholdRefreshResult<P: SchemeParams>(PhantomData<P>);
+
+impl<P: SchemeParams> ProtocolResult for ThresholdRefreshResult<P> {
+    const NAME: &'static str = "ThresholdRefresh";
+    type Success = ThresholdKeyShareChange<P>;
+    type ProvableError = ThresholdRefreshError;
+    type CorrectnessProof = ();
+}
+
+/// Possible verifiable errors of the threshold KeyRefresh protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum ThresholdRefreshError {
+    /// The commitments to the polynomial sent in Round 1 have a wrong number of coefficients,
+    /// or the ephemeral key is invalid.
+    R1InvalidCommitments,
+    /// The value sent in Round 2 does not match the commitments to the sender's polynomial.
+    R2InvalidSubshare,
+}
+
+#[derive(Clone)]
+pub struct Inputs<P: SchemeParams> {
+    pub key_share: ThresholdKeyShare<P>,
+}
+
+fn make_sid_hash<P: SchemeParams>(
+    shared_randomness: &[u8],
+    key_share: &ThresholdKeyShare<P>,
+) -> HashOutput {
+    Hash::new_with_dst(b"SID")
+        .chain_type::<P>()
+        .chain(&shared_randomness)
+        .chain(&key_share.share_set_id)
+        .finalize()
+}
+
+/// Derives the value masking the subshare of `recipient` from the shared Diffie-Hellman point.
+fn subshare_mask(sid_hash: &HashOutput, recipient: &ShareIdx, shared_point: &Point) -> Scalar {
+    let mut reader = XofHash::new_with_dst(b"ThresholdRefreshSubshare")
+        .chain(sid_hash)
+        .chain(recipient)
+        .chain(shared_point)
+        .finalize_to_reader();
+    Scalar::from_reader(&mut reader)
+}
+
+struct Context<P: SchemeParams> {
+    num_parties: usize,
+    party_idx: PartyIdx,
+    key_share: ThresholdKeyShare<P>,
+    // The share indices of all the parties, in the order of the parties.
+    share_idxs: Vec<ShareIdx>,
+    polynomial: Polynomial,
+    public_polynomial: PublicPolynomial,
+    // The ephemeral Diffie-Hellman key
+    a: Scalar,
+    cap_a: Point,
+    sid_hash: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for Context<P> {
+    fn drop(&mut self) {
+        self.a.zeroize();
+        self.polynomial.zeroize();
+    }
+}
+
+pub struct Round1<P: SchemeParams> {
+    context: Context<P>,
+}
+
+impl<P: SchemeParams> FirstRound for Round1<P> {
+    type Inputs = Inputs<P>;
+
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        let key_share = inputs.key_share;
+
+        // The parties hold the shares in the order of their indices.
+        let share_idxs = key_share.public_shares.keys().cloned().collect::<Vec<_>>();
+        if share_idxs.len() != num_parties {
+            return Err(InitError(
+                "All the holders of the shares must participate in the refresh".into(),
+            ));
+        }
+        if share_idxs[party_idx.as_usize()] != key_share.index() {
+            return Err(InitError(
+                "The position of the party must match the position of its share index".into(),
+            ));
+        }
+
+        let sid_hash = make_sid_hash(shared_randomness, &key_share);
+
+        let polynomial = Polynomial::random(rng, &Scalar::ZERO, key_share.threshold as usize);
+        let public_polynomial = polynomial.public();
+
+        let a = Scalar::random_nonzero(rng);
+        let cap_a = a.mul_by_generator();
+
+        Ok(Self {
+            context: Context {
+                num_parties,
+                party_idx,
+                key_share,
+                share_idxs,
+                polynomial,
+                public_polynomial,
+                a,
+                cap_a,
+                sid_hash,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round1Message {
+    cap_a: Point,
+    // The commitments to the coefficients of the polynomial except the zero constant term
+    // (which is not sent, since the identity point cannot be serialized).
+    coeff_commitments: Vec<Point>,
+}
+
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        // The threshold does not exceed the number of parties.
+        Point::max_serialized_size(num_parties) + seq_size::<Point>(num_parties, num_parties)
+    }
+}
+
+pub struct Round1Payload {
+    cap_a: Point,
+    public_polynomial: PublicPolynomial,
+}
+
+impl<P: SchemeParams> Round for Round1<P> {
+    type Type = ToNextRound;
+    type Result = ThresholdRefreshResult<P>;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = Some(2);
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    // All the parties must check their subshares against the same commitments.
+    const REQUIRES_ECHO: bool = true;
+    type BroadcastMessage = Round1Message;
+    type DirectMessage = ();
+    type Payload = Round1Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round1Message {
+            cap_a: self.context.cap_a,
+            coeff_commitments: self.context.public_polynomial.higher_coeffs().into(),
+        })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        _from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        if broadcast_msg.cap_a == Point::IDENTITY
+            || broadcast_msg.coeff_commitments.len() + 1
+                != self.context.key_share.threshold as usize
+        {
+            return Err(ThresholdRefreshError::R1InvalidCommitments);
+        }
+
+        Ok(Round1Payload {
+            cap_a: broadcast_msg.cap_a,
+            public_polynomial: PublicPolynomial::with_zero_coeff0(broadcast_msg.coeff_commitments),
+        })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToNextRound for Round1<P> {
+    type NextRound = Round2<P>;
+    fn finalize_to_next_round(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let (others_cap_a, others_public_polynomials) = payloads
+            .map(|payload| (payload.cap_a, payload.public_polynomial))
+            .unzip();
+        Ok(Round2 {
+            context: self.context,
+            others_cap_a,
+            others_public_polynomials,
+        })
+    }
+}
+
+pub struct Round2<P: SchemeParams> {
+    context: Context<P>,
+    others_cap_a: HoleVec<Point>,
+    others_public_polynomials: HoleVec<PublicPolynomial>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round2Message {
+    encrypted_subshare: Scalar,
+}
+
+impl MaxSerializedSize for Round2Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round2Payload {
+    subshare: Scalar,
+}
+
+impl<P: SchemeParams> Round for Round2<P> {
+    type Type = ToResult;
+    type Result = ThresholdRefreshResult<P>;
+    const ROUND_NUM: u8 = 2;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = ();
+    type DirectMessage = Round2Message;
+    type Payload = Round2Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    no_broadcast_messages!();
+
+    fn make_direct_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+        destination: PartyIdx,
+    ) -> (Self::DirectMessage, Self::Artifact) {
+        let share_idx = &self.context.share_idxs[destination.as_usize()];
+        let cap_a = self.others_cap_a.get(destination.as_usize()).unwrap();
+        let mask = subshare_mask(
+            &self.context.sid_hash,
+            share_idx,
+            &(cap_a * &self.context.a),
+        );
+        let encrypted_subshare = self.context.polynomial.evaluate(share_idx) + mask;
+        (Round2Message { encrypted_subshare }, ())
+    }
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        _broadcast_msg: Self::BroadcastMessage,
+        direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let share_idx = self.context.key_share.index();
+        let cap_a = self.others_cap_a.get(from.as_usize()).unwrap();
+        let mask = subshare_mask(
+            &self.context.sid_hash,
+            &share_idx,
+            &(cap_a * &self.context.a),
+        );
+        let subshare = direct_msg.encrypted_subshare - mask;
+
+        let public_polynomial = self.others_public_polynomials.get(from.as_usize()).unwrap();
+        if subshare.mul_by_generator() != public_polynomial.evaluate(&share_idx) {
+            return Err(ThresholdRefreshError::R2InvalidSubshare);
+        }
+
+        Ok(Round2Payload { subshare })
+    }
+}
+
+impl<P: SchemeParams> FinalizableToResult for Round2<P> {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let share_idx = self.context.key_share.index();
+        let secret_share_change = self.context.polynomial.evaluate(&share_idx)
+            + payloads
+                .values()
+                .map(|payload| payload.subshare)
+                .sum::<Scalar>();
+
+        let public_share_changes = self
+            .context
+            .share_idxs
+            .iter()
+            .map(|idx| {
+                let change = self.context.public_polynomial.evaluate(idx)
+                    + self
+                        .others_public_polynomials
+                        .iter()
+                        .map(|public_polynomial| public_polynomial.evaluate(idx))
+                        .sum::<Point>();
+                (*idx, change)
+            })
+            .collect();
+
+        Ok(ThresholdKeyShareChange {
+            index: share_idx,
+            secret_share_change,
+            public_share_changes,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand_core::{OsRng, RngCore};
+
+    use super::{Inputs, Round1};
+    use crate::cggmp21::TestParams;
+    use crate::curve::{Scalar, SigningKey};
+    use crate::rounds::{
+        test_utils::{step_next_round, step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+    use crate::threshold::ThresholdKeyShare;
+
+    #[test]
+    fn execute_threshold_refresh() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let sk = SigningKey::random(&mut OsRng);
+        let num_parties = 3;
+        let shares =
+            ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 2, num_parties, Some(&sk));
+
+        let r1 = shares
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| {
+                Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    num_parties,
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        key_share: share.clone(),
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let changes = step_result(&mut OsRng, r2a).unwrap();
+
+        let new_shares = shares
+            .iter()
+            .cloned()
+            .zip(changes)
+            .map(|(share, change)| share.update(change))
+            .collect::<Vec<_>>();
+
+        for (old_share, new_share) in shares.iter().zip(new_shares.iter()) {
+            assert_eq!(new_share.verifying_key(), *sk.verifying_key());
+            assert_eq!(new_share.epoch(), old_share.epoch() + 1);
+            assert_ne!(new_share.secret_share, old_share.secret_share);
+            assert_eq!(new_share.public_shares, new_shares[0].public_shares);
+            assert_eq!(
+                new_share.secret_share.mul_by_generator(),
+                new_share.public_shares[&new_share.index()]
+            );
+        }
+
+        // Any two of the new shares still reconstruct the same key.
+        let share_idxs = [new_shares[2].index(), new_shares[0].index()];
+        let nt_share0 = new_shares[2].to_key_share(&share_idxs);
+        let nt_share1 = new_shares[0].to_key_share(&share_idxs);
+        assert_eq!(
+            nt_share0.secret_share + nt_share1.secret_share,
+            Scalar::from(sk.as_nonzero_scalar())
+        );
+    }
+}
@@ -1,23 +1,41 @@
 //! Sigma-protocols
 
+#[cfg(feature = "signing")]
 mod aff_g;
+#[cfg(feature = "signing")]
 mod dec;
+#[cfg(feature = "signing")]
 mod enc;
+#[cfg(feature = "key-refresh")]
 mod fac;
+#[cfg(feature = "signing")]
 mod log_star;
+#[cfg(feature = "key-refresh")]
 mod mod_;
+#[cfg(feature = "signing")]
 mod mul;
+#[cfg(feature = "signing")]
 mod mul_star;
+#[cfg(feature = "key-refresh")]
 mod prm;
 mod sch;
 
+#[cfg(feature = "signing")]
 pub(crate) use aff_g::AffGProof;
+#[cfg(feature = "signing")]
 pub(crate) use dec::DecProof;
-pub(crate) use enc::EncProof;
+#[cfg(feature = "signing")]
+pub(crate) use enc::{EncProof, EncProofCommitment};
+#[cfg(feature = "key-refresh")]
 pub(crate) use fac::FacProof;
+#[cfg(feature = "signing")]
 pub(crate) use log_star::LogStarProof;
+#[cfg(feature = "key-refresh")]
 pub(crate) use mod_::ModProof;
+#[cfg(feature = "signing")]
 pub(crate) use mul::MulProof;
+#[cfg(feature = "signing")]
 pub(crate) use mul_star::MulStarProof;
+#[cfg(feature = "key-refresh")]
 pub(crate) use prm::PrmProof;
-pub(crate) use sch::{SchCommitment, SchProof, SchSecret};
+pub(crate) use sch::{SchCommitment, SchEquation, SchProof, SchSecret};
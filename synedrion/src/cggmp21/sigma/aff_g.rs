@@ -9,7 +9,8 @@ use crate::paillier::{
     Ciphertext, CiphertextMod, PaillierParams, PublicKeyPaillierPrecomputed, RPCommitment,
     RPParamsMod, Randomizer, RandomizerMod,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::Signed;
 
 const HASH_TAG: &[u8] = b"P_aff_g";
@@ -54,6 +55,19 @@ pub(crate) struct AffGProof<P: SchemeParams> {
     omega_y: Randomizer<P::Paillier>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for AffGProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        3 * Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + 2 * Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(
+                num_parties,
+            )
+            + 2 * Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + Point::max_serialized_size(num_parties)
+            + 4 * RPCommitment::<P::Paillier>::max_serialized_size(num_parties)
+            + 2 * Randomizer::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 impl<P: SchemeParams> AffGProof<P> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -105,7 +119,7 @@ impl<P: SchemeParams> AffGProof<P> {
         // Original: $s^y$. Modified: $s^{-y}$
         let cap_t = setup.commit(&-y, &mu).retrieve();
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&cap_a)
             .chain(&cap_b_x)
@@ -181,7 +195,7 @@ impl<P: SchemeParams> AffGProof<P> {
         assert!(cap_d.public_key() == pk0);
         assert!(cap_y.public_key() == pk1);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_a)
             .chain(&self.cap_b_x)
@@ -221,16 +235,23 @@ impl<P: SchemeParams> AffGProof<P> {
         }
 
         // C^{z_1} (1 + N_0)^{z_2} \omega^{N_0} = A D^e \mod N_0^2
-        // => C (*) z_1 (+) encrypt_0(z_2, \omega) = A (+) D (*) e
-        if cap_c * self.z1 + CiphertextMod::new_with_randomizer_signed(pk0, &self.z2, &self.omega)
-            != cap_d * e + self.cap_a.to_mod(pk0)
+        // => encrypt_0(z_2, \omega) (+) C (*) z_1 (+) D (*) (-e) = A
+        if CiphertextMod::new_with_randomizer_and_lincomb_vartime(
+            pk0,
+            &self.z2,
+            &self.omega,
+            &[(cap_c, self.z1), (cap_d, -e)],
+        ) != Some(self.cap_a.to_mod(pk0))
         {
             return false;
         }
 
         // g^{z_1} = B_x X^e
-        if P::scalar_from_signed(&self.z1).mul_by_generator()
-            != self.cap_b_x + cap_x * &P::scalar_from_signed(&e)
+        // => g^{z_1} X^{-e} = B_x
+        if Point::lincomb_array(&[
+            (Point::GENERATOR, P::scalar_from_signed(&self.z1)),
+            (*cap_x, -P::scalar_from_signed(&e)),
+        ]) != self.cap_b_x
         {
             return false;
         }
@@ -239,24 +260,34 @@ impl<P: SchemeParams> AffGProof<P> {
         // (see the comment in `AffGProof`)
         // Original: `Y^e`. Modified `Y^{-e}`.
         // (1 + N_1)^{z_2} \omega_y^{N_1} = B_y Y^(-e) \mod N_1^2
-        // => encrypt_1(z_2, \omega_y) = B_y (+) Y (*) (-e)
-        if CiphertextMod::new_with_randomizer_signed(pk1, &self.z2, &self.omega_y)
-            != cap_y * (-e) + self.cap_b_y.to_mod(pk1)
+        // => encrypt_1(z_2, \omega_y) (+) Y (*) e = B_y
+        if CiphertextMod::new_with_randomizer_and_lincomb_vartime(
+            pk1,
+            &self.z2,
+            &self.omega_y,
+            &[(cap_y, e)],
+        ) != Some(self.cap_b_y.to_mod(pk1))
         {
             return false;
         }
 
         // s^{z_1} t^{z_3} = E S^e \mod \hat{N}
+        // => s^{z_1} t^{z_3} S^{-e} = E \mod \hat{N}
         let cap_e_mod = self.cap_e.to_mod(aux_pk);
         let cap_s_mod = self.cap_s.to_mod(aux_pk);
-        if setup.commit(&self.z1, &self.z3) != &cap_e_mod * &cap_s_mod.pow_signed_vartime(&e) {
+        if setup.commit_and_lincomb_vartime(&self.z1, &self.z3, &[(&cap_s_mod, -e)])
+            != Some(cap_e_mod)
+        {
             return false;
         }
 
         // s^{z_2} t^{z_4} = F T^e \mod \hat{N}
+        // => s^{z_2} t^{z_4} T^{-e} = F \mod \hat{N}
         let cap_f_mod = self.cap_f.to_mod(aux_pk);
         let cap_t_mod = self.cap_t.to_mod(aux_pk);
-        if setup.commit(&self.z2, &self.z4) != &cap_f_mod * &cap_t_mod.pow_signed_vartime(&e) {
+        if setup.commit_and_lincomb_vartime(&self.z2, &self.z4, &[(&cap_t_mod, -e)])
+            != Some(cap_f_mod)
+        {
             return false;
         }
 
@@ -9,7 +9,8 @@ use crate::paillier::{
     Ciphertext, CiphertextMod, PaillierParams, PublicKeyPaillierPrecomputed, RPCommitment,
     RPParamsMod, Randomizer, RandomizerMod,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::Signed;
 
 const HASH_TAG: &[u8] = b"P_dec";
@@ -40,6 +41,19 @@ pub(crate) struct DecProof<P: SchemeParams> {
     omega: Randomizer<P::Paillier>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for DecProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + 2 * Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(
+                num_parties,
+            )
+            + 2 * RPCommitment::<P::Paillier>::max_serialized_size(num_parties)
+            + Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + Scalar::max_serialized_size(num_parties)
+            + Randomizer::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 impl<P: SchemeParams> DecProof<P> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -67,7 +81,7 @@ impl<P: SchemeParams> DecProof<P> {
             CiphertextMod::new_with_randomizer_signed(pk0, &alpha, &r.retrieve()).retrieve();
         let gamma = P::scalar_from_signed(&alpha);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             // NOTE: the paper only says "sends (A, gamma) to the verifier",
             // but clearly S and T are sent too since the verifier needs access to them.
@@ -114,7 +128,7 @@ impl<P: SchemeParams> DecProof<P> {
     ) -> bool {
         assert_eq!(cap_c.public_key(), pk0);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_s)
             .chain(&self.cap_t)
@@ -2,13 +2,16 @@
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use super::super::SchemeParams;
 use crate::paillier::{
     Ciphertext, CiphertextMod, PaillierParams, PublicKeyPaillierPrecomputed, RPCommitment,
     RPParamsMod, Randomizer, RandomizerMod,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::Signed;
 
 const HASH_TAG: &[u8] = b"P_enc";
@@ -36,18 +39,39 @@ pub(crate) struct EncProof<P: SchemeParams> {
     z3: Signed<<P::Paillier as PaillierParams>::WideUint>,
 }
 
-impl<P: SchemeParams> EncProof<P> {
+impl<P: SchemeParams> MaxSerializedSize for EncProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        2 * Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(num_parties)
+            + 2 * RPCommitment::<P::Paillier>::max_serialized_size(num_parties)
+            + Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + Randomizer::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
+/// The part of [`EncProof`] that does not depend on the auxiliary data:
+/// the random values and the commitments to them, which can be computed in advance.
+///
+/// A commitment must only be used for a single proof.
+#[derive(Debug, Clone)]
+pub(crate) struct EncProofCommitment<P: SchemeParams> {
+    alpha: Signed<<P::Paillier as PaillierParams>::Uint>,
+    mu: Signed<<P::Paillier as PaillierParams>::WideUint>,
+    r: RandomizerMod<P::Paillier>,
+    gamma: Signed<<P::Paillier as PaillierParams>::WideUint>,
+    cap_s: RPCommitment<P::Paillier>,
+    cap_a: Ciphertext<P::Paillier>,
+    cap_c: RPCommitment<P::Paillier>,
+}
+
+impl<P: SchemeParams> EncProofCommitment<P> {
     pub fn new(
         rng: &mut impl CryptoRngCore,
         k: &Signed<<P::Paillier as PaillierParams>::Uint>,
-        rho: &RandomizerMod<P::Paillier>,
         pk0: &PublicKeyPaillierPrecomputed<P::Paillier>,
-        cap_k: &CiphertextMod<P::Paillier>,
         setup: &RPParamsMod<P::Paillier>,
-        aux: &impl Hashable,
     ) -> Self {
         k.assert_bound(P::L_BOUND);
-        assert_eq!(cap_k.public_key(), pk0);
 
         let hat_cap_n = &setup.public_key().modulus_bounded(); // $\hat{N}$
 
@@ -63,11 +87,61 @@ impl<P: SchemeParams> EncProof<P> {
             CiphertextMod::new_with_randomizer_signed(pk0, &alpha, &r.retrieve()).retrieve();
         let cap_c = setup.commit(&alpha, &gamma).retrieve();
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        Self {
+            alpha,
+            mu,
+            r,
+            gamma,
+            cap_s,
+            cap_a,
+            cap_c,
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for EncProofCommitment<P> {
+    fn drop(&mut self) {
+        self.alpha.zeroize();
+        self.mu.zeroize();
+        self.r.zeroize();
+        self.gamma.zeroize();
+    }
+}
+
+impl<P: SchemeParams> EncProof<P> {
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub fn new(
+        rng: &mut impl CryptoRngCore,
+        k: &Signed<<P::Paillier as PaillierParams>::Uint>,
+        rho: &RandomizerMod<P::Paillier>,
+        pk0: &PublicKeyPaillierPrecomputed<P::Paillier>,
+        cap_k: &CiphertextMod<P::Paillier>,
+        setup: &RPParamsMod<P::Paillier>,
+        aux: &impl Hashable,
+    ) -> Self {
+        let commitment = EncProofCommitment::new(rng, k, pk0, setup);
+        Self::from_commitment(&commitment, k, rho, pk0, cap_k, setup, aux)
+    }
+
+    /// Finishes the proof with a commitment created for the same `k`, `pk0` and `setup`.
+    pub fn from_commitment(
+        commitment: &EncProofCommitment<P>,
+        k: &Signed<<P::Paillier as PaillierParams>::Uint>,
+        rho: &RandomizerMod<P::Paillier>,
+        pk0: &PublicKeyPaillierPrecomputed<P::Paillier>,
+        cap_k: &CiphertextMod<P::Paillier>,
+        setup: &RPParamsMod<P::Paillier>,
+        aux: &impl Hashable,
+    ) -> Self {
+        k.assert_bound(P::L_BOUND);
+        assert_eq!(cap_k.public_key(), pk0);
+
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
-            .chain(&cap_s)
-            .chain(&cap_a)
-            .chain(&cap_c)
+            .chain(&commitment.cap_s)
+            .chain(&commitment.cap_a)
+            .chain(&commitment.cap_c)
             // public parameters
             .chain(pk0)
             .chain(&cap_k.retrieve())
@@ -78,15 +152,15 @@ impl<P: SchemeParams> EncProof<P> {
         // Non-interactive challenge
         let e = Signed::from_xof_reader_bounded(&mut reader, &P::CURVE_ORDER);
 
-        let z1 = alpha + e * k;
-        let z2 = (r * rho.pow_signed_vartime(&e)).retrieve();
-        let z3 = gamma + mu * e.into_wide();
+        let z1 = commitment.alpha + e * k;
+        let z2 = (commitment.r.clone() * rho.pow_signed_vartime(&e)).retrieve();
+        let z3 = commitment.gamma + commitment.mu * e.into_wide();
 
         Self {
             e,
-            cap_s,
-            cap_a,
-            cap_c,
+            cap_s: commitment.cap_s.clone(),
+            cap_a: commitment.cap_a.clone(),
+            cap_c: commitment.cap_c.clone(),
             z1,
             z2,
             z3,
@@ -102,7 +176,7 @@ impl<P: SchemeParams> EncProof<P> {
     ) -> bool {
         assert_eq!(cap_k.public_key(), pk0);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_s)
             .chain(&self.cap_a)
@@ -127,15 +201,24 @@ impl<P: SchemeParams> EncProof<P> {
         }
 
         // enc_0(z1, z2) == A (+) K (*) e
-        let c = CiphertextMod::new_with_randomizer_signed(pk0, &self.z1, &self.z2);
-        if c != self.cap_a.to_mod(pk0) + cap_k * e {
+        // => enc_0(z1, z2) (+) K (*) (-e) == A
+        let c = CiphertextMod::new_with_randomizer_and_lincomb_vartime(
+            pk0,
+            &self.z1,
+            &self.z2,
+            &[(cap_k, -e)],
+        );
+        if c != Some(self.cap_a.to_mod(pk0)) {
             return false;
         }
 
         // s^{z_1} t^{z_3} == C S^e \mod \hat{N}
+        // => s^{z_1} t^{z_3} S^{-e} == C \mod \hat{N}
         let cap_c_mod = self.cap_c.to_mod(setup.public_key());
         let cap_s_mod = self.cap_s.to_mod(setup.public_key());
-        if setup.commit(&self.z1, &self.z3) != &cap_c_mod * &cap_s_mod.pow_signed_vartime(&e) {
+        if setup.commit_and_lincomb_vartime(&self.z1, &self.z3, &[(&cap_s_mod, -e)])
+            != Some(cap_c_mod)
+        {
             return false;
         }
 
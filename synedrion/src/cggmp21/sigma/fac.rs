@@ -8,7 +8,8 @@ use crate::paillier::{
     PaillierParams, PublicKeyPaillierPrecomputed, RPCommitment, RPParamsMod,
     SecretKeyPaillierPrecomputed,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::{Bounded, Integer, Signed};
 
 const HASH_TAG: &[u8] = b"P_fac";
@@ -39,6 +40,19 @@ pub(crate) struct FacProof<P: SchemeParams> {
     v: Signed<<P::Paillier as PaillierParams>::ExtraWideUint>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for FacProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + 5 * RPCommitment::<P::Paillier>::max_serialized_size(num_parties)
+            + 4 * Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(
+                num_parties,
+            )
+            + 2 * Signed::<<P::Paillier as PaillierParams>::ExtraWideUint>::max_serialized_size(
+                num_parties,
+            )
+    }
+}
+
 impl<P: SchemeParams> FacProof<P> {
     pub fn new(
         rng: &mut impl CryptoRngCore,
@@ -94,7 +108,7 @@ impl<P: SchemeParams> FacProof<P> {
         let cap_t = (&cap_q.pow_signed_wide(&alpha) * &setup.commit_base_xwide(&r)).retrieve();
         let cap_q = cap_q.retrieve();
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&cap_p)
             .chain(&cap_q)
@@ -141,7 +155,7 @@ impl<P: SchemeParams> FacProof<P> {
         setup: &RPParamsMod<P::Paillier>,
         aux: &impl Hashable,
     ) -> bool {
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_p)
             .chain(&self.cap_q)
@@ -9,7 +9,8 @@ use crate::paillier::{
     Ciphertext, CiphertextMod, PaillierParams, PublicKeyPaillierPrecomputed, RPCommitment,
     RPParamsMod, Randomizer, RandomizerMod,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::Signed;
 
 const HASH_TAG: &[u8] = b"P_log*";
@@ -40,6 +41,17 @@ pub(crate) struct LogStarProof<P: SchemeParams> {
     z3: Signed<<P::Paillier as PaillierParams>::WideUint>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for LogStarProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        2 * Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(num_parties)
+            + 2 * RPCommitment::<P::Paillier>::max_serialized_size(num_parties)
+            + Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + Point::max_serialized_size(num_parties)
+            + Randomizer::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 impl<P: SchemeParams> LogStarProof<P> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -69,7 +81,7 @@ impl<P: SchemeParams> LogStarProof<P> {
         let cap_y = g * &P::scalar_from_signed(&alpha);
         let cap_d = setup.commit(&alpha, &gamma).retrieve();
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&cap_s)
             .chain(&cap_a)
@@ -115,7 +127,7 @@ impl<P: SchemeParams> LogStarProof<P> {
     ) -> bool {
         assert_eq!(cap_c.public_key(), pk0);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_s)
             .chain(&self.cap_a)
@@ -7,8 +7,9 @@ use serde::{Deserialize, Serialize};
 
 use super::super::SchemeParams;
 use crate::paillier::{PaillierParams, PublicKeyPaillierPrecomputed, SecretKeyPaillierPrecomputed};
-use crate::tools::hashing::{Chain, Hashable, XofHash};
-use crate::uint::{RandomPrimeWithRng, Retrieve, UintLike, UintModLike};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::{uint_size, MaxSerializedSize, LENGTH_PREFIX_SIZE};
+use crate::uint::{UintLike, UintModLike};
 
 const HASH_TAG: &[u8] = b"P_mod";
 
@@ -39,7 +40,7 @@ impl<P: SchemeParams> ModChallenge<P> {
         commitment: &ModCommitment<P>,
         aux: &impl Hashable,
     ) -> Self {
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             .chain(pk)
             .chain(commitment)
             .chain(aux)
@@ -81,6 +82,18 @@ pub(crate) struct ModProof<P: SchemeParams> {
     proof: Vec<ModProofElem<P::Paillier>>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for ModProof<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        let uint_size = uint_size::<<P::Paillier as PaillierParams>::Uint>();
+        // The commitment, the challenge, and the proof elements (two integers and two flags each).
+        uint_size
+            + LENGTH_PREFIX_SIZE
+            + P::SECURITY_PARAMETER * uint_size
+            + LENGTH_PREFIX_SIZE
+            + P::SECURITY_PARAMETER * (2 * uint_size + 2)
+    }
+}
+
 impl<P: SchemeParams> ModProof<P> {
     pub fn new(
         rng: &mut impl CryptoRngCore,
@@ -110,7 +123,10 @@ impl<P: SchemeParams> ModProof<P> {
                         y_mod_q = y_mod_q * omega_mod_q;
                     }
 
-                    if let Some((p, q)) = sk.sqrt(&(y_mod_p, y_mod_q)) {
+                    // Which of the four candidates is a quadratic residue is revealed
+                    // by the proof anyway (as `a` and `b`), so branching on it is fine.
+                    let sqrt: Option<_> = sk.sqrt(&(y_mod_p, y_mod_q)).into();
+                    if let Some((p, q)) = sqrt {
                         y_sqrt = Some((p, q));
                         found_a = *a;
                         found_b = *b;
@@ -122,14 +138,13 @@ impl<P: SchemeParams> ModProof<P> {
                 let y_4th_parts = sk.sqrt(&y_sqrt).unwrap();
                 let y_4th = sk.rns_join(&y_4th_parts);
 
-                let y = challenge.0[i].to_mod(pk.precomputed_modulus());
-                let z = y.pow_bounded(sk.inv_modulus());
+                let z = sk.nth_root(&challenge.0[i]);
 
                 ModProofElem {
                     x: y_4th,
                     a: found_a,
                     b: found_b,
-                    z: z.retrieve(),
+                    z,
                 }
             })
             .collect();
@@ -160,7 +175,7 @@ impl<P: SchemeParams> ModProof<P> {
         // but it would require quite a bit of changes because an external RNG is not accessible
         // at the callsite.
         // TODO (#105): consider if we should keep using the default RNG here.
-        if pk.modulus().is_prime_with_rng(&mut OsRng) {
+        if pk.modulus().is_prime(&mut OsRng) {
             return false;
         }
 
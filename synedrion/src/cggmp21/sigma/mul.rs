@@ -8,7 +8,8 @@ use crate::paillier::{
     Ciphertext, CiphertextMod, PaillierParams, PublicKeyPaillierPrecomputed, Randomizer,
     RandomizerMod,
 };
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 use crate::uint::{Bounded, Retrieve, Signed};
 
 const HASH_TAG: &[u8] = b"P_mul";
@@ -23,6 +24,15 @@ pub(crate) struct MulProof<P: SchemeParams> {
     v: Randomizer<P::Paillier>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for MulProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Signed::<<P::Paillier as PaillierParams>::Uint>::max_serialized_size(num_parties)
+            + Signed::<<P::Paillier as PaillierParams>::WideUint>::max_serialized_size(num_parties)
+            + 2 * Ciphertext::<P::Paillier>::max_serialized_size(num_parties)
+            + 2 * Randomizer::<P::Paillier>::max_serialized_size(num_parties)
+    }
+}
+
 /**
 ZK proof: Paillier multiplication.
 
@@ -71,7 +81,7 @@ impl<P: SchemeParams> MulProof<P> {
         let cap_a = (cap_y * alpha).mul_randomizer(&r).retrieve();
         let cap_b = CiphertextMod::new_with_randomizer(pk, alpha.as_ref(), &s).retrieve();
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&cap_a)
             .chain(&cap_b)
@@ -112,7 +122,7 @@ impl<P: SchemeParams> MulProof<P> {
         assert_eq!(cap_y.public_key(), pk);
         assert_eq!(cap_c.public_key(), pk);
 
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             // commitments
             .chain(&self.cap_a)
             .chain(&self.cap_b)
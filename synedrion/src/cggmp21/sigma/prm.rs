@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 
 use super::super::SchemeParams;
 use crate::paillier::{PaillierParams, RPParamsMod, RPSecret, SecretKeyPaillierPrecomputed};
-use crate::tools::hashing::{Chain, Hashable, XofHash};
+use crate::tools::hashing::{Chain, FiatShamirTranscript, Hashable};
+use crate::tools::serialized_size::{seq_size, uint_size, MaxSerializedSize, LENGTH_PREFIX_SIZE};
 use crate::uint::{
     subtle::{Choice, ConditionallySelectable},
     Bounded, Retrieve, UintLike, UintModLike,
@@ -65,7 +66,7 @@ impl PrmChallenge {
         aux: &impl Hashable,
     ) -> Self {
         // TODO (#61): generate m/8 random bytes instead and fill the vector bit by bit.
-        let mut reader = XofHash::new_with_dst(HASH_TAG)
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
             .chain(commitment)
             .chain(setup)
             .chain(aux)
@@ -101,6 +102,20 @@ pub(crate) struct PrmProof<P: SchemeParams> {
     proof: Vec<Bounded<<P::Paillier as PaillierParams>::Uint>>,
 }
 
+impl<P: SchemeParams> MaxSerializedSize for PrmProof<P> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        // The commitment, the challenge bits, and the proof elements.
+        LENGTH_PREFIX_SIZE
+            + P::SECURITY_PARAMETER * uint_size::<<P::Paillier as PaillierParams>::Uint>()
+            + LENGTH_PREFIX_SIZE
+            + P::SECURITY_PARAMETER
+            + seq_size::<Bounded<<P::Paillier as PaillierParams>::Uint>>(
+                P::SECURITY_PARAMETER,
+                num_parties,
+            )
+    }
+}
+
 impl<P: SchemeParams> PrmProof<P> {
     /// Create a proof that we know the `secret`
     /// (the power that was used to create RP parameters).
@@ -186,4 +201,24 @@ mod tests {
         let proof = PrmProof::<Params>::new(&mut OsRng, &sk, &lambda, &setup, &aux);
         assert!(proof.verify(&setup, &aux));
     }
+
+    #[test]
+    fn reject_wrong_setup() {
+        type Params = TestParams;
+        type Paillier = <Params as SchemeParams>::Paillier;
+
+        let sk = SecretKeyPaillier::<Paillier>::random(&mut OsRng).to_precomputed();
+        let pk = sk.public_key();
+
+        let lambda = RPSecret::random(&mut OsRng, &sk);
+        let setup = RPParamsMod::random_with_secret(&mut OsRng, &lambda, pk);
+        let other_setup = RPParamsMod::random(&mut OsRng, &sk);
+
+        let aux: &[u8] = b"abcde";
+        let other_aux: &[u8] = b"fghij";
+
+        let proof = PrmProof::<Params>::new(&mut OsRng, &sk, &lambda, &setup, &aux);
+        assert!(!proof.verify(&other_setup, &aux));
+        assert!(!proof.verify(&setup, &other_aux));
+    }
 }
@@ -3,11 +3,17 @@
 //! Publish $X$ and prove that we know a secret $x$ such that $g^x = X$,
 //! where $g$ is a EC generator.
 
+use alloc::vec::Vec;
+
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
+use crate::cggmp21::SchemeParams;
 use crate::curve::{Point, Scalar};
-use crate::tools::hashing::{Chain, Hash, Hashable};
+use crate::rounds::PartyIdx;
+use crate::tools::hashing::{Chain, FiatShamirTranscript, HashInto, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 
 const HASH_TAG: &[u8] = b"P_sch";
 
@@ -18,6 +24,12 @@ pub(crate) struct SchSecret(
     Scalar,
 );
 
+impl Zeroize for SchSecret {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl SchSecret {
     pub fn random(rng: &mut impl CryptoRngCore) -> Self {
         Self(Scalar::random(rng))
@@ -34,6 +46,12 @@ impl SchCommitment {
     }
 }
 
+impl MaxSerializedSize for SchCommitment {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties)
+    }
+}
+
 impl Hashable for SchCommitment {
     fn chain<C: Chain>(&self, digest: C) -> C {
         digest.chain(&self.0)
@@ -44,14 +62,17 @@ impl Hashable for SchCommitment {
 struct SchChallenge(Scalar);
 
 impl SchChallenge {
-    fn new(public: &Point, commitment: &SchCommitment, aux: &impl Hashable) -> Self {
-        Self(
-            Hash::new_with_dst(HASH_TAG)
-                .chain(aux)
-                .chain(public)
-                .chain(commitment)
-                .finalize_to_scalar(),
-        )
+    fn new<P: SchemeParams>(
+        public: &Point,
+        commitment: &SchCommitment,
+        aux: &impl Hashable,
+    ) -> Self {
+        let mut reader = P::Transcript::new_with_dst(HASH_TAG)
+            .chain(aux)
+            .chain(public)
+            .chain(commitment)
+            .finalize_to_reader();
+        Self(Scalar::from_reader(&mut reader))
     }
 }
 
@@ -70,32 +91,179 @@ pub(crate) struct SchProof {
     proof: Scalar,
 }
 
+impl MaxSerializedSize for SchProof {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        2 * Scalar::max_serialized_size(num_parties)
+    }
+}
+
 impl SchProof {
-    pub fn new(
+    pub fn new<P: SchemeParams>(
         proof_secret: &SchSecret,
         x: &Scalar,
         commitment: &SchCommitment,
         cap_x: &Point,
         aux: &impl Hashable,
     ) -> Self {
-        let challenge = SchChallenge::new(cap_x, commitment, aux);
+        let challenge = SchChallenge::new::<P>(cap_x, commitment, aux);
         let proof = proof_secret.0 + challenge.0 * x;
         Self { challenge, proof }
     }
 
-    pub fn verify(&self, commitment: &SchCommitment, cap_x: &Point, aux: &impl Hashable) -> bool {
-        let challenge = SchChallenge::new(cap_x, commitment, aux);
-        challenge == self.challenge
-            && self.proof.mul_by_generator() == commitment.0 + cap_x * &challenge.0
+    pub fn verify<P: SchemeParams>(
+        &self,
+        commitment: &SchCommitment,
+        cap_x: &Point,
+        aux: &impl Hashable,
+    ) -> bool {
+        self.verify_challenge::<P>(commitment, cap_x, aux)
+            .is_some_and(|equation| equation.verify())
+    }
+
+    /// Checks the challenge of the proof, and returns the equation that remains to be checked
+    /// (either with [`SchEquation::verify`], or batched with [`SchEquation::verify_batch`]).
+    pub fn verify_challenge<P: SchemeParams>(
+        &self,
+        commitment: &SchCommitment,
+        cap_x: &Point,
+        aux: &impl Hashable,
+    ) -> Option<SchEquation> {
+        let challenge = SchChallenge::new::<P>(cap_x, commitment, aux);
+        (challenge == self.challenge).then_some(SchEquation {
+            commitment: commitment.0,
+            cap_x: *cap_x,
+            challenge: challenge.0,
+            proof: self.proof,
+        })
+    }
+}
+
+/// The equation $g^z = A X^e$ of a Schnorr proof with a verified challenge.
+#[derive(Debug, Clone)]
+pub(crate) struct SchEquation {
+    commitment: Point,
+    cap_x: Point,
+    challenge: Scalar,
+    proof: Scalar,
+}
+
+impl SchEquation {
+    pub fn verify(&self) -> bool {
+        self.proof.mul_by_generator() == self.commitment + self.cap_x * self.challenge
+    }
+
+    /// Checks the equations of the proofs made by several parties at once
+    /// by checking a random linear combination of them, which is cheaper than checking each one.
+    ///
+    /// If the combination does not hold, returns the index of the first party
+    /// with an invalid proof (found by checking the equations one by one).
+    pub fn verify_batch(
+        rng: &mut impl CryptoRngCore,
+        equations: &[(PartyIdx, SchEquation)],
+    ) -> Result<(), PartyIdx> {
+        if equations.is_empty() {
+            return Ok(());
+        }
+
+        // $\sum_i \rho_i (z_i g - A_i - e_i X_i) = 0$ for random $\rho_i$
+        // holds with a negligible probability unless all the equations hold.
+        let mut proof_sum = Scalar::ZERO;
+        let mut points_and_scalars = Vec::with_capacity(2 * equations.len() + 1);
+        for (_, equation) in equations {
+            let rho = Scalar::random(rng);
+            proof_sum = proof_sum + rho * equation.proof;
+            points_and_scalars.push((equation.commitment, -rho));
+            points_and_scalars.push((equation.cap_x, -(rho * equation.challenge)));
+        }
+        points_and_scalars.push((Point::GENERATOR, proof_sum));
+
+        if Point::lincomb(&points_and_scalars) == Point::IDENTITY {
+            return Ok(());
+        }
+
+        let (idx, _) = equations
+            .iter()
+            .find(|(_, equation)| !equation.verify())
+            // The combination can only fail if one of the equations does not hold.
+            .unwrap_or(&equations[0]);
+        Err(*idx)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use digest::Digest;
     use rand_core::OsRng;
+    use sha2::Sha256;
+    use sha3::Keccak256;
 
-    use super::{SchCommitment, SchProof, SchSecret};
+    use alloc::vec::Vec;
+
+    use super::{SchCommitment, SchEquation, SchProof, SchSecret};
+    use crate::cggmp21::{SchemeParams, TestParams};
     use crate::curve::Scalar;
+    use crate::paillier::PaillierParams;
+    use crate::rounds::PartyIdx;
+    use crate::tools::hashing::{Chain, FiatShamirTranscript, Keccak256Transcript, XofHash};
+    use crate::uint::NonZero;
+
+    /// A transcript with an application-specific domain separation.
+    struct AppTranscript(XofHash);
+
+    impl Chain for AppTranscript {
+        fn chain_raw_bytes(self, bytes: &[u8]) -> Self {
+            Self(self.0.chain_raw_bytes(bytes))
+        }
+    }
+
+    impl FiatShamirTranscript for AppTranscript {
+        type Reader = <XofHash as FiatShamirTranscript>::Reader;
+
+        fn new_with_dst(dst: &[u8]) -> Self {
+            Self(XofHash::new_with_dst(b"MyApp").chain_bytes(dst))
+        }
+
+        fn finalize_to_reader(self) -> Self::Reader {
+            self.0.finalize_to_reader()
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct AppParams;
+
+    impl SchemeParams for AppParams {
+        const ID: &'static str = "my-app";
+        const SECURITY_PARAMETER: usize = TestParams::SECURITY_PARAMETER;
+        const L_BOUND: usize = TestParams::L_BOUND;
+        const LP_BOUND: usize = TestParams::LP_BOUND;
+        const EPS_BOUND: usize = TestParams::EPS_BOUND;
+        type Paillier = <TestParams as SchemeParams>::Paillier;
+        type Transcript = AppTranscript;
+        type MessageDigest = Sha256;
+        const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
+            TestParams::CURVE_ORDER;
+        const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
+            TestParams::CURVE_ORDER_WIDE;
+    }
+
+    /// Parameters for a chain expecting Keccak-256 to be used everywhere.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct KeccakParams;
+
+    impl SchemeParams for KeccakParams {
+        const ID: &'static str = "keccak";
+        const SECURITY_PARAMETER: usize = TestParams::SECURITY_PARAMETER;
+        const L_BOUND: usize = TestParams::L_BOUND;
+        const LP_BOUND: usize = TestParams::LP_BOUND;
+        const EPS_BOUND: usize = TestParams::EPS_BOUND;
+        type Paillier = <TestParams as SchemeParams>::Paillier;
+        type Transcript = Keccak256Transcript;
+        type MessageDigest = Keccak256;
+        const CURVE_ORDER: NonZero<<Self::Paillier as PaillierParams>::Uint> =
+            TestParams::CURVE_ORDER;
+        const CURVE_ORDER_WIDE: NonZero<<Self::Paillier as PaillierParams>::WideUint> =
+            TestParams::CURVE_ORDER_WIDE;
+    }
 
     #[test]
     fn prove_and_verify() {
@@ -105,7 +273,73 @@ mod tests {
 
         let proof_secret = SchSecret::random(&mut OsRng);
         let commitment = SchCommitment::new(&proof_secret);
-        let proof = SchProof::new(&proof_secret, &secret, &commitment, &public, &aux);
-        assert!(proof.verify(&commitment, &public, &aux));
+        let proof = SchProof::new::<TestParams>(&proof_secret, &secret, &commitment, &public, &aux);
+        assert!(proof.verify::<TestParams>(&commitment, &public, &aux));
+    }
+
+    #[test]
+    fn verify_batch() {
+        let aux: &[u8] = b"abcde";
+        let mut equations = (0..4)
+            .map(|idx| {
+                let secret = Scalar::random(&mut OsRng);
+                let public = secret.mul_by_generator();
+                let proof_secret = SchSecret::random(&mut OsRng);
+                let commitment = SchCommitment::new(&proof_secret);
+                let proof =
+                    SchProof::new::<TestParams>(&proof_secret, &secret, &commitment, &public, &aux);
+                let equation = proof
+                    .verify_challenge::<TestParams>(&commitment, &public, &aux)
+                    .unwrap();
+                (PartyIdx::from_usize(idx), equation)
+            })
+            .collect::<Vec<(PartyIdx, SchEquation)>>();
+        assert!(SchEquation::verify_batch(&mut OsRng, &equations).is_ok());
+        assert!(SchEquation::verify_batch(&mut OsRng, &[]).is_ok());
+
+        equations[2].1.proof = equations[2].1.proof + Scalar::ONE;
+        assert_eq!(
+            SchEquation::verify_batch(&mut OsRng, &equations),
+            Err(PartyIdx::from_usize(2))
+        );
+    }
+
+    #[test]
+    fn custom_transcript() {
+        let secret = Scalar::random(&mut OsRng);
+        let public = secret.mul_by_generator();
+        let aux: &[u8] = b"abcde";
+
+        let proof_secret = SchSecret::random(&mut OsRng);
+        let commitment = SchCommitment::new(&proof_secret);
+        let proof = SchProof::new::<AppParams>(&proof_secret, &secret, &commitment, &public, &aux);
+        assert!(proof.verify::<AppParams>(&commitment, &public, &aux));
+
+        // The challenge depends on the transcript
+        assert!(!proof.verify::<TestParams>(&commitment, &public, &aux));
+    }
+
+    #[test]
+    fn keccak_params() {
+        let secret = Scalar::random(&mut OsRng);
+        let public = secret.mul_by_generator();
+        let aux: &[u8] = b"abcde";
+
+        let proof_secret = SchSecret::random(&mut OsRng);
+        let commitment = SchCommitment::new(&proof_secret);
+        let proof =
+            SchProof::new::<KeccakParams>(&proof_secret, &secret, &commitment, &public, &aux);
+        assert!(proof.verify::<KeccakParams>(&commitment, &public, &aux));
+        assert!(!proof.verify::<TestParams>(&commitment, &public, &aux));
+
+        let message = b"a message";
+        assert_eq!(
+            KeccakParams::prehash_message(message),
+            <[u8; 32]>::from(Keccak256::digest(message))
+        );
+        assert_eq!(
+            TestParams::prehash_message(message),
+            <[u8; 32]>::from(Sha256::digest(message))
+        );
     }
 }
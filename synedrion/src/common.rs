@@ -1,14 +1,37 @@
+use alloc::borrow::Cow;
 use alloc::boxed::Box;
+#[cfg(feature = "signing")]
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
 
-#[cfg(any(test, feature = "bench-internals"))]
+#[cfg(any(
+    test,
+    feature = "bench-internals",
+    feature = "testing",
+    feature = "signing"
+))]
 use alloc::vec::Vec;
+#[cfg(feature = "signing")]
+use core::convert::Infallible;
+use core::marker::PhantomData;
 
-use k256::ecdsa::VerifyingKey;
+use displaydoc::Display;
 use rand_core::CryptoRngCore;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
+#[cfg(feature = "signing")]
+use crate::adaptor::AdaptorPresigningData;
+#[cfg(all(feature = "signing", feature = "testing"))]
+use crate::audit::NonceAuditRecord;
+use crate::bip32::{derive_tweak, ChainCode, DeriveChildError};
+#[cfg(feature = "key-refresh")]
+use crate::cggmp21::key_refresh::mod_self_check;
 use crate::cggmp21::SchemeParams;
-use crate::curve::{Point, Scalar};
+use crate::curve::{Point, Scalar, SigningKey, VerifyingKey};
+#[cfg(feature = "signing")]
+use crate::paillier::Ciphertext;
 use crate::paillier::{
     CiphertextMod, PaillierParams, PublicKeyPaillier, PublicKeyPaillierPrecomputed, RPParams,
     RPParamsMod, Randomizer, SecretKeyPaillier, SecretKeyPaillierPrecomputed,
@@ -18,8 +41,11 @@ use crate::tools::{
     bitvec::BitVec,
     collections::HoleVec,
     hashing::{Chain, Hash, HashOutput, Hashable},
+    serde_bytes,
 };
 use crate::uint::Signed;
+#[cfg(feature = "key-refresh")]
+use crate::uint::{uint_from_be_slice, Bounded, Zero};
 
 #[cfg(any(test, feature = "bench-internals"))]
 use crate::{
@@ -42,13 +68,14 @@ pub struct KeyShareSeed {
 }
 
 /// The full key share with auxiliary parameters.
+///
+/// The serialized share starts with a header identifying the format version,
+/// the scheme parameters, the number of parties, the threshold, and the verifying key,
+/// and the deserialization fails if they do not match the share
+/// (in particular, a share created with one [`SchemeParams`] cannot be loaded with another one).
 // TODO (#77): Debug can be derived automatically here if `secret_share` is wrapped in its own struct,
 // or in a `SecretBox`-type wrapper.
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(bound(serialize = "SecretAuxInfo<P>: Serialize,
-        PublicAuxInfo<P>: Serialize"))]
-#[serde(bound(deserialize = "SecretAuxInfo<P>: for<'x> Deserialize<'x>,
-        PublicAuxInfo<P>: for <'x> Deserialize<'x>"))]
+#[derive(Clone)]
 pub struct KeyShare<P: SchemeParams> {
     pub(crate) index: PartyIdx,
     pub(crate) secret_share: Scalar,
@@ -62,6 +89,191 @@ pub struct KeyShare<P: SchemeParams> {
     /// changed after refresh.
     // Takes place of $ssid$ in the paper when used in hashes/proofs.
     pub(crate) share_set_id: HashOutput,
+    /// The number of refreshes applied to this share set since its creation.
+    pub(crate) epoch: u64,
+}
+
+/// The self-describing header of a serialized [`KeyShare`]
+/// or [`ThresholdKeyShare`](crate::ThresholdKeyShare).
+#[derive(Serialize, Deserialize)]
+pub(crate) struct KeyShareHeader {
+    pub(crate) version: u8,
+    pub(crate) scheme_params: Cow<'static, str>,
+    pub(crate) num_parties: u32,
+    // Always equal to `num_parties` for `KeyShare`, since it is an n-of-n share.
+    pub(crate) threshold: u32,
+    pub(crate) verifying_key: Point,
+}
+
+impl KeyShareHeader {
+    pub(crate) fn new<P: SchemeParams>(
+        num_parties: usize,
+        threshold: usize,
+        verifying_key: Point,
+    ) -> Self {
+        Self {
+            version: KEY_SHARE_FORMAT_VERSION,
+            scheme_params: Cow::Borrowed(P::ID),
+            num_parties: num_parties as u32,
+            threshold: threshold as u32,
+            verifying_key,
+        }
+    }
+
+    /// Returns the fingerprint of the set of shares described by the header.
+    pub(crate) fn fingerprint(&self) -> KeyShareFingerprint {
+        // The format version is left out, so that the fingerprint survives format upgrades.
+        let hash = Hash::new_with_dst(b"KeyShareFingerprint")
+            .chain_bytes(self.scheme_params.as_bytes())
+            .chain(&self.num_parties)
+            .chain(&self.threshold)
+            .chain(&self.verifying_key)
+            .finalize();
+        let mut fingerprint = [0u8; 32];
+        fingerprint.copy_from_slice(hash.as_ref());
+        KeyShareFingerprint(fingerprint)
+    }
+
+    fn check_params<P: SchemeParams>(&self) -> Result<(), KeyShareDecodingError> {
+        if self.version != KEY_SHARE_FORMAT_VERSION {
+            return Err(KeyShareDecodingError::UnsupportedVersion(self.version));
+        }
+        if self.scheme_params != P::ID {
+            return Err(KeyShareDecodingError::SchemeParamsMismatch {
+                expected: P::ID,
+                found: self.scheme_params.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A public identifier of a set of key shares, the same for all of its holders
+/// (see [`KeyShare::fingerprint`]).
+///
+/// It is derived from the verifying key, the number of parties, the threshold
+/// and the identifier of the scheme parameters, so it is preserved by the refreshes
+/// and can be logged, e.g. to tell which wallet a session relates to.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct KeyShareFingerprint(#[serde(with = "serde_bytes::as_hex")] [u8; 32]);
+
+impl KeyShareFingerprint {
+    /// Returns the fingerprint as bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl core::fmt::Display for KeyShareFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl core::fmt::Debug for KeyShareFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "KeyShareFingerprint({})", hex::encode(self.0))
+    }
+}
+
+/// A [`KeyShareHeader`] that fails to deserialize if it does not match the scheme parameters `P`,
+/// before the rest of the share is read.
+pub(crate) struct CheckedKeyShareHeader<P>(pub(crate) KeyShareHeader, pub(crate) PhantomData<P>);
+
+impl<'de, P: SchemeParams> Deserialize<'de> for CheckedKeyShareHeader<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let header = KeyShareHeader::deserialize(deserializer)?;
+        header
+            .check_params::<P>()
+            .map_err(serde::de::Error::custom)?;
+        Ok(Self(header, PhantomData))
+    }
+}
+
+/// Deserializes a share written with a [`KeyShareHeader`] by `bincode`,
+/// checking the header before the rest of the share is read,
+/// since with different scheme parameters the rest of the encoding is unlikely to be readable.
+pub(crate) fn decode_key_share_bytes<P: SchemeParams, T: for<'de> Deserialize<'de>>(
+    bytes: &[u8],
+) -> Result<T, KeyShareDecodingError> {
+    let version = bytes.first().ok_or(KeyShareDecodingError::Empty)?;
+    if *version != KEY_SHARE_FORMAT_VERSION {
+        return Err(KeyShareDecodingError::UnsupportedVersion(*version));
+    }
+
+    let header: KeyShareHeader = bincode::deserialize(bytes)
+        .map_err(|err| KeyShareDecodingError::InvalidEncoding(err.to_string()))?;
+    header.check_params::<P>()?;
+
+    bincode::deserialize(bytes)
+        .map_err(|err| KeyShareDecodingError::InvalidEncoding(err.to_string()))
+}
+
+#[derive(Serialize)]
+#[serde(bound(serialize = "SecretAuxInfo<P>: Serialize,
+        PublicAuxInfo<P>: Serialize"))]
+struct KeyShareEnvelopeRef<'a, P: SchemeParams> {
+    header: KeyShareHeader,
+    index: &'a PartyIdx,
+    secret_share: &'a Scalar,
+    public_shares: &'a [Point],
+    secret_aux: &'a SecretAuxInfo<P>,
+    public_aux: &'a [PublicAuxInfo<P>],
+    init_id: &'a BitVec,
+    share_set_id: &'a HashOutput,
+    epoch: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "SecretAuxInfo<P>: for<'x> Deserialize<'x>,
+        PublicAuxInfo<P>: for <'x> Deserialize<'x>"))]
+struct KeyShareEnvelope<P: SchemeParams> {
+    header: CheckedKeyShareHeader<P>,
+    index: PartyIdx,
+    secret_share: Scalar,
+    public_shares: Box<[Point]>,
+    secret_aux: SecretAuxInfo<P>,
+    public_aux: Box<[PublicAuxInfo<P>]>,
+    init_id: BitVec,
+    share_set_id: HashOutput,
+    epoch: u64,
+}
+
+impl<P: SchemeParams> Serialize for KeyShare<P>
+where
+    SecretAuxInfo<P>: Serialize,
+    PublicAuxInfo<P>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let header = KeyShareHeader::new::<P>(
+            self.num_parties(),
+            self.num_parties(),
+            self.verifying_key_as_point(),
+        );
+        KeyShareEnvelopeRef {
+            header,
+            index: &self.index,
+            secret_share: &self.secret_share,
+            public_shares: &self.public_shares,
+            secret_aux: &self.secret_aux,
+            public_aux: &self.public_aux,
+            init_id: &self.init_id,
+            share_set_id: &self.share_set_id,
+            epoch: self.epoch,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P: SchemeParams> Deserialize<'de> for KeyShare<P>
+where
+    SecretAuxInfo<P>: for<'x> Deserialize<'x>,
+    PublicAuxInfo<P>: for<'x> Deserialize<'x>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let envelope = KeyShareEnvelope::deserialize(deserializer)?;
+        KeyShare::from_envelope(envelope).map_err(serde::de::Error::custom)
+    }
 }
 
 // TODO (#77): Debug can be derived automatically here if `el_gamal_sk` is wrapped in its own struct,
@@ -74,6 +286,99 @@ pub(crate) struct SecretAuxInfo<P: SchemeParams> {
     pub(crate) el_gamal_sk: Scalar, // `y_i`
 }
 
+/// A Paillier secret key generated in advance for the KeyRefresh protocol.
+///
+/// Finding the safe primes for the key takes most of the time of the KeyRefresh protocol.
+/// Generating them beforehand (e.g. in the background or on another machine) and passing them to
+/// [`make_key_refresh_session_with_paillier_key`](crate::make_key_refresh_session_with_paillier_key)
+/// makes the interactive part of the protocol fast.
+///
+/// A key must only be used in a single session.
+#[cfg(feature = "key-refresh")]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "SecretKeyPaillier<P::Paillier>: Serialize"))]
+#[serde(bound(deserialize = "SecretKeyPaillier<P::Paillier>: for <'x> Deserialize<'x>"))]
+pub struct PregeneratedPaillierKey<P: SchemeParams>(pub(crate) SecretKeyPaillier<P::Paillier>);
+
+#[cfg(feature = "key-refresh")]
+impl<P: SchemeParams> PregeneratedPaillierKey<P> {
+    /// Generates a new key.
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        Self(SecretKeyPaillier::random(rng))
+    }
+}
+
+/// Possible errors when creating an [`ExternalPaillierKey`].
+#[cfg(feature = "key-refresh")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ExternalPaillierKeyError {
+    /// The primes or the trapdoor do not fit into the integers of the scheme parameters.
+    InvalidEncoding,
+    /// The primes are not distinct safe primes of the size required by the scheme parameters.
+    InvalidPrimes,
+    /// The modulus does not pass the self-check of the Π^mod proof.
+    ModProofFailed,
+    /// The ring-Pedersen trapdoor is zero or not smaller than the totient of the modulus.
+    InvalidTrapdoor,
+}
+
+/// A Paillier secret key and a ring-Pedersen trapdoor generated outside of the library
+/// (e.g. in an HSM), to be used in the KeyRefresh protocol via
+/// [`make_key_refresh_session_with_external_paillier_key`](crate::make_key_refresh_session_with_external_paillier_key).
+///
+/// The values are validated on creation. Same as [`PregeneratedPaillierKey`],
+/// a key must only be used in a single session.
+#[cfg(feature = "key-refresh")]
+pub struct ExternalPaillierKey<P: SchemeParams> {
+    pub(crate) paillier_sk: SecretKeyPaillier<P::Paillier>,
+    pub(crate) rp_secret: Bounded<<P::Paillier as PaillierParams>::Uint>,
+}
+
+#[cfg(feature = "key-refresh")]
+impl<P: SchemeParams> ExternalPaillierKey<P> {
+    /// Creates a key from the Paillier primes `p` and `q`
+    /// and the ring-Pedersen trapdoor $\lambda$ (used to derive $t = s^\lambda \mod N$),
+    /// all given as big-endian integers.
+    ///
+    /// Checks that `p` and `q` are distinct safe primes of `PRIME_BITS` bits
+    /// (so the modulus has the bit length expected by the scheme parameters),
+    /// that a $\Pi^{mod}$ proof for the modulus can be created and verified,
+    /// and that the trapdoor is in the range $(0, \phi(N))$.
+    pub fn new(
+        rng: &mut impl CryptoRngCore,
+        p: &[u8],
+        q: &[u8],
+        rp_trapdoor: &[u8],
+    ) -> Result<Self, ExternalPaillierKeyError> {
+        let p = uint_from_be_slice(p).ok_or(ExternalPaillierKeyError::InvalidEncoding)?;
+        let q = uint_from_be_slice(q).ok_or(ExternalPaillierKeyError::InvalidEncoding)?;
+        let lambda = uint_from_be_slice::<<P::Paillier as PaillierParams>::Uint>(rp_trapdoor)
+            .ok_or(ExternalPaillierKeyError::InvalidEncoding)?;
+
+        let paillier_sk = SecretKeyPaillier::<P::Paillier>::from_primes(p, q);
+        if !paillier_sk.has_safe_primes(rng) {
+            return Err(ExternalPaillierKeyError::InvalidPrimes);
+        }
+
+        let paillier_sk_precomp = paillier_sk.to_precomputed();
+        if !mod_self_check::<P>(rng, &paillier_sk_precomp) {
+            return Err(ExternalPaillierKeyError::ModProofFailed);
+        }
+
+        let totient = paillier_sk_precomp.totient();
+        if bool::from(lambda.is_zero()) || &lambda >= totient.as_ref() {
+            return Err(ExternalPaillierKeyError::InvalidTrapdoor);
+        }
+        let rp_secret = Bounded::new(lambda, totient.bound())
+            .ok_or(ExternalPaillierKeyError::InvalidTrapdoor)?;
+
+        Ok(Self {
+            paillier_sk,
+            rp_secret,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(serialize = "PublicKeyPaillier<P::Paillier>: Serialize"))]
 #[serde(bound(deserialize = "PublicKeyPaillier<P::Paillier>: for <'x> Deserialize<'x>"))]
@@ -125,13 +430,27 @@ pub struct KeyShareChange<P: SchemeParams> {
 }
 
 /// The result of the Presigning protocol.
-#[derive(Debug, Clone)]
+///
+/// Using the same presigning data to sign two different messages reveals the secret key,
+/// so the type cannot be cloned, and is consumed by [`make_signing_session`](crate::make_signing_session).
+/// The data can be serialized to be kept across restarts (the encoding is versioned
+/// and fails to deserialize with different scheme parameters).
+/// Since a stored copy may then be used twice, [`make_signing_session_with_journal`](crate::make_signing_session_with_journal)
+/// can additionally record its [`id`](Self::id) in a persistent [`PresignatureJournal`].
+#[cfg(feature = "signing")]
+#[derive(Debug)]
 pub struct PresigningData<P: SchemeParams> {
     pub(crate) nonce: Scalar, // x-coordinate of $R$
     /// An additive share of the ephemeral scalar.
     pub(crate) ephemeral_scalar_share: Scalar, // $k_i$
     /// An additive share of `k * x` where `x` is the secret key.
     pub(crate) product_share: Scalar,
+    /// $\Gamma$, the base point of the public counterparts of the shares.
+    pub(crate) cap_gamma: Point,
+    /// $\Delta_j = k_j \Gamma$ for all the parties (the public ephemeral scalar shares).
+    pub(crate) cap_deltas: Box<[Point]>,
+    /// $S_j = \chi_j \Gamma$ for all the parties (the public product shares).
+    pub(crate) cap_ss: Box<[Point]>,
 
     // Values generated during presigning,
     // kept in case we need to generate a proof of correctness.
@@ -147,54 +466,433 @@ pub struct PresigningData<P: SchemeParams> {
     /// Sent $\hat{D}$, that is $\hat{D}_{j,i}$, $j != i$, where $i$ is this party's index.
     pub(crate) hat_cap_d: HoleVec<CiphertextMod<P::Paillier>>,
     pub(crate) hat_cap_f: HoleVec<CiphertextMod<P::Paillier>>,
+    /// The data for an adaptor signature, if the nonce was created for one.
+    pub(crate) adaptor: Option<AdaptorPresigningData>,
+    /// The record of this party's nonce contributions, in the audit mode.
+    #[cfg(feature = "testing")]
+    pub(crate) audit_record: Option<NonceAuditRecord>,
+}
+
+/// A public identifier of a presignature (see [`PresigningData::id`]).
+#[cfg(feature = "signing")]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PresignatureId(#[serde(with = "serde_bytes::as_hex")] [u8; 32]);
+
+#[cfg(feature = "signing")]
+impl PresignatureId {
+    /// Returns the identifier as bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+#[cfg(feature = "signing")]
+impl core::fmt::Display for PresignatureId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+#[cfg(feature = "signing")]
+impl core::fmt::Debug for PresignatureId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "PresignatureId({})", hex::encode(self.0))
+    }
+}
+
+/// A record of the presignatures that were used for signing,
+/// checked by [`make_signing_session_with_journal`](crate::make_signing_session_with_journal).
+///
+/// To enforce that each presignature is used at most once even if the party restarts
+/// (e.g. from a backup of its stored presignatures), the record must be persistent,
+/// and the id must be durably stored before `mark_used` returns.
+/// An in-memory implementation is provided for `BTreeSet<PresignatureId>`.
+#[cfg(feature = "signing")]
+pub trait PresignatureJournal {
+    /// The error returned if the record cannot be updated.
+    type Error: core::fmt::Display;
+
+    /// Records `id` as used.
+    ///
+    /// Returns `false` if it was already recorded.
+    /// Must be atomic with respect to concurrent calls with the same `id`.
+    fn mark_used(&mut self, id: &PresignatureId) -> Result<bool, Self::Error>;
+}
+
+#[cfg(feature = "signing")]
+impl PresignatureJournal for BTreeSet<PresignatureId> {
+    type Error = Infallible;
+
+    fn mark_used(&mut self, id: &PresignatureId) -> Result<bool, Self::Error> {
+        Ok(self.insert(*id))
+    }
+}
+
+/// The version of the serialization format of [`PresigningData`].
+#[cfg(feature = "signing")]
+pub(crate) const PRESIGNING_DATA_FORMAT_VERSION: u8 = 1;
+
+#[cfg(feature = "signing")]
+#[derive(Serialize, Deserialize)]
+struct PresigningDataHeader {
+    version: u8,
+    scheme_params: Cow<'static, str>,
+}
+
+/// A [`PresigningDataHeader`] that fails to deserialize if it does not match the scheme parameters `P`,
+/// before the rest of the data is read.
+#[cfg(feature = "signing")]
+struct CheckedPresigningDataHeader<P>(PhantomData<P>);
+
+#[cfg(feature = "signing")]
+impl<'de, P: SchemeParams> Deserialize<'de> for CheckedPresigningDataHeader<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let header = PresigningDataHeader::deserialize(deserializer)?;
+        if header.version != PRESIGNING_DATA_FORMAT_VERSION {
+            return Err(serde::de::Error::custom(format_args!(
+                "Unsupported presigning data format version: {}",
+                header.version
+            )));
+        }
+        if header.scheme_params != P::ID {
+            return Err(serde::de::Error::custom(format_args!(
+                "The presigning data was created with the scheme parameters `{}`, expected `{}`",
+                header.scheme_params,
+                P::ID
+            )));
+        }
+        Ok(Self(PhantomData))
+    }
+}
+
+// The ciphertexts are stored without their Paillier keys,
+// which are stored once for all the parties in `paillier_pks`.
+#[cfg(feature = "signing")]
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct PresigningDataEnvelopeRef<'a, P: SchemeParams> {
+    header: PresigningDataHeader,
+    nonce: &'a Scalar,
+    ephemeral_scalar_share: &'a Scalar,
+    product_share: &'a Scalar,
+    cap_gamma: &'a Point,
+    cap_deltas: &'a [Point],
+    cap_ss: &'a [Point],
+    product_share_nonreduced: &'a Signed<<P::Paillier as PaillierParams>::Uint>,
+    hat_beta: &'a HoleVec<Signed<<P::Paillier as PaillierParams>::Uint>>,
+    hat_r: &'a HoleVec<Randomizer<P::Paillier>>,
+    hat_s: &'a HoleVec<Randomizer<P::Paillier>>,
+    paillier_pks: Box<[PublicKeyPaillier<P::Paillier>]>,
+    cap_k: Box<[Ciphertext<P::Paillier>]>,
+    hat_cap_d_received: HoleVec<Ciphertext<P::Paillier>>,
+    hat_cap_d: HoleVec<Ciphertext<P::Paillier>>,
+    hat_cap_f: HoleVec<Ciphertext<P::Paillier>>,
+    adaptor: &'a Option<AdaptorPresigningData>,
+    #[cfg(feature = "testing")]
+    audit_record: &'a Option<NonceAuditRecord>,
+}
+
+#[cfg(feature = "signing")]
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct PresigningDataEnvelope<P: SchemeParams> {
+    // Only read for the checks it performs on deserialization.
+    #[allow(dead_code)]
+    header: CheckedPresigningDataHeader<P>,
+    nonce: Scalar,
+    ephemeral_scalar_share: Scalar,
+    product_share: Scalar,
+    cap_gamma: Point,
+    cap_deltas: Box<[Point]>,
+    cap_ss: Box<[Point]>,
+    product_share_nonreduced: Signed<<P::Paillier as PaillierParams>::Uint>,
+    hat_beta: HoleVec<Signed<<P::Paillier as PaillierParams>::Uint>>,
+    hat_r: HoleVec<Randomizer<P::Paillier>>,
+    hat_s: HoleVec<Randomizer<P::Paillier>>,
+    paillier_pks: Box<[PublicKeyPaillier<P::Paillier>]>,
+    cap_k: Box<[Ciphertext<P::Paillier>]>,
+    hat_cap_d_received: HoleVec<Ciphertext<P::Paillier>>,
+    hat_cap_d: HoleVec<Ciphertext<P::Paillier>>,
+    hat_cap_f: HoleVec<Ciphertext<P::Paillier>>,
+    adaptor: Option<AdaptorPresigningData>,
+    #[cfg(feature = "testing")]
+    audit_record: Option<NonceAuditRecord>,
+}
+
+#[cfg(all(feature = "zeroize", feature = "signing"))]
+impl<P: SchemeParams> Drop for PresigningDataEnvelope<P> {
+    fn drop(&mut self) {
+        self.ephemeral_scalar_share.zeroize();
+        self.product_share.zeroize();
+        self.product_share_nonreduced.zeroize();
+        self.hat_beta.zeroize();
+        self.hat_r.zeroize();
+        self.hat_s.zeroize();
+    }
+}
+
+#[cfg(feature = "signing")]
+impl<P: SchemeParams> Serialize for PresigningData<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PresigningDataEnvelopeRef::<P> {
+            header: PresigningDataHeader {
+                version: PRESIGNING_DATA_FORMAT_VERSION,
+                scheme_params: Cow::Borrowed(P::ID),
+            },
+            nonce: &self.nonce,
+            ephemeral_scalar_share: &self.ephemeral_scalar_share,
+            product_share: &self.product_share,
+            cap_gamma: &self.cap_gamma,
+            cap_deltas: &self.cap_deltas,
+            cap_ss: &self.cap_ss,
+            product_share_nonreduced: &self.product_share_nonreduced,
+            hat_beta: &self.hat_beta,
+            hat_r: &self.hat_r,
+            hat_s: &self.hat_s,
+            paillier_pks: self
+                .cap_k
+                .iter()
+                .map(|cap_k| cap_k.public_key().to_minimal())
+                .collect(),
+            cap_k: self.cap_k.iter().map(|cap_k| cap_k.retrieve()).collect(),
+            hat_cap_d_received: self.hat_cap_d_received.map_ref(|cap_d| cap_d.retrieve()),
+            hat_cap_d: self.hat_cap_d.map_ref(|cap_d| cap_d.retrieve()),
+            hat_cap_f: self.hat_cap_f.map_ref(|cap_f| cap_f.retrieve()),
+            adaptor: &self.adaptor,
+            #[cfg(feature = "testing")]
+            audit_record: &self.audit_record,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "signing")]
+impl<'de, P: SchemeParams> Deserialize<'de> for PresigningData<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let envelope = PresigningDataEnvelope::deserialize(deserializer)?;
+        PresigningData::from_envelope(&envelope).ok_or_else(|| {
+            serde::de::Error::custom("The decoded presigning data is internally inconsistent")
+        })
+    }
+}
+
+impl KeyShareSeed {
+    /// Returns `num_parties` of random self-consistent key share seeds
+    /// (which in a decentralized case would be the output of the KeyInit protocol).
+    ///
+    /// If `signing_key` is given, the seeds are made for that key.
+    /// Note that the party calling this learns the whole key,
+    /// so the seeds have to be distributed to the parties over secure channels
+    /// (see [`make_key_import_session`](crate::make_key_import_session) for the interactive alternative).
+    pub fn new_centralized(
+        rng: &mut impl CryptoRngCore,
+        num_parties: usize,
+        signing_key: Option<&SigningKey>,
+    ) -> Box<[Self]> {
+        let secret = match signing_key {
+            None => Scalar::random(rng),
+            Some(sk) => Scalar::from(sk.as_nonzero_scalar()),
+        };
+
+        let secret_shares = secret.split(rng, num_parties);
+        let public_shares = secret_shares
+            .iter()
+            .map(|s| s.mul_by_generator())
+            .collect::<Box<_>>();
+
+        let mut init_id = [0u8; 32];
+        rng.fill_bytes(&mut init_id);
+        let init_id = BitVec::from_bytes(&init_id);
+
+        secret_shares
+            .into_iter()
+            .map(|secret_share| Self {
+                secret_share,
+                public_shares: public_shares.clone(),
+                init_id: init_id.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the verifying key to which this set of shares corresponds,
+    /// so that it can be checked (e.g. against an expected address)
+    /// before the auxiliary data is generated with KeyRefresh.
+    ///
+    /// Returns an error if the sum of the public shares is the identity point,
+    /// in which case [`KeyShare::new`] would fail for this seed as well.
+    pub fn verifying_key(&self) -> Result<VerifyingKey, KeyShareError> {
+        self.public_shares
+            .iter()
+            .sum::<Point>()
+            .to_verifying_key()
+            .ok_or(KeyShareError::IdentityVerifyingKey)
+    }
+
+    /// Returns the number of parties in this set of shares.
+    pub fn num_parties(&self) -> usize {
+        self.public_shares.len()
+    }
+
+    /// Combines the seeds of all the parties (in the order of their party indices)
+    /// into the signing key they are the shares of.
+    ///
+    /// Panics if the seeds are not a complete and consistent set.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reconstruct_signing_key(seeds: &[Self]) -> SigningKey {
+        assert!(!seeds.is_empty(), "No seeds given");
+        for seed in seeds {
+            assert!(
+                seed.public_shares == seeds[0].public_shares && seed.init_id == seeds[0].init_id,
+                "The seeds belong to different share sets"
+            );
+        }
+        reconstruct_signing_key(
+            seeds.iter().map(|seed| &seed.secret_share),
+            &seeds[0].public_shares,
+        )
+    }
+}
+
+/// Adds up the secret shares, checking them against the public shares
+/// and the resulting key against the verifying key.
+#[cfg(any(test, feature = "testing"))]
+fn reconstruct_signing_key<'a>(
+    secret_shares: impl ExactSizeIterator<Item = &'a Scalar>,
+    public_shares: &[Point],
+) -> SigningKey {
+    assert_eq!(
+        secret_shares.len(),
+        public_shares.len(),
+        "The number of shares does not match the number of parties"
+    );
+    let mut secret = Scalar::ZERO;
+    for (idx, (secret_share, public_share)) in secret_shares.zip(public_shares).enumerate() {
+        assert!(
+            &secret_share.mul_by_generator() == public_share,
+            "The secret share of the party {idx} does not match its public share"
+        );
+        secret = secret + *secret_share;
+    }
+    let signing_key = SigningKey::from_bytes(&secret.to_bytes()).expect("The key is zero");
+    // The key must round-trip to the verifying key of the share set.
+    assert_eq!(
+        Some(*signing_key.verifying_key()),
+        public_shares.iter().sum::<Point>().to_verifying_key()
+    );
+    signing_key
 }
 
 impl<P: SchemeParams> KeyShare<P> {
     pub(crate) fn make_share_set_id(
         init_id: &BitVec,
+        epoch: u64,
         public_shares: &[Point],
         public_aux: &[PublicAuxInfo<P>],
     ) -> HashOutput {
         Hash::new_with_dst(b"ShareSetID")
             .chain_type::<P>()
             .chain(init_id)
+            .chain(&epoch)
             .chain_slice(public_shares)
             .chain_slice(public_aux)
             .finalize()
     }
 
-    /// Creates a key share out of the seed (obtained from the KeyGen protocol)
+    /// Creates a key share out of the seed (obtained from the KeyInit protocol,
+    /// or imported from another implementation)
     /// and the share change (obtained from the KeyRefresh+Auxiliary protocol).
-    pub(crate) fn new(seed: KeyShareSeed, change: KeyShareChange<P>) -> Self {
-        // TODO (#68): check that party_idx is the same for both, and the number of parties is the same
-        let secret_share = seed.secret_share + change.secret_share_change;
-        let public_shares = seed
-            .public_shares
+    ///
+    /// Returns an error if the seed and the change are not a part of the same share set,
+    /// or if the resulting share is inconsistent.
+    ///
+    /// A new share set does not need to be assembled this way:
+    /// [`make_key_gen_session`](crate::make_key_gen_session) runs both protocols
+    /// in a single session and returns the complete key share.
+    pub fn new(seed: KeyShareSeed, change: KeyShareChange<P>) -> Result<Self, KeyShareError> {
+        Self::apply_change(
+            &seed.secret_share,
+            &seed.public_shares,
+            &seed.init_id,
+            0,
+            change,
+        )
+    }
+
+    /// Creates the key share from the current one and the change,
+    /// checking that the result is consistent.
+    fn apply_change(
+        secret_share: &Scalar,
+        public_shares: &[Point],
+        init_id: &BitVec,
+        epoch: u64,
+        change: KeyShareChange<P>,
+    ) -> Result<Self, KeyShareError> {
+        let num_parties = public_shares.len();
+        if change.public_share_changes.len() != num_parties
+            || change.public_aux.len() != num_parties
+        {
+            return Err(KeyShareError::NumPartiesMismatch);
+        }
+        let index = change.index.as_usize();
+        if index >= num_parties {
+            return Err(KeyShareError::PartyIndexOutOfRange { index, num_parties });
+        }
+
+        let secret_share = *secret_share + change.secret_share_change;
+        let public_shares_before = public_shares;
+        let public_shares = public_shares
             .iter()
-            .zip(change.public_share_changes.into_vec())
-            .map(|(public_share, public_share_change)| public_share + &public_share_change)
+            .zip(change.public_share_changes.iter())
+            .map(|(public_share, public_share_change)| public_share + public_share_change)
             .collect::<Box<_>>();
 
+        // This catches the seed and the change created for different parties.
+        if secret_share.mul_by_generator() != public_shares[index] {
+            return Err(KeyShareError::SecretShareMismatch);
+        }
+        let verifying_key = public_shares.iter().sum::<Point>();
+        if verifying_key == Point::IDENTITY {
+            return Err(KeyShareError::IdentityVerifyingKey);
+        }
+        // The KeyRefresh changes are shares of zero.
+        if verifying_key != public_shares_before.iter().sum::<Point>() {
+            return Err(KeyShareError::VerifyingKeyChanged);
+        }
+
+        // The auxiliary info is replaced with the one from the change,
+        // so it has to be consistent by itself.
+        let public_aux = &change.public_aux[index];
+        if !change
+            .secret_aux
+            .paillier_sk
+            .matches_public_key(&public_aux.paillier_pk)
+            || change.secret_aux.el_gamal_sk.mul_by_generator() != public_aux.el_gamal_pk
+        {
+            return Err(KeyShareError::AuxInfoMismatch);
+        }
+
         let share_set_id =
-            Self::make_share_set_id(&seed.init_id, &public_shares, &change.public_aux);
+            Self::make_share_set_id(init_id, epoch, &public_shares, &change.public_aux);
 
-        Self {
+        Ok(Self {
             index: change.index,
             secret_share,
             public_shares,
-            secret_aux: change.secret_aux,
-            public_aux: change.public_aux,
-            init_id: seed.init_id,
+            secret_aux: change.secret_aux.clone(),
+            public_aux: change.public_aux.clone(),
+            init_id: init_id.clone(),
             share_set_id,
-        }
+            epoch,
+        })
     }
 
     /// Returns `num_parties` of random self-consistent key shares
     /// (which in a decentralized case would be the output of KeyGen + Auxiliary protocols).
+    #[cfg(any(test, feature = "key-refresh"))]
     pub fn new_centralized(
         rng: &mut impl CryptoRngCore,
         num_parties: usize,
-        signing_key: Option<&k256::ecdsa::SigningKey>,
+        signing_key: Option<&SigningKey>,
     ) -> Box<[Self]> {
         let secret = match signing_key {
             None => Scalar::random(rng),
@@ -210,7 +908,8 @@ impl<P: SchemeParams> KeyShare<P> {
         let (secret_aux, public_aux) = make_aux_info(rng, num_parties);
 
         let init_id = BitVec::random(rng, P::SECURITY_PARAMETER);
-        let share_set_id = Self::make_share_set_id(&init_id, &public_shares, &public_aux);
+        let epoch = 0;
+        let share_set_id = Self::make_share_set_id(&init_id, epoch, &public_shares, &public_aux);
 
         secret_aux
             .into_vec()
@@ -224,35 +923,108 @@ impl<P: SchemeParams> KeyShare<P> {
                 public_aux: public_aux.clone(),
                 init_id: init_id.clone(),
                 share_set_id,
+                epoch,
             })
             .collect()
     }
 
+    /// Combines the shares of all the parties (in any order) into the signing key
+    /// they are the shares of.
+    ///
+    /// Panics if the shares are not a complete and consistent set.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reconstruct_signing_key(shares: &[Self]) -> SigningKey {
+        assert!(!shares.is_empty(), "No shares given");
+        let mut ordered = shares.iter().collect::<Vec<_>>();
+        ordered.sort_by_key(|share| share.index);
+        for (idx, share) in ordered.iter().enumerate() {
+            assert_eq!(
+                share.index.as_usize(),
+                idx,
+                "Missing or duplicate party indices"
+            );
+            assert_eq!(
+                share.share_set_id, shares[0].share_set_id,
+                "The shares belong to different share sets"
+            );
+        }
+        reconstruct_signing_key(
+            ordered.iter().map(|share| &share.secret_share),
+            &shares[0].public_shares,
+        )
+    }
+
     /// Return the updated key share using the share change
     /// obtained from the KeyRefresh+Auxiliary protocol).
-    pub fn update(self, change: KeyShareChange<P>) -> Self {
-        // TODO (#68): check that party_idx is the same for both, and the number of parties is the same
-        let secret_share = self.secret_share + change.secret_share_change;
-        let public_shares = self
-            .public_shares
-            .iter()
-            .zip(change.public_share_changes.into_vec())
-            .map(|(public_share, public_share_change)| public_share + &public_share_change)
-            .collect::<Box<_>>();
+    ///
+    /// The epoch of the resulting share is incremented,
+    /// and the auxiliary info is replaced with the one from the change.
+    ///
+    /// Returns an error if the change was made for another party or another number of parties,
+    /// if it changes the verifying key, if its secret auxiliary info
+    /// does not match the public one, or if the resulting share is inconsistent.
+    pub fn try_update(self, change: KeyShareChange<P>) -> Result<Self, KeyShareError> {
+        if change.index != self.index {
+            return Err(KeyShareError::PartyIndexMismatch {
+                expected: self.index.as_usize(),
+                found: change.index.as_usize(),
+            });
+        }
+        Self::apply_change(
+            &self.secret_share,
+            &self.public_shares,
+            &self.init_id,
+            self.epoch + 1,
+            change,
+        )
+    }
+
+    /// Returns the key share for the non-hardened BIP32 child key number `child_number`
+    /// along with its chain code, given the chain code of this share's verifying key.
+    ///
+    /// All the parties must use the same `chain_code` and `child_number`
+    /// to obtain a consistent set of child key shares.
+    pub fn derive_child(
+        &self,
+        chain_code: &ChainCode,
+        child_number: u32,
+    ) -> Result<(Self, ChainCode), DeriveChildError> {
+        let (tweak, child_chain_code) =
+            derive_tweak(&self.verifying_key_as_point(), chain_code, child_number)?;
+        Ok((self.tweaked(&tweak), child_chain_code))
+    }
+
+    /// Returns the key share for the verifying key `X + tweak * G`,
+    /// where `X` is the verifying key of this share.
+    pub(crate) fn tweaked(&self, tweak: &Scalar) -> Self {
+        // Since the shares are additive, one of them absorbs the whole tweak.
+        let tweaked_idx = PartyIdx::from_usize(0);
+        let secret_share = if self.index == tweaked_idx {
+            self.secret_share + *tweak
+        } else {
+            self.secret_share
+        };
+        let mut public_shares = self.public_shares.clone();
+        public_shares[tweaked_idx.as_usize()] =
+            public_shares[tweaked_idx.as_usize()] + tweak.mul_by_generator();
+
         let share_set_id =
-            Self::make_share_set_id(&self.init_id, &public_shares, &change.public_aux);
+            Self::make_share_set_id(&self.init_id, self.epoch, &public_shares, &self.public_aux);
+
         Self {
-            index: change.index,
+            index: self.index,
             secret_share,
             public_shares,
-            secret_aux: change.secret_aux,
-            public_aux: change.public_aux,
-            init_id: self.init_id,
+            secret_aux: self.secret_aux.clone(),
+            public_aux: self.public_aux.clone(),
+            init_id: self.init_id.clone(),
             share_set_id,
+            epoch: self.epoch,
         }
     }
 
     pub(crate) fn to_precomputed(&self) -> KeySharePrecomputed<P> {
+        let () = P::BOUNDS_CHECK;
         KeySharePrecomputed {
             index: self.index,
             secret_share: self.secret_share,
@@ -283,10 +1055,21 @@ impl<P: SchemeParams> KeyShare<P> {
     }
 
     /// Return the verifying key to which this set of shares corresponds.
+    ///
+    /// Panics if the sum of the public shares is the identity point,
+    /// which is checked on creation and deserialization of the share,
+    /// see [`try_verifying_key`](Self::try_verifying_key) for the fallible version.
     pub fn verifying_key(&self) -> VerifyingKey {
-        // TODO (#5): need to ensure on creation of the share that the verifying key actually exists
-        // (that is, the sum of public keys does not evaluate to the infinity point)
-        self.verifying_key_as_point().to_verifying_key().unwrap()
+        self.try_verifying_key()
+            .expect("the verifying key is checked on creation of the share")
+    }
+
+    /// Return the verifying key to which this set of shares corresponds,
+    /// or an error if the sum of the public shares is the identity point.
+    pub fn try_verifying_key(&self) -> Result<VerifyingKey, KeyShareError> {
+        self.verifying_key_as_point()
+            .to_verifying_key()
+            .ok_or(KeyShareError::IdentityVerifyingKey)
     }
 
     /// Returns the number of parties in this set of shares.
@@ -296,12 +1079,267 @@ impl<P: SchemeParams> KeyShare<P> {
         self.public_shares.len()
     }
 
+    /// Returns the fingerprint of this set of shares,
+    /// which is the same for all of its holders and preserved by the refreshes.
+    pub fn fingerprint(&self) -> KeyShareFingerprint {
+        KeyShareHeader::new::<P>(
+            self.num_parties(),
+            self.num_parties(),
+            self.verifying_key_as_point(),
+        )
+        .fingerprint()
+    }
+
+    /// Returns the number of refreshes applied to this share set since its creation.
+    ///
+    /// The epoch is a part of the session context of the protocols using the share,
+    /// so the parties holding shares from different epochs will fail to execute them.
+    /// This can be used to enforce refresh policies.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
     /// Returns the index of this share's party.
     pub fn party_index(&self) -> usize {
         // TODO (#31): technically it is the share index, but for now we are equating the two,
         // since we assume that one party has one share.
         self.index.as_usize()
     }
+
+    /// Checks the internal consistency of the key share, returning the result of each check.
+    ///
+    /// This only involves the data stored in the share and does not require communication,
+    /// so it can be used to detect corrupted or tampered shares, e.g. on loading them
+    /// or before starting a protocol.
+    /// Note that it cannot detect the shares that are consistent,
+    /// but do not belong to the same set as the shares of the other parties.
+    pub fn verify_consistency(&self) -> KeyShareConsistencyReport {
+        let num_parties = self.public_shares.len();
+        let index = self.index.as_usize();
+        let lengths_match = index < num_parties && self.public_aux.len() == num_parties;
+
+        let secret_share_matches =
+            lengths_match && self.secret_share.mul_by_generator() == self.public_shares[index];
+
+        let paillier_key_matches = lengths_match
+            && self
+                .secret_aux
+                .paillier_sk
+                .matches_public_key(&self.public_aux[index].paillier_pk);
+
+        // For this party the secret key is available, so we can additionally check
+        // that the ring-Pedersen parameters are squares, as they are supposed to be.
+        let paillier_sk =
+            paillier_key_matches.then(|| self.secret_aux.paillier_sk.to_precomputed());
+        let invalid_public_aux = self
+            .public_aux
+            .iter()
+            .enumerate()
+            .filter(|(idx, public_aux)| {
+                if !public_aux.paillier_pk.is_valid() {
+                    return true;
+                }
+                let rp_params = &public_aux.rp_params;
+                if !rp_params.is_valid(&public_aux.paillier_pk.to_precomputed()) {
+                    return true;
+                }
+                match &paillier_sk {
+                    Some(sk) if *idx == index => {
+                        !(sk.is_square(&rp_params.base) && sk.is_square(&rp_params.power))
+                    }
+                    _ => false,
+                }
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let verifying_key_valid = self.verifying_key_as_point() != Point::IDENTITY;
+
+        KeyShareConsistencyReport {
+            lengths_match,
+            secret_share_matches,
+            paillier_key_matches,
+            invalid_public_aux,
+            verifying_key_valid,
+        }
+    }
+
+    /// Serializes the key share into a versioned binary format that can be read back
+    /// with [`from_bytes`](Self::from_bytes).
+    ///
+    /// This is the [`bincode`](https://docs.rs/bincode/1) encoding
+    /// (with the default configuration: fixed-size little-endian integers
+    /// and `u64` length prefixes) of the following fields:
+    ///
+    /// | Field          | Encoding                                                               |
+    /// |----------------|------------------------------------------------------------------------|
+    /// | `version`      | `u8`, the format version (currently `2`)                               |
+    /// | `scheme_params`| string, [`SchemeParams::ID`]                                           |
+    /// | `num_parties`  | `u32`                                                                  |
+    /// | `threshold`    | `u32`, the number of shares required to sign (equal to `num_parties`)  |
+    /// | `verifying_key`| byte string, a 33-byte compressed SEC1 point                           |
+    /// | `index`        | `u32`, the position of this party among the share holders              |
+    /// | `secret_share` | byte string, a 32-byte big-endian scalar                               |
+    /// | `public_shares`| sequence of byte strings, 33-byte compressed SEC1 points               |
+    /// | `secret_aux`   | the Paillier primes `p` and `q`, then the El Gamal secret scalar       |
+    /// | `public_aux`   | sequence of the El Gamal public point, the Paillier modulus, and the ring-Pedersen parameters `t` and `s` |
+    /// | `init_id`      | byte string                                                            |
+    /// | `share_set_id` | byte string, 32 bytes                                                  |
+    /// | `epoch`        | `u64`                                                                  |
+    ///
+    /// Here a byte string is a `u64` length followed by the bytes,
+    /// and the Paillier integers are encoded as little-endian bytes
+    /// of the fixed size determined by `P::Paillier`, without a length prefix.
+    /// A string is encoded as a byte string.
+    /// The share can only be read back with the same scheme parameters.
+    ///
+    /// The same header and fields are written by the [`Serialize`] implementation
+    /// in any other `serde` format.
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        // Serialization into a vector cannot fail for this type.
+        bincode::serialize(self).unwrap().into()
+    }
+
+    /// Deserializes a key share created by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareDecodingError> {
+        let envelope = decode_key_share_bytes::<P, KeyShareEnvelope<P>>(bytes)?;
+        Self::from_envelope(envelope)
+    }
+
+    fn from_envelope(envelope: KeyShareEnvelope<P>) -> Result<Self, KeyShareDecodingError> {
+        let KeyShareEnvelope {
+            header: CheckedKeyShareHeader(header, _),
+            index,
+            secret_share,
+            public_shares,
+            secret_aux,
+            public_aux,
+            init_id,
+            share_set_id,
+            epoch,
+        } = envelope;
+
+        // Assembled before the checks so that the secrets are zeroized if any of them fails.
+        let key_share = Self {
+            index,
+            secret_share,
+            public_shares,
+            secret_aux,
+            public_aux,
+            init_id,
+            share_set_id,
+            epoch,
+        };
+
+        let num_parties = key_share.public_shares.len();
+        let index = key_share.index.as_usize();
+        if index >= num_parties || key_share.public_aux.len() != num_parties {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+        if key_share.secret_share.mul_by_generator() != key_share.public_shares[index] {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+        if header.num_parties as usize != num_parties
+            || header.threshold as usize != num_parties
+            || header.verifying_key != key_share.verifying_key_as_point()
+            || header.verifying_key == Point::IDENTITY
+        {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+
+        Ok(key_share)
+    }
+}
+
+/// The version of the format produced by [`KeyShare::to_bytes`]
+/// and [`ThresholdKeyShare::to_bytes`](crate::ThresholdKeyShare::to_bytes).
+const KEY_SHARE_FORMAT_VERSION: u8 = 2;
+
+/// Possible errors when decoding a key share with [`KeyShare::from_bytes`]
+/// or [`ThresholdKeyShare::from_bytes`](crate::ThresholdKeyShare::from_bytes).
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum KeyShareDecodingError {
+    /// The input is empty.
+    Empty,
+    /// Unsupported format version: {0}.
+    UnsupportedVersion(u8),
+    /// Failed to deserialize the key share: {0}.
+    InvalidEncoding(String),
+    /// The key share was created with the scheme parameters `{found}`, expected `{expected}`.
+    SchemeParamsMismatch {
+        /// The identifier of the requested scheme parameters.
+        expected: &'static str,
+        /// The identifier recorded in the key share.
+        found: String,
+    },
+    /// The decoded key share is internally inconsistent, or does not match its header.
+    Inconsistent,
+}
+
+impl core::error::Error for KeyShareDecodingError {}
+
+/// Possible errors when creating a key share with [`KeyShare::new`] or [`KeyShare::try_update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum KeyShareError {
+    /// The number of parties in the share change does not match the number of the public shares.
+    NumPartiesMismatch,
+    /// The party index {index} is out of range for {num_parties} parties.
+    PartyIndexOutOfRange {
+        /// The index of the party.
+        index: usize,
+        /// The number of parties.
+        num_parties: usize,
+    },
+    /// The secret share does not match the public share of the party.
+    SecretShareMismatch,
+    /// The sum of the public shares is the identity point, so it cannot be a verifying key.
+    IdentityVerifyingKey,
+    /// The share change is for the party {found}, but the share belongs to the party {expected}.
+    PartyIndexMismatch {
+        /// The index of the party holding the share.
+        expected: usize,
+        /// The index of the party the change was made for.
+        found: usize,
+    },
+    /// The share change does not preserve the verifying key.
+    VerifyingKeyChanged,
+    /// The secret auxiliary info in the share change does not match the public one of the party.
+    AuxInfoMismatch,
+}
+
+impl core::error::Error for KeyShareError {}
+
+/// The result of [`KeyShare::verify_consistency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyShareConsistencyReport {
+    /// The party index is in range, and the share contains the public data for each party.
+    ///
+    /// If this is `false`, the checks involving this party's public data fail as well.
+    pub lengths_match: bool,
+    /// The secret share corresponds to this party's public share.
+    pub secret_share_matches: bool,
+    /// The Paillier secret key corresponds to this party's Paillier public key.
+    pub paillier_key_matches: bool,
+    /// The indices of the parties whose Paillier public keys or ring-Pedersen parameters
+    /// are malformed.
+    ///
+    /// For this party the ring-Pedersen parameters are also checked to be squares
+    /// modulo the Paillier modulus (if the Paillier secret key matches the public one).
+    pub invalid_public_aux: Box<[usize]>,
+    /// The sum of the public shares is not the identity point,
+    /// so it can be used as a verifying key.
+    pub verifying_key_valid: bool,
+}
+
+impl KeyShareConsistencyReport {
+    /// Returns `true` if all the checks passed.
+    pub fn is_consistent(&self) -> bool {
+        self.lengths_match
+            && self.secret_share_matches
+            && self.paillier_key_matches
+            && self.invalid_public_aux.is_empty()
+            && self.verifying_key_valid
+    }
 }
 
 impl<P: SchemeParams> KeySharePrecomputed<P> {
@@ -324,7 +1362,143 @@ impl<P: SchemeParams> KeySharePrecomputed<P> {
     }
 }
 
+#[cfg(feature = "signing")]
 impl<P: SchemeParams> PresigningData<P> {
+    /// Returns the number of parties that participated in the Presigning protocol.
+    pub(crate) fn num_parties(&self) -> usize {
+        self.cap_k.len()
+    }
+
+    /// Returns the identifier of this presignature, the same for all the parties
+    /// that created it in one Presigning session.
+    pub fn id(&self) -> PresignatureId {
+        let hash = self
+            .cap_deltas
+            .iter()
+            .fold(
+                Hash::new_with_dst(b"PresignatureId")
+                    .chain(&self.nonce)
+                    .chain(&self.cap_gamma),
+                |digest, cap_delta| digest.chain(cap_delta),
+            )
+            .finalize();
+        let mut id = [0u8; 32];
+        id.copy_from_slice(hash.as_ref());
+        PresignatureId(id)
+    }
+
+    /// Returns the record of this party's nonce contributions
+    /// if the data was created by a session made with
+    /// [`make_presigning_session_with_audit`](crate::make_presigning_session_with_audit).
+    #[cfg(feature = "testing")]
+    pub fn audit_record(&self) -> Option<&NonceAuditRecord> {
+        self.audit_record.as_ref()
+    }
+
+    /// Restores the data from an envelope, returning `None` if it is inconsistent.
+    fn from_envelope(envelope: &PresigningDataEnvelope<P>) -> Option<Self> {
+        let num_parties = envelope.cap_k.len();
+        let index = envelope.hat_beta.hole_at();
+        if index >= num_parties
+            || envelope.paillier_pks.len() != num_parties
+            || envelope.cap_deltas.len() != num_parties
+            || envelope.cap_ss.len() != num_parties
+            || envelope
+                .adaptor
+                .as_ref()
+                .is_some_and(|adaptor| adaptor.shares.len() != num_parties)
+        {
+            return None;
+        }
+        let holes = [
+            (envelope.hat_beta.len(), envelope.hat_beta.hole_at()),
+            (envelope.hat_r.len(), envelope.hat_r.hole_at()),
+            (envelope.hat_s.len(), envelope.hat_s.hole_at()),
+            (
+                envelope.hat_cap_d_received.len(),
+                envelope.hat_cap_d_received.hole_at(),
+            ),
+            (envelope.hat_cap_d.len(), envelope.hat_cap_d.hole_at()),
+            (envelope.hat_cap_f.len(), envelope.hat_cap_f.hole_at()),
+        ];
+        if holes.iter().any(|hole| *hole != (num_parties, index)) {
+            return None;
+        }
+        if envelope.cap_gamma * envelope.ephemeral_scalar_share != envelope.cap_deltas[index]
+            || envelope.cap_gamma * envelope.product_share != envelope.cap_ss[index]
+        {
+            return None;
+        }
+        if !envelope.paillier_pks.iter().all(|pk| pk.is_valid()) {
+            return None;
+        }
+
+        let pks = envelope
+            .paillier_pks
+            .iter()
+            .map(|pk| pk.to_precomputed())
+            .collect::<Vec<_>>();
+        let own_pk = &pks[index];
+
+        Some(Self {
+            nonce: envelope.nonce,
+            ephemeral_scalar_share: envelope.ephemeral_scalar_share,
+            product_share: envelope.product_share,
+            cap_gamma: envelope.cap_gamma,
+            cap_deltas: envelope.cap_deltas.clone(),
+            cap_ss: envelope.cap_ss.clone(),
+            product_share_nonreduced: envelope.product_share_nonreduced,
+            hat_beta: envelope.hat_beta.clone(),
+            hat_r: envelope.hat_r.clone(),
+            hat_s: envelope.hat_s.clone(),
+            cap_k: envelope
+                .cap_k
+                .iter()
+                .zip(pks.iter())
+                .map(|(cap_k, pk)| cap_k.to_mod(pk))
+                .collect(),
+            // Encrypted with this party's key by the other parties.
+            hat_cap_d_received: envelope
+                .hat_cap_d_received
+                .map_ref(|cap_d| cap_d.to_mod(own_pk)),
+            // Encrypted with the receivers' keys.
+            hat_cap_d: envelope
+                .hat_cap_d
+                .clone()
+                .map_enumerate(|(j, cap_d)| cap_d.to_mod(&pks[j])),
+            // Encrypted with this party's key.
+            hat_cap_f: envelope.hat_cap_f.map_ref(|cap_f| cap_f.to_mod(own_pk)),
+            adaptor: envelope.adaptor.clone(),
+            #[cfg(feature = "testing")]
+            audit_record: envelope.audit_record.clone(),
+        })
+    }
+
+    /// Returns a copy of the data, for the tests and benchmarks signing several messages
+    /// with the same presignature (which must never happen outside of them).
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            nonce: self.nonce,
+            ephemeral_scalar_share: self.ephemeral_scalar_share,
+            product_share: self.product_share,
+            cap_gamma: self.cap_gamma,
+            cap_deltas: self.cap_deltas.clone(),
+            cap_ss: self.cap_ss.clone(),
+            product_share_nonreduced: self.product_share_nonreduced,
+            hat_beta: self.hat_beta.clone(),
+            hat_r: self.hat_r.clone(),
+            hat_s: self.hat_s.clone(),
+            cap_k: self.cap_k.clone(),
+            hat_cap_d_received: self.hat_cap_d_received.clone(),
+            hat_cap_d: self.hat_cap_d.clone(),
+            hat_cap_f: self.hat_cap_f.clone(),
+            adaptor: self.adaptor.clone(),
+            #[cfg(feature = "testing")]
+            audit_record: self.audit_record.clone(),
+        }
+    }
+
     /// Creates a consistent set of presigning data for testing purposes.
     #[cfg(any(test, feature = "bench-internals"))]
     pub fn new_centralized(
@@ -338,6 +1512,11 @@ impl<P: SchemeParams> PresigningData<P> {
             .mul_by_generator()
             .x_coordinate();
         let ephemeral_scalar_shares = ephemeral_scalar.split(rng, key_shares.len());
+        let cap_gamma = Scalar::random_nonzero(rng).mul_by_generator();
+        let cap_deltas = ephemeral_scalar_shares
+            .iter()
+            .map(|k| cap_gamma * *k)
+            .collect::<Box<[_]>>();
 
         let num_parties = key_shares.len();
         let public_keys = key_shares[0]
@@ -420,6 +1599,10 @@ impl<P: SchemeParams> PresigningData<P> {
 
             presigning.push(PresigningData {
                 nonce,
+                cap_gamma,
+                cap_deltas: cap_deltas.clone(),
+                // Filled in below, when all the product shares are known.
+                cap_ss: Box::new([]),
                 ephemeral_scalar_share: k,
                 product_share: P::scalar_from_signed(&product_share_nonreduced),
                 product_share_nonreduced,
@@ -430,9 +1613,20 @@ impl<P: SchemeParams> PresigningData<P> {
                 hat_cap_d: hat_cap_ds[i].clone(),
                 hat_cap_f: hat_cap_f_vec.finalize().unwrap(),
                 cap_k: cap_k.clone().into_boxed_slice(),
+                adaptor: None,
+                #[cfg(feature = "testing")]
+                audit_record: None,
             });
         }
 
+        let cap_ss = presigning
+            .iter()
+            .map(|data| cap_gamma * data.product_share)
+            .collect::<Box<[_]>>();
+        for data in presigning.iter_mut() {
+            data.cap_ss = cap_ss.clone();
+        }
+
         presigning.into()
     }
 }
@@ -455,6 +1649,29 @@ impl<P: SchemeParams> core::fmt::Debug for SecretAuxInfo<P> {
     }
 }
 
+// A custom Debug impl that skips the secret value
+#[cfg(feature = "key-refresh")]
+impl<P: SchemeParams> core::fmt::Debug for PregeneratedPaillierKey<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "PregeneratedPaillierKey {{ <...> }}",)
+    }
+}
+
+// A custom Debug impl that skips the secret values
+#[cfg(feature = "key-refresh")]
+impl<P: SchemeParams> core::fmt::Debug for ExternalPaillierKey<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "ExternalPaillierKey {{ <...> }}",)
+    }
+}
+
+#[cfg(all(feature = "key-refresh", feature = "zeroize"))]
+impl<P: SchemeParams> Drop for ExternalPaillierKey<P> {
+    fn drop(&mut self) {
+        self.rp_secret.zeroize();
+    }
+}
+
 // A custom Debug impl that skips the secret values
 impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for KeyShare<P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
@@ -462,6 +1679,7 @@ impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for KeyShare<P> {
             f,
             concat![
                 "KeyShare {{",
+                "fingerprint: {}, ",
                 "index: {:?}, ",
                 "secret_share: <...>, ",
                 "public_shares: {:?}, ",
@@ -469,7 +1687,11 @@ impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for KeyShare<P> {
                 "public_aux: {:?} ",
                 "}}"
             ],
-            self.index, self.public_shares, self.secret_aux, self.public_aux
+            self.fingerprint(),
+            self.index,
+            self.public_shares,
+            self.secret_aux,
+            self.public_aux
         )
     }
 }
@@ -478,13 +1700,15 @@ impl<P: SchemeParams> core::fmt::Display for KeyShare<P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(
             f,
-            "KeyShare(idx={}, vkey={})",
+            "KeyShare(idx={}, vkey={}, fingerprint={})",
             self.index.as_usize(),
-            hex::encode(self.verifying_key_as_point().to_compressed_array())
+            hex::encode(self.verifying_key_as_point().to_compressed_array()),
+            self.fingerprint()
         )
     }
 }
 
+#[cfg(any(test, feature = "key-refresh"))]
 #[allow(clippy::type_complexity)]
 pub(crate) fn make_aux_info<P: SchemeParams>(
     rng: &mut impl CryptoRngCore,
@@ -521,13 +1745,78 @@ impl<P: SchemeParams> Hashable for PublicAuxInfo<P> {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Drop for KeyShareSeed {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for KeyShare<P> {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for SecretAuxInfo<P> {
+    fn drop(&mut self) {
+        self.el_gamal_sk.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for KeySharePrecomputed<P> {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for SecretAuxInfoPrecomputed<P> {
+    fn drop(&mut self) {
+        self.el_gamal_sk.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for KeyShareChange<P> {
+    fn drop(&mut self) {
+        self.secret_share_change.zeroize();
+    }
+}
+
+#[cfg(all(feature = "zeroize", feature = "signing"))]
+impl<P: SchemeParams> Drop for PresigningData<P> {
+    fn drop(&mut self) {
+        self.ephemeral_scalar_share.zeroize();
+        self.product_share.zeroize();
+        self.product_share_nonreduced.zeroize();
+        self.hat_beta.zeroize();
+        self.hat_r.zeroize();
+        self.hat_s.zeroize();
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use k256::ecdsa::SigningKey;
+    use alloc::vec::Vec;
+
     use rand_core::OsRng;
 
-    use super::KeyShare;
-    use crate::TestParams;
+    #[cfg(feature = "key-refresh")]
+    use super::{ExternalPaillierKey, ExternalPaillierKeyError};
+    use super::{
+        KeyShare, KeyShareChange, KeyShareDecodingError, KeyShareError, KeyShareFingerprint,
+        KeyShareSeed,
+    };
+    use crate::bip32::ChainCode;
+    use crate::curve::{BackendSignature, Point, Scalar, SigningKey};
+    use crate::paillier::SecretKeyPaillier;
+    use crate::rounds::PartyIdx;
+    use crate::uint::Encoding;
+    use crate::{ProductionParams, SchemeParams, TestParams, ThresholdKeyShare};
 
     #[test]
     fn key_share_centralized() {
@@ -535,4 +1824,571 @@ mod tests {
         let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, Some(&sk));
         assert_eq!(&shares[0].verifying_key(), sk.verifying_key());
     }
+
+    #[test]
+    fn key_share_seed_centralized() {
+        let sk = SigningKey::random(&mut OsRng);
+        let seeds = KeyShareSeed::new_centralized(&mut OsRng, 3, Some(&sk));
+        assert_eq!(KeyShareSeed::reconstruct_signing_key(&seeds), sk);
+        assert!(seeds
+            .iter()
+            .all(|seed| seed.verifying_key() == Ok(*sk.verifying_key())));
+        assert!(seeds.iter().all(|seed| seed.num_parties() == 3));
+
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, Some(&sk));
+        let mut reversed = shares.to_vec();
+        reversed.reverse();
+        assert_eq!(KeyShare::reconstruct_signing_key(&reversed), sk);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match its public share")]
+    fn reconstruct_signing_key_inconsistent() {
+        let mut seeds = KeyShareSeed::new_centralized(&mut OsRng, 3, None).into_vec();
+        seeds[1].secret_share = seeds[1].secret_share + Scalar::ONE;
+        KeyShareSeed::reconstruct_signing_key(&seeds);
+    }
+
+    #[test]
+    fn key_share_consistency() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        for share in shares.iter() {
+            assert!(share.verify_consistency().is_consistent());
+        }
+
+        let mut share = shares[0].clone();
+        share.secret_share = share.secret_share + Scalar::ONE;
+        let report = share.verify_consistency();
+        assert!(!report.secret_share_matches);
+        assert!(report.paillier_key_matches);
+        assert!(!report.is_consistent());
+
+        let mut share = shares[0].clone();
+        share.secret_aux = shares[1].secret_aux.clone();
+        let report = share.verify_consistency();
+        assert!(report.secret_share_matches);
+        assert!(!report.paillier_key_matches);
+
+        let mut share = shares[0].clone();
+        let mut public_aux = share.public_aux.to_vec();
+        public_aux[2].rp_params.base = public_aux[2].rp_params.power;
+        public_aux[2].rp_params.power = *public_aux[2].paillier_pk.modulus();
+        share.public_aux = public_aux.into();
+        let report = share.verify_consistency();
+        assert_eq!(&*report.invalid_public_aux, &[2]);
+
+        let mut share = shares[0].clone();
+        let mut public_shares = share.public_shares.to_vec();
+        public_shares[1] = -(public_shares[0] + public_shares[2]);
+        share.public_shares = public_shares.into();
+        let report = share.verify_consistency();
+        assert!(report.secret_share_matches);
+        assert!(!report.verifying_key_valid);
+
+        let mut share = shares[0].clone();
+        share.index = PartyIdx::from_usize(3);
+        let report = share.verify_consistency();
+        assert!(!report.lengths_match);
+        assert!(!report.secret_share_matches);
+    }
+
+    #[test]
+    fn update_increments_epoch() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let share = shares[0].clone();
+        assert_eq!(share.epoch(), 0);
+
+        // A trivial change, keeping the secret and the auxiliary info the same.
+        let change = KeyShareChange {
+            index: share.index,
+            secret_share_change: Scalar::ZERO,
+            public_share_changes: share
+                .public_shares
+                .iter()
+                .map(|_| Point::IDENTITY)
+                .collect(),
+            secret_aux: share.secret_aux.clone(),
+            public_aux: share.public_aux.clone(),
+        };
+        let updated = share.clone().try_update(change).unwrap();
+
+        assert_eq!(updated.epoch(), 1);
+        assert_eq!(updated.verifying_key(), share.verifying_key());
+        // The epoch is a part of the share set ID, so the updated share
+        // cannot be used in the same session with the old ones.
+        assert_ne!(updated.share_set_id, share.share_set_id);
+        // But it belongs to the same wallet.
+        assert_eq!(updated.fingerprint(), share.fingerprint());
+    }
+
+    #[test]
+    fn try_update_checks_change() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let share = shares[1].clone();
+        let trivial_change = || KeyShareChange {
+            index: share.index,
+            secret_share_change: Scalar::ZERO,
+            public_share_changes: share
+                .public_shares
+                .iter()
+                .map(|_| Point::IDENTITY)
+                .collect(),
+            secret_aux: share.secret_aux.clone(),
+            public_aux: share.public_aux.clone(),
+        };
+
+        // A change made for another party.
+        let mut change = trivial_change();
+        change.index = PartyIdx::from_usize(2);
+        assert_eq!(
+            share.clone().try_update(change).unwrap_err(),
+            KeyShareError::PartyIndexMismatch {
+                expected: 1,
+                found: 2
+            }
+        );
+
+        // A change consistent with the secret share, but not summing up to zero.
+        let mut change = trivial_change();
+        change.secret_share_change = Scalar::ONE;
+        change.public_share_changes[1] = Point::GENERATOR;
+        assert_eq!(
+            share.clone().try_update(change).unwrap_err(),
+            KeyShareError::VerifyingKeyChanged
+        );
+
+        // The secret auxiliary info of another party.
+        let mut change = trivial_change();
+        change.secret_aux = shares[0].secret_aux.clone();
+        assert_eq!(
+            share.clone().try_update(change).unwrap_err(),
+            KeyShareError::AuxInfoMismatch
+        );
+
+        // The public auxiliary info in the wrong order.
+        let mut change = trivial_change();
+        change.public_aux.swap(1, 2);
+        assert_eq!(
+            share.clone().try_update(change).unwrap_err(),
+            KeyShareError::AuxInfoMismatch
+        );
+
+        assert!(share.clone().try_update(trivial_change()).is_ok());
+    }
+
+    #[test]
+    fn fingerprint() {
+        let sk = SigningKey::random(&mut OsRng);
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, Some(&sk));
+        let fingerprint = shares[0].fingerprint();
+        assert!(shares
+            .iter()
+            .all(|share| share.fingerprint() == fingerprint));
+
+        // Shares of the same key for a different set of parties.
+        let other_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 4, Some(&sk));
+        assert_ne!(other_shares[0].fingerprint(), fingerprint);
+        // The threshold is taken into account.
+        assert_eq!(
+            ThresholdKeyShare::from_key_share(&shares[0]).fingerprint(),
+            fingerprint
+        );
+        let threshold_shares =
+            ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 2, 3, Some(&sk));
+        assert_ne!(threshold_shares[0].fingerprint(), fingerprint);
+
+        assert_eq!(fingerprint.to_string().len(), 64);
+        assert!(format!("{:?}", shares[0]).contains(&fingerprint.to_string()));
+        let serialized = bincode::serialize(&fingerprint).unwrap();
+        assert_eq!(
+            bincode::deserialize::<KeyShareFingerprint>(&serialized).unwrap(),
+            fingerprint
+        );
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn presignature_journal() {
+        use alloc::collections::BTreeSet;
+
+        use super::{PresignatureId, PresignatureJournal, PresigningData};
+        use crate::make_signing_session_with_journal;
+        use crate::sessions::Bincode;
+        use crate::testnet::make_signers;
+
+        let (signers, verifiers) = make_signers(3, || SigningKey::random(&mut OsRng));
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let presigning_datas = PresigningData::new_centralized(&mut OsRng, &key_shares);
+
+        // All the parties refer to the presignature by the same id.
+        let id = presigning_datas[0].id();
+        assert!(presigning_datas.iter().all(|data| data.id() == id));
+        let other_datas = PresigningData::new_centralized(&mut OsRng, &key_shares);
+        assert_ne!(other_datas[0].id(), id);
+        assert_eq!(id.to_string().len(), 64);
+        let serialized = bincode::serialize(&id).unwrap();
+        assert_eq!(
+            bincode::deserialize::<PresignatureId>(&serialized).unwrap(),
+            id
+        );
+
+        let mut journal = BTreeSet::new();
+        let make_session = |presigning_data, journal: &mut BTreeSet<PresignatureId>| {
+            make_signing_session_with_journal::<_, BackendSignature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signers[0].clone(),
+                &verifiers,
+                &key_shares[0],
+                presigning_data,
+                b"abcdefghijklmnopqrstuvwxyz123456",
+                journal,
+            )
+            .map(|_| ())
+        };
+        make_session(presigning_datas[0].duplicate(), &mut journal).unwrap();
+        assert!(journal.contains(&id));
+        let err = make_session(presigning_datas[0].duplicate(), &mut journal).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Local error: The presignature {id} has already been used")
+        );
+        assert_eq!(journal.mark_used(&id), Ok(false));
+        assert_eq!(journal.mark_used(&other_datas[0].id()), Ok(true));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn presigning_data_reload() {
+        use alloc::collections::BTreeSet;
+
+        use super::PresigningData;
+        use crate::make_signing_session_with_journal;
+        use crate::sessions::Bincode;
+
+        let signer = SigningKey::random(&mut OsRng);
+        let verifiers = [
+            *signer.verifying_key(),
+            *SigningKey::random(&mut OsRng).verifying_key(),
+        ]
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 2, None);
+        let presigning_datas = PresigningData::new_centralized(&mut OsRng, &key_shares);
+        let index = verifiers
+            .iter()
+            .position(|verifier| verifier == signer.verifying_key())
+            .unwrap();
+        let id = presigning_datas[index].id();
+
+        // Stored before the restart...
+        let serialized = bincode::serialize(&presigning_datas[index]).unwrap();
+        // ...and reloaded after it.
+        let reloaded = bincode::deserialize::<PresigningData<TestParams>>(&serialized).unwrap();
+        assert_eq!(reloaded.id(), id);
+        assert_eq!(bincode::serialize(&reloaded).unwrap(), serialized);
+
+        // The data is tied to the scheme parameters.
+        let err =
+            bincode::deserialize::<PresigningData<ProductionParams>>(&serialized).unwrap_err();
+        assert!(err.to_string().contains("expected `synedrion-production`"));
+
+        let mut journal = BTreeSet::new();
+        let make_session = |presigning_data, journal: &mut BTreeSet<_>| {
+            make_signing_session_with_journal::<_, BackendSignature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+                &key_shares[index],
+                presigning_data,
+                b"abcdefghijklmnopqrstuvwxyz123456",
+                journal,
+            )
+            .map(|_| ())
+        };
+        make_session(reloaded, &mut journal).unwrap();
+
+        // A copy restored from the same backup is rejected.
+        let restored = bincode::deserialize::<PresigningData<TestParams>>(&serialized).unwrap();
+        let err = make_session(restored, &mut journal).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Local error: The presignature {id} has already been used")
+        );
+    }
+
+    #[test]
+    fn new_checks_consistency() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let share = shares[1].clone();
+        let seed = KeyShareSeed {
+            secret_share: share.secret_share,
+            public_shares: share.public_shares.clone(),
+            init_id: share.init_id.clone(),
+        };
+        let change = KeyShareChange {
+            index: share.index,
+            secret_share_change: Scalar::ZERO,
+            public_share_changes: share
+                .public_shares
+                .iter()
+                .map(|_| Point::IDENTITY)
+                .collect(),
+            secret_aux: share.secret_aux.clone(),
+            public_aux: share.public_aux.clone(),
+        };
+
+        let new_share = KeyShare::new(seed.clone(), change.clone()).unwrap();
+        assert_eq!(new_share.try_verifying_key(), Ok(share.verifying_key()));
+
+        let mut wrong_index = change.clone();
+        wrong_index.index = PartyIdx::from_usize(0);
+        assert_eq!(
+            KeyShare::new(seed.clone(), wrong_index).unwrap_err(),
+            KeyShareError::SecretShareMismatch
+        );
+
+        let mut out_of_range = change.clone();
+        out_of_range.index = PartyIdx::from_usize(3);
+        assert_eq!(
+            KeyShare::new(seed.clone(), out_of_range).unwrap_err(),
+            KeyShareError::PartyIndexOutOfRange {
+                index: 3,
+                num_parties: 3
+            }
+        );
+
+        let mut fewer_parties = change.clone();
+        fewer_parties.public_aux = share.public_aux[..2].into();
+        assert_eq!(
+            KeyShare::new(seed.clone(), fewer_parties).unwrap_err(),
+            KeyShareError::NumPartiesMismatch
+        );
+
+        let mut identity = change;
+        identity.public_share_changes[0] = -share.verifying_key_as_point();
+        identity.secret_share_change = Scalar::ZERO;
+        assert_eq!(
+            KeyShare::new(seed, identity).unwrap_err(),
+            KeyShareError::IdentityVerifyingKey
+        );
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn derive_child() {
+        use signature::hazmat::PrehashVerifier;
+
+        use crate::bip32::derive_tweak;
+        use crate::cggmp21::signing::{Inputs, Round1};
+        use crate::common::PresigningData;
+        use crate::rounds::{
+            test_utils::{step_result, step_round},
+            FirstRound,
+        };
+
+        // The derivation itself is checked against the BIP32 test vectors in `bip32`.
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let chain_code = ChainCode([7u8; 32]);
+        let child_number = 5;
+        let (tweak, child_chain_code) = derive_tweak(
+            &shares[0].verifying_key_as_point(),
+            &chain_code,
+            child_number,
+        )
+        .unwrap();
+
+        let children = shares
+            .iter()
+            .map(|share| share.derive_child(&chain_code, child_number).unwrap())
+            .collect::<Vec<_>>();
+
+        let child_key = shares[0].verifying_key_as_point() + tweak.mul_by_generator();
+        for (child, chain_code) in children.iter() {
+            assert_eq!(child.verifying_key_as_point(), child_key);
+            assert_eq!(chain_code, &child_chain_code);
+            assert_eq!(child.share_set_id, children[0].0.share_set_id);
+        }
+
+        // The child shares still sign, with signatures verifiable under the child key.
+        let children = children
+            .into_iter()
+            .map(|(child, _)| child)
+            .collect::<Vec<_>>();
+        let presigning_datas = PresigningData::new_centralized(&mut OsRng, &children);
+        let message = Scalar::random(&mut OsRng);
+        let r1 = children
+            .iter()
+            .zip(presigning_datas.into_vec())
+            .enumerate()
+            .map(|(idx, (child, presigning))| {
+                Round1::new(
+                    &mut OsRng,
+                    b"1234567890",
+                    children.len(),
+                    PartyIdx::from_usize(idx),
+                    Inputs {
+                        message,
+                        presigning,
+                        key_share: child.to_precomputed(),
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let signatures = step_result(&mut OsRng, r1a).unwrap();
+        let (signature, _rec_id) = signatures[0].to_backend();
+        children[0]
+            .verifying_key()
+            .verify_prehash(&message.to_bytes(), &signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn key_share_bytes_roundtrip() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let share = &shares[1];
+        let bytes = share.to_bytes();
+
+        // The header: the version, the length-prefixed scheme parameters identifier,
+        // the `u32` number of parties and threshold, and the length-prefixed verifying key.
+        assert_eq!(bytes[0], 2);
+        assert_eq!(bytes[1..9], 14u64.to_le_bytes());
+        assert_eq!(&bytes[9..23], b"synedrion-test");
+        assert_eq!(bytes[23..27], 3u32.to_le_bytes());
+        assert_eq!(bytes[27..31], 3u32.to_le_bytes());
+        assert_eq!(bytes[31..39], 33u64.to_le_bytes());
+        assert_eq!(
+            bytes[39..72],
+            share.verifying_key_as_point().to_compressed_array()[..]
+        );
+
+        // Followed by the `u32` index, and the length-prefixed secret share.
+        assert_eq!(bytes[72..76], 1u32.to_le_bytes());
+        assert_eq!(bytes[76..84], 32u64.to_le_bytes());
+        assert_eq!(bytes[84..116], share.secret_share.to_bytes()[..]);
+
+        let decoded = KeyShare::<TestParams>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.verifying_key(), share.verifying_key());
+        assert_eq!(decoded.share_set_id, share.share_set_id);
+
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&[]).unwrap_err(),
+            KeyShareDecodingError::Empty
+        );
+
+        let mut wrong_version = bytes.to_vec();
+        wrong_version[0] = 1;
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&wrong_version).unwrap_err(),
+            KeyShareDecodingError::UnsupportedVersion(1)
+        );
+
+        // Claim the share belongs to another party.
+        let mut wrong_index = bytes.to_vec();
+        wrong_index[72] = 0;
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&wrong_index).unwrap_err(),
+            KeyShareDecodingError::Inconsistent
+        );
+
+        assert!(matches!(
+            KeyShare::<TestParams>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(KeyShareDecodingError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn key_share_header_mismatch() {
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let bytes = shares[0].to_bytes();
+
+        // A share created with `TestParams` cannot be loaded with other parameters.
+        assert_eq!(
+            KeyShare::<ProductionParams>::from_bytes(&bytes).unwrap_err(),
+            KeyShareDecodingError::SchemeParamsMismatch {
+                expected: "synedrion-production",
+                found: "synedrion-test".into(),
+            }
+        );
+        let error = bincode::deserialize::<KeyShare<ProductionParams>>(&bytes)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("synedrion-test"));
+
+        // Wrong number of parties
+        let mut wrong_num_parties = bytes.to_vec();
+        wrong_num_parties[23] = 4;
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&wrong_num_parties).unwrap_err(),
+            KeyShareDecodingError::Inconsistent
+        );
+
+        // Wrong threshold
+        let mut wrong_threshold = bytes.to_vec();
+        wrong_threshold[27] = 2;
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&wrong_threshold).unwrap_err(),
+            KeyShareDecodingError::Inconsistent
+        );
+
+        // Wrong verifying key
+        let mut wrong_verifying_key = bytes.to_vec();
+        wrong_verifying_key[39..72]
+            .copy_from_slice(&shares[1].public_shares[0].to_compressed_array());
+        assert_eq!(
+            KeyShare::<TestParams>::from_bytes(&wrong_verifying_key).unwrap_err(),
+            KeyShareDecodingError::Inconsistent
+        );
+    }
+
+    #[cfg(feature = "key-refresh")]
+    #[test]
+    fn external_paillier_key() {
+        type Paillier = <TestParams as SchemeParams>::Paillier;
+
+        let sk = SecretKeyPaillier::<Paillier>::random(&mut OsRng);
+        let (p, q) = sk.raw_primes();
+        let (p, q) = (p.to_be_bytes(), q.to_be_bytes());
+        let totient = sk.to_precomputed().totient().as_ref().to_be_bytes();
+
+        let key = ExternalPaillierKey::<TestParams>::new(&mut OsRng, &p, &q, &[7]).unwrap();
+        assert!(key
+            .paillier_sk
+            .matches_public_key(&sk.to_precomputed().public_key().to_minimal()));
+
+        // Leading zeros are allowed, but the values must fit into the integers of the parameters.
+        let mut long_p = [0u8; 1].to_vec();
+        long_p.extend(p.as_ref());
+        assert!(ExternalPaillierKey::<TestParams>::new(&mut OsRng, &long_p, &q, &[7]).is_ok());
+        long_p[0] = 1;
+        assert_eq!(
+            ExternalPaillierKey::<TestParams>::new(&mut OsRng, &long_p, &q, &[7]).unwrap_err(),
+            ExternalPaillierKeyError::InvalidEncoding
+        );
+
+        // The primes must be distinct safe primes of the right size
+        assert_eq!(
+            ExternalPaillierKey::<TestParams>::new(&mut OsRng, &p, &p, &[7]).unwrap_err(),
+            ExternalPaillierKeyError::InvalidPrimes
+        );
+        assert_eq!(
+            ExternalPaillierKey::<TestParams>::new(&mut OsRng, &p, &[11], &[7]).unwrap_err(),
+            ExternalPaillierKeyError::InvalidPrimes
+        );
+
+        // The trapdoor must be in the range `(0, phi(N))`
+        assert_eq!(
+            ExternalPaillierKey::<TestParams>::new(&mut OsRng, &p, &q, &[0]).unwrap_err(),
+            ExternalPaillierKeyError::InvalidTrapdoor
+        );
+        assert_eq!(
+            ExternalPaillierKey::<TestParams>::new(&mut OsRng, &p, &q, totient.as_ref())
+                .unwrap_err(),
+            ExternalPaillierKeyError::InvalidTrapdoor
+        );
+    }
 }
@@ -1,6 +1,10 @@
+use alloc::collections::BTreeSet;
 use alloc::format;
 use core::fmt::Debug;
+use core::ops::RangeInclusive;
 
+#[cfg(feature = "signing")]
+use digest::{consts::U32, Digest};
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
 use signature::{
@@ -8,89 +12,1142 @@ use signature::{
     Keypair,
 };
 
+#[cfg(feature = "signing")]
+use crate::beacon::{BeaconRandomness, BeaconVerifier};
+use crate::bip340::TaprootTweak;
+#[cfg(feature = "signing")]
 use crate::cggmp21::{
-    interactive_signing, key_gen, key_refresh, InteractiveSigningResult, KeyGenResult,
-    KeyRefreshResult, SchemeParams,
+    interactive_signing, presigning, schnorr_signing, signing, AdaptorSigningResult,
+    InteractiveSigningResult, PresigningMode, PresigningPrecompute, PresigningResult,
+    SchnorrSigningResult, SigningResult,
 };
+use crate::cggmp21::{
+    key_export, threshold_refresh, KeyExportResult, SchemeParams, ThresholdRefreshResult,
+};
+#[cfg(feature = "keygen")]
+use crate::cggmp21::{key_gen, key_import, KeyGenResult, KeyImportResult};
+#[cfg(feature = "key-refresh")]
+use crate::cggmp21::{key_refresh, key_refresh::KeyRefreshMode, KeyRefreshResult};
 use crate::common::KeyShare;
+#[cfg(feature = "key-refresh")]
+use crate::common::{ExternalPaillierKey, PregeneratedPaillierKey};
+#[cfg(feature = "signing")]
+use crate::common::{PresignatureJournal, PresigningData};
+#[cfg(feature = "signing")]
+use crate::curve::BackendScalar;
+#[cfg(any(feature = "keygen", feature = "signing"))]
 use crate::curve::Scalar;
-use crate::sessions::{LocalError, Session};
+#[cfg(feature = "keygen")]
+use crate::curve::SigningKey;
+use crate::curve::{Point, VerifyingKey};
+#[cfg(feature = "ed25519")]
+use crate::ed25519::{signing as ed25519_signing, Ed25519KeyShare, Ed25519SigningResult};
+#[cfg(feature = "key-refresh")]
+use crate::paillier::RPSecret;
+#[cfg(feature = "keygen")]
+use crate::rounds::PartyIdx;
+#[cfg(feature = "signing")]
+use crate::rounds::{BatchResult, BatchRound};
+use crate::sessions::{LocalError, MessageFormat, Session};
+use crate::threshold::ThresholdKeyShare;
 
 /// Prehashed message to sign.
 pub type PrehashedMessage = [u8; 32];
 
+pub(crate) fn check_key_share<P, Signer, Verifier>(
+    signer: &Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+) -> Result<(), LocalError>
+where
+    P: SchemeParams,
+    Signer: Keypair<VerifyingKey = Verifier>,
+    Verifier: Ord,
+{
+    check_party_position(
+        signer,
+        verifiers,
+        key_share.num_parties(),
+        key_share.party_index(),
+    )
+    .map_err(|err| LocalError(format!("{} (key share {})", err.0, key_share.fingerprint())))
+}
+
+/// Checks that the signer's position among the verifiers matches the party index in a key share.
+fn check_party_position<Signer, Verifier>(
+    signer: &Signer,
+    verifiers: &BTreeSet<Verifier>,
+    num_parties: usize,
+    party_index: usize,
+) -> Result<(), LocalError>
+where
+    Signer: Keypair<VerifyingKey = Verifier>,
+    Verifier: Ord,
+{
+    if verifiers.len() != num_parties {
+        return Err(LocalError(format!(
+            concat![
+                "Number of verifiers (got: {}) must be equal ",
+                "to the number of parties in the key share (got: {})"
+            ],
+            verifiers.len(),
+            num_parties
+        )));
+    }
+
+    let verifying_key = signer.verifying_key();
+    let position = verifiers
+        .iter()
+        .position(|verifier| verifier == &verifying_key)
+        .ok_or(LocalError(
+            "The given signer's verifying key is not among the verifiers".into(),
+        ))?;
+    if position != party_index {
+        return Err(LocalError(format!(
+            concat![
+                "The signer's position among the verifiers (got: {}) must be equal ",
+                "to the party index in the key share (got: {})"
+            ],
+            position, party_index
+        )));
+    }
+
+    Ok(())
+}
+
+fn beacon_shared_randomness(
+    beacon: &BeaconRandomness,
+    beacon_verifier: &impl BeaconVerifier,
+    accepted_rounds: RangeInclusive<u64>,
+) -> Result<[u8; 32], LocalError> {
+    beacon
+        .shared_randomness(beacon_verifier, accepted_rounds)
+        .map_err(|err| LocalError(format!("Invalid beacon randomness: {err}")))
+}
+
 /// Creates the initial state for the joined KeyGen and KeyRefresh+Auxiliary protocols.
-pub fn make_key_gen_session<P, Sig, Signer, Verifier>(
+///
+/// This is the full KeyGen of the paper: the two protocols are executed in parallel
+/// in the same three rounds, and the session finalizes with a complete [`KeyShare`],
+/// so there is no need to combine a [`KeyShareSeed`](crate::KeyShareSeed)
+/// and a [`KeyShareChange`](crate::KeyShareChange) with [`KeyShare::new`].
+///
+/// The parties are identified by their verifiers; the index of each party
+/// (in particular, the one saved in the resulting [`KeyShare`])
+/// is its verifier's position in the ordered set,
+/// so all the nodes agree on it regardless of how they obtained the set.
+#[cfg(feature = "keygen")]
+pub fn make_key_gen_session<P, Sig, Signer, Verifier, F>(
     rng: &mut impl CryptoRngCore,
     shared_randomness: &[u8],
     signer: Signer,
-    verifiers: &[Verifier],
-) -> Result<Session<KeyGenResult<P>, Sig, Signer, Verifier>, LocalError>
+    verifiers: &BTreeSet<Verifier>,
+) -> Result<Session<KeyGenResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    Session::new::<key_gen::Round1<P>>(rng, shared_randomness, signer, verifiers, 0, ())
+}
+
+/// Creates the initial state for the joined KeyGen and KeyRefresh+Auxiliary protocols,
+/// taking the shared randomness from the output of a randomness beacon.
+///
+/// Returns an error without creating the session if the round of `beacon`
+/// is not within `accepted_rounds` or its signature is not accepted by `beacon_verifier`
+/// (see [`BeaconRandomness::shared_randomness`]).
+/// Otherwise the same as [`make_key_gen_session`].
+#[cfg(feature = "keygen")]
+pub fn make_key_gen_session_with_beacon<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    beacon: &BeaconRandomness,
+    beacon_verifier: &impl BeaconVerifier,
+    accepted_rounds: RangeInclusive<u64>,
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+) -> Result<Session<KeyGenResult<P>, Sig, Signer, Verifier, F>, LocalError>
 where
     Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
     P: SchemeParams + 'static,
     Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
-    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
 {
-    Session::new::<key_gen::Round1<P>>(rng, shared_randomness, signer, verifiers, ())
+    let shared_randomness = beacon_shared_randomness(beacon, beacon_verifier, accepted_rounds)?;
+    make_key_gen_session(rng, &shared_randomness, signer, verifiers)
 }
 
 /// Creates the initial state for the KeyRefresh+Auxiliary protocol.
-pub fn make_key_refresh_session<P, Sig, Signer, Verifier>(
+#[cfg(feature = "key-refresh")]
+pub fn make_key_refresh_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    Session::new::<key_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        0,
+        key_refresh::Inputs::default(),
+    )
+}
+
+/// Creates the initial state for the KeyRefresh+Auxiliary protocol,
+/// taking the shared randomness from the output of a randomness beacon.
+///
+/// Returns an error without creating the session if the beacon output is not accepted,
+/// as in [`make_key_gen_session_with_beacon`].
+/// Otherwise the same as [`make_key_refresh_session`].
+#[cfg(feature = "key-refresh")]
+pub fn make_key_refresh_session_with_beacon<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    beacon: &BeaconRandomness,
+    beacon_verifier: &impl BeaconVerifier,
+    accepted_rounds: RangeInclusive<u64>,
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let shared_randomness = beacon_shared_randomness(beacon, beacon_verifier, accepted_rounds)?;
+    make_key_refresh_session(rng, &shared_randomness, signer, verifiers)
+}
+
+/// Creates the initial state for the KeyRefresh protocol
+/// using a Paillier key generated in advance.
+#[cfg(feature = "key-refresh")]
+pub fn make_key_refresh_session_with_paillier_key<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    paillier_key: PregeneratedPaillierKey<P>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    Session::new::<key_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        0,
+        key_refresh::Inputs {
+            mode: KeyRefreshMode::Full,
+            paillier_key: Some((paillier_key.0, None)),
+            expected_paillier_pks: None,
+        },
+    )
+}
+
+/// Creates the initial state for the KeyRefresh protocol
+/// using a Paillier key and a ring-Pedersen trapdoor generated outside of the library.
+#[cfg(feature = "key-refresh")]
+pub fn make_key_refresh_session_with_external_paillier_key<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    paillier_key: ExternalPaillierKey<P>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    Session::new::<key_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        0,
+        key_refresh::Inputs {
+            mode: KeyRefreshMode::Full,
+            paillier_key: Some((
+                paillier_key.paillier_sk.clone(),
+                Some(RPSecret::new(paillier_key.rp_secret)),
+            )),
+            expected_paillier_pks: None,
+        },
+    )
+}
+
+/// Creates the initial state for the KeyRefresh protocol in the mode where only the auxiliary data
+/// (the Paillier keys and the ring-Pedersen parameters) is replaced,
+/// e.g. after a suspected leak of a Paillier key.
+///
+/// The secret shares are not re-randomized: the `secret_share_change` of the resulting
+/// [`KeyShareChange`](crate::KeyShareChange) is zero, so applying it with [`KeyShare::try_update`]
+/// only replaces the auxiliary data of `key_share`. All the parties must use this mode;
+/// if some of them run a full refresh instead, the session fails in the second round.
+///
+/// If `paillier_key` is `None`, a new Paillier key is generated.
+#[cfg(feature = "key-refresh")]
+pub fn make_aux_refresh_session<P, Sig, Signer, Verifier, F>(
     rng: &mut impl CryptoRngCore,
     shared_randomness: &[u8],
     signer: Signer,
-    verifiers: &[Verifier],
-) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier>, LocalError>
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    paillier_key: Option<PregeneratedPaillierKey<P>>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
 where
     Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
     P: SchemeParams + 'static,
     Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
-    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
 {
-    Session::new::<key_refresh::Round1<P>>(rng, shared_randomness, signer, verifiers, ())
+    check_key_share(&signer, verifiers, key_share)?;
+    Session::new::<key_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        key_refresh::Inputs {
+            mode: KeyRefreshMode::AuxOnly,
+            paillier_key: paillier_key.map(|key| (key.0, None)),
+            expected_paillier_pks: None,
+        },
+    )
+}
+
+/// Creates the initial state for the KeyRefresh protocol in the mode where only the secret shares
+/// are re-randomized, and every party reuses the Paillier key of its `key_share`.
+///
+/// This avoids the generation of the Paillier primes, which takes most of the time of a full refresh,
+/// so it is suitable for frequent proactive refreshes. The other parties check that each party
+/// sends the Paillier key they have in their key shares; the ring-Pedersen parameters
+/// and the El-Gamal keys are still replaced. All the parties must use this mode.
+#[cfg(feature = "key-refresh")]
+pub fn make_share_refresh_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+) -> Result<Session<KeyRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+    Session::new::<key_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        key_refresh::Inputs {
+            mode: KeyRefreshMode::SharesOnly,
+            paillier_key: Some((key_share.secret_aux.paillier_sk.clone(), None)),
+            expected_paillier_pks: Some(
+                key_share
+                    .public_aux
+                    .iter()
+                    .map(|aux| aux.paillier_pk.clone())
+                    .collect(),
+            ),
+        },
+    )
+}
+
+/// Creates the initial state for the KeyImport protocol,
+/// where the party `dealer` splits an existing key into shares and distributes them.
+///
+/// The dealer must provide the `signing_key`, and the other parties must pass `None`.
+/// The other parties must also provide the `expected_verifying_key` of the imported key
+/// (e.g. the one of the wallet being migrated), known to them in advance,
+/// and the session fails with a provable error against the dealer if it deals a different key;
+/// for the dealer it is optional.
+/// All the parties verify that the shares are consistent
+/// and sum up to the key whose knowledge the dealer proves.
+///
+/// Since the dealer knows all the resulting shares,
+/// the returned [`KeyShareSeed`](crate::KeyShareSeed) must be combined
+/// with the result of a KeyRefresh session (see [`make_key_refresh_session`])
+/// via [`KeyShare::new`], after which the dealer's copy of the key can be discarded.
+#[cfg(feature = "keygen")]
+pub fn make_key_import_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    dealer: &Verifier,
+    signing_key: Option<&SigningKey>,
+    expected_verifying_key: Option<&VerifyingKey>,
+) -> Result<Session<KeyImportResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let dealer_idx = verifiers
+        .iter()
+        .position(|verifier| verifier == dealer)
+        .ok_or(LocalError("The dealer is not among the verifiers".into()))?;
+
+    let is_dealer = &signer.verifying_key() == dealer;
+    if is_dealer != signing_key.is_some() {
+        return Err(LocalError(
+            "The signing key must be given by the dealer and only by the dealer".into(),
+        ));
+    }
+    if !is_dealer && expected_verifying_key.is_none() {
+        return Err(LocalError(
+            "The expected verifying key must be given by the parties other than the dealer".into(),
+        ));
+    }
+
+    let inputs = key_import::Inputs {
+        dealer: PartyIdx::from_usize(dealer_idx),
+        secret_key: signing_key.map(|key| Scalar::from(key.as_nonzero_scalar())),
+        expected_verifying_key: expected_verifying_key.map(Point::from_verifying_key),
+    };
+
+    Session::new::<key_import::Round1<P>>(rng, shared_randomness, signer, verifiers, 0, inputs)
+}
+
+/// Creates the initial state for the KeyExport protocol,
+/// where the parties encrypt their shares of the key to the holder of `recipient`.
+///
+/// All the parties of the key share must take part in the session,
+/// so it only succeeds if every one of them authorizes the export.
+/// To export a [`ThresholdKeyShare`](crate::ThresholdKeyShare),
+/// convert it with [`ThresholdKeyShare::to_key_share`](crate::ThresholdKeyShare::to_key_share)
+/// for the quorum of parties authorizing the export.
+///
+/// The resulting [`ExportedKey`](crate::ExportedKey) can be decrypted by the recipient only.
+pub fn make_key_export_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    recipient: &VerifyingKey,
+) -> Result<Session<KeyExportResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    let inputs = key_export::Inputs {
+        key_share: key_share.clone(),
+        recipient: Point::from_verifying_key(recipient),
+    };
+
+    Session::new::<key_export::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Creates the initial state for the threshold KeyRefresh protocol,
+/// where all the holders of a set of [`ThresholdKeyShare`]s jointly re-randomize them,
+/// keeping the verifying key and the threshold.
+///
+/// The parties must hold the shares in the order of their share indices,
+/// that is, the party with the `i`-th verifier must hold the share with the `i`-th smallest index
+/// (as is the case for the shares created by
+/// [`ThresholdKeyShare::from_key_share`](crate::ThresholdKeyShare::from_key_share)).
+///
+/// The resulting [`ThresholdKeyShareChange`](crate::ThresholdKeyShareChange)
+/// is applied with [`ThresholdKeyShare::update`],
+/// after which the old shares are useless in combination with the new ones.
+pub fn make_threshold_refresh_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &ThresholdKeyShare<P>,
+) -> Result<Session<ThresholdRefreshResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let inputs = threshold_refresh::Inputs {
+        key_share: key_share.clone(),
+    };
+
+    Session::new::<threshold_refresh::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
 }
 
 /// Creates the initial state for the joined Presigning and Signing protocols.
-pub fn make_interactive_signing_session<P, Sig, Signer, Verifier>(
+#[cfg(feature = "signing")]
+pub fn make_interactive_signing_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    prehashed_message: &PrehashedMessage,
+) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    let scalar_message = Scalar::from_reduced_bytes(prehashed_message);
+
+    let inputs = interactive_signing::Inputs {
+        key_share: key_share.clone(),
+        message: scalar_message,
+        adaptor_point: None,
+        presigning_precompute: None,
+    };
+
+    Session::new::<interactive_signing::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Creates the initial state for the joined Presigning and Signing protocols,
+/// taking the shared randomness from the output of a randomness beacon.
+///
+/// Returns an error without creating the session if the beacon output is not accepted,
+/// as in [`make_key_gen_session_with_beacon`].
+/// Otherwise the same as [`make_interactive_signing_session`].
+#[cfg(feature = "signing")]
+#[allow(clippy::too_many_arguments)]
+pub fn make_interactive_signing_session_with_beacon<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    beacon: &BeaconRandomness,
+    beacon_verifier: &impl BeaconVerifier,
+    accepted_rounds: RangeInclusive<u64>,
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    prehashed_message: &PrehashedMessage,
+) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let shared_randomness = beacon_shared_randomness(beacon, beacon_verifier, accepted_rounds)?;
+    make_interactive_signing_session(
+        rng,
+        &shared_randomness,
+        signer,
+        verifiers,
+        key_share,
+        prehashed_message,
+    )
+}
+
+/// Creates the initial state for the joined Presigning and Signing protocols,
+/// with the values of the first Presigning round computed in advance,
+/// so that the session takes less time once the message to sign is known.
+///
+/// Otherwise the same as [`make_interactive_signing_session`]
+/// with the key share `precompute` was created for.
+#[cfg(feature = "signing")]
+pub fn make_interactive_signing_session_with_precompute<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    precompute: PresigningPrecompute<P>,
+    prehashed_message: &PrehashedMessage,
+) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let key_share = precompute.key_share().clone();
+    check_key_share(&signer, verifiers, &key_share)?;
+
+    let inputs = interactive_signing::Inputs {
+        key_share: key_share.clone(),
+        message: Scalar::from_reduced_bytes(prehashed_message),
+        adaptor_point: None,
+        presigning_precompute: Some(precompute),
+    };
+
+    Session::new::<interactive_signing::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Creates the initial state for the joined Presigning and Signing protocols,
+/// producing a signature for the verifying key `X + tweak * G`,
+/// where `X` is the verifying key of `key_share`.
+///
+/// The tweak is applied to the key share for this session only
+/// (the share of the first party absorbs it, and the public shares are adjusted accordingly),
+/// so that, e.g., a separate key for each invoice can be derived from public data
+/// without storing a derived key share.
+/// All the parties must use the same tweak.
+#[cfg(feature = "signing")]
+pub fn make_interactive_signing_session_with_tweak<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    tweak: &BackendScalar,
+    prehashed_message: &PrehashedMessage,
+) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let tweaked_key_share = key_share.tweaked(&Scalar::from_backend(*tweak));
+    if tweaked_key_share.verifying_key_as_point() == Point::IDENTITY {
+        return Err(LocalError("The tweaked key is the identity point".into()));
+    }
+    make_interactive_signing_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        &tweaked_key_share,
+        prehashed_message,
+    )
+}
+
+/// Creates the initial state for the joined Presigning and Signing protocols,
+/// signing the message hashed with `digest`.
+///
+/// As in [`DigestSigner`](signature::DigestSigner), `digest` is the hasher
+/// that has already been updated with the message (e.g. `Sha256::new_with_prefix(message)`);
+/// the resulting signature is for the prehash it outputs.
+#[cfg(feature = "signing")]
+pub fn make_interactive_signing_session_with_digest<P, D, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    digest: D,
+) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    D: Digest<OutputSize = U32>,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let prehashed_message: PrehashedMessage = digest.finalize().into();
+    make_interactive_signing_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share,
+        &prehashed_message,
+    )
+}
+
+/// Creates the initial state for signing several messages at once,
+/// executing the joined Presigning and Signing protocols for each of them in parallel.
+///
+/// This takes the same number of rounds as signing a single message,
+/// with the messages of all the instances packed together.
+/// The resulting signatures are returned in the order of `prehashed_messages`.
+#[cfg(feature = "signing")]
+#[allow(clippy::type_complexity)]
+pub fn make_batch_interactive_signing_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    prehashed_messages: &[PrehashedMessage],
+) -> Result<Session<BatchResult<InteractiveSigningResult<P>>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    let inputs = prehashed_messages
+        .iter()
+        .map(|message| interactive_signing::Inputs {
+            key_share: key_share.clone(),
+            message: Scalar::from_reduced_bytes(message),
+            adaptor_point: None,
+            presigning_precompute: None,
+        })
+        .collect();
+
+    Session::new::<BatchRound<interactive_signing::Round1<P>>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Creates the initial state for the joined Presigning and Signing protocols
+/// producing an [`AdaptorSignature`](crate::AdaptorSignature) for `adaptor_point`.
+///
+/// The result is not a valid signature of the message; it can be checked by anyone
+/// with [`AdaptorSignature::verify`](crate::AdaptorSignature::verify),
+/// and completed into one by the holder of the secret key of `adaptor_point`.
+#[cfg(feature = "signing")]
+pub fn make_adaptor_signing_session<P, Sig, Signer, Verifier, F>(
     rng: &mut impl CryptoRngCore,
     shared_randomness: &[u8],
     signer: Signer,
-    verifiers: &[Verifier],
+    verifiers: &BTreeSet<Verifier>,
     key_share: &KeyShare<P>,
     prehashed_message: &PrehashedMessage,
-) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier>, LocalError>
+    adaptor_point: &VerifyingKey,
+) -> Result<Session<AdaptorSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    let inputs = interactive_signing::Inputs {
+        key_share: key_share.clone(),
+        message: Scalar::from_reduced_bytes(prehashed_message),
+        adaptor_point: Some(Point::from_verifying_key(adaptor_point)),
+        presigning_precompute: None,
+    };
+
+    Session::new::<interactive_signing::Round1<P, AdaptorSigningResult<P>>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Creates the initial state for the Presigning protocol.
+///
+/// The resulting [`PresigningData`] can be stored and later used
+/// in a single-round signing session created with [`make_signing_session`].
+///
+/// `mode` selects between the fast protocol and the one with identifiable abort
+/// (see [`PresigningMode`]); all the parties must use the same one.
+#[cfg(feature = "signing")]
+pub fn make_presigning_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    mode: PresigningMode,
+) -> Result<Session<PresigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    new_presigning_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        mode,
+        presigning::Inputs::new(key_share.clone()),
+    )
+}
+
+/// Creates the initial state for the Presigning protocol,
+/// taking the shared randomness from the output of a randomness beacon.
+///
+/// Returns an error without creating the session if the beacon output is not accepted,
+/// as in [`make_key_gen_session_with_beacon`].
+/// Otherwise the same as [`make_presigning_session`].
+#[cfg(feature = "signing")]
+#[allow(clippy::too_many_arguments)]
+pub fn make_presigning_session_with_beacon<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    beacon: &BeaconRandomness,
+    beacon_verifier: &impl BeaconVerifier,
+    accepted_rounds: RangeInclusive<u64>,
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    mode: PresigningMode,
+) -> Result<Session<PresigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
 where
     Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
     P: SchemeParams + 'static,
     Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
-    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
 {
-    // TODO (#68): check that key share party index corresponds to the signer's position
-    // among the verifiers
-    if verifiers.len() != key_share.num_parties() {
+    let shared_randomness = beacon_shared_randomness(beacon, beacon_verifier, accepted_rounds)?;
+    make_presigning_session(rng, &shared_randomness, signer, verifiers, key_share, mode)
+}
+
+/// Creates the initial state for the Presigning protocol,
+/// with the values of the first round computed in advance.
+///
+/// Otherwise the same as [`make_presigning_session`]
+/// with the key share `precompute` was created for.
+#[cfg(feature = "signing")]
+pub fn make_presigning_session_with_precompute<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    precompute: PresigningPrecompute<P>,
+    mode: PresigningMode,
+) -> Result<Session<PresigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, precompute.key_share())?;
+
+    new_presigning_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        precompute.key_share().epoch(),
+        mode,
+        presigning::Inputs::with_precompute(precompute),
+    )
+}
+
+/// Creates the initial state for the Presigning protocol in the audit mode,
+/// which is otherwise the same as [`make_presigning_session`].
+///
+/// The resulting [`PresigningData`] carries a [`NonceAuditRecord`](crate::NonceAuditRecord)
+/// (see [`PresigningData::audit_record`]) with this party's contributions to the nonce
+/// encrypted to `auditor_key`. The holder of the corresponding secret key can check
+/// the records of all the parties with
+/// [`NonceAuditRecord::audit`](crate::NonceAuditRecord::audit).
+///
+/// **Warning:** the auditor learns the nonce, and therefore the signing key
+/// once a signature is made with this presigning data. Intended for testing and certification.
+#[cfg(all(feature = "signing", feature = "testing"))]
+pub fn make_presigning_session_with_audit<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    mode: PresigningMode,
+    auditor_key: &VerifyingKey,
+) -> Result<Session<PresigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    new_presigning_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        mode,
+        presigning::Inputs {
+            auditor_key: Some(Point::from_verifying_key(auditor_key)),
+            ..presigning::Inputs::new(key_share.clone())
+        },
+    )
+}
+
+#[cfg(feature = "signing")]
+fn new_presigning_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    epoch: u64,
+    mode: PresigningMode,
+    inputs: presigning::Inputs<P>,
+) -> Result<Session<PresigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    match mode {
+        PresigningMode::Fast => Session::new::<presigning::Round1<P>>(
+            rng,
+            shared_randomness,
+            signer,
+            verifiers,
+            epoch,
+            inputs,
+        ),
+        PresigningMode::IdentifiableAbort => Session::new::<presigning::identifiable::Round1<P>>(
+            rng,
+            shared_randomness,
+            signer,
+            verifiers,
+            epoch,
+            inputs,
+        ),
+    }
+}
+
+/// Creates the initial state for the Signing protocol,
+/// using the presigning data produced by a session created with [`make_presigning_session`].
+///
+/// The signing itself takes a single round.
+/// Each [`PresigningData`] object must be used to sign only one message.
+#[cfg(feature = "signing")]
+pub fn make_signing_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    presigning_data: PresigningData<P>,
+    prehashed_message: &PrehashedMessage,
+) -> Result<Session<SigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    if presigning_data.num_parties() != key_share.num_parties() {
         return Err(LocalError(format!(
             concat![
-                "Number of verifiers (got: {}) must be equal ",
+                "Number of parties in the presigning data (got: {}) must be equal ",
                 "to the number of parties in the key share (got: {})"
             ],
-            verifiers.len(),
+            presigning_data.num_parties(),
             key_share.num_parties()
         )));
     }
 
-    let scalar_message = Scalar::from_reduced_bytes(prehashed_message);
+    let inputs = signing::Inputs {
+        message: Scalar::from_reduced_bytes(prehashed_message),
+        presigning: presigning_data,
+        key_share: key_share.to_precomputed(),
+    };
 
-    let inputs = interactive_signing::Inputs {
+    Session::new::<signing::Round1<P>>(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share.epoch(),
+        inputs,
+    )
+}
+
+/// Same as [`make_signing_session`], but first records the [`id`](PresigningData::id)
+/// of `presigning_data` in `journal`, failing if it was recorded before.
+///
+/// The id is recorded before the session is created,
+/// so a presignature is also considered used if the creation fails afterwards.
+#[cfg(feature = "signing")]
+#[allow(clippy::too_many_arguments)]
+pub fn make_signing_session_with_journal<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    presigning_data: PresigningData<P>,
+    prehashed_message: &PrehashedMessage,
+    journal: &mut impl PresignatureJournal,
+) -> Result<Session<SigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    let id = presigning_data.id();
+    let newly_used = journal.mark_used(&id).map_err(|err| {
+        LocalError(format!(
+            "Failed to record the presignature {id} as used: {err}"
+        ))
+    })?;
+    if !newly_used {
+        return Err(LocalError(format!(
+            "The presignature {id} has already been used"
+        )));
+    }
+
+    make_signing_session(
+        rng,
+        shared_randomness,
+        signer,
+        verifiers,
+        key_share,
+        presigning_data,
+        prehashed_message,
+    )
+}
+
+/// Creates the initial state for the Schnorr signing protocol,
+/// producing a BIP340 signature of `prehashed_message` with the shared key.
+///
+/// If `tweak` is given, the signature is made with the Taproot output key
+/// obtained by tweaking the x-only shared key as in BIP341;
+/// otherwise, with the x-only shared key itself.
+#[cfg(feature = "signing")]
+pub fn make_schnorr_signing_session<P, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &KeyShare<P>,
+    prehashed_message: &PrehashedMessage,
+    tweak: Option<TaprootTweak>,
+) -> Result<Session<SchnorrSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    P: SchemeParams + 'static,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_key_share(&signer, verifiers, key_share)?;
+
+    let inputs = schnorr_signing::Inputs {
         key_share: key_share.clone(),
-        message: scalar_message,
+        message: *prehashed_message,
+        tweak,
     };
 
-    Session::new::<interactive_signing::Round1<P>>(
+    Session::new::<schnorr_signing::Round1<P>>(
         rng,
         shared_randomness,
         signer,
         verifiers,
+        key_share.epoch(),
         inputs,
     )
 }
+
+/// Creates the initial state for the threshold Ed25519 signing protocol,
+/// producing an RFC 8032 signature of `message` with the shared key.
+///
+/// The key share is obtained from an [`Ed25519ThresholdKeyShare`](crate::Ed25519ThresholdKeyShare)
+/// for the share indices of the parties taking part in the session.
+#[cfg(feature = "ed25519")]
+pub fn make_ed25519_signing_session<Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    shared_randomness: &[u8],
+    signer: Signer,
+    verifiers: &BTreeSet<Verifier>,
+    key_share: &Ed25519KeyShare,
+    message: &[u8],
+) -> Result<Session<Ed25519SigningResult, Sig, Signer, Verifier, F>, LocalError>
+where
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: PrehashVerifier<Sig> + Debug + Clone + Ord + Serialize,
+    F: MessageFormat,
+{
+    check_party_position(
+        &signer,
+        verifiers,
+        key_share.num_parties(),
+        key_share.party_index(),
+    )?;
+
+    let inputs = ed25519_signing::Inputs {
+        key_share: key_share.clone(),
+        message: message.into(),
+    };
+
+    // Ed25519 key shares are not refreshed, so there is only one epoch.
+    Session::new::<ed25519_signing::Round1>(rng, shared_randomness, signer, verifiers, 0, inputs)
+}
@@ -1,12 +1,21 @@
 //! This module is an adapter to the ECC backend.
 //! `elliptic_curves` has a somewhat unstable API,
 //! and we isolate all the related logic here.
+//! The rest of the crate must not refer to the backend crate directly,
+//! so that it could be switched by only changing this module.
+//! The backend is `k256` (secp256k1), or `p256` (NIST P-256) if the `p256` feature is enabled.
 // TODO (#27): make the library generic over the curve
 
 mod arithmetic;
 mod ecdsa;
 
+#[cfg(not(feature = "p256"))]
+use k256 as backend;
+#[cfg(feature = "p256")]
+use p256 as backend;
+
 pub(crate) use arithmetic::ORDER;
 
-pub use self::ecdsa::RecoverableSignature;
-pub(crate) use arithmetic::{Curve, Point, Scalar};
+pub(crate) use self::ecdsa::BackendSignature;
+pub use self::ecdsa::{RecoverableSignature, SigningKey, VerifyingKey};
+pub(crate) use arithmetic::{BackendPoint, BackendScalar, Curve, Point, Scalar};
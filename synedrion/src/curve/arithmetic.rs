@@ -1,38 +1,52 @@
+use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::String;
 use alloc::{vec, vec::Vec};
 use core::default::Default;
 use core::ops::{Add, Mul, Neg, Sub};
 
-use digest::Digest;
-use k256::elliptic_curve::group::ff::PrimeField;
-use k256::elliptic_curve::{
+use super::backend::ecdsa::VerifyingKey;
+use super::backend::elliptic_curve::group::ff::PrimeField;
+use super::backend::elliptic_curve::{
     bigint::U256, // Note that this type is different from typenum::U256
     generic_array::typenum::marker_traits::Unsigned,
-    generic_array::GenericArray,
     ops::Reduce,
     point::AffineCoordinates,
     sec1::{EncodedPoint, FromEncodedPoint, ModulusSize, ToEncodedPoint},
-    subtle::{Choice, ConditionallySelectable, CtOption},
+    subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption},
     Curve as _,
     Field,
     FieldBytesSize,
     NonZeroScalar,
 };
-use k256::{ecdsa::VerifyingKey, Secp256k1};
+use digest::{Digest, XofReader};
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
 
-use crate::tools::hashing::{Chain, Hashable, HashableType};
+use crate::tools::hashing::{Chain, HashInto, Hashable, HashableType};
 use crate::tools::serde_bytes;
+use crate::tools::serialized_size::{bytes_size, MaxSerializedSize};
 
-pub(crate) type Curve = Secp256k1;
-pub(crate) type BackendScalar = k256::Scalar;
-pub(crate) type BackendPoint = k256::ProjectivePoint;
-pub(crate) type CompressedPointSize =
-    <FieldBytesSize<Secp256k1> as ModulusSize>::CompressedPointSize;
+#[cfg(not(feature = "p256"))]
+pub(crate) type Curve = super::backend::Secp256k1;
+#[cfg(feature = "p256")]
+pub(crate) type Curve = super::backend::NistP256;
+pub(crate) type BackendScalar = super::backend::Scalar;
+pub(crate) type BackendPoint = super::backend::ProjectivePoint;
+pub(crate) type CompressedPointSize = <FieldBytesSize<Curve> as ModulusSize>::CompressedPointSize;
 
-pub(crate) const ORDER: U256 = Secp256k1::ORDER;
+pub(crate) const ORDER: U256 = Curve::ORDER;
+
+impl HashInto for Scalar {
+    fn from_reader(reader: &mut impl XofReader) -> Self {
+        // Same as in `from_digest`, we take 256 bits and reduce them modulo the curve order,
+        // which has a negligible bias for secp256k1 (and a small but acceptable one for P-256).
+        let mut bytes = [0u8; 32];
+        reader.read(&mut bytes);
+        Self::from_reduced_bytes(&bytes)
+    }
+}
 
 impl HashableType for Curve {
     fn chain_type<C: Chain>(digest: C) -> C {
@@ -52,7 +66,7 @@ impl Scalar {
     }
 
     pub fn random_nonzero(rng: &mut impl CryptoRngCore) -> Self {
-        Self(*NonZeroScalar::<Secp256k1>::random(rng).as_ref())
+        Self(*NonZeroScalar::<Curve>::random(rng).as_ref())
     }
 
     pub fn mul_by_generator(&self) -> Point {
@@ -63,7 +77,7 @@ impl Scalar {
         self.0.invert().map(Self)
     }
 
-    pub fn from_digest(d: impl Digest<OutputSize = FieldBytesSize<Secp256k1>>) -> Self {
+    pub fn from_digest(d: impl Digest<OutputSize = FieldBytesSize<Curve>>) -> Self {
         // There's currently no way to make the required digest output size
         // depend on the target scalar size, so we are hardcoding it to 256 bit
         // (that is, equal to the scalar size).
@@ -73,31 +87,33 @@ impl Scalar {
     /// Convert a 32-byte hash digest into a scalar as per SEC1:
     /// <https://www.secg.org/sec1-v2.pdf< Section 4.1.3 steps 5-6 page 45
     ///
-    /// SEC1 specifies to subtract the secp256k1 modulus when the byte array
-    /// is larger than the modulus.
+    /// SEC1 specifies to subtract the curve order when the byte array
+    /// is larger than the order.
     pub fn from_reduced_bytes(bytes: &[u8; 32]) -> Self {
-        let arr = GenericArray::<u8, FieldBytesSize<Secp256k1>>::from(*bytes);
+        let arr = super::backend::FieldBytes::from(*bytes);
         Self(<BackendScalar as Reduce<U256>>::reduce_bytes(&arr))
     }
 
-    pub fn to_bytes(self) -> k256::FieldBytes {
+    pub fn to_bytes(self) -> super::backend::FieldBytes {
         self.0.to_bytes()
     }
 
     pub fn repr_len() -> usize {
-        <FieldBytesSize<Secp256k1> as Unsigned>::to_usize()
+        <FieldBytesSize<Curve> as Unsigned>::to_usize()
     }
 
     pub(crate) fn to_backend(self) -> BackendScalar {
         self.0
     }
 
+    pub(crate) fn from_backend(scalar: BackendScalar) -> Self {
+        Self(scalar)
+    }
+
     pub(crate) fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
-        let arr =
-            GenericArray::<u8, FieldBytesSize<Secp256k1>>::from_exact_iter(bytes.iter().cloned())
-                .ok_or("Invalid length of a curve scalar")?;
+        let arr = <[u8; 32]>::try_from(bytes).map_err(|_| "Invalid length of a curve scalar")?;
 
-        BackendScalar::from_repr_vartime(arr)
+        BackendScalar::from_repr_vartime(arr.into())
             .map(Self)
             .ok_or_else(|| "Invalid curve scalar representation".into())
     }
@@ -124,8 +140,8 @@ impl<'a> TryFrom<&'a [u8]> for Scalar {
     }
 }
 
-impl From<&NonZeroScalar<Secp256k1>> for Scalar {
-    fn from(val: &NonZeroScalar<Secp256k1>) -> Self {
+impl From<&NonZeroScalar<Curve>> for Scalar {
+    fn from(val: &NonZeroScalar<Curve>) -> Self {
         Self(*val.as_ref())
     }
 }
@@ -136,6 +152,12 @@ impl ConditionallySelectable for Scalar {
     }
 }
 
+impl ConstantTimeEq for Scalar {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
 impl Serialize for Scalar {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serde_bytes::as_hex::serialize(&self.to_bytes(), serializer)
@@ -148,9 +170,21 @@ impl<'de> Deserialize<'de> for Scalar {
     }
 }
 
+impl MaxSerializedSize for Scalar {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        bytes_size(Self::repr_len())
+    }
+}
+
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl Hashable for Scalar {
     fn chain<C: Chain>(&self, digest: C) -> C {
-        digest.chain_constant_sized_bytes(&self.to_bytes().as_slice())
+        digest.chain_constant_sized_bytes(&self.to_bytes())
     }
 }
 
@@ -171,25 +205,119 @@ impl Point {
         VerifyingKey::from_affine(self.0.to_affine()).ok()
     }
 
+    pub(crate) fn from_verifying_key(key: &VerifyingKey) -> Self {
+        Self(key.as_affine().into())
+    }
+
+    /// Returns `true` if the y-coordinate of the point is even
+    /// (which is the case for the points represented by x-only keys in BIP340).
+    pub(crate) fn has_even_y(&self) -> bool {
+        !bool::from(self.0.to_affine().y_is_odd())
+    }
+
+    /// Returns the big-endian bytes of the x-coordinate of the point.
+    pub(crate) fn x_only_bytes(&self) -> [u8; 32] {
+        self.0.to_affine().x().into()
+    }
+
     pub(crate) fn try_from_compressed_bytes(bytes: &[u8]) -> Result<Self, String> {
-        let ep = EncodedPoint::<Secp256k1>::from_bytes(bytes).map_err(|err| format!("{err}"))?;
+        let ep = EncodedPoint::<Curve>::from_bytes(bytes).map_err(|err| format!("{err}"))?;
+        // Only one encoding of each point is accepted, so that the serialization is canonical.
+        if !ep.is_compressed() {
+            return Err("Expected a compressed curve point".into());
+        }
+        Self::try_from_encoded_point(&ep)
+    }
+
+    pub(crate) fn try_from_uncompressed_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let ep = EncodedPoint::<Curve>::from_bytes(bytes).map_err(|err| format!("{err}"))?;
+        if ep.is_compressed() || ep.is_identity() {
+            return Err("Expected an uncompressed curve point".into());
+        }
+        Self::try_from_encoded_point(&ep)
+    }
+
+    /// Returns the SEC1 encoding of the point
+    /// (a single zero byte for the identity, regardless of `compress`).
+    pub(crate) fn to_sec1_bytes(self, compress: bool) -> Box<[u8]> {
+        self.0
+            .to_affine()
+            .to_encoded_point(compress)
+            .as_bytes()
+            .into()
+    }
 
+    #[cfg(feature = "tss-lib")]
+    pub(crate) fn try_from_affine_coordinates(x: &[u8; 32], y: &[u8; 32]) -> Result<Self, String> {
+        let ep = EncodedPoint::<Curve>::from_affine_coordinates(x.into(), y.into(), false);
+        Self::try_from_encoded_point(&ep)
+    }
+
+    fn try_from_encoded_point(ep: &EncodedPoint<Curve>) -> Result<Self, String> {
         // Unwrap CtOption into Option
-        let cp_opt: Option<BackendPoint> = BackendPoint::from_encoded_point(&ep).into();
+        let cp_opt: Option<BackendPoint> = BackendPoint::from_encoded_point(ep).into();
         cp_opt
             .map(Self)
             .ok_or_else(|| "Invalid curve point representation".into())
     }
 
-    pub(crate) fn to_compressed_array(self) -> GenericArray<u8, CompressedPointSize> {
-        *GenericArray::<u8, CompressedPointSize>::from_slice(
-            self.0.to_affine().to_encoded_point(true).as_bytes(),
-        )
+    pub(crate) fn to_compressed_array(self) -> super::backend::CompressedPoint {
+        let mut arr = super::backend::CompressedPoint::default();
+        arr.copy_from_slice(self.0.to_affine().to_encoded_point(true).as_bytes());
+        arr
     }
 
     pub(crate) fn to_backend(self) -> BackendPoint {
         self.0
     }
+
+    pub(crate) fn from_backend(point: BackendPoint) -> Self {
+        Self(point)
+    }
+
+    /// Returns the sum of the points multiplied by the corresponding scalars.
+    ///
+    /// With the `batch-verify` feature this is a multi-scalar multiplication,
+    /// which is faster than multiplying the points one by one.
+    pub(crate) fn lincomb(points_and_scalars: &[(Point, Scalar)]) -> Self {
+        #[cfg(all(feature = "batch-verify", not(feature = "p256")))]
+        {
+            use k256::elliptic_curve::ops::LinearCombinationExt;
+            let backend = points_and_scalars
+                .iter()
+                .map(|(point, scalar)| (point.0, scalar.0))
+                .collect::<Vec<_>>();
+            Self(BackendPoint::lincomb_ext(backend.as_slice()))
+        }
+        #[cfg(not(all(feature = "batch-verify", not(feature = "p256"))))]
+        {
+            points_and_scalars
+                .iter()
+                .map(|(point, scalar)| point * scalar)
+                .sum()
+        }
+    }
+
+    /// Returns the sum of a fixed number of points multiplied by the corresponding scalars.
+    ///
+    /// Unlike [`lincomb`](Self::lincomb), this is always a multi-scalar multiplication
+    /// (since it does not need to allocate), except for P-256,
+    /// whose backend does not implement it.
+    pub(crate) fn lincomb_array<const N: usize>(points_and_scalars: &[(Point, Scalar); N]) -> Self {
+        #[cfg(not(feature = "p256"))]
+        {
+            use k256::elliptic_curve::ops::LinearCombinationExt;
+            let backend = points_and_scalars.map(|(point, scalar)| (point.0, scalar.0));
+            Self(BackendPoint::lincomb_ext(&backend))
+        }
+        #[cfg(feature = "p256")]
+        {
+            points_and_scalars
+                .iter()
+                .map(|(point, scalar)| point * scalar)
+                .sum()
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a [u8]> for Point {
@@ -211,6 +339,12 @@ impl<'de> Deserialize<'de> for Point {
     }
 }
 
+impl MaxSerializedSize for Point {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        bytes_size(<CompressedPointSize as Unsigned>::to_usize())
+    }
+}
+
 impl Hashable for Point {
     fn chain<C: Chain>(&self, digest: C) -> C {
         let arr = self.to_compressed_array();
@@ -238,6 +372,13 @@ impl Neg for Scalar {
     }
 }
 
+impl Neg for Point {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
 impl Add<Scalar> for Scalar {
     type Output = Scalar;
 
@@ -278,6 +419,14 @@ impl Sub<&Scalar> for &Scalar {
     }
 }
 
+impl Sub<Point> for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point(self.0.sub(&(other.0)))
+    }
+}
+
 impl Mul<Scalar> for Point {
     type Output = Point;
 
@@ -328,7 +477,7 @@ impl Mul<&Scalar> for &Scalar {
 
 impl core::iter::Sum for Scalar {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.reduce(core::ops::Add::add).unwrap_or(Self::ZERO)
+        iter.reduce(Add::add).unwrap_or(Self::ZERO)
     }
 }
 
@@ -340,13 +489,13 @@ impl<'a> core::iter::Sum<&'a Self> for Scalar {
 
 impl core::iter::Product for Scalar {
     fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.reduce(core::ops::Mul::mul).unwrap_or(Self::ONE)
+        iter.reduce(Mul::mul).unwrap_or(Self::ONE)
     }
 }
 
 impl core::iter::Sum for Point {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.reduce(core::ops::Add::add).unwrap_or(Self::IDENTITY)
+        iter.reduce(Add::add).unwrap_or(Self::IDENTITY)
     }
 }
 
@@ -1,6 +1,14 @@
-use k256::ecdsa::{RecoveryId, Signature as BackendSignature, VerifyingKey};
+// `RecoveryId` does not depend on the curve, but only `k256` re-exports it.
+use k256::ecdsa::RecoveryId;
+
+/// The ECDSA signature of the backend crate.
+pub(crate) use super::backend::ecdsa::Signature as BackendSignature;
+
+/// The ECDSA keys of the backend crate.
+pub use super::backend::ecdsa::{SigningKey, VerifyingKey};
 
 use super::arithmetic::{Point, Scalar};
+use crate::PrehashedMessage;
 
 /// A wrapper for a signature and public key recovery info.
 #[derive(Debug, Clone, Copy)]
@@ -41,4 +49,152 @@ impl RecoverableSignature {
     pub fn to_backend(self) -> (BackendSignature, RecoveryId) {
         (self.signature, self.recovery_id)
     }
+
+    /// Recovers the verifying key from the signature of the prehashed message `prehash`.
+    ///
+    /// Returns `None` if the signature is malformed or does not verify with the recovered key.
+    pub fn recover_verifying_key(&self, prehash: &PrehashedMessage) -> Option<VerifyingKey> {
+        VerifyingKey::recover_from_prehash(prehash, &self.signature, self.recovery_id).ok()
+    }
+
+    /// Verifies the signature of the prehashed message `prehash` with `verifying_key`,
+    /// checking that the recovery info points to the same key.
+    pub fn verify(&self, verifying_key: &VerifyingKey, prehash: &PrehashedMessage) -> bool {
+        self.recover_verifying_key(prehash).as_ref() == Some(verifying_key)
+    }
+
+    /// Returns the signature with the `s` component normalized to the lower half of the curve order,
+    /// with the recovery info adjusted accordingly.
+    ///
+    /// The signatures produced by the protocols are already normalized,
+    /// in which case this is a no-op.
+    pub fn normalize_s(self) -> Self {
+        match self.signature.normalize_s() {
+            Some(signature) => Self {
+                signature,
+                // Negating `s` negates the recovered point `R`, flipping the parity of its y-coordinate.
+                recovery_id: RecoveryId::new(
+                    !self.recovery_id.is_y_odd(),
+                    self.recovery_id.is_x_reduced(),
+                ),
+            },
+            None => self,
+        }
+    }
+
+    /// Returns the big-endian `r` and `s` components of the signature, and the Ethereum recovery value `v`.
+    ///
+    /// `v` is `27` or `28` if `chain_id` is `None`, and `chain_id * 2 + 35` or `chain_id * 2 + 36`
+    /// otherwise, as specified by [EIP-155](https://eips.ethereum.org/EIPS/eip-155).
+    ///
+    /// Note that `v` only encodes the parity of the y-coordinate of the point `R`.
+    /// The case of its x-coordinate exceeding the curve order, which Ethereum cannot represent,
+    /// occurs with a negligible probability.
+    pub fn to_rsv(&self, chain_id: Option<u64>) -> ([u8; 32], [u8; 32], u64) {
+        let (r, s) = self.signature.split_bytes();
+        let y_parity = u64::from(self.recovery_id.is_y_odd());
+        let v = match chain_id {
+            Some(chain_id) => chain_id * 2 + 35 + y_parity,
+            None => 27 + y_parity,
+        };
+        (r.into(), s.into(), v)
+    }
+
+    /// Serializes the signature as a 65-byte array `r || s || v` with `v` equal to `27` or `28`,
+    /// as returned by Ethereum's `eth_sign` and accepted by the `ecrecover` precompile.
+    pub fn to_ethereum_bytes(&self) -> [u8; 65] {
+        let (r, s, v) = self.to_rsv(None);
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&r);
+        bytes[32..64].copy_from_slice(&s);
+        // `v` is 27 or 28 without a chain ID.
+        bytes[64] = v as u8;
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::{BackendSignature, RecoverableSignature, RecoveryId, VerifyingKey};
+    use crate::curve::Scalar;
+
+    #[test]
+    fn normalization_and_ethereum_encoding() {
+        let sk = Scalar::random_nonzero(&mut OsRng);
+        let vkey = sk.mul_by_generator();
+        let message = Scalar::random(&mut OsRng);
+        let nonce = Scalar::random_nonzero(&mut OsRng);
+        let r = nonce.mul_by_generator().x_coordinate();
+        let s = nonce.invert().unwrap() * (message + r * sk);
+
+        let signature = RecoverableSignature::from_scalars(&r, &s, &vkey, &message).unwrap();
+        let message_bytes = message.to_bytes();
+        let expected_key = VerifyingKey::from_affine(vkey.to_backend().to_affine()).unwrap();
+
+        // A high-s signature is normalized, and the key can still be recovered.
+        let high_s = RecoverableSignature {
+            signature: BackendSignature::from_scalars(
+                signature.signature.r(),
+                -*signature.signature.s(),
+            )
+            .unwrap(),
+            recovery_id: RecoveryId::new(
+                !signature.recovery_id.is_y_odd(),
+                signature.recovery_id.is_x_reduced(),
+            ),
+        };
+        let normalized = high_s.normalize_s();
+        assert_eq!(normalized.signature, signature.signature);
+        assert_eq!(normalized.recovery_id, signature.recovery_id);
+        assert_eq!(signature.normalize_s().signature, signature.signature);
+
+        let (r_bytes, s_bytes, v) = signature.to_rsv(None);
+        assert!(v == 27 || v == 28);
+        assert_eq!(signature.to_rsv(Some(1)).2, v + 10);
+
+        let bytes = signature.to_ethereum_bytes();
+        assert_eq!(&bytes[..32], &r_bytes);
+        assert_eq!(&bytes[32..64], &s_bytes);
+
+        let recovered_signature = BackendSignature::from_slice(&bytes[..64]).unwrap();
+        let recovery_id = RecoveryId::from_byte(bytes[64] - 27).unwrap();
+        let recovered_key =
+            VerifyingKey::recover_from_prehash(&message_bytes, &recovered_signature, recovery_id)
+                .unwrap();
+        assert_eq!(recovered_key, expected_key);
+    }
+
+    #[test]
+    fn verification() {
+        let sk = Scalar::random_nonzero(&mut OsRng);
+        let vkey = sk.mul_by_generator();
+        let message = Scalar::random(&mut OsRng);
+        let nonce = Scalar::random_nonzero(&mut OsRng);
+        let r = nonce.mul_by_generator().x_coordinate();
+        let s = nonce.invert().unwrap() * (message + r * sk);
+
+        let signature = RecoverableSignature::from_scalars(&r, &s, &vkey, &message).unwrap();
+        let message_bytes: [u8; 32] = message.to_bytes().into();
+        let expected_key = VerifyingKey::from_affine(vkey.to_backend().to_affine()).unwrap();
+
+        assert_eq!(
+            signature.recover_verifying_key(&message_bytes),
+            Some(expected_key)
+        );
+        assert!(signature.verify(&expected_key, &message_bytes));
+
+        let other_message: [u8; 32] = (message + Scalar::ONE).to_bytes().into();
+        assert!(!signature.verify(&expected_key, &other_message));
+
+        let other_key = VerifyingKey::from_affine(
+            Scalar::random_nonzero(&mut OsRng)
+                .mul_by_generator()
+                .to_backend()
+                .to_affine(),
+        )
+        .unwrap();
+        assert!(!signature.verify(&other_key, &message_bytes));
+    }
 }
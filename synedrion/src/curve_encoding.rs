@@ -0,0 +1,184 @@
+//! The byte encodings of the curve scalars and points,
+//! the same as the ones used in the messages and the key shares.
+//!
+//! Exposed for the integrators building their own proofs or messages on top of the library.
+//! The scalars are [`k256::Scalar`]s, and the points are [`k256::ProjectivePoint`]s
+//! (or the `p256` ones with the `p256` feature).
+//!
+//! A scalar is always encoded as 32 big-endian bytes, and must be reduced modulo the curve order.
+//! A point is encoded in the SEC1 format, either compressed (33 bytes) or uncompressed (65 bytes),
+//! see [`PointEncoding`]. The library itself only produces and accepts the compressed encoding,
+//! so that each point has a single representation in the signed messages.
+
+use alloc::boxed::Box;
+
+use displaydoc::Display;
+
+use crate::curve::{BackendPoint, BackendScalar, Point, Scalar};
+
+/// The size of an encoded scalar.
+pub const SCALAR_SIZE: usize = 32;
+
+/// Possible errors when decoding a scalar or a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum CurveEncodingError {
+    /// Expected {expected} bytes, got {found}.
+    InvalidLength {
+        /// The size of the encoding.
+        expected: usize,
+        /// The size of the given bytes.
+        found: usize,
+    },
+    /// The scalar is not reduced modulo the curve order.
+    NonCanonicalScalar,
+    /// The bytes do not encode a curve point in the selected encoding.
+    InvalidPoint,
+}
+
+impl core::error::Error for CurveEncodingError {}
+
+/// The SEC1 encoding of a curve point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// The x-coordinate prefixed by a byte with the parity of the y-coordinate (33 bytes).
+    ///
+    /// This is the encoding used by the library.
+    #[default]
+    Compressed,
+    /// Both coordinates prefixed by a `0x04` byte (65 bytes).
+    Uncompressed,
+}
+
+impl PointEncoding {
+    /// Returns the size of an encoded point (other than the identity, see [`point_to_bytes`]).
+    pub fn encoded_size(&self) -> usize {
+        match self {
+            Self::Compressed => 33,
+            Self::Uncompressed => 65,
+        }
+    }
+}
+
+/// Returns the 32-byte big-endian encoding of a scalar.
+pub fn scalar_to_bytes(scalar: &BackendScalar) -> [u8; SCALAR_SIZE] {
+    Scalar::from_backend(*scalar).to_bytes().into()
+}
+
+/// Decodes a scalar from 32 big-endian bytes, failing if it is not reduced modulo the curve order.
+pub fn scalar_from_bytes(bytes: &[u8]) -> Result<BackendScalar, CurveEncodingError> {
+    if bytes.len() != SCALAR_SIZE {
+        return Err(CurveEncodingError::InvalidLength {
+            expected: SCALAR_SIZE,
+            found: bytes.len(),
+        });
+    }
+    Scalar::try_from_bytes(bytes)
+        .map(Scalar::to_backend)
+        .map_err(|_| CurveEncodingError::NonCanonicalScalar)
+}
+
+/// Returns the SEC1 encoding of a point.
+///
+/// The identity point cannot be represented by coordinates,
+/// and is encoded as a single zero byte in both encodings.
+/// It is not accepted by [`point_from_bytes`] (nor by the library in the messages).
+pub fn point_to_bytes(point: &BackendPoint, encoding: PointEncoding) -> Box<[u8]> {
+    Point::from_backend(*point).to_sec1_bytes(encoding == PointEncoding::Compressed)
+}
+
+/// Decodes a point from its SEC1 encoding, failing if it is in a different encoding,
+/// or if it is the identity.
+pub fn point_from_bytes(
+    bytes: &[u8],
+    encoding: PointEncoding,
+) -> Result<BackendPoint, CurveEncodingError> {
+    if bytes.len() != encoding.encoded_size() {
+        return Err(CurveEncodingError::InvalidLength {
+            expected: encoding.encoded_size(),
+            found: bytes.len(),
+        });
+    }
+    let point = match encoding {
+        PointEncoding::Compressed => Point::try_from_compressed_bytes(bytes),
+        PointEncoding::Uncompressed => Point::try_from_uncompressed_bytes(bytes),
+    };
+    point
+        .map(Point::to_backend)
+        .map_err(|_| CurveEncodingError::InvalidPoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::{
+        point_from_bytes, point_to_bytes, scalar_from_bytes, scalar_to_bytes, CurveEncodingError,
+        PointEncoding,
+    };
+    use crate::curve::{BackendPoint, BackendScalar, Point, Scalar};
+
+    #[test]
+    fn scalar_roundtrip() {
+        let scalar = Scalar::random(&mut OsRng).to_backend();
+        let bytes = scalar_to_bytes(&scalar);
+        assert_eq!(scalar_from_bytes(&bytes).unwrap(), scalar);
+
+        // Same as the encoding used in the messages.
+        let serialized = bincode::serialize(&Scalar::from_backend(scalar)).unwrap();
+        assert!(serialized.ends_with(&bytes));
+
+        // Big-endian.
+        assert_eq!(scalar_to_bytes(&BackendScalar::ONE)[31], 1);
+
+        assert_eq!(
+            scalar_from_bytes(&bytes[1..]),
+            Err(CurveEncodingError::InvalidLength {
+                expected: 32,
+                found: 31
+            })
+        );
+        assert_eq!(
+            scalar_from_bytes(&[0xff; 32]),
+            Err(CurveEncodingError::NonCanonicalScalar)
+        );
+    }
+
+    #[test]
+    fn point_roundtrip() {
+        let point = BackendPoint::GENERATOR * Scalar::random(&mut OsRng).to_backend();
+
+        let compressed = point_to_bytes(&point, PointEncoding::Compressed);
+        assert_eq!(compressed.len(), 33);
+        assert_eq!(
+            point_from_bytes(&compressed, PointEncoding::Compressed).unwrap(),
+            point
+        );
+        // Same as the encoding used in the messages.
+        let serialized = bincode::serialize(&Point::from_backend(point)).unwrap();
+        assert!(serialized.ends_with(&compressed));
+
+        let uncompressed = point_to_bytes(&point, PointEncoding::Uncompressed);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 4);
+        assert_eq!(
+            point_from_bytes(&uncompressed, PointEncoding::Uncompressed).unwrap(),
+            point
+        );
+        assert_eq!(uncompressed[1..33], compressed[1..]);
+
+        // The encodings are not interchangeable.
+        assert!(point_from_bytes(&uncompressed, PointEncoding::Compressed).is_err());
+        assert!(point_from_bytes(&compressed, PointEncoding::Uncompressed).is_err());
+        let mut hybrid = uncompressed.clone();
+        hybrid[0] = 6 | (hybrid[64] & 1);
+        assert_eq!(
+            point_from_bytes(&hybrid, PointEncoding::Uncompressed),
+            Err(CurveEncodingError::InvalidPoint)
+        );
+
+        // The identity.
+        let identity = point_to_bytes(&BackendPoint::IDENTITY, PointEncoding::Compressed);
+        assert_eq!(identity.as_ref(), [0]);
+        assert!(point_from_bytes(&identity, PointEncoding::Compressed).is_err());
+    }
+}
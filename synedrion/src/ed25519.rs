@@ -0,0 +1,161 @@
+//! Ed25519 (<https://www.rfc-editor.org/rfc/rfc8032>) signatures created by a threshold of parties
+//! holding the shares of the key.
+//!
+//! This is independent of the ECDSA machinery: the keys live on the `edwards25519` curve,
+//! and the signatures are created with a FROST-style protocol
+//! (see [`make_ed25519_signing_session`](crate::make_ed25519_signing_session)).
+
+mod arithmetic;
+#[cfg(feature = "transport-ed25519")]
+mod identity;
+mod key_share;
+pub(crate) mod signing;
+
+use sha2::{Digest, Sha512};
+
+use arithmetic::{Point, Scalar};
+
+#[cfg(feature = "transport-ed25519")]
+pub use identity::{Ed25519IdentityKey, Ed25519IdentitySignature, Ed25519IdentityVerifyingKey};
+pub use key_share::{Ed25519KeyShare, Ed25519ThresholdKeyShare};
+pub use signing::{Ed25519SigningError, Ed25519SigningResult};
+
+/// An Ed25519 signature along with the key it can be verified with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Signature {
+    signature: [u8; 64],
+    verifying_key: [u8; 32],
+}
+
+impl Ed25519Signature {
+    /// Creates the signature `(R, s)` and checks that it is valid for the message
+    /// and the key `verifying_key`.
+    pub(crate) fn from_parts(
+        cap_r: &Point,
+        s: &Scalar,
+        verifying_key: &Point,
+        message: &[u8],
+    ) -> Option<Self> {
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&cap_r.to_bytes());
+        signature[32..].copy_from_slice(&s.to_bytes());
+        let signature = Self {
+            signature,
+            verifying_key: verifying_key.to_bytes(),
+        };
+        signature.verify(message).then_some(signature)
+    }
+
+    /// Serializes the signature as the 64-byte array `R || S`, as defined in RFC 8032.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.signature
+    }
+
+    /// Returns the 32-byte encoding of the public key the signature can be verified with.
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key
+    }
+
+    /// Verifies the signature of a message, as defined in RFC 8032
+    /// (using the equation without the cofactor).
+    ///
+    /// The key and the nonce point are additionally required to belong to the prime-order subgroup,
+    /// which is always the case for the signatures created by this library.
+    pub fn verify(&self, message: &[u8]) -> bool {
+        let verifying_key = match Point::try_from_bytes(&self.verifying_key) {
+            Ok(point) => point,
+            Err(_) => return false,
+        };
+        let cap_r = match Point::try_from_bytes(&self.signature[..32]) {
+            Ok(point) => point,
+            Err(_) => return false,
+        };
+        let s = match Scalar::try_from_bytes(&self.signature[32..]) {
+            Ok(scalar) => scalar,
+            Err(_) => return false,
+        };
+
+        let k = challenge(&cap_r, &verifying_key, message);
+        s.mul_by_generator() == cap_r + verifying_key * k
+    }
+}
+
+/// Returns the challenge `k = SHA512(R || A || M)` for the nonce point `cap_r`
+/// and the public key `verifying_key`.
+pub(crate) fn challenge(cap_r: &Point, verifying_key: &Point, message: &[u8]) -> Scalar {
+    let digest = Sha512::new()
+        .chain_update(cap_r.to_bytes())
+        .chain_update(verifying_key.to_bytes())
+        .chain_update(message)
+        .finalize();
+    Scalar::from_wide_bytes(&digest.into())
+}
+
+/// Returns the secret scalar of the Ed25519 private key `seed`, as defined in RFC 8032.
+pub(crate) fn expand_secret_key(seed: &[u8; 32]) -> Scalar {
+    let digest = Sha512::digest(seed);
+    let mut scalar_bytes: [u8; 32] = digest[..32].try_into().unwrap();
+    scalar_bytes[0] &= 248;
+    scalar_bytes[31] &= 127;
+    scalar_bytes[31] |= 64;
+    Scalar::from_reduced_bytes(&scalar_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha512};
+
+    use super::{challenge, expand_secret_key, Ed25519Signature, Scalar};
+
+    /// Signs a message with a regular Ed25519 private key, as defined in RFC 8032.
+    fn sign(seed: &[u8; 32], message: &[u8]) -> Ed25519Signature {
+        let a = expand_secret_key(seed);
+        let verifying_key = a.mul_by_generator();
+        let prefix = &Sha512::digest(seed)[32..];
+        let r = Scalar::from_wide_bytes(
+            &Sha512::new()
+                .chain_update(prefix)
+                .chain_update(message)
+                .finalize()
+                .into(),
+        );
+        let cap_r = r.mul_by_generator();
+        let s = r + challenge(&cap_r, &verifying_key, message) * a;
+        Ed25519Signature::from_parts(&cap_r, &s, &verifying_key, message).unwrap()
+    }
+
+    #[test]
+    fn rfc8032_test_vectors() {
+        // Tests 1 and 2 from RFC 8032, Section 7.1.
+        let vectors: [(&str, &str, &str, &str); 2] = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                concat![
+                    "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155",
+                    "5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+                ],
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                concat![
+                    "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+                    "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+                ],
+            ),
+        ];
+
+        for (seed, verifying_key, message, signature) in vectors {
+            let seed: [u8; 32] = hex::decode(seed).unwrap().try_into().unwrap();
+            let message = hex::decode(message).unwrap();
+            let created = sign(&seed, &message);
+            assert_eq!(hex::encode(created.verifying_key_bytes()), verifying_key);
+            assert_eq!(hex::encode(created.to_bytes()), signature);
+            assert!(created.verify(&message));
+            assert!(!created.verify(b"another message"));
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! The arithmetic of the twisted Edwards curve `edwards25519` used by Ed25519 (RFC 8032).
+//!
+//! A thin wrapper over `curve25519-dalek`, adding the hashing, the serialization
+//! and the stricter decoding the protocols need.
+
+use alloc::format;
+use alloc::string::String;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crypto_bigint::subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+use curve25519_dalek::{
+    edwards::CompressedEdwardsY,
+    traits::{Identity, IsIdentity},
+    EdwardsPoint, Scalar as BackendScalar,
+};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+use crate::tools::hashing::{Chain, Hashable};
+use crate::tools::serde_bytes;
+use crate::tools::serialized_size::{bytes_size, MaxSerializedSize};
+
+/// An element of the scalar field of the prime-order subgroup of `edwards25519`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub(crate) struct Scalar(BackendScalar);
+
+impl Scalar {
+    pub const ZERO: Self = Self(BackendScalar::ZERO);
+    pub const ONE: Self = Self(BackendScalar::ONE);
+
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        Self(BackendScalar::random(rng))
+    }
+
+    pub fn random_nonzero(rng: &mut impl CryptoRngCore) -> Self {
+        loop {
+            let scalar = Self::random(rng);
+            if scalar != Self::ZERO {
+                return scalar;
+            }
+        }
+    }
+
+    pub fn mul_by_generator(&self) -> Point {
+        Point(EdwardsPoint::mul_base(&self.0))
+    }
+
+    pub fn invert(&self) -> CtOption<Self> {
+        CtOption::new(Self(self.0.invert()), !self.0.ct_eq(&BackendScalar::ZERO))
+    }
+
+    /// Reduces a 512-bit little-endian integer (e.g. a SHA-512 hash) modulo the group order.
+    pub fn from_wide_bytes(bytes: &[u8; 64]) -> Self {
+        Self(BackendScalar::from_bytes_mod_order_wide(bytes))
+    }
+
+    /// Reduces a 256-bit little-endian integer modulo the group order.
+    pub fn from_reduced_bytes(bytes: &[u8; 32]) -> Self {
+        Self(BackendScalar::from_bytes_mod_order(*bytes))
+    }
+
+    /// Returns the canonical little-endian encoding of the scalar.
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Deserializes the canonical little-endian encoding of a scalar.
+    pub(crate) fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| format!("Invalid scalar length: {}", bytes.len()))?;
+        Option::from(BackendScalar::from_canonical_bytes(bytes))
+            .map(Self)
+            .ok_or_else(|| "The scalar is not reduced modulo the group order".into())
+    }
+}
+
+impl From<u32> for Scalar {
+    fn from(val: u32) -> Self {
+        Self(BackendScalar::from(val))
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Scalar {
+    type Error = String;
+    fn try_from(val: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(val)
+    }
+}
+
+impl ConditionallySelectable for Scalar {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(BackendScalar::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Serialize for Scalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::as_hex::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_bytes::as_hex::deserialize(deserializer)
+    }
+}
+
+impl MaxSerializedSize for Scalar {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        bytes_size(32)
+    }
+}
+
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Hashable for Scalar {
+    fn chain<C: Chain>(&self, digest: C) -> C {
+        digest.chain_constant_sized_bytes(&self.to_bytes())
+    }
+}
+
+impl Add<Scalar> for Scalar {
+    type Output = Scalar;
+    fn add(self, other: Scalar) -> Scalar {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub<Scalar> for Scalar {
+    type Output = Scalar;
+    fn sub(self, other: Scalar) -> Scalar {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Mul<Scalar> for Scalar {
+    type Output = Scalar;
+    fn mul(self, other: Scalar) -> Scalar {
+        Self(self.0 * other.0)
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+    fn neg(self) -> Scalar {
+        Self(-self.0)
+    }
+}
+
+impl core::iter::Sum for Scalar {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or(Self::ZERO)
+    }
+}
+
+impl core::iter::Product for Scalar {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Mul::mul).unwrap_or(Self::ONE)
+    }
+}
+
+/// A point of `edwards25519`.
+///
+/// Only the canonically encoded points of the prime-order subgroup can be deserialized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Point(EdwardsPoint);
+
+impl Point {
+    #[cfg(test)]
+    pub const GENERATOR: Self = Self(curve25519_dalek::constants::ED25519_BASEPOINT_POINT);
+
+    pub fn identity() -> Self {
+        Self(EdwardsPoint::identity())
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.0.is_identity()
+    }
+
+    /// Returns the 32-byte encoding of the point as defined in RFC 8032:
+    /// the little-endian $y$-coordinate, with the sign of the $x$-coordinate in the top bit.
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0.compress().to_bytes()
+    }
+
+    /// Decodes a point as defined in RFC 8032,
+    /// additionally requiring the encoding to be canonical
+    /// and the point to belong to the prime-order subgroup.
+    pub(crate) fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let compressed = CompressedEdwardsY::from_slice(bytes)
+            .map_err(|_| format!("Invalid point length: {}", bytes.len()))?;
+        let point = compressed
+            .decompress()
+            .ok_or("The point is not on the curve")?;
+        // `decompress()` accepts a non-reduced `y` and a negative zero `x`.
+        if point.compress() != compressed {
+            return Err("The point encoding is not canonical".into());
+        }
+        if !point.is_torsion_free() {
+            return Err("The point does not belong to the prime-order subgroup".into());
+        }
+        Ok(Self(point))
+    }
+}
+
+impl ConstantTimeEq for Point {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for Point {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(EdwardsPoint::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Point {
+    type Error = String;
+    fn try_from(val: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(val)
+    }
+}
+
+impl Serialize for Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::as_hex::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_bytes::as_hex::deserialize(deserializer)
+    }
+}
+
+impl MaxSerializedSize for Point {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        bytes_size(32)
+    }
+}
+
+impl Hashable for Point {
+    fn chain<C: Chain>(&self, digest: C) -> C {
+        digest.chain_constant_sized_bytes(&self.to_bytes())
+    }
+}
+
+impl Add<Point> for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub<Point> for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Self(-self.0)
+    }
+}
+
+impl Mul<&Scalar> for Point {
+    type Output = Point;
+    fn mul(self, scalar: &Scalar) -> Point {
+        Self(self.0 * scalar.0)
+    }
+}
+
+impl Mul<Scalar> for Point {
+    type Output = Point;
+    fn mul(self, scalar: Scalar) -> Point {
+        self * &scalar
+    }
+}
+
+impl core::iter::Sum for Point {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or_else(Self::identity)
+    }
+}
+
+impl<'a> core::iter::Sum<&'a Self> for Point {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::{Point, Scalar};
+
+    #[test]
+    fn constants() {
+        // The standard encoding of the base point.
+        assert_eq!(
+            hex::encode(Point::GENERATOR.to_bytes()),
+            "5866666666666666666666666666666666666666666666666666666666666666"
+        );
+        assert_eq!(
+            Point::try_from_bytes(&Point::GENERATOR.to_bytes()).unwrap(),
+            Point::GENERATOR
+        );
+    }
+
+    #[test]
+    fn encoding_roundtrip() {
+        for _ in 0..16 {
+            let scalar = Scalar::random(&mut OsRng);
+            assert_eq!(Scalar::try_from_bytes(&scalar.to_bytes()).unwrap(), scalar);
+
+            let point = scalar.mul_by_generator();
+            assert_eq!(Point::try_from_bytes(&point.to_bytes()).unwrap(), point);
+            assert_eq!(Point::try_from_bytes(&(-point).to_bytes()).unwrap(), -point);
+        }
+        assert!(Point::try_from_bytes(&Point::identity().to_bytes())
+            .unwrap()
+            .is_identity());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Scalar::random(&mut OsRng);
+        let b = Scalar::random(&mut OsRng);
+        assert_eq!(
+            a.mul_by_generator() + b.mul_by_generator(),
+            (a + b).mul_by_generator()
+        );
+        assert_eq!(a.mul_by_generator() * b, (a * b).mul_by_generator());
+        assert_eq!(a * a.invert().unwrap(), Scalar::ONE);
+        assert_eq!((-a).mul_by_generator(), -a.mul_by_generator());
+        assert!((Point::GENERATOR * (Scalar::ZERO - Scalar::ONE) + Point::GENERATOR).is_identity());
+    }
+
+    #[test]
+    fn small_order_points_are_rejected() {
+        // The point `(0, -1)` of order 2.
+        let mut bytes = [0u8; 32];
+        bytes[..32].copy_from_slice(
+            &hex::decode("ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f")
+                .unwrap(),
+        );
+        assert!(Point::try_from_bytes(&bytes).is_err());
+
+        // The non-canonical encodings of the identity: `y = p + 1`, and `x = -0`.
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = 0xee;
+        bytes[31] = 0x7f;
+        assert!(Point::try_from_bytes(&bytes).is_err());
+        let mut bytes = Point::identity().to_bytes();
+        bytes[31] |= 0x80;
+        assert!(Point::try_from_bytes(&bytes).is_err());
+
+        // A non-reduced scalar.
+        assert!(Scalar::try_from_bytes(&[0xff; 32]).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! Ed25519 keys identifying the parties of a session,
+//! for the applications that do not have their own signature scheme for the messages.
+
+use alloc::format;
+use alloc::string::String;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha512};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::arithmetic::{Point, Scalar};
+use super::{challenge, expand_secret_key};
+use crate::tools::serde_bytes;
+
+/// An Ed25519 (RFC 8032) private key identifying a party,
+/// usable as the `Signer` type of the sessions.
+///
+/// The messages are signed with the plain Ed25519, treating the message hash as the message,
+/// so the signatures can be verified by any other Ed25519 implementation.
+#[derive(Clone)]
+pub struct Ed25519IdentityKey {
+    seed: [u8; 32],
+    secret: Scalar,
+    verifying_key: Ed25519IdentityVerifyingKey,
+}
+
+impl Ed25519IdentityKey {
+    /// Generates a random key.
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let key = Self::from_seed(&seed);
+        #[cfg(feature = "zeroize")]
+        seed.zeroize();
+        key
+    }
+
+    /// Creates the key from the 32-byte private key defined in RFC 8032.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        let secret = expand_secret_key(seed);
+        let point = secret.mul_by_generator();
+        Self {
+            seed: *seed,
+            secret,
+            verifying_key: Ed25519IdentityVerifyingKey {
+                bytes: point.to_bytes(),
+                point,
+            },
+        }
+    }
+
+    /// Returns the 32-byte private key defined in RFC 8032.
+    pub fn to_seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Returns the public key corresponding to this key.
+    pub fn verifying_key(&self) -> Ed25519IdentityVerifyingKey {
+        self.verifying_key
+    }
+
+    /// Signs a message as defined in RFC 8032.
+    pub fn sign(&self, message: &[u8]) -> Ed25519IdentitySignature {
+        let digest = Sha512::digest(self.seed);
+        let r = Scalar::from_wide_bytes(
+            &Sha512::new()
+                .chain_update(&digest[32..])
+                .chain_update(message)
+                .finalize()
+                .into(),
+        );
+        let cap_r = r.mul_by_generator();
+        let s = r + challenge(&cap_r, &self.verifying_key.point, message) * self.secret;
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&cap_r.to_bytes());
+        signature[32..].copy_from_slice(&s.to_bytes());
+        Ed25519IdentitySignature(signature)
+    }
+}
+
+impl Keypair for Ed25519IdentityKey {
+    type VerifyingKey = Ed25519IdentityVerifyingKey;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        self.verifying_key
+    }
+}
+
+impl RandomizedPrehashSigner<Ed25519IdentitySignature> for Ed25519IdentityKey {
+    fn sign_prehash_with_rng(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+        prehash: &[u8],
+    ) -> Result<Ed25519IdentitySignature, signature::Error> {
+        // Ed25519 signatures are deterministic.
+        Ok(self.sign(prehash))
+    }
+}
+
+// A custom Debug impl that skips the secret value
+impl core::fmt::Debug for Ed25519IdentityKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "Ed25519IdentityKey {{ seed: <...>, verifying_key: {:?} }}",
+            self.verifying_key
+        )
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Ed25519IdentityKey {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.secret.zeroize();
+    }
+}
+
+/// An Ed25519 public key identifying a party, usable as the `Verifier` type of the sessions.
+///
+/// Serialized as the 32-byte encoding defined in RFC 8032.
+/// Only the keys from the prime-order subgroup are accepted.
+#[derive(Clone, Copy)]
+pub struct Ed25519IdentityVerifyingKey {
+    bytes: [u8; 32],
+    point: Point,
+}
+
+impl Ed25519IdentityVerifyingKey {
+    /// Decodes the key from the 32-byte encoding defined in RFC 8032.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let point = Point::try_from_bytes(bytes)?;
+        if point.is_identity() {
+            return Err("The key is the identity".into());
+        }
+        Ok(Self {
+            bytes: point.to_bytes(),
+            point,
+        })
+    }
+
+    /// Returns the 32-byte encoding of the key defined in RFC 8032.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    /// Verifies the signature of a message as defined in RFC 8032
+    /// (using the equation without the cofactor).
+    pub fn verify(&self, message: &[u8], signature: &Ed25519IdentitySignature) -> bool {
+        let cap_r = match Point::try_from_bytes(&signature.0[..32]) {
+            Ok(point) => point,
+            Err(_) => return false,
+        };
+        let s = match Scalar::try_from_bytes(&signature.0[32..]) {
+            Ok(scalar) => scalar,
+            Err(_) => return false,
+        };
+        let k = challenge(&cap_r, &self.point, message);
+        s.mul_by_generator() == cap_r + self.point * k
+    }
+}
+
+impl PrehashVerifier<Ed25519IdentitySignature> for Ed25519IdentityVerifyingKey {
+    fn verify_prehash(
+        &self,
+        prehash: &[u8],
+        signature: &Ed25519IdentitySignature,
+    ) -> Result<(), signature::Error> {
+        if self.verify(prehash, signature) {
+            Ok(())
+        } else {
+            Err(signature::Error::new())
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Ed25519IdentityVerifyingKey {
+    type Error = String;
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Ed25519IdentityVerifyingKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Serialize for Ed25519IdentityVerifyingKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::as_hex::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519IdentityVerifyingKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_bytes::as_hex::deserialize(deserializer)
+    }
+}
+
+// The keys are compared by their encodings, which are unique.
+
+impl PartialEq for Ed25519IdentityVerifyingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for Ed25519IdentityVerifyingKey {}
+
+impl PartialOrd for Ed25519IdentityVerifyingKey {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ed25519IdentityVerifyingKey {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.bytes.cmp(&other.bytes)
+    }
+}
+
+impl core::hash::Hash for Ed25519IdentityVerifyingKey {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+}
+
+impl core::fmt::Debug for Ed25519IdentityVerifyingKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "Ed25519IdentityVerifyingKey({})",
+            hex::encode(self.bytes)
+        )
+    }
+}
+
+/// An Ed25519 signature of a message, created by [`Ed25519IdentityKey`].
+///
+/// Serialized as the 64-byte encoding `R || S` defined in RFC 8032.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519IdentitySignature([u8; 64]);
+
+impl Ed25519IdentitySignature {
+    /// Creates the signature out of the 64-byte encoding `R || S` defined in RFC 8032.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+        Self(*bytes)
+    }
+
+    /// Returns the 64-byte encoding `R || S` defined in RFC 8032.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Ed25519IdentitySignature {
+    type Error = String;
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; 64] = bytes
+            .try_into()
+            .map_err(|_| format!("Invalid signature length: {}", bytes.len()))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl AsRef<[u8]> for Ed25519IdentitySignature {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Serialize for Ed25519IdentitySignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::as_hex::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519IdentitySignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_bytes::as_hex::deserialize(deserializer)
+    }
+}
+
+impl core::fmt::Debug for Ed25519IdentitySignature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "Ed25519IdentitySignature({})", hex::encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand_core::OsRng;
+
+    use super::{Ed25519IdentityKey, Ed25519IdentitySignature, Ed25519IdentityVerifyingKey};
+    use crate::curve::SigningKey;
+    use crate::sessions::Bincode;
+    use crate::testnet::{make_signers, TestNetwork};
+    use crate::{make_key_export_session, KeyShare, TestParams};
+
+    #[test]
+    fn rfc8032_compatibility() {
+        // Test 2 from RFC 8032, Section 7.1.
+        let seed: [u8; 32] =
+            hex::decode("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let key = Ed25519IdentityKey::from_seed(&seed);
+        let signature = key.sign(&[0x72]);
+        assert_eq!(
+            hex::encode(signature.to_bytes()),
+            concat![
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+                "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+            ]
+        );
+
+        let verifying_key = key.verifying_key();
+        assert!(verifying_key.verify(&[0x72], &signature));
+        assert!(!verifying_key.verify(&[0x73], &signature));
+
+        let serialized = bincode::serialize(&verifying_key).unwrap();
+        let deserialized: Ed25519IdentityVerifyingKey = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, verifying_key);
+        let serialized = bincode::serialize(&signature).unwrap();
+        let deserialized: Ed25519IdentitySignature = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, signature);
+
+        // The identity is not a valid key.
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(Ed25519IdentityVerifyingKey::try_from_bytes(&identity).is_err());
+    }
+
+    #[test]
+    fn session_with_identity_keys() {
+        let num_parties = 3;
+        let (signers, verifiers) =
+            make_signers(num_parties, || Ed25519IdentityKey::random(&mut OsRng));
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+        let recipient_key = SigningKey::random(&mut OsRng);
+
+        let sessions = key_shares
+            .iter()
+            .zip(signers)
+            .map(|(key_share, signer)| {
+                make_key_export_session::<_, Ed25519IdentitySignature, _, _, Bincode>(
+                    &mut OsRng,
+                    b"1234567890",
+                    signer,
+                    &verifiers,
+                    key_share,
+                    recipient_key.verifying_key(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let results = TestNetwork::new(sessions).run(&mut OsRng);
+        for result in results.into_values() {
+            let secret_key = result.unwrap().decrypt(&recipient_key).unwrap();
+            assert_eq!(secret_key.verifying_key(), &key_shares[0].verifying_key());
+        }
+    }
+}
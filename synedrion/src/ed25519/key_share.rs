@@ -0,0 +1,258 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::arithmetic::{Point, Scalar};
+use super::expand_secret_key;
+use crate::rounds::PartyIdx;
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+use crate::tools::sss::ShareIdx;
+
+/// Returns the share index as an element of the scalar field of `edwards25519`.
+fn share_idx_to_scalar(idx: &ShareIdx) -> Scalar {
+    // The indices are small integers, so reducing them modulo the other group order
+    // does not change them.
+    let mut bytes = idx.to_scalar().to_bytes();
+    bytes.reverse();
+    Scalar::from_reduced_bytes(&bytes.into())
+}
+
+fn interpolation_coeff(idxs: &[ShareIdx], exclude_idx: &ShareIdx) -> Scalar {
+    let exclude = share_idx_to_scalar(exclude_idx);
+    idxs.iter()
+        .filter(|idx| idx != &exclude_idx)
+        .map(|idx| {
+            let idx = share_idx_to_scalar(idx);
+            idx * (idx - exclude).invert().unwrap()
+        })
+        .product()
+}
+
+fn evaluate_polynomial(coeffs: &[Scalar], x: &ShareIdx) -> Scalar {
+    let x = share_idx_to_scalar(x);
+    coeffs
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |res, coeff| res * x + *coeff)
+}
+
+/// A share of an Ed25519 key, any `threshold` of which can be used to create a signature.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ed25519ThresholdKeyShare {
+    pub(crate) index: ShareIdx,
+    pub(crate) threshold: u32,
+    pub(crate) secret_share: Scalar,
+    pub(crate) public_shares: BTreeMap<ShareIdx, Point>,
+    /// A random identifier, the same for all holders of the shares of this set.
+    pub(crate) share_set_id: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Ed25519ThresholdKeyShare {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+impl Ed25519ThresholdKeyShare {
+    /// Splits a key into `num_parties` shares, any `threshold` of which can create a signature.
+    ///
+    /// If `seed` (the 32-byte Ed25519 private key of RFC 8032) is given, the shares are made
+    /// for that key (so existing keys can be imported), otherwise a random key is generated.
+    /// The shares have the indices `1..=num_parties`.
+    ///
+    /// Note that the party calling this learns the whole key,
+    /// so the shares have to be distributed to the parties over secure channels.
+    pub fn new_centralized(
+        rng: &mut impl CryptoRngCore,
+        threshold: usize,
+        num_parties: usize,
+        seed: Option<&[u8; 32]>,
+    ) -> Box<[Self]> {
+        assert!(threshold > 0 && threshold <= num_parties);
+
+        let secret = match seed {
+            None => Scalar::random_nonzero(rng),
+            Some(seed) => expand_secret_key(seed),
+        };
+        let mut coeffs = Vec::with_capacity(threshold);
+        coeffs.push(secret);
+        coeffs.extend((1..threshold).map(|_| Scalar::random(rng)));
+
+        let share_idxs = (1..=num_parties).map(ShareIdx::new).collect::<Vec<_>>();
+        let secret_shares = share_idxs
+            .iter()
+            .map(|idx| evaluate_polynomial(&coeffs, idx))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "zeroize")]
+        coeffs.zeroize();
+
+        let public_shares = share_idxs
+            .iter()
+            .zip(secret_shares.iter())
+            .map(|(idx, share)| (*idx, share.mul_by_generator()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut init_id = [0u8; 32];
+        rng.fill_bytes(&mut init_id);
+        let share_set_id = Hash::new_with_dst(b"Ed25519ShareSetID")
+            .chain(&init_id)
+            .chain(&(threshold as u32))
+            .chain(&public_shares)
+            .finalize();
+
+        share_idxs
+            .iter()
+            .zip(secret_shares)
+            .map(|(idx, secret_share)| Self {
+                index: *idx,
+                threshold: threshold as u32,
+                secret_share,
+                public_shares: public_shares.clone(),
+                share_set_id,
+            })
+            .collect()
+    }
+
+    /// Returns the index of this share.
+    pub fn index(&self) -> ShareIdx {
+        self.index
+    }
+
+    /// Returns the number of shares required to create a signature.
+    pub fn threshold(&self) -> usize {
+        self.threshold as usize
+    }
+
+    /// Returns the total number of shares.
+    pub fn num_parties(&self) -> usize {
+        self.public_shares.len()
+    }
+
+    pub(crate) fn verifying_key_as_point(&self) -> Point {
+        let share_idxs = self
+            .public_shares
+            .keys()
+            .take(self.threshold as usize)
+            .cloned()
+            .collect::<Vec<_>>();
+        share_idxs
+            .iter()
+            .map(|idx| self.public_shares[idx] * interpolation_coeff(&share_idxs, idx))
+            .sum()
+    }
+
+    /// Returns the 32-byte encoding of the public key to which this set of shares corresponds.
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key_as_point().to_bytes()
+    }
+
+    /// Converts a t-of-n key share into a t-of-t key share
+    /// (for the `t` share indices supplied as `share_idxs`)
+    /// that can be used in the signing protocol.
+    ///
+    /// `share_idxs` must be listed in the order of the verifiers of the parties holding them
+    /// (as given by `Ord`).
+    pub fn to_key_share(&self, share_idxs: &[ShareIdx]) -> Ed25519KeyShare {
+        debug_assert!(share_idxs.len() == self.threshold as usize);
+        let my_idx_position = share_idxs
+            .iter()
+            .position(|idx| idx == &self.index)
+            .unwrap();
+
+        let secret_share = self.secret_share * interpolation_coeff(share_idxs, &self.index);
+        let public_shares = share_idxs
+            .iter()
+            .map(|share_idx| {
+                self.public_shares[share_idx] * interpolation_coeff(share_idxs, share_idx)
+            })
+            .collect();
+
+        Ed25519KeyShare {
+            index: PartyIdx::from_usize(my_idx_position),
+            secret_share,
+            public_shares,
+            share_set_id: self.share_set_id,
+        }
+    }
+}
+
+/// A share of an Ed25519 key, all of which are required to create a signature.
+///
+/// Obtained from [`Ed25519ThresholdKeyShare::to_key_share`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ed25519KeyShare {
+    pub(crate) index: PartyIdx,
+    pub(crate) secret_share: Scalar,
+    pub(crate) public_shares: Box<[Point]>,
+    pub(crate) share_set_id: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Ed25519KeyShare {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+impl Ed25519KeyShare {
+    /// Returns the number of parties in this set of shares.
+    pub fn num_parties(&self) -> usize {
+        self.public_shares.len()
+    }
+
+    /// Returns the index of this share's party.
+    pub fn party_index(&self) -> usize {
+        self.index.as_usize()
+    }
+
+    pub(crate) fn verifying_key_as_point(&self) -> Point {
+        self.public_shares.iter().sum()
+    }
+
+    /// Returns the 32-byte encoding of the public key to which this set of shares corresponds.
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key_as_point().to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::Ed25519ThresholdKeyShare;
+    use crate::rounds::test_utils::quorum_key_shares;
+
+    #[test]
+    fn threshold_key_share_centralized() {
+        let seed = [7u8; 32];
+        let shares = Ed25519ThresholdKeyShare::new_centralized(&mut OsRng, 2, 3, Some(&seed));
+        let verifying_key = shares[0].verifying_key_bytes();
+        for share in shares.iter() {
+            assert_eq!(share.verifying_key_bytes(), verifying_key);
+        }
+        assert_eq!(
+            verifying_key,
+            super::expand_secret_key(&seed)
+                .mul_by_generator()
+                .to_bytes()
+        );
+
+        // Any two of the shares can be converted into the additive shares of the same key.
+        let quorum_shares = quorum_key_shares(&shares, &[0, 2]);
+        let (share0, share2) = (&quorum_shares[0], &quorum_shares[1]);
+        assert_eq!(share0.verifying_key_bytes(), verifying_key);
+        assert_eq!(share2.party_index(), 1);
+        assert_eq!(
+            (share0.secret_share + share2.secret_share)
+                .mul_by_generator()
+                .to_bytes(),
+            verifying_key
+        );
+    }
+}
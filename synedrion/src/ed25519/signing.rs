@@ -0,0 +1,420 @@
+//! Threshold Ed25519 signing following FROST (<https://www.rfc-editor.org/rfc/rfc9591>).
+//!
+//! Each party broadcasts the commitments to its two nonces; once everyone has received
+//! the same commitments, the nonce point is fixed by the binding factors derived from all of them,
+//! and the parties sum up their partial signatures, each of which is checked
+//! against the nonce commitments and the public share of its sender.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::arithmetic::{Point, Scalar};
+use super::{challenge, Ed25519KeyShare, Ed25519Signature};
+use crate::rounds::{
+    all_parties_except, no_direct_messages, try_to_holevec, FinalizableToNextRound,
+    FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx, ProtocolResult, Round,
+    ToNextRound, ToResult,
+};
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+use crate::tools::serialized_size::MaxSerializedSize;
+
+/// Possible results of the Ed25519 signing protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct Ed25519SigningResult;
+
+impl ProtocolResult for Ed25519SigningResult {
+    const NAME: &'static str = "Ed25519Signing";
+    type Success = Ed25519Signature;
+    type ProvableError = Ed25519SigningError;
+    type CorrectnessProof = ();
+}
+
+/// Possible verifiable errors of the Ed25519 signing protocol.
+#[derive(Debug, Clone, Copy)]
+pub enum Ed25519SigningError {
+    /// One of the nonce commitments sent in Round 1 is the identity point.
+    R1IdentityCommitment,
+    /// The partial signature sent in Round 2 does not match the sender's nonce commitments
+    /// and public share.
+    R2InvalidPartialSignature,
+}
+
+#[derive(Clone)]
+pub struct Inputs {
+    pub key_share: Ed25519KeyShare,
+    pub message: Box<[u8]>,
+}
+
+struct Context {
+    num_parties: usize,
+    party_idx: PartyIdx,
+    message: Box<[u8]>,
+    x: Scalar,
+    public_shares: Box<[Point]>,
+    verifying_key: Point,
+    d: Scalar,
+    e: Scalar,
+    cap_d: Point,
+    cap_e: Point,
+    ssid_hash: HashOutput,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.x.zeroize();
+        self.d.zeroize();
+        self.e.zeroize();
+    }
+}
+
+/// Creates a nonce from the randomness and the secret share,
+/// so that it stays secret even if the random number generator is weak.
+fn hedged_nonce(rng: &mut impl CryptoRngCore, secret: &Scalar) -> Scalar {
+    let mut random_bytes = [0u8; 32];
+    rng.fill_bytes(&mut random_bytes);
+    let digest = Sha512::new()
+        .chain_update(b"Ed25519SigningNonce")
+        .chain_update(random_bytes)
+        .chain_update(secret.to_bytes())
+        .finalize();
+    Scalar::from_wide_bytes(&digest.into())
+}
+
+/// Returns the binding factor of each party, which ties its nonces to the message
+/// and the commitments of all the parties.
+fn binding_factors(context: &Context, commitments: &[(Point, Point)]) -> Vec<Scalar> {
+    let binding_context = Hash::new_with_dst(b"Ed25519SigningBinding")
+        .chain(&context.ssid_hash)
+        .chain(&context.verifying_key)
+        .chain(&context.message)
+        .chain_slice(
+            &commitments
+                .iter()
+                .map(|(cap_d, cap_e)| (cap_d, cap_e))
+                .collect::<Vec<_>>(),
+        )
+        .finalize();
+    (0..commitments.len())
+        .map(|idx| {
+            let digest = Sha512::new()
+                .chain_update(binding_context.as_ref())
+                .chain_update((idx as u32).to_be_bytes())
+                .finalize();
+            Scalar::from_wide_bytes(&digest.into())
+        })
+        .collect()
+}
+
+pub struct Round1 {
+    context: Context,
+}
+
+impl FirstRound for Round1 {
+    type Inputs = Inputs;
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        let ssid_hash = Hash::new_with_dst(b"SSID")
+            .chain(&shared_randomness)
+            .chain(&inputs.key_share.share_set_id)
+            .finalize();
+
+        let verifying_key = inputs.key_share.verifying_key_as_point();
+        if verifying_key.is_identity() {
+            return Err(InitError("The shared key is the identity point".into()));
+        }
+
+        let x = inputs.key_share.secret_share;
+        let d = hedged_nonce(rng, &x);
+        let e = hedged_nonce(rng, &x);
+
+        Ok(Self {
+            context: Context {
+                num_parties,
+                party_idx,
+                message: inputs.message,
+                x,
+                public_shares: inputs.key_share.public_shares.clone(),
+                verifying_key,
+                d,
+                e,
+                cap_d: d.mul_by_generator(),
+                cap_e: e.mul_by_generator(),
+                ssid_hash,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round1Message {
+    cap_d: Point,
+    cap_e: Point,
+}
+
+impl MaxSerializedSize for Round1Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Point::max_serialized_size(num_parties) * 2
+    }
+}
+
+pub struct Round1Payload {
+    cap_d: Point,
+    cap_e: Point,
+}
+
+impl Round for Round1 {
+    type Type = ToNextRound;
+    type Result = Ed25519SigningResult;
+    const ROUND_NUM: u8 = 1;
+    const NEXT_ROUND_NUM: Option<u8> = Some(2);
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    // All the parties must receive the same commitments to agree on the nonce point.
+    const REQUIRES_ECHO: bool = true;
+    type BroadcastMessage = Round1Message;
+    type DirectMessage = ();
+    type Payload = Round1Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round1Message {
+            cap_d: self.context.cap_d,
+            cap_e: self.context.cap_e,
+        })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        _from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        if broadcast_msg.cap_d.is_identity() || broadcast_msg.cap_e.is_identity() {
+            return Err(Ed25519SigningError::R1IdentityCommitment);
+        }
+
+        Ok(Round1Payload {
+            cap_d: broadcast_msg.cap_d,
+            cap_e: broadcast_msg.cap_e,
+        })
+    }
+}
+
+impl FinalizableToNextRound for Round1 {
+    type NextRound = Round2;
+    fn finalize_to_next_round(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let commitments = payloads
+            .map(|payload| (payload.cap_d, payload.cap_e))
+            .into_vec((self.context.cap_d, self.context.cap_e));
+        let context = self.context;
+
+        let rhos = binding_factors(&context, &commitments);
+        let all_cap_r = commitments
+            .iter()
+            .zip(rhos.iter())
+            .map(|((cap_d, cap_e), rho)| *cap_d + *cap_e * rho)
+            .collect::<Vec<_>>();
+
+        let cap_r: Point = all_cap_r.iter().sum();
+        if cap_r.is_identity() {
+            // The binding factors depend on all the commitments,
+            // so this can only happen with a negligible probability.
+            return Err(FinalizeError::Proof(()));
+        }
+
+        let c = challenge(&cap_r, &context.verifying_key, &context.message);
+        let z = context.d + context.e * rhos[context.party_idx.as_usize()] + c * context.x;
+
+        Ok(Round2 {
+            context,
+            all_cap_r,
+            cap_r,
+            c,
+            z,
+        })
+    }
+}
+
+pub struct Round2 {
+    context: Context,
+    all_cap_r: Vec<Point>,
+    cap_r: Point,
+    c: Scalar,
+    z: Scalar,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round2Message {
+    z: Scalar,
+}
+
+impl MaxSerializedSize for Round2Message {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        Scalar::max_serialized_size(num_parties)
+    }
+}
+
+pub struct Round2Payload {
+    z: Scalar,
+}
+
+impl Round for Round2 {
+    type Type = ToResult;
+    type Result = Ed25519SigningResult;
+    const ROUND_NUM: u8 = 2;
+    const NEXT_ROUND_NUM: Option<u8> = None;
+
+    fn num_parties(&self) -> usize {
+        self.context.num_parties
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.context.party_idx
+    }
+
+    type BroadcastMessage = Round2Message;
+    type DirectMessage = ();
+    type Payload = Round2Payload;
+    type Artifact = ();
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        all_parties_except(self.num_parties(), self.party_idx())
+    }
+
+    fn make_broadcast_message(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        Some(Round2Message { z: self.z })
+    }
+
+    no_direct_messages!();
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        _direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        let idx = from.as_usize();
+        let expected = self.all_cap_r[idx] + self.context.public_shares[idx] * self.c;
+        if broadcast_msg.z.mul_by_generator() != expected {
+            return Err(Ed25519SigningError::R2InvalidPartialSignature);
+        }
+
+        Ok(Round2Payload { z: broadcast_msg.z })
+    }
+}
+
+impl FinalizableToResult for Round2 {
+    fn finalize_to_result(
+        self,
+        _rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        _artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let payloads = try_to_holevec(payloads, self.num_parties(), self.party_idx()).unwrap();
+        let s = payloads.iter().map(|payload| payload.z).sum::<Scalar>() + self.z;
+
+        // All the partial signatures have been verified, so the signature is valid.
+        Ed25519Signature::from_parts(
+            &self.cap_r,
+            &s,
+            &self.context.verifying_key,
+            &self.context.message,
+        )
+        .ok_or(FinalizeError::Proof(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand_core::{OsRng, RngCore};
+
+    use super::{Inputs, Round1};
+    use crate::ed25519::Ed25519ThresholdKeyShare;
+    use crate::rounds::{
+        test_utils::{quorum_key_shares, step_next_round, step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+
+    #[test]
+    fn execute_ed25519_signing() {
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+
+        let message = b"a message to sign";
+
+        let threshold_key_shares =
+            Ed25519ThresholdKeyShare::new_centralized(&mut OsRng, 2, 3, None);
+        let verifying_key = threshold_key_shares[0].verifying_key_bytes();
+
+        // Any two of the three shares can sign.
+        for signers in [[0, 1], [0, 2], [1, 2]] {
+            let r1 = quorum_key_shares(&threshold_key_shares, &signers)
+                .into_iter()
+                .enumerate()
+                .map(|(party_idx, key_share)| {
+                    Round1::new(
+                        &mut OsRng,
+                        &shared_randomness,
+                        signers.len(),
+                        PartyIdx::from_usize(party_idx),
+                        Inputs {
+                            key_share,
+                            message: message.as_ref().into(),
+                        },
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>();
+
+            let r1a = step_round(&mut OsRng, r1).unwrap();
+            let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+            let r2a = step_round(&mut OsRng, r2).unwrap();
+            let signatures = step_result(&mut OsRng, r2a).unwrap();
+
+            for signature in signatures {
+                assert_eq!(signature.verifying_key_bytes(), verifying_key);
+                assert!(signature.verify(message));
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Encryption of key shares at rest with a symmetric key supplied by the application.
+//!
+//! The share (see [`KeyShare::to_bytes`]) is encrypted with XChaCha20-Poly1305 under a random nonce,
+//! with the type of the share and the identifier of the scheme parameters as the associated data.
+
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    KeyInit, XChaCha20Poly1305,
+};
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::cggmp21::SchemeParams;
+use crate::common::{KeyShare, KeyShareDecodingError};
+use crate::threshold::ThresholdKeyShare;
+use crate::tools::{
+    hashing::{Chain, Hash, HashOutput},
+    serde_bytes,
+};
+
+/// The size of the key used to encrypt the shares.
+pub const SEALING_KEY_SIZE: usize = 32;
+
+/// The size of the random nonce chosen for each encryption.
+const NONCE_SIZE: usize = 24;
+
+/// Possible errors when decrypting an [`EncryptedKeyShare`].
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum KeyShareOpeningError {
+    /// The key is wrong, or the encrypted share has been modified.
+    AuthenticationFailed,
+    /// The decrypted key share could not be decoded: {0}
+    Decoding(KeyShareDecodingError),
+}
+
+impl core::error::Error for KeyShareOpeningError {}
+
+/// A [`KeyShare`] or a [`ThresholdKeyShare`] encrypted with a symmetric key,
+/// to be stored on disk or in a database.
+///
+/// The key must be [`SEALING_KEY_SIZE`] uniformly random bytes.
+/// If it is derived from a password, the application must use a memory-hard KDF
+/// (e.g. Argon2id) with a random salt stored along with the encrypted share.
+///
+/// The ciphertext is bound to the type of the share and the scheme parameters,
+/// so it can only be opened as the same type it was sealed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EncryptedKeyShare<S: SealableKeyShare> {
+    #[serde(with = "serde_bytes::as_base64")]
+    nonce: [u8; NONCE_SIZE],
+    #[serde(with = "serde_bytes::as_base64")]
+    ciphertext: Box<[u8]>,
+    #[serde(skip)]
+    phantom: PhantomData<fn() -> S>,
+}
+
+/// A key share that can be encrypted into an [`EncryptedKeyShare`].
+pub trait SealableKeyShare: Sized {
+    /// The name of the share type the ciphertext is bound to.
+    const SHARE_TYPE: &'static str;
+
+    /// The identifier of the scheme parameters the ciphertext is bound to.
+    const SCHEME_PARAMS: &'static str;
+
+    /// Serializes the share into bytes.
+    fn to_bytes(&self) -> Box<[u8]>;
+
+    /// Deserializes the share created by [`to_bytes`](Self::to_bytes).
+    fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareDecodingError>;
+}
+
+impl<P: SchemeParams> SealableKeyShare for KeyShare<P> {
+    const SHARE_TYPE: &'static str = "KeyShare";
+    const SCHEME_PARAMS: &'static str = P::ID;
+
+    fn to_bytes(&self) -> Box<[u8]> {
+        KeyShare::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareDecodingError> {
+        KeyShare::from_bytes(bytes)
+    }
+}
+
+impl<P: SchemeParams> SealableKeyShare for ThresholdKeyShare<P> {
+    const SHARE_TYPE: &'static str = "ThresholdKeyShare";
+    const SCHEME_PARAMS: &'static str = P::ID;
+
+    fn to_bytes(&self) -> Box<[u8]> {
+        ThresholdKeyShare::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareDecodingError> {
+        ThresholdKeyShare::from_bytes(bytes)
+    }
+}
+
+impl<S: SealableKeyShare> EncryptedKeyShare<S> {
+    /// Encrypts the key share with `key`.
+    pub fn seal(rng: &mut impl CryptoRngCore, key: &[u8; SEALING_KEY_SIZE], key_share: &S) -> Self {
+        Self::seal_bytes(rng, key, key_share.to_bytes())
+    }
+
+    /// Decrypts the key share with the key it was sealed with.
+    pub fn open(&self, key: &[u8; SEALING_KEY_SIZE]) -> Result<S, KeyShareOpeningError> {
+        let plaintext = XChaCha20Poly1305::new(key.into())
+            .decrypt(
+                &self.nonce.into(),
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: associated_data::<S>().as_ref(),
+                },
+            )
+            .map_err(|_| KeyShareOpeningError::AuthenticationFailed)?;
+        let plaintext = Zeroizing::new(plaintext);
+        S::from_bytes(&plaintext).map_err(KeyShareOpeningError::Decoding)
+    }
+
+    fn seal_bytes(
+        rng: &mut impl CryptoRngCore,
+        key: &[u8; SEALING_KEY_SIZE],
+        plaintext: Box<[u8]>,
+    ) -> Self {
+        let plaintext = Zeroizing::new(plaintext);
+        let mut nonce = [0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(key.into())
+            .encrypt(
+                &nonce.into(),
+                Payload {
+                    msg: &plaintext,
+                    aad: associated_data::<S>().as_ref(),
+                },
+            )
+            .expect("the key share is shorter than the XChaCha20-Poly1305 limit");
+
+        Self {
+            nonce,
+            ciphertext: ciphertext.into(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// The associated data binding the ciphertext to the share type and the scheme parameters.
+fn associated_data<S: SealableKeyShare>() -> HashOutput {
+    Hash::new_with_dst(b"KeyShareSealing")
+        .chain_bytes(S::SHARE_TYPE)
+        .chain_bytes(S::SCHEME_PARAMS)
+        .finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{OsRng, RngCore};
+
+    use super::{EncryptedKeyShare, KeyShareOpeningError, SEALING_KEY_SIZE};
+    use crate::cggmp21::{ProductionParams, TestParams};
+    use crate::common::{KeyShare, KeyShareDecodingError};
+    use crate::threshold::ThresholdKeyShare;
+
+    fn random_key() -> [u8; SEALING_KEY_SIZE] {
+        let mut key = [0u8; SEALING_KEY_SIZE];
+        OsRng.fill_bytes(&mut key);
+        key
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let key = random_key();
+
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let sealed = EncryptedKeyShare::seal(&mut OsRng, &key, &shares[1]);
+        let opened = sealed.open(&key).unwrap();
+        assert_eq!(opened.to_bytes(), shares[1].to_bytes());
+
+        // Survives a roundtrip through `serde`.
+        let bytes = bincode::serialize(&sealed).unwrap();
+        let decoded: EncryptedKeyShare<KeyShare<TestParams>> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.open(&key).unwrap().to_bytes(), shares[1].to_bytes());
+
+        let shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 2, 3, None);
+        let sealed = EncryptedKeyShare::seal(&mut OsRng, &key, &shares[2]);
+        let opened = sealed.open(&key).unwrap();
+        assert_eq!(opened.to_bytes(), shares[2].to_bytes());
+    }
+
+    #[test]
+    fn open_failures() {
+        let key = random_key();
+        let shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let sealed = EncryptedKeyShare::seal(&mut OsRng, &key, &shares[0]);
+
+        assert_eq!(
+            sealed.open(&random_key()).unwrap_err(),
+            KeyShareOpeningError::AuthenticationFailed
+        );
+
+        let mut modified = sealed.clone();
+        let mut ciphertext = modified.ciphertext.into_vec();
+        ciphertext[0] ^= 1;
+        modified.ciphertext = ciphertext.into();
+        assert_eq!(
+            modified.open(&key).unwrap_err(),
+            KeyShareOpeningError::AuthenticationFailed
+        );
+
+        // The ciphertext is bound to the share type and the scheme parameters.
+        let as_threshold = EncryptedKeyShare::<ThresholdKeyShare<TestParams>> {
+            nonce: sealed.nonce,
+            ciphertext: sealed.ciphertext.clone(),
+            phantom: Default::default(),
+        };
+        assert_eq!(
+            as_threshold.open(&key).unwrap_err(),
+            KeyShareOpeningError::AuthenticationFailed
+        );
+        let as_production = EncryptedKeyShare::<KeyShare<ProductionParams>> {
+            nonce: sealed.nonce,
+            ciphertext: sealed.ciphertext.clone(),
+            phantom: Default::default(),
+        };
+        assert_eq!(
+            as_production.open(&key).unwrap_err(),
+            KeyShareOpeningError::AuthenticationFailed
+        );
+
+        // A valid encryption of something that is not a key share.
+        let garbage =
+            EncryptedKeyShare::<KeyShare<TestParams>>::seal_bytes(&mut OsRng, &key, [].into());
+        assert_eq!(
+            garbage.open(&key).unwrap_err(),
+            KeyShareOpeningError::Decoding(KeyShareDecodingError::Empty)
+        );
+    }
+}
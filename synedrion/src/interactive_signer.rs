@@ -0,0 +1,149 @@
+use alloc::collections::BTreeSet;
+use alloc::format;
+use core::fmt::Debug;
+
+use digest::{consts::U32, Digest};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+
+use crate::cggmp21::{InteractiveSigningResult, SchemeParams};
+use crate::common::KeyShare;
+use crate::constructors::{
+    check_key_share, make_interactive_signing_session,
+    make_interactive_signing_session_with_digest, PrehashedMessage,
+};
+use crate::curve::VerifyingKey;
+use crate::sessions::{LocalError, MessageFormat, Session};
+
+/// A party holding a [`KeyShare`], creating the sessions that sign with the shared key.
+///
+/// The methods mirror [`PrehashSigner`](signature::hazmat::PrehashSigner)
+/// and [`DigestSigner`](signature::DigestSigner),
+/// but since the signature is created by all the parties together,
+/// they return a session to be executed (e.g. with [`run_session`](crate::run_session))
+/// instead of the signature itself.
+/// The [`Keypair`] implementation returns the shared verifying key.
+#[derive(Debug, Clone)]
+pub struct InteractiveSigner<P: SchemeParams, Signer, Verifier> {
+    key_share: KeyShare<P>,
+    signer: Signer,
+    verifiers: BTreeSet<Verifier>,
+}
+
+impl<P, Signer, Verifier> InteractiveSigner<P, Signer, Verifier>
+where
+    P: SchemeParams + 'static,
+    Signer: Keypair<VerifyingKey = Verifier> + Clone,
+    Verifier: Debug + Clone + Ord + Serialize,
+{
+    /// Creates a signer for the party identified by `signer` among `verifiers`,
+    /// checking that `key_share` belongs to it.
+    pub fn new(
+        key_share: KeyShare<P>,
+        signer: Signer,
+        verifiers: BTreeSet<Verifier>,
+    ) -> Result<Self, LocalError> {
+        check_key_share(&signer, &verifiers, &key_share)?;
+        Ok(Self {
+            key_share,
+            signer,
+            verifiers,
+        })
+    }
+
+    /// Returns the key share of this party.
+    pub fn key_share(&self) -> &KeyShare<P> {
+        &self.key_share
+    }
+
+    /// Creates a session signing `prehash`, which must be 32 bytes long
+    /// (see [`make_interactive_signing_session`]).
+    pub fn sign_prehash_session<Sig, F>(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        prehash: &[u8],
+    ) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+    where
+        Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+        Signer: RandomizedPrehashSigner<Sig>,
+        Verifier: PrehashVerifier<Sig>,
+        F: MessageFormat,
+    {
+        let prehashed_message: PrehashedMessage = prehash.try_into().map_err(|_| {
+            LocalError(format!(
+                "The prehash must be 32 bytes long (got: {})",
+                prehash.len()
+            ))
+        })?;
+        make_interactive_signing_session(
+            rng,
+            shared_randomness,
+            self.signer.clone(),
+            &self.verifiers,
+            &self.key_share,
+            &prehashed_message,
+        )
+    }
+
+    /// Creates a session signing the message hashed with `digest`
+    /// (see [`make_interactive_signing_session_with_digest`]).
+    pub fn sign_digest_session<D, Sig, F>(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        digest: D,
+    ) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+    where
+        D: Digest<OutputSize = U32>,
+        Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+        Signer: RandomizedPrehashSigner<Sig>,
+        Verifier: PrehashVerifier<Sig>,
+        F: MessageFormat,
+    {
+        make_interactive_signing_session_with_digest(
+            rng,
+            shared_randomness,
+            self.signer.clone(),
+            &self.verifiers,
+            &self.key_share,
+            digest,
+        )
+    }
+
+    /// Creates a session signing `message` hashed with [`SchemeParams::MessageDigest`]
+    /// (see [`SchemeParams::prehash_message`]).
+    pub fn sign_message_session<Sig, F>(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        message: &[u8],
+    ) -> Result<Session<InteractiveSigningResult<P>, Sig, Signer, Verifier, F>, LocalError>
+    where
+        Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+        Signer: RandomizedPrehashSigner<Sig>,
+        Verifier: PrehashVerifier<Sig>,
+        F: MessageFormat,
+    {
+        make_interactive_signing_session(
+            rng,
+            shared_randomness,
+            self.signer.clone(),
+            &self.verifiers,
+            &self.key_share,
+            &P::prehash_message(message),
+        )
+    }
+}
+
+impl<P: SchemeParams, Signer, Verifier> Keypair for InteractiveSigner<P, Signer, Verifier> {
+    type VerifyingKey = VerifyingKey;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        self.key_share.verifying_key()
+    }
+}
@@ -0,0 +1,204 @@
+//! Lagrange interpolation over the share indices of [`ThresholdKeyShare`](crate::ThresholdKeyShare)s.
+//!
+//! These are the functions used internally to convert between the threshold and the additive shares,
+//! exposed for the external tools that reconstruct keys or audit threshold share sets.
+//! The secret values are [`k256::Scalar`]s, and the public ones are [`k256::ProjectivePoint`]s
+//! (or the `p256` ones with the `p256` feature).
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+
+use crate::curve::{BackendPoint, BackendScalar, Point, Scalar};
+use crate::tools::sss::{evaluate_polynomial, interpolation_coeff as coeff, ShareIdx};
+
+/// Possible errors when interpolating shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum InterpolationError {
+    /// No share indices were given.
+    Empty,
+    /// One of the share indices is zero (the point where the shared secret is located).
+    ZeroIndex,
+    /// The share indices are not distinct.
+    DuplicateIndex,
+    /// The share index is not among the given ones.
+    UnknownIndex,
+}
+
+impl core::error::Error for InterpolationError {}
+
+fn check_idxs<'a>(
+    share_idxs: impl Iterator<Item = &'a ShareIdx>,
+) -> Result<(), InterpolationError> {
+    let mut share_idxs = share_idxs.collect::<Vec<_>>();
+    if share_idxs.is_empty() {
+        return Err(InterpolationError::Empty);
+    }
+    if share_idxs.iter().any(|idx| **idx == ShareIdx::new(0)) {
+        return Err(InterpolationError::ZeroIndex);
+    }
+    let len = share_idxs.len();
+    share_idxs.sort();
+    share_idxs.dedup();
+    if share_idxs.len() != len {
+        return Err(InterpolationError::DuplicateIndex);
+    }
+    Ok(())
+}
+
+/// Returns the Lagrange coefficient of the share with the index `share_idx`
+/// for interpolating at zero from the shares with the indices `share_idxs`.
+///
+/// That is, if `share_idxs` includes at least `threshold` indices,
+/// the shared secret is the sum of the shares multiplied by their coefficients.
+pub fn interpolation_coeff(
+    share_idxs: &[ShareIdx],
+    share_idx: &ShareIdx,
+) -> Result<BackendScalar, InterpolationError> {
+    check_idxs(share_idxs.iter())?;
+    if !share_idxs.contains(share_idx) {
+        return Err(InterpolationError::UnknownIndex);
+    }
+    Ok(coeff(share_idxs, share_idx).to_backend())
+}
+
+/// Returns the value at zero of the polynomial passing through the given secret shares
+/// (that is, the shared secret, if there are at least `threshold` shares).
+pub fn interpolate_scalars(
+    shares: &BTreeMap<ShareIdx, BackendScalar>,
+) -> Result<BackendScalar, InterpolationError> {
+    check_idxs(shares.keys())?;
+    let share_idxs = shares.keys().cloned().collect::<Vec<_>>();
+    Ok(shares
+        .iter()
+        .map(|(idx, share)| Scalar::from_backend(*share) * coeff(&share_idxs, idx))
+        .sum::<Scalar>()
+        .to_backend())
+}
+
+/// Returns the value at zero of the polynomial passing through the given public shares
+/// (that is, the verifying key, if there are at least `threshold` shares).
+pub fn interpolate_points(
+    shares: &BTreeMap<ShareIdx, BackendPoint>,
+) -> Result<BackendPoint, InterpolationError> {
+    check_idxs(shares.keys())?;
+    let share_idxs = shares.keys().cloned().collect::<Vec<_>>();
+    Ok(shares
+        .iter()
+        .map(|(idx, share)| Point::from_backend(*share) * coeff(&share_idxs, idx))
+        .sum::<Point>()
+        .to_backend())
+}
+
+/// Returns the secret share with the index `share_idx` for the sharing polynomial
+/// with the coefficients `coeffs` (starting from the constant term, which is the shared secret).
+pub fn evaluate_share(coeffs: &[BackendScalar], share_idx: &ShareIdx) -> BackendScalar {
+    if coeffs.is_empty() {
+        return BackendScalar::ZERO;
+    }
+    let coeffs = coeffs
+        .iter()
+        .map(|coeff| Scalar::from_backend(*coeff))
+        .collect::<Vec<_>>();
+    evaluate_polynomial(&coeffs, &share_idx.to_scalar()).to_backend()
+}
+
+/// Returns the public share with the index `share_idx` for the sharing polynomial
+/// with the coefficients multiplied by the generator `coeffs`
+/// (starting from the constant term, which is the verifying key).
+///
+/// This can be used to check the shares against a published commitment to the sharing polynomial.
+pub fn evaluate_public_share(coeffs: &[BackendPoint], share_idx: &ShareIdx) -> BackendPoint {
+    if coeffs.is_empty() {
+        return BackendPoint::IDENTITY;
+    }
+    let coeffs = coeffs
+        .iter()
+        .map(|coeff| Point::from_backend(*coeff))
+        .collect::<Vec<_>>();
+    evaluate_polynomial(&coeffs, &share_idx.to_scalar()).to_backend()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    use rand_core::OsRng;
+
+    use super::{
+        evaluate_public_share, evaluate_share, interpolate_points, interpolate_scalars,
+        interpolation_coeff, InterpolationError,
+    };
+    use crate::curve::{BackendPoint, BackendScalar, Scalar};
+    use crate::ShareIdx;
+
+    #[test]
+    fn split_and_interpolate() {
+        let coeffs = (0..3)
+            .map(|_| Scalar::random(&mut OsRng).to_backend())
+            .collect::<Vec<_>>();
+        let public_coeffs = coeffs
+            .iter()
+            .map(|coeff| BackendPoint::GENERATOR * coeff)
+            .collect::<Vec<_>>();
+
+        let share_idxs = [2, 3, 5].map(ShareIdx::new);
+        let shares = share_idxs
+            .iter()
+            .map(|idx| (*idx, evaluate_share(&coeffs, idx)))
+            .collect::<BTreeMap<_, _>>();
+        let public_shares = share_idxs
+            .iter()
+            .map(|idx| (*idx, evaluate_public_share(&public_coeffs, idx)))
+            .collect::<BTreeMap<_, _>>();
+
+        for idx in share_idxs.iter() {
+            assert_eq!(BackendPoint::GENERATOR * shares[idx], public_shares[idx]);
+        }
+
+        assert_eq!(interpolate_scalars(&shares).unwrap(), coeffs[0]);
+        assert_eq!(
+            interpolate_points(&public_shares).unwrap(),
+            public_coeffs[0]
+        );
+
+        // The coefficients interpolate the constant polynomial 1.
+        let sum = share_idxs
+            .iter()
+            .map(|idx| interpolation_coeff(&share_idxs, idx).unwrap())
+            .sum::<BackendScalar>();
+        assert_eq!(sum, BackendScalar::ONE);
+
+        // Fewer shares than the threshold do not determine the secret.
+        let mut too_few = shares.clone();
+        too_few.remove(&share_idxs[0]);
+        assert_ne!(interpolate_scalars(&too_few).unwrap(), coeffs[0]);
+    }
+
+    #[test]
+    fn invalid_idxs() {
+        let share_idxs = [1, 2].map(ShareIdx::new);
+        assert_eq!(
+            interpolation_coeff(&share_idxs, &ShareIdx::new(3)),
+            Err(InterpolationError::UnknownIndex)
+        );
+        assert_eq!(
+            interpolation_coeff(&[], &ShareIdx::new(1)),
+            Err(InterpolationError::Empty)
+        );
+        assert_eq!(
+            interpolation_coeff(&[0, 1].map(ShareIdx::new), &ShareIdx::new(1)),
+            Err(InterpolationError::ZeroIndex)
+        );
+        assert_eq!(
+            interpolation_coeff(&[1, 2, 1].map(ShareIdx::new), &ShareIdx::new(1)),
+            Err(InterpolationError::DuplicateIndex)
+        );
+        assert_eq!(
+            interpolate_scalars(&BTreeMap::new()),
+            Err(InterpolationError::Empty)
+        );
+    }
+}
@@ -13,6 +13,11 @@
     trivial_numeric_casts,
     unused_qualifications
 )]
+// Some of the shared internals are only used by a part of the protocols.
+#![cfg_attr(
+    not(all(feature = "keygen", feature = "signing")),
+    allow(dead_code, unused_imports, unused_macros)
+)]
 
 extern crate alloc;
 
@@ -25,34 +30,140 @@ cfg_if::cfg_if! {
     }
 }
 
+mod adaptor;
+#[cfg(all(feature = "signing", feature = "testing"))]
+mod audit;
+mod beacon;
+mod bip32;
+mod bip340;
 mod common;
 mod constructors;
 mod curve;
+pub mod curve_encoding;
+#[cfg(feature = "ed25519")]
+mod ed25519;
+mod encrypted_key_share;
+#[cfg(feature = "signing")]
+mod interactive_signer;
+pub mod interpolation;
 mod paillier;
+mod recovery;
 mod rounds;
 pub mod sessions;
+mod sub_share;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+#[cfg(any(test, feature = "testnet"))]
+pub mod testnet;
 mod threshold;
 mod tools;
+#[cfg(feature = "tss-lib")]
+mod tss_lib;
 mod uint;
+pub mod verify;
+// The WWW02 key resharing is not exposed through the sessions yet.
+#[allow(dead_code)]
 mod www02;
 
 // Some re-exports to avoid the need for version-matching
+pub use digest;
 pub use k256;
+// The ECDSA keys and signatures of the curve the shared keys are on.
+#[cfg(not(feature = "p256"))]
 pub use k256::ecdsa;
+#[cfg(feature = "p256")]
+pub use p256;
+#[cfg(feature = "p256")]
+pub use p256::ecdsa;
 pub use signature;
 
+pub use adaptor::AdaptorSignature;
+#[cfg(all(feature = "signing", feature = "testing"))]
+pub use audit::{NonceAuditError, NonceAuditRecord};
+pub use beacon::{BeaconError, BeaconRandomness, BeaconVerifier};
+pub use bip32::{ChainCode, DeriveChildError};
+pub use bip340::{SchnorrSignature, TaprootTweak};
+#[cfg(feature = "key-refresh")]
+pub use cggmp21::KeyRefreshResult;
+pub use cggmp21::{
+    validate_scheme_params, ExportedKey, KeyDecryptionError, KeyExportError, KeyExportResult,
+    Params1536, Params4096, ProductionParams, SchemeParams, TestParams, ThresholdRefreshError,
+    ThresholdRefreshResult, PRESET_SECURITY_LEVELS,
+};
+#[cfg(feature = "signing")]
+pub use cggmp21::{
+    AdaptorSigningResult, InteractiveSigningError, InteractiveSigningProof,
+    InteractiveSigningResult, PresigningError, PresigningMode, PresigningPrecompute,
+    PresigningProof, PresigningResult, SchnorrSigningError, SchnorrSigningResult, SigningError,
+    SigningProof, SigningResult,
+};
+#[cfg(feature = "keygen")]
 pub use cggmp21::{
-    InteractiveSigningError, InteractiveSigningProof, InteractiveSigningResult, KeyGenError,
-    KeyGenProof, KeyGenResult, KeyInitError, KeyInitResult, KeyRefreshResult, PresigningError,
-    PresigningProof, PresigningResult, ProductionParams, SchemeParams, SigningProof, SigningResult,
-    TestParams,
+    KeyGenError, KeyGenProof, KeyGenResult, KeyImportError, KeyImportResult, KeyInitError,
+    KeyInitResult,
+};
+#[cfg(feature = "key-refresh")]
+pub use common::{ExternalPaillierKey, ExternalPaillierKeyError, PregeneratedPaillierKey};
+pub use common::{
+    KeyShare, KeyShareChange, KeyShareConsistencyReport, KeyShareDecodingError, KeyShareError,
+    KeyShareFingerprint, KeyShareSeed,
+};
+#[cfg(feature = "signing")]
+pub use common::{PresignatureId, PresignatureJournal, PresigningData};
+#[cfg(feature = "ed25519")]
+pub use constructors::make_ed25519_signing_session;
+#[cfg(all(feature = "signing", feature = "testing"))]
+pub use constructors::make_presigning_session_with_audit;
+#[cfg(feature = "signing")]
+pub use constructors::{
+    make_adaptor_signing_session, make_batch_interactive_signing_session,
+    make_interactive_signing_session, make_interactive_signing_session_with_beacon,
+    make_interactive_signing_session_with_digest, make_interactive_signing_session_with_precompute,
+    make_interactive_signing_session_with_tweak, make_presigning_session,
+    make_presigning_session_with_beacon, make_presigning_session_with_precompute,
+    make_schnorr_signing_session, make_signing_session, make_signing_session_with_journal,
 };
-pub use common::{KeyShare, KeyShareChange, PresigningData};
+#[cfg(feature = "key-refresh")]
 pub use constructors::{
-    make_interactive_signing_session, make_key_gen_session, make_key_refresh_session,
-    PrehashedMessage,
+    make_aux_refresh_session, make_key_refresh_session, make_key_refresh_session_with_beacon,
+    make_key_refresh_session_with_external_paillier_key,
+    make_key_refresh_session_with_paillier_key, make_share_refresh_session,
+};
+pub use constructors::{make_key_export_session, make_threshold_refresh_session, PrehashedMessage};
+#[cfg(feature = "keygen")]
+pub use constructors::{
+    make_key_gen_session, make_key_gen_session_with_beacon, make_key_import_session,
 };
 pub use curve::RecoverableSignature;
-pub use rounds::ProtocolResult;
-pub use sessions::{CombinedMessage, FinalizeOutcome, Session};
-pub use threshold::ThresholdKeyShare;
+#[cfg(feature = "transport-ed25519")]
+pub use ed25519::{Ed25519IdentityKey, Ed25519IdentitySignature, Ed25519IdentityVerifyingKey};
+#[cfg(feature = "ed25519")]
+pub use ed25519::{
+    Ed25519KeyShare, Ed25519Signature, Ed25519SigningError, Ed25519SigningResult,
+    Ed25519ThresholdKeyShare,
+};
+pub use encrypted_key_share::{
+    EncryptedKeyShare, KeyShareOpeningError, SealableKeyShare, SEALING_KEY_SIZE,
+};
+#[cfg(feature = "signing")]
+pub use interactive_signer::InteractiveSigner;
+#[cfg(feature = "paillier")]
+pub use paillier::{PaillierCiphertext, PaillierError, PaillierPublicKey, PaillierSecretKey};
+pub use recovery::{EncryptedShare, ShareDecryptionError};
+pub use rounds::{BatchError, BatchProof, BatchResult, ProtocolResult};
+pub use sessions::{
+    run_session, CombinedMessage, FinalizeOutcome, ProtocolInfo, ReceiveOutcome, RoundInfo,
+    Session, Transport,
+};
+pub use sub_share::{KeySubShare, KeySubShareError};
+pub use threshold::{
+    CombineKeySharesError, RetryQuorum, RetryQuorumError, ThresholdKeyShare,
+    ThresholdKeyShareChange, WeightedKeyShare, WeightedKeyShareError,
+};
+pub use tools::hashing::{
+    Chain, DigestReader, DigestTranscript, FiatShamirTranscript, Keccak256Transcript,
+    Sha256Transcript, XofHash,
+};
+pub use tools::sss::ShareIdx;
+#[cfg(feature = "tss-lib")]
+pub use tss_lib::{TssLibImportError, TssLibKeyShare};
@@ -1,8 +1,13 @@
 mod encryption;
+#[cfg(feature = "paillier")]
+mod homomorphic;
 mod keys;
 mod params;
 mod ring_pedersen;
 
+#[cfg(feature = "paillier")]
+pub use homomorphic::{PaillierCiphertext, PaillierError, PaillierPublicKey, PaillierSecretKey};
+
 pub(crate) use encryption::{Ciphertext, CiphertextMod, Randomizer, RandomizerMod};
 pub(crate) use keys::{
     PublicKeyPaillier, PublicKeyPaillierPrecomputed, SecretKeyPaillier,
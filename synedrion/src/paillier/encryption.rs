@@ -1,14 +1,17 @@
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use core::ops::{Add, Mul};
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 use super::keys::{PublicKeyPaillierPrecomputed, SecretKeyPaillierPrecomputed};
 use super::params::PaillierParams;
 use crate::tools::hashing::{Chain, Hashable};
+use crate::tools::serialized_size::{uint_size, MaxSerializedSize};
 use crate::uint::{
-    subtle::{Choice, ConditionallyNegatable, ConditionallySelectable},
+    subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeGreater},
     Bounded, HasWide, NonZero, Retrieve, Signed, UintLike, UintModLike,
 };
 
@@ -16,6 +19,18 @@ use crate::uint::{
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub(crate) struct Randomizer<P: PaillierParams>(P::Uint);
 
+impl<P: PaillierParams> MaxSerializedSize for Randomizer<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        uint_size::<P::Uint>()
+    }
+}
+
+impl<P: PaillierParams> Zeroize for Randomizer<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl<P: PaillierParams> Randomizer<P> {
     pub fn random(rng: &mut impl CryptoRngCore, pk: &PublicKeyPaillierPrecomputed<P>) -> Self {
         RandomizerMod::random(rng, pk).retrieve()
@@ -29,6 +44,12 @@ impl<P: PaillierParams> Randomizer<P> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct RandomizerMod<P: PaillierParams>(P::UintMod);
 
+impl<P: PaillierParams> Zeroize for RandomizerMod<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl<P: PaillierParams> RandomizerMod<P> {
     pub fn random(rng: &mut impl CryptoRngCore, pk: &PublicKeyPaillierPrecomputed<P>) -> Self {
         Self(pk.random_invertible_group_elem(rng))
@@ -54,14 +75,14 @@ impl<'a, P: PaillierParams> Mul<&'a RandomizerMod<P>> for &'a RandomizerMod<P> {
     }
 }
 
-impl<'a, P: PaillierParams> Mul<RandomizerMod<P>> for &'a RandomizerMod<P> {
+impl<P: PaillierParams> Mul<RandomizerMod<P>> for &RandomizerMod<P> {
     type Output = RandomizerMod<P>;
     fn mul(self, rhs: RandomizerMod<P>) -> Self::Output {
         self * &rhs
     }
 }
 
-impl<'a, P: PaillierParams> Mul<&'a RandomizerMod<P>> for RandomizerMod<P> {
+impl<P: PaillierParams> Mul<&RandomizerMod<P>> for RandomizerMod<P> {
     type Output = RandomizerMod<P>;
     fn mul(self, rhs: &RandomizerMod<P>) -> Self::Output {
         &self * rhs
@@ -88,6 +109,12 @@ pub(crate) struct Ciphertext<P: PaillierParams> {
     phantom: PhantomData<P>,
 }
 
+impl<P: PaillierParams> MaxSerializedSize for Ciphertext<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        uint_size::<P::WideUint>()
+    }
+}
+
 impl<P: PaillierParams> Ciphertext<P> {
     pub fn to_mod(&self, pk: &PublicKeyPaillierPrecomputed<P>) -> CiphertextMod<P> {
         CiphertextMod {
@@ -183,6 +210,43 @@ impl<P: PaillierParams> CiphertextMod<P> {
         Self::new_with_randomizer_inner(pk, &plaintext_reduced, randomizer, plaintext.is_negative())
     }
 
+    /// Returns `enc(plaintext, randomizer) (+) sum_i C_i (*) e_i`
+    /// for the ciphertexts and coefficients in `terms`,
+    /// calculating the randomizer power and the ciphertext powers with a single multi-exponentiation.
+    ///
+    /// Note: this is variable time, so it must only be used with public values
+    /// (e.g. when verifying ZK proofs).
+    /// Returns `None` if one of the ciphertexts with a negative coefficient is not invertible.
+    pub fn new_with_randomizer_and_lincomb_vartime(
+        pk: &PublicKeyPaillierPrecomputed<P>,
+        plaintext: &Signed<P::Uint>,
+        randomizer: &Randomizer<P>,
+        terms: &[(&Self, Signed<P::Uint>)],
+    ) -> Option<Self> {
+        let precomputed = pk.precomputed_modulus_squared();
+
+        // `(N + 1)^m mod N^2 == 1 + m * N mod N^2`, as in `new_with_randomizer_inner()`.
+        let mut prod_mod = plaintext.abs().mul_wide(pk.modulus()).to_mod(precomputed);
+        prod_mod.conditional_negate(plaintext.is_negative());
+        let factor1 = prod_mod + P::WideUintMod::one(precomputed);
+
+        let mut bases_and_exponents = Vec::with_capacity(terms.len() + 1);
+        bases_and_exponents.push((
+            randomizer.0.into_wide().to_mod(precomputed),
+            Signed::new_positive(pk.modulus().into_wide(), P::MODULUS_BITS as u32).unwrap(),
+        ));
+        for (ciphertext, coeff) in terms {
+            assert!(ciphertext.pk == *pk);
+            bases_and_exponents.push((ciphertext.ciphertext, coeff.into_wide()));
+        }
+        let factor2 = P::WideUintMod::multi_pow_signed_vartime(precomputed, &bases_and_exponents)?;
+
+        Some(Self {
+            pk: pk.clone(),
+            ciphertext: factor1 * factor2,
+        })
+    }
+
     /// Encrypts the plaintext with a random randomizer.
     pub fn new(
         rng: &mut impl CryptoRngCore,
@@ -192,7 +256,7 @@ impl<P: PaillierParams> CiphertextMod<P> {
         Self::new_with_randomizer(pk, plaintext, &Randomizer::random(rng, pk))
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "bench-internals"))]
     pub fn new_signed(
         rng: &mut impl CryptoRngCore,
         pk: &PublicKeyPaillierPrecomputed<P>,
@@ -202,12 +266,30 @@ impl<P: PaillierParams> CiphertextMod<P> {
     }
 
     /// Decrypts this ciphertext assuming that the plaintext is in range `[0, N)`.
-    pub fn decrypt(&self, sk: &SecretKeyPaillierPrecomputed<P>) -> P::Uint {
+    ///
+    /// Returns `None` if the ciphertext is not invertible modulo `N`.
+    pub fn decrypt(&self, sk: &SecretKeyPaillierPrecomputed<P>) -> Option<P::Uint> {
+        assert_eq!(sk.public_key(), &self.pk);
+        sk.decrypt(&self.ciphertext.retrieve())
+    }
+
+    /// Decrypts this ciphertext with a single exponentiation modulo `N^2`,
+    /// as a reference for [`decrypt`](Self::decrypt) in tests and benchmarks.
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub fn decrypt_without_crt(&self, sk: &SecretKeyPaillierPrecomputed<P>) -> P::Uint {
+        use crate::uint::Invert;
+
         assert_eq!(sk.public_key(), &self.pk);
 
         let pk = sk.public_key();
         let totient_wide = sk.totient().into_wide();
         let modulus_wide = NonZero::new(pk.modulus().into_wide()).unwrap();
+        let inv_totient = sk
+            .totient()
+            .as_ref()
+            .to_mod(pk.precomputed_modulus())
+            .invert()
+            .unwrap();
 
         // Calculate the plaintext `m = ((C^phi mod N^2 - 1) / N) * mu mod N`,
         // where `m` is the plaintext, `C` is the ciphertext,
@@ -224,17 +306,19 @@ impl<P: PaillierParams> CiphertextMod<P> {
         .unwrap();
         let x_mod = x.to_mod(pk.precomputed_modulus());
 
-        (x_mod * sk.inv_totient()).retrieve()
+        (x_mod * inv_totient).retrieve()
     }
 
     /// Decrypts this ciphertext assuming that the plaintext is in range `[-N/2, N/2)`.
-    pub fn decrypt_signed(&self, sk: &SecretKeyPaillierPrecomputed<P>) -> Signed<P::Uint> {
+    ///
+    /// Returns `None` if the ciphertext is not invertible modulo `N`.
+    pub fn decrypt_signed(&self, sk: &SecretKeyPaillierPrecomputed<P>) -> Option<Signed<P::Uint>> {
         assert_eq!(sk.public_key(), &self.pk);
 
         let pk = sk.public_key();
-        let positive_result = self.decrypt(sk);
+        let positive_result = self.decrypt(sk)?;
         let negative_result = pk.modulus().wrapping_sub(&positive_result);
-        let is_negative = Choice::from((positive_result > pk.modulus().shr_vartime(1)) as u8);
+        let is_negative = positive_result.ct_gt(&pk.modulus().shr_vartime(1));
 
         let mut result = Signed::new_from_unsigned(
             P::Uint::conditional_select(&positive_result, &negative_result, is_negative),
@@ -243,7 +327,7 @@ impl<P: PaillierParams> CiphertextMod<P> {
         .unwrap();
 
         result.conditional_negate(is_negative);
-        result
+        Some(result)
     }
 
     /// Derive the randomizer used to create this ciphertext.
@@ -263,11 +347,13 @@ impl<P: PaillierParams> CiphertextMod<P> {
         // Therefore `C mod N = rho^N mod N`.
         let ciphertext_mod_n =
             P::Uint::try_from_wide(self.ciphertext.retrieve() % modulus_wide).unwrap();
-        let ciphertext_mod_n = ciphertext_mod_n.to_mod(pk.precomputed_modulus());
 
         // To isolate `rho`, calculate `(rho^N)^(N^(-1)) mod N`.
         // The order of `Z_N` is `phi(N)`, so the inversion in the exponent is modulo `phi(N)`.
-        RandomizerMod(ciphertext_mod_n.pow_bounded(sk.inv_modulus()))
+        RandomizerMod(
+            sk.nth_root(&ciphertext_mod_n)
+                .to_mod(pk.precomputed_modulus()),
+        )
     }
 
     // Note: while it is true that `enc(x) (*) rhs == enc((x * rhs) mod N)`,
@@ -392,11 +478,13 @@ impl<P: PaillierParams> Hashable for Ciphertext<P> {
 
 #[cfg(test)]
 mod tests {
+    use core::marker::PhantomData;
+
     use rand_core::OsRng;
 
     use super::super::params::PaillierTest;
     use super::super::{PaillierParams, SecretKeyPaillier};
-    use super::{CiphertextMod, RandomizerMod};
+    use super::{Ciphertext, CiphertextMod, RandomizerMod};
 
     use crate::uint::{
         subtle::ConditionallyNegatable, HasWide, NonZero, RandomMod, Signed, UintLike,
@@ -444,21 +532,44 @@ mod tests {
         let plaintext =
             <PaillierTest as PaillierParams>::Uint::random_mod(&mut OsRng, &pk.modulus_nonzero());
         let ciphertext = CiphertextMod::<PaillierTest>::new(&mut OsRng, pk, &plaintext);
-        let plaintext_back = ciphertext.decrypt(&sk);
+        let plaintext_back = ciphertext.decrypt(&sk).unwrap();
         assert_eq!(plaintext, plaintext_back);
+        assert_eq!(ciphertext.decrypt_without_crt(&sk), plaintext);
 
         let ciphertext_wire = ciphertext.retrieve();
         let ciphertext_back = ciphertext_wire.to_mod(pk);
         assert_eq!(ciphertext, ciphertext_back);
     }
 
+    #[test]
+    fn decrypt_non_invertible() {
+        let sk = SecretKeyPaillier::<PaillierTest>::random(&mut OsRng).to_precomputed();
+        let pk = sk.public_key();
+
+        // A malicious party can send a ciphertext sharing a factor with the modulus,
+        // which must be rejected instead of causing a panic.
+        let (p, _q) = sk.primes();
+        for value in [
+            <PaillierTest as PaillierParams>::WideUint::ZERO,
+            p.abs().into_wide(),
+        ] {
+            let ciphertext = Ciphertext::<PaillierTest> {
+                ciphertext: value,
+                phantom: PhantomData,
+            }
+            .to_mod(pk);
+            assert!(ciphertext.decrypt(&sk).is_none());
+            assert!(ciphertext.decrypt_signed(&sk).is_none());
+        }
+    }
+
     #[test]
     fn signed_roundtrip() {
         let sk = SecretKeyPaillier::<PaillierTest>::random(&mut OsRng).to_precomputed();
         let pk = sk.public_key();
         let plaintext = Signed::random(&mut OsRng);
         let ciphertext = CiphertextMod::new_signed(&mut OsRng, pk, &plaintext);
-        let plaintext_back = ciphertext.decrypt_signed(&sk);
+        let plaintext_back = ciphertext.decrypt_signed(&sk).unwrap();
         let plaintext_reduced = reduce::<PaillierTest>(&plaintext, &pk.modulus_nonzero());
         assert_eq!(plaintext_reduced, plaintext_back);
     }
@@ -489,7 +600,7 @@ mod tests {
 
         let coeff = Signed::random(&mut OsRng);
         let new_ciphertext = ciphertext * coeff;
-        let new_plaintext = new_ciphertext.decrypt(&sk);
+        let new_plaintext = new_ciphertext.decrypt(&sk).unwrap();
 
         assert_eq!(
             mul_mod(&plaintext, &coeff, &pk.modulus_nonzero()),
@@ -511,7 +622,7 @@ mod tests {
         let ciphertext2 = CiphertextMod::<PaillierTest>::new(&mut OsRng, pk, &plaintext2);
 
         let new_ciphertext = ciphertext1 + ciphertext2;
-        let new_plaintext = new_ciphertext.decrypt(&sk);
+        let new_plaintext = new_ciphertext.decrypt(&sk).unwrap();
 
         assert_eq!(plaintext1.add_mod(&plaintext2, pk.modulus()), new_plaintext);
     }
@@ -531,11 +642,44 @@ mod tests {
         let ciphertext3 = CiphertextMod::<PaillierTest>::new(&mut OsRng, pk, &plaintext3);
         let result = ciphertext1 * plaintext2 + ciphertext3;
 
-        let plaintext_back = result.decrypt(&sk);
+        let plaintext_back = result.decrypt(&sk).unwrap();
         assert_eq!(
             mul_mod(&plaintext1, &plaintext2, &pk.modulus_nonzero())
                 .add_mod(&plaintext3, pk.modulus()),
             plaintext_back
         );
     }
+
+    #[test]
+    fn lincomb_vartime() {
+        let sk = SecretKeyPaillier::<PaillierTest>::random(&mut OsRng).to_precomputed();
+        let pk = sk.public_key();
+
+        let ciphertext1 = CiphertextMod::<PaillierTest>::new_signed(
+            &mut OsRng,
+            pk,
+            &Signed::random_bounded_bits(&mut OsRng, 256),
+        );
+        let ciphertext2 = CiphertextMod::<PaillierTest>::new_signed(
+            &mut OsRng,
+            pk,
+            &Signed::random_bounded_bits(&mut OsRng, 256),
+        );
+        let plaintext = Signed::random_bounded_bits(&mut OsRng, 256);
+        let randomizer = RandomizerMod::random(&mut OsRng, pk).retrieve();
+        let coeff1 = Signed::random_bounded_bits(&mut OsRng, 256);
+        let coeff2 = -Signed::random_bounded_bits(&mut OsRng, 256);
+
+        let expected = CiphertextMod::new_with_randomizer_signed(pk, &plaintext, &randomizer)
+            + &ciphertext1 * coeff1
+            + &ciphertext2 * coeff2;
+        let result = CiphertextMod::new_with_randomizer_and_lincomb_vartime(
+            pk,
+            &plaintext,
+            &randomizer,
+            &[(&ciphertext1, coeff1), (&ciphertext2, coeff2)],
+        )
+        .unwrap();
+        assert_eq!(result, expected);
+    }
 }
@@ -0,0 +1,291 @@
+//! A public interface to the Paillier encryption used in the protocols,
+//! for building extensions that compute on encrypted values.
+
+use alloc::boxed::Box;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::encryption::{Ciphertext, CiphertextMod, Randomizer};
+use super::keys::{
+    PublicKeyPaillier, PublicKeyPaillierPrecomputed, SecretKeyPaillier,
+    SecretKeyPaillierPrecomputed,
+};
+use super::params::PaillierParams;
+use crate::cggmp21::SchemeParams;
+use crate::common::PregeneratedPaillierKey;
+use crate::uint::{uint_from_be_slice, Bounded, Encoding};
+
+/// Errors of the Paillier operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum PaillierError {
+    /// The plaintext is not smaller than the modulus.
+    PlaintextOutOfRange,
+    /// The modulus of the public key is not odd or not greater than 1.
+    InvalidPublicKey,
+    /// The primes of the secret key are invalid or do not match its public key.
+    InvalidSecretKey,
+    /// The ciphertext shares a factor with the modulus.
+    InvalidCiphertext,
+}
+
+/// A Paillier secret key with the parameters of the scheme `P`.
+#[derive(Clone)]
+pub struct PaillierSecretKey<P: SchemeParams>(SecretKeyPaillierPrecomputed<P::Paillier>);
+
+impl<P: SchemeParams> PaillierSecretKey<P> {
+    /// Generates a new key.
+    ///
+    /// This searches for two safe primes, so it takes a while
+    /// (see [`PregeneratedPaillierKey`] for the same key generated in advance).
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        Self(SecretKeyPaillier::random(rng).to_precomputed())
+    }
+
+    /// Returns the public key corresponding to this secret key.
+    pub fn public_key(&self) -> PaillierPublicKey<P> {
+        PaillierPublicKey(self.0.public_key().clone())
+    }
+
+    /// Decrypts the ciphertext, returning the plaintext (in `[0, N)`)
+    /// as big-endian bytes of the size of the modulus.
+    pub fn decrypt(&self, ciphertext: &PaillierCiphertext<P>) -> Result<Box<[u8]>, PaillierError> {
+        let ciphertext = ciphertext.0.to_mod(self.0.public_key());
+        let plaintext = ciphertext
+            .decrypt(&self.0)
+            .ok_or(PaillierError::InvalidCiphertext)?;
+        Ok(plaintext.to_be_bytes().as_ref().into())
+    }
+}
+
+impl<P: SchemeParams> From<PregeneratedPaillierKey<P>> for PaillierSecretKey<P> {
+    fn from(key: PregeneratedPaillierKey<P>) -> Self {
+        Self(key.0.to_precomputed())
+    }
+}
+
+impl<P: SchemeParams> core::fmt::Debug for PaillierSecretKey<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "PaillierSecretKey {{ <...> }}",)
+    }
+}
+
+impl<P: SchemeParams> Serialize for PaillierSecretKey<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.0.to_minimal(), self.0.public_key().to_minimal()).serialize(serializer)
+    }
+}
+
+impl<'de, P: SchemeParams> Deserialize<'de> for PaillierSecretKey<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (sk, pk) = <(
+            SecretKeyPaillier<P::Paillier>,
+            PublicKeyPaillier<P::Paillier>,
+        )>::deserialize(deserializer)?;
+        if !sk.matches_public_key(&pk) {
+            return Err(serde::de::Error::custom(PaillierError::InvalidSecretKey));
+        }
+        Ok(Self(sk.to_precomputed()))
+    }
+}
+
+/// A Paillier public key with the parameters of the scheme `P`.
+///
+/// The homomorphic operations on the ciphertexts encrypted with this key are its methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaillierPublicKey<P: SchemeParams>(PublicKeyPaillierPrecomputed<P::Paillier>);
+
+impl<P: SchemeParams> PaillierPublicKey<P> {
+    /// Creates a public key from the big-endian bytes of its modulus.
+    pub fn from_modulus_bytes(bytes: &[u8]) -> Result<Self, PaillierError> {
+        let modulus = uint_from_be_slice::<<P::Paillier as PaillierParams>::Uint>(bytes)
+            .ok_or(PaillierError::InvalidPublicKey)?;
+        let pk = PublicKeyPaillier::new(modulus);
+        if !pk.is_valid() {
+            return Err(PaillierError::InvalidPublicKey);
+        }
+        Ok(Self(pk.to_precomputed()))
+    }
+
+    /// Returns the big-endian bytes of the modulus.
+    pub fn modulus_bytes(&self) -> Box<[u8]> {
+        self.0.modulus().to_be_bytes().as_ref().into()
+    }
+
+    /// Encrypts a plaintext given as big-endian bytes
+    /// (it must be smaller than the modulus).
+    pub fn encrypt(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+    ) -> Result<PaillierCiphertext<P>, PaillierError> {
+        let plaintext = self.plaintext_from_bytes(plaintext)?;
+        Ok(PaillierCiphertext(
+            CiphertextMod::new(rng, &self.0, &plaintext).retrieve(),
+        ))
+    }
+
+    /// Returns a ciphertext of the sum (modulo `N`) of the plaintexts of `lhs` and `rhs`.
+    pub fn add_ciphertexts(
+        &self,
+        lhs: &PaillierCiphertext<P>,
+        rhs: &PaillierCiphertext<P>,
+    ) -> PaillierCiphertext<P> {
+        PaillierCiphertext((lhs.0.to_mod(&self.0) + rhs.0.to_mod(&self.0)).retrieve())
+    }
+
+    /// Returns a ciphertext of the plaintext of `ciphertext` multiplied (modulo `N`)
+    /// by a plaintext given as big-endian bytes (it must be smaller than the modulus).
+    ///
+    /// The result has the randomizer of `ciphertext` raised to the power of `plaintext`,
+    /// so, if it is going to be revealed, it should be [re-randomized](Self::rerandomize).
+    pub fn mul_by_plaintext(
+        &self,
+        ciphertext: &PaillierCiphertext<P>,
+        plaintext: &[u8],
+    ) -> Result<PaillierCiphertext<P>, PaillierError> {
+        let plaintext = Bounded::new(
+            self.plaintext_from_bytes(plaintext)?,
+            <P::Paillier as PaillierParams>::MODULUS_BITS as u32,
+        )
+        .expect("the plaintext is smaller than the modulus");
+        Ok(PaillierCiphertext(
+            (ciphertext.0.to_mod(&self.0) * plaintext).retrieve(),
+        ))
+    }
+
+    /// Returns a ciphertext of the same plaintext with a new random randomizer,
+    /// which cannot be linked to the original one without the secret key.
+    pub fn rerandomize(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        ciphertext: &PaillierCiphertext<P>,
+    ) -> PaillierCiphertext<P> {
+        let randomizer = Randomizer::random(rng, &self.0);
+        PaillierCiphertext(
+            ciphertext
+                .0
+                .to_mod(&self.0)
+                .mul_randomizer(&randomizer)
+                .retrieve(),
+        )
+    }
+
+    fn plaintext_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<<P::Paillier as PaillierParams>::Uint, PaillierError> {
+        uint_from_be_slice::<<P::Paillier as PaillierParams>::Uint>(bytes)
+            .filter(|plaintext| plaintext < self.0.modulus())
+            .ok_or(PaillierError::PlaintextOutOfRange)
+    }
+}
+
+impl<P: SchemeParams> Serialize for PaillierPublicKey<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.to_minimal().serialize(serializer)
+    }
+}
+
+impl<'de, P: SchemeParams> Deserialize<'de> for PaillierPublicKey<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pk = PublicKeyPaillier::<P::Paillier>::deserialize(deserializer)?;
+        if !pk.is_valid() {
+            return Err(serde::de::Error::custom(PaillierError::InvalidPublicKey));
+        }
+        Ok(Self(pk.to_precomputed()))
+    }
+}
+
+/// A Paillier ciphertext.
+///
+/// It does not keep the public key it was encrypted with,
+/// so the same key has to be used for all the operations on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "Ciphertext<P::Paillier>: Serialize"))]
+#[serde(bound(deserialize = "Ciphertext<P::Paillier>: for <'x> Deserialize<'x>"))]
+pub struct PaillierCiphertext<P: SchemeParams>(Ciphertext<P::Paillier>);
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{OsRng, RngCore};
+
+    use super::{PaillierError, PaillierPublicKey, PaillierSecretKey};
+    use crate::TestParams;
+
+    fn to_u128(bytes: &[u8]) -> u128 {
+        let (leading, bytes) = bytes.split_at(bytes.len() - 16);
+        assert!(leading.iter().all(|byte| *byte == 0));
+        u128::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    #[test]
+    fn homomorphic_operations() {
+        let sk = PaillierSecretKey::<TestParams>::random(&mut OsRng);
+        let pk = sk.public_key();
+
+        let x = OsRng.next_u64();
+        let y = OsRng.next_u64();
+        let k = OsRng.next_u64();
+
+        let cx = pk.encrypt(&mut OsRng, &x.to_be_bytes()).unwrap();
+        let cy = pk.encrypt(&mut OsRng, &y.to_be_bytes()).unwrap();
+
+        let sum = pk.add_ciphertexts(&cx, &cy);
+        assert_eq!(to_u128(&sk.decrypt(&sum).unwrap()), x as u128 + y as u128);
+
+        let product = pk.mul_by_plaintext(&cx, &k.to_be_bytes()).unwrap();
+        assert_eq!(
+            to_u128(&sk.decrypt(&product).unwrap()),
+            x as u128 * k as u128
+        );
+
+        let rerandomized = pk.rerandomize(&mut OsRng, &product);
+        assert_eq!(sk.decrypt(&rerandomized), sk.decrypt(&product));
+        assert_ne!(
+            bincode::serialize(&rerandomized).unwrap(),
+            bincode::serialize(&product).unwrap()
+        );
+
+        // The plaintexts must be smaller than the modulus
+        let modulus = pk.modulus_bytes();
+        assert_eq!(
+            pk.encrypt(&mut OsRng, &modulus).unwrap_err(),
+            PaillierError::PlaintextOutOfRange
+        );
+        let mut too_long = [0u8; 1].to_vec();
+        too_long.extend(modulus.iter());
+        too_long[0] = 1;
+        assert_eq!(
+            pk.mul_by_plaintext(&cx, &too_long).unwrap_err(),
+            PaillierError::PlaintextOutOfRange
+        );
+    }
+
+    #[test]
+    fn serialization() {
+        let sk = PaillierSecretKey::<TestParams>::random(&mut OsRng);
+        let pk = sk.public_key();
+        let ciphertext = pk.encrypt(&mut OsRng, b"plaintext").unwrap();
+
+        let sk_back: PaillierSecretKey<TestParams> =
+            bincode::deserialize(&bincode::serialize(&sk).unwrap()).unwrap();
+        let pk_back: PaillierPublicKey<TestParams> =
+            bincode::deserialize(&bincode::serialize(&pk).unwrap()).unwrap();
+        assert_eq!(pk_back, pk);
+        assert_eq!(
+            PaillierPublicKey::<TestParams>::from_modulus_bytes(&pk.modulus_bytes()).unwrap(),
+            pk
+        );
+        assert_eq!(sk_back.decrypt(&ciphertext), sk.decrypt(&ciphertext));
+
+        // An even modulus is rejected
+        let mut modulus = pk.modulus_bytes();
+        *modulus.last_mut().unwrap() &= 0xfe;
+        assert_eq!(
+            PaillierPublicKey::<TestParams>::from_modulus_bytes(&modulus).unwrap_err(),
+            PaillierError::InvalidPublicKey
+        );
+    }
+}
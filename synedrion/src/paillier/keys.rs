@@ -1,12 +1,15 @@
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use super::params::PaillierParams;
 use crate::tools::hashing::{Chain, Hashable};
+use crate::tools::serialized_size::{uint_size, MaxSerializedSize};
 use crate::uint::{
-    subtle::{Choice, ConditionallySelectable},
+    subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption},
     Bounded, CheckedAdd, CheckedSub, HasWide, Integer, Invert, NonZero, PowBoundedExp, RandomMod,
-    RandomPrimeWithRng, Retrieve, Signed, UintLike, UintModLike,
+    Retrieve, Signed, UintLike, UintModLike, Zero,
 };
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -15,14 +18,82 @@ pub(crate) struct SecretKeyPaillier<P: PaillierParams> {
     q: P::HalfUint,
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: PaillierParams> Drop for SecretKeyPaillier<P> {
+    fn drop(&mut self) {
+        self.p.zeroize();
+        self.q.zeroize();
+    }
+}
+
+#[cfg(all(not(feature = "rayon"), any(test, feature = "key-refresh")))]
+fn generate_safe_prime<T: UintLike>(rng: &mut impl CryptoRngCore, bit_length: usize) -> T {
+    T::generate_safe_prime(rng, bit_length)
+}
+
+/// Searches for a safe prime in all the threads of the `rayon` pool.
+///
+/// The candidates are tested in parallel, but the first safe prime in the order of the search
+/// is taken, so the result is still determined by the state of `rng`.
+#[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+fn generate_safe_prime<T: UintLike>(rng: &mut impl CryptoRngCore, bit_length: usize) -> T {
+    T::generate_safe_prime_in_parallel(rng, bit_length)
+}
+
 impl<P: PaillierParams> SecretKeyPaillier<P> {
+    #[cfg(any(test, feature = "key-refresh"))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "paillier_key_generation",
+            level = "debug",
+            skip_all,
+            fields(prime_bits = P::PRIME_BITS)
+        )
+    )]
     pub fn random(rng: &mut impl CryptoRngCore) -> Self {
-        let p = P::HalfUint::generate_safe_prime_with_rng(rng, Some(P::PRIME_BITS));
-        let q = P::HalfUint::generate_safe_prime_with_rng(rng, Some(P::PRIME_BITS));
+        let p = generate_safe_prime(rng, P::PRIME_BITS);
+        let q = generate_safe_prime(rng, P::PRIME_BITS);
+
+        Self { p, q }
+    }
 
+    /// Creates a key from the given primes.
+    ///
+    /// The primes are not checked here, see [`Self::has_safe_primes`].
+    #[cfg(feature = "key-refresh")]
+    pub fn from_primes(p: P::HalfUint, q: P::HalfUint) -> Self {
         Self { p, q }
     }
 
+    #[cfg(test)]
+    pub fn raw_primes(&self) -> (P::HalfUint, P::HalfUint) {
+        (self.p, self.q)
+    }
+
+    /// Returns `true` if the primes are distinct safe primes of exactly `P::PRIME_BITS` bits
+    /// (so that the modulus has `P::MODULUS_BITS` or `P::MODULUS_BITS - 1` bits).
+    #[cfg(feature = "key-refresh")]
+    pub fn has_safe_primes(&self, rng: &mut impl CryptoRngCore) -> bool {
+        fn is_safe_prime<T: UintLike>(rng: &mut impl CryptoRngCore, x: &T, bits: usize) -> bool {
+            x.bits_vartime() == bits && x.is_prime(rng) && x.shr_vartime(1).is_prime(rng)
+        }
+        self.p != self.q
+            && is_safe_prime(rng, &self.p, P::PRIME_BITS)
+            && is_safe_prime(rng, &self.q, P::PRIME_BITS)
+    }
+
+    /// Returns `true` if the primes are odd, distinct, and their product is the modulus of `pk`.
+    pub fn matches_public_key(&self, pk: &PublicKeyPaillier<P>) -> bool {
+        let one = P::HalfUint::ONE;
+        bool::from(self.p.is_odd())
+            && bool::from(self.q.is_odd())
+            && self.p > one
+            && self.q > one
+            && self.p != self.q
+            && self.p.mul_wide(&self.q) == pk.modulus
+    }
+
     pub fn to_precomputed(&self) -> SecretKeyPaillierPrecomputed<P> {
         // Euler's totient function of $p q$ - the number of positive integers up to $p q$
         // that are relatively prime to it.
@@ -36,27 +107,26 @@ impl<P: PaillierParams> SecretKeyPaillier<P> {
         let precomputed_mod_p = P::HalfUintMod::new_precomputed(&NonZero::new(self.p).unwrap());
         let precomputed_mod_q = P::HalfUintMod::new_precomputed(&NonZero::new(self.q).unwrap());
 
-        let public_key = PublicKeyPaillier {
-            modulus: self.p.mul_wide(&self.q),
-        };
+        let public_key = PublicKeyPaillier::from_modulus(self.p.mul_wide(&self.q));
         let public_key = public_key.to_precomputed();
 
-        let inv_totient = totient
-            .as_ref()
-            .to_mod(public_key.precomputed_modulus())
-            .invert()
-            .unwrap();
-
-        let modulus: &P::Uint = public_key.modulus();
-        let inv_modulus = Bounded::new(
-            modulus.inv_mod(totient.as_ref()).unwrap(),
-            P::MODULUS_BITS as u32,
-        )
-        .unwrap();
+        let precomputed_mod_p_squared =
+            P::UintMod::new_precomputed(&NonZero::new(self.p.square_wide()).unwrap());
+        let precomputed_mod_q_squared =
+            P::UintMod::new_precomputed(&NonZero::new(self.q.square_wide()).unwrap());
 
         let inv_p_mod_q = self.p.to_mod(&precomputed_mod_q).invert().unwrap();
         let inv_q_mod_p = self.q.to_mod(&precomputed_mod_p).invert().unwrap();
 
+        // For $g = N + 1$, $L_p(g^{p-1} \mod p^2) = (p - 1) q = -q \mod p$,
+        // so $h_p = L_p(g^{p-1} \mod p^2)^{-1} = -q^{-1} \mod p$, and similarly for $h_q$.
+        let hp = -inv_q_mod_p;
+        let hq = -inv_p_mod_q;
+
+        let modulus: &P::Uint = public_key.modulus();
+        let inv_modulus_mod_p_minus_one = inv_mod_half::<P>(modulus, &p_minus_one);
+        let inv_modulus_mod_q_minus_one = inv_mod_half::<P>(modulus, &q_minus_one);
+
         // Calculate $u$ such that $u = 1 \mod p$ and $u = -1 \mod q$.
         // Using step of Garner's algorithm:
         // $u = q - 1 + q (2 q^{-1} - 1 \mod p)$
@@ -74,12 +144,16 @@ impl<P: PaillierParams> SecretKeyPaillier<P> {
         SecretKeyPaillierPrecomputed {
             sk: self.clone(),
             totient,
-            inv_totient,
-            inv_modulus,
+            hp,
+            hq,
+            inv_modulus_mod_p_minus_one,
+            inv_modulus_mod_q_minus_one,
             inv_p_mod_q,
             nonsquare_sampling_constant,
             precomputed_mod_p,
             precomputed_mod_q,
+            precomputed_mod_p_squared,
+            precomputed_mod_q_squared,
             public_key,
         }
     }
@@ -89,18 +163,40 @@ impl<P: PaillierParams> SecretKeyPaillier<P> {
 pub(crate) struct SecretKeyPaillierPrecomputed<P: PaillierParams> {
     sk: SecretKeyPaillier<P>,
     totient: Bounded<P::Uint>,
-    /// $\phi(N)^{-1} \mod N$
-    inv_totient: P::UintMod,
-    /// $N^{-1} \mod \phi(N)$
-    inv_modulus: Bounded<P::Uint>,
+    /// $-q^{-1} \mod p$, the decryption constant for the CRT component modulo $p$.
+    hp: P::HalfUintMod,
+    /// $-p^{-1} \mod q$, the decryption constant for the CRT component modulo $q$.
+    hq: P::HalfUintMod,
+    /// $N^{-1} \mod (p - 1)$
+    inv_modulus_mod_p_minus_one: P::HalfUint,
+    /// $N^{-1} \mod (q - 1)$
+    inv_modulus_mod_q_minus_one: P::HalfUint,
+    /// $p^{-1} \mod q$, used to join the CRT components.
     inv_p_mod_q: P::HalfUintMod,
     // $u$ such that $u = 1 \mod p$ and $u = -1 \mod q$.
     nonsquare_sampling_constant: P::UintMod,
     precomputed_mod_p: <P::HalfUintMod as UintModLike>::Precomputed,
     precomputed_mod_q: <P::HalfUintMod as UintModLike>::Precomputed,
+    precomputed_mod_p_squared: <P::UintMod as UintModLike>::Precomputed,
+    precomputed_mod_q_squared: <P::UintMod as UintModLike>::Precomputed,
     public_key: PublicKeyPaillierPrecomputed<P>,
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: PaillierParams> Drop for SecretKeyPaillierPrecomputed<P> {
+    fn drop(&mut self) {
+        // TODO (#77): `precomputed_mod_p` and `precomputed_mod_q` contain the primes as well,
+        // but the backend does not support zeroizing them.
+        self.totient.zeroize();
+        self.hp.zeroize();
+        self.hq.zeroize();
+        self.inv_modulus_mod_p_minus_one.zeroize();
+        self.inv_modulus_mod_q_minus_one.zeroize();
+        self.inv_p_mod_q.zeroize();
+        self.nonsquare_sampling_constant.zeroize();
+    }
+}
+
 impl<P: PaillierParams> SecretKeyPaillierPrecomputed<P> {
     pub fn to_minimal(&self) -> SecretKeyPaillier<P> {
         self.sk.clone()
@@ -127,17 +223,6 @@ impl<P: PaillierParams> SecretKeyPaillierPrecomputed<P> {
         NonZero::new(*self.totient.as_ref()).unwrap()
     }
 
-    /// Returns $\phi(N)^{-1} \mod N$
-    pub fn inv_totient(&self) -> &P::UintMod {
-        // TODO (#77): must be wrapped in a Secret
-        &self.inv_totient
-    }
-
-    /// Returns $N^{-1} \mod \phi(N)$
-    pub fn inv_modulus(&self) -> &Bounded<P::Uint> {
-        &self.inv_modulus
-    }
-
     fn precomputed_mod_p(&self) -> &<P::HalfUintMod as UintModLike>::Precomputed {
         &self.precomputed_mod_p
     }
@@ -165,7 +250,73 @@ impl<P: PaillierParams> SecretKeyPaillierPrecomputed<P> {
         (p_rem_mod, q_rem_mod)
     }
 
-    fn sqrt_part(&self, x: &P::HalfUintMod, modulus: &P::HalfUint) -> Option<P::HalfUintMod> {
+    /// Decrypts a ciphertext (an element of $\mathbb{Z}_{N^2}$) assuming that the plaintext
+    /// is in range `[0, N)`.
+    ///
+    /// The plaintext is recovered separately modulo $p$ and $q$ and then joined,
+    /// which replaces an exponentiation modulo $N^2$ by two half-sized ones modulo $p^2$ and $q^2$.
+    ///
+    /// Returns `None` if the ciphertext shares a factor with $N$, and therefore is not a valid one.
+    pub fn decrypt(&self, ciphertext: &P::WideUint) -> Option<P::Uint> {
+        // TODO (#77): zeroize intermediate values
+        let m_p = self.decrypt_part(
+            ciphertext,
+            &self.sk.p,
+            &self.precomputed_mod_p_squared,
+            &self.precomputed_mod_p,
+            &self.hp,
+        )?;
+        let m_q = self.decrypt_part(
+            ciphertext,
+            &self.sk.q,
+            &self.precomputed_mod_q_squared,
+            &self.precomputed_mod_q,
+            &self.hq,
+        )?;
+        Some(self.rns_join(&(m_p, m_q)))
+    }
+
+    fn decrypt_part(
+        &self,
+        ciphertext: &P::WideUint,
+        prime: &P::HalfUint,
+        precomputed_mod_prime_squared: &<P::UintMod as UintModLike>::Precomputed,
+        precomputed_mod_prime: &<P::HalfUintMod as UintModLike>::Precomputed,
+        h: &P::HalfUintMod,
+    ) -> Option<P::HalfUintMod> {
+        // Calculate `m mod p = L_p(C^(p-1) mod p^2) * h_p mod p`, where `L_p(x) = (x - 1) / p`.
+        let prime_squared = NonZero::new(prime.square_wide().into_wide()).unwrap();
+        let c = P::Uint::try_from_wide(*ciphertext % prime_squared).unwrap();
+        let exponent = prime.checked_sub(&P::HalfUint::ONE).unwrap().into_wide();
+
+        let y = c
+            .to_mod(precomputed_mod_prime_squared)
+            .pow_bounded_exp(&exponent, P::PRIME_BITS)
+            .retrieve();
+
+        // `C^(p-1) mod p^2` is 0 if and only if `C` is divisible by `p`,
+        // which never happens for a valid ciphertext, but can be sent by a malicious party.
+        // Otherwise it is 1 modulo `p`, so `L_p` is an exact division with the result below `p`.
+        if bool::from(y.is_zero()) {
+            return None;
+        }
+
+        let x = y.wrapping_sub(&P::Uint::ONE) / NonZero::new(prime.into_wide()).unwrap();
+        let x = P::HalfUint::try_from_wide(x).unwrap();
+        Some(x.to_mod(precomputed_mod_prime) * h)
+    }
+
+    /// Returns $x^{N^{-1} \mod \phi(N)} \mod N$, that is the $N$-th root of $x$ modulo $N$,
+    /// calculated separately modulo $p$ and $q$.
+    pub fn nth_root(&self, x: &P::Uint) -> P::Uint {
+        // TODO (#77): zeroize intermediate values
+        let (x_p, x_q) = self.rns_split(x);
+        let root_p = x_p.pow_bounded_exp(&self.inv_modulus_mod_p_minus_one, P::PRIME_BITS);
+        let root_q = x_q.pow_bounded_exp(&self.inv_modulus_mod_q_minus_one, P::PRIME_BITS);
+        self.rns_join(&(root_p, root_q))
+    }
+
+    fn sqrt_part(&self, x: &P::HalfUintMod, modulus: &P::HalfUint) -> CtOption<P::HalfUintMod> {
         // Both `p` and `q` are safe primes, so they're 3 mod 4.
         // This means that if square root exists, it must be of the form `+/- x^((modulus+1)/4)`.
         // Also it means that `(modulus+1)/4 == modulus/4+1`
@@ -174,26 +325,25 @@ impl<P: PaillierParams> SecretKeyPaillierPrecomputed<P> {
             &modulus.shr_vartime(2).wrapping_add(&P::HalfUint::ONE),
             P::PRIME_BITS - 1,
         );
-        if candidate.square() == *x {
-            Some(candidate)
-        } else {
-            None
-        }
+        CtOption::new(candidate, candidate.square().ct_eq(x))
     }
 
     pub fn sqrt(
         &self,
         rns: &(P::HalfUintMod, P::HalfUintMod),
-    ) -> Option<(P::HalfUintMod, P::HalfUintMod)> {
+    ) -> CtOption<(P::HalfUintMod, P::HalfUintMod)> {
         // TODO (#73): when we can extract the modulus from `HalfUintMod`, this can be moved there.
         // For now we have to keep this a method of SecretKey to have access to `p` and `q`.
         let (p_part, q_part) = *rns;
         let p_res = self.sqrt_part(&p_part, &self.sk.p);
         let q_res = self.sqrt_part(&q_part, &self.sk.q);
-        match (p_res, q_res) {
-            (Some(p), Some(q)) => Some((p, q)),
-            _ => None,
-        }
+        let is_some = p_res.is_some() & q_res.is_some();
+        CtOption::new((p_res.unwrap_or(p_part), q_res.unwrap_or(q_part)), is_some)
+    }
+
+    /// Returns `true` if `x` is a square modulo $N$.
+    pub fn is_square(&self, x: &P::Uint) -> bool {
+        self.sqrt(&self.rns_split(x)).is_some().into()
     }
 
     pub fn rns_join(&self, rns: &(P::HalfUintMod, P::HalfUintMod)) -> P::Uint {
@@ -252,26 +402,127 @@ impl<P: PaillierParams> SecretKeyPaillierPrecomputed<P> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Returns $N^{-1} \mod (p - 1)$ given $p - 1$.
+fn inv_mod_half<P: PaillierParams>(
+    modulus: &P::Uint,
+    prime_minus_one: &P::HalfUint,
+) -> P::HalfUint {
+    let reduced = *modulus % NonZero::new(prime_minus_one.into_wide()).unwrap();
+    P::HalfUint::try_from_wide(reduced)
+        .unwrap()
+        .inv_mod(prime_minus_one)
+        .unwrap()
+}
+
+/// The Montgomery parameters for the integers modulo $N$ and $N^2$.
+#[derive(Debug)]
+struct MontgomeryParams<P: PaillierParams> {
+    modulus: <P::UintMod as UintModLike>::Precomputed,
+    modulus_squared: <P::WideUintMod as UintModLike>::Precomputed,
+}
+
+impl<P: PaillierParams> Clone for MontgomeryParams<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: PaillierParams> Copy for MontgomeryParams<P> {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    from = "PublicKeyPaillierRepr<P>",
+    into = "PublicKeyPaillierRepr<P>",
+    bound = ""
+)]
 pub(crate) struct PublicKeyPaillier<P: PaillierParams> {
     modulus: P::Uint, // TODO (#104): wrap it in `crypto_bigint::Odd`
+    /// Computing the Montgomery parameters takes about as long as an exponentiation,
+    /// so they are computed once when the key is created or deserialized,
+    /// and shared by all the precomputed keys made from it.
+    /// `None` if the modulus is not valid.
+    params: Option<MontgomeryParams<P>>,
+}
+
+/// The serialized form of [`PublicKeyPaillier`], without the Montgomery parameters.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "PublicKeyPaillier", bound = "")]
+struct PublicKeyPaillierRepr<P: PaillierParams> {
+    modulus: P::Uint,
+}
+
+impl<P: PaillierParams> From<PublicKeyPaillierRepr<P>> for PublicKeyPaillier<P> {
+    fn from(repr: PublicKeyPaillierRepr<P>) -> Self {
+        Self::from_modulus(repr.modulus)
+    }
+}
+
+impl<P: PaillierParams> From<PublicKeyPaillier<P>> for PublicKeyPaillierRepr<P> {
+    fn from(pk: PublicKeyPaillier<P>) -> Self {
+        Self {
+            modulus: pk.modulus,
+        }
+    }
+}
+
+impl<P: PaillierParams> PartialEq for PublicKeyPaillier<P> {
+    fn eq(&self, other: &Self) -> bool {
+        // The parameters are determined by the modulus.
+        self.modulus == other.modulus
+    }
+}
+
+impl<P: PaillierParams> Eq for PublicKeyPaillier<P> {}
+
+impl<P: PaillierParams> MaxSerializedSize for PublicKeyPaillier<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        uint_size::<P::Uint>()
+    }
 }
 
 impl<P: PaillierParams> PublicKeyPaillier<P> {
+    #[cfg(feature = "paillier")]
+    pub fn new(modulus: P::Uint) -> Self {
+        Self::from_modulus(modulus)
+    }
+
+    fn from_modulus(modulus: P::Uint) -> Self {
+        let mut pk = Self {
+            modulus,
+            params: None,
+        };
+        if pk.is_valid() {
+            pk.params = Some(MontgomeryParams {
+                modulus: P::UintMod::new_precomputed(&NonZero::new(modulus).unwrap()),
+                modulus_squared: P::WideUintMod::new_precomputed(
+                    &NonZero::new(modulus.square_wide()).unwrap(),
+                ),
+            });
+        }
+        pk
+    }
+
     pub fn modulus(&self) -> &P::Uint {
         &self.modulus
     }
 
+    /// Returns `true` if the modulus is odd and greater than 1,
+    /// so that [`to_precomputed`](Self::to_precomputed) can be called.
+    pub fn is_valid(&self) -> bool {
+        bool::from(self.modulus.is_odd()) && self.modulus > P::Uint::ONE
+    }
+
     pub fn to_precomputed(&self) -> PublicKeyPaillierPrecomputed<P> {
-        // Note that this ensures that `self.modulus` is odd,
+        // Note that this ensures that `self.modulus` is valid,
         // otherwise creating the Montgomery parameters fails.
-        let precomputed_modulus = P::UintMod::new_precomputed(&NonZero::new(self.modulus).unwrap());
-        let precomputed_modulus_squared =
-            P::WideUintMod::new_precomputed(&NonZero::new(self.modulus.square_wide()).unwrap());
+        let params = self
+            .params
+            .as_ref()
+            .expect("the Paillier modulus is odd and greater than 1");
         PublicKeyPaillierPrecomputed {
             pk: self.clone(),
-            precomputed_modulus,
-            precomputed_modulus_squared,
+            precomputed_modulus: params.modulus,
+            precomputed_modulus_squared: params.modulus_squared,
         }
     }
 }
@@ -344,14 +595,51 @@ impl<P: PaillierParams> Hashable for PublicKeyPaillierPrecomputed<P> {
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
     use rand_core::OsRng;
 
     use super::super::params::PaillierTest;
-    use super::SecretKeyPaillier;
+    use super::{PublicKeyPaillier, SecretKeyPaillier};
+    use crate::uint::U1024;
 
     #[test]
     fn basics() {
         let sk = SecretKeyPaillier::<PaillierTest>::random(&mut OsRng).to_precomputed();
         let _pk = sk.public_key();
     }
+
+    #[test]
+    fn random_is_deterministic() {
+        // Holds with the `rayon` feature as well, regardless of the scheduling.
+        let sk1 = SecretKeyPaillier::<PaillierTest>::random(&mut StdRng::seed_from_u64(123));
+        let sk2 = SecretKeyPaillier::<PaillierTest>::random(&mut StdRng::seed_from_u64(123));
+        assert_eq!(sk1.raw_primes(), sk2.raw_primes());
+        #[cfg(feature = "key-refresh")]
+        assert!(sk1.has_safe_primes(&mut OsRng));
+    }
+
+    #[test]
+    fn public_key_serialization() {
+        let sk = SecretKeyPaillier::<PaillierTest>::random(&mut OsRng).to_precomputed();
+        let pk = sk.public_key().to_minimal();
+
+        // Only the modulus is serialized, and the Montgomery parameters are restored from it
+        let serialized = bincode::serialize(&pk).unwrap();
+        assert_eq!(serialized, bincode::serialize(pk.modulus()).unwrap());
+        let deserialized: PublicKeyPaillier<PaillierTest> =
+            bincode::deserialize(&serialized).unwrap();
+        assert_eq!(
+            deserialized.to_precomputed().precomputed_modulus(),
+            sk.public_key().precomputed_modulus()
+        );
+        assert_eq!(
+            deserialized.to_precomputed().precomputed_modulus_squared(),
+            sk.public_key().precomputed_modulus_squared()
+        );
+
+        // An invalid modulus can still be deserialized and checked
+        let even_modulus = bincode::serialize(&U1024::from(4u8)).unwrap();
+        let invalid: PublicKeyPaillier<PaillierTest> = bincode::deserialize(&even_modulus).unwrap();
+        assert!(!invalid.is_valid());
+    }
 }
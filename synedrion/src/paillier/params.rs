@@ -5,13 +5,16 @@ use crate::uint::{HasWide, UintLike, UintModLike};
 #[cfg(test)]
 use crate::uint::{U1024Mod, U2048Mod, U512Mod, U1024, U2048, U4096, U512};
 
-pub trait PaillierParams: PartialEq + Eq + Clone + core::fmt::Debug + Send {
+pub trait PaillierParams: PartialEq + Eq + Clone + core::fmt::Debug + Send + Sync {
     /// The size of one of the pair of RSA primes.
     const PRIME_BITS: usize;
     /// The size of the RSA modulus (a product of two primes).
     const MODULUS_BITS: usize = Self::PRIME_BITS * 2;
     /// An integer that fits a single RSA prime.
-    type HalfUint: UintLike<ModUint = Self::HalfUintMod> + HasWide<Wide = Self::Uint>;
+    type HalfUint: UintLike<ModUint = Self::HalfUintMod>
+        + HasWide<Wide = Self::Uint>
+        + Serialize
+        + for<'de> Deserialize<'de>;
     /// A modulo-residue counterpart of `HalfUint`.
     type HalfUintMod: UintModLike<RawUint = Self::HalfUint>;
     /// An integer that fits the RSA modulus.
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::ops::Mul;
 
 use rand_core::CryptoRngCore;
@@ -5,7 +6,8 @@ use serde::{Deserialize, Serialize};
 
 use super::{PaillierParams, PublicKeyPaillierPrecomputed, SecretKeyPaillierPrecomputed};
 use crate::tools::hashing::{Chain, Hashable};
-use crate::uint::{Bounded, Retrieve, Signed, UintLike, UintModLike};
+use crate::tools::serialized_size::{uint_size, MaxSerializedSize};
+use crate::uint::{Bounded, Integer, Invert, Retrieve, Signed, UintLike, UintModLike};
 
 pub(crate) struct RPSecret<P: PaillierParams>(Bounded<P::Uint>);
 
@@ -16,6 +18,11 @@ impl<P: PaillierParams> RPSecret<P> {
         // So we are sampling it from this range to begin with.
         Self(sk.random_field_elem(rng))
     }
+
+    /// Creates a secret from a value in the range `[0, phi(N))`.
+    pub fn new(secret: Bounded<P::Uint>) -> Self {
+        Self(secret)
+    }
 }
 
 impl<P: PaillierParams> AsRef<Bounded<P::Uint>> for RPSecret<P> {
@@ -76,6 +83,29 @@ impl<P: PaillierParams> RPParamsMod<P> {
         RPCommitmentMod(self.base.pow_signed_wide(randomizer) * self.power.pow_signed(secret))
     }
 
+    /// Returns the commitment for `secret` with the randomizer `randomizer`
+    /// multiplied by `prod_i C_i^{e_i}` for the commitments and exponents in `terms`,
+    /// calculating all the powers with a single multi-exponentiation.
+    ///
+    /// Note: this is variable time, so it must only be used with public values
+    /// (e.g. when verifying ZK proofs).
+    /// Returns `None` if one of the commitments with a negative exponent is not invertible.
+    pub fn commit_and_lincomb_vartime(
+        &self,
+        secret: &Signed<P::Uint>,
+        randomizer: &Signed<P::WideUint>,
+        terms: &[(&RPCommitmentMod<P>, Signed<P::Uint>)],
+    ) -> Option<RPCommitmentMod<P>> {
+        let mut bases_and_exponents = Vec::with_capacity(terms.len() + 2);
+        bases_and_exponents.push((self.base, *randomizer));
+        bases_and_exponents.push((self.power, secret.into_wide()));
+        for (commitment, exponent) in terms {
+            bases_and_exponents.push((commitment.0, exponent.into_wide()));
+        }
+        P::UintMod::multi_pow_signed_vartime(self.pk.precomputed_modulus(), &bases_and_exponents)
+            .map(RPCommitmentMod)
+    }
+
     pub fn commit_wide(
         &self,
         secret: &Signed<P::WideUint>,
@@ -125,6 +155,23 @@ impl<P: PaillierParams> RPParams<P> {
             power: self.power.to_mod(pk.precomputed_modulus()),
         }
     }
+
+    /// Returns `true` if both the base and the power are residues modulo $N$ (the modulus of `pk`)
+    /// different from 1 and invertible.
+    pub fn is_valid(&self, pk: &PublicKeyPaillierPrecomputed<P>) -> bool {
+        let is_valid_elem = |x: &P::Uint| {
+            x < pk.modulus()
+                && x != &P::Uint::ONE
+                && bool::from(x.to_mod(pk.precomputed_modulus()).invert().is_some())
+        };
+        is_valid_elem(&self.base) && is_valid_elem(&self.power)
+    }
+}
+
+impl<P: PaillierParams> MaxSerializedSize for RPParams<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        2 * uint_size::<P::Uint>()
+    }
 }
 
 impl<P: PaillierParams> Hashable for RPParams<P> {
@@ -176,6 +223,12 @@ impl<P: PaillierParams> RPCommitment<P> {
     }
 }
 
+impl<P: PaillierParams> MaxSerializedSize for RPCommitment<P> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        uint_size::<P::Uint>()
+    }
+}
+
 impl<P: PaillierParams> Hashable for RPCommitment<P> {
     fn chain<C: Chain>(&self, digest: C) -> C {
         digest.chain(&self.0)
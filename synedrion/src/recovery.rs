@@ -0,0 +1,420 @@
+//! Verifiable encryption of secret shares to a recovery key kept in cold storage.
+//!
+//! The share is encrypted bit by bit with the exponential ElGamal scheme under the recovery key $Q$:
+//! the $i$-th bit $b_i$ becomes $(A_i, B_i) = (r_i G, b_i G + r_i Q)$,
+//! with a proof that $b_i \in \{0, 1\}$ (a disjunction of two Chaum-Pedersen proofs).
+//! Since $\sum_i 2^i B_i - X = (\sum_i 2^i r_i) Q$ and $\sum_i 2^i A_i = (\sum_i 2^i r_i) G$
+//! for the public share $X$, another Chaum-Pedersen proof shows that the bits make up
+//! the discrete log of $X$. So anyone can check that the holder of the recovery key
+//! will be able to decrypt the share, without trusting the party that encrypted it.
+
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::cggmp21::SchemeParams;
+use crate::common::KeyShare;
+use crate::curve::{BackendScalar, Point, Scalar, SigningKey, VerifyingKey};
+use crate::rounds::PartyIdx;
+use crate::tools::hashing::{Chain, FiatShamirTranscript, HashInto, HashOutput, XofHash};
+
+/// The number of bits in the encoding of a scalar.
+const SCALAR_BITS: usize = 256;
+
+/// Possible errors when decrypting an [`EncryptedShare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ShareDecryptionError {
+    /// The share was encrypted to a different recovery key.
+    WrongRecoveryKey,
+    /// The ciphertext of the bit {0} does not decrypt to 0 or 1.
+    InvalidBit(usize),
+    /// The decrypted share does not match the public share.
+    PublicShareMismatch,
+}
+
+impl core::error::Error for ShareDecryptionError {}
+
+/// The values all the proofs of an encrypted share are bound to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct EncryptionContext {
+    recovery_key: Point,
+    public_share: Point,
+    share_set_id: HashOutput,
+    party_idx: PartyIdx,
+    epoch: u64,
+}
+
+impl EncryptionContext {
+    fn transcript(&self, dst: &[u8]) -> XofHash {
+        XofHash::new_with_dst(dst)
+            .chain(&self.recovery_key)
+            .chain(&self.public_share)
+            .chain(&self.share_set_id)
+            .chain(&self.party_idx)
+            .chain(&self.epoch)
+    }
+}
+
+/// Returns the commitments of a Chaum-Pedersen proof that $\log_G A = \log_Q B$
+/// for the challenge `e` and the response `z`.
+fn dleq_commitments(
+    recovery_key: &Point,
+    cap_a: &Point,
+    cap_b: &Point,
+    e: &Scalar,
+    z: &Scalar,
+) -> (Point, Point) {
+    (
+        z.mul_by_generator() - cap_a * e,
+        recovery_key * z - cap_b * e,
+    )
+}
+
+/// An encrypted bit with the proof that it is 0 or 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedBit {
+    // `r G`
+    cap_a: Point,
+    // `b G + r Q`
+    cap_b: Point,
+    // The challenges and the responses of the branches `b = 0` and `b = 1`
+    e: [Scalar; 2],
+    z: [Scalar; 2],
+}
+
+impl EncryptedBit {
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        context: &EncryptionContext,
+        idx: usize,
+        bit: bool,
+        r: &Scalar,
+    ) -> Self {
+        let q = &context.recovery_key;
+        let cap_a = r.mul_by_generator();
+        let cap_b = if bit { Point::GENERATOR + q * r } else { q * r };
+        let real = bit as usize;
+        let simulated = 1 - real;
+
+        // Simulate the proof for the other value of the bit, and make the real one for this value.
+        let mut e = [Scalar::ZERO; 2];
+        let mut z = [Scalar::ZERO; 2];
+        e[simulated] = Scalar::random(rng);
+        z[simulated] = Scalar::random(rng);
+        let branches = Self::branches(&cap_b);
+        let mut commitments = [(Point::IDENTITY, Point::IDENTITY); 2];
+        commitments[simulated] = dleq_commitments(
+            q,
+            &cap_a,
+            &branches[simulated],
+            &e[simulated],
+            &z[simulated],
+        );
+        let w = Scalar::random_nonzero(rng);
+        commitments[real] = (w.mul_by_generator(), q * &w);
+
+        let challenge = Self::challenge(context, idx, &cap_a, &cap_b, &commitments);
+        e[real] = challenge - e[simulated];
+        z[real] = w + e[real] * r;
+
+        Self { cap_a, cap_b, e, z }
+    }
+
+    /// Returns the points whose discrete log base $Q$ must be equal to that of $A$ base $G$
+    /// if the bit is 0 and 1 respectively.
+    fn branches(cap_b: &Point) -> [Point; 2] {
+        [*cap_b, *cap_b - Point::GENERATOR]
+    }
+
+    fn challenge(
+        context: &EncryptionContext,
+        idx: usize,
+        cap_a: &Point,
+        cap_b: &Point,
+        commitments: &[(Point, Point); 2],
+    ) -> Scalar {
+        let mut reader = context
+            .transcript(b"RecoveryBit")
+            .chain(&(idx as u32))
+            .chain(cap_a)
+            .chain(cap_b)
+            .chain(&commitments[0].0)
+            .chain(&commitments[0].1)
+            .chain(&commitments[1].0)
+            .chain(&commitments[1].1)
+            .finalize_to_reader();
+        Scalar::from_reader(&mut reader)
+    }
+
+    fn verify(&self, context: &EncryptionContext, idx: usize) -> bool {
+        let branches = Self::branches(&self.cap_b);
+        let commitments = [0, 1].map(|branch| {
+            dleq_commitments(
+                &context.recovery_key,
+                &self.cap_a,
+                &branches[branch],
+                &self.e[branch],
+                &self.z[branch],
+            )
+        });
+        let challenge = Self::challenge(context, idx, &self.cap_a, &self.cap_b, &commitments);
+        self.e[0] + self.e[1] == challenge
+    }
+}
+
+/// A Chaum-Pedersen proof that the encrypted bits make up the discrete log of the public share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SumProof {
+    e: Scalar,
+    z: Scalar,
+}
+
+impl SumProof {
+    fn challenge(context: &EncryptionContext, commitments: &(Point, Point)) -> Scalar {
+        let mut reader = context
+            .transcript(b"RecoverySum")
+            .chain(&commitments.0)
+            .chain(&commitments.1)
+            .finalize_to_reader();
+        Scalar::from_reader(&mut reader)
+    }
+
+    fn new(rng: &mut impl CryptoRngCore, context: &EncryptionContext, rho: &Scalar) -> Self {
+        let w = Scalar::random_nonzero(rng);
+        let commitments = (w.mul_by_generator(), context.recovery_key * w);
+        let e = Self::challenge(context, &commitments);
+        let z = w + e * rho;
+        Self { e, z }
+    }
+
+    fn verify(&self, context: &EncryptionContext, cap_a: &Point, cap_b: &Point) -> bool {
+        let commitments = dleq_commitments(&context.recovery_key, cap_a, cap_b, &self.e, &self.z);
+        Self::challenge(context, &commitments) == self.e
+    }
+}
+
+/// Returns $\sum_i 2^i P_i$ (with the most significant bit first).
+fn combine_bits(points: impl Iterator<Item = Point>) -> Point {
+    points.fold(Point::IDENTITY, |acc, point| acc + acc + point)
+}
+
+/// A secret share encrypted to a recovery key, along with the proof
+/// that it can be decrypted into the discrete log of the party's public share.
+///
+/// Created by [`KeyShare::encrypt_for_recovery`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedShare {
+    context: EncryptionContext,
+    // Starting from the most significant bit
+    bits: Vec<EncryptedBit>,
+    proof: SumProof,
+}
+
+impl EncryptedShare {
+    pub(crate) fn new<P: SchemeParams>(
+        rng: &mut impl CryptoRngCore,
+        key_share: &KeyShare<P>,
+        recovery_key: &VerifyingKey,
+    ) -> Self {
+        let context = EncryptionContext {
+            recovery_key: Point::from_verifying_key(recovery_key),
+            public_share: key_share.public_shares[key_share.index.as_usize()],
+            share_set_id: key_share.share_set_id,
+            party_idx: key_share.index,
+            epoch: key_share.epoch,
+        };
+
+        let share_bytes = Zeroizing::new(<[u8; 32]>::from(key_share.secret_share.to_bytes()));
+        let rs = Zeroizing::new(
+            (0..SCALAR_BITS)
+                .map(|_| Scalar::random_nonzero(rng))
+                .collect::<Vec<_>>(),
+        );
+        let bits = rs
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| {
+                let bit = (share_bytes[idx / 8] >> (7 - idx % 8)) & 1 == 1;
+                EncryptedBit::new(rng, &context, idx, bit, r)
+            })
+            .collect();
+
+        // `rho = sum_i 2^i r_i`, accumulated in the same order as the points.
+        let rho = Zeroizing::new(rs.iter().fold(Scalar::ZERO, |acc, r| acc + acc + *r));
+        let proof = SumProof::new(rng, &context, &rho);
+
+        Self {
+            context,
+            bits,
+            proof,
+        }
+    }
+
+    /// Checks the proofs of the encryption, that is, that the holder of the recovery key
+    /// will be able to decrypt the secret share matching the public share.
+    ///
+    /// This does not check that the public share belongs to a specific share set,
+    /// see [`KeyShare::verify_encrypted_share`] for that.
+    pub fn verify(&self) -> bool {
+        if self.bits.len() != SCALAR_BITS
+            || self.context.recovery_key == Point::IDENTITY
+            || self.context.public_share == Point::IDENTITY
+        {
+            return false;
+        }
+
+        if !self
+            .bits
+            .iter()
+            .enumerate()
+            .all(|(idx, bit)| bit.verify(&self.context, idx))
+        {
+            return false;
+        }
+
+        let cap_a = combine_bits(self.bits.iter().map(|bit| bit.cap_a));
+        let cap_b = combine_bits(self.bits.iter().map(|bit| bit.cap_b)) - self.context.public_share;
+        self.proof.verify(&self.context, &cap_a, &cap_b)
+    }
+
+    /// Returns the recovery key the share is encrypted to,
+    /// or `None` if it is not a valid key (in which case [`verify`](Self::verify) fails as well).
+    pub fn recovery_key(&self) -> Option<VerifyingKey> {
+        self.context.recovery_key.to_verifying_key()
+    }
+
+    /// Returns the index of the party whose share is encrypted.
+    pub fn party_index(&self) -> usize {
+        self.context.party_idx.as_usize()
+    }
+
+    /// Returns the epoch of the key share that was encrypted
+    /// (see [`KeyShare::epoch`]).
+    pub fn epoch(&self) -> u64 {
+        self.context.epoch
+    }
+
+    /// Decrypts the secret share with the recovery key.
+    ///
+    /// The shares of all the parties add up to the secret key.
+    pub fn decrypt(
+        &self,
+        recovery_key: &SigningKey,
+    ) -> Result<BackendScalar, ShareDecryptionError> {
+        let q = Scalar::from(recovery_key.as_nonzero_scalar());
+        if q.mul_by_generator() != self.context.recovery_key {
+            return Err(ShareDecryptionError::WrongRecoveryKey);
+        }
+
+        let mut share_bytes = Zeroizing::new([0u8; 32]);
+        for (idx, bit) in self.bits.iter().enumerate() {
+            let cap_m = bit.cap_b - bit.cap_a * q;
+            if cap_m == Point::GENERATOR {
+                share_bytes[idx / 8] |= 1 << (7 - idx % 8);
+            } else if cap_m != Point::IDENTITY {
+                return Err(ShareDecryptionError::InvalidBit(idx));
+            }
+        }
+
+        // The proof only guarantees that the bits add up to the share modulo the group order.
+        let share = Scalar::from_reduced_bytes(&share_bytes);
+        if share.mul_by_generator() != self.context.public_share {
+            return Err(ShareDecryptionError::PublicShareMismatch);
+        }
+        Ok(share.to_backend())
+    }
+}
+
+impl<P: SchemeParams> KeyShare<P> {
+    /// Encrypts the secret share to `recovery_key`
+    /// with a proof that it is the discrete log of this party's public share.
+    ///
+    /// The result can be given to anyone for safekeeping, and checked by the other parties
+    /// with [`verify_encrypted_share`](Self::verify_encrypted_share);
+    /// if the shares are lost, the holder of the recovery key can decrypt
+    /// the [`EncryptedShare`]s of all the parties and reconstruct the secret key.
+    pub fn encrypt_for_recovery(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        recovery_key: &VerifyingKey,
+    ) -> EncryptedShare {
+        EncryptedShare::new(rng, self, recovery_key)
+    }
+
+    /// Checks that an encrypted share was created for a share of this set
+    /// (in the same epoch) and that its proofs are valid.
+    pub fn verify_encrypted_share(&self, encrypted_share: &EncryptedShare) -> bool {
+        let context = &encrypted_share.context;
+        let Some(public_share) = self.public_shares.get(context.party_idx.as_usize()) else {
+            return false;
+        };
+        context.share_set_id == self.share_set_id
+            && context.epoch == self.epoch
+            && &context.public_share == public_share
+            && encrypted_share.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::ShareDecryptionError;
+    use crate::cggmp21::TestParams;
+    use crate::common::KeyShare;
+    use crate::curve::{BackendScalar, Point, Scalar, SigningKey};
+
+    #[test]
+    fn encrypt_and_recover() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 2, Some(&signing_key));
+        let recovery_key = SigningKey::random(&mut OsRng);
+
+        let encrypted_shares = key_shares
+            .iter()
+            .map(|key_share| {
+                key_share.encrypt_for_recovery(&mut OsRng, recovery_key.verifying_key())
+            })
+            .collect::<Vec<_>>();
+
+        for encrypted_share in encrypted_shares.iter() {
+            assert!(encrypted_share.verify());
+            for key_share in key_shares.iter() {
+                assert!(key_share.verify_encrypted_share(encrypted_share));
+            }
+        }
+
+        let secret = encrypted_shares
+            .iter()
+            .map(|encrypted_share| encrypted_share.decrypt(&recovery_key).unwrap())
+            .sum::<BackendScalar>();
+        assert_eq!(secret, *signing_key.as_nonzero_scalar().as_ref());
+
+        assert_eq!(
+            encrypted_shares[0].decrypt(&SigningKey::random(&mut OsRng)),
+            Err(ShareDecryptionError::WrongRecoveryKey)
+        );
+
+        // A share of another set is rejected.
+        let other_shares =
+            KeyShare::<TestParams>::new_centralized(&mut OsRng, 2, Some(&signing_key));
+        assert!(!other_shares[0].verify_encrypted_share(&encrypted_shares[0]));
+
+        // Flipping a bit invalidates the proofs.
+        let mut tampered = encrypted_shares[0].clone();
+        tampered.bits[10].cap_b = tampered.bits[10].cap_b + Point::GENERATOR;
+        assert!(!tampered.verify());
+
+        // The proofs are bound to the positions of the bits.
+        let mut tampered = encrypted_shares[0].clone();
+        tampered.bits.swap(3, 200);
+        assert!(!tampered.verify());
+
+        let mut tampered = encrypted_shares[0].clone();
+        tampered.proof.z = tampered.proof.z + Scalar::ONE;
+        assert!(!tampered.verify());
+    }
+}
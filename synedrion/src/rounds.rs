@@ -1,9 +1,12 @@
+mod batch;
 mod generic;
 mod wrappers;
 
 #[cfg(any(test, feature = "bench-internals"))]
 pub(crate) mod test_utils;
 
+pub(crate) use batch::BatchRound;
+pub use batch::{BatchError, BatchProof, BatchResult};
 pub use generic::ProtocolResult;
 pub(crate) use generic::{
     all_parties_except, no_broadcast_messages, no_direct_messages, try_to_holevec,
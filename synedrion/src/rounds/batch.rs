@@ -0,0 +1,244 @@
+//! Parallel execution of several instances of the same protocol.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use rand_core::CryptoRngCore;
+
+use super::generic::{
+    FinalizableToNextRound, FinalizableToResult, FinalizeError, FirstRound, InitError, PartyIdx,
+    ProtocolResult, Round,
+};
+
+/// Possible results of several instances of a protocol executed in parallel.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchResult<Res: ProtocolResult>(PhantomData<Res>);
+
+impl<Res: ProtocolResult> ProtocolResult for BatchResult<Res> {
+    const NAME: &'static str = Res::NAME;
+    type Success = Vec<Res::Success>;
+    type ProvableError = BatchError<Res::ProvableError>;
+    type CorrectnessProof = BatchProof<Res::CorrectnessProof>;
+}
+
+/// Possible verifiable errors of a batch of protocol instances.
+#[derive(Debug, Clone)]
+pub enum BatchError<E> {
+    /// The number of messages differs from the number of instances in the batch.
+    InvalidBatchSize,
+    /// An error in one of the instances.
+    Instance {
+        /// The position of the instance in the batch.
+        index: usize,
+        /// The error of the instance.
+        error: E,
+    },
+}
+
+/// A proof of a node's correct behavior in one of the instances of the batch.
+#[derive(Debug, Clone)]
+pub struct BatchProof<P> {
+    /// The position of the instance in the batch.
+    pub index: usize,
+    /// The proof for the instance.
+    pub proof: P,
+}
+
+/// A round executing the corresponding rounds of all the instances in the batch,
+/// with their messages packed together.
+pub(crate) struct BatchRound<R> {
+    rounds: Vec<R>,
+}
+
+impl<R: FirstRound> FirstRound for BatchRound<R> {
+    type Inputs = Vec<R::Inputs>;
+    fn new(
+        rng: &mut impl CryptoRngCore,
+        shared_randomness: &[u8],
+        num_parties: usize,
+        party_idx: PartyIdx,
+        inputs: Self::Inputs,
+    ) -> Result<Self, InitError> {
+        if inputs.is_empty() {
+            return Err(InitError("The batch must not be empty".into()));
+        }
+
+        let rounds = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, inputs)| {
+                // Make sure each instance has its own session ID,
+                // so that the proofs of one instance cannot be replayed in another.
+                let mut instance_randomness = shared_randomness.to_vec();
+                instance_randomness.extend((index as u64).to_be_bytes());
+                R::new(rng, &instance_randomness, num_parties, party_idx, inputs)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { rounds })
+    }
+}
+
+impl<R: Round> Round for BatchRound<R> {
+    type Type = R::Type;
+    type Result = BatchResult<R::Result>;
+    const ROUND_NUM: u8 = R::ROUND_NUM;
+    const NEXT_ROUND_NUM: Option<u8> = R::NEXT_ROUND_NUM;
+
+    fn num_parties(&self) -> usize {
+        self.rounds[0].num_parties()
+    }
+
+    fn party_idx(&self) -> PartyIdx {
+        self.rounds[0].party_idx()
+    }
+
+    const REQUIRES_ECHO: bool = R::REQUIRES_ECHO;
+    type BroadcastMessage = Vec<R::BroadcastMessage>;
+    type DirectMessage = Vec<R::DirectMessage>;
+    type Payload = Vec<R::Payload>;
+    type Artifact = Vec<R::Artifact>;
+
+    fn message_destinations(&self) -> Vec<PartyIdx> {
+        self.rounds[0].message_destinations()
+    }
+
+    fn make_direct_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        destination: PartyIdx,
+    ) -> (Self::DirectMessage, Self::Artifact) {
+        self.rounds
+            .iter()
+            .map(|round| round.make_direct_message(rng, destination))
+            .unzip()
+    }
+
+    fn make_broadcast_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+    ) -> Option<Self::BroadcastMessage> {
+        self.rounds
+            .iter()
+            .map(|round| round.make_broadcast_message(rng))
+            .collect()
+    }
+
+    fn verify_message(
+        &self,
+        from: PartyIdx,
+        broadcast_msg: Self::BroadcastMessage,
+        direct_msg: Self::DirectMessage,
+    ) -> Result<Self::Payload, <Self::Result as ProtocolResult>::ProvableError> {
+        if broadcast_msg.len() != self.rounds.len() || direct_msg.len() != self.rounds.len() {
+            return Err(BatchError::InvalidBatchSize);
+        }
+
+        self.rounds
+            .iter()
+            .zip(broadcast_msg)
+            .zip(direct_msg)
+            .enumerate()
+            .map(|(index, ((round, broadcast_msg), direct_msg))| {
+                round
+                    .verify_message(from, broadcast_msg, direct_msg)
+                    .map_err(|error| BatchError::Instance { index, error })
+            })
+            .collect()
+    }
+
+    fn can_finalize<'a>(
+        &self,
+        payloads: impl Iterator<Item = &'a PartyIdx>,
+        artifacts: impl Iterator<Item = &'a PartyIdx>,
+    ) -> bool {
+        self.rounds[0].can_finalize(payloads, artifacts)
+    }
+
+    fn missing_payloads<'a>(
+        &self,
+        payloads: impl Iterator<Item = &'a PartyIdx>,
+        artifacts: impl Iterator<Item = &'a PartyIdx>,
+    ) -> BTreeSet<PartyIdx> {
+        self.rounds[0].missing_payloads(payloads, artifacts)
+    }
+}
+
+/// Splits the batched values received from each party into per-instance collections.
+fn unbatch<T>(
+    num_instances: usize,
+    batched: BTreeMap<PartyIdx, Vec<T>>,
+) -> Vec<BTreeMap<PartyIdx, T>> {
+    let mut unbatched = (0..num_instances)
+        .map(|_| BTreeMap::new())
+        .collect::<Vec<_>>();
+    for (party_idx, values) in batched {
+        for (instance, value) in unbatched.iter_mut().zip(values) {
+            instance.insert(party_idx, value);
+        }
+    }
+    unbatched
+}
+
+fn wrap_batch_error<Res: ProtocolResult>(
+    index: usize,
+    error: FinalizeError<Res>,
+) -> FinalizeError<BatchResult<Res>> {
+    match error {
+        FinalizeError::Init(msg) => FinalizeError::Init(msg),
+        FinalizeError::Local(msg) => FinalizeError::Local(msg),
+        FinalizeError::Proof(proof) => FinalizeError::Proof(BatchProof { index, proof }),
+        FinalizeError::Provable { party, error } => FinalizeError::Provable {
+            party,
+            error: BatchError::Instance { index, error },
+        },
+    }
+}
+
+impl<R: FinalizableToNextRound> FinalizableToNextRound for BatchRound<R> {
+    type NextRound = BatchRound<R::NextRound>;
+    fn finalize_to_next_round(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<Self::NextRound, FinalizeError<Self::Result>> {
+        let num_instances = self.rounds.len();
+        let rounds = self
+            .rounds
+            .into_iter()
+            .zip(unbatch(num_instances, payloads))
+            .zip(unbatch(num_instances, artifacts))
+            .enumerate()
+            .map(|(index, ((round, payloads), artifacts))| {
+                round
+                    .finalize_to_next_round(rng, payloads, artifacts)
+                    .map_err(|error| wrap_batch_error(index, error))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BatchRound { rounds })
+    }
+}
+
+impl<R: FinalizableToResult> FinalizableToResult for BatchRound<R> {
+    fn finalize_to_result(
+        self,
+        rng: &mut impl CryptoRngCore,
+        payloads: BTreeMap<PartyIdx, <Self as Round>::Payload>,
+        artifacts: BTreeMap<PartyIdx, <Self as Round>::Artifact>,
+    ) -> Result<<Self::Result as ProtocolResult>::Success, FinalizeError<Self::Result>> {
+        let num_instances = self.rounds.len();
+        self.rounds
+            .into_iter()
+            .zip(unbatch(num_instances, payloads))
+            .zip(unbatch(num_instances, artifacts))
+            .enumerate()
+            .map(|(index, ((round, payloads), artifacts))| {
+                round
+                    .finalize_to_result(rng, payloads, artifacts)
+                    .map_err(|error| wrap_batch_error(index, error))
+            })
+            .collect()
+    }
+}
@@ -9,23 +9,35 @@ use serde::{Deserialize, Serialize};
 
 use crate::tools::collections::{HoleRange, HoleVec, HoleVecAccum};
 use crate::tools::hashing::{Chain, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 
 /// A typed integer denoting the index of a party in the group.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PartyIdx(u32);
 
+// Ensures that the conversion in `PartyIdx::as_usize()` is lossless.
+const _: () = assert!(usize::BITS >= u32::BITS);
+
 impl PartyIdx {
     /// Converts the party index to a regular integer.
     pub fn as_usize(self) -> usize {
-        self.0.try_into().unwrap()
+        self.0 as usize
     }
 
     /// Wraps an integers into the party index.
+    ///
+    /// Panics if `val` does not fit into `u32`.
     pub fn from_usize(val: usize) -> Self {
         Self(val.try_into().unwrap())
     }
 }
 
+impl MaxSerializedSize for PartyIdx {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        4
+    }
+}
+
 impl Hashable for PartyIdx {
     fn chain<C: Chain>(&self, digest: C) -> C {
         digest.chain(&self.0)
@@ -132,11 +144,13 @@ pub(crate) trait Round {
 /// Typed outcomes of a protocol, specific for each protocol
 /// (in addition to non-specific errors common for all protocols).
 pub trait ProtocolResult: Debug {
+    /// The name of the protocol, used in error reports.
+    const NAME: &'static str;
     /// The result obtained on successful termination of the protocol.
     type Success;
     /// A collection of data which, in combination with the messages received,
     /// can be used to prove malicious behavior of a remote node.
-    type ProvableError: Debug + Clone;
+    type ProvableError: Debug + Clone + Send;
     /// A collection of data which, in combination with the messages received,
     /// can be used to prove correct behavior of this node.
     ///
@@ -158,6 +172,8 @@ impl FinalizableType for ToNextRound {}
 #[allow(clippy::enum_variant_names)]
 pub(crate) enum FinalizationRequirement {
     All,
+    // None of the current rounds need it, but it is kept for the ones that may.
+    #[allow(dead_code)]
     Custom,
 }
 
@@ -183,9 +199,18 @@ pub(crate) trait FinalizableToNextRound: Round<Type = ToNextRound> {
 #[derive(Debug, Clone)]
 pub enum FinalizeError<Res: ProtocolResult> {
     Proof(Res::CorrectnessProof),
+    /// Returned when a fault of a party is only found on finalization
+    /// (e.g. when the proofs of all the parties are checked at once).
+    Provable {
+        party: PartyIdx,
+        error: Res::ProvableError,
+    },
     /// Returned when there is an error chaining the start of another protocol
     /// on the finalization of the previous one.
     Init(InitError),
+    /// Returned when the result fails a self-check of the local node,
+    /// which indicates a bug or a corruption of the local state rather than a fault of another party.
+    Local(String),
 }
 
 /// An error that can occur when initializing a protocol.
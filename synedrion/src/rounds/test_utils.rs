@@ -9,6 +9,16 @@ use rand_core::CryptoRngCore;
 
 use super::generic::{FinalizableToNextRound, FinalizableToResult, ProtocolResult, Round};
 use super::{FinalizeError, PartyIdx};
+#[cfg(test)]
+use crate::cggmp21::SchemeParams;
+#[cfg(test)]
+use crate::common::KeyShare;
+#[cfg(all(test, feature = "ed25519"))]
+use crate::ed25519::{Ed25519KeyShare, Ed25519ThresholdKeyShare};
+#[cfg(test)]
+use crate::threshold::ThresholdKeyShare;
+#[cfg(test)]
+use crate::tools::sss::ShareIdx;
 
 #[derive(Debug, Display)]
 pub(crate) enum StepError {
@@ -123,3 +133,50 @@ pub(crate) fn step_result<R: FinalizableToResult>(
     }
     Ok(results)
 }
+
+/// A t-of-n key share that can be converted into a t-of-t key share for a quorum of the holders.
+#[cfg(test)]
+pub(crate) trait ToQuorumKeyShare {
+    type KeyShare;
+    fn share_idx(&self) -> ShareIdx;
+    fn to_quorum_key_share(&self, share_idxs: &[ShareIdx]) -> Self::KeyShare;
+}
+
+#[cfg(test)]
+impl<P: SchemeParams> ToQuorumKeyShare for ThresholdKeyShare<P> {
+    type KeyShare = KeyShare<P>;
+    fn share_idx(&self) -> ShareIdx {
+        self.index()
+    }
+    fn to_quorum_key_share(&self, share_idxs: &[ShareIdx]) -> Self::KeyShare {
+        self.to_key_share(share_idxs)
+    }
+}
+
+#[cfg(all(test, feature = "ed25519"))]
+impl ToQuorumKeyShare for Ed25519ThresholdKeyShare {
+    type KeyShare = Ed25519KeyShare;
+    fn share_idx(&self) -> ShareIdx {
+        self.index()
+    }
+    fn to_quorum_key_share(&self, share_idxs: &[ShareIdx]) -> Self::KeyShare {
+        self.to_key_share(share_idxs)
+    }
+}
+
+/// Converts the threshold key shares at positions `holders` in `shares`
+/// into the t-of-t key shares of that quorum, with the party indices following the order of `holders`.
+#[cfg(test)]
+pub(crate) fn quorum_key_shares<T: ToQuorumKeyShare>(
+    shares: &[T],
+    holders: &[usize],
+) -> Vec<T::KeyShare> {
+    let share_idxs = holders
+        .iter()
+        .map(|idx| shares[*idx].share_idx())
+        .collect::<Vec<_>>();
+    holders
+        .iter()
+        .map(|idx| shares[*idx].to_quorum_key_share(&share_idxs))
+        .collect()
+}
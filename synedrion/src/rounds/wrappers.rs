@@ -14,16 +14,24 @@ pub(crate) trait CorrectnessProofWrapper<Res: ProtocolResult>: ProtocolResult {
     fn wrap_proof(proof: Res::CorrectnessProof) -> Self::CorrectnessProof;
 }
 
-pub(crate) fn wrap_finalize_error<T: ProtocolResult, Res: CorrectnessProofWrapper<T>>(
+pub(crate) fn wrap_finalize_error<
+    T: ProtocolResult,
+    Res: CorrectnessProofWrapper<T> + ProvableErrorWrapper<T>,
+>(
     error: FinalizeError<T>,
 ) -> FinalizeError<Res> {
     match error {
         FinalizeError::Init(msg) => FinalizeError::Init(msg),
+        FinalizeError::Local(msg) => FinalizeError::Local(msg),
         FinalizeError::Proof(proof) => FinalizeError::Proof(Res::wrap_proof(proof)),
+        FinalizeError::Provable { party, error } => FinalizeError::Provable {
+            party,
+            error: Res::wrap_error(error),
+        },
     }
 }
 
-pub(crate) trait RoundWrapper: 'static + Sized + Send {
+pub(crate) trait RoundWrapper: 'static + Sized + Send + Sync {
     type Result: ProtocolResult + ProvableErrorWrapper<<Self::InnerRound as Round>::Result>;
     type Type: FinalizableType;
     type InnerRound: Round;
@@ -1,15 +1,68 @@
 //! Mutable wrappers around the protocols for easier handling.
 
+mod attestation;
 mod combined_message;
+mod driver;
 mod echo;
+mod encryption;
 mod error;
+mod format;
+mod info;
+#[cfg(feature = "low-level")]
+mod low_level;
+mod manager;
+mod metrics;
+#[cfg(feature = "testing")]
+mod misbehavior;
+mod recorder;
+mod resumable;
 mod session;
 mod signed_message;
+#[cfg(feature = "tokio")]
+mod tokio_transport;
+#[cfg(feature = "keygen")]
+mod transcript;
 mod type_erased;
 
-pub use combined_message::CombinedMessage;
+pub use attestation::{SigningAttestation, SigningCertificate, SigningCertificateError};
+pub use combined_message::{CombinedMessage, EquivocationEvidence};
+pub use driver::{
+    run_session, run_session_with_metrics, run_session_with_observer, run_session_with_recorder,
+    run_session_with_rng_factory, RngFactory, RunError, SessionObserver, Transport,
+};
 pub use echo::EchoError;
-pub use error::{Error, LocalError, ProvableError, RemoteError, RemoteErrorEnum};
+pub use encryption::{DirectMessageEncryption, DIRECT_MESSAGE_ENCRYPTION_OVERHEAD};
+pub use error::{
+    AbortCertificate, AbortCertificateError, AbortReport, Error, ErrorContext, LocalError,
+    ProvableError, RemoteError, RemoteErrorEnum,
+};
+#[cfg(feature = "cbor")]
+pub use format::Cbor;
+pub use format::{Bincode, Compact, MessageFormat};
+pub use info::{ProtocolInfo, RoundInfo};
+#[cfg(feature = "low-level")]
+pub use low_level::{LowLevelMessage, LowLevelOutcome, LowLevelRound};
+pub use manager::{SessionLimits, SessionManager, SessionManagerError, SessionStatus};
+pub use metrics::{AbortCause, SessionMetrics};
+#[cfg(feature = "testing")]
+pub use misbehavior::Misbehavior;
+pub use recorder::{
+    MessageDirection, MessageTranscript, MessageTranscriptError, TranscriptEntry,
+    TranscriptRecorder,
+};
+pub use resumable::{MessageLog, ResumableTransport};
 pub use session::{
-    Artifact, FinalizeOutcome, PreprocessedMessage, ProcessedMessage, RoundAccumulator, Session,
+    Artifact, FinalizeOutcome, OutgoingPart, PreprocessedMessage, ProcessedMessage, ReceiveOutcome,
+    RoundAccumulator, SendingState, Session,
+};
+pub use signed_message::{
+    FragmentError, MessageFragment, MessageReassembler, MessageType, MessageVersion, SessionId,
+    SignedMessage,
+};
+#[cfg(feature = "keygen")]
+pub use transcript::{KeyGenTranscript, TranscriptError};
+
+#[cfg(feature = "tokio")]
+pub use tokio_transport::{
+    ChannelTransport, ChannelTransportError, IncomingMessage, OutgoingMessage,
 };
@@ -0,0 +1,286 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+
+use super::error::LocalError;
+use super::signed_message::SessionId;
+use super::type_erased::serialize_message;
+use crate::curve::{BackendSignature, Point, RecoverableSignature, Scalar, VerifyingKey};
+use crate::tools::{
+    hashing::{Chain, Hash, HashOutput},
+    serde_bytes,
+};
+use crate::PrehashedMessage;
+
+/// A statement that a signature was created by the given parties in a signing session,
+/// which they can endorse (see [`SigningCertificate`]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningAttestation<Verifier> {
+    session_id: SessionId,
+    participants: Vec<Verifier>,
+    verifying_key: Point,
+    #[serde(with = "serde_bytes::as_hex")]
+    prehashed_message: PrehashedMessage,
+    r: Scalar,
+    s: Scalar,
+}
+
+impl<Verifier: Clone> SigningAttestation<Verifier> {
+    /// Creates the attestation of `signature`, created for `prehashed_message`
+    /// with `verifying_key` in the session with the ID `session_id`
+    /// (see [`Session::session_id`](super::Session::session_id))
+    /// by the parties `participants`.
+    ///
+    /// Fails if the signature is invalid.
+    pub fn new(
+        session_id: &SessionId,
+        participants: &BTreeSet<Verifier>,
+        verifying_key: &VerifyingKey,
+        prehashed_message: &PrehashedMessage,
+        signature: &RecoverableSignature,
+    ) -> Result<Self, LocalError> {
+        if !signature.verify(verifying_key, prehashed_message) {
+            return Err(LocalError(
+                "The signature does not verify with the given key and message".into(),
+            ));
+        }
+        let (signature, _recovery_id) = signature.to_backend();
+        Ok(Self {
+            session_id: *session_id,
+            participants: participants.iter().cloned().collect(),
+            verifying_key: Point::from_verifying_key(verifying_key),
+            prehashed_message: *prehashed_message,
+            r: Scalar::from(&signature.r()),
+            s: Scalar::from(&signature.s()),
+        })
+    }
+}
+
+impl<Verifier> SigningAttestation<Verifier> {
+    /// The ID of the session that created the signature.
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    /// The parties of the session, in the order of their indices.
+    pub fn participants(&self) -> &[Verifier] {
+        &self.participants
+    }
+
+    /// The verifying key of the signature.
+    ///
+    /// Returns `None` if the attestation was deserialized from a malformed one.
+    pub fn verifying_key(&self) -> Option<VerifyingKey> {
+        self.verifying_key.to_verifying_key()
+    }
+
+    /// The signed prehashed message.
+    pub fn prehashed_message(&self) -> &PrehashedMessage {
+        &self.prehashed_message
+    }
+
+    /// The signature.
+    ///
+    /// Returns `None` if the attestation was deserialized from a malformed one.
+    pub fn signature(&self) -> Option<BackendSignature> {
+        BackendSignature::from_scalars(self.r.to_backend(), self.s.to_backend()).ok()
+    }
+
+    fn signature_is_valid(&self) -> bool {
+        match (self.verifying_key(), self.signature()) {
+            (Some(verifying_key), Some(signature)) => verifying_key
+                .verify_prehash(&self.prehashed_message, &signature)
+                .is_ok(),
+            _ => false,
+        }
+    }
+}
+
+impl<Verifier: Serialize> SigningAttestation<Verifier> {
+    fn hash(&self) -> Result<HashOutput, LocalError> {
+        let participants = self
+            .participants
+            .iter()
+            .map(serialize_message)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Hash::new_with_dst(b"SigningAttestation")
+            .chain(&self.session_id)
+            .chain(&participants)
+            .chain(&self.verifying_key)
+            .chain(&self.prehashed_message)
+            .chain(&self.r)
+            .chain(&self.s)
+            .finalize())
+    }
+}
+
+/// Errors returned by [`SigningCertificate::verify`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum SigningCertificateError {
+    /// The attestation cannot be serialized.
+    CannotSerialize,
+    /// The attested signature is invalid.
+    InvalidSignature,
+    /// One of the endorsers is not a participant of the session.
+    UnknownEndorser,
+    /// One of the endorsement signatures is invalid.
+    InvalidEndorsement,
+    /// {missing} of the participants have not endorsed the attestation.
+    MissingEndorsements {
+        /// The number of the participants without an endorsement.
+        missing: usize,
+    },
+}
+
+impl core::error::Error for SigningCertificateError {}
+
+/// A [`SigningAttestation`] signed by all the participants of the session,
+/// certifying to third parties which parties took part in creating the signature
+/// (e.g. to enforce that a certain party is involved in every signature).
+///
+/// Once a signing session succeeds, each party creates the attestation from the result
+/// and the session it ran, and signs it with [`new`](Self::new) or [`endorse`](Self::endorse)
+/// (which checks that the attestation matches its own).
+/// The endorsements are exchanged the same way as the ones of an
+/// [`AbortCertificate`](super::AbortCertificate), and combined with [`merge`](Self::merge).
+/// Anyone can then check with [`verify`](Self::verify) that the signature is valid
+/// and that every participant has endorsed it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Sig: Serialize, Verifier: Serialize",
+    deserialize = "Sig: for<'x> Deserialize<'x>, Verifier: Ord + for<'x> Deserialize<'x>"
+))]
+pub struct SigningCertificate<Sig, Verifier> {
+    attestation: SigningAttestation<Verifier>,
+    endorsements: BTreeMap<Verifier, Sig>,
+}
+
+impl<Sig, Verifier> SigningCertificate<Sig, Verifier>
+where
+    Verifier: Clone + Ord + Serialize,
+{
+    /// Creates a certificate for the attestation, endorsed by `signer`.
+    pub fn new<Signer>(
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+        attestation: SigningAttestation<Verifier>,
+    ) -> Result<Self, LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        let mut certificate = Self {
+            attestation,
+            endorsements: BTreeMap::new(),
+        };
+        certificate.add_endorsement(rng, signer)?;
+        Ok(certificate)
+    }
+
+    /// The attestation being endorsed.
+    pub fn attestation(&self) -> &SigningAttestation<Verifier> {
+        &self.attestation
+    }
+
+    /// The parties that have endorsed the attestation.
+    pub fn endorsers(&self) -> Vec<Verifier> {
+        self.endorsements.keys().cloned().collect()
+    }
+
+    /// Adds the endorsement of `signer`, whose own attestation of the signature is `own_attestation`.
+    ///
+    /// Fails if the attestations do not match.
+    pub fn endorse<Signer>(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+        own_attestation: &SigningAttestation<Verifier>,
+    ) -> Result<(), LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        if own_attestation != &self.attestation {
+            return Err(LocalError(
+                "The attestation does not match the one being endorsed".into(),
+            ));
+        }
+        self.add_endorsement(rng, signer)
+    }
+
+    /// Adds the endorsements from another certificate for the same attestation.
+    ///
+    /// The endorsements are not verified.
+    pub fn merge(&mut self, other: Self) -> Result<(), LocalError> {
+        if other.attestation != self.attestation {
+            return Err(LocalError(
+                "Cannot merge the certificates for different attestations".into(),
+            ));
+        }
+        self.endorsements.extend(other.endorsements);
+        Ok(())
+    }
+
+    /// Checks that the attested signature is valid,
+    /// and that it is endorsed by every participant of the session.
+    ///
+    /// The verifying key and the participants must be checked separately
+    /// against the ones expected by the caller.
+    pub fn verify(&self) -> Result<(), SigningCertificateError>
+    where
+        Verifier: PrehashVerifier<Sig>,
+    {
+        if !self.attestation.signature_is_valid() {
+            return Err(SigningCertificateError::InvalidSignature);
+        }
+        let hash = self
+            .attestation
+            .hash()
+            .map_err(|_| SigningCertificateError::CannotSerialize)?;
+        for (endorser, signature) in self.endorsements.iter() {
+            if !self.attestation.participants.contains(endorser) {
+                return Err(SigningCertificateError::UnknownEndorser);
+            }
+            endorser
+                .verify_prehash(hash.as_ref(), signature)
+                .map_err(|_| SigningCertificateError::InvalidEndorsement)?;
+        }
+        let missing = self
+            .attestation
+            .participants
+            .iter()
+            .filter(|participant| !self.endorsements.contains_key(participant))
+            .count();
+        if missing > 0 {
+            return Err(SigningCertificateError::MissingEndorsements { missing });
+        }
+        Ok(())
+    }
+
+    fn add_endorsement<Signer>(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+    ) -> Result<(), LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        let endorser = signer.verifying_key();
+        if !self.attestation.participants.contains(&endorser) {
+            return Err(LocalError(
+                "Only a participant of the session can endorse the attestation".into(),
+            ));
+        }
+        let signature = signer
+            .sign_prehash_with_rng(rng, self.attestation.hash()?.as_ref())
+            .map_err(|err| LocalError(err.to_string()))?;
+        self.endorsements.insert(endorser, signature);
+        Ok(())
+    }
+}
@@ -1,14 +1,23 @@
+use alloc::boxed::Box;
 use alloc::string::String;
 
 use serde::{Deserialize, Serialize};
 use signature::hazmat::PrehashVerifier;
 
-use super::signed_message::{MessageType, SessionId, SignedMessage, VerifiedMessage};
+use super::error::{LocalError, RemoteErrorEnum};
+use super::format::{deserialize_canonical, Bincode};
+use super::signed_message::{
+    max_signed_message_size, MessageType, SessionId, SignedMessage, VerifiedMessage,
+};
+use super::type_erased::serialize_message;
+use crate::rounds::PartyIdx;
+use crate::tools::serde_bytes;
+use crate::tools::serialized_size::VARIANT_INDEX_SIZE;
 
 /// Combined message from a single round
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum CombinedMessage<Sig> {
-    /// One message (broadcast, direct, or echo)
+    /// One message (broadcast, direct, echo, or cancellation)
     One(SignedMessage<Sig>),
     /// A broadcast and a direct message
     Both {
@@ -19,21 +28,90 @@ pub enum CombinedMessage<Sig> {
     },
 }
 
+/// Returns the upper bound on the size of a serialized [`CombinedMessage`]
+/// of the protocol `protocol` with the payloads of the given sizes.
+pub(crate) fn max_combined_message_size(
+    protocol: &str,
+    broadcast_payload_size: Option<usize>,
+    direct_payload_size: Option<usize>,
+    max_signature_size: usize,
+) -> usize {
+    VARIANT_INDEX_SIZE
+        + [broadcast_payload_size, direct_payload_size]
+            .into_iter()
+            .flatten()
+            .map(|size| max_signed_message_size(protocol, size, max_signature_size))
+            .sum::<usize>()
+}
+
 impl<Sig> CombinedMessage<Sig> {
+    /// Returns the ID of the session this message belongs to.
+    ///
+    /// Note that the message is not verified yet, so it is only useful for routing.
+    pub fn session_id(&self) -> &SessionId {
+        match self {
+            CombinedMessage::One(msg) => msg.session_id(),
+            CombinedMessage::Both { broadcast, .. } => broadcast.session_id(),
+        }
+    }
+
+    /// Puts together the parts of a message sent separately
+    /// (see [`SendingState::parts`](super::SendingState::parts)).
+    ///
+    /// Returns `None` if both parts are missing.
+    /// Which parts to expect in a given round is listed by [`RoundInfo`](super::RoundInfo).
+    /// The parts are checked to belong together when the message is preprocessed.
+    pub fn from_parts(
+        broadcast: Option<SignedMessage<Sig>>,
+        direct: Option<SignedMessage<Sig>>,
+    ) -> Option<Self> {
+        match (broadcast, direct) {
+            (Some(broadcast), Some(direct)) => Some(Self::Both { broadcast, direct }),
+            (Some(message), None) | (None, Some(message)) => Some(Self::One(message)),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the canonical encoding of this message.
+    ///
+    /// Regardless of the way the messages are serialized for transport,
+    /// this encoding (`bincode` with the default options) is fixed,
+    /// so it can be used to hash or compare the messages, e.g. when verifying evidence.
+    /// Deserializing it with `bincode` and encoding the result again produces the same bytes.
+    pub fn canonical_bytes(&self) -> Result<Box<[u8]>, LocalError>
+    where
+        Sig: Serialize,
+    {
+        serialize_message(self)
+    }
+
     pub(crate) fn check(self) -> Result<CheckedCombinedMessage<Sig>, String> {
         let messages = match self {
             CombinedMessage::One(msg) => match msg.message_type() {
                 MessageType::Broadcast => CombinedMessageEnum::Broadcast(msg),
                 MessageType::Direct => CombinedMessageEnum::Direct(msg),
                 MessageType::Echo => CombinedMessageEnum::Echo(msg),
+                MessageType::Cancel => CombinedMessageEnum::Cancel(msg),
             },
             CombinedMessage::Both { broadcast, direct } => {
+                if broadcast.version() != direct.version() {
+                    return Err("Mismatched message versions".into());
+                }
                 if broadcast.session_id() != direct.session_id() {
                     return Err("Mismatched session IDs".into());
                 }
                 if broadcast.round() != direct.round() {
                     return Err("Mismatched round numbers".into());
                 }
+                if broadcast.format() != direct.format() {
+                    return Err("Mismatched message formats".into());
+                }
+                if broadcast.protocol() != direct.protocol() {
+                    return Err("Mismatched protocol names".into());
+                }
+                if broadcast.sender() != direct.sender() {
+                    return Err("Mismatched senders".into());
+                }
                 if broadcast.message_type() != MessageType::Broadcast {
                     return Err("Invalid message type of the broadcast field".into());
                 }
@@ -53,17 +131,54 @@ enum CombinedMessageEnum<M> {
     Direct(M),
     Both { broadcast: M, direct: M },
     Echo(M),
+    Cancel(M),
+}
+
+impl<M> CombinedMessageEnum<M> {
+    /// Returns the broadcast, the direct and the echo parts of the message
+    /// (none for a cancellation, which is handled before the messages are compared).
+    fn parts(&self) -> [Option<&M>; 3] {
+        match self {
+            Self::Broadcast(msg) => [Some(msg), None, None],
+            Self::Direct(msg) => [None, Some(msg), None],
+            Self::Both { broadcast, direct } => [Some(broadcast), Some(direct), None],
+            Self::Echo(msg) => [None, None, Some(msg)],
+            Self::Cancel(_) => [None, None, None],
+        }
+    }
+}
+
+fn is_same_as<Sig>(
+    lhs: [Option<&SignedMessage<Sig>>; 3],
+    rhs: [Option<&SignedMessage<Sig>>; 3],
+) -> bool {
+    lhs.iter().zip(rhs.iter()).all(|parts| match parts {
+        (None, None) => true,
+        (Some(lhs), Some(rhs)) => lhs.is_same_as(rhs),
+        _ => false,
+    })
 }
 
 #[derive(Clone, Debug)]
 pub struct CheckedCombinedMessage<Sig>(CombinedMessageEnum<SignedMessage<Sig>>);
 
 impl<Sig> CheckedCombinedMessage<Sig> {
+    pub fn version(&self) -> u8 {
+        match &self.0 {
+            CombinedMessageEnum::Broadcast(msg) => msg.version(),
+            CombinedMessageEnum::Direct(msg) => msg.version(),
+            CombinedMessageEnum::Echo(msg) => msg.version(),
+            CombinedMessageEnum::Cancel(msg) => msg.version(),
+            CombinedMessageEnum::Both { broadcast, .. } => broadcast.version(),
+        }
+    }
+
     pub fn session_id(&self) -> &SessionId {
         match &self.0 {
             CombinedMessageEnum::Broadcast(msg) => msg.session_id(),
             CombinedMessageEnum::Direct(msg) => msg.session_id(),
             CombinedMessageEnum::Echo(msg) => msg.session_id(),
+            CombinedMessageEnum::Cancel(msg) => msg.session_id(),
             CombinedMessageEnum::Both { broadcast, .. } => broadcast.session_id(),
         }
     }
@@ -73,14 +188,70 @@ impl<Sig> CheckedCombinedMessage<Sig> {
             CombinedMessageEnum::Broadcast(msg) => msg.round(),
             CombinedMessageEnum::Direct(msg) => msg.round(),
             CombinedMessageEnum::Echo(msg) => msg.round(),
+            CombinedMessageEnum::Cancel(msg) => msg.round(),
             CombinedMessageEnum::Both { broadcast, .. } => broadcast.round(),
         }
     }
 
+    pub fn format(&self) -> u8 {
+        match &self.0 {
+            CombinedMessageEnum::Broadcast(msg) => msg.format(),
+            CombinedMessageEnum::Direct(msg) => msg.format(),
+            CombinedMessageEnum::Echo(msg) => msg.format(),
+            CombinedMessageEnum::Cancel(msg) => msg.format(),
+            CombinedMessageEnum::Both { broadcast, .. } => broadcast.format(),
+        }
+    }
+
     pub fn is_echo(&self) -> bool {
         matches!(&self.0, CombinedMessageEnum::Echo(_))
     }
 
+    pub fn is_cancel(&self) -> bool {
+        matches!(&self.0, CombinedMessageEnum::Cancel(_))
+    }
+
+    /// Compares the "significant" part of the messages (that is, everything but signatures).
+    pub fn is_same_as(&self, other: &VerifiedCombinedMessage<Sig>) -> bool {
+        is_same_as(self.0.parts(), other.signed_parts())
+    }
+
+    /// Checks that the message belongs to the protocol `protocol`, was sent by `sender`,
+    /// and, if it has a direct part, is intended for `recipient`
+    /// and is encrypted if `encrypted` is `true` (and only then).
+    pub fn check_header(
+        &self,
+        protocol: &str,
+        sender: PartyIdx,
+        recipient: PartyIdx,
+        encrypted: bool,
+    ) -> Result<(), RemoteErrorEnum> {
+        let messages = match &self.0 {
+            CombinedMessageEnum::Broadcast(msg)
+            | CombinedMessageEnum::Direct(msg)
+            | CombinedMessageEnum::Echo(msg)
+            | CombinedMessageEnum::Cancel(msg) => [Some(msg), None],
+            CombinedMessageEnum::Both { broadcast, direct } => [Some(broadcast), Some(direct)],
+        };
+        for msg in messages.into_iter().flatten() {
+            if msg.protocol() != protocol {
+                return Err(RemoteErrorEnum::UnexpectedProtocol);
+            }
+            if msg.sender() != sender.as_usize() {
+                return Err(RemoteErrorEnum::UnexpectedSender);
+            }
+            let expected_recipient =
+                (msg.message_type() == MessageType::Direct).then_some(recipient.as_usize());
+            if msg.recipient() != expected_recipient {
+                return Err(RemoteErrorEnum::UnexpectedRecipient);
+            }
+            if msg.is_encrypted() != (encrypted && msg.message_type() == MessageType::Direct) {
+                return Err(RemoteErrorEnum::UnexpectedEncryption);
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn verify(
         self,
         verifier: &impl PrehashVerifier<Sig>,
@@ -91,6 +262,7 @@ impl<Sig> CheckedCombinedMessage<Sig> {
             }
             CombinedMessageEnum::Direct(msg) => CombinedMessageEnum::Direct(msg.verify(verifier)?),
             CombinedMessageEnum::Echo(msg) => CombinedMessageEnum::Echo(msg.verify(verifier)?),
+            CombinedMessageEnum::Cancel(msg) => CombinedMessageEnum::Cancel(msg.verify(verifier)?),
             CombinedMessageEnum::Both { broadcast, direct } => CombinedMessageEnum::Both {
                 broadcast: broadcast.verify(verifier)?,
                 direct: direct.verify(verifier)?,
@@ -121,9 +293,13 @@ impl<Sig> VerifiedCombinedMessage<Sig> {
     }
 
     pub fn direct_payload(&self) -> Option<&[u8]> {
+        self.direct_message().map(VerifiedMessage::payload)
+    }
+
+    pub fn direct_message(&self) -> Option<&VerifiedMessage<Sig>> {
         match &self.0 {
-            CombinedMessageEnum::Direct(msg) => Some(msg.payload()),
-            CombinedMessageEnum::Both { direct, .. } => Some(direct.payload()),
+            CombinedMessageEnum::Direct(msg) => Some(msg),
+            CombinedMessageEnum::Both { direct, .. } => Some(direct),
             _ => None,
         }
     }
@@ -138,4 +314,129 @@ impl<Sig> VerifiedCombinedMessage<Sig> {
     pub fn is_echo(&self) -> bool {
         matches!(&self.0, CombinedMessageEnum::Echo(_))
     }
+
+    /// Returns the reason given by the sender if this is a cancellation.
+    pub fn cancellation_reason(&self) -> Option<String> {
+        match &self.0 {
+            CombinedMessageEnum::Cancel(msg) => {
+                Some(String::from_utf8_lossy(msg.payload()).into_owned())
+            }
+            _ => None,
+        }
+    }
+
+    fn signed_parts(&self) -> [Option<&SignedMessage<Sig>>; 3] {
+        self.0
+            .parts()
+            .map(|part| part.map(VerifiedMessage::as_unverified))
+    }
+
+    /// Compares the "significant" part of the messages (that is, everything but signatures).
+    pub fn is_same_as(&self, other: &Self) -> bool {
+        is_same_as(self.signed_parts(), other.signed_parts())
+    }
+
+    /// Returns `true` if this message and `other`, both signed by the same party,
+    /// prove that it equivocated: they belong to the same session, protocol and round,
+    /// and either their broadcast parts differ, or they differ and are intended for the same party.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        let [lhs_broadcast, lhs_direct, lhs_echo] = self.signed_parts();
+        let [rhs_broadcast, rhs_direct, rhs_echo] = other.signed_parts();
+        let (Some(lhs), Some(rhs)) = (
+            lhs_broadcast.or(lhs_direct).or(lhs_echo),
+            rhs_broadcast.or(rhs_direct).or(rhs_echo),
+        ) else {
+            return false;
+        };
+        if lhs.session_id() != rhs.session_id()
+            || lhs.protocol() != rhs.protocol()
+            || lhs.round() != rhs.round()
+            || lhs.sender() != rhs.sender()
+            || self.is_echo() != other.is_echo()
+        {
+            return false;
+        }
+
+        let broadcasts_differ = match (lhs_broadcast, rhs_broadcast) {
+            (Some(lhs), Some(rhs)) => !lhs.is_same_as(rhs),
+            _ => false,
+        };
+        let same_recipient = lhs_direct.and_then(|msg| msg.recipient())
+            == rhs_direct.and_then(|msg| msg.recipient());
+        broadcasts_differ || (same_recipient && !self.is_same_as(other))
+    }
+
+    pub fn to_unverified(&self) -> CombinedMessage<Sig>
+    where
+        Sig: Clone,
+    {
+        match &self.0 {
+            CombinedMessageEnum::Broadcast(msg)
+            | CombinedMessageEnum::Direct(msg)
+            | CombinedMessageEnum::Echo(msg)
+            | CombinedMessageEnum::Cancel(msg) => CombinedMessage::One(msg.as_unverified().clone()),
+            CombinedMessageEnum::Both { broadcast, direct } => CombinedMessage::Both {
+                broadcast: broadcast.as_unverified().clone(),
+                direct: direct.as_unverified().clone(),
+            },
+        }
+    }
+}
+
+/// Two different messages signed by the same party for the same round of a session,
+/// which prove that it equivocated
+/// (see [`ProvableError::Equivocation`](super::ProvableError::Equivocation)).
+///
+/// The messages are kept in their canonical encoding (see [`CombinedMessage::canonical_bytes`]),
+/// so that the evidence can be stored and checked by a third party
+/// knowing the verifier of the accused party.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquivocationEvidence {
+    #[serde(with = "serde_bytes::as_base64")]
+    first: Box<[u8]>,
+    #[serde(with = "serde_bytes::as_base64")]
+    second: Box<[u8]>,
+}
+
+impl EquivocationEvidence {
+    pub(crate) fn new<Sig: Clone + Serialize>(
+        first: &VerifiedCombinedMessage<Sig>,
+        second: &VerifiedCombinedMessage<Sig>,
+    ) -> Result<Self, LocalError> {
+        Ok(Self {
+            first: first.to_unverified().canonical_bytes()?,
+            second: second.to_unverified().canonical_bytes()?,
+        })
+    }
+
+    /// The canonical encoding of the message that was received first.
+    pub fn first_message(&self) -> &[u8] {
+        &self.first
+    }
+
+    /// The canonical encoding of the conflicting message that was received after it.
+    pub fn second_message(&self) -> &[u8] {
+        &self.second
+    }
+
+    /// Checks that both messages are signed by `verifier`,
+    /// belong to the same session, protocol and round, and conflict with each other:
+    /// either their broadcast parts differ, or they differ and are intended for the same party.
+    pub fn verify<Sig>(&self, verifier: &impl PrehashVerifier<Sig>) -> bool
+    where
+        Sig: Serialize + for<'de> Deserialize<'de>,
+    {
+        let decode = |bytes: &[u8]| {
+            deserialize_canonical::<Bincode, CombinedMessage<Sig>>(bytes)
+                .ok()?
+                .check()
+                .ok()?
+                .verify(verifier)
+                .ok()
+        };
+        match (decode(&self.first), decode(&self.second)) {
+            (Some(first), Some(second)) => first.conflicts_with(&second),
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,459 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use core::future::Future;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+
+use super::combined_message::CombinedMessage;
+use super::error::{Error, LocalError};
+use super::format::MessageFormat;
+use super::metrics::{AbortCause, SessionMetrics};
+use super::recorder::TranscriptRecorder;
+use super::session::{FinalizeOutcome, Session};
+use crate::rounds::ProtocolResult;
+
+/// The networking layer used by [`run_session`] to exchange messages with other parties.
+pub trait Transport<Sig, Verifier> {
+    /// The error returned if the message cannot be sent or received.
+    type Error;
+
+    /// Sends a message to the party with the given verifier.
+    fn send(
+        &mut self,
+        destination: &Verifier,
+        message: CombinedMessage<Sig>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Waits for the next message from any of the other parties,
+    /// returning it along with the verifier of the sender.
+    fn receive(
+        &mut self,
+    ) -> impl Future<Output = Result<(Verifier, CombinedMessage<Sig>), Self::Error>> + Send;
+}
+
+/// A source of the random number generators used by [`run_session_with_rng_factory`].
+///
+/// An RNG is requested whenever the session needs randomness (to create the messages of a round
+/// and to finalize it) and dropped before the next `await`, so it is never held
+/// while waiting for the transport. This allows drawing the randomness of each round
+/// from a resource that cannot be kept across the awaits or moved between threads,
+/// such as a handle of a hardware TRNG device.
+///
+/// Implemented for the closures returning an RNG.
+pub trait RngFactory {
+    /// The type of the created RNGs.
+    type Rng<'a>: CryptoRngCore
+    where
+        Self: 'a;
+
+    /// Creates an RNG.
+    fn make_rng(&mut self) -> Self::Rng<'_>;
+}
+
+impl<F, R> RngFactory for F
+where
+    F: FnMut() -> R,
+    R: CryptoRngCore,
+{
+    type Rng<'a>
+        = R
+    where
+        Self: 'a;
+
+    fn make_rng(&mut self) -> Self::Rng<'_> {
+        self()
+    }
+}
+
+/// Lends the same RNG for every request.
+struct BorrowedRng<'r, R>(&'r mut R);
+
+impl<R: CryptoRngCore> RngFactory for BorrowedRng<'_, R> {
+    type Rng<'a>
+        = &'a mut R
+    where
+        Self: 'a;
+
+    fn make_rng(&mut self) -> Self::Rng<'_> {
+        self.0
+    }
+}
+
+/// Receives the progress of a session executed by [`run_session_with_observer`],
+/// e.g. to show "round 2 of 5, waiting for 2 parties" in a UI
+/// (the total number of rounds is available from [`ProtocolInfo`](super::ProtocolInfo)).
+///
+/// All the methods do nothing by default. They must not block,
+/// since the session is not advanced while they are executed.
+pub trait SessionObserver<Verifier> {
+    /// Called when a round starts, after the messages of this party are sent
+    /// and the messages for this round received in advance are processed.
+    ///
+    /// `missing` lists the parties whose messages for this round are still being waited for.
+    fn on_round_start(&mut self, _round: u8, _is_echo_round: bool, _missing: &[Verifier]) {}
+
+    /// Called when a message from `from` is processed.
+    ///
+    /// `missing` lists the parties whose messages for this round are still being waited for.
+    fn on_message_received(&mut self, _from: &Verifier, _missing: &[Verifier]) {}
+
+    /// Called when a round is finalized.
+    fn on_finalize(&mut self, _round: u8, _is_echo_round: bool) {}
+}
+
+/// An observer ignoring all the events.
+impl<Verifier> SessionObserver<Verifier> for () {}
+
+/// Receives the messages of a session executed by [`run_session_inner`].
+trait MessageRecorder<Sig, Verifier> {
+    fn record_sent(
+        &mut self,
+        destination: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError>;
+
+    fn record_received(
+        &mut self,
+        from: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError>;
+}
+
+impl<Sig, Verifier> MessageRecorder<Sig, Verifier> for () {
+    fn record_sent(&mut self, _: &Verifier, _: &CombinedMessage<Sig>) -> Result<(), LocalError> {
+        Ok(())
+    }
+
+    fn record_received(
+        &mut self,
+        _: &Verifier,
+        _: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        Ok(())
+    }
+}
+
+impl<Sig, Verifier> MessageRecorder<Sig, Verifier> for TranscriptRecorder<Verifier>
+where
+    Sig: Serialize,
+    Verifier: Clone + Serialize,
+{
+    fn record_sent(
+        &mut self,
+        destination: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        TranscriptRecorder::record_sent(self, destination, message)
+    }
+
+    fn record_received(
+        &mut self,
+        from: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        TranscriptRecorder::record_received(self, from, message)
+    }
+}
+
+/// Possible errors returned by [`run_session`].
+#[derive(Clone, Debug)]
+pub enum RunError<Res: ProtocolResult, Verifier, TransportError> {
+    /// An error returned by the session.
+    Session(Error<Res, Verifier>),
+    /// An error returned by the transport.
+    Transport(TransportError),
+}
+
+impl<Res, Verifier, TransportError> Display for RunError<Res, Verifier, TransportError>
+where
+    Res: ProtocolResult,
+    Verifier: Debug,
+    TransportError: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Session(error) => write!(f, "{error}"),
+            Self::Transport(error) => write!(f, "Transport error: {error}"),
+        }
+    }
+}
+
+impl<Res, Verifier, TransportError> core::error::Error for RunError<Res, Verifier, TransportError>
+where
+    Res: ProtocolResult + 'static,
+    Verifier: Debug + 'static,
+    TransportError: Debug + Display,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Session(error) => Some(error),
+            Self::Transport(_) => None,
+        }
+    }
+}
+
+impl<Res: ProtocolResult, Verifier, TransportError> From<Error<Res, Verifier>>
+    for RunError<Res, Verifier, TransportError>
+{
+    fn from(error: Error<Res, Verifier>) -> Self {
+        Self::Session(error)
+    }
+}
+
+/// Executes the session to completion, exchanging messages via the given transport.
+///
+/// Messages are created and processed sequentially; if parallelism is required,
+/// the low-level [`Session`] methods can be used directly.
+pub async fn run_session<Res, Sig, Signer, Verifier, F, T>(
+    rng: &mut impl CryptoRngCore,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    run_session_with_observer(rng, transport, session, &mut ()).await
+}
+
+/// Executes the session to completion like [`run_session`],
+/// requesting an RNG from `rng_factory` every time the session needs randomness
+/// instead of holding one for the whole execution.
+pub async fn run_session_with_rng_factory<Res, Sig, Signer, Verifier, F, T>(
+    rng_factory: &mut impl RngFactory,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    run_session_inner(rng_factory, transport, session, &mut (), &mut (), &mut ()).await
+}
+
+/// Executes the session to completion like [`run_session`],
+/// reporting the progress to `observer`.
+pub async fn run_session_with_observer<Res, Sig, Signer, Verifier, F, T>(
+    rng: &mut impl CryptoRngCore,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    observer: &mut impl SessionObserver<Verifier>,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    run_session_inner(
+        &mut BorrowedRng(rng),
+        transport,
+        session,
+        observer,
+        &mut (),
+        &mut (),
+    )
+    .await
+}
+
+/// Executes the session to completion like [`run_session_with_observer`],
+/// additionally feeding every sent and received message into `recorder`.
+///
+/// The messages are recorded before they are processed,
+/// so the transcript covers the messages that caused the session to fail as well.
+pub async fn run_session_with_recorder<Res, Sig, Signer, Verifier, F, T>(
+    rng: &mut impl CryptoRngCore,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    observer: &mut impl SessionObserver<Verifier>,
+    recorder: &mut TranscriptRecorder<Verifier>,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord + Serialize,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    run_session_inner(
+        &mut BorrowedRng(rng),
+        transport,
+        session,
+        observer,
+        recorder,
+        &mut (),
+    )
+    .await
+}
+
+/// Executes the session to completion like [`run_session_with_observer`],
+/// additionally reporting the counters of the session to `metrics`.
+pub async fn run_session_with_metrics<Res, Sig, Signer, Verifier, F, T>(
+    rng: &mut impl CryptoRngCore,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    observer: &mut impl SessionObserver<Verifier>,
+    metrics: &mut impl SessionMetrics,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    run_session_inner(
+        &mut BorrowedRng(rng),
+        transport,
+        session,
+        observer,
+        &mut (),
+        metrics,
+    )
+    .await
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(protocol = Res::NAME))
+)]
+async fn run_session_inner<Res, Sig, Signer, Verifier, F, T>(
+    rng_factory: &mut impl RngFactory,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    observer: &mut impl SessionObserver<Verifier>,
+    recorder: &mut impl MessageRecorder<Sig, Verifier>,
+    metrics: &mut impl SessionMetrics,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    let result =
+        run_session_loop(rng_factory, transport, session, observer, recorder, metrics).await;
+    match &result {
+        Ok(_) => metrics.on_session_completed(Res::NAME),
+        Err(RunError::Session(error)) => metrics.on_abort(Res::NAME, error.into()),
+        Err(RunError::Transport(_)) => metrics.on_abort(Res::NAME, AbortCause::Transport),
+    }
+    result
+}
+
+/// Returns the size of the canonical encoding of the message.
+fn message_size<Sig: Serialize>(message: &CombinedMessage<Sig>) -> usize {
+    // The size is only used for the metrics, so an unserializable message is not an error here
+    // (it will fail when it is actually serialized).
+    bincode::serialized_size(message).map_or(0, |size| size as usize)
+}
+
+async fn run_session_loop<Res, Sig, Signer, Verifier, F, T>(
+    rng_factory: &mut impl RngFactory,
+    transport: &mut T,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    observer: &mut impl SessionObserver<Verifier>,
+    recorder: &mut impl MessageRecorder<Sig, Verifier>,
+    metrics: &mut impl SessionMetrics,
+) -> Result<Res::Success, RunError<Res, Verifier, T::Error>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+    T: Transport<Sig, Verifier>,
+{
+    let mut session = session;
+    let mut cached_messages = Vec::new();
+
+    loop {
+        let mut accum = session.make_accumulator();
+
+        // The messages are created before any of them is sent, so that the RNG is not held
+        // across the awaits.
+        let messages = session
+            .make_messages(&mut rng_factory.make_rng(), &mut accum)
+            .map_err(Error::Local)?;
+
+        for (destination, message) in messages {
+            recorder
+                .record_sent(&destination, &message)
+                .map_err(Error::Local)?;
+            let size = message_size(&message);
+            transport
+                .send(&destination, message)
+                .await
+                .map_err(RunError::Transport)?;
+            metrics.on_message_sent(Res::NAME, size);
+        }
+
+        for processed in session.process_messages(cached_messages) {
+            let processed =
+                processed.inspect_err(|_| metrics.on_verification_failure(Res::NAME))?;
+            accum
+                .add_processed_message(processed)
+                .map_err(Error::Local)?
+                .map_err(Error::Remote)?;
+        }
+
+        let (round, is_echo_round) = session.current_round();
+        let missing = session.missing_messages(&accum).map_err(Error::Local)?;
+        observer.on_round_start(round, is_echo_round, &missing);
+
+        while !session.can_finalize(&accum).map_err(Error::Local)? {
+            let (from, message) = transport.receive().await.map_err(RunError::Transport)?;
+            recorder
+                .record_received(&from, &message)
+                .map_err(Error::Local)?;
+            metrics.on_message_received(Res::NAME, message_size(&message));
+            let preprocessed = session
+                .preprocess_message(&mut accum, &from, message)
+                .inspect_err(|_| metrics.on_verification_failure(Res::NAME))?;
+            if let Some(preprocessed) = preprocessed {
+                let processed = session
+                    .process_message(preprocessed)
+                    .inspect_err(|_| metrics.on_verification_failure(Res::NAME))?;
+                accum
+                    .add_processed_message(processed)
+                    .map_err(Error::Local)?
+                    .map_err(Error::Remote)?;
+                let missing = session.missing_messages(&accum).map_err(Error::Local)?;
+                observer.on_message_received(&from, &missing);
+            }
+        }
+
+        let outcome = session.finalize_round(&mut rng_factory.make_rng(), accum)?;
+        observer.on_finalize(round, is_echo_round);
+        metrics.on_round_completed(Res::NAME, round, is_echo_round);
+        match outcome {
+            FinalizeOutcome::Success(result) => return Ok(result),
+            FinalizeOutcome::AnotherRound {
+                session: new_session,
+                cached_messages: new_cached_messages,
+            } => {
+                session = new_session;
+                cached_messages = new_cached_messages;
+            }
+        }
+    }
+}
@@ -3,12 +3,15 @@ use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use displaydoc::Display;
 use serde::{Deserialize, Serialize};
+use signature::hazmat::PrehashVerifier;
 
-use super::signed_message::{SignedMessage, VerifiedMessage};
-use super::type_erased::{deserialize_message, serialize_message};
+use super::format::{deserialize_canonical, MessageFormat};
+use super::signed_message::{max_signed_message_size, SignedMessage, VerifiedMessage};
 use crate::rounds::PartyIdx;
 use crate::tools::collections::HoleVecAccum;
+use crate::tools::serialized_size::{MaxSerializedSize, LENGTH_PREFIX_SIZE};
 
 #[derive(Clone)]
 pub(crate) struct EchoRound<Sig> {
@@ -20,29 +23,63 @@ struct Message<Sig> {
     broadcasts: Vec<(PartyIdx, SignedMessage<Sig>)>,
 }
 
+/// Returns the upper bound on the size of the echo message payload
+/// for the broadcasts with payloads of the given size.
+pub(crate) fn max_echo_payload_size(
+    protocol: &str,
+    num_parties: usize,
+    broadcast_payload_size: usize,
+    max_signature_size: usize,
+) -> usize {
+    // The broadcasts from all the other parties, with their indices.
+    LENGTH_PREFIX_SIZE
+        + (num_parties - 1)
+            * (PartyIdx::max_serialized_size(num_parties)
+                + max_signed_message_size(protocol, broadcast_payload_size, max_signature_size))
+}
+
 /// Errors that can occur during an echo round.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Display)]
 pub enum EchoError {
-    /// Cannot deserialize the message.
+    /// Cannot deserialize the message: {0}.
     CannotDeserialize(String),
     /// Unexpected number of broadcasts in the message.
     UnexpectedNumberOfBroadcasts,
     /// A broadcast from one of the parties is missing.
     MissingBroadcast,
-    /// The broadcasts received during the echo round
-    /// do not match the ones received previously.
+    /// One of the echoed broadcasts is not signed by its original sender,
+    /// or does not belong to this session and round.
+    #[displaydoc("An echoed broadcast is invalid")]
+    InvalidEchoedBroadcast,
+    /// The original sender of a broadcast signed different versions of it
+    /// for different parties.
+    #[displaydoc("The original sender signed conflicting broadcasts")]
     ConflictingBroadcasts,
 }
 
+/// An echo round failure along with the party that caused it.
+pub(crate) struct EchoFault {
+    pub(crate) party: PartyIdx,
+    pub(crate) error: EchoError,
+}
+
+impl EchoFault {
+    fn new(party: PartyIdx, error: EchoError) -> Self {
+        Self { party, error }
+    }
+}
+
 impl<Sig> EchoRound<Sig>
 where
     Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
 {
-    pub fn new(broadcasts: Vec<(PartyIdx, VerifiedMessage<Sig>)>) -> Self {
+    pub fn new(mut broadcasts: Vec<(PartyIdx, VerifiedMessage<Sig>)>) -> Self {
+        // Make the echo message independent of the order in which the broadcasts were received.
+        broadcasts.sort_by_key(|(idx, _)| *idx);
         Self { broadcasts }
     }
 
-    pub fn make_broadcast(&self) -> Box<[u8]> {
+    pub fn make_broadcast<F: MessageFormat>(&self) -> Box<[u8]> {
         let message = Message {
             broadcasts: self
                 .broadcasts
@@ -51,19 +88,48 @@ where
                 .map(|(idx, msg)| (idx, msg.into_unverified()))
                 .collect(),
         };
-        serialize_message(&message).unwrap()
+        F::serialize(&message).unwrap()
     }
 
-    pub fn verify_broadcast(&self, from: PartyIdx, payload: &[u8]) -> Result<(), EchoError> {
+    /// Checks the broadcasts echoed by the party `from` against the ones we received.
+    ///
+    /// On failure, returns the party at fault: either `from`, if the echo itself is malformed,
+    /// or the sender of a broadcast, if it is proven to have sent different broadcasts
+    /// to different parties.
+    pub fn verify_broadcast<F: MessageFormat, Verifier: PrehashVerifier<Sig>>(
+        &self,
+        from: PartyIdx,
+        payload: &[u8],
+        verifiers: &[Verifier],
+    ) -> Result<(), EchoFault> {
         // TODO (#68): check that the direct payload is empty?
-        let message: Message<Sig> = deserialize_message(payload)
-            .map_err(|err| EchoError::CannotDeserialize(err.to_string()))?;
+        let message: Message<Sig> = deserialize_canonical::<F, _>(payload)
+            .map_err(|err| EchoFault::new(from, EchoError::CannotDeserialize(err.to_string())))?;
 
-        // TODO (#68): check that there are no repeating indices, and the indices are in range.
+        // The broadcasts are echoed in the order of the party indices,
+        // so that the echo of the same broadcasts has only one valid encoding.
+        if message
+            .broadcasts
+            .windows(2)
+            .any(|pair| pair[0].0 >= pair[1].0)
+        {
+            return Err(EchoFault::new(
+                from,
+                EchoError::CannotDeserialize(
+                    "The broadcasts are not sorted by the party index".into(),
+                ),
+            ));
+        }
+
+        let num_broadcasts = message.broadcasts.len();
         let bc_map = message.broadcasts.into_iter().collect::<BTreeMap<_, _>>();
 
-        if bc_map.len() != self.broadcasts.len() {
-            return Err(EchoError::UnexpectedNumberOfBroadcasts);
+        // Catches the repeating indices as well.
+        if bc_map.len() != self.broadcasts.len() || num_broadcasts != self.broadcasts.len() {
+            return Err(EchoFault::new(
+                from,
+                EchoError::UnexpectedNumberOfBroadcasts,
+            ));
         }
 
         for (idx, broadcast) in self.broadcasts.iter() {
@@ -73,11 +139,26 @@ where
                 continue;
             }
 
-            let echoed_bc = bc_map.get(idx).ok_or(EchoError::MissingBroadcast)?;
+            let echoed_bc = bc_map
+                .get(idx)
+                .ok_or(EchoFault::new(from, EchoError::MissingBroadcast))?;
 
-            if !broadcast.as_unverified().is_same_as(echoed_bc) {
-                return Err(EchoError::ConflictingBroadcasts);
+            let broadcast = broadcast.as_unverified();
+            if broadcast.is_same_as(echoed_bc) {
+                continue;
             }
+
+            // Our own copy has been verified already, so if the echoed one has the same metadata
+            // and a valid signature, the original sender must have signed two different payloads.
+            // TODO (#43): attach both messages as the evidence.
+            let same_metadata = broadcast.header() == echoed_bc.header();
+            let signed_by_sender = echoed_bc.clone().verify(&verifiers[idx.as_usize()]).is_ok();
+
+            return if same_metadata && signed_by_sender {
+                Err(EchoFault::new(*idx, EchoError::ConflictingBroadcasts))
+            } else {
+                Err(EchoFault::new(from, EchoError::InvalidEchoedBroadcast))
+            };
         }
 
         Ok(())
@@ -103,12 +184,6 @@ impl EchoAccum {
             .collect()
     }
 
-    pub fn contains(&self, party_idx: PartyIdx) -> bool {
-        self.received_echo_from
-            .contains(party_idx.as_usize())
-            .unwrap()
-    }
-
     pub fn add_echo_received(&mut self, from: PartyIdx) -> Option<()> {
         self.received_echo_from.insert(from.as_usize(), ())
     }
@@ -125,3 +200,87 @@ impl EchoAccum {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand_core::OsRng;
+
+    use super::{EchoError, EchoRound};
+    use crate::curve::{BackendSignature as Signature, SigningKey, VerifyingKey};
+    use crate::rounds::PartyIdx;
+    use crate::sessions::format::{Bincode, MessageFormat};
+    use crate::sessions::signed_message::{
+        MessageHeader, MessageType, MessageVersion, SessionId, VerifiedMessage,
+    };
+
+    fn broadcast(signer: &SigningKey, sender: usize, payload: &[u8]) -> VerifiedMessage<Signature> {
+        let header = MessageHeader {
+            version: MessageVersion::CURRENT.as_u8(),
+            session_id: SessionId::new::<VerifyingKey>(b"session", "Test", &[], 0).unwrap(),
+            protocol: "Test".into(),
+            round: 1,
+            message_type: MessageType::Broadcast,
+            sender: PartyIdx::from_usize(sender),
+            recipient: None,
+            format: Bincode::ID,
+            encrypted: false,
+        };
+        VerifiedMessage::new(&mut OsRng, signer, header, payload).unwrap()
+    }
+
+    #[test]
+    fn identify_faulty_party() {
+        let signers = (0..3)
+            .map(|_| SigningKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let verifiers = signers
+            .iter()
+            .map(|signer| *signer.verifying_key())
+            .collect::<Vec<VerifyingKey>>();
+        let idx = PartyIdx::from_usize;
+
+        // Party 0 receives the broadcasts from parties 1 and 2.
+        let echo_round = EchoRound::new(alloc::vec![
+            (idx(1), broadcast(&signers[1], 1, b"1")),
+            (idx(2), broadcast(&signers[2], 2, b"2")),
+        ]);
+
+        // Party 2 signed a different broadcast for party 1.
+        let echo = EchoRound::new(alloc::vec![
+            (idx(0), broadcast(&signers[0], 0, b"0")),
+            (idx(2), broadcast(&signers[2], 2, b"2'")),
+        ])
+        .make_broadcast::<Bincode>();
+        let fault = echo_round
+            .verify_broadcast::<Bincode, _>(idx(1), &echo, &verifiers)
+            .err()
+            .unwrap();
+        assert_eq!(fault.party, idx(2));
+        assert!(matches!(fault.error, EchoError::ConflictingBroadcasts));
+
+        // Party 1 forged a broadcast from party 2.
+        let echo = EchoRound::new(alloc::vec![
+            (idx(0), broadcast(&signers[0], 0, b"0")),
+            (idx(2), broadcast(&signers[1], 2, b"2'")),
+        ])
+        .make_broadcast::<Bincode>();
+        let fault = echo_round
+            .verify_broadcast::<Bincode, _>(idx(1), &echo, &verifiers)
+            .err()
+            .unwrap();
+        assert_eq!(fault.party, idx(1));
+        assert!(matches!(fault.error, EchoError::InvalidEchoedBroadcast));
+
+        // Consistent broadcasts pass.
+        let echo = EchoRound::new(alloc::vec![
+            (idx(0), broadcast(&signers[0], 0, b"0")),
+            (idx(2), broadcast(&signers[2], 2, b"2")),
+        ])
+        .make_broadcast::<Bincode>();
+        assert!(echo_round
+            .verify_broadcast::<Bincode, _>(idx(1), &echo, &verifiers)
+            .is_ok());
+    }
+}
@@ -0,0 +1,274 @@
+//! End-to-end encryption of the direct messages, for the transports that relay them
+//! through parties that should not learn their contents.
+//!
+//! The payload is encrypted to the recipient's secp256k1 encryption key
+//! with ECDH, HKDF-SHA256 and ChaCha20-Poly1305, with the header of the message as the associated data.
+//! The ciphertext is then signed as the payload of the message, so the signature can be checked
+//! (e.g. by a relay) without decrypting it.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "reuse-transport-keys")]
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+
+use super::error::LocalError;
+use super::signed_message::MessageHeader;
+use crate::curve::{Point, Scalar, SigningKey, VerifyingKey};
+use crate::tools::ecies::{self, ECIES_OVERHEAD};
+
+/// The number of bytes the encryption adds to the payload of each direct message.
+///
+/// The message size bounds given by [`ProtocolInfo`](crate::ProtocolInfo) do not include it.
+pub const DIRECT_MESSAGE_ENCRYPTION_OVERHEAD: usize = ECIES_OVERHEAD;
+
+/// The keys for the end-to-end encryption of the direct messages of a session
+/// (see [`Session::with_direct_message_encryption`](crate::Session::with_direct_message_encryption)).
+///
+/// The encryption keys of the other parties must be obtained over an authenticated channel
+/// (e.g. published along with their verifiers).
+/// They should be separate from the keys the parties sign their messages with.
+pub struct DirectMessageEncryption<Verifier> {
+    decryption_key: SigningKey,
+    encryption_keys: BTreeMap<Verifier, VerifyingKey>,
+}
+
+impl<Verifier: Ord> DirectMessageEncryption<Verifier> {
+    /// Creates the keys from the local decryption key and the encryption keys of the parties,
+    /// addressed by their verifiers.
+    pub fn new(
+        decryption_key: SigningKey,
+        encryption_keys: BTreeMap<Verifier, VerifyingKey>,
+    ) -> Self {
+        Self {
+            decryption_key,
+            encryption_keys,
+        }
+    }
+}
+
+#[cfg(feature = "reuse-transport-keys")]
+impl DirectMessageEncryption<VerifyingKey> {
+    /// Uses the ECDSA transport keys of the parties (that is, the signer and the verifiers
+    /// of the session, on the curve of the shared keys) as the decryption and the encryption keys respectively,
+    /// so that no other keys have to be distributed.
+    ///
+    /// **Warning:** this reuses the signing keys for encryption.
+    /// The two schemes are not proven secure when sharing a key,
+    /// so this should only be used when distributing separate encryption keys is not possible.
+    pub fn from_transport_keys(signer: &SigningKey, verifiers: &BTreeSet<VerifyingKey>) -> Self {
+        Self::new(
+            signer.clone(),
+            verifiers
+                .iter()
+                .map(|verifier| (*verifier, *verifier))
+                .collect(),
+        )
+    }
+}
+
+/// The encryption keys of a session, indexed by the party.
+pub(crate) struct EncryptionKeys {
+    decryption_key: SigningKey,
+    encryption_keys: Vec<Point>,
+}
+
+impl EncryptionKeys {
+    /// Assigns the encryption keys to the parties with the given verifiers
+    /// (in the order of their indices).
+    pub(crate) fn new<Verifier: Ord + core::fmt::Debug>(
+        encryption: DirectMessageEncryption<Verifier>,
+        verifiers: &[Verifier],
+        own_verifier: &Verifier,
+    ) -> Result<Self, LocalError> {
+        let encryption_keys = verifiers
+            .iter()
+            .map(|verifier| {
+                encryption
+                    .encryption_keys
+                    .get(verifier)
+                    .map(Point::from_verifying_key)
+                    .ok_or_else(|| {
+                        LocalError(format!("No encryption key is given for {verifier:?}"))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let own_key = encryption.encryption_keys.get(own_verifier);
+        if own_key != Some(encryption.decryption_key.verifying_key()) {
+            return Err(LocalError(
+                "The decryption key does not match the encryption key of this party".into(),
+            ));
+        }
+
+        Ok(Self {
+            decryption_key: encryption.decryption_key,
+            encryption_keys,
+        })
+    }
+
+    /// Encrypts the payload of a direct message with the given header for its recipient.
+    pub(crate) fn encrypt(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        header: &MessageHeader,
+        payload: &[u8],
+    ) -> Result<Box<[u8]>, LocalError> {
+        let recipient = header
+            .recipient
+            .ok_or_else(|| LocalError("Only the direct messages can be encrypted".into()))?;
+        let recipient_key = self
+            .encryption_keys
+            .get(recipient.as_usize())
+            .ok_or_else(|| LocalError(format!("No encryption key for {recipient:?}")))?;
+        Ok(ecies::encrypt(
+            rng,
+            b"DirectMessageEncryption",
+            recipient_key,
+            header,
+            payload,
+        ))
+    }
+
+    /// Decrypts the payload of a direct message with the given header sent to this party.
+    pub(crate) fn decrypt(
+        &self,
+        header: &MessageHeader,
+        ciphertext: &[u8],
+    ) -> Result<Box<[u8]>, String> {
+        let decryption_key = Scalar::from(self.decryption_key.as_nonzero_scalar());
+        ecies::decrypt(
+            b"DirectMessageEncryption",
+            &decryption_key,
+            header,
+            ciphertext,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    use rand_core::OsRng;
+
+    use super::{DirectMessageEncryption, EncryptionKeys};
+    use crate::curve::{SigningKey, VerifyingKey};
+    use crate::rounds::PartyIdx;
+    use crate::sessions::format::{Bincode, MessageFormat};
+    use crate::sessions::signed_message::{MessageHeader, MessageType, MessageVersion, SessionId};
+
+    fn header(recipient: usize) -> MessageHeader {
+        MessageHeader {
+            version: MessageVersion::CURRENT.as_u8(),
+            session_id: SessionId::new::<VerifyingKey>(b"session", "Test", &[], 0).unwrap(),
+            protocol: "Test".into(),
+            round: 1,
+            message_type: MessageType::Direct,
+            sender: PartyIdx::from_usize(0),
+            recipient: Some(PartyIdx::from_usize(recipient)),
+            format: Bincode::ID,
+            encrypted: true,
+        }
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let signers = (0..3)
+            .map(|_| SigningKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let decryption_keys = (0..3)
+            .map(|_| SigningKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let encryption_keys = signers
+            .iter()
+            .zip(decryption_keys.iter())
+            .map(|(signer, key)| (*signer.verifying_key(), *key.verifying_key()))
+            .collect::<BTreeMap<_, _>>();
+        let ordered_verifiers = encryption_keys.keys().cloned().collect::<Vec<_>>();
+        let keys = signers
+            .iter()
+            .zip(decryption_keys)
+            .map(|(signer, key)| {
+                EncryptionKeys::new(
+                    DirectMessageEncryption::new(key, encryption_keys.clone()),
+                    &ordered_verifiers,
+                    signer.verifying_key(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let idx = |signer: &SigningKey| {
+            ordered_verifiers
+                .iter()
+                .position(|verifier| verifier == signer.verifying_key())
+                .unwrap()
+        };
+        let (recipient, other) = (idx(&signers[1]), idx(&signers[2]));
+
+        let payload = b"a direct message payload";
+        let ciphertext = keys[0]
+            .encrypt(&mut OsRng, &header(recipient), payload)
+            .unwrap();
+        assert_ne!(&ciphertext[33..ciphertext.len() - 16], payload);
+        assert_eq!(
+            &*keys[1].decrypt(&header(recipient), &ciphertext).unwrap(),
+            payload
+        );
+
+        // Another party cannot decrypt it
+        assert!(keys[2].decrypt(&header(recipient), &ciphertext).is_err());
+
+        // The ciphertext is bound to the header
+        assert!(keys[1].decrypt(&header(other), &ciphertext).is_err());
+
+        // A modified ciphertext is rejected
+        let mut modified = ciphertext.clone();
+        modified[40] ^= 1;
+        assert!(keys[1].decrypt(&header(recipient), &modified).is_err());
+    }
+
+    #[cfg(feature = "reuse-transport-keys")]
+    #[test]
+    fn transport_keys() {
+        use alloc::collections::BTreeSet;
+
+        let signers = (0..2)
+            .map(|_| SigningKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let verifiers = signers
+            .iter()
+            .map(|signer| *signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let ordered_verifiers = verifiers.iter().cloned().collect::<Vec<_>>();
+        let keys = signers
+            .iter()
+            .map(|signer| {
+                EncryptionKeys::new(
+                    DirectMessageEncryption::from_transport_keys(signer, &verifiers),
+                    &ordered_verifiers,
+                    signer.verifying_key(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let recipient = ordered_verifiers
+            .iter()
+            .position(|verifier| verifier == signers[1].verifying_key())
+            .unwrap();
+
+        let payload = b"a direct message payload";
+        let ciphertext = keys[0]
+            .encrypt(&mut OsRng, &header(recipient), payload)
+            .unwrap();
+        assert_eq!(
+            &*keys[1].decrypt(&header(recipient), &ciphertext).unwrap(),
+            payload
+        );
+    }
+}
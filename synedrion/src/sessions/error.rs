@@ -1,9 +1,43 @@
-use alloc::string::String;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
 use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
 
+use super::combined_message::EquivocationEvidence;
 use super::echo::EchoError;
+use super::signed_message::SessionId;
+use super::type_erased::serialize_message;
 use crate::rounds::ProtocolResult;
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+
+/// The protocol and the round of the session in which an error occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The name of the protocol (see [`ProtocolResult::NAME`]).
+    pub protocol: &'static str,
+    /// The round number.
+    pub round: u8,
+    /// Whether the error occurred in the echo round following `round`.
+    pub is_echo_round: bool,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, round {}", self.protocol, self.round)?;
+        if self.is_echo_round {
+            write!(f, " (echo)")?;
+        }
+        Ok(())
+    }
+}
 
 /// Possible errors returned by session methods.
 #[derive(Clone, Debug)]
@@ -14,14 +48,18 @@ pub enum Error<Res: ProtocolResult, Verifier> {
     // TODO (#43): attach the party's messages up to this round
     // for this to be verifiable by a third party
     Provable {
-        /// The index of the failed party.
+        /// The failed party.
         party: Verifier,
+        /// The round in which the fault was detected.
+        context: ErrorContext,
         /// The error that occurred.
         error: ProvableError<Res>,
     },
     /// An error occurred, but the fault of a specific party cannot be immediately proven.
     /// This structure instead proves that this party performed its calculations correctly.
     Proof {
+        /// The round which failed to finalize.
+        context: ErrorContext,
         // TODO (#43): attach all received messages from other parties.
         // What else do we need to verify it?
         /// The proof of correctness.
@@ -32,6 +70,96 @@ pub enum Error<Res: ProtocolResult, Verifier> {
     /// This error may be eventually provable if there are some external guarantees
     /// provided by the communication channel.
     Remote(RemoteError<Verifier>),
+    /// Some parties did not send their messages in time, and the round cannot be finalized without them.
+    ///
+    /// This error is only returned by [`Session::finalize_with_absent`](crate::Session::finalize_with_absent),
+    /// and is unprovable, since the parties may have been unable to deliver their messages
+    /// because of the communication channel.
+    Unresponsive {
+        /// The parties that did not respond.
+        parties: Vec<Verifier>,
+        /// The round which failed to finalize.
+        context: ErrorContext,
+    },
+}
+
+impl<Res: ProtocolResult, Verifier> Error<Res, Verifier> {
+    /// Returns the round in which the error occurred,
+    /// if it was caused by (or blamed on) the protocol execution.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Local(_) => None,
+            Self::Provable { context, .. } => Some(context),
+            Self::Proof { context, .. } => Some(context),
+            Self::Remote(error) => Some(&error.context),
+            Self::Unresponsive { context, .. } => Some(context),
+        }
+    }
+
+    /// Returns the party the error is blamed on, if any.
+    ///
+    /// For [`Error::Unresponsive`] returns `None`, since the error may be blamed on several parties.
+    pub fn party(&self) -> Option<&Verifier> {
+        match self {
+            Self::Local(_) | Self::Proof { .. } | Self::Unresponsive { .. } => None,
+            Self::Provable { party, .. } => Some(party),
+            Self::Remote(error) => Some(&error.party),
+        }
+    }
+
+    /// Returns all the parties the error is blamed on:
+    /// the one returned by [`party`](Self::party), or the parties of [`Error::Unresponsive`].
+    ///
+    /// These can be excluded from the next attempt, e.g. with
+    /// [`ThresholdKeyShare::retry_quorum`](crate::ThresholdKeyShare::retry_quorum).
+    pub fn blamed_parties(&self) -> Vec<Verifier>
+    where
+        Verifier: Clone,
+    {
+        match self {
+            Self::Unresponsive { parties, .. } => parties.clone(),
+            _ => self.party().cloned().into_iter().collect(),
+        }
+    }
+}
+
+impl<Res: ProtocolResult, Verifier: fmt::Debug> fmt::Display for Error<Res, Verifier> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(error) => write!(f, "{error}"),
+            Self::Provable {
+                party,
+                context,
+                error,
+            } => write!(f, "Provable fault of {party:?} in {context}: {error}"),
+            Self::Proof { context, .. } => write!(
+                f,
+                "Failed to finalize {context}, the party at fault is not known"
+            ),
+            Self::Remote(error) => write!(f, "{error}"),
+            Self::Unresponsive { parties, context } => {
+                write!(
+                    f,
+                    "Failed to finalize {context}, unresponsive parties: {parties:?}"
+                )
+            }
+        }
+    }
+}
+
+impl<Res, Verifier> core::error::Error for Error<Res, Verifier>
+where
+    Res: ProtocolResult + 'static,
+    Verifier: fmt::Debug + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Local(error) => Some(error),
+            Self::Provable { error, .. } => Some(error),
+            Self::Proof { .. } | Self::Unresponsive { .. } => None,
+            Self::Remote(error) => Some(error),
+        }
+    }
 }
 
 /// An error on this party's side.
@@ -40,29 +168,64 @@ pub enum Error<Res: ProtocolResult, Verifier> {
 #[displaydoc("Local error: {0}")]
 pub struct LocalError(pub(crate) String);
 
+impl core::error::Error for LocalError {}
+
 /// An unprovable fault of another party.
-#[derive(Clone, Debug, Display)]
+#[derive(Clone, Debug)]
 pub struct RemoteError<Verifier> {
     /// The offending party.
     pub party: Verifier,
+    /// The round in which the fault was detected.
+    pub context: ErrorContext,
     /// The error type
     pub error: RemoteErrorEnum,
 }
 
+impl<Verifier: fmt::Debug> fmt::Display for RemoteError<Verifier> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unprovable fault of {:?} in {}: {}",
+            self.party, self.context, self.error
+        )
+    }
+}
+
+impl<Verifier: fmt::Debug> core::error::Error for RemoteError<Verifier> {}
+
 /// Types of unprovable faults of another party.
 #[derive(Clone, Debug, Display)]
 pub enum RemoteErrorEnum {
+    /// The message has the version {0} of the message format, which is not supported by this library.
+    UnsupportedVersion(u8),
     /// Session ID does not match the one provided to the local session constructor.
     UnexpectedSessionId,
+    /// The message payload is serialized in a format different from the one of the local session.
+    UnexpectedFormat,
+    /// The message belongs to a protocol different from the one of the local session.
+    UnexpectedProtocol,
+    /// The sender named in the message header is not the party the message was received from.
+    UnexpectedSender,
+    /// The message is a direct message intended for another party,
+    /// or a broadcast or echo message with a recipient.
+    UnexpectedRecipient,
+    /// The direct message is not encrypted while the local session encrypts the direct messages,
+    /// or the other way around, or a broadcast or echo message is marked as encrypted.
+    UnexpectedEncryption,
+    /// The direct message cannot be decrypted: {0}.
+    CannotDecrypt(String),
     /// A message is intended for an unexpected round (not the current one or the next one).
     OutOfOrderMessage,
-    /// A message from this party has already been received.
+    /// A message from this party has already been received,
+    /// and the new one differs from it, but does not prove an equivocation.
     DuplicateMessage,
     /// The message signature does not match its contents: {0}.
     InvalidSignature(String),
     /// The message has invalid contents, but the fault is unprovable: {0}.
     // (e.g. correctly signed messages belonging to a different session, possibly a replay attack)
     InvalidContents(String),
+    /// The party has cancelled the session: {0}.
+    Cancelled(String),
 }
 
 /// A provable fault of another party.
@@ -74,4 +237,243 @@ pub enum ProvableError<Res: ProtocolResult> {
     CannotDeserialize(String),
     /// Echo round failed.
     Echo(EchoError),
+    /// The party sent two different messages in the same round.
+    Equivocation(EquivocationEvidence),
+}
+
+impl<Res: ProtocolResult> fmt::Display for ProvableError<Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Protocol(error) => write!(f, "Protocol error: {error:?}"),
+            Self::CannotDeserialize(error) => {
+                write!(f, "Failed to deserialize the message: {error}")
+            }
+            Self::Echo(error) => write!(f, "Echo round failed: {error}"),
+            Self::Equivocation(_) => write!(f, "Sent conflicting messages in the same round"),
+        }
+    }
+}
+
+impl<Res: ProtocolResult + 'static> core::error::Error for ProvableError<Res> {}
+
+/// A report of a provable fault of a party in a session,
+/// which the other parties of the session can endorse (see [`AbortCertificate`]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbortReport<Verifier> {
+    /// The ID of the session in which the fault occurred.
+    pub session_id: SessionId,
+    /// The party at fault.
+    pub accused: Verifier,
+    /// The name of the protocol (see [`ProtocolResult::NAME`]).
+    pub protocol: String,
+    /// The round number.
+    pub round: u8,
+    /// Whether the fault occurred in the echo round following `round`.
+    pub is_echo_round: bool,
+    /// The description of the fault.
+    pub reason: String,
+}
+
+impl<Verifier: Clone> AbortReport<Verifier> {
+    /// Creates a report from an error returned by the session with the given ID.
+    ///
+    /// Returns `None` if the error is not an [`Error::Provable`].
+    pub fn new<Res: ProtocolResult>(
+        session_id: &SessionId,
+        error: &Error<Res, Verifier>,
+    ) -> Option<Self> {
+        match error {
+            Error::Provable {
+                party,
+                context,
+                error,
+            } => Some(Self {
+                session_id: *session_id,
+                accused: party.clone(),
+                protocol: context.protocol.into(),
+                round: context.round,
+                is_echo_round: context.is_echo_round,
+                reason: error.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<Verifier: Serialize> AbortReport<Verifier> {
+    fn hash(&self) -> Result<HashOutput, LocalError> {
+        let accused = serialize_message(&self.accused)?;
+        Ok(Hash::new_with_dst(b"AbortReport")
+            .chain(&self.session_id)
+            .chain(&accused)
+            .chain(&self.protocol.as_bytes())
+            .chain(&self.round)
+            .chain(&self.is_echo_round)
+            .chain(&self.reason.as_bytes())
+            .finalize())
+    }
+}
+
+/// Errors returned by [`AbortCertificate::verify`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum AbortCertificateError {
+    /// The report cannot be serialized.
+    CannotSerialize,
+    /// One of the endorsers is not a party of the session.
+    UnknownEndorser,
+    /// The accused party is among the endorsers.
+    EndorsedByAccused,
+    /// One of the endorsement signatures is invalid.
+    InvalidSignature,
+    /// The certificate has {found} endorsements, but {required} are required.
+    NotEnoughEndorsements {
+        /// The required number of endorsements.
+        required: usize,
+        /// The number of endorsements in the certificate.
+        found: usize,
+    },
+}
+
+impl core::error::Error for AbortCertificateError {}
+
+/// An [`AbortReport`] signed by the parties that agree with it.
+///
+/// The party that detected the fault creates the certificate with [`new`](Self::new),
+/// and sends it to the other parties, which [`endorse`](Self::endorse) it
+/// if they have detected the same fault in their own sessions.
+/// The endorsements collected by different parties can be combined with [`merge`](Self::merge).
+/// Anyone knowing the parties of the session can then check with [`verify`](Self::verify)
+/// that the report is endorsed by a quorum of them.
+///
+/// Note that the signatures only attest that the endorsers agree with the report:
+/// the messages proving the fault are not attached to the errors yet,
+/// so a party can only endorse a fault it has observed itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Sig: Serialize, Verifier: Serialize",
+    deserialize = "Sig: for<'x> Deserialize<'x>, Verifier: Ord + for<'x> Deserialize<'x>"
+))]
+pub struct AbortCertificate<Sig, Verifier> {
+    report: AbortReport<Verifier>,
+    endorsements: BTreeMap<Verifier, Sig>,
+}
+
+impl<Sig, Verifier> AbortCertificate<Sig, Verifier>
+where
+    Verifier: Clone + Ord + Serialize,
+{
+    /// Creates a certificate for the report, endorsed by `signer`.
+    pub fn new<Signer>(
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+        report: AbortReport<Verifier>,
+    ) -> Result<Self, LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        let mut certificate = Self {
+            report,
+            endorsements: BTreeMap::new(),
+        };
+        certificate.add_endorsement(rng, signer)?;
+        Ok(certificate)
+    }
+
+    /// The report being endorsed.
+    pub fn report(&self) -> &AbortReport<Verifier> {
+        &self.report
+    }
+
+    /// The parties that have endorsed the report.
+    pub fn endorsers(&self) -> Vec<Verifier> {
+        self.endorsements.keys().cloned().collect()
+    }
+
+    /// Adds the endorsement of `signer`, whose own report of the fault is `own_report`.
+    ///
+    /// Fails if the reports do not match.
+    pub fn endorse<Signer>(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+        own_report: &AbortReport<Verifier>,
+    ) -> Result<(), LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        if own_report != &self.report {
+            return Err(LocalError(
+                "The report does not match the one being endorsed".into(),
+            ));
+        }
+        self.add_endorsement(rng, signer)
+    }
+
+    /// Adds the endorsements from another certificate for the same report.
+    ///
+    /// The endorsements are not verified.
+    pub fn merge(&mut self, other: Self) -> Result<(), LocalError> {
+        if other.report != self.report {
+            return Err(LocalError(
+                "Cannot merge the certificates for different reports".into(),
+            ));
+        }
+        self.endorsements.extend(other.endorsements);
+        Ok(())
+    }
+
+    /// Checks that the report is endorsed by at least `quorum` of `verifiers` (the parties of the session),
+    /// excluding the accused party.
+    pub fn verify(
+        &self,
+        verifiers: &BTreeSet<Verifier>,
+        quorum: usize,
+    ) -> Result<(), AbortCertificateError>
+    where
+        Verifier: PrehashVerifier<Sig>,
+    {
+        let hash = self
+            .report
+            .hash()
+            .map_err(|_| AbortCertificateError::CannotSerialize)?;
+        for (endorser, signature) in self.endorsements.iter() {
+            if !verifiers.contains(endorser) {
+                return Err(AbortCertificateError::UnknownEndorser);
+            }
+            if endorser == &self.report.accused {
+                return Err(AbortCertificateError::EndorsedByAccused);
+            }
+            endorser
+                .verify_prehash(hash.as_ref(), signature)
+                .map_err(|_| AbortCertificateError::InvalidSignature)?;
+        }
+        if self.endorsements.len() < quorum {
+            return Err(AbortCertificateError::NotEnoughEndorsements {
+                required: quorum,
+                found: self.endorsements.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn add_endorsement<Signer>(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        signer: &Signer,
+    ) -> Result<(), LocalError>
+    where
+        Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    {
+        let endorser = signer.verifying_key();
+        if endorser == self.report.accused {
+            return Err(LocalError(
+                "The accused party cannot endorse the report".into(),
+            ));
+        }
+        let signature = signer
+            .sign_prehash_with_rng(rng, self.report.hash()?.as_ref())
+            .map_err(|err| LocalError(err.to_string()))?;
+        self.endorsements.insert(endorser, signature);
+        Ok(())
+    }
 }
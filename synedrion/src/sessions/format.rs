@@ -0,0 +1,323 @@
+//! Serialization formats of the message payloads.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+#[cfg(feature = "cbor")]
+use alloc::vec;
+use alloc::vec::Vec;
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use super::error::LocalError;
+
+/// A serialization format of the payloads of the messages created by a [`Session`](crate::Session).
+///
+/// The identifier of the format is signed along with each message,
+/// so a payload cannot be reinterpreted in a different format.
+/// All the parties of a session must use the same format.
+///
+/// Note that [`CombinedMessage`](crate::CombinedMessage) itself is serialized by the user,
+/// and can use any format regardless of the one used for the payloads.
+pub trait MessageFormat: 'static + Send + Sync {
+    /// The identifier of the format.
+    ///
+    /// Must be different for all the formats used by the same parties.
+    const ID: u8;
+
+    /// Serializes a value.
+    fn serialize<T: Serialize>(value: &T) -> Result<Box<[u8]>, LocalError>;
+
+    /// Deserializes a value, returning the description of the error on failure.
+    fn deserialize<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, String>;
+}
+
+/// Deserializes a value, checking that `bytes` is its canonical encoding in the format `F`,
+/// that is, the one produced by serializing the value again.
+///
+/// Thus the signed payloads and the values they contain determine each other unambiguously,
+/// which keeps the hashes of the payloads (and the evidence based on them) well-defined.
+pub(crate) fn deserialize_canonical<F, T>(bytes: &[u8]) -> Result<T, String>
+where
+    F: MessageFormat,
+    T: Serialize + for<'de> Deserialize<'de>,
+{
+    let value = F::deserialize::<T>(bytes)?;
+    let reserialized = F::serialize(&value).map_err(|err| err.0)?;
+    if &*reserialized != bytes {
+        return Err("The value is not encoded canonically".into());
+    }
+    Ok(value)
+}
+
+/// The [`bincode`](https://docs.rs/bincode/1) format with the default options.
+///
+/// This is the format used by the sessions unless another one is specified,
+/// and the one the message size bounds in [`ProtocolInfo`](crate::ProtocolInfo) are given for.
+#[derive(Debug, Clone, Copy)]
+pub struct Bincode;
+
+impl MessageFormat for Bincode {
+    const ID: u8 = 0;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Box<[u8]>, LocalError> {
+        bincode::serialize(value)
+            .map(|serialized| serialized.into_boxed_slice())
+            .map_err(|err| LocalError(format!("Failed to serialize: {err:?}")))
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, String> {
+        bincode::deserialize(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// A compact variant of the [`bincode`](https://docs.rs/bincode/1) format for the links
+/// where the size of the messages matters.
+///
+/// The integers (including the lengths of the sequences) are encoded as varints,
+/// and then the runs of zero bytes (e.g. the leading zeros of the Paillier values
+/// that do not take the whole size of their integer type) are replaced with their lengths:
+/// a zero byte is followed by the number of zeros in the run (1 to 255).
+///
+/// The size reduction depends on the scheme parameters: the values indistinguishable
+/// from random (e.g. the Paillier ciphertexts) cannot be compressed, so the gain is negligible
+/// when the Paillier moduli fill their integer types (as for [`ProductionParams`](crate::ProductionParams)),
+/// and about 15% for [`TestParams`](crate::TestParams).
+/// The message size bounds in [`ProtocolInfo`](crate::ProtocolInfo) only hold for [`Bincode`].
+#[derive(Debug, Clone, Copy)]
+pub struct Compact;
+
+impl MessageFormat for Compact {
+    const ID: u8 = 2;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Box<[u8]>, LocalError> {
+        let serialized = bincode::DefaultOptions::new()
+            .serialize(value)
+            .map_err(|err| LocalError(format!("Failed to serialize: {err:?}")))?;
+        Ok(compress_zero_runs(&serialized))
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, String> {
+        let decompressed = decompress_zero_runs(bytes)?;
+        bincode::DefaultOptions::new()
+            .deserialize(&decompressed)
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Replaces each run of zero bytes with a zero byte followed by the length of the run,
+/// splitting the runs longer than 255 bytes.
+fn compress_zero_runs(bytes: &[u8]) -> Box<[u8]> {
+    let mut compressed = Vec::with_capacity(bytes.len());
+    let mut zeros = 0u8;
+    for byte in bytes {
+        if *byte == 0 {
+            if zeros == u8::MAX {
+                compressed.extend([0, zeros]);
+                zeros = 0;
+            }
+            zeros += 1;
+        } else {
+            if zeros > 0 {
+                compressed.extend([0, zeros]);
+                zeros = 0;
+            }
+            compressed.push(*byte);
+        }
+    }
+    if zeros > 0 {
+        compressed.extend([0, zeros]);
+    }
+    compressed.into()
+}
+
+/// The inverse of [`compress_zero_runs`].
+///
+/// Does not check that the runs are maximal; the canonicity of the encoding
+/// is checked by [`deserialize_canonical`] instead.
+fn decompress_zero_runs(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decompressed = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(byte) = iter.next() {
+        if *byte == 0 {
+            let zeros = iter
+                .next()
+                .ok_or_else(|| "The input ends in the middle of a run of zeros".to_string())?;
+            if *zeros == 0 {
+                return Err("A run of zeros cannot be empty".into());
+            }
+            decompressed.resize(decompressed.len() + *zeros as usize, 0);
+        } else {
+            decompressed.push(*byte);
+        }
+    }
+    Ok(decompressed)
+}
+
+/// The [CBOR](https://www.rfc-editor.org/rfc/rfc8949) format,
+/// as implemented by [`ciborium`](https://docs.rs/ciborium).
+///
+/// Structs are encoded as maps with the field names as keys,
+/// which makes the messages easier to decode for the implementations in other languages
+/// at the cost of a larger size.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl MessageFormat for Cbor {
+    const ID: u8 = 1;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Box<[u8]>, LocalError> {
+        let mut serialized = Vec::new();
+        ciborium::into_writer(value, &mut serialized)
+            .map_err(|err| LocalError(format!("Failed to serialize: {err:?}")))?;
+        Ok(serialized.into_boxed_slice())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, String> {
+        // `ciborium` can only deserialize the byte strings that fit into the scratch buffer,
+        // and none of them can be longer than the whole message.
+        let mut scratch = vec![0u8; bytes.len()];
+        ciborium::from_reader_with_buffer(bytes, &mut scratch).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use rand_core::OsRng;
+    use serde::{Deserialize, Serialize};
+
+    use super::{deserialize_canonical, Bincode, Compact, MessageFormat};
+    use crate::curve::{Point, Scalar};
+    use crate::uint::{Signed, U1024};
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct Message {
+        round: u8,
+        payload: Vec<u8>,
+    }
+
+    fn roundtrip<F: MessageFormat>() {
+        let message = Message {
+            round: 1,
+            payload: vec![1, 2, 3],
+        };
+        let serialized = F::serialize(&message).unwrap();
+        assert_eq!(F::deserialize::<Message>(&serialized).unwrap(), message);
+        assert!(F::deserialize::<Message>(&serialized[..serialized.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        roundtrip::<Bincode>();
+    }
+
+    #[test]
+    fn compact_roundtrip() {
+        roundtrip::<Compact>();
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_roundtrip() {
+        roundtrip::<super::Cbor>();
+    }
+
+    fn assert_canonical<F, T>(value: &T)
+    where
+        F: MessageFormat,
+        T: Serialize + for<'de> Deserialize<'de>,
+    {
+        let serialized = F::serialize(value).unwrap();
+        let deserialized = deserialize_canonical::<F, T>(&serialized).unwrap();
+        assert_eq!(F::serialize(&deserialized).unwrap(), serialized);
+    }
+
+    fn canonical_roundtrips<F: MessageFormat>() {
+        for _ in 0..32 {
+            assert_canonical::<F, _>(&Scalar::random(&mut OsRng));
+            assert_canonical::<F, _>(&Scalar::random(&mut OsRng).mul_by_generator());
+            assert_canonical::<F, _>(&Signed::<U1024>::random(&mut OsRng));
+            assert_canonical::<F, _>(&Signed::<U1024>::random_bounded_bits(&mut OsRng, 100));
+        }
+        assert_canonical::<F, _>(&Signed::<U1024>::new_positive(U1024::ZERO, 0).unwrap());
+    }
+
+    #[test]
+    fn bincode_canonical_roundtrips() {
+        canonical_roundtrips::<Bincode>();
+    }
+
+    #[test]
+    fn compact_canonical_roundtrips() {
+        canonical_roundtrips::<Compact>();
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_canonical_roundtrips() {
+        canonical_roundtrips::<super::Cbor>();
+    }
+
+    #[test]
+    fn non_canonical_encodings() {
+        let message = Message {
+            round: 1,
+            payload: vec![1, 2, 3],
+        };
+        let mut serialized = Bincode::serialize(&message).unwrap().into_vec();
+        serialized.push(0);
+        assert!(Bincode::deserialize::<Message>(&serialized).is_ok());
+        assert!(deserialize_canonical::<Bincode, Message>(&serialized).is_err());
+
+        let point = Scalar::random_nonzero(&mut OsRng).mul_by_generator();
+        let uncompressed = point.to_backend().to_affine().to_encoded_point(false);
+        let serialized = Bincode::serialize(&uncompressed.as_bytes()).unwrap();
+        assert!(Bincode::deserialize::<Point>(&serialized).is_err());
+
+        // A negative zero.
+        let serialized = Bincode::serialize(&(true, 0u32, Vec::<u8>::new())).unwrap();
+        assert!(Bincode::deserialize::<Signed<U1024>>(&serialized).is_err());
+
+        // A value with a leading zero byte not required by the bound.
+        let serialized = Bincode::serialize(&(false, 8u32, vec![0u8, 1u8])).unwrap();
+        assert!(Bincode::deserialize::<Signed<U1024>>(&serialized).is_err());
+        let serialized = Bincode::serialize(&(false, 8u32, vec![1u8])).unwrap();
+        assert!(Bincode::deserialize::<Signed<U1024>>(&serialized).is_ok());
+    }
+
+    #[test]
+    fn compact_encoding() {
+        // The lengths and the zero padding of the values take less space than with `Bincode`.
+        let value = (Signed::<U1024>::random(&mut OsRng), vec![0u8; 300], 1u64);
+        let compact = Compact::serialize(&value).unwrap();
+        let bincode = Bincode::serialize(&value).unwrap();
+        assert!(compact.len() < bincode.len() / 2);
+
+        // A run of 300 zeros is split into the runs of 255 and 45 zeros.
+        let serialized = Compact::serialize(&vec![0u8; 300]).unwrap();
+        assert_eq!(&*serialized, &[0xfb, 0x2c, 1, 0, 255, 0, 45]);
+        assert_eq!(
+            Compact::deserialize::<Vec<u8>>(&serialized).unwrap(),
+            vec![0u8; 300]
+        );
+
+        // Runs of zeros that are not maximal can be decoded, but are not canonical.
+        let non_maximal = [0xfb, 0x2c, 1, 0, 200, 0, 100];
+        assert_eq!(
+            Compact::deserialize::<Vec<u8>>(&non_maximal).unwrap(),
+            vec![0u8; 300]
+        );
+        assert!(deserialize_canonical::<Compact, Vec<u8>>(&non_maximal).is_err());
+
+        // Truncated and empty runs.
+        assert!(Compact::deserialize::<Vec<u8>>(&serialized[..serialized.len() - 1]).is_err());
+        assert!(Compact::deserialize::<u8>(&[0, 0]).is_err());
+    }
+}
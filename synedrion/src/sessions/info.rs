@@ -0,0 +1,337 @@
+//! Information about the messages of the protocols available before starting a session.
+
+use alloc::vec::Vec;
+
+use super::combined_message::max_combined_message_size;
+use super::echo::max_echo_payload_size;
+use super::type_erased::is_null_type;
+#[cfg(feature = "signing")]
+use crate::cggmp21::{
+    interactive_signing, interactive_signing::SigningOutput, presigning, schnorr_signing, signing,
+    AdaptorSigningResult, InteractiveSigningResult, PresigningMode, PresigningResult,
+    SchnorrSigningResult, SigningResult,
+};
+use crate::cggmp21::{
+    key_export, threshold_refresh, KeyExportResult, SchemeParams, ThresholdRefreshResult,
+};
+#[cfg(feature = "keygen")]
+use crate::cggmp21::{key_gen, key_import, KeyGenResult, KeyImportResult};
+#[cfg(feature = "key-refresh")]
+use crate::cggmp21::{key_refresh, KeyRefreshResult};
+#[cfg(feature = "ed25519")]
+use crate::ed25519::{signing as ed25519_signing, Ed25519SigningResult};
+use crate::rounds::{ProtocolResult, Round};
+use crate::tools::serialized_size::{MaxSerializedSize, LENGTH_PREFIX_SIZE};
+
+/// Information about the messages each party sends in a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundInfo {
+    /// The round number (as returned by [`Session::current_round`](crate::Session::current_round)).
+    pub round: u8,
+    /// Whether this is the echo round following the round `round`.
+    pub is_echo_round: bool,
+    /// The number of messages sent by each party (one to each of the other parties).
+    pub num_messages: usize,
+    /// The upper bound on the size of the broadcast payload,
+    /// or `None` if the messages of this round do not have a broadcast part.
+    ///
+    /// In echo rounds this is the payload of the echo message,
+    /// which is also the same for all the destinations.
+    pub max_broadcast_payload_size: Option<usize>,
+    /// The upper bound on the size of the direct payload,
+    /// or `None` if the messages of this round do not have a direct part.
+    pub max_direct_payload_size: Option<usize>,
+    /// The upper bound on the size of a [`CombinedMessage`](crate::CombinedMessage)
+    /// of this round serialized with [`bincode`](https://docs.rs/bincode/1).
+    pub max_message_size: usize,
+}
+
+/// Information about the rounds of a protocol
+/// for the given scheme parameters and the number of parties.
+///
+/// The bounds are given for the payloads serialized with [`Bincode`](crate::sessions::Bincode),
+/// the default [`MessageFormat`](crate::sessions::MessageFormat) of the sessions.
+///
+/// The size bounds depend on the maximum size of a signature (`max_signature_size`),
+/// which is the size of the `Sig` type serialized with [`bincode`](https://docs.rs/bincode/1)
+/// (e.g. 64 bytes for [`k256::ecdsa::Signature`]),
+/// since the echo messages include the signed broadcasts of other parties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolInfo {
+    /// The name of the protocol (see [`ProtocolResult::NAME`]).
+    pub protocol: &'static str,
+    /// The rounds of the protocol, including the echo rounds, in the order of execution.
+    pub rounds: Vec<RoundInfo>,
+}
+
+impl ProtocolInfo {
+    /// Returns the information for a session created by
+    /// [`make_key_gen_session`](crate::make_key_gen_session).
+    #[cfg(feature = "keygen")]
+    pub fn key_gen<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder = Builder::new::<KeyGenResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<key_gen::Round1<P>>();
+        builder.add_round::<key_gen::Round2<P>>();
+        builder.add_round::<key_gen::Round3<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_key_refresh_session`](crate::make_key_refresh_session).
+    #[cfg(feature = "key-refresh")]
+    pub fn key_refresh<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder =
+            Builder::new::<KeyRefreshResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<key_refresh::Round1<P>>();
+        builder.add_round::<key_refresh::Round2<P>>();
+        builder.add_round::<key_refresh::Round3<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_key_import_session`](crate::make_key_import_session).
+    #[cfg(feature = "keygen")]
+    pub fn key_import<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder = Builder::new::<KeyImportResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<key_import::Round1<P>>();
+        builder.add_round::<key_import::Round2<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_key_export_session`](crate::make_key_export_session).
+    pub fn key_export<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder = Builder::new::<KeyExportResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<key_export::Round1<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_threshold_refresh_session`](crate::make_threshold_refresh_session).
+    pub fn threshold_refresh<P: SchemeParams>(
+        num_parties: usize,
+        max_signature_size: usize,
+    ) -> Self {
+        let mut builder =
+            Builder::new::<ThresholdRefreshResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<threshold_refresh::Round1<P>>();
+        builder.add_round::<threshold_refresh::Round2<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_interactive_signing_session`](crate::make_interactive_signing_session).
+    #[cfg(feature = "signing")]
+    pub fn interactive_signing<P: SchemeParams>(
+        num_parties: usize,
+        max_signature_size: usize,
+    ) -> Self {
+        let mut builder =
+            Builder::new::<InteractiveSigningResult<P>>(num_parties, max_signature_size, None);
+        builder.add_interactive_signing_rounds::<P, InteractiveSigningResult<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_batch_interactive_signing_session`](crate::make_batch_interactive_signing_session)
+    /// with `batch_size` messages to sign.
+    #[cfg(feature = "signing")]
+    pub fn batch_interactive_signing<P: SchemeParams>(
+        num_parties: usize,
+        max_signature_size: usize,
+        batch_size: usize,
+    ) -> Self {
+        let mut builder = Builder::new::<InteractiveSigningResult<P>>(
+            num_parties,
+            max_signature_size,
+            Some(batch_size),
+        );
+        builder.add_interactive_signing_rounds::<P, InteractiveSigningResult<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_adaptor_signing_session`](crate::make_adaptor_signing_session).
+    #[cfg(feature = "signing")]
+    pub fn adaptor_signing<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder =
+            Builder::new::<AdaptorSigningResult<P>>(num_parties, max_signature_size, None);
+        builder.add_interactive_signing_rounds::<P, AdaptorSigningResult<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_presigning_session`](crate::make_presigning_session) with the given `mode`.
+    #[cfg(feature = "signing")]
+    pub fn presigning<P: SchemeParams>(
+        num_parties: usize,
+        max_signature_size: usize,
+        mode: PresigningMode,
+    ) -> Self {
+        let mut builder =
+            Builder::new::<PresigningResult<P>>(num_parties, max_signature_size, None);
+        match mode {
+            PresigningMode::Fast => {
+                builder.add_round::<presigning::Round1<P>>();
+                builder.add_round::<presigning::Round2<P>>();
+                builder.add_round::<presigning::Round3<P>>();
+            }
+            PresigningMode::IdentifiableAbort => {
+                builder.add_round::<presigning::identifiable::Round1<P>>();
+                builder.add_round::<presigning::identifiable::Round2<P>>();
+                builder.add_round::<presigning::identifiable::Round3<P>>();
+                builder.add_round::<presigning::identifiable::Round4<P>>();
+            }
+        }
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_signing_session`](crate::make_signing_session).
+    #[cfg(feature = "signing")]
+    pub fn signing<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder = Builder::new::<SigningResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<signing::Round1<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_schnorr_signing_session`](crate::make_schnorr_signing_session).
+    #[cfg(feature = "signing")]
+    pub fn schnorr_signing<P: SchemeParams>(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder =
+            Builder::new::<SchnorrSigningResult<P>>(num_parties, max_signature_size, None);
+        builder.add_round::<schnorr_signing::Round1<P>>();
+        builder.add_round::<schnorr_signing::Round2<P>>();
+        builder.add_round::<schnorr_signing::Round3<P>>();
+        builder.info
+    }
+
+    /// Returns the information for a session created by
+    /// [`make_ed25519_signing_session`](crate::make_ed25519_signing_session).
+    #[cfg(feature = "ed25519")]
+    pub fn ed25519_signing(num_parties: usize, max_signature_size: usize) -> Self {
+        let mut builder =
+            Builder::new::<Ed25519SigningResult>(num_parties, max_signature_size, None);
+        builder.add_round::<ed25519_signing::Round1>();
+        builder.add_round::<ed25519_signing::Round2>();
+        builder.info
+    }
+
+    /// Returns the information about the given round.
+    pub fn round(&self, round: u8, is_echo_round: bool) -> Option<&RoundInfo> {
+        self.rounds
+            .iter()
+            .find(|info| info.round == round && info.is_echo_round == is_echo_round)
+    }
+
+    /// Returns the position of the given round among [`rounds`](Self::rounds)
+    /// (starting from 0), e.g. to report the progress of a session
+    /// as "round `position + 1` of `rounds.len()`".
+    pub fn round_position(&self, round: u8, is_echo_round: bool) -> Option<usize> {
+        self.rounds
+            .iter()
+            .position(|info| info.round == round && info.is_echo_round == is_echo_round)
+    }
+
+    /// Returns the upper bound on the size of a message in any round of the protocol.
+    pub fn max_message_size(&self) -> usize {
+        self.rounds
+            .iter()
+            .map(|info| info.max_message_size)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+struct Builder {
+    num_parties: usize,
+    max_signature_size: usize,
+    // If set, the rounds are executed as a part of a batch of this size.
+    batch_size: Option<usize>,
+    info: ProtocolInfo,
+}
+
+impl Builder {
+    fn new<Res: ProtocolResult>(
+        num_parties: usize,
+        max_signature_size: usize,
+        batch_size: Option<usize>,
+    ) -> Self {
+        Self {
+            num_parties,
+            max_signature_size,
+            batch_size,
+            info: ProtocolInfo {
+                protocol: Res::NAME,
+                rounds: Vec::new(),
+            },
+        }
+    }
+
+    #[cfg(feature = "signing")]
+    fn add_interactive_signing_rounds<P: SchemeParams, Res: SigningOutput<P>>(&mut self) {
+        self.add_round::<interactive_signing::Round1<P, Res>>();
+        self.add_round::<interactive_signing::Round2<P, Res>>();
+        self.add_round::<interactive_signing::Round3<P, Res>>();
+        self.add_round::<interactive_signing::Round4<P, Res>>();
+    }
+
+    fn push(
+        &mut self,
+        round: u8,
+        is_echo_round: bool,
+        broadcast: Option<usize>,
+        direct: Option<usize>,
+    ) {
+        self.info.rounds.push(RoundInfo {
+            round,
+            is_echo_round,
+            num_messages: self.num_parties - 1,
+            max_broadcast_payload_size: broadcast,
+            max_direct_payload_size: direct,
+            max_message_size: max_combined_message_size(
+                self.info.protocol,
+                broadcast,
+                direct,
+                self.max_signature_size,
+            ),
+        })
+    }
+
+    fn add_round<R>(&mut self)
+    where
+        R: Round,
+        R::BroadcastMessage: MaxSerializedSize + 'static,
+        R::DirectMessage: MaxSerializedSize + 'static,
+    {
+        let broadcast = (!is_null_type::<R::BroadcastMessage>())
+            .then(|| R::BroadcastMessage::max_serialized_size(self.num_parties));
+        let direct = (!is_null_type::<R::DirectMessage>())
+            .then(|| R::DirectMessage::max_serialized_size(self.num_parties));
+
+        let (broadcast, direct) = match self.batch_size {
+            // The batched messages are vectors of the messages of each instance.
+            // Note that the direct parts are sent even if they are empty in the instances.
+            Some(batch_size) => (
+                broadcast.map(|size| LENGTH_PREFIX_SIZE + batch_size * size),
+                Some(LENGTH_PREFIX_SIZE + batch_size * direct.unwrap_or(0)),
+            ),
+            None => (broadcast, direct),
+        };
+
+        self.push(R::ROUND_NUM, false, broadcast, direct);
+
+        if R::REQUIRES_ECHO {
+            let echo = broadcast.map(|size| {
+                max_echo_payload_size(
+                    self.info.protocol,
+                    self.num_parties,
+                    size,
+                    self.max_signature_size,
+                )
+            });
+            self.push(R::ROUND_NUM, true, echo, None);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+/*!
+The round-by-round interface of the protocols, for the applications that schedule
+the message creation, verification and finalization themselves (e.g. in an actor framework).
+
+Unlike [`Session`](super::Session), a [`LowLevelRound`] does not sign, encrypt or route the messages,
+does not run the echo rounds, and identifies the parties by their indices;
+all of this is the responsibility of the application.
+*/
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+
+use super::error::{Error, ErrorContext, LocalError, ProvableError};
+use super::format::Bincode;
+use super::session::wrap_receive_result;
+use super::type_erased::{self, AccumAddError, DynFinalizable, DynRoundAccum};
+use crate::rounds::{self, PartyIdx, ProtocolResult};
+
+/// A message created by [`LowLevelRound::to_send`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowLevelMessage {
+    /// The index of the party the message is for.
+    pub destination: usize,
+    /// The broadcast part of the message, the same for all the destinations
+    /// (`None` if the round does not send broadcasts).
+    pub broadcast: Option<Box<[u8]>>,
+    /// The direct part of the message (`None` if the round does not send direct messages).
+    pub direct: Option<Box<[u8]>>,
+}
+
+/// Possible outcomes of [`LowLevelRound::finalize`].
+pub enum LowLevelOutcome<Res: ProtocolResult, F = Bincode> {
+    /// The protocol result is available.
+    Success(Res::Success),
+    /// Starting the next round.
+    AnotherRound(LowLevelRound<Res, F>),
+}
+
+/// A round of a protocol together with the messages received in it so far.
+///
+/// Created from a new session with [`Session::into_low_level`](super::Session::into_low_level).
+/// The message payloads are serialized in the format `F` of the session.
+pub struct LowLevelRound<Res: ProtocolResult, F = Bincode> {
+    round: Box<dyn DynFinalizable<Res, F>>,
+    accum: DynRoundAccum,
+    party_idx: PartyIdx,
+    num_parties: usize,
+}
+
+impl<Res: ProtocolResult, F> LowLevelRound<Res, F> {
+    pub(crate) fn new(
+        round: Box<dyn DynFinalizable<Res, F>>,
+        party_idx: PartyIdx,
+        num_parties: usize,
+    ) -> Self {
+        Self {
+            round,
+            accum: DynRoundAccum::new(),
+            party_idx,
+            num_parties,
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        ErrorContext {
+            protocol: Res::NAME,
+            round: self.round.round_num(),
+            is_echo_round: false,
+        }
+    }
+
+    /// Returns the current round number.
+    pub fn round_num(&self) -> u8 {
+        self.round.round_num()
+    }
+
+    /// Returns the index of this party.
+    pub fn party_index(&self) -> usize {
+        self.party_idx.as_usize()
+    }
+
+    /// Returns `true` if the parties must make sure they received the same broadcasts
+    /// in this round before finalizing it (e.g. by exchanging their hashes,
+    /// or by using a reliable broadcast channel).
+    ///
+    /// [`Session`](super::Session) does it with an additional echo round.
+    pub fn requires_echo(&self) -> bool {
+        self.round.requires_echo()
+    }
+
+    /// Creates the messages of this round for all their destinations.
+    ///
+    /// Must be called once per round; the data needed to finalize the round
+    /// is saved in the round itself.
+    pub fn to_send(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<LowLevelMessage>, LocalError> {
+        let broadcast = self.round.make_broadcast_message(rng)?;
+        self.round
+            .message_destinations()
+            .into_iter()
+            .map(|destination| {
+                let (direct, artifact) = self.round.make_direct_message(rng, destination)?;
+                self.accum
+                    .add_artifact(destination, artifact)
+                    .map_err(|err| match err {
+                        AccumAddError::SlotTaken => LocalError(format!(
+                            "The messages for {destination:?} were already created"
+                        )),
+                    })?;
+                Ok(LowLevelMessage {
+                    destination: destination.as_usize(),
+                    broadcast: broadcast.clone(),
+                    direct,
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies a message of this round received from the party with the index `from`
+    /// and saves the result for the finalization.
+    pub fn receive(
+        &mut self,
+        from: usize,
+        message: &LowLevelMessage,
+    ) -> Result<(), Error<Res, usize>> {
+        if from >= self.num_parties || from == self.party_idx.as_usize() {
+            return Err(Error::Local(LocalError(format!(
+                "{from} is not the index of another party"
+            ))));
+        }
+        if message.destination != self.party_idx.as_usize() {
+            return Err(Error::Local(LocalError(format!(
+                "The message is for the party {}",
+                message.destination
+            ))));
+        }
+
+        let from_idx = PartyIdx::from_usize(from);
+        let payload = wrap_receive_result(
+            &from,
+            self.error_context(),
+            self.round.verify_message(
+                from_idx,
+                message.broadcast.as_deref(),
+                message.direct.as_deref(),
+            ),
+        )?;
+        self.accum
+            .add_payload(from_idx, payload)
+            .map_err(|err| match err {
+                AccumAddError::SlotTaken => Error::Local(LocalError(format!(
+                    "A message from the party {from} was already received"
+                ))),
+            })
+    }
+
+    /// Returns `true` if the round can be finalized.
+    pub fn can_finalize(&self) -> bool {
+        self.round.can_finalize(&self.accum)
+    }
+
+    /// Returns the indices of the parties whose messages are needed to finalize the round.
+    pub fn missing_messages(&self) -> Vec<usize> {
+        self.round
+            .missing_payloads(&self.accum)
+            .into_iter()
+            .map(PartyIdx::as_usize)
+            .collect()
+    }
+
+    /// Finalizes the round, returning the protocol result or the next round.
+    pub fn finalize(
+        self,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<LowLevelOutcome<Res, F>, Error<Res, usize>> {
+        let context = self.error_context();
+        let outcome = self
+            .round
+            .finalize(rng, self.accum)
+            .map_err(|err| match err {
+                type_erased::FinalizeError::Protocol(err) => match err {
+                    rounds::FinalizeError::Init(err) => Error::Local(LocalError(format!(
+                        "Failed to initialize the protocol: {err:?}"
+                    ))),
+                    rounds::FinalizeError::Local(msg) => {
+                        Error::Local(LocalError(format!("Failed to finalize: {msg}")))
+                    }
+                    rounds::FinalizeError::Proof(proof) => Error::Proof { context, proof },
+                    rounds::FinalizeError::Provable { party, error } => Error::Provable {
+                        party: party.as_usize(),
+                        context,
+                        error: ProvableError::Protocol(error),
+                    },
+                },
+                type_erased::FinalizeError::Accumulator(err) => {
+                    Error::Local(LocalError(format!("Failed to finalize: {err:?}")))
+                }
+            })?;
+
+        Ok(match outcome {
+            type_erased::FinalizeOutcome::Success(result) => LowLevelOutcome::Success(result),
+            type_erased::FinalizeOutcome::AnotherRound(round) => {
+                LowLevelOutcome::AnotherRound(Self::new(round, self.party_idx, self.num_parties))
+            }
+        })
+    }
+}
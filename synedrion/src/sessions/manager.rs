@@ -0,0 +1,405 @@
+//! Bookkeeping for many concurrent sessions of a single party.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+
+use super::combined_message::CombinedMessage;
+use super::error::Error;
+use super::format::{Bincode, MessageFormat};
+use super::session::{
+    FinalizeOutcome, PreprocessedMessage, ProcessedMessage, RoundAccumulator, Session,
+};
+use super::signed_message::SessionId;
+use crate::rounds::ProtocolResult;
+
+/// Limits on the resources taken by the sessions of a [`SessionManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionLimits {
+    /// The maximum number of sessions held at the same time,
+    /// including the finished and aborted ones that have not been collected yet.
+    pub max_sessions: usize,
+    /// The maximum number of messages accepted by a session over all of its rounds.
+    pub max_messages_per_session: usize,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: 256,
+            max_messages_per_session: 4096,
+        }
+    }
+}
+
+/// Errors returned by [`SessionManager`] methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SessionManagerError {
+    /// No session with the ID {0:?} is held by the manager.
+    UnknownSession(SessionId),
+    /// A session with the ID {0:?} is already held by the manager.
+    DuplicateSession(SessionId),
+    /// The maximum number of sessions is reached.
+    TooManySessions,
+    /// The session {0:?} has reached the maximum number of messages.
+    TooManyMessages(SessionId),
+    /// The session {0:?} is already finished or aborted.
+    Inactive(SessionId),
+    /// The session {0:?} was aborted because of an error; it can be retrieved with `collect_garbage()`.
+    Aborted(SessionId),
+}
+
+impl core::error::Error for SessionManagerError {}
+
+/// The state of a session held by a [`SessionManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    /// The session is waiting for messages.
+    InProgress {
+        /// The current round, as returned by [`Session::current_round`].
+        round: (u8, bool),
+    },
+    /// The session finished successfully.
+    Finished,
+    /// The session was aborted because of an error.
+    Aborted,
+}
+
+// Most of the held sessions are expected to be active, so boxing would not save memory.
+#[allow(clippy::large_enum_variant)]
+enum SessionState<Res: ProtocolResult, Sig, Signer, Verifier, F> {
+    Active {
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        accum: RoundAccumulator<Sig>,
+        received: usize,
+    },
+    Finished(Res::Success),
+    Aborted(Error<Res, Verifier>),
+}
+
+/// The outgoing messages: the destination and the message itself.
+type Outgoing<Sig, Verifier> = Vec<(Verifier, CombinedMessage<Sig>)>;
+
+/// Holds the concurrent sessions of a single party,
+/// routing the incoming messages to them by their session IDs.
+///
+/// The manager creates the messages, processes the incoming ones, and finalizes the rounds
+/// as soon as possible, returning the messages to be sent to the other parties.
+/// Finished and aborted sessions keep their outcomes
+/// until they are removed with [`collect_garbage`](Self::collect_garbage).
+///
+/// All the sessions must execute the same protocol and use the same message format;
+/// a separate manager is needed for each type of the protocol result.
+pub struct SessionManager<Res: ProtocolResult, Sig, Signer, Verifier, F = Bincode> {
+    limits: SessionLimits,
+    sessions: BTreeMap<SessionId, SessionState<Res, Sig, Signer, Verifier, F>>,
+}
+
+impl<Res, Sig, Signer, Verifier, F> SessionManager<Res, Sig, Signer, Verifier, F>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    /// Creates an empty manager with the given limits.
+    pub fn new(limits: SessionLimits) -> Self {
+        Self {
+            limits,
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of the held sessions, including the finished and aborted ones.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if the manager holds no sessions.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Returns the state of the session with the given ID, if it is held by the manager.
+    pub fn status(&self, session_id: &SessionId) -> Option<SessionStatus> {
+        self.sessions.get(session_id).map(|state| match state {
+            SessionState::Active { session, .. } => SessionStatus::InProgress {
+                round: session.current_round(),
+            },
+            SessionState::Finished(_) => SessionStatus::Finished,
+            SessionState::Aborted(_) => SessionStatus::Aborted,
+        })
+    }
+
+    /// Adds a new session, returning its ID and the messages of its first round.
+    pub fn add_session(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session: Session<Res, Sig, Signer, Verifier, F>,
+    ) -> Result<(SessionId, Outgoing<Sig, Verifier>), SessionManagerError> {
+        let session_id = *session.session_id();
+        if self.sessions.contains_key(&session_id) {
+            return Err(SessionManagerError::DuplicateSession(session_id));
+        }
+        if self.sessions.len() >= self.limits.max_sessions {
+            return Err(SessionManagerError::TooManySessions);
+        }
+
+        let mut outgoing = Vec::new();
+        let state = start_round(rng, session, Vec::new(), 0, &mut outgoing);
+        self.insert(session_id, state, outgoing)
+            .map(|outgoing| (session_id, outgoing))
+    }
+
+    /// Routes a message from another party to its session,
+    /// returning the messages to be sent if the session has moved on to the next round.
+    ///
+    /// If the message causes an error, the session is aborted.
+    pub fn receive_message(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        from: &Verifier,
+        message: CombinedMessage<Sig>,
+    ) -> Result<Outgoing<Sig, Verifier>, SessionManagerError> {
+        let session_id = *message.session_id();
+        let (session, mut accum, received) = self.take_active(&session_id)?;
+
+        if received >= self.limits.max_messages_per_session {
+            self.sessions.insert(
+                session_id,
+                SessionState::Active {
+                    session,
+                    accum,
+                    received,
+                },
+            );
+            return Err(SessionManagerError::TooManyMessages(session_id));
+        }
+
+        let result = session
+            .preprocess_message(&mut accum, from, message)
+            .and_then(|preprocessed| match preprocessed {
+                Some(preprocessed) => add_message(&session, &mut accum, preprocessed),
+                None => Ok(()),
+            });
+
+        let mut outgoing = Vec::new();
+        let state = match result {
+            Ok(()) => try_finalize(rng, session, accum, received + 1, &mut outgoing),
+            Err(error) => SessionState::Aborted(error),
+        };
+        self.insert(session_id, state, outgoing)
+    }
+
+    /// Finalizes the current round of the session without waiting for the missing messages
+    /// (see [`Session::finalize_with_absent`]),
+    /// returning the messages to be sent if the session has moved on to the next round.
+    ///
+    /// If the round cannot be finalized without the missing messages, the session is aborted.
+    pub fn finalize_with_absent(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session_id: &SessionId,
+    ) -> Result<Outgoing<Sig, Verifier>, SessionManagerError> {
+        let (session, accum, received) = self.take_active(session_id)?;
+
+        let mut outgoing = Vec::new();
+        let state = match session.missing_messages(&accum) {
+            Ok(absent) => match session.finalize_with_absent(rng, accum, &absent) {
+                Ok(outcome) => apply_outcome(rng, outcome, received, &mut outgoing),
+                Err(error) => SessionState::Aborted(error),
+            },
+            Err(error) => SessionState::Aborted(Error::Local(error)),
+        };
+        self.insert(*session_id, state, outgoing)
+    }
+
+    /// Removes the finished and aborted sessions, returning their outcomes.
+    #[allow(clippy::type_complexity)]
+    pub fn collect_garbage(
+        &mut self,
+    ) -> Vec<(SessionId, Result<Res::Success, Error<Res, Verifier>>)> {
+        let mut outcomes = Vec::new();
+        let sessions = core::mem::take(&mut self.sessions);
+        for (session_id, state) in sessions {
+            match state {
+                SessionState::Finished(result) => outcomes.push((session_id, Ok(result))),
+                SessionState::Aborted(error) => outcomes.push((session_id, Err(error))),
+                state => {
+                    self.sessions.insert(session_id, state);
+                }
+            }
+        }
+        outcomes
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn take_active(
+        &mut self,
+        session_id: &SessionId,
+    ) -> Result<
+        (
+            Session<Res, Sig, Signer, Verifier, F>,
+            RoundAccumulator<Sig>,
+            usize,
+        ),
+        SessionManagerError,
+    > {
+        match self.sessions.remove(session_id) {
+            Some(SessionState::Active {
+                session,
+                accum,
+                received,
+            }) => Ok((session, accum, received)),
+            Some(state) => {
+                self.sessions.insert(*session_id, state);
+                Err(SessionManagerError::Inactive(*session_id))
+            }
+            None => Err(SessionManagerError::UnknownSession(*session_id)),
+        }
+    }
+
+    fn insert(
+        &mut self,
+        session_id: SessionId,
+        state: SessionState<Res, Sig, Signer, Verifier, F>,
+        outgoing: Outgoing<Sig, Verifier>,
+    ) -> Result<Outgoing<Sig, Verifier>, SessionManagerError> {
+        let aborted = matches!(state, SessionState::Aborted(_));
+        self.sessions.insert(session_id, state);
+        if aborted {
+            // The messages created before the error are not needed anymore.
+            Err(SessionManagerError::Aborted(session_id))
+        } else {
+            Ok(outgoing)
+        }
+    }
+}
+
+fn add_message<Res, Sig, Signer, Verifier, F>(
+    session: &Session<Res, Sig, Signer, Verifier, F>,
+    accum: &mut RoundAccumulator<Sig>,
+    preprocessed: PreprocessedMessage<Sig>,
+) -> Result<(), Error<Res, Verifier>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    let processed = session.process_message(preprocessed)?;
+    add_processed_message(accum, processed)
+}
+
+fn add_processed_message<Res, Sig, Verifier>(
+    accum: &mut RoundAccumulator<Sig>,
+    processed: ProcessedMessage<Sig, Verifier>,
+) -> Result<(), Error<Res, Verifier>>
+where
+    Res: ProtocolResult,
+    Sig: Clone,
+    Verifier: Debug,
+{
+    accum
+        .add_processed_message(processed)
+        .map_err(Error::Local)?
+        .map_err(Error::Remote)
+}
+
+/// Creates the messages of the current round and processes the messages cached for it.
+fn start_round<Res, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    cached_messages: Vec<PreprocessedMessage<Sig>>,
+    received: usize,
+    outgoing: &mut Outgoing<Sig, Verifier>,
+) -> SessionState<Res, Sig, Signer, Verifier, F>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    let mut accum = session.make_accumulator();
+
+    match session.make_messages(rng, &mut accum) {
+        Ok(messages) => outgoing.extend(messages),
+        Err(error) => return SessionState::Aborted(Error::Local(error)),
+    }
+
+    for processed in session.process_messages(cached_messages) {
+        let result = processed.and_then(|processed| add_processed_message(&mut accum, processed));
+        if let Err(error) = result {
+            return SessionState::Aborted(error);
+        }
+    }
+
+    // The cached messages may be enough to finalize the round right away.
+    try_finalize(rng, session, accum, received, outgoing)
+}
+
+/// Finalizes the round if all the required messages are received.
+fn try_finalize<Res, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    session: Session<Res, Sig, Signer, Verifier, F>,
+    accum: RoundAccumulator<Sig>,
+    received: usize,
+    outgoing: &mut Outgoing<Sig, Verifier>,
+) -> SessionState<Res, Sig, Signer, Verifier, F>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    match session.can_finalize(&accum) {
+        Ok(true) => {}
+        Ok(false) => {
+            return SessionState::Active {
+                session,
+                accum,
+                received,
+            }
+        }
+        Err(error) => return SessionState::Aborted(Error::Local(error)),
+    }
+
+    match session.finalize_round(rng, accum) {
+        Ok(outcome) => apply_outcome(rng, outcome, received, outgoing),
+        Err(error) => SessionState::Aborted(error),
+    }
+}
+
+fn apply_outcome<Res, Sig, Signer, Verifier, F>(
+    rng: &mut impl CryptoRngCore,
+    outcome: FinalizeOutcome<Res, Sig, Signer, Verifier, F>,
+    received: usize,
+    outgoing: &mut Outgoing<Sig, Verifier>,
+) -> SessionState<Res, Sig, Signer, Verifier, F>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    match outcome {
+        FinalizeOutcome::Success(result) => SessionState::Finished(result),
+        FinalizeOutcome::AnotherRound {
+            session,
+            cached_messages,
+        } => start_round(rng, session, cached_messages, received, outgoing),
+    }
+}
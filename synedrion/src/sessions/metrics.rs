@@ -0,0 +1,78 @@
+use super::error::Error;
+use crate::rounds::ProtocolResult;
+
+/// The kind of the error that made a session fail, reported to [`SessionMetrics::on_abort`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AbortCause {
+    /// [`Error::Local`].
+    Local,
+    /// [`Error::Provable`].
+    Provable,
+    /// [`Error::Proof`].
+    Proof,
+    /// [`Error::Remote`].
+    Remote,
+    /// [`Error::Unresponsive`].
+    Unresponsive,
+    /// The transport failed to send or receive a message.
+    Transport,
+}
+
+impl AbortCause {
+    /// Returns the name of the cause, to be used e.g. as a metric label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Provable => "provable",
+            Self::Proof => "proof",
+            Self::Remote => "remote",
+            Self::Unresponsive => "unresponsive",
+            Self::Transport => "transport",
+        }
+    }
+}
+
+impl<Res: ProtocolResult, Verifier> From<&Error<Res, Verifier>> for AbortCause {
+    fn from(error: &Error<Res, Verifier>) -> Self {
+        match error {
+            Error::Local(_) => Self::Local,
+            Error::Provable { .. } => Self::Provable,
+            Error::Proof { .. } => Self::Proof,
+            Error::Remote(_) => Self::Remote,
+            Error::Unresponsive { .. } => Self::Unresponsive,
+        }
+    }
+}
+
+/// Receives the counters of a session executed by
+/// [`run_session_with_metrics`](super::run_session_with_metrics),
+/// to be fed into a metrics system (e.g. Prometheus counters labeled by the protocol name).
+///
+/// Unlike [`SessionObserver`](super::SessionObserver), which reports the progress of a session,
+/// the methods only carry the values suitable for aggregation over many sessions.
+/// All the methods do nothing by default. They must not block,
+/// since the session is not advanced while they are executed.
+pub trait SessionMetrics {
+    /// Called when a message is sent, with the size of its
+    /// [canonical encoding](crate::CombinedMessage::canonical_bytes).
+    fn on_message_sent(&mut self, _protocol: &'static str, _bytes: usize) {}
+
+    /// Called when a message is received, with the size of its
+    /// [canonical encoding](crate::CombinedMessage::canonical_bytes).
+    fn on_message_received(&mut self, _protocol: &'static str, _bytes: usize) {}
+
+    /// Called when a received message fails the verification.
+    fn on_verification_failure(&mut self, _protocol: &'static str) {}
+
+    /// Called when a round is finalized.
+    fn on_round_completed(&mut self, _protocol: &'static str, _round: u8, _is_echo_round: bool) {}
+
+    /// Called when the session produces its result.
+    fn on_session_completed(&mut self, _protocol: &'static str) {}
+
+    /// Called when the session fails.
+    fn on_abort(&mut self, _protocol: &'static str, _cause: AbortCause) {}
+}
+
+/// Metrics ignoring all the counters.
+impl SessionMetrics for () {}
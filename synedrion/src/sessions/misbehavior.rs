@@ -0,0 +1,124 @@
+//! Simulation of misbehaving parties for testing the fault handling (behind the `testing` feature).
+
+use alloc::format;
+
+use rand_core::CryptoRngCore;
+use signature::hazmat::RandomizedPrehashSigner;
+
+use super::combined_message::CombinedMessage;
+use super::error::LocalError;
+use super::signed_message::{MessageType, SignedMessage};
+
+/// A deviation from the protocol applied to a message
+/// created by [`Session::make_malicious_message`](crate::Session::make_malicious_message).
+///
+/// The payloads are corrupted by flipping the lowest bit of one of their bytes,
+/// addressed by its position counted from the end of the serialized payload
+/// (so `0` is the last byte).
+/// Depending on the position, this results in a wrong commitment,
+/// a corrupted ZK proof, or some other invalid value.
+/// Note that some corruptions make the payload impossible to deserialize,
+/// in which case the receiver reports an error of a different kind.
+///
+/// Sending a corrupted broadcast only to some of the parties results in an inconsistent broadcast,
+/// which is detected in the echo round if the round requires it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// The message is not sent.
+    Silence,
+    /// The broadcast part of the message is corrupted and signed again.
+    CorruptBroadcast {
+        /// The position of the byte to corrupt, counted from the end of the payload.
+        position: usize,
+    },
+    /// The direct part of the message is corrupted and signed again.
+    CorruptDirect {
+        /// The position of the byte to corrupt, counted from the end of the payload.
+        position: usize,
+    },
+    /// The last byte of the message payload (of the direct part, if there are two)
+    /// is corrupted, but the original signature is kept.
+    InvalidSignature,
+    /// The message header carries the given version of the message format, and is signed again,
+    /// as if the party was running a version of the library with an incompatible format.
+    UnsupportedVersion {
+        /// The identifier of the version.
+        version: u8,
+    },
+}
+
+impl Misbehavior {
+    pub(crate) fn apply<Sig>(
+        self,
+        rng: &mut impl CryptoRngCore,
+        signer: &impl RandomizedPrehashSigner<Sig>,
+        message: CombinedMessage<Sig>,
+    ) -> Result<Option<CombinedMessage<Sig>>, LocalError> {
+        let message = match (self, message) {
+            (Self::Silence, _) => return Ok(None),
+            (Self::CorruptBroadcast { position }, CombinedMessage::One(message))
+                if message.message_type() != MessageType::Direct =>
+            {
+                CombinedMessage::One(message.corrupted(position)?.signed(rng, signer)?)
+            }
+            (Self::CorruptBroadcast { position }, CombinedMessage::Both { broadcast, direct }) => {
+                CombinedMessage::Both {
+                    broadcast: broadcast.corrupted(position)?.signed(rng, signer)?,
+                    direct,
+                }
+            }
+            (Self::CorruptDirect { position }, CombinedMessage::One(message))
+                if message.message_type() == MessageType::Direct =>
+            {
+                CombinedMessage::One(message.corrupted(position)?.signed(rng, signer)?)
+            }
+            (Self::CorruptDirect { position }, CombinedMessage::Both { broadcast, direct }) => {
+                CombinedMessage::Both {
+                    broadcast,
+                    direct: direct.corrupted(position)?.signed(rng, signer)?,
+                }
+            }
+            (Self::InvalidSignature, CombinedMessage::One(message)) => {
+                CombinedMessage::One(message.corrupted(0)?)
+            }
+            (Self::InvalidSignature, CombinedMessage::Both { broadcast, direct }) => {
+                CombinedMessage::Both {
+                    broadcast,
+                    direct: direct.corrupted(0)?,
+                }
+            }
+            (Self::UnsupportedVersion { version }, CombinedMessage::One(message)) => {
+                CombinedMessage::One(message.with_version(version).signed(rng, signer)?)
+            }
+            (Self::UnsupportedVersion { version }, CombinedMessage::Both { broadcast, direct }) => {
+                CombinedMessage::Both {
+                    broadcast: broadcast.with_version(version).signed(rng, signer)?,
+                    direct: direct.with_version(version).signed(rng, signer)?,
+                }
+            }
+            (misbehavior, _) => {
+                return Err(LocalError(format!(
+                    "{misbehavior:?} cannot be applied to the messages of this round"
+                )))
+            }
+        };
+        Ok(Some(message))
+    }
+}
+
+impl<Sig> SignedMessage<Sig> {
+    fn corrupted(mut self, position: usize) -> Result<Self, LocalError> {
+        let payload = self.payload_mut();
+        let idx = payload
+            .len()
+            .checked_sub(position + 1)
+            .ok_or_else(|| LocalError(format!("The payload is shorter than {}", position + 1)))?;
+        payload[idx] ^= 1;
+        Ok(self)
+    }
+
+    fn with_version(mut self, version: u8) -> Self {
+        self.header_mut().version = version;
+        self
+    }
+}
@@ -0,0 +1,278 @@
+//! A tamper-evident record of all the messages sent and received by a session.
+//!
+//! Every message is kept in its canonical encoding (see [`CombinedMessage::canonical_bytes`])
+//! along with a hash covering it and all the preceding entries,
+//! so a recorded log cannot be modified, reordered or truncated
+//! without changing the hash of its last entry.
+//! Storing or publishing that hash separately (e.g. in an audit system)
+//! allows one to later check that the log presented is the one that was recorded.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+use signature::hazmat::PrehashVerifier;
+
+use super::combined_message::CombinedMessage;
+use super::error::LocalError;
+use super::format::{deserialize_canonical, Bincode, MessageFormat};
+use crate::tools::hashing::{Chain, Hash, HashOutput};
+use crate::tools::serde_bytes;
+
+/// Whether a recorded message was sent or received by the party.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageDirection {
+    /// The message was sent by the party.
+    Sent,
+    /// The message was received by the party.
+    Received,
+}
+
+impl MessageDirection {
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Sent => 0,
+            Self::Received => 1,
+        }
+    }
+}
+
+/// An entry of a [`MessageTranscript`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry<Verifier> {
+    direction: MessageDirection,
+    counterparty: Verifier,
+    #[serde(with = "serde_bytes::as_base64")]
+    message: Box<[u8]>,
+    hash: HashOutput,
+}
+
+impl<Verifier> TranscriptEntry<Verifier> {
+    /// Whether the message was sent or received.
+    pub fn direction(&self) -> MessageDirection {
+        self.direction
+    }
+
+    /// The destination of a sent message, or the sender of a received one.
+    pub fn counterparty(&self) -> &Verifier {
+        &self.counterparty
+    }
+
+    /// The canonical encoding of the message.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// The hash of this entry and all the preceding ones.
+    pub fn hash(&self) -> &[u8] {
+        self.hash.as_ref()
+    }
+}
+
+/// Possible errors when verifying a [`MessageTranscript`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum MessageTranscriptError {
+    /// The hash of the entry {index} does not match its contents and the preceding entries.
+    BrokenChain {
+        /// The index of the offending entry.
+        index: usize,
+    },
+    /// The entry {index} does not contain a valid message signed by its sender.
+    InvalidMessage {
+        /// The index of the offending entry.
+        index: usize,
+    },
+}
+
+fn genesis_hash(party: &[u8]) -> HashOutput {
+    Hash::new_with_dst(b"MessageTranscript")
+        .chain_bytes(party)
+        .finalize()
+}
+
+fn entry_hash(
+    prev: &HashOutput,
+    direction: MessageDirection,
+    counterparty: &[u8],
+    message: &[u8],
+) -> HashOutput {
+    Hash::new_with_dst(b"MessageTranscriptEntry")
+        .chain_constant_sized_bytes(prev)
+        .chain(&direction.as_u8())
+        .chain_bytes(counterparty)
+        .chain_bytes(message)
+        .finalize()
+}
+
+/// Records the messages of a session as a hash-chained log.
+///
+/// Can be passed to [`run_session_with_recorder`](super::run_session_with_recorder),
+/// or fed manually when the low-level [`Session`](super::Session) methods are used.
+#[derive(Debug, Clone)]
+pub struct TranscriptRecorder<Verifier> {
+    party: Verifier,
+    head: HashOutput,
+    entries: Vec<TranscriptEntry<Verifier>>,
+}
+
+impl<Verifier> TranscriptRecorder<Verifier>
+where
+    Verifier: Clone + Serialize,
+{
+    /// Creates an empty log for the party with the verifier `party`.
+    pub fn new(party: &Verifier) -> Result<Self, LocalError> {
+        let head = genesis_hash(&Bincode::serialize(party)?);
+        Ok(Self {
+            party: party.clone(),
+            head,
+            entries: Vec::new(),
+        })
+    }
+
+    fn record<Sig: Serialize>(
+        &mut self,
+        direction: MessageDirection,
+        counterparty: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        let message = message.canonical_bytes()?;
+        let hash = entry_hash(
+            &self.head,
+            direction,
+            &Bincode::serialize(counterparty)?,
+            &message,
+        );
+        self.head = hash;
+        self.entries.push(TranscriptEntry {
+            direction,
+            counterparty: counterparty.clone(),
+            message,
+            hash,
+        });
+        Ok(())
+    }
+
+    /// Records a message sent to `destination`.
+    pub fn record_sent<Sig: Serialize>(
+        &mut self,
+        destination: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        self.record(MessageDirection::Sent, destination, message)
+    }
+
+    /// Records a message received from `from`.
+    ///
+    /// All the received messages should be recorded, including the ones rejected by the session.
+    pub fn record_received<Sig: Serialize>(
+        &mut self,
+        from: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), LocalError> {
+        self.record(MessageDirection::Received, from, message)
+    }
+
+    /// Returns the number of recorded messages.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no messages were recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Finishes the recording, returning the log.
+    pub fn finish(self) -> MessageTranscript<Verifier> {
+        MessageTranscript {
+            party: self.party,
+            entries: self.entries,
+        }
+    }
+}
+
+/// A hash-chained log of all the messages sent and received by a party during a session,
+/// created by [`TranscriptRecorder`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTranscript<Verifier> {
+    party: Verifier,
+    entries: Vec<TranscriptEntry<Verifier>>,
+}
+
+impl<Verifier> MessageTranscript<Verifier>
+where
+    Verifier: Serialize,
+{
+    /// The verifier of the party that recorded the log.
+    pub fn party(&self) -> &Verifier {
+        &self.party
+    }
+
+    /// The recorded messages, in the order they were sent or received.
+    pub fn entries(&self) -> &[TranscriptEntry<Verifier>] {
+        &self.entries
+    }
+
+    /// The hash of the whole log.
+    ///
+    /// Unless it is compared with a value stored elsewhere,
+    /// the hash chain does not protect from the log being replaced altogether.
+    pub fn head(&self) -> Result<[u8; 32], LocalError> {
+        let head = match self.entries.last() {
+            Some(entry) => entry.hash,
+            None => genesis_hash(&Bincode::serialize(&self.party)?),
+        };
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(head.as_ref());
+        Ok(bytes)
+    }
+
+    /// Checks that the hash of every entry matches its contents and the preceding entries.
+    pub fn verify_chain(&self) -> Result<(), MessageTranscriptError> {
+        let serialize = |verifier: &Verifier, index| {
+            Bincode::serialize(verifier).map_err(|_| MessageTranscriptError::BrokenChain { index })
+        };
+
+        let mut head = genesis_hash(&serialize(&self.party, 0)?);
+        for (index, entry) in self.entries.iter().enumerate() {
+            let hash = entry_hash(
+                &head,
+                entry.direction,
+                &serialize(&entry.counterparty, index)?,
+                &entry.message,
+            );
+            if hash != entry.hash {
+                return Err(MessageTranscriptError::BrokenChain { index });
+            }
+            head = hash;
+        }
+        Ok(())
+    }
+
+    /// Checks the hash chain (see [`verify_chain`](Self::verify_chain)),
+    /// and that every entry contains a message signed by its sender:
+    /// the recording party for the sent messages, and the counterparty for the received ones.
+    ///
+    /// Note that a received message may fail this check if the sender misbehaved,
+    /// in which case the session would have returned an error as well.
+    pub fn verify<Sig>(&self) -> Result<(), MessageTranscriptError>
+    where
+        Sig: Serialize + for<'de> Deserialize<'de>,
+        Verifier: PrehashVerifier<Sig>,
+    {
+        self.verify_chain()?;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let sender = match entry.direction {
+                MessageDirection::Sent => &self.party,
+                MessageDirection::Received => &entry.counterparty,
+            };
+            deserialize_canonical::<Bincode, CombinedMessage<Sig>>(&entry.message)
+                .ok()
+                .and_then(|message| message.check().ok())
+                .and_then(|message| message.verify(sender).ok())
+                .ok_or(MessageTranscriptError::InvalidMessage { index })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Crash recovery for sessions executed with [`run_session`](super::run_session).
+//!
+//! Instead of serializing the session itself (which holds type-erased round objects
+//! and secret data), we rely on the protocol execution being deterministic:
+//! given the same inputs, the same RNG and the same sequence of received messages,
+//! [`run_session`](super::run_session) will produce exactly the same outgoing messages.
+//! Therefore, it is enough to persist the log of received messages
+//! (which contains nothing that was not already sent over the network)
+//! and the seed of the RNG the session was run with.
+//!
+//! The seed is secret key material: it determines all the secret values of the session
+//! (the key shares being created, the nonces of a signature),
+//! so it must be stored with the same protection as the key shares themselves.
+//!
+//! To resume a session after a restart, the party must create it anew
+//! with the same constructor, inputs and an RNG seeded with **the same persisted seed**,
+//! and run it with [`ResumableTransport::restore`] using the last persisted [`MessageLog`].
+//! The logged messages are replayed, and the messages that were sent before the crash
+//! are not sent again.
+//! With a different seed, the party would create different messages for the rounds
+//! it has already sent messages in, which the other parties treat as an equivocation
+//! (and which, in a signing session, can expose the secret key share).
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use super::combined_message::CombinedMessage;
+use super::driver::Transport;
+
+/// The log of messages exchanged by a session, sufficient to resume it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageLog<Sig, Verifier> {
+    sent: usize,
+    received: Vec<(Verifier, CombinedMessage<Sig>)>,
+}
+
+impl<Sig, Verifier> MessageLog<Sig, Verifier> {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self {
+            sent: 0,
+            received: Vec::new(),
+        }
+    }
+
+    /// Returns the number of messages that were successfully sent.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Returns the number of messages that were received.
+    pub fn received(&self) -> usize {
+        self.received.len()
+    }
+}
+
+impl<Sig, Verifier> Default for MessageLog<Sig, Verifier> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Transport`] wrapper that records the exchanged messages in a [`MessageLog`],
+/// and can replay a previously recorded log.
+///
+/// The callback given on creation is called every time the log is updated,
+/// and is expected to persist it.
+/// Note that a message is recorded as sent only after the inner transport reports success,
+/// so if the party crashes in-between, the message will be sent again after the restart.
+pub struct ResumableTransport<T, Sig, Verifier, F> {
+    inner: T,
+    log: MessageLog<Sig, Verifier>,
+    to_skip: usize,
+    replay_position: usize,
+    on_update: F,
+    phantom: PhantomData<fn() -> Sig>,
+}
+
+impl<T, Sig, Verifier, F> ResumableTransport<T, Sig, Verifier, F>
+where
+    F: FnMut(&MessageLog<Sig, Verifier>),
+{
+    /// Wraps a transport for a new session.
+    pub fn new(inner: T, on_update: F) -> Self {
+        Self::restore(inner, MessageLog::new(), on_update)
+    }
+
+    /// Wraps a transport for a session being resumed from the given log.
+    pub fn restore(inner: T, log: MessageLog<Sig, Verifier>, on_update: F) -> Self {
+        Self {
+            inner,
+            to_skip: log.sent,
+            log,
+            replay_position: 0,
+            on_update,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the current log.
+    pub fn log(&self) -> &MessageLog<Sig, Verifier> {
+        &self.log
+    }
+
+    /// Returns the inner transport and the current log.
+    pub fn into_parts(self) -> (T, MessageLog<Sig, Verifier>) {
+        (self.inner, self.log)
+    }
+}
+
+impl<T, Sig, Verifier, F> Transport<Sig, Verifier> for ResumableTransport<T, Sig, Verifier, F>
+where
+    T: Transport<Sig, Verifier> + Send,
+    Sig: Clone + Send,
+    Verifier: Clone + Send + Sync,
+    F: FnMut(&MessageLog<Sig, Verifier>) + Send,
+{
+    type Error = T::Error;
+
+    async fn send(
+        &mut self,
+        destination: &Verifier,
+        message: CombinedMessage<Sig>,
+    ) -> Result<(), Self::Error> {
+        if self.to_skip > 0 {
+            self.to_skip -= 1;
+            return Ok(());
+        }
+
+        self.inner.send(destination, message).await?;
+        self.log.sent += 1;
+        (self.on_update)(&self.log);
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<(Verifier, CombinedMessage<Sig>), Self::Error> {
+        if let Some(message) = self.log.received.get(self.replay_position).cloned() {
+            self.replay_position += 1;
+            return Ok(message);
+        }
+
+        let message = self.inner.receive().await?;
+        self.log.received.push(message.clone());
+        self.replay_position += 1;
+        (self.on_update)(&self.log);
+        Ok(message)
+    }
+}
@@ -1,25 +1,37 @@
 use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::format;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
-use rand_core::CryptoRngCore;
+use digest::XofReader;
+use rand_core::{CryptoRng, CryptoRngCore, RngCore};
 use serde::{Deserialize, Serialize};
 use signature::{
     hazmat::{PrehashVerifier, RandomizedPrehashSigner},
     Keypair,
 };
 
-use super::combined_message::{CheckedCombinedMessage, CombinedMessage, VerifiedCombinedMessage};
+use super::combined_message::{
+    CheckedCombinedMessage, CombinedMessage, EquivocationEvidence, VerifiedCombinedMessage,
+};
 use super::echo::{EchoAccum, EchoRound};
-use super::error::{Error, LocalError, ProvableError, RemoteError, RemoteErrorEnum};
-use super::signed_message::{MessageType, SessionId, SignedMessage, VerifiedMessage};
+use super::encryption::{DirectMessageEncryption, EncryptionKeys};
+use super::error::{Error, ErrorContext, LocalError, ProvableError, RemoteError, RemoteErrorEnum};
+use super::format::{Bincode, MessageFormat};
+#[cfg(feature = "low-level")]
+use super::low_level::LowLevelRound;
+use super::signed_message::{
+    fragmented_message_hash, MessageFragment, MessageHeader, MessageReassembler, MessageType,
+    MessageVersion, SessionId, SignedMessage, VerifiedMessage,
+};
 use super::type_erased::{
     self, AccumAddError, DynArtifact, DynFinalizable, DynPayload, DynRoundAccum, ReceiveError,
 };
 use crate::rounds::{self, FirstRound, PartyIdx, ProtocolResult, Round};
 use crate::tools::collections::HoleRange;
+use crate::tools::hashing::{Chain, FiatShamirTranscript, XofHash};
+use crate::tools::parallel::{map, map_with_rng};
 
 struct Context<Signer, Verifier> {
     signer: Signer,
@@ -27,22 +39,164 @@ struct Context<Signer, Verifier> {
     session_id: SessionId,
     party_idx: PartyIdx,
     verifier_to_idx: BTreeMap<Verifier, PartyIdx>,
+    // Boxed so that moving the context between rounds does not leave copies of the seed.
+    rng_seed: Box<RngSeed>,
+    deterministic: bool,
+    encryption: Option<EncryptionKeys>,
+}
+
+/// The seed for the randomness of the session in the deterministic mode.
+struct RngSeed([u8; 32]);
+
+#[cfg(feature = "zeroize")]
+impl Drop for RngSeed {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+/// An RNG derived from the session seed.
+struct DerivedRng(<XofHash as FiatShamirTranscript>::Reader);
+
+impl RngCore for DerivedRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.read(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for DerivedRng {}
+
+/// Either the RNG supplied by the caller, or the one derived from the session seed.
+enum SessionRng<'a, R> {
+    Supplied(&'a mut R),
+    Derived(Box<DerivedRng>),
+}
+
+impl<R: CryptoRngCore> RngCore for SessionRng<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Supplied(rng) => rng.next_u32(),
+            Self::Derived(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Supplied(rng) => rng.next_u64(),
+            Self::Derived(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Supplied(rng) => rng.fill_bytes(dest),
+            Self::Derived(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        match self {
+            Self::Supplied(rng) => rng.try_fill_bytes(dest),
+            Self::Derived(rng) => rng.try_fill_bytes(dest),
+        }
+    }
 }
 
-enum SessionType<Res, Sig> {
+impl<R: CryptoRngCore> CryptoRng for SessionRng<'_, R> {}
+
+impl<Signer, Verifier> Context<Signer, Verifier> {
+    /// Returns the RNG to be used for the operation with the given parameters.
+    ///
+    /// In the deterministic mode, the RNG is derived from the session seed and the parameters,
+    /// so the result does not depend on the order in which the operations are performed.
+    fn rng<'a, R: CryptoRngCore>(
+        &self,
+        rng: &'a mut R,
+        round: u8,
+        is_echo_round: bool,
+        operation: &[u8],
+        destination: Option<PartyIdx>,
+    ) -> SessionRng<'a, R> {
+        match self.derived_rng(round, is_echo_round, operation, destination) {
+            Some(derived_rng) => SessionRng::Derived(derived_rng),
+            None => SessionRng::Supplied(rng),
+        }
+    }
+
+    /// Returns the RNG derived from the session seed in the deterministic mode, or `None` otherwise.
+    fn derived_rng(
+        &self,
+        round: u8,
+        is_echo_round: bool,
+        operation: &[u8],
+        destination: Option<PartyIdx>,
+    ) -> Option<Box<DerivedRng>> {
+        if !self.deterministic {
+            return None;
+        }
+
+        let mut digest = XofHash::new_with_dst(b"SessionRng")
+            .chain(&self.rng_seed.0)
+            .chain(&round)
+            .chain(&is_echo_round)
+            .chain_bytes(operation);
+        if let Some(destination) = destination {
+            digest = digest.chain(&destination);
+        }
+        Some(Box::new(DerivedRng(digest.finalize_to_reader())))
+    }
+
+    /// Returns the header of a message sent by this party.
+    fn message_header<Res: ProtocolResult, F: MessageFormat>(
+        &self,
+        round: u8,
+        message_type: MessageType,
+        recipient: Option<PartyIdx>,
+    ) -> MessageHeader {
+        MessageHeader {
+            version: MessageVersion::CURRENT.as_u8(),
+            session_id: self.session_id,
+            protocol: Res::NAME.into(),
+            round,
+            message_type,
+            sender: self.party_idx,
+            recipient,
+            format: F::ID,
+            encrypted: message_type == MessageType::Direct && self.encryption.is_some(),
+        }
+    }
+}
+
+enum SessionType<Res, Sig, F> {
     Normal {
-        this_round: Box<dyn DynFinalizable<Res>>,
+        this_round: Box<dyn DynFinalizable<Res, F>>,
         broadcast: Option<SignedMessage<Sig>>,
     },
     Echo {
-        next_round: Box<dyn DynFinalizable<Res>>,
+        next_round: Box<dyn DynFinalizable<Res, F>>,
         echo_round: EchoRound<Sig>,
     },
 }
 
 /// The session state where it is ready to send messages.
-pub struct Session<Res, Sig, Signer, Verifier> {
-    tp: SessionType<Res, Sig>,
+///
+/// The message payloads are serialized in the format `F`
+/// (see [`MessageFormat`] for the available formats).
+pub struct Session<Res, Sig, Signer, Verifier, F = Bincode> {
+    tp: SessionType<Res, Sig, F>,
     context: Context<Signer, Verifier>,
 }
 
@@ -51,8 +205,8 @@ enum MessageFor {
     NextRound,
 }
 
-fn route_message_normal<Res: ProtocolResult, Sig>(
-    round: &dyn DynFinalizable<Res>,
+fn route_message_normal<Res: ProtocolResult, Sig, F>(
+    round: &dyn DynFinalizable<Res, F>,
     message: &CheckedCombinedMessage<Sig>,
 ) -> Result<MessageFor, RemoteErrorEnum> {
     let this_round = round.round_num();
@@ -79,8 +233,8 @@ fn route_message_normal<Res: ProtocolResult, Sig>(
     Err(RemoteErrorEnum::OutOfOrderMessage)
 }
 
-fn route_message_echo<Res: ProtocolResult, Sig>(
-    next_round: &dyn DynFinalizable<Res>,
+fn route_message_echo<Res: ProtocolResult, Sig, F>(
+    next_round: &dyn DynFinalizable<Res, F>,
     message: &CheckedCombinedMessage<Sig>,
 ) -> Result<MessageFor, RemoteErrorEnum> {
     let next_round = next_round.round_num();
@@ -98,8 +252,9 @@ fn route_message_echo<Res: ProtocolResult, Sig>(
     Err(RemoteErrorEnum::OutOfOrderMessage)
 }
 
-fn wrap_receive_result<Res: ProtocolResult, Verifier: Clone, T>(
+pub(crate) fn wrap_receive_result<Res: ProtocolResult, Verifier: Clone, T>(
     from: &Verifier,
+    context: ErrorContext,
     result: Result<T, ReceiveError<Res>>,
 ) -> Result<T, Error<Res, Verifier>> {
     // TODO (#43): we need to attach all the necessary messages here,
@@ -108,46 +263,83 @@ fn wrap_receive_result<Res: ProtocolResult, Verifier: Clone, T>(
     result.map_err(|err| match err {
         ReceiveError::InvalidContents(msg) => Error::Remote(RemoteError {
             party: from.clone(),
+            context,
             error: RemoteErrorEnum::InvalidContents(msg),
         }),
         ReceiveError::CannotDeserialize(msg) => Error::Provable {
             party: from.clone(),
+            context,
             error: ProvableError::CannotDeserialize(msg),
         },
         ReceiveError::Protocol(err) => Error::Provable {
             party: from.clone(),
+            context,
             error: ProvableError::Protocol(err),
         },
     })
 }
 
 /// Possible outcomes of successfully finalizing a round.
-pub enum FinalizeOutcome<Res: ProtocolResult, Sig, Signer, Verifier> {
+// The outcome is unpacked by the caller right away, so boxing the session would not save memory.
+#[allow(clippy::large_enum_variant)]
+pub enum FinalizeOutcome<Res: ProtocolResult, Sig, Signer, Verifier, F = Bincode> {
     /// The protocol result is available.
     Success(Res::Success),
     /// Starting the next round.
     AnotherRound {
         /// The new session object.
-        session: Session<Res, Sig, Signer, Verifier>,
+        session: Session<Res, Sig, Signer, Verifier, F>,
         /// The messages for the new round received during the previous round.
+        /// They must be processed with [`Session::process_message`] of the new session
+        /// and added to its accumulator before waiting for new messages.
         cached_messages: Vec<PreprocessedMessage<Sig>>,
     },
 }
 
-impl<Res, Sig, Signer, Verifier> Session<Res, Sig, Signer, Verifier>
+/// Possible outcomes of [`Session::receive_and_maybe_finalize`].
+// The outcome is unpacked by the caller right away, so boxing the session would not save memory.
+#[allow(clippy::large_enum_variant)]
+pub enum ReceiveOutcome<Res: ProtocolResult, Sig, Signer, Verifier, F = Bincode> {
+    /// The round cannot be finalized yet.
+    Pending {
+        /// The same session object.
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        /// The accumulator with the received message added.
+        accum: RoundAccumulator<Sig>,
+    },
+    /// The round received all the messages it needs and was finalized.
+    Finalized(FinalizeOutcome<Res, Sig, Signer, Verifier, F>),
+}
+
+impl<Res, Sig, Signer, Verifier, F> Session<Res, Sig, Signer, Verifier, F>
 where
     Res: ProtocolResult,
     Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
     Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
     Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
 {
-    pub(crate) fn new<R: FirstRound + DynFinalizable<Res> + Round<Result = Res> + 'static>(
+    pub(crate) fn new<R: FirstRound + DynFinalizable<Res, F> + Round<Result = Res> + 'static>(
         rng: &mut impl CryptoRngCore,
         shared_randomness: &[u8],
         signer: Signer,
-        verifiers: &[Verifier],
+        verifiers: &BTreeSet<Verifier>,
+        epoch: u64,
         inputs: R::Inputs,
-    ) -> Result<Self, LocalError> {
+    ) -> Result<Self, LocalError>
+    where
+        Verifier: Serialize,
+    {
+        // The verifiers are unique since they come in a set,
+        // but their number must still be supported by the protocols.
+        if verifiers.len() < 2 || u32::try_from(verifiers.len()).is_err() {
+            return Err(LocalError(format!(
+                "Number of verifiers must be between 2 and {} (got: {})",
+                u32::MAX,
+                verifiers.len()
+            )));
+        }
+
         let verifier_to_idx = verifiers
             .iter()
             .enumerate()
@@ -159,26 +351,40 @@ where
                 "The given signer's verifying key is not among the verifiers".into(),
             ))?;
 
-        // TODO (#3): Is this enough? Do we need to hash in e.g. the verifier public keys?
-        //            Need to specify the requirements for the shared randomness in the docstring.
-        let session_id = SessionId::from_seed(shared_randomness);
+        let verifiers = verifiers.iter().cloned().collect::<Vec<_>>();
+        let session_id = SessionId::new(shared_randomness, Res::NAME, &verifiers, epoch)?;
+        let mut rng_seed = Box::new(RngSeed([0u8; 32]));
+        rng.fill_bytes(&mut rng_seed.0);
         let typed_round = R::new(rng, shared_randomness, verifiers.len(), party_idx, inputs)
             .map_err(|err| LocalError(format!("Failed to initialize the protocol: {err:?}")))?;
-        let round: Box<dyn DynFinalizable<Res>> = Box::new(typed_round);
+        let round: Box<dyn DynFinalizable<Res, F>> = Box::new(typed_round);
         let context = Context {
             signer,
-            verifiers: verifiers.into(),
+            verifiers,
             session_id,
             party_idx,
             verifier_to_idx,
+            rng_seed,
+            deterministic: false,
+            encryption: None,
         };
-        Self::new_internal(rng, context, round)
+        let session = Self::new_internal(rng, context, round)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            protocol = Res::NAME,
+            party_idx = party_idx.as_usize(),
+            num_parties = session.context.verifiers.len(),
+            "session created"
+        );
+
+        Ok(session)
     }
 
     fn new_internal(
         rng: &mut impl CryptoRngCore,
         context: Context<Signer, Verifier>,
-        round: Box<dyn DynFinalizable<Res>>,
+        round: Box<dyn DynFinalizable<Res, F>>,
     ) -> Result<Self, LocalError> {
         let broadcast = round.make_broadcast_message(rng)?;
 
@@ -187,9 +393,11 @@ where
                 VerifiedMessage::new(
                     rng,
                     &context.signer,
-                    &context.session_id,
-                    round.round_num(),
-                    MessageType::Broadcast,
+                    context.message_header::<Res, F>(
+                        round.round_num(),
+                        MessageType::Broadcast,
+                        None,
+                    ),
                     &payload,
                 )?
                 .into_unverified(),
@@ -207,11 +415,53 @@ where
         })
     }
 
+    /// Switches the session to the deterministic mode, where all the randomness
+    /// used by the session methods is derived from the RNG given to the session constructor,
+    /// and the RNGs passed to [`make_message`](Self::make_message)
+    /// and [`finalize_round`](Self::finalize_round) are ignored.
+    ///
+    /// The randomness for each operation is derived from its round and destination,
+    /// so the messages do not depend on the order in which they are created or received.
+    /// Therefore, if the constructor is given a seeded RNG (e.g. `ChaCha20Rng::from_seed()`),
+    /// the same inputs and seeds result in the same messages and results
+    /// across runs and platforms, which can be used to create test vectors.
+    /// The mode applies to all the subsequent rounds of the session.
+    ///
+    /// **Warning:** this mode is intended for testing. The secrecy of all the values in the session
+    /// depends on the secrecy of the constructor's RNG output.
+    pub fn deterministic(mut self) -> Self {
+        self.context.deterministic = true;
+        self
+    }
+
+    /// Enables the end-to-end encryption of the direct messages of the session
+    /// with the given keys, which must include the encryption keys of all the parties.
+    ///
+    /// All the parties must enable it; the unencrypted direct messages are rejected
+    /// with [`RemoteErrorEnum::UnexpectedEncryption`], and vice versa.
+    /// The broadcasts are not encrypted, since they are echoed to all the parties anyway.
+    ///
+    /// Note that a provable error caused by the contents of an encrypted direct message
+    /// can only be checked by someone who can decrypt it.
+    pub fn with_direct_message_encryption(
+        mut self,
+        encryption: DirectMessageEncryption<Verifier>,
+    ) -> Result<Self, LocalError> {
+        let keys = EncryptionKeys::new(encryption, &self.context.verifiers, &self.verifier())?;
+        self.context.encryption = Some(keys);
+        Ok(self)
+    }
+
     /// This session's verifier object.
     pub fn verifier(&self) -> Verifier {
         self.context.signer.verifying_key()
     }
 
+    /// Returns the ID of this session, which is the same for all the participants.
+    pub fn session_id(&self) -> &SessionId {
+        &self.context.session_id
+    }
+
     /// Returns a pair of the current round index and whether it is an echo round.
     pub fn current_round(&self) -> (u8, bool) {
         match &self.tp {
@@ -220,12 +470,48 @@ where
         }
     }
 
+    /// Returns `true` if the current round is the last one of the protocol,
+    /// that is, finalizing it produces the result.
+    pub fn is_last_round(&self) -> bool {
+        match &self.tp {
+            SessionType::Normal { this_round, .. } => this_round.next_round_num().is_none(),
+            SessionType::Echo { .. } => false,
+        }
+    }
+
+    /// Converts a new session into a [`LowLevelRound`],
+    /// to drive the rounds of the protocol without the message signing and routing of the session.
+    ///
+    /// Must be called before any messages of the session are created or processed.
+    #[cfg(feature = "low-level")]
+    pub fn into_low_level(self) -> Result<LowLevelRound<Res, F>, LocalError> {
+        match self.tp {
+            SessionType::Normal { this_round, .. } => Ok(LowLevelRound::new(
+                this_round,
+                self.context.party_idx,
+                self.context.verifiers.len(),
+            )),
+            SessionType::Echo { .. } => Err(LocalError(
+                "Cannot convert a session in an echo round into a low-level round".into(),
+            )),
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        let (round, is_echo_round) = self.current_round();
+        ErrorContext {
+            protocol: Res::NAME,
+            round,
+            is_echo_round,
+        }
+    }
+
     /// Create an accumulator to store message creation and processing results of this round.
     pub fn make_accumulator(&self) -> RoundAccumulator<Sig> {
         RoundAccumulator::new(
             self.context.verifiers.len(),
             self.context.party_idx,
-            self.is_echo_round(),
+            self.error_context(),
         )
     }
 
@@ -268,13 +554,6 @@ where
         })
     }
 
-    fn is_echo_round(&self) -> bool {
-        match &self.tp {
-            SessionType::Normal { .. } => false,
-            SessionType::Echo { .. } => true,
-        }
-    }
-
     /// Returns the party indices to which the messages of this round should be sent.
     pub fn message_destinations(&self) -> Vec<Verifier> {
         match &self.tp {
@@ -299,6 +578,19 @@ where
 
     /// Returns the message for the given destination
     /// (must be one of those returned by [`Self::message_destinations`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                protocol = Res::NAME,
+                round = self.current_round().0,
+                echo = self.current_round().1,
+                destination = ?destination,
+            )
+        )
+    )]
     pub fn make_message(
         &self,
         rng: &mut impl CryptoRngCore,
@@ -310,62 +602,38 @@ where
             .get(destination)
             .ok_or(LocalError(format!("Verifier not found: {destination:?}")))?;
 
+        let (round_num, is_echo_round) = self.current_round();
+        let rng = &mut self.context.rng(
+            rng,
+            round_num,
+            is_echo_round,
+            b"make_message",
+            Some(destination_idx),
+        );
+
         match &self.tp {
             SessionType::Normal {
                 this_round,
                 broadcast,
             } => {
-                let round_num = this_round.round_num();
                 let (payload, artifact) = this_round.make_direct_message(rng, destination_idx)?;
-
-                let direct_message = if let Some(payload) = payload {
-                    Some(
-                        VerifiedMessage::new(
-                            rng,
-                            &self.context.signer,
-                            &self.context.session_id,
-                            round_num,
-                            MessageType::Direct,
-                            &payload,
-                        )?
-                        .into_unverified(),
-                    )
-                } else {
-                    None
-                };
-
-                let message = match (broadcast, direct_message) {
-                    (Some(broadcast), Some(direct)) => CombinedMessage::Both {
-                        broadcast: broadcast.clone(),
-                        direct,
-                    },
-                    (None, Some(direct)) => CombinedMessage::One(direct),
-                    (Some(broadcast), None) => CombinedMessage::One(broadcast.clone()),
-                    (None, None) => return Err(LocalError("The round must send messages".into())),
-                };
-
-                Ok((
-                    message,
-                    Artifact {
-                        destination: destination.clone(),
-                        destination_idx,
-                        artifact,
-                    },
-                ))
+                self.sign_message(
+                    rng,
+                    broadcast.as_ref(),
+                    destination,
+                    destination_idx,
+                    payload,
+                    artifact,
+                )
             }
-            SessionType::Echo {
-                next_round,
-                echo_round,
-            } => {
-                let round_num = next_round.round_num() - 1;
-                let payload = echo_round.make_broadcast();
+            SessionType::Echo { echo_round, .. } => {
+                let payload = echo_round.make_broadcast::<F>();
                 let artifact = DynArtifact::null();
                 let message = VerifiedMessage::new(
                     rng,
                     &self.context.signer,
-                    &self.context.session_id,
-                    round_num,
-                    MessageType::Echo,
+                    self.context
+                        .message_header::<Res, F>(round_num, MessageType::Echo, None),
                     &payload,
                 )?
                 .into_unverified();
@@ -381,6 +649,222 @@ where
         }
     }
 
+    /// Signs (and encrypts, if enabled) the direct message payload created by the round,
+    /// and combines it with the broadcast, if any.
+    fn sign_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        broadcast: Option<&SignedMessage<Sig>>,
+        destination: &Verifier,
+        destination_idx: PartyIdx,
+        payload: Option<Box<[u8]>>,
+        artifact: DynArtifact,
+    ) -> Result<(CombinedMessage<Sig>, Artifact<Verifier>), LocalError> {
+        let (round_num, _is_echo_round) = self.current_round();
+
+        let direct_message = if let Some(payload) = payload {
+            let header = self.context.message_header::<Res, F>(
+                round_num,
+                MessageType::Direct,
+                Some(destination_idx),
+            );
+            let payload = match &self.context.encryption {
+                Some(keys) => keys.encrypt(rng, &header, &payload)?,
+                None => payload,
+            };
+            Some(
+                VerifiedMessage::new(rng, &self.context.signer, header, &payload)?
+                    .into_unverified(),
+            )
+        } else {
+            None
+        };
+
+        let message = match (broadcast, direct_message) {
+            (Some(broadcast), Some(direct)) => CombinedMessage::Both {
+                broadcast: broadcast.clone(),
+                direct,
+            },
+            (None, Some(direct)) => CombinedMessage::One(direct),
+            (Some(broadcast), None) => CombinedMessage::One(broadcast.clone()),
+            (None, None) => return Err(LocalError("The round must send messages".into())),
+        };
+
+        Ok((
+            message,
+            Artifact {
+                destination: destination.clone(),
+                destination_idx,
+                artifact,
+            },
+        ))
+    }
+
+    /// Creates the messages of this round for all the destinations
+    /// returned by [`message_destinations`](Self::message_destinations),
+    /// and adds the corresponding artifacts to the accumulator.
+    ///
+    /// The result is the same as of calling [`make_message`](Self::make_message)
+    /// for each destination in turn, but with the `rayon` feature enabled,
+    /// the payloads of a regular round (where the ZK proofs and the Paillier encryptions are made)
+    /// are created in the threads of the `rayon` pool.
+    pub(crate) fn make_messages(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        accum: &mut RoundAccumulator<Sig>,
+    ) -> Result<Vec<(Verifier, CombinedMessage<Sig>)>, LocalError> {
+        let (this_round, broadcast) = match &self.tp {
+            SessionType::Normal {
+                this_round,
+                broadcast,
+            } => (this_round, broadcast),
+            SessionType::Echo { .. } => {
+                return self
+                    .message_destinations()
+                    .into_iter()
+                    .map(|destination| {
+                        let (message, artifact) = self.make_message(rng, &destination)?;
+                        accum.add_artifact(artifact)?;
+                        Ok((destination, message))
+                    })
+                    .collect();
+            }
+        };
+
+        // In the deterministic mode each payload is created with the RNG derived for its destination,
+        // which is then used to sign the message, exactly as in `make_message`.
+        let (round_num, is_echo_round) = self.current_round();
+        let destination_idxs = this_round.message_destinations();
+        let items = destination_idxs
+            .iter()
+            .map(|idx| {
+                (
+                    *idx,
+                    self.context
+                        .derived_rng(round_num, is_echo_round, b"make_message", Some(*idx)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let payloads = map_with_rng(rng, items, |mut rng, (destination_idx, derived_rng)| {
+            let mut rng = match derived_rng {
+                Some(derived_rng) => SessionRng::Derived(derived_rng),
+                None => SessionRng::Supplied(&mut rng),
+            };
+            let result = this_round.make_direct_message(&mut rng, destination_idx);
+            let derived_rng = match rng {
+                SessionRng::Derived(derived_rng) => Some(derived_rng),
+                SessionRng::Supplied(_) => None,
+            };
+            (result, derived_rng)
+        });
+
+        destination_idxs
+            .into_iter()
+            .zip(payloads)
+            .map(|(destination_idx, (result, derived_rng))| {
+                let (payload, artifact) = result?;
+                let destination = &self.context.verifiers[destination_idx.as_usize()];
+                let rng = &mut match derived_rng {
+                    Some(derived_rng) => SessionRng::Derived(derived_rng),
+                    None => SessionRng::Supplied(&mut *rng),
+                };
+                let (message, artifact) = self.sign_message(
+                    rng,
+                    broadcast.as_ref(),
+                    destination,
+                    destination_idx,
+                    payload,
+                    artifact,
+                )?;
+                accum.add_artifact(artifact)?;
+                Ok((destination.clone(), message))
+            })
+            .collect()
+    }
+
+    /// Creates the messages of this round for all the destinations
+    /// returned by [`message_destinations`](Self::message_destinations),
+    /// and adds the corresponding artifacts to the accumulator.
+    ///
+    /// Calling [`make_message`](Self::make_message) again for the same destination
+    /// can produce a different message (e.g. with a new ZK proof),
+    /// which the receiver will treat as an equivocation.
+    /// The returned state keeps the messages, so that they can be sent again
+    /// (e.g. by a transport with at-least-once delivery, after a timeout)
+    /// without creating them anew.
+    /// The receiver ignores an exact copy of a message it already received.
+    pub fn make_sending_state(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        accum: &mut RoundAccumulator<Sig>,
+    ) -> Result<SendingState<Sig, Verifier>, LocalError> {
+        let destinations = self.message_destinations();
+        let messages = self.make_messages(rng, accum)?;
+        Ok(SendingState {
+            destinations,
+            messages,
+        })
+    }
+
+    /// Cancels the session (e.g. when the operation was rejected by a local policy),
+    /// returning the signed notifications of the cancellation for all the other parties.
+    ///
+    /// The notification is the same for all the destinations
+    /// (see [`SendingState::broadcast`]), and carries `reason` in plain text.
+    /// A party receiving it aborts the session with [`RemoteErrorEnum::Cancelled`]
+    /// instead of waiting for the messages of the cancelling party until a timeout.
+    /// The session is consumed, so that no more messages can be created or processed.
+    pub fn cancel(
+        self,
+        rng: &mut impl CryptoRngCore,
+        reason: &str,
+    ) -> Result<SendingState<Sig, Verifier>, LocalError> {
+        let (round_num, is_echo_round) = self.current_round();
+        let rng = &mut self
+            .context
+            .rng(rng, round_num, is_echo_round, b"cancel", None);
+        let header = self
+            .context
+            .message_header::<Res, F>(round_num, MessageType::Cancel, None);
+        let message = VerifiedMessage::new(rng, &self.context.signer, header, reason.as_bytes())?
+            .into_unverified();
+
+        let destinations = self
+            .context
+            .verifiers
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.context.party_idx.as_usize())
+            .map(|(_, verifier)| verifier.clone())
+            .collect::<Vec<_>>();
+        let messages = destinations
+            .iter()
+            .map(|destination| (destination.clone(), CombinedMessage::One(message.clone())))
+            .collect();
+        Ok(SendingState {
+            destinations,
+            messages,
+        })
+    }
+
+    /// Creates a message for the given destination like [`make_message`](Self::make_message) does,
+    /// and applies the given misbehavior to it.
+    ///
+    /// Returns `None` if the misbehavior is [`Misbehavior::Silence`](super::Misbehavior::Silence).
+    /// The artifact is the same as for the honest message.
+    #[cfg(feature = "testing")]
+    #[allow(clippy::type_complexity)]
+    pub fn make_malicious_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        destination: &Verifier,
+        misbehavior: super::Misbehavior,
+    ) -> Result<Option<(CombinedMessage<Sig>, Artifact<Verifier>)>, LocalError> {
+        let (message, artifact) = self.make_message(rng, destination)?;
+        let message = misbehavior.apply(rng, &self.context.signer, message)?;
+        Ok(message.map(|message| (message, artifact)))
+    }
+
     fn route_message(
         &self,
         from: &Verifier,
@@ -398,12 +882,68 @@ where
         message_for.map_err(|err| {
             Error::Remote(RemoteError {
                 party: from.clone(),
+                context: self.error_context(),
                 error: err,
             })
         })
     }
 
+    /// Splits a message created by [`make_message`](Self::make_message) into signed fragments
+    /// carrying at most `max_data_size` bytes of the serialized message each,
+    /// for the transports with a limited payload size.
+    ///
+    /// The receiving party puts them back together with the reassembler
+    /// returned by [`make_reassembler`](Self::make_reassembler).
+    pub fn make_fragments(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        message: &CombinedMessage<Sig>,
+        max_data_size: usize,
+    ) -> Result<Vec<MessageFragment<Sig>>, LocalError> {
+        let message_bytes = F::serialize(message)?;
+
+        // The signatures of the fragments of different messages must not share the randomness.
+        let operation = [
+            b"make_fragments".as_slice(),
+            fragmented_message_hash(&message_bytes).as_ref(),
+        ]
+        .concat();
+        let (round_num, is_echo_round) = self.current_round();
+        let rng = &mut self
+            .context
+            .rng(rng, round_num, is_echo_round, &operation, None);
+
+        MessageFragment::split(
+            rng,
+            &self.context.signer,
+            &self.context.session_id,
+            &message_bytes,
+            max_data_size,
+        )
+    }
+
+    /// Creates a reassembler for the message fragments sent by the other parties in this session.
+    ///
+    /// The same reassembler can be used in all the rounds.
+    pub fn make_reassembler(&self) -> MessageReassembler<Sig, Verifier, F> {
+        MessageReassembler::new(&self.context.session_id, &self.context.verifiers)
+    }
+
     /// Perform quick checks on a received message.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                protocol = Res::NAME,
+                round = self.current_round().0,
+                echo = self.current_round().1,
+                from = ?from,
+            ),
+            err(level = "warn", Debug)
+        )
+    )]
     pub fn preprocess_message(
         &self,
         accum: &mut RoundAccumulator<Sig>,
@@ -413,26 +953,36 @@ where
         let checked = message.check().map_err(|msg| {
             Error::Remote(RemoteError {
                 party: from.clone(),
+                context: self.error_context(),
                 error: RemoteErrorEnum::InvalidContents(msg),
             })
         })?;
 
+        // Checked first, since the other fields may have a different meaning in another version.
+        if MessageVersion::from_u8(checked.version()) != Some(MessageVersion::CURRENT) {
+            return Err(Error::Remote(RemoteError {
+                party: from.clone(),
+                context: self.error_context(),
+                error: RemoteErrorEnum::UnsupportedVersion(checked.version()),
+            }));
+        }
+
         // This is an unprovable fault (may be a replay attack)
         if checked.session_id() != &self.context.session_id {
             return Err(Error::Remote(RemoteError {
                 party: from.clone(),
+                context: self.error_context(),
                 error: RemoteErrorEnum::UnexpectedSessionId,
             }));
         }
 
-        let message_for = self.route_message(from, &checked)?;
-
-        let verified_message = checked.verify(from).map_err(|err| {
-            Error::Remote(RemoteError {
+        if checked.format() != F::ID {
+            return Err(Error::Remote(RemoteError {
                 party: from.clone(),
-                error: RemoteErrorEnum::InvalidSignature(err),
-            })
-        })?;
+                context: self.error_context(),
+                error: RemoteErrorEnum::UnexpectedFormat,
+            }));
+        }
 
         let from_idx = *self
             .context
@@ -448,6 +998,76 @@ where
             )));
         }
 
+        // A correctly signed message with a different header may be a replay
+        // from another protocol, another party, or a direct message intended for someone else.
+        checked
+            .check_header(
+                Res::NAME,
+                from_idx,
+                self.context.party_idx,
+                self.context.encryption.is_some(),
+            )
+            .map_err(|error| {
+                Error::Remote(RemoteError {
+                    party: from.clone(),
+                    context: self.error_context(),
+                    error,
+                })
+            })?;
+
+        // A cancellation ends the session regardless of the round it was sent in.
+        if checked.is_cancel() {
+            let verified_message = checked.verify(from).map_err(|err| {
+                Error::Remote(RemoteError {
+                    party: from.clone(),
+                    context: self.error_context(),
+                    error: RemoteErrorEnum::InvalidSignature(err),
+                })
+            })?;
+            let reason = verified_message.cancellation_reason().unwrap_or_default();
+            return Err(Error::Remote(RemoteError {
+                party: from.clone(),
+                context: self.error_context(),
+                error: RemoteErrorEnum::Cancelled(reason),
+            }));
+        }
+
+        let message_for = self.route_message(from, &checked)?;
+
+        // The first message received from a party for a round is binding.
+        // Its copies (e.g. re-sent by the transport) are ignored without verifying them again.
+        if let Some(first) = accum.first_message(&message_for, from_idx) {
+            if checked.is_same_as(first) {
+                return Ok(None);
+            }
+        }
+
+        let verified_message = checked.verify(from).map_err(|err| {
+            Error::Remote(RemoteError {
+                party: from.clone(),
+                context: self.error_context(),
+                error: RemoteErrorEnum::InvalidSignature(err),
+            })
+        })?;
+
+        // A different message signed by the same party for the same round proves that it equivocated.
+        if let Some(first) = accum.first_message(&message_for, from_idx) {
+            if !first.conflicts_with(&verified_message) {
+                return Err(Error::Remote(RemoteError {
+                    party: from.clone(),
+                    context: self.error_context(),
+                    error: RemoteErrorEnum::DuplicateMessage,
+                }));
+            }
+            let evidence =
+                EquivocationEvidence::new(first, &verified_message).map_err(Error::Local)?;
+            return Err(Error::Provable {
+                party: from.clone(),
+                context: self.error_context(),
+                error: ProvableError::Equivocation(evidence),
+            });
+        }
+
         let preprocessed = PreprocessedMessage {
             from_idx,
             message: verified_message,
@@ -455,21 +1075,10 @@ where
 
         Ok(match message_for {
             MessageFor::ThisRound => {
-                if accum.is_already_processed(&preprocessed) {
-                    return Err(Error::Remote(RemoteError {
-                        party: from.clone(),
-                        error: RemoteErrorEnum::DuplicateMessage,
-                    }));
-                }
+                accum.add_preprocessed_message(&preprocessed);
                 Some(preprocessed)
             }
             MessageFor::NextRound => {
-                if accum.is_already_cached(&preprocessed) {
-                    return Err(Error::Remote(RemoteError {
-                        party: from.clone(),
-                        error: RemoteErrorEnum::DuplicateMessage,
-                    }));
-                }
                 accum.add_cached_message(preprocessed);
                 None
             }
@@ -477,6 +1086,20 @@ where
     }
 
     /// Process a received message from another party.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                protocol = Res::NAME,
+                round = self.current_round().0,
+                echo = self.current_round().1,
+                from_idx = preprocessed.from_idx.as_usize(),
+            ),
+            err(level = "warn", Debug)
+        )
+    )]
     pub fn process_message(
         &self,
         preprocessed: PreprocessedMessage<Sig>,
@@ -486,57 +1109,277 @@ where
         let message = preprocessed.message;
         match &self.tp {
             SessionType::Normal { this_round, .. } => {
+                let decrypted_payload = self.decrypt_direct_payload(&from, &message)?;
+                let direct_payload = decrypted_payload
+                    .as_deref()
+                    .or_else(|| message.direct_payload());
                 let result = this_round.verify_message(
                     from_idx,
                     message.broadcast_payload(),
-                    message.direct_payload(),
+                    direct_payload,
                 );
-                let payload = wrap_receive_result(&from, result)?;
+                let payload = wrap_receive_result(&from, self.error_context(), result)?;
                 Ok(ProcessedMessage {
-                    from: from.clone(),
+                    from,
                     from_idx,
                     message: ProcessedMessageEnum::Payload { payload, message },
                 })
             }
             SessionType::Echo { echo_round, .. } => {
                 echo_round
-                    .verify_broadcast(from_idx, message.echo_payload().unwrap())
-                    .map_err(|err| Error::Provable {
-                        party: from.clone(),
-                        error: ProvableError::Echo(err),
+                    .verify_broadcast::<F, _>(
+                        from_idx,
+                        message.echo_payload().unwrap(),
+                        &self.context.verifiers,
+                    )
+                    .map_err(|fault| Error::Provable {
+                        party: self.context.verifiers[fault.party.as_usize()].clone(),
+                        context: self.error_context(),
+                        error: ProvableError::Echo(fault.error),
                     })?;
                 Ok(ProcessedMessage {
                     from: from.clone(),
                     from_idx,
-                    message: ProcessedMessageEnum::Bc,
+                    message: ProcessedMessageEnum::Bc { message },
                 })
             }
         }
     }
 
+    /// Processes several messages received from other parties,
+    /// returning the same results as [`process_message`](Self::process_message) for each of them,
+    /// in the same order.
+    ///
+    /// With the `rayon` feature enabled, the messages of a regular round are verified
+    /// (which includes checking the ZK proofs they carry) in the threads of the `rayon` pool.
+    #[allow(clippy::type_complexity)]
+    pub fn process_messages(
+        &self,
+        preprocessed: Vec<PreprocessedMessage<Sig>>,
+    ) -> Vec<Result<ProcessedMessage<Sig, Verifier>, Error<Res, Verifier>>> {
+        let this_round = match &self.tp {
+            SessionType::Normal { this_round, .. } => this_round,
+            SessionType::Echo { .. } => {
+                return preprocessed
+                    .into_iter()
+                    .map(|preprocessed| self.process_message(preprocessed))
+                    .collect();
+            }
+        };
+
+        // The decryption is cheap compared to the verification, so it is done beforehand.
+        let decrypted = preprocessed
+            .into_iter()
+            .map(|preprocessed| {
+                let from = self.context.verifiers[preprocessed.from_idx.as_usize()].clone();
+                let decrypted_payload =
+                    self.decrypt_direct_payload(&from, &preprocessed.message)?;
+                Ok((from, preprocessed, decrypted_payload))
+            })
+            .collect::<Vec<Result<_, Error<Res, Verifier>>>>();
+
+        let inputs = decrypted
+            .iter()
+            .flatten()
+            .map(|(_from, preprocessed, decrypted_payload)| {
+                let message = &preprocessed.message;
+                (
+                    preprocessed.from_idx,
+                    message.broadcast_payload(),
+                    decrypted_payload
+                        .as_deref()
+                        .or_else(|| message.direct_payload()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut results = map(inputs, |(from_idx, broadcast_payload, direct_payload)| {
+            this_round.verify_message(from_idx, broadcast_payload, direct_payload)
+        })
+        .into_iter();
+
+        decrypted
+            .into_iter()
+            .map(|decrypted| {
+                let (from, preprocessed, _decrypted_payload) = decrypted?;
+                let result = results
+                    .next()
+                    .expect("there is a result for each decrypted message");
+                let payload = wrap_receive_result(&from, self.error_context(), result)?;
+                Ok(ProcessedMessage {
+                    from,
+                    from_idx: preprocessed.from_idx,
+                    message: ProcessedMessageEnum::Payload {
+                        payload,
+                        message: preprocessed.message,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Decrypts the direct part of the message, if the direct message encryption is enabled.
+    fn decrypt_direct_payload(
+        &self,
+        from: &Verifier,
+        message: &VerifiedCombinedMessage<Sig>,
+    ) -> Result<Option<Box<[u8]>>, Error<Res, Verifier>> {
+        match (&self.context.encryption, message.direct_message()) {
+            (Some(keys), Some(direct)) => keys
+                .decrypt(direct.as_unverified().header(), direct.payload())
+                .map(Some)
+                .map_err(|err| {
+                    Error::Remote(RemoteError {
+                        party: from.clone(),
+                        context: self.error_context(),
+                        error: RemoteErrorEnum::CannotDecrypt(err),
+                    })
+                }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Handles a message received from another party,
+    /// and finalizes the round as soon as it has all the messages it needs.
+    ///
+    /// This is a shortcut for calling [`preprocess_message`](Self::preprocess_message),
+    /// [`process_message`](Self::process_message), [`RoundAccumulator::add_processed_message`],
+    /// [`can_finalize`](Self::can_finalize) and [`finalize_round`](Self::finalize_round)
+    /// in sequence, for the integrations that do not process the messages in parallel.
+    /// As with [`preprocess_message`](Self::preprocess_message), a message for the next round
+    /// is kept in the accumulator and returned in [`FinalizeOutcome::AnotherRound`].
+    /// Note that processing these cached messages at the start of the next round
+    /// may already make it ready to be finalized, before any new message arrives.
+    #[allow(clippy::type_complexity)]
+    pub fn receive_and_maybe_finalize(
+        self,
+        rng: &mut impl CryptoRngCore,
+        accum: RoundAccumulator<Sig>,
+        from: &Verifier,
+        message: CombinedMessage<Sig>,
+    ) -> Result<ReceiveOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>> {
+        let mut accum = accum;
+        if let Some(preprocessed) = self.preprocess_message(&mut accum, from, message)? {
+            let processed = self.process_message(preprocessed)?;
+            accum
+                .add_processed_message(processed)
+                .map_err(Error::Local)?
+                .map_err(Error::Remote)?;
+        }
+
+        if !self.can_finalize(&accum).map_err(Error::Local)? {
+            return Ok(ReceiveOutcome::Pending {
+                session: self,
+                accum,
+            });
+        }
+        self.finalize_round(rng, accum)
+            .map(ReceiveOutcome::Finalized)
+    }
+
     /// Try to finalize the round.
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                protocol = Res::NAME,
+                round = self.current_round().0,
+                echo = self.current_round().1,
+            ),
+            err(level = "warn", Debug)
+        )
+    )]
     pub fn finalize_round(
         self,
         rng: &mut impl CryptoRngCore,
         accum: RoundAccumulator<Sig>,
-    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier>, Error<Res, Verifier>> {
-        match self.tp {
+    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>> {
+        let (round_num, is_echo_round) = self.current_round();
+        let mut rng = self
+            .context
+            .rng(rng, round_num, is_echo_round, b"finalize_round", None);
+        let outcome = match self.tp {
             SessionType::Normal { this_round, .. } => {
-                Self::finalize_regular_round(self.context, this_round, rng, accum)
+                Self::finalize_regular_round(self.context, this_round, &mut rng, accum)
             }
             SessionType::Echo { next_round, .. } => {
-                Self::finalize_bc_round(self.context, next_round, rng, accum)
+                Self::finalize_bc_round(self.context, next_round, &mut rng, accum)
             }
+        };
+
+        #[cfg(feature = "tracing")]
+        match &outcome {
+            Ok(FinalizeOutcome::Success(_)) => tracing::info!("session finished"),
+            Ok(FinalizeOutcome::AnotherRound {
+                session,
+                cached_messages,
+            }) => {
+                let (round, echo) = session.current_round();
+                tracing::debug!(
+                    round,
+                    echo,
+                    cached_messages = cached_messages.len(),
+                    "starting the next round"
+                );
+            }
+            Err(_) => {}
+        }
+
+        outcome
+    }
+
+    /// Finalize the round after a deadline has passed without receiving messages
+    /// from the parties in `absent`.
+    ///
+    /// Each of `absent` must be one of the parties returned by [`Self::missing_messages`].
+    /// If the round can be finalized without their messages (that is, it only requires messages
+    /// from a threshold of the parties), it is finalized as with [`Self::finalize_round`].
+    /// Otherwise, [`Error::Unresponsive`] is returned accusing the absent parties.
+    #[allow(clippy::type_complexity)]
+    pub fn finalize_with_absent(
+        self,
+        rng: &mut impl CryptoRngCore,
+        accum: RoundAccumulator<Sig>,
+        absent: &[Verifier],
+    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>> {
+        let missing = self.missing_messages(&accum).map_err(Error::Local)?;
+        if let Some(party) = absent.iter().find(|party| !missing.contains(party)) {
+            return Err(Error::Local(LocalError(format!(
+                "{party:?} is declared absent, but its message is not missing"
+            ))));
+        }
+
+        if self.can_finalize(&accum).map_err(Error::Local)? {
+            return self.finalize_round(rng, accum);
+        }
+
+        if absent.is_empty() {
+            return Err(Error::Local(LocalError(
+                "Cannot finalize the round: no parties are declared absent".into(),
+            )));
         }
+
+        Err(Error::Unresponsive {
+            parties: absent.to_vec(),
+            context: self.error_context(),
+        })
     }
 
+    #[allow(clippy::type_complexity)]
     fn finalize_regular_round(
         context: Context<Signer, Verifier>,
-        round: Box<dyn DynFinalizable<Res>>,
+        round: Box<dyn DynFinalizable<Res, F>>,
         rng: &mut impl CryptoRngCore,
         accum: RoundAccumulator<Sig>,
-    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier>, Error<Res, Verifier>> {
+    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>> {
         let requires_echo = round.requires_echo();
+        let error_context = ErrorContext {
+            protocol: Res::NAME,
+            round: round.round_num(),
+            is_echo_round: false,
+        };
 
         let outcome = round
             .finalize(rng, accum.processed)
@@ -545,7 +1388,18 @@ where
                     rounds::FinalizeError::Init(err) => Error::Local(LocalError(format!(
                         "Failed to initialize the protocol: {err:?}"
                     ))),
-                    rounds::FinalizeError::Proof(proof) => Error::Proof { proof },
+                    rounds::FinalizeError::Local(msg) => {
+                        Error::Local(LocalError(format!("Failed to finalize: {msg}")))
+                    }
+                    rounds::FinalizeError::Proof(proof) => Error::Proof {
+                        context: error_context,
+                        proof,
+                    },
+                    rounds::FinalizeError::Provable { party, error } => Error::Provable {
+                        party: context.verifiers[party.as_usize()].clone(),
+                        context: error_context,
+                        error: ProvableError::Protocol(error),
+                    },
                 },
                 type_erased::FinalizeError::Accumulator(err) => {
                     Error::Local(LocalError(format!("Failed to finalize: {err:?}")))
@@ -588,12 +1442,13 @@ where
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn finalize_bc_round(
         context: Context<Signer, Verifier>,
-        round: Box<dyn DynFinalizable<Res>>,
+        round: Box<dyn DynFinalizable<Res, F>>,
         rng: &mut impl CryptoRngCore,
         accum: RoundAccumulator<Sig>,
-    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier>, Error<Res, Verifier>> {
+    ) -> Result<FinalizeOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>> {
         let echo_accum = accum.echo_accum.ok_or(Error::Local(LocalError(
             "The accumulator is in the invalid state for the echo round".into(),
         )))?;
@@ -613,20 +1468,25 @@ where
 
 /// A mutable accumulator created for each round to assemble processed messages from other parties.
 pub struct RoundAccumulator<Sig> {
+    /// The first message received from each party for this round (whether processed or not).
+    first_messages: BTreeMap<PartyIdx, VerifiedCombinedMessage<Sig>>,
     received_messages: Vec<(PartyIdx, VerifiedCombinedMessage<Sig>)>,
     processed: DynRoundAccum,
     cached_messages: BTreeMap<PartyIdx, PreprocessedMessage<Sig>>,
     echo_accum: Option<EchoAccum>,
+    context: ErrorContext,
 }
 
 impl<Sig> RoundAccumulator<Sig> {
-    fn new(num_parties: usize, party_idx: PartyIdx, is_echo_round: bool) -> Self {
+    fn new(num_parties: usize, party_idx: PartyIdx, context: ErrorContext) -> Self {
         // TODO (#68): can return an error if party_idx is out of bounds
         Self {
+            first_messages: BTreeMap::new(),
             received_messages: Vec::new(),
             processed: DynRoundAccum::new(),
             cached_messages: BTreeMap::new(),
-            echo_accum: if is_echo_round {
+            context,
+            echo_accum: if context.is_echo_round {
                 Some(EchoAccum::new(num_parties, party_idx))
             } else {
                 None
@@ -653,7 +1513,18 @@ impl<Sig> RoundAccumulator<Sig> {
     pub fn add_processed_message<Verifier>(
         &mut self,
         pm: ProcessedMessage<Sig, Verifier>,
-    ) -> Result<Result<(), RemoteError<Verifier>>, LocalError> {
+    ) -> Result<Result<(), RemoteError<Verifier>>, LocalError>
+    where
+        Sig: Clone,
+    {
+        // The messages cached in the previous round are processed without being preprocessed
+        // with this accumulator, so they have to be recorded here.
+        let (ProcessedMessageEnum::Payload { message, .. } | ProcessedMessageEnum::Bc { message }) =
+            &pm.message;
+        self.first_messages
+            .entry(pm.from_idx)
+            .or_insert_with(|| message.clone());
+
         match pm.message {
             ProcessedMessageEnum::Payload { payload, message } => {
                 if let Err(AccumAddError::SlotTaken) =
@@ -661,16 +1532,18 @@ impl<Sig> RoundAccumulator<Sig> {
                 {
                     return Ok(Err(RemoteError {
                         party: pm.from,
+                        context: self.context,
                         error: RemoteErrorEnum::DuplicateMessage,
                     }));
                 }
                 self.received_messages.push((pm.from_idx, message));
             }
-            ProcessedMessageEnum::Bc => match &mut self.echo_accum {
+            ProcessedMessageEnum::Bc { .. } => match &mut self.echo_accum {
                 Some(accum) => {
                     if accum.add_echo_received(pm.from_idx).is_none() {
                         return Ok(Err(RemoteError {
                             party: pm.from,
+                            context: self.context,
                             error: RemoteErrorEnum::DuplicateMessage,
                         }));
                     }
@@ -681,19 +1554,26 @@ impl<Sig> RoundAccumulator<Sig> {
         Ok(Ok(()))
     }
 
-    fn is_already_processed(&self, preprocessed: &PreprocessedMessage<Sig>) -> bool {
-        if preprocessed.message.is_echo() {
-            self.echo_accum
-                .as_ref()
-                .unwrap()
-                .contains(preprocessed.from_idx)
-        } else {
-            self.processed.contains(preprocessed.from_idx)
+    fn first_message(
+        &self,
+        message_for: &MessageFor,
+        from_idx: PartyIdx,
+    ) -> Option<&VerifiedCombinedMessage<Sig>> {
+        match message_for {
+            MessageFor::ThisRound => self.first_messages.get(&from_idx),
+            MessageFor::NextRound => self
+                .cached_messages
+                .get(&from_idx)
+                .map(|preprocessed| &preprocessed.message),
         }
     }
 
-    fn is_already_cached(&self, preprocessed: &PreprocessedMessage<Sig>) -> bool {
-        self.cached_messages.contains_key(&preprocessed.from_idx)
+    fn add_preprocessed_message(&mut self, preprocessed: &PreprocessedMessage<Sig>)
+    where
+        Sig: Clone,
+    {
+        self.first_messages
+            .insert(preprocessed.from_idx, preprocessed.message.clone());
     }
 
     fn add_cached_message(&mut self, preprocessed: PreprocessedMessage<Sig>) {
@@ -710,6 +1590,103 @@ pub struct Artifact<Verifier> {
     artifact: DynArtifact,
 }
 
+/// The outgoing messages of a round, created by [`Session::make_sending_state`].
+///
+/// The messages can be sent as a whole with [`messages`](Self::messages),
+/// or split with [`parts`](Self::parts) into the part that is the same for all the destinations
+/// and the parts for a single destination, e.g. to send the former over a gossip layer
+/// and the latter over point-to-point links.
+/// The receiver puts the parts back together with [`CombinedMessage::from_parts`].
+pub struct SendingState<Sig, Verifier> {
+    destinations: Vec<Verifier>,
+    messages: Vec<(Verifier, CombinedMessage<Sig>)>,
+}
+
+/// A part of the outgoing messages of a round (see [`SendingState::parts`]).
+#[derive(Debug, Clone, Copy)]
+pub enum OutgoingPart<'a, Sig, Verifier> {
+    /// The message to be delivered to all of `destinations`:
+    /// the broadcast part of the messages, the echo message in an echo round,
+    /// or the notification of a cancellation.
+    Broadcast {
+        /// The destinations of the round.
+        destinations: &'a [Verifier],
+        /// The signed message.
+        message: &'a SignedMessage<Sig>,
+    },
+    /// The message to be delivered only to `destination`.
+    Direct {
+        /// The destination of the message.
+        destination: &'a Verifier,
+        /// The signed message.
+        message: &'a SignedMessage<Sig>,
+    },
+}
+
+impl<Sig, Verifier: PartialEq> SendingState<Sig, Verifier> {
+    /// Returns the messages of the round along with their destinations.
+    ///
+    /// Can be called any number of times, returning the same messages each time.
+    pub fn messages(&self) -> &[(Verifier, CombinedMessage<Sig>)] {
+        &self.messages
+    }
+
+    /// Returns the message for the given destination, if it is one of the destinations of the round.
+    pub fn message(&self, destination: &Verifier) -> Option<&CombinedMessage<Sig>> {
+        self.messages
+            .iter()
+            .find(|(verifier, _)| verifier == destination)
+            .map(|(_, message)| message)
+    }
+
+    /// Returns the message to be delivered to all the destinations, if the round has one:
+    /// the broadcast part of the messages, the echo message in an echo round,
+    /// or the notification created by [`Session::cancel`].
+    ///
+    /// The echo messages for different destinations only differ in the signatures,
+    /// so any of them can be delivered to all the destinations.
+    pub fn broadcast(&self) -> Option<&SignedMessage<Sig>> {
+        self.messages
+            .first()
+            .and_then(|(_, message)| match message {
+                CombinedMessage::Both { broadcast, .. } => Some(broadcast),
+                CombinedMessage::One(message) => {
+                    (message.message_type() != MessageType::Direct).then_some(message)
+                }
+            })
+    }
+
+    /// Returns the messages to be delivered only to one of the destinations,
+    /// along with the destinations.
+    pub fn direct_messages(&self) -> impl Iterator<Item = (&Verifier, &SignedMessage<Sig>)> {
+        self.messages
+            .iter()
+            .filter_map(|(destination, message)| match message {
+                CombinedMessage::Both { direct, .. } => Some((destination, direct)),
+                CombinedMessage::One(message) => (message.message_type() == MessageType::Direct)
+                    .then_some((destination, message)),
+            })
+    }
+
+    /// Returns the parts of the messages: the [`broadcast`](Self::broadcast) one first, if any,
+    /// followed by the [direct ones](Self::direct_messages).
+    pub fn parts(&self) -> impl Iterator<Item = OutgoingPart<'_, Sig, Verifier>> {
+        let broadcast = self.broadcast().map(|message| OutgoingPart::Broadcast {
+            destinations: &self.destinations,
+            message,
+        });
+        broadcast
+            .into_iter()
+            .chain(
+                self.direct_messages()
+                    .map(|(destination, message)| OutgoingPart::Direct {
+                        destination,
+                        message,
+                    }),
+            )
+    }
+}
+
 /// A message that passed initial validity checks.
 pub struct PreprocessedMessage<Sig> {
     from_idx: PartyIdx,
@@ -728,5 +1705,7 @@ enum ProcessedMessageEnum<Sig> {
         payload: DynPayload,
         message: VerifiedCombinedMessage<Sig>,
     },
-    Bc,
+    Bc {
+        message: VerifiedCombinedMessage<Sig>,
+    },
 }
@@ -1,21 +1,54 @@
 use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
 
+use displaydoc::Display;
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
 use signature::hazmat::{PrehashVerifier, RandomizedPrehashSigner};
 
+use super::combined_message::CombinedMessage;
 use super::error::LocalError;
+use super::format::{Bincode, MessageFormat};
+use super::type_erased::serialize_message;
+use crate::rounds::PartyIdx;
 use crate::tools::hashing::{Chain, Hash, HashOutput, Hashable};
 use crate::tools::serde_bytes;
+use crate::tools::serialized_size::{bytes_size, MaxSerializedSize, VARIANT_INDEX_SIZE};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+/// The identifier of a session, shared by all of its participants
+/// (see [`Session::session_id`](crate::Session::session_id)).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
 pub struct SessionId(HashOutput);
 
 impl SessionId {
-    pub(crate) fn from_seed(seed: &[u8]) -> Self {
-        Self(Hash::new_with_dst(b"SessionId").chain(&seed).finalize())
+    /// Derives the session ID from the shared randomness, the name of the protocol,
+    /// the verifiers of all the parties (in the order of their indices),
+    /// and the epoch of the key share used in the session (0 if there is none).
+    ///
+    /// Thus the messages of a session cannot be replayed in another one
+    /// unless all of these match, even if the shared randomness is reused.
+    pub(crate) fn new<Verifier: Serialize>(
+        shared_randomness: &[u8],
+        protocol: &str,
+        verifiers: &[Verifier],
+        epoch: u64,
+    ) -> Result<Self, LocalError> {
+        let verifiers = verifiers
+            .iter()
+            .map(serialize_message)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self(
+            Hash::new_with_dst(b"SessionId")
+                .chain(&shared_randomness)
+                .chain(&protocol.as_bytes())
+                .chain(&verifiers)
+                .chain(&epoch)
+                .finalize(),
+        ))
     }
 }
 
@@ -25,16 +58,44 @@ impl Hashable for SessionId {
     }
 }
 
-fn message_hash(
-    session_id: &SessionId,
-    round: u8,
-    message_type: MessageType,
-    payload: &[u8],
-) -> HashOutput {
+/// The version of the message format: the layout of the signed header
+/// and the encoding of the payloads of the protocols.
+///
+/// It is the first field of every message header, so that a party running
+/// a version of the library with an incompatible format rejects the messages
+/// with [`RemoteErrorEnum::UnsupportedVersion`](super::RemoteErrorEnum::UnsupportedVersion)
+/// in the first round, instead of failing to deserialize a payload later on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum MessageVersion {
+    /// The format with the signed header carrying the protocol, the sender, the recipient
+    /// and whether the payload is encrypted.
+    V1,
+}
+
+impl MessageVersion {
+    /// The version of the messages created by this library.
+    pub const CURRENT: Self = Self::V1;
+
+    /// Returns the identifier of the version, as sent in the messages.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+        }
+    }
+
+    /// Returns the version with the given identifier, if it is known to this library.
+    pub fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::V1),
+            _ => None,
+        }
+    }
+}
+
+fn message_hash(header: &MessageHeader, payload: &[u8]) -> HashOutput {
     Hash::new_with_dst(b"SignedMessage")
-        .chain(session_id)
-        .chain(&round)
-        .chain(&message_type)
+        .chain(header)
         .chain(&payload)
         .finalize()
 }
@@ -42,11 +103,15 @@ fn message_hash(
 /// Protocol message type.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
 pub enum MessageType {
+    /// The part of the round that is the same for all the destinations.
     Broadcast,
     /// Regular messaging part of the round.
     Direct,
     /// A service message for echo-broadcast.
     Echo,
+    /// A notification that the sender has cancelled the session
+    /// (see [`Session::cancel`](super::Session::cancel)), with the reason as the payload.
+    Cancel,
 }
 
 impl Hashable for MessageType {
@@ -55,17 +120,76 @@ impl Hashable for MessageType {
             Self::Broadcast => 0,
             Self::Direct => 1,
             Self::Echo => 2,
+            Self::Cancel => 3,
         };
         digest.chain(&value)
     }
 }
 
+/// The metadata of a message, signed along with its payload.
+///
+/// Since the signature covers the sender and the intended recipient as well as the session,
+/// the protocol and the round, a valid message cannot be replayed in another context.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub(crate) struct MessageHeader {
+    /// Kept first, so that it can be read regardless of the layout of the rest of the message.
+    pub(crate) version: u8,
+    pub(crate) session_id: SessionId,
+    pub(crate) protocol: Box<str>,
+    pub(crate) round: u8,
+    pub(crate) message_type: MessageType,
+    pub(crate) sender: PartyIdx,
+    /// Only set for direct messages.
+    pub(crate) recipient: Option<PartyIdx>,
+    pub(crate) format: u8,
+    /// Only set for direct messages, if the payload is encrypted to the recipient.
+    pub(crate) encrypted: bool,
+}
+
+impl Hashable for MessageHeader {
+    fn chain<C: Chain>(&self, digest: C) -> C {
+        let digest = digest
+            .chain(&self.version)
+            .chain(&self.session_id)
+            .chain(&self.protocol.as_bytes())
+            .chain(&self.round)
+            .chain(&self.message_type)
+            .chain(&self.sender);
+        let digest = match &self.recipient {
+            None => digest.chain(&0u8),
+            Some(recipient) => digest.chain(&1u8).chain(recipient),
+        };
+        digest.chain(&self.format).chain(&self.encrypted)
+    }
+}
+
+/// Returns the upper bound on the size of a serialized [`SignedMessage`]
+/// of the protocol `protocol` with a payload of the given size.
+pub(crate) fn max_signed_message_size(
+    protocol: &str,
+    payload_size: usize,
+    max_signature_size: usize,
+) -> usize {
+    // The version, the session ID, the protocol name, the round number, the message type,
+    // the sender and the recipient, the format ID, the encryption flag, the payload,
+    // and the signature.
+    1 + HashOutput::max_serialized_size(0)
+        + bytes_size(protocol.len())
+        + 1
+        + VARIANT_INDEX_SIZE
+        + PartyIdx::max_serialized_size(0)
+        + 1
+        + PartyIdx::max_serialized_size(0)
+        + 1
+        + 1
+        + bytes_size(payload_size)
+        + max_signature_size
+}
+
 /// A (yet) unverified message from a round that includes the payload signature.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct SignedMessage<Sig> {
-    session_id: SessionId,
-    round: u8,
-    message_type: MessageType,
+    header: MessageHeader,
     #[serde(with = "serde_bytes::as_base64")]
     payload: Box<[u8]>,
     signature: Sig,
@@ -78,40 +202,99 @@ impl<Sig> SignedMessage<Sig> {
     ) -> Result<VerifiedMessage<Sig>, String> {
         verifier
             .verify_prehash(
-                message_hash(
-                    &self.session_id,
-                    self.round,
-                    self.message_type,
-                    &self.payload,
-                )
-                .as_ref(),
+                message_hash(&self.header, &self.payload).as_ref(),
                 &self.signature,
             )
             .map_err(|err| format!("{:?}", err))?;
         Ok(VerifiedMessage(self))
     }
 
+    pub(crate) fn header(&self) -> &MessageHeader {
+        &self.header
+    }
+
+    /// The identifier of the [`MessageVersion`] of this message.
+    pub fn version(&self) -> u8 {
+        self.header.version
+    }
+
     /// The session ID of this message.
     pub fn session_id(&self) -> &SessionId {
-        &self.session_id
+        &self.header.session_id
+    }
+
+    /// The name of the protocol this message belongs to (see [`ProtocolResult::NAME`](crate::ProtocolResult::NAME)).
+    pub fn protocol(&self) -> &str {
+        &self.header.protocol
     }
 
     /// The round of this message.
     pub fn round(&self) -> u8 {
-        self.round
+        self.header.round
     }
 
     /// The message type.
     pub fn message_type(&self) -> MessageType {
-        self.message_type
+        self.header.message_type
+    }
+
+    /// The index of the party that sent this message.
+    pub fn sender(&self) -> usize {
+        self.header.sender.as_usize()
+    }
+
+    /// The index of the party this message is intended for, if it is a direct message.
+    pub fn recipient(&self) -> Option<usize> {
+        self.header.recipient.map(|idx| idx.as_usize())
+    }
+
+    /// The identifier of the [`MessageFormat`](crate::sessions::MessageFormat) of the payload.
+    pub fn format(&self) -> u8 {
+        self.header.format
+    }
+
+    /// Whether the payload is encrypted to the recipient
+    /// (see [`DirectMessageEncryption`](crate::sessions::DirectMessageEncryption)).
+    pub fn is_encrypted(&self) -> bool {
+        self.header.encrypted
     }
 
     /// Compares the "significant" part of the messages (that is, everything but signatures)
     pub fn is_same_as(&self, other: &Self) -> bool {
-        self.session_id == other.session_id
-            && self.round == other.round
-            && self.message_type == other.message_type
-            && self.payload == other.payload
+        self.header == other.header && self.payload == other.payload
+    }
+}
+
+impl<Sig: Serialize> SignedMessage<Sig> {
+    /// Returns the canonical encoding of this message.
+    ///
+    /// Regardless of the way the messages are serialized for transport,
+    /// this encoding (`bincode` with the default options) is fixed,
+    /// so it can be used to hash or compare the messages, e.g. when verifying evidence.
+    /// Deserializing it with `bincode` and encoding the result again produces the same bytes.
+    pub fn canonical_bytes(&self) -> Result<Box<[u8]>, LocalError> {
+        serialize_message(self)
+    }
+}
+
+#[cfg(feature = "testing")]
+impl<Sig> SignedMessage<Sig> {
+    pub(crate) fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.payload
+    }
+
+    pub(crate) fn header_mut(&mut self) -> &mut MessageHeader {
+        &mut self.header
+    }
+
+    /// Replaces the signature with a new one for the current contents of the message.
+    pub(crate) fn signed(
+        self,
+        rng: &mut impl CryptoRngCore,
+        signer: &impl RandomizedPrehashSigner<Sig>,
+    ) -> Result<Self, LocalError> {
+        VerifiedMessage::new(rng, signer, self.header, &self.payload)
+            .map(VerifiedMessage::into_unverified)
     }
 }
 
@@ -122,27 +305,21 @@ impl<Sig> VerifiedMessage<Sig> {
     pub(crate) fn new(
         rng: &mut impl CryptoRngCore,
         signer: &impl RandomizedPrehashSigner<Sig>,
-        session_id: &SessionId,
-        round: u8,
-        message_type: MessageType,
+        header: MessageHeader,
         message_bytes: &[u8],
     ) -> Result<Self, LocalError> {
         // In order for the messages be impossible to reuse by a malicious third party,
-        // we need to sign, besides the message itself, the session and the round in this session
-        // it belongs to.
+        // we need to sign, besides the message itself, the session, the protocol and the round
+        // in this session it belongs to, the sender and the recipient,
+        // and the format of the payload, so that it cannot be decoded differently.
         // We also need the exact way we sign this to be a part of the public ABI,
         // so that these signatures could be verified by a third party.
 
         let signature = signer
-            .sign_prehash_with_rng(
-                rng,
-                message_hash(session_id, round, message_type, message_bytes).as_ref(),
-            )
+            .sign_prehash_with_rng(rng, message_hash(&header, message_bytes).as_ref())
             .map_err(|err| LocalError(err.to_string()))?;
         Ok(Self(SignedMessage {
-            session_id: *session_id,
-            round,
-            message_type,
+            header,
             payload: message_bytes.into(),
             signature,
         }))
@@ -160,3 +337,232 @@ impl<Sig> VerifiedMessage<Sig> {
         &self.0.payload
     }
 }
+
+/// Returns the hash identifying a serialized message split into fragments.
+pub(crate) fn fragmented_message_hash(message_bytes: &[u8]) -> HashOutput {
+    Hash::new_with_dst(b"FragmentedMessage")
+        .chain(&message_bytes)
+        .finalize()
+}
+
+fn fragment_hash(
+    session_id: &SessionId,
+    message_hash: &HashOutput,
+    index: u32,
+    num_fragments: u32,
+    data: &[u8],
+) -> HashOutput {
+    Hash::new_with_dst(b"MessageFragment")
+        .chain(session_id)
+        .chain(message_hash)
+        .chain(&index)
+        .chain(&num_fragments)
+        .chain(&data)
+        .finalize()
+}
+
+/// A part of a serialized [`CombinedMessage`], for the transports with a limited payload size
+/// (created by [`Session::make_fragments`](crate::Session::make_fragments)).
+///
+/// Each fragment is signed by the sender, so it can be checked on arrival,
+/// before the whole message is received.
+/// With [`Bincode`], a serialized fragment takes 96 bytes plus the size of the signature
+/// in addition to its share of the message.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MessageFragment<Sig> {
+    session_id: SessionId,
+    message_hash: HashOutput,
+    index: u32,
+    num_fragments: u32,
+    #[serde(with = "serde_bytes::as_base64")]
+    data: Box<[u8]>,
+    signature: Sig,
+}
+
+impl<Sig> MessageFragment<Sig> {
+    pub(crate) fn split(
+        rng: &mut impl CryptoRngCore,
+        signer: &impl RandomizedPrehashSigner<Sig>,
+        session_id: &SessionId,
+        message_bytes: &[u8],
+        max_data_size: usize,
+    ) -> Result<Vec<Self>, LocalError> {
+        if max_data_size == 0 {
+            return Err(LocalError("The fragment size must be positive".into()));
+        }
+
+        let message_hash = fragmented_message_hash(message_bytes);
+        let chunks = message_bytes.chunks(max_data_size);
+        let num_fragments =
+            u32::try_from(chunks.len()).map_err(|_| LocalError("Too many fragments".into()))?;
+
+        chunks
+            .enumerate()
+            .map(|(index, data)| {
+                // Cannot overflow since the number of fragments fits into `u32`.
+                let index = index as u32;
+                let signature = signer
+                    .sign_prehash_with_rng(
+                        rng,
+                        fragment_hash(session_id, &message_hash, index, num_fragments, data)
+                            .as_ref(),
+                    )
+                    .map_err(|err| LocalError(err.to_string()))?;
+                Ok(Self {
+                    session_id: *session_id,
+                    message_hash,
+                    index,
+                    num_fragments,
+                    data: data.into(),
+                    signature,
+                })
+            })
+            .collect()
+    }
+
+    fn verify(&self, verifier: &impl PrehashVerifier<Sig>) -> Result<(), String> {
+        verifier
+            .verify_prehash(
+                fragment_hash(
+                    &self.session_id,
+                    &self.message_hash,
+                    self.index,
+                    self.num_fragments,
+                    &self.data,
+                )
+                .as_ref(),
+                &self.signature,
+            )
+            .map_err(|err| format!("{:?}", err))
+    }
+
+    /// The session ID of this fragment.
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    /// The position of this fragment in the message.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The total number of fragments in the message.
+    pub fn num_fragments(&self) -> u32 {
+        self.num_fragments
+    }
+}
+
+/// Errors that can occur when reassembling a message from fragments.
+#[derive(Debug, Clone, Display)]
+pub enum FragmentError {
+    /// The fragment was sent by a party that is not one of the participants.
+    UnknownParty,
+    /// The fragment belongs to a different session.
+    UnexpectedSessionId,
+    /// The fragment signature does not match its contents: {0}.
+    InvalidSignature(String),
+    /// The fragment is inconsistent with the other fragments of the same message.
+    InconsistentFragment,
+    /// The reassembled message does not match its hash.
+    InvalidMessageHash,
+    /// Failed to deserialize the reassembled message: {0}.
+    CannotDeserialize(String),
+}
+
+impl core::error::Error for FragmentError {}
+
+struct PendingMessage {
+    num_fragments: u32,
+    fragments: BTreeMap<u32, Box<[u8]>>,
+}
+
+/// Collects the fragments received from other parties
+/// (created by [`Session::make_reassembler`](crate::Session::make_reassembler)),
+/// and returns the messages once all their fragments arrive.
+///
+/// The fragments can arrive in any order, and the fragments of different messages
+/// can be interleaved.
+pub struct MessageReassembler<Sig, Verifier, F = Bincode> {
+    session_id: SessionId,
+    verifiers: BTreeSet<Verifier>,
+    pending: BTreeMap<(Verifier, HashOutput), PendingMessage>,
+    phantom: PhantomData<fn() -> (Sig, F)>,
+}
+
+impl<Sig, Verifier, F> MessageReassembler<Sig, Verifier, F>
+where
+    Sig: for<'de> Deserialize<'de>,
+    Verifier: Clone + Ord + PrehashVerifier<Sig>,
+    F: MessageFormat,
+{
+    pub(crate) fn new(session_id: &SessionId, verifiers: &[Verifier]) -> Self {
+        Self {
+            session_id: *session_id,
+            verifiers: verifiers.iter().cloned().collect(),
+            pending: BTreeMap::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Checks a fragment received from the party `from` and stores it.
+    ///
+    /// Returns the message if this was its last missing fragment.
+    /// Repeated fragments are ignored.
+    pub fn add_fragment(
+        &mut self,
+        from: &Verifier,
+        fragment: MessageFragment<Sig>,
+    ) -> Result<Option<CombinedMessage<Sig>>, FragmentError> {
+        if !self.verifiers.contains(from) {
+            return Err(FragmentError::UnknownParty);
+        }
+        if fragment.session_id != self.session_id {
+            return Err(FragmentError::UnexpectedSessionId);
+        }
+        fragment
+            .verify(from)
+            .map_err(FragmentError::InvalidSignature)?;
+        if fragment.index >= fragment.num_fragments {
+            return Err(FragmentError::InconsistentFragment);
+        }
+
+        let key = (from.clone(), fragment.message_hash);
+        let pending = self.pending.entry(key.clone()).or_insert(PendingMessage {
+            num_fragments: fragment.num_fragments,
+            fragments: BTreeMap::new(),
+        });
+
+        if pending.num_fragments != fragment.num_fragments {
+            return Err(FragmentError::InconsistentFragment);
+        }
+        if let Some(data) = pending.fragments.get(&fragment.index) {
+            return if data == &fragment.data {
+                Ok(None)
+            } else {
+                Err(FragmentError::InconsistentFragment)
+            };
+        }
+        pending.fragments.insert(fragment.index, fragment.data);
+
+        if pending.fragments.len() < pending.num_fragments as usize {
+            return Ok(None);
+        }
+
+        let pending = self
+            .pending
+            .remove(&key)
+            .expect("the entry was just accessed");
+        let message_bytes = pending.fragments.into_values().collect::<Vec<_>>().concat();
+        if fragmented_message_hash(&message_bytes) != key.1 {
+            return Err(FragmentError::InvalidMessageHash);
+        }
+        F::deserialize(&message_bytes)
+            .map(Some)
+            .map_err(FragmentError::CannotDeserialize)
+    }
+
+    /// Returns the number of messages with some of the fragments still missing.
+    pub fn pending_messages(&self) -> usize {
+        self.pending.len()
+    }
+}
@@ -0,0 +1,68 @@
+use displaydoc::Display;
+use tokio::sync::mpsc;
+
+use super::combined_message::CombinedMessage;
+use super::driver::Transport;
+
+/// An outgoing message: the sender, the destination, and the message itself.
+pub type OutgoingMessage<Sig, Verifier> = (Verifier, Verifier, CombinedMessage<Sig>);
+
+/// An incoming message: the sender and the message itself.
+pub type IncomingMessage<Sig, Verifier> = (Verifier, CombinedMessage<Sig>);
+
+/// Errors returned by [`ChannelTransport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ChannelTransportError {
+    /// The outgoing channel is closed.
+    SendFailed,
+    /// The incoming channel is closed.
+    ReceiveFailed,
+}
+
+impl core::error::Error for ChannelTransportError {}
+
+/// A [`Transport`] that passes messages through `tokio` channels,
+/// leaving the routing between the parties to the owner of the other ends.
+#[derive(Debug)]
+pub struct ChannelTransport<Sig, Verifier> {
+    verifier: Verifier,
+    tx: mpsc::Sender<OutgoingMessage<Sig, Verifier>>,
+    rx: mpsc::Receiver<IncomingMessage<Sig, Verifier>>,
+}
+
+impl<Sig, Verifier> ChannelTransport<Sig, Verifier> {
+    /// Creates a new transport for the party with the given verifier.
+    pub fn new(
+        verifier: Verifier,
+        tx: mpsc::Sender<OutgoingMessage<Sig, Verifier>>,
+        rx: mpsc::Receiver<IncomingMessage<Sig, Verifier>>,
+    ) -> Self {
+        Self { verifier, tx, rx }
+    }
+}
+
+impl<Sig, Verifier> Transport<Sig, Verifier> for ChannelTransport<Sig, Verifier>
+where
+    Sig: Send,
+    Verifier: Clone + Send + Sync,
+{
+    type Error = ChannelTransportError;
+
+    async fn send(
+        &mut self,
+        destination: &Verifier,
+        message: CombinedMessage<Sig>,
+    ) -> Result<(), Self::Error> {
+        self.tx
+            .send((self.verifier.clone(), destination.clone(), message))
+            .await
+            .map_err(|_| ChannelTransportError::SendFailed)
+    }
+
+    async fn receive(&mut self) -> Result<IncomingMessage<Sig, Verifier>, Self::Error> {
+        self.rx
+            .recv()
+            .await
+            .ok_or(ChannelTransportError::ReceiveFailed)
+    }
+}
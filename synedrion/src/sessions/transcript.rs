@@ -0,0 +1,302 @@
+//! A publicly verifiable record of a KeyGen execution.
+//!
+//! All the values determining the resulting verifying key (the commitments to the public shares,
+//! the public shares themselves, and the Schnorr proofs of knowledge of the secret shares)
+//! are sent in broadcasts signed by their senders.
+//! Therefore, anyone who has all the signed broadcasts and the verifiers of the parties
+//! can check that each party committed to its public share before seeing the others,
+//! and proved the knowledge of the corresponding secret,
+//! without access to any secret data or the direct messages.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use signature::hazmat::PrehashVerifier;
+
+use super::combined_message::CombinedMessage;
+use super::error::LocalError;
+use super::format::{deserialize_canonical, Bincode, MessageFormat};
+use super::signed_message::{MessageType, MessageVersion, SessionId, SignedMessage};
+use crate::cggmp21::{key_gen, KeyGenResult, KeyInitError, SchemeParams};
+use crate::curve::{Point, VerifyingKey};
+use crate::rounds::{ProtocolResult, Round};
+use crate::tools::serde_bytes;
+
+const NUM_ROUNDS: u8 = 3;
+
+/// Possible errors when collecting or verifying a [`KeyGenTranscript`].
+#[derive(Debug, Clone)]
+pub enum TranscriptError<Verifier> {
+    /// The message was sent by a party that is not one of the participants.
+    UnknownParty(Verifier),
+    /// The message has a session ID different from the one of the transcript.
+    UnexpectedSessionId(Verifier),
+    /// The message header names a sender different from the party that sent the message.
+    UnexpectedSender(Verifier),
+    /// The party sent different broadcasts in the same round.
+    ConflictingBroadcasts {
+        /// The offending party.
+        party: Verifier,
+        /// The round number.
+        round: u8,
+    },
+    /// The broadcast of the party is missing from the transcript.
+    MissingMessage {
+        /// The party whose message is missing.
+        party: Verifier,
+        /// The round number.
+        round: u8,
+    },
+    /// The signature of the broadcast does not match its contents.
+    InvalidSignature {
+        /// The offending party.
+        party: Verifier,
+        /// The round number.
+        round: u8,
+        /// The error returned by the verifier.
+        reason: String,
+    },
+    /// Failed to deserialize the broadcast.
+    CannotDeserialize {
+        /// The offending party.
+        party: Verifier,
+        /// The round number.
+        round: u8,
+        /// The deserialization error.
+        reason: String,
+    },
+    /// The broadcasts of the party do not pass the protocol checks.
+    Protocol {
+        /// The offending party.
+        party: Verifier,
+        /// The error that occurred.
+        error: KeyInitError,
+    },
+    /// The public shares sum up to the identity point.
+    InvalidVerifyingKey,
+}
+
+impl<Verifier: fmt::Debug> fmt::Display for TranscriptError<Verifier> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownParty(party) => write!(f, "Unknown party {party:?}"),
+            Self::UnexpectedSessionId(party) => {
+                write!(f, "Unexpected session ID in a message from {party:?}")
+            }
+            Self::UnexpectedSender(party) => {
+                write!(f, "Unexpected sender in a message from {party:?}")
+            }
+            Self::ConflictingBroadcasts { party, round } => {
+                write!(f, "{party:?} sent conflicting broadcasts in round {round}")
+            }
+            Self::MissingMessage { party, round } => {
+                write!(f, "The broadcast of {party:?} in round {round} is missing")
+            }
+            Self::InvalidSignature {
+                party,
+                round,
+                reason,
+            } => write!(
+                f,
+                "Invalid signature of the broadcast of {party:?} in round {round}: {reason}"
+            ),
+            Self::CannotDeserialize {
+                party,
+                round,
+                reason,
+            } => write!(
+                f,
+                "Failed to deserialize the broadcast of {party:?} in round {round}: {reason}"
+            ),
+            Self::Protocol { party, error } => write!(f, "Protocol error of {party:?}: {error:?}"),
+            Self::InvalidVerifyingKey => write!(f, "The resulting verifying key is invalid"),
+        }
+    }
+}
+
+impl<Verifier: fmt::Debug> core::error::Error for TranscriptError<Verifier> {}
+
+/// The signed broadcasts of all the parties in a KeyGen execution
+/// (started by [`make_key_gen_session`](crate::make_key_gen_session)),
+/// which can be verified by a third party to obtain the resulting verifying key.
+///
+/// The transcript can be collected by any participant, by adding to it
+/// the messages it sends and receives, or by an observer of the broadcasts.
+/// The payloads are deserialized in the format `F`, which must be the one used by the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "Sig: Serialize, Verifier: Serialize"))]
+#[serde(bound(deserialize = "Sig: for<'x> Deserialize<'x>, Verifier: for<'x> Deserialize<'x>"))]
+pub struct KeyGenTranscript<P, Sig, Verifier, F = Bincode> {
+    #[serde(with = "serde_bytes::as_base64")]
+    shared_randomness: Box<[u8]>,
+    verifiers: Vec<Verifier>,
+    session_id: SessionId,
+    // `broadcasts[round - 1][party_idx]`
+    broadcasts: Vec<Vec<Option<SignedMessage<Sig>>>>,
+    phantom: PhantomData<fn() -> (P, F)>,
+}
+
+impl<P, Sig, Verifier, F> KeyGenTranscript<P, Sig, Verifier, F>
+where
+    P: SchemeParams,
+    Sig: Clone,
+    Verifier: Clone + Ord + PrehashVerifier<Sig> + Serialize,
+    F: MessageFormat,
+{
+    /// Creates an empty transcript for the session with the given shared randomness and verifiers
+    /// (the same ones that were passed to [`make_key_gen_session`](crate::make_key_gen_session)).
+    pub fn new(
+        shared_randomness: &[u8],
+        verifiers: &BTreeSet<Verifier>,
+    ) -> Result<Self, LocalError> {
+        let verifiers = verifiers.iter().cloned().collect::<Vec<_>>();
+        let session_id = SessionId::new(shared_randomness, KeyGenResult::<P>::NAME, &verifiers, 0)?;
+        Ok(Self {
+            shared_randomness: shared_randomness.into(),
+            broadcasts: vec![vec![None; verifiers.len()]; NUM_ROUNDS as usize],
+            verifiers,
+            session_id,
+            phantom: PhantomData,
+        })
+    }
+
+    fn party_idx(&self, party: &Verifier) -> Result<usize, TranscriptError<Verifier>> {
+        self.verifiers
+            .binary_search(party)
+            .map_err(|_| TranscriptError::UnknownParty(party.clone()))
+    }
+
+    /// Records the broadcast part of a message sent by the party `from`.
+    ///
+    /// Messages without a broadcast part (direct messages and echo messages) are ignored.
+    /// Note that the signature is only checked in [`verify`](Self::verify).
+    pub fn add_message(
+        &mut self,
+        from: &Verifier,
+        message: &CombinedMessage<Sig>,
+    ) -> Result<(), TranscriptError<Verifier>> {
+        let broadcast = match message {
+            CombinedMessage::One(msg) if msg.message_type() == MessageType::Broadcast => msg,
+            CombinedMessage::Both { broadcast, .. } => broadcast,
+            _ => return Ok(()),
+        };
+
+        let party_idx = self.party_idx(from)?;
+
+        if broadcast.session_id() != &self.session_id {
+            return Err(TranscriptError::UnexpectedSessionId(from.clone()));
+        }
+        if broadcast.sender() != party_idx {
+            return Err(TranscriptError::UnexpectedSender(from.clone()));
+        }
+
+        let round = broadcast.round();
+        if round == 0 || round > NUM_ROUNDS {
+            // Not a KeyGen round, nothing to record.
+            return Ok(());
+        }
+
+        let slot = &mut self.broadcasts[round as usize - 1][party_idx];
+        match slot {
+            Some(existing) if !existing.is_same_as(broadcast) => {
+                Err(TranscriptError::ConflictingBroadcasts {
+                    party: from.clone(),
+                    round,
+                })
+            }
+            Some(_) => Ok(()),
+            None => {
+                *slot = Some(broadcast.clone());
+                Ok(())
+            }
+        }
+    }
+
+    fn verified_broadcasts<M>(&self, round: u8) -> Result<Vec<M>, TranscriptError<Verifier>>
+    where
+        M: Serialize + for<'de> Deserialize<'de>,
+    {
+        self.broadcasts[round as usize - 1]
+            .iter()
+            .zip(self.verifiers.iter())
+            .enumerate()
+            .map(|(party_idx, (broadcast, verifier))| {
+                let broadcast =
+                    broadcast
+                        .clone()
+                        .ok_or_else(|| TranscriptError::MissingMessage {
+                            party: verifier.clone(),
+                            round,
+                        })?;
+                if MessageVersion::from_u8(broadcast.version()) != Some(MessageVersion::CURRENT) {
+                    return Err(TranscriptError::CannotDeserialize {
+                        party: verifier.clone(),
+                        round,
+                        reason: format!("Unsupported message version {}", broadcast.version()),
+                    });
+                }
+                if broadcast.session_id() != &self.session_id {
+                    return Err(TranscriptError::UnexpectedSessionId(verifier.clone()));
+                }
+                if broadcast.sender() != party_idx || broadcast.recipient().is_some() {
+                    return Err(TranscriptError::UnexpectedSender(verifier.clone()));
+                }
+                let verified = broadcast.verify(verifier).map_err(|reason| {
+                    TranscriptError::InvalidSignature {
+                        party: verifier.clone(),
+                        round,
+                        reason,
+                    }
+                })?;
+                if verified.as_unverified().format() != F::ID {
+                    return Err(TranscriptError::CannotDeserialize {
+                        party: verifier.clone(),
+                        round,
+                        reason: format!(
+                            "Unexpected message format {}",
+                            verified.as_unverified().format()
+                        ),
+                    });
+                }
+                deserialize_canonical::<F, _>(verified.payload()).map_err(|reason| {
+                    TranscriptError::CannotDeserialize {
+                        party: verifier.clone(),
+                        round,
+                        reason,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the signatures and the contents of all the broadcasts,
+    /// and returns the verifying key resulting from the KeyGen execution.
+    pub fn verify(&self) -> Result<VerifyingKey, TranscriptError<Verifier>> {
+        let round1 =
+            self.verified_broadcasts::<<key_gen::Round1<P> as Round>::BroadcastMessage>(1)?;
+        let round2 =
+            self.verified_broadcasts::<<key_gen::Round2<P> as Round>::BroadcastMessage>(2)?;
+        let round3 =
+            self.verified_broadcasts::<<key_gen::Round3<P> as Round>::BroadcastMessage>(3)?;
+
+        let public_shares =
+            key_gen::verify_broadcasts::<P>(&self.shared_randomness, round1, round2, round3)
+                .map_err(|(party_idx, error)| TranscriptError::Protocol {
+                    party: self.verifiers[party_idx.as_usize()].clone(),
+                    error,
+                })?;
+
+        public_shares
+            .iter()
+            .sum::<Point>()
+            .to_verifying_key()
+            .ok_or(TranscriptError::InvalidVerifyingKey)
+    }
+}
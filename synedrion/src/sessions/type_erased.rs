@@ -6,34 +6,29 @@ This way they can be used in a state machine loop without code repetition.
 use alloc::boxed::Box;
 use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::format;
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::any::{Any, TypeId};
 
 use rand_core::CryptoRngCore;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 
 use super::error::LocalError;
+use super::format::{deserialize_canonical, Bincode, MessageFormat};
 use crate::rounds::{
     self, FinalizableToNextRound, FinalizableToResult, PartyIdx, ProtocolResult, Round,
     ToNextRound, ToResult,
 };
 
+/// Serializes a value with [`Bincode`], for the uses where the format is fixed
+/// (e.g. the hashed values).
 pub(crate) fn serialize_message(message: &impl Serialize) -> Result<Box<[u8]>, LocalError> {
-    bincode::serialize(message)
-        .map(|serialized| serialized.into_boxed_slice())
-        .map_err(|err| LocalError(format!("Failed to serialize: {err:?}")))
+    Bincode::serialize(message)
 }
 
-pub(crate) fn deserialize_message<M: for<'de> Deserialize<'de>>(
-    message_bytes: &[u8],
-) -> Result<M, String> {
-    bincode::deserialize(message_bytes).map_err(|err| err.to_string())
-}
-
-pub(crate) enum FinalizeOutcome<Res: ProtocolResult> {
+pub(crate) enum FinalizeOutcome<Res: ProtocolResult, F> {
     Success(Res::Success),
-    AnotherRound(Box<dyn DynFinalizable<Res>>),
+    AnotherRound(Box<dyn DynFinalizable<Res, F>>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -44,6 +39,8 @@ pub enum AccumAddError {
 
 #[derive(Debug, Clone)]
 pub enum AccumFinalizeError {
+    // Only read through the `Debug` impl.
+    #[allow(dead_code)]
     Downcast(String),
 }
 
@@ -97,7 +94,7 @@ impl DynArtifact {
 }
 
 /// An object-safe trait wrapping `Round`.
-pub(crate) trait DynRound<Res: ProtocolResult>: Send {
+pub(crate) trait DynRound<Res: ProtocolResult, F>: Send + Sync {
     fn round_num(&self) -> u8;
     fn next_round_num(&self) -> Option<u8>;
 
@@ -123,13 +120,24 @@ pub(crate) trait DynRound<Res: ProtocolResult>: Send {
     fn missing_payloads(&self, accum: &DynRoundAccum) -> BTreeSet<PartyIdx>;
 }
 
-fn is_null_type<T: 'static>() -> bool {
+pub(crate) fn is_null_type<T: 'static>() -> bool {
     TypeId::of::<T>() == TypeId::of::<()>()
 }
 
-impl<R> DynRound<R::Result> for R
+/// Returns the value of a type for which [`is_null_type`] is `true`.
+///
+/// Not all the formats can deserialize `()` from an empty payload, so it is created directly.
+fn null_value<T: 'static>() -> T {
+    let value: Box<dyn Any> = Box::new(());
+    *value
+        .downcast::<T>()
+        .expect("`T` is checked to be the null type")
+}
+
+impl<R, F> DynRound<R::Result, F> for R
 where
-    R: Round + Send,
+    R: Round + Send + Sync,
+    F: MessageFormat,
     <R as Round>::BroadcastMessage: 'static,
     <R as Round>::DirectMessage: 'static,
     <R as Round>::Payload: 'static + Send,
@@ -158,7 +166,7 @@ where
         let mut boxed_rng = BoxedRng(rng);
         let typed_message = self.make_broadcast_message(&mut boxed_rng);
         let serialized = typed_message
-            .map(|message| serialize_message(&message))
+            .map(|message| F::serialize(&message))
             .transpose()?;
         Ok(serialized)
     }
@@ -181,7 +189,7 @@ where
         let message = if null_message {
             None
         } else {
-            Some(serialize_message(&typed_message)?)
+            Some(F::serialize(&typed_message)?)
         };
 
         Ok((message, DynArtifact(Box::new(typed_artifact))))
@@ -196,37 +204,26 @@ where
         let null_broadcast = is_null_type::<R::BroadcastMessage>();
         let null_direct = is_null_type::<R::DirectMessage>();
 
-        let broadcast_data = if let Some(data) = broadcast_data {
-            data
+        let broadcast_message: <R as Round>::BroadcastMessage = if let Some(data) = broadcast_data {
+            deserialize_canonical::<F, _>(data).map_err(ReceiveError::CannotDeserialize)?
         } else {
             if !null_broadcast {
                 return Err(ReceiveError::InvalidContents(
                     "Expected a non-null broadcast message".into(),
                 ));
             }
-            b""
+            null_value()
         };
 
-        let broadcast_message: <R as Round>::BroadcastMessage =
-            match deserialize_message(broadcast_data) {
-                Ok(message) => message,
-                Err(err) => return Err(ReceiveError::CannotDeserialize(err)),
-            };
-
-        let direct_data = if let Some(data) = direct_data {
-            data
+        let direct_message: <R as Round>::DirectMessage = if let Some(data) = direct_data {
+            deserialize_canonical::<F, _>(data).map_err(ReceiveError::CannotDeserialize)?
         } else {
             if !null_direct {
                 return Err(ReceiveError::InvalidContents(
                     "Expected a non-null direct message".into(),
                 ));
             }
-            b""
-        };
-
-        let direct_message: <R as Round>::DirectMessage = match deserialize_message(direct_data) {
-            Ok(message) => message,
-            Err(err) => return Err(ReceiveError::CannotDeserialize(err)),
+            null_value()
         };
 
         let payload = self
@@ -267,10 +264,6 @@ impl DynRoundAccum {
         }
     }
 
-    pub fn contains(&self, from: PartyIdx) -> bool {
-        self.payloads.contains_key(&from)
-    }
-
     pub fn add_payload(
         &mut self,
         from: PartyIdx,
@@ -326,12 +319,12 @@ fn downcast<T: 'static>(boxed: Box<dyn Any>) -> Result<T, AccumFinalizeError> {
     })?))
 }
 
-pub(crate) trait DynFinalizable<Res: ProtocolResult>: DynRound<Res> {
+pub(crate) trait DynFinalizable<Res: ProtocolResult, F>: DynRound<Res, F> {
     fn finalize(
         self: Box<Self>,
         rng: &mut dyn CryptoRngCore,
         accum: DynRoundAccum,
-    ) -> Result<FinalizeOutcome<Res>, FinalizeError<Res>>;
+    ) -> Result<FinalizeOutcome<Res, F>, FinalizeError<Res>>;
 }
 
 // This is needed because Rust does not currently support exclusive trait implementations.
@@ -346,33 +339,34 @@ const _: () = {
     //    of the target associated type, with the same methods as the target trait;
     // 2) A blanket implementation for the target trait.
 
-    trait _DynFinalizable<Res: ProtocolResult, T> {
+    trait _DynFinalizable<Res: ProtocolResult, F, T> {
         fn finalize(
             self: Box<Self>,
             rng: &mut dyn CryptoRngCore,
             accum: DynRoundAccum,
-        ) -> Result<FinalizeOutcome<Res>, FinalizeError<Res>>;
+        ) -> Result<FinalizeOutcome<Res, F>, FinalizeError<Res>>;
     }
 
-    impl<R> DynFinalizable<R::Result> for R
+    impl<R, F> DynFinalizable<R::Result, F> for R
     where
-        R: Round + Send + 'static,
+        R: Round + Send + Sync + 'static,
         <R as Round>::Payload: Send,
         <R as Round>::Artifact: Send,
-        Self: _DynFinalizable<R::Result, R::Type>,
+        F: MessageFormat,
+        Self: _DynFinalizable<R::Result, F, R::Type>,
     {
         fn finalize(
             self: Box<Self>,
             rng: &mut dyn CryptoRngCore,
             accum: DynRoundAccum,
-        ) -> Result<FinalizeOutcome<R::Result>, FinalizeError<R::Result>> {
+        ) -> Result<FinalizeOutcome<R::Result, F>, FinalizeError<R::Result>> {
             Self::finalize(self, rng, accum)
         }
     }
 
     // Actual diverging implementations.
 
-    impl<R> _DynFinalizable<R::Result, ToResult> for R
+    impl<R, F> _DynFinalizable<R::Result, F, ToResult> for R
     where
         R: 'static + FinalizableToResult,
     {
@@ -380,7 +374,7 @@ const _: () = {
             self: Box<Self>,
             rng: &mut dyn CryptoRngCore,
             accum: DynRoundAccum,
-        ) -> Result<FinalizeOutcome<R::Result>, FinalizeError<R::Result>> {
+        ) -> Result<FinalizeOutcome<R::Result, F>, FinalizeError<R::Result>> {
             let mut boxed_rng = BoxedRng(rng);
             let typed_accum = accum.finalize::<R>().map_err(FinalizeError::Accumulator)?;
             let result = (*self)
@@ -390,16 +384,16 @@ const _: () = {
         }
     }
 
-    impl<R> _DynFinalizable<R::Result, ToNextRound> for R
+    impl<R, F> _DynFinalizable<R::Result, F, ToNextRound> for R
     where
         R: 'static + FinalizableToNextRound,
-        <R as FinalizableToNextRound>::NextRound: DynFinalizable<R::Result>,
+        <R as FinalizableToNextRound>::NextRound: DynFinalizable<R::Result, F>,
     {
         fn finalize(
             self: Box<Self>,
             rng: &mut dyn CryptoRngCore,
             accum: DynRoundAccum,
-        ) -> Result<FinalizeOutcome<R::Result>, FinalizeError<R::Result>> {
+        ) -> Result<FinalizeOutcome<R::Result, F>, FinalizeError<R::Result>> {
             let mut boxed_rng = BoxedRng(rng);
             let typed_accum = accum.finalize::<R>().map_err(FinalizeError::Accumulator)?;
             let next_round = (*self)
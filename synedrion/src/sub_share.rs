@@ -0,0 +1,322 @@
+//! Splitting of a key share among the internal machines of a party (hierarchical custody).
+//!
+//! The secret share $x_i$ of a [`KeyShare`] is split with Shamir's scheme into $n'$ sub-shares,
+//! $t'$ of which are needed to restore it. This is done by the party alone:
+//! the other parties are not involved and do not see any difference,
+//! since the restored key share is the same as the original one.
+//! The public sub-shares $x_{i,j} G$ are kept with every sub-share,
+//! so that a corrupted sub-share is detected before the key share is restored.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use displaydoc::Display;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::cggmp21::SchemeParams;
+use crate::common::{KeyShare, PublicAuxInfo, SecretAuxInfo};
+use crate::curve::{Point, Scalar, VerifyingKey};
+use crate::rounds::PartyIdx;
+use crate::tools::bitvec::BitVec;
+use crate::tools::hashing::HashOutput;
+use crate::tools::sss::{
+    shamir_evaluation_points, shamir_join_points, shamir_join_scalars, shamir_split, ShareIdx,
+};
+
+/// Possible errors when splitting a key share into [`KeySubShare`]s or combining them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum KeySubShareError {
+    /// The threshold must be between 1 and the number of sub-shares.
+    InvalidThreshold,
+    /// {found} distinct sub-shares are given, but {threshold} are required.
+    NotEnoughSubShares {
+        /// The number of distinct sub-shares given.
+        found: usize,
+        /// The number of sub-shares required.
+        threshold: usize,
+    },
+    /// The sub-shares were split from different key shares, or from different epochs of the same one.
+    Mismatch,
+    /// The sub-share {0:?} does not match its public sub-share.
+    InvalidSubShare(ShareIdx),
+    /// The restored secret share does not match the public share of the party.
+    PublicShareMismatch,
+}
+
+impl core::error::Error for KeySubShareError {}
+
+/// A sub-share of a [`KeyShare`], held by one of the internal machines of a party,
+/// so that the key share can only be used when `threshold` of them are combined
+/// (e.g. a company-level share held by an internal quorum).
+///
+/// Only the secret share is split; the auxiliary data of the party
+/// (including its Paillier secret key) is copied into every sub-share,
+/// since it is needed to run the protocols, but is not enough to sign on its own.
+/// A signing quorum restores the key share with [`combine`](Self::combine)
+/// on the machine running the session, which should discard it afterwards.
+///
+/// The sub-shares must be created anew after every refresh of the key share.
+// TODO (#77): Debug can be derived automatically here if `secret_sub_share` is wrapped in its own struct,
+// or in a `SecretBox`-type wrapper.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "SecretAuxInfo<P>: Serialize,
+        PublicAuxInfo<P>: Serialize"))]
+#[serde(bound(deserialize = "SecretAuxInfo<P>: for <'x> Deserialize<'x>,
+        PublicAuxInfo<P>: for <'x> Deserialize<'x>"))]
+pub struct KeySubShare<P: SchemeParams> {
+    index: ShareIdx,
+    threshold: u32,
+    secret_sub_share: Scalar,
+    public_sub_shares: BTreeMap<ShareIdx, Point>,
+    party_idx: PartyIdx,
+    public_shares: Box<[Point]>,
+    secret_aux: SecretAuxInfo<P>,
+    public_aux: Box<[PublicAuxInfo<P>]>,
+    init_id: BitVec,
+    share_set_id: HashOutput,
+    epoch: u64,
+}
+
+impl<P: SchemeParams> KeySubShare<P> {
+    /// Splits the key share into `num_sub_shares` sub-shares with the indices `1..=num_sub_shares`,
+    /// any `threshold` of which can restore it.
+    pub fn split(
+        rng: &mut impl CryptoRngCore,
+        key_share: &KeyShare<P>,
+        threshold: usize,
+        num_sub_shares: usize,
+    ) -> Result<Box<[Self]>, KeySubShareError> {
+        if threshold == 0 || threshold > num_sub_shares {
+            return Err(KeySubShareError::InvalidThreshold);
+        }
+
+        let sub_share_idxs = shamir_evaluation_points(num_sub_shares);
+        let secret_sub_shares =
+            shamir_split(rng, &key_share.secret_share, threshold, &sub_share_idxs);
+        let public_sub_shares = secret_sub_shares
+            .iter()
+            .map(|(idx, sub_share)| (*idx, sub_share.mul_by_generator()))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(secret_sub_shares
+            .iter()
+            .map(|(idx, secret_sub_share)| Self {
+                index: *idx,
+                threshold: threshold as u32,
+                secret_sub_share: *secret_sub_share,
+                public_sub_shares: public_sub_shares.clone(),
+                party_idx: key_share.index,
+                public_shares: key_share.public_shares.clone(),
+                secret_aux: key_share.secret_aux.clone(),
+                public_aux: key_share.public_aux.clone(),
+                init_id: key_share.init_id.clone(),
+                share_set_id: key_share.share_set_id,
+                epoch: key_share.epoch,
+            })
+            .collect())
+    }
+
+    /// Restores the key share from at least `threshold` sub-shares,
+    /// so that it can be used in the sessions of the party.
+    ///
+    /// The sub-shares are checked against the public sub-shares they were created with,
+    /// so the restored key share is either the original one or an error is returned.
+    pub fn combine(sub_shares: &[Self]) -> Result<KeyShare<P>, KeySubShareError> {
+        let some_sub_share = sub_shares
+            .first()
+            .ok_or(KeySubShareError::NotEnoughSubShares {
+                found: 0,
+                threshold: 1,
+            })?;
+        let threshold = some_sub_share.threshold as usize;
+
+        let mut secret_sub_shares = BTreeMap::new();
+        for sub_share in sub_shares {
+            if sub_share.threshold != some_sub_share.threshold
+                || sub_share.public_sub_shares != some_sub_share.public_sub_shares
+                || sub_share.party_idx != some_sub_share.party_idx
+                || sub_share.public_shares != some_sub_share.public_shares
+                || sub_share.share_set_id != some_sub_share.share_set_id
+                || sub_share.epoch != some_sub_share.epoch
+            {
+                return Err(KeySubShareError::Mismatch);
+            }
+            if some_sub_share.public_sub_shares.get(&sub_share.index)
+                != Some(&sub_share.secret_sub_share.mul_by_generator())
+            {
+                return Err(KeySubShareError::InvalidSubShare(sub_share.index));
+            }
+            secret_sub_shares.insert(sub_share.index, sub_share.secret_sub_share);
+        }
+
+        if secret_sub_shares.len() < threshold {
+            return Err(KeySubShareError::NotEnoughSubShares {
+                found: secret_sub_shares.len(),
+                threshold,
+            });
+        }
+
+        let secret_share = shamir_join_scalars(secret_sub_shares.iter().take(threshold));
+        #[cfg(feature = "zeroize")]
+        secret_sub_shares
+            .values_mut()
+            .for_each(|value| value.zeroize());
+
+        let key_share = KeyShare {
+            index: some_sub_share.party_idx,
+            secret_share,
+            public_shares: some_sub_share.public_shares.clone(),
+            secret_aux: some_sub_share.secret_aux.clone(),
+            public_aux: some_sub_share.public_aux.clone(),
+            init_id: some_sub_share.init_id.clone(),
+            share_set_id: some_sub_share.share_set_id,
+            epoch: some_sub_share.epoch,
+        };
+        if key_share.public_shares.get(key_share.index.as_usize())
+            != Some(&key_share.secret_share.mul_by_generator())
+        {
+            return Err(KeySubShareError::PublicShareMismatch);
+        }
+        Ok(key_share)
+    }
+
+    /// Returns the index of this sub-share.
+    pub fn index(&self) -> ShareIdx {
+        self.index
+    }
+
+    /// Returns the number of sub-shares required to restore the key share.
+    pub fn threshold(&self) -> usize {
+        self.threshold as usize
+    }
+
+    /// Returns the indices of all the sub-shares the key share was split into.
+    pub fn sub_share_idxs(&self) -> BTreeSet<ShareIdx> {
+        self.public_sub_shares.keys().cloned().collect()
+    }
+
+    /// Returns the index of the party holding the key share.
+    pub fn party_index(&self) -> usize {
+        self.party_idx.as_usize()
+    }
+
+    /// Returns the number of refreshes applied to the key share before it was split.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Return the verifying key to which the key share corresponds.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        // The public sub-shares interpolate to the public share of the party,
+        // and the public shares of all the parties add up to the verifying key.
+        let public_share =
+            shamir_join_points(self.public_sub_shares.iter().take(self.threshold as usize));
+        let others = self
+            .public_shares
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.party_idx.as_usize())
+            .map(|(_, public_share)| *public_share)
+            .sum::<Point>();
+        (public_share + others).to_verifying_key().unwrap()
+    }
+}
+
+// A custom Debug impl that skips the secret values
+impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for KeySubShare<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            concat![
+                "KeySubShare {{",
+                "index: {:?}, ",
+                "threshold: {:?}, ",
+                "secret_sub_share: <...>, ",
+                "public_sub_shares: {:?}, ",
+                "party_idx: {:?}, ",
+                "public_shares: {:?}, ",
+                "secret_aux: {:?}, ",
+                "public_aux: {:?} ",
+                "}}"
+            ],
+            self.index,
+            self.threshold,
+            self.public_sub_shares,
+            self.party_idx,
+            self.public_shares,
+            self.secret_aux,
+            self.public_aux
+        )
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for KeySubShare<P> {
+    fn drop(&mut self) {
+        self.secret_sub_share.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::{KeySubShare, KeySubShareError};
+    use crate::cggmp21::TestParams;
+    use crate::common::KeyShare;
+    use crate::curve::Scalar;
+
+    #[test]
+    fn split_and_combine() {
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+        let key_share = &key_shares[1];
+        let sub_shares = KeySubShare::split(&mut OsRng, key_share, 2, 3).unwrap();
+        assert_eq!(sub_shares.len(), 3);
+        assert!(sub_shares
+            .iter()
+            .all(|sub_share| sub_share.verifying_key() == key_share.verifying_key()));
+
+        // Any two sub-shares restore the key share
+        for pair in [[0, 1], [0, 2], [2, 1]] {
+            let quorum = pair.map(|idx| sub_shares[idx].clone());
+            let restored = KeySubShare::combine(&quorum).unwrap();
+            assert_eq!(restored.secret_share, key_share.secret_share);
+            assert_eq!(restored.share_set_id, key_share.share_set_id);
+            assert_eq!(restored.party_index(), key_share.party_index());
+            assert!(restored.verify_consistency().is_consistent());
+        }
+
+        // One sub-share is not enough, even if it is given twice
+        assert_eq!(
+            KeySubShare::combine(&[sub_shares[0].clone(), sub_shares[0].clone()]).unwrap_err(),
+            KeySubShareError::NotEnoughSubShares {
+                found: 1,
+                threshold: 2
+            }
+        );
+
+        // A corrupted sub-share is detected
+        let mut corrupted = sub_shares[1].clone();
+        corrupted.secret_sub_share = corrupted.secret_sub_share + Scalar::ONE;
+        assert_eq!(
+            KeySubShare::combine(&[sub_shares[0].clone(), corrupted]).unwrap_err(),
+            KeySubShareError::InvalidSubShare(sub_shares[1].index())
+        );
+
+        // Sub-shares of different splits cannot be mixed
+        let other_sub_shares = KeySubShare::split(&mut OsRng, key_share, 2, 3).unwrap();
+        assert_eq!(
+            KeySubShare::combine(&[sub_shares[0].clone(), other_sub_shares[1].clone()])
+                .unwrap_err(),
+            KeySubShareError::Mismatch
+        );
+
+        assert_eq!(
+            KeySubShare::split(&mut OsRng, key_share, 4, 3).unwrap_err(),
+            KeySubShareError::InvalidThreshold
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Test vectors of the full ceremonies under a seeded RNG (behind the `test-vectors` feature).
+//!
+//! A [`TestVector`] records the canonical encodings of all the messages of a ceremony
+//! and the serialized results of all the parties, with every party running a
+//! [deterministic](crate::sessions::Session::deterministic) session
+//! seeded from the vector's seed. The messages are recorded in a fixed order
+//! (as if the rounds were executed in lockstep), so the vector only depends on the seed and the protocol.
+//!
+//! Pinning a vector created by one release and checking it with [`TestVector::check`]
+//! in the next one ensures that the messages and the stored results did not change,
+//! so a committee can upgrade its parties one by one.
+//!
+//! With the `rayon` feature, the Paillier primes and some of the proofs are created
+//! from the randomness split between the threads, so the messages are different
+//! (although still compatible); the vectors must be created and checked with the same setting.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::cggmp21::SchemeParams;
+use crate::common::KeyShare;
+use crate::constructors::{
+    make_interactive_signing_session, make_key_gen_session, make_key_refresh_session,
+};
+use crate::rounds::ProtocolResult;
+use crate::sessions::Session;
+use crate::testnet::{make_signers, TestNetwork};
+use crate::tools::{
+    hashing::{Chain, Hash},
+    serde_bytes,
+};
+use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+
+/// The number of parties taking part in the ceremonies of the test vectors.
+const NUM_PARTIES: usize = 3;
+
+/// A ceremony recorded in a [`TestVector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ceremony {
+    /// The KeyGen protocol, with the key shares as the results.
+    KeyGen,
+    /// The KeyRefresh protocol applied to the results of KeyGen,
+    /// with the updated key shares as the results.
+    KeyRefresh,
+    /// The InteractiveSigning protocol with the results of KeyGen,
+    /// with the serialized signature and the recovery id as the results.
+    InteractiveSigning,
+}
+
+impl Ceremony {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::KeyGen => "KeyGen",
+            Self::KeyRefresh => "KeyRefresh",
+            Self::InteractiveSigning => "InteractiveSigning",
+        }
+    }
+}
+
+/// Possible errors when creating or checking a [`TestVector`].
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum TestVectorError {
+    /// The ceremony failed: {0}
+    Ceremony(String),
+    /// Invalid JSON: {0}
+    Json(String),
+    /// The vector was made with the scheme parameters {expected}, not {found}.
+    SchemeParamsMismatch {
+        /// The identifier of the parameters of the vector.
+        expected: String,
+        /// The identifier of the parameters it was checked with.
+        found: &'static str,
+    },
+    /// The ceremony produced {found} messages instead of {expected}.
+    MessageCountMismatch {
+        /// The number of messages in the vector.
+        expected: usize,
+        /// The number of messages produced by the ceremony.
+        found: usize,
+    },
+    /// The message {index} (round {round}, from party {from} to party {to}) differs from the vector.
+    MessageMismatch {
+        /// The index of the message in the vector.
+        index: usize,
+        /// The round of the message.
+        round: u8,
+        /// The index of the sender.
+        from: u32,
+        /// The index of the receiver.
+        to: u32,
+    },
+    /// The result of the party {party} differs from the vector.
+    ResultMismatch {
+        /// The index of the party.
+        party: usize,
+    },
+}
+
+impl core::error::Error for TestVectorError {}
+
+/// A message of a [`TestVector`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorMessage {
+    /// The round number.
+    pub round: u8,
+    /// Whether this is the echo round following the round `round`.
+    pub is_echo_round: bool,
+    /// The index of the sender.
+    pub from: u32,
+    /// The index of the receiver.
+    pub to: u32,
+    /// The [canonical encoding](crate::CombinedMessage::canonical_bytes) of the message.
+    #[serde(with = "serde_bytes::as_hex")]
+    pub message: Box<[u8]>,
+}
+
+/// A transcript of a ceremony of all the parties, along with their results.
+///
+/// The parties are indexed in the order of their verifying keys,
+/// which are derived from the seed along with the RNG seeds of their sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// The version of the crate that created the vector (not checked).
+    pub crate_version: String,
+    /// The identifier of the scheme parameters the ceremony was run with.
+    pub scheme_params: String,
+    /// The recorded ceremony.
+    pub ceremony: Ceremony,
+    /// The seed the ceremony and its inputs were derived from.
+    #[serde(with = "serde_bytes::as_hex")]
+    pub seed: [u8; 32],
+    /// The messages in the order they were created.
+    pub messages: Vec<VectorMessage>,
+    /// The serialized results of the parties.
+    pub results: Vec<VectorResult>,
+}
+
+/// The serialized result of a party in a [`TestVector`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorResult(#[serde(with = "serde_bytes::as_hex")] pub Box<[u8]>);
+
+impl TestVector {
+    /// Runs the ceremony with the inputs derived from `seed`, recording the messages and results.
+    pub fn generate<P: SchemeParams + 'static>(
+        ceremony: Ceremony,
+        seed: &[u8; 32],
+    ) -> Result<Self, TestVectorError> {
+        let parties = Parties::new(seed);
+        let mut messages = Vec::new();
+
+        let results = match ceremony {
+            Ceremony::KeyGen => key_gen::<P>(&parties, &mut messages)?
+                .iter()
+                .map(|key_share| key_share.to_bytes())
+                .collect::<Vec<_>>(),
+            Ceremony::KeyRefresh => {
+                let key_shares = key_gen::<P>(&parties, &mut Vec::new())?;
+                let sessions = parties.make_sessions(ceremony, |rng, sr, signer, verifiers| {
+                    make_key_refresh_session::<P, _, _, _, _>(rng, sr, signer, verifiers)
+                })?;
+                let changes = run_ceremony(&parties, ceremony, sessions, &mut messages)?;
+                key_shares
+                    .into_iter()
+                    .zip(changes)
+                    .map(|(key_share, change)| {
+                        key_share
+                            .try_update(change)
+                            .map(|key_share| key_share.to_bytes())
+                            .map_err(|err| TestVectorError::Ceremony(err.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            Ceremony::InteractiveSigning => {
+                let key_shares = key_gen::<P>(&parties, &mut Vec::new())?;
+                let message = parties.derive(b"message", 0);
+                let sessions = parties.make_sessions(ceremony, |rng, sr, signer, verifiers| {
+                    let idx = parties.index(signer.verifying_key());
+                    make_interactive_signing_session::<P, _, _, _, _>(
+                        rng,
+                        sr,
+                        signer,
+                        verifiers,
+                        &key_shares[idx],
+                        &message,
+                    )
+                })?;
+                run_ceremony(&parties, ceremony, sessions, &mut messages)?
+                    .into_iter()
+                    .map(|signature| {
+                        let (signature, recovery_id) = signature.to_backend();
+                        let mut bytes = signature.to_bytes().to_vec();
+                        bytes.push(recovery_id.to_byte());
+                        bytes.into_boxed_slice()
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            scheme_params: P::ID.to_string(),
+            ceremony,
+            seed: *seed,
+            messages,
+            results: results.into_iter().map(VectorResult).collect(),
+        })
+    }
+
+    /// Runs the ceremony again and checks that it produces the same messages and results.
+    pub fn check<P: SchemeParams + 'static>(&self) -> Result<(), TestVectorError> {
+        if self.scheme_params != P::ID {
+            return Err(TestVectorError::SchemeParamsMismatch {
+                expected: self.scheme_params.clone(),
+                found: P::ID,
+            });
+        }
+
+        let current = Self::generate::<P>(self.ceremony, &self.seed)?;
+        if let Some((index, message)) = self
+            .messages
+            .iter()
+            .zip(current.messages.iter())
+            .enumerate()
+            .find_map(|(index, (pinned, current))| (pinned != current).then_some((index, pinned)))
+        {
+            return Err(TestVectorError::MessageMismatch {
+                index,
+                round: message.round,
+                from: message.from,
+                to: message.to,
+            });
+        }
+        if self.messages.len() != current.messages.len() {
+            return Err(TestVectorError::MessageCountMismatch {
+                expected: self.messages.len(),
+                found: current.messages.len(),
+            });
+        }
+        if let Some(party) = (0..self.results.len().max(current.results.len()))
+            .find(|idx| self.results.get(*idx) != current.results.get(*idx))
+        {
+            return Err(TestVectorError::ResultMismatch { party });
+        }
+        Ok(())
+    }
+
+    /// Serializes the vector as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the vector can be serialized")
+    }
+
+    /// Deserializes the vector from JSON.
+    pub fn from_json(json: &str) -> Result<Self, TestVectorError> {
+        serde_json::from_str(json).map_err(|err| TestVectorError::Json(err.to_string()))
+    }
+}
+
+/// The signers and verifiers of the parties derived from a seed.
+struct Parties {
+    seed: [u8; 32],
+    signers: Vec<SigningKey>,
+    verifiers: BTreeSet<VerifyingKey>,
+}
+
+impl Parties {
+    fn new(seed: &[u8; 32]) -> Self {
+        let mut counter = 0;
+        let (signers, verifiers) = make_signers(NUM_PARTIES, || loop {
+            let signer = SigningKey::from_slice(&derive(seed, b"signer", counter));
+            counter += 1;
+            // Practically never fails, but the key must be nonzero and below the curve order.
+            if let Ok(signer) = signer {
+                break signer;
+            }
+        });
+        Self {
+            seed: *seed,
+            signers,
+            verifiers,
+        }
+    }
+
+    fn derive(&self, label: &[u8], index: u32) -> [u8; 32] {
+        derive(&self.seed, label, index)
+    }
+
+    fn index(&self, verifier: &VerifyingKey) -> usize {
+        self.verifiers
+            .iter()
+            .position(|v| v == verifier)
+            .expect("the verifier belongs to one of the parties")
+    }
+
+    fn make_sessions<Res, F>(
+        &self,
+        ceremony: Ceremony,
+        make_session: F,
+    ) -> Result<Vec<Session<Res, Signature, SigningKey, VerifyingKey>>, TestVectorError>
+    where
+        Res: ProtocolResult,
+        F: Fn(
+            &mut ChaCha20Rng,
+            &[u8],
+            SigningKey,
+            &BTreeSet<VerifyingKey>,
+        ) -> Result<
+            Session<Res, Signature, SigningKey, VerifyingKey>,
+            crate::sessions::LocalError,
+        >,
+    {
+        let shared_randomness = self.derive(ceremony.as_str().as_bytes(), 0);
+        self.signers
+            .iter()
+            .enumerate()
+            .map(|(idx, signer)| {
+                let mut rng = ChaCha20Rng::from_seed(
+                    self.derive(ceremony.as_str().as_bytes(), idx as u32 + 1),
+                );
+                make_session(
+                    &mut rng,
+                    &shared_randomness,
+                    signer.clone(),
+                    &self.verifiers,
+                )
+                .map(|session| session.deterministic())
+                .map_err(|err| TestVectorError::Ceremony(err.to_string()))
+            })
+            .collect()
+    }
+}
+
+fn derive(seed: &[u8; 32], label: &[u8], index: u32) -> [u8; 32] {
+    let hash = Hash::new_with_dst(b"TestVector")
+        .chain_constant_sized_bytes(seed)
+        .chain_bytes(label)
+        .chain(&index)
+        .finalize();
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(hash.as_ref());
+    bytes
+}
+
+fn key_gen<P: SchemeParams + 'static>(
+    parties: &Parties,
+    messages: &mut Vec<VectorMessage>,
+) -> Result<Vec<KeyShare<P>>, TestVectorError> {
+    let sessions = parties.make_sessions(Ceremony::KeyGen, |rng, sr, signer, verifiers| {
+        make_key_gen_session::<P, _, _, _, _>(rng, sr, signer, verifiers)
+    })?;
+    run_ceremony(parties, Ceremony::KeyGen, sessions, messages)
+}
+
+/// Runs the sessions of all the parties on a [`TestNetwork`], recording the messages,
+/// and returns the results in the order of the parties.
+///
+/// The messages are sorted in the order they would be created if the rounds were executed
+/// in lockstep: by round, then by sender, then by receiver.
+fn run_ceremony<Res: ProtocolResult>(
+    parties: &Parties,
+    ceremony: Ceremony,
+    sessions: Vec<Session<Res, Signature, SigningKey, VerifyingKey>>,
+    messages: &mut Vec<VectorMessage>,
+) -> Result<Vec<Res::Success>, TestVectorError> {
+    let ceremony_error = |err: &dyn core::fmt::Display| TestVectorError::Ceremony(err.to_string());
+    // The deterministic sessions do not use it.
+    let mut rng = ChaCha20Rng::from_seed(parties.derive(b"unused", 0));
+
+    let (mut outcomes, sent) = TestNetwork::new(sessions).run_and_record(&mut rng);
+
+    let mut recorded = sent
+        .into_iter()
+        .map(|sent| {
+            Ok(VectorMessage {
+                round: sent.round,
+                is_echo_round: sent.is_echo_round,
+                from: parties.index(&sent.from) as u32,
+                to: parties.index(&sent.to) as u32,
+                message: sent
+                    .message
+                    .canonical_bytes()
+                    .map_err(|err| ceremony_error(&err))?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    recorded.sort_by_key(|message| {
+        (
+            message.round,
+            message.is_echo_round,
+            message.from,
+            message.to,
+        )
+    });
+    messages.extend(recorded);
+
+    parties
+        .verifiers
+        .iter()
+        .map(|verifier| match outcomes.remove(verifier) {
+            Some(Ok(result)) => Ok(result),
+            Some(Err(err)) => Err(ceremony_error(&err)),
+            None => Err(TestVectorError::Ceremony(format!(
+                "A party of {} did not finish",
+                ceremony.as_str()
+            ))),
+        })
+        .collect()
+}
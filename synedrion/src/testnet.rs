@@ -0,0 +1,495 @@
+//! A synchronous in-memory network running the sessions of all the parties to completion
+//! (behind the `testnet` feature).
+//!
+//! Intended for the integration tests of the code orchestrating the sessions:
+//! the messages are routed between the parties in a single thread, without any real networking,
+//! and the delivery can be disrupted by injecting [`Fault`]s.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashVerifier, RandomizedPrehashSigner},
+    Keypair,
+};
+
+use crate::rounds::ProtocolResult;
+#[cfg(feature = "testing")]
+use crate::sessions::Misbehavior;
+use crate::sessions::{
+    Artifact, CombinedMessage, Error, FinalizeOutcome, LocalError, MessageFormat,
+    PreprocessedMessage, ReceiveOutcome, RoundAccumulator, Session,
+};
+
+/// Creates the signers of `num_parties` parties with `make_signer`,
+/// sorted in the order of their verifiers, and returns them along with the set of the verifiers.
+///
+/// The party indices of the key shares created with `new_centralized()`
+/// follow the order of the verifiers, so the returned signers can be zipped with such shares.
+pub fn make_signers<Signer, Verifier>(
+    num_parties: usize,
+    make_signer: impl FnMut() -> Signer,
+) -> (Vec<Signer>, BTreeSet<Verifier>)
+where
+    Signer: Keypair<VerifyingKey = Verifier>,
+    Verifier: Ord,
+{
+    let mut signers = core::iter::repeat_with(make_signer)
+        .take(num_parties)
+        .collect::<Vec<_>>();
+    signers.sort_by_key(|signer| signer.verifying_key());
+    let verifiers = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect();
+    (signers, verifiers)
+}
+
+/// Selects the messages a [`Fault`] applies to.
+///
+/// Matches all the messages unless narrowed down with the builder methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageFilter<Verifier> {
+    from: Option<Verifier>,
+    to: Option<Verifier>,
+    round: Option<u8>,
+}
+
+impl<Verifier: PartialEq> MessageFilter<Verifier> {
+    /// Creates a filter matching all the messages.
+    pub fn all() -> Self {
+        Self {
+            from: None,
+            to: None,
+            round: None,
+        }
+    }
+
+    /// Only match the messages sent by `party`.
+    pub fn sent_by(self, party: Verifier) -> Self {
+        Self {
+            from: Some(party),
+            ..self
+        }
+    }
+
+    /// Only match the messages sent to `party`.
+    pub fn sent_to(self, party: Verifier) -> Self {
+        Self {
+            to: Some(party),
+            ..self
+        }
+    }
+
+    /// Only match the messages of the given round (including its echo round, if there is one).
+    pub fn in_round(self, round: u8) -> Self {
+        Self {
+            round: Some(round),
+            ..self
+        }
+    }
+
+    fn matches(&self, from: &Verifier, to: &Verifier, round: u8) -> bool {
+        self.from.as_ref().is_none_or(|party| party == from)
+            && self.to.as_ref().is_none_or(|party| party == to)
+            && self.round.is_none_or(|r| r == round)
+    }
+}
+
+/// A disruption of the message delivery injected by a [`TestNetwork`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault<Verifier> {
+    /// The matching messages are lost.
+    Drop(MessageFilter<Verifier>),
+    /// The matching messages are held back until there are no other messages in flight,
+    /// as if they were sent over a slow link.
+    Delay(MessageFilter<Verifier>),
+    /// The matching messages are created by
+    /// [`Session::make_malicious_message`] with the given misbehavior.
+    #[cfg(feature = "testing")]
+    Misbehave(MessageFilter<Verifier>, Misbehavior),
+    /// The party stops when it reaches the given round:
+    /// it does not send any messages starting from this round, and its outcome is not reported.
+    Crash {
+        /// The crashing party.
+        party: Verifier,
+        /// The round in which the party crashes.
+        round: u8,
+    },
+}
+
+/// A message sent by a party, as recorded by [`TestNetwork::run_and_record`].
+#[derive(Debug, Clone)]
+pub struct SentMessage<Sig, Verifier> {
+    /// The sender.
+    pub from: Verifier,
+    /// The receiver.
+    pub to: Verifier,
+    /// The round the message was created in.
+    pub round: u8,
+    /// Whether the message was created in the echo round following `round`.
+    pub is_echo_round: bool,
+    /// The message.
+    pub message: CombinedMessage<Sig>,
+}
+
+/// A message in flight.
+struct Envelope<Sig, Verifier> {
+    from: Verifier,
+    to: Verifier,
+    message: CombinedMessage<Sig>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum PartyState<Res: ProtocolResult, Sig, Signer, Verifier, F> {
+    Running {
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        accum: RoundAccumulator<Sig>,
+    },
+    Finished(Result<Res::Success, Error<Res, Verifier>>),
+    Crashed,
+}
+
+/// Runs the sessions of all the parties to completion, routing the messages between them in memory.
+///
+/// The messages are delivered one at a time, in the order they were sent
+/// (or in a random order, see [`with_random_delivery_order`](Self::with_random_delivery_order)).
+/// When there are no more messages in flight but some parties are still waiting,
+/// the party in the earliest round declares the parties it is waiting for absent
+/// with [`Session::finalize_with_absent`], as it would after a timeout in a real network.
+/// A message arriving after that is rejected by the receiver as out of order.
+pub struct TestNetwork<Res: ProtocolResult, Sig, Signer, Verifier, F> {
+    sessions: Vec<Session<Res, Sig, Signer, Verifier, F>>,
+    faults: Vec<Fault<Verifier>>,
+    random_delivery_order: bool,
+    in_flight: VecDeque<Envelope<Sig, Verifier>>,
+    delayed: VecDeque<Envelope<Sig, Verifier>>,
+    log: Option<Vec<SentMessage<Sig, Verifier>>>,
+}
+
+impl<Res, Sig, Signer, Verifier, F> TestNetwork<Res, Sig, Signer, Verifier, F>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    /// Creates a network for the given sessions, one for each party.
+    pub fn new(sessions: impl IntoIterator<Item = Session<Res, Sig, Signer, Verifier, F>>) -> Self {
+        Self {
+            sessions: sessions.into_iter().collect(),
+            faults: Vec::new(),
+            random_delivery_order: false,
+            in_flight: VecDeque::new(),
+            delayed: VecDeque::new(),
+            log: None,
+        }
+    }
+
+    /// Injects a fault into the message delivery.
+    pub fn with_fault(mut self, fault: Fault<Verifier>) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Delivers the messages in flight in a random order instead of the order they were sent in.
+    pub fn with_random_delivery_order(mut self) -> Self {
+        self.random_delivery_order = true;
+        self
+    }
+
+    /// Runs the sessions until each of them succeeds, fails, or crashes,
+    /// returning the outcomes of the parties that did not crash.
+    pub fn run(
+        mut self,
+        rng: &mut impl CryptoRngCore,
+    ) -> BTreeMap<Verifier, Result<Res::Success, Error<Res, Verifier>>> {
+        self.run_parties(rng)
+    }
+
+    /// Same as [`run`](Self::run), but also returns all the messages sent by the parties
+    /// (including the ones lost to a [`Fault::Drop`]), in the order they were created.
+    #[allow(clippy::type_complexity)]
+    pub fn run_and_record(
+        mut self,
+        rng: &mut impl CryptoRngCore,
+    ) -> (
+        BTreeMap<Verifier, Result<Res::Success, Error<Res, Verifier>>>,
+        Vec<SentMessage<Sig, Verifier>>,
+    ) {
+        self.log = Some(Vec::new());
+        let outcomes = self.run_parties(rng);
+        (outcomes, self.log.unwrap_or_default())
+    }
+
+    fn run_parties(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+    ) -> BTreeMap<Verifier, Result<Res::Success, Error<Res, Verifier>>> {
+        let mut states = BTreeMap::new();
+        for session in core::mem::take(&mut self.sessions) {
+            let verifier = session.verifier();
+            let state = self.start_round(rng, session, Vec::new());
+            states.insert(verifier, state);
+        }
+
+        loop {
+            let envelope = if self.random_delivery_order && !self.in_flight.is_empty() {
+                let idx = (rng.next_u64() % self.in_flight.len() as u64) as usize;
+                self.in_flight.swap_remove_back(idx)
+            } else {
+                self.in_flight.pop_front()
+            };
+            let envelope = envelope.or_else(|| self.delayed.pop_front());
+
+            let (verifier, state) = match envelope {
+                Some(envelope) => {
+                    let verifier = envelope.to.clone();
+                    match states.remove(&verifier) {
+                        Some(PartyState::Running { session, accum }) => {
+                            (verifier, self.deliver(rng, session, accum, envelope))
+                        }
+                        // The messages to the parties that have already stopped are lost.
+                        Some(state) => (verifier, state),
+                        None => continue,
+                    }
+                }
+                None => {
+                    // Nothing is going to arrive anymore, so the party in the earliest round
+                    // gives up on the messages it is still waiting for.
+                    let stalled = states
+                        .iter()
+                        .filter_map(|(verifier, state)| match state {
+                            PartyState::Running { session, .. } => {
+                                Some((session.current_round(), verifier.clone()))
+                            }
+                            _ => None,
+                        })
+                        .min();
+                    let Some((_round, verifier)) = stalled else {
+                        break;
+                    };
+                    let Some(PartyState::Running { session, accum }) = states.remove(&verifier)
+                    else {
+                        unreachable!("the party was selected among the running ones");
+                    };
+                    (verifier, self.finalize_with_absent(rng, session, accum))
+                }
+            };
+            states.insert(verifier, state);
+        }
+
+        states
+            .into_iter()
+            .filter_map(|(verifier, state)| match state {
+                PartyState::Finished(result) => Some((verifier, result)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sends the messages of this party for the current round of the session,
+    /// and processes the messages for this round received in advance.
+    fn start_round(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        cached_messages: Vec<PreprocessedMessage<Sig>>,
+    ) -> PartyState<Res, Sig, Signer, Verifier, F> {
+        let verifier = session.verifier();
+        let (round, is_echo_round) = session.current_round();
+        let crashed = self.faults.iter().any(|fault| {
+            matches!(fault, Fault::Crash { party, round: crash_round }
+                if party == &verifier && *crash_round <= round)
+        });
+        if crashed {
+            return PartyState::Crashed;
+        }
+
+        let mut accum = session.make_accumulator();
+        for destination in session.message_destinations() {
+            let (message, artifact) = match self.make_message(rng, &session, &destination, round) {
+                Ok(result) => result,
+                Err(error) => return PartyState::Finished(Err(Error::Local(error))),
+            };
+            if let Err(error) = accum.add_artifact(artifact) {
+                return PartyState::Finished(Err(Error::Local(error)));
+            }
+            if let Some(message) = message {
+                self.send(&verifier, destination, (round, is_echo_round), message);
+            }
+        }
+
+        for preprocessed in cached_messages {
+            if let Err(error) = process_message(&session, &mut accum, preprocessed) {
+                return PartyState::Finished(Err(error));
+            }
+        }
+
+        self.try_finalize(rng, session, accum)
+    }
+
+    fn deliver(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        accum: RoundAccumulator<Sig>,
+        envelope: Envelope<Sig, Verifier>,
+    ) -> PartyState<Res, Sig, Signer, Verifier, F> {
+        match session.receive_and_maybe_finalize(rng, accum, &envelope.from, envelope.message) {
+            Ok(ReceiveOutcome::Pending { session, accum }) => {
+                PartyState::Running { session, accum }
+            }
+            Ok(ReceiveOutcome::Finalized(outcome)) => self.next_round(rng, Ok(outcome)),
+            Err(error) => PartyState::Finished(Err(error)),
+        }
+    }
+
+    fn try_finalize(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        accum: RoundAccumulator<Sig>,
+    ) -> PartyState<Res, Sig, Signer, Verifier, F> {
+        match session.can_finalize(&accum) {
+            Ok(true) => {}
+            Ok(false) => return PartyState::Running { session, accum },
+            Err(error) => return PartyState::Finished(Err(Error::Local(error))),
+        }
+        let outcome = session.finalize_round(rng, accum);
+        self.next_round(rng, outcome)
+    }
+
+    fn finalize_with_absent(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        session: Session<Res, Sig, Signer, Verifier, F>,
+        accum: RoundAccumulator<Sig>,
+    ) -> PartyState<Res, Sig, Signer, Verifier, F> {
+        let absent = match session.missing_messages(&accum) {
+            Ok(absent) => absent,
+            Err(error) => return PartyState::Finished(Err(Error::Local(error))),
+        };
+        let outcome = session.finalize_with_absent(rng, accum, &absent);
+        self.next_round(rng, outcome)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn next_round(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        outcome: Result<FinalizeOutcome<Res, Sig, Signer, Verifier, F>, Error<Res, Verifier>>,
+    ) -> PartyState<Res, Sig, Signer, Verifier, F> {
+        match outcome {
+            Ok(FinalizeOutcome::Success(result)) => PartyState::Finished(Ok(result)),
+            Ok(FinalizeOutcome::AnotherRound {
+                session,
+                cached_messages,
+            }) => self.start_round(rng, session, cached_messages),
+            Err(error) => PartyState::Finished(Err(error)),
+        }
+    }
+
+    /// Creates the message for `destination`, applying the misbehavior injected for it, if any.
+    ///
+    /// Returns `None` instead of the message if it is not to be sent.
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "testing"), allow(unused_variables))]
+    fn make_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        session: &Session<Res, Sig, Signer, Verifier, F>,
+        destination: &Verifier,
+        round: u8,
+    ) -> Result<(Option<CombinedMessage<Sig>>, Artifact<Verifier>), LocalError> {
+        #[cfg(feature = "testing")]
+        {
+            let verifier = session.verifier();
+            let misbehavior = self.faults.iter().find_map(|fault| match fault {
+                Fault::Misbehave(filter, misbehavior)
+                    if filter.matches(&verifier, destination, round) =>
+                {
+                    Some(*misbehavior)
+                }
+                _ => None,
+            });
+            if let Some(misbehavior) = misbehavior {
+                if let Some((message, artifact)) =
+                    session.make_malicious_message(rng, destination, misbehavior)?
+                {
+                    return Ok((Some(message), artifact));
+                }
+                // `Misbehavior::Silence`: the party still keeps the artifact of the message.
+                let (_message, artifact) = session.make_message(rng, destination)?;
+                return Ok((None, artifact));
+            }
+        }
+        let (message, artifact) = session.make_message(rng, destination)?;
+        Ok((Some(message), artifact))
+    }
+
+    fn send(
+        &mut self,
+        from: &Verifier,
+        to: Verifier,
+        (round, is_echo_round): (u8, bool),
+        message: CombinedMessage<Sig>,
+    ) {
+        if let Some(log) = self.log.as_mut() {
+            log.push(SentMessage {
+                from: from.clone(),
+                to: to.clone(),
+                round,
+                is_echo_round,
+                message: message.clone(),
+            });
+        }
+
+        let matches = |filter: &MessageFilter<Verifier>| filter.matches(from, &to, round);
+        let dropped = self
+            .faults
+            .iter()
+            .any(|fault| matches!(fault, Fault::Drop(filter) if matches(filter)));
+        let delayed = self
+            .faults
+            .iter()
+            .any(|fault| matches!(fault, Fault::Delay(filter) if matches(filter)));
+        if dropped {
+            return;
+        }
+
+        let envelope = Envelope {
+            from: from.clone(),
+            to,
+            message,
+        };
+        if delayed {
+            self.delayed.push_back(envelope);
+        } else {
+            self.in_flight.push_back(envelope);
+        }
+    }
+}
+
+fn process_message<Res, Sig, Signer, Verifier, F>(
+    session: &Session<Res, Sig, Signer, Verifier, F>,
+    accum: &mut RoundAccumulator<Sig>,
+    preprocessed: PreprocessedMessage<Sig>,
+) -> Result<(), Error<Res, Verifier>>
+where
+    Res: ProtocolResult,
+    Signer: RandomizedPrehashSigner<Sig> + Keypair<VerifyingKey = Verifier>,
+    Verifier: Debug + Clone + PrehashVerifier<Sig> + Ord,
+    Sig: Clone + Serialize + for<'de> Deserialize<'de> + PartialEq + Eq,
+    F: MessageFormat,
+{
+    let processed = session.process_message(preprocessed)?;
+    accum
+        .add_processed_message(processed)
+        .map_err(Error::Local)?
+        .map_err(Error::Remote)
+}
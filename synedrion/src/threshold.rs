@@ -1,15 +1,25 @@
 use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
+#[cfg(any(test, feature = "key-refresh"))]
+use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
-use k256::ecdsa::VerifyingKey;
+use displaydoc::Display;
 use rand_core::CryptoRngCore;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
+use crate::bip32::{derive_tweak, ChainCode, DeriveChildError};
 use crate::cggmp21::SchemeParams;
-use crate::common::{make_aux_info, KeyShare, PublicAuxInfo, SecretAuxInfo};
-use crate::curve::{Point, Scalar};
+#[cfg(any(test, feature = "key-refresh"))]
+use crate::common::make_aux_info;
+use crate::common::{
+    decode_key_share_bytes, CheckedKeyShareHeader, KeyShare, KeyShareDecodingError,
+    KeyShareFingerprint, KeyShareHeader, PublicAuxInfo, SecretAuxInfo,
+};
+use crate::curve::{Point, Scalar, SigningKey, VerifyingKey};
 use crate::rounds::PartyIdx;
 use crate::tools::{
     bitvec::BitVec,
@@ -43,7 +53,7 @@ impl<P: SchemeParams> ThresholdKeyShareSeed<P> {
         rng: &mut impl CryptoRngCore,
         threshold: usize,
         num_parties: usize,
-        signing_key: Option<&k256::ecdsa::SigningKey>,
+        signing_key: Option<&SigningKey>,
     ) -> Box<[Self]> {
         debug_assert!(threshold <= num_parties); // TODO (#68): make the method fallible
 
@@ -88,13 +98,12 @@ impl<P: SchemeParams> ThresholdKeyShareSeed<P> {
 
 /// A threshold variant of the key share, where any `threshold` shares our of the total number
 /// is enough to perform signing.
+///
+/// The serialized share starts with the same header as a serialized [`KeyShare`]
+/// (see [`to_bytes`](Self::to_bytes)).
 // TODO (#77): Debug can be derived automatically here if `secret_share` is wrapped in its own struct,
 // or in a `SecretBox`-type wrapper.
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(bound(serialize = "SecretAuxInfo<P>: Serialize,
-        PublicAuxInfo<P>: Serialize"))]
-#[serde(bound(deserialize = "SecretAuxInfo<P>: for <'x> Deserialize<'x>,
-        PublicAuxInfo<P>: for <'x> Deserialize<'x>"))]
+#[derive(Clone)]
 pub struct ThresholdKeyShare<P: SchemeParams> {
     pub(crate) index: ShareIdx,
     pub(crate) threshold: u32,
@@ -104,38 +113,142 @@ pub struct ThresholdKeyShare<P: SchemeParams> {
     pub(crate) public_aux: BTreeMap<ShareIdx, PublicAuxInfo<P>>,
     pub(crate) init_id: BitVec,
     pub(crate) share_set_id: HashOutput,
+    pub(crate) epoch: u64,
+}
+
+#[derive(Serialize)]
+#[serde(bound(serialize = "SecretAuxInfo<P>: Serialize,
+        PublicAuxInfo<P>: Serialize"))]
+struct ThresholdKeyShareEnvelopeRef<'a, P: SchemeParams> {
+    header: KeyShareHeader,
+    index: &'a ShareIdx,
+    secret_share: &'a Scalar,
+    public_shares: &'a BTreeMap<ShareIdx, Point>,
+    secret_aux: &'a SecretAuxInfo<P>,
+    public_aux: &'a BTreeMap<ShareIdx, PublicAuxInfo<P>>,
+    init_id: &'a BitVec,
+    share_set_id: &'a HashOutput,
+    epoch: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "SecretAuxInfo<P>: for<'x> Deserialize<'x>,
+        PublicAuxInfo<P>: for <'x> Deserialize<'x>"))]
+struct ThresholdKeyShareEnvelope<P: SchemeParams> {
+    header: CheckedKeyShareHeader<P>,
+    index: ShareIdx,
+    secret_share: Scalar,
+    public_shares: BTreeMap<ShareIdx, Point>,
+    secret_aux: SecretAuxInfo<P>,
+    public_aux: BTreeMap<ShareIdx, PublicAuxInfo<P>>,
+    init_id: BitVec,
+    share_set_id: HashOutput,
+    epoch: u64,
+}
+
+impl<P: SchemeParams> Serialize for ThresholdKeyShare<P>
+where
+    SecretAuxInfo<P>: Serialize,
+    PublicAuxInfo<P>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let header = KeyShareHeader::new::<P>(
+            self.public_shares.len(),
+            self.threshold as usize,
+            self.verifying_key_as_point(),
+        );
+        ThresholdKeyShareEnvelopeRef {
+            header,
+            index: &self.index,
+            secret_share: &self.secret_share,
+            public_shares: &self.public_shares,
+            secret_aux: &self.secret_aux,
+            public_aux: &self.public_aux,
+            init_id: &self.init_id,
+            share_set_id: &self.share_set_id,
+            epoch: self.epoch,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P: SchemeParams> Deserialize<'de> for ThresholdKeyShare<P>
+where
+    SecretAuxInfo<P>: for<'x> Deserialize<'x>,
+    PublicAuxInfo<P>: for<'x> Deserialize<'x>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let envelope = ThresholdKeyShareEnvelope::deserialize(deserializer)?;
+        ThresholdKeyShare::from_envelope(envelope).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The result of the threshold KeyRefresh protocol - the update to the threshold key share
+/// (see [`make_threshold_refresh_session`](crate::make_threshold_refresh_session)).
+#[derive(Debug, Clone)]
+pub struct ThresholdKeyShareChange<P: SchemeParams> {
+    pub(crate) index: ShareIdx,
+    /// The value to be added to the secret share.
+    pub(crate) secret_share_change: Scalar,
+    /// The values to be added to the public shares of all the share indices.
+    pub(crate) public_share_changes: BTreeMap<ShareIdx, Point>,
+    pub(crate) phantom: PhantomData<P>,
 }
 
 impl<P: SchemeParams> ThresholdKeyShare<P> {
     /// Returns `num_parties` of random self-consistent key shares
     /// (which in a decentralized case would be the output of KeyGen + Auxiliary protocols).
+    #[cfg(any(test, feature = "key-refresh"))]
     pub fn new_centralized(
         rng: &mut impl CryptoRngCore,
         threshold: usize,
         num_parties: usize,
-        signing_key: Option<&k256::ecdsa::SigningKey>,
+        signing_key: Option<&SigningKey>,
     ) -> Box<[Self]> {
         debug_assert!(threshold <= num_parties); // TODO (#68): make the method fallible
+        let weights = vec![1; num_parties];
+        Self::new_centralized_weighted(rng, threshold, &weights, signing_key)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
 
+    /// Returns the shares of `weights.len()` parties, where the party `i` holds `weights[i]`
+    /// consecutive shares of a set with `threshold` out of `weights.iter().sum()` shares required,
+    /// and all the shares of a party have the same auxiliary information.
+    #[cfg(any(test, feature = "key-refresh"))]
+    fn new_centralized_weighted(
+        rng: &mut impl CryptoRngCore,
+        threshold: usize,
+        weights: &[usize],
+        signing_key: Option<&SigningKey>,
+    ) -> Vec<Vec<Self>> {
         let secret = match signing_key {
             None => Scalar::random(rng),
             Some(sk) => Scalar::from(sk.as_nonzero_scalar()),
         };
 
-        let share_idxs = shamir_evaluation_points(num_parties);
+        let share_idxs = shamir_evaluation_points(weights.iter().sum());
         let secret_shares = shamir_split(rng, &secret, threshold, &share_idxs);
         let public_shares = secret_shares
             .iter()
             .map(|(idx, share)| (*idx, share.mul_by_generator()))
             .collect::<BTreeMap<_, _>>();
 
-        let (secret_aux, public_aux) = make_aux_info(rng, num_parties);
+        let mut party_share_idxs = Vec::with_capacity(weights.len());
+        let mut remaining_idxs = share_idxs.as_slice();
+        for weight in weights {
+            let (idxs, rest) = remaining_idxs.split_at(*weight);
+            party_share_idxs.push(idxs);
+            remaining_idxs = rest;
+        }
 
-        let public_aux = public_aux
-            .into_vec()
-            .into_iter()
-            .enumerate()
-            .map(|(idx, public)| (share_idxs[idx], public))
+        let (secret_aux, public_aux) = make_aux_info(rng, weights.len());
+
+        let public_aux = party_share_idxs
+            .iter()
+            .zip(public_aux.iter())
+            .flat_map(|(idxs, public)| idxs.iter().map(|idx| (*idx, public.clone())))
             .collect::<BTreeMap<_, _>>();
 
         let init_id = BitVec::random(rng, P::SECURITY_PARAMETER);
@@ -147,22 +260,27 @@ impl<P: SchemeParams> ThresholdKeyShare<P> {
         let public_shares_vec = public_shares.values().cloned().collect::<Vec<_>>();
         let public_aux_vec = public_aux.values().cloned().collect::<Vec<_>>();
 
+        let epoch = 0;
         let share_set_id =
-            KeyShare::make_share_set_id(&init_id, &public_shares_vec, &public_aux_vec);
+            KeyShare::make_share_set_id(&init_id, epoch, &public_shares_vec, &public_aux_vec);
 
-        secret_aux
-            .into_vec()
-            .into_iter()
-            .enumerate()
-            .map(|(idx, secret_aux)| ThresholdKeyShare {
-                index: share_idxs[idx],
-                threshold: threshold as u32,
-                secret_share: secret_shares[&share_idxs[idx]],
-                public_shares: public_shares.clone(),
-                secret_aux,
-                public_aux: public_aux.clone(),
-                init_id: init_id.clone(),
-                share_set_id,
+        party_share_idxs
+            .iter()
+            .zip(secret_aux.iter())
+            .map(|(idxs, secret_aux)| {
+                idxs.iter()
+                    .map(|idx| ThresholdKeyShare {
+                        index: *idx,
+                        threshold: threshold as u32,
+                        secret_share: secret_shares[idx],
+                        public_shares: public_shares.clone(),
+                        secret_aux: secret_aux.clone(),
+                        public_aux: public_aux.clone(),
+                        init_id: init_id.clone(),
+                        share_set_id,
+                        epoch,
+                    })
+                    .collect()
             })
             .collect()
     }
@@ -178,14 +296,203 @@ impl<P: SchemeParams> ThresholdKeyShare<P> {
         self.verifying_key_as_point().to_verifying_key().unwrap()
     }
 
+    /// Returns the fingerprint of this set of shares (see [`KeyShare::fingerprint`]).
+    ///
+    /// The threshold is included, so it is only the same as the fingerprint of a [`KeyShare`]
+    /// for an n-of-n share (e.g. the one created by [`from_key_share`](Self::from_key_share)).
+    pub fn fingerprint(&self) -> KeyShareFingerprint {
+        KeyShareHeader::new::<P>(
+            self.public_shares.len(),
+            self.threshold as usize,
+            self.verifying_key_as_point(),
+        )
+        .fingerprint()
+    }
+
+    /// Returns the number of refreshes applied to this share set since its creation
+    /// (see [`KeyShare::epoch`]).
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
     /// Returns the index of this share's party.
     pub fn index(&self) -> ShareIdx {
         self.index
     }
 
+    /// Serializes the key share into a versioned binary format that can be read back
+    /// with [`from_bytes`](Self::from_bytes).
+    ///
+    /// The encoding starts with the same header as [`KeyShare::to_bytes`],
+    /// with the actual threshold of the share set, followed by:
+    ///
+    /// | Field          | Encoding                                                               |
+    /// |----------------|------------------------------------------------------------------------|
+    /// | `index`        | byte string, a 32-byte big-endian scalar                               |
+    /// | `secret_share` | byte string, a 32-byte big-endian scalar                               |
+    /// | `public_shares`| map from the share indices to byte strings, 33-byte compressed SEC1 points |
+    /// | `secret_aux`   | the Paillier primes `p` and `q`, then the El Gamal secret scalar       |
+    /// | `public_aux`   | map from the share indices to the public auxiliary data, as in [`KeyShare::to_bytes`] |
+    /// | `init_id`      | byte string                                                            |
+    /// | `share_set_id` | byte string, 32 bytes                                                  |
+    /// | `epoch`        | `u64`                                                                  |
+    ///
+    /// A map is encoded as a `u64` length followed by the key-value pairs in ascending key order.
+    ///
+    /// The same header and fields are written by the [`Serialize`] implementation
+    /// in any other `serde` format.
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        // Serialization into a vector cannot fail for this type.
+        bincode::serialize(self).unwrap().into()
+    }
+
+    /// Deserializes a key share created by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareDecodingError> {
+        let envelope = decode_key_share_bytes::<P, ThresholdKeyShareEnvelope<P>>(bytes)?;
+        Self::from_envelope(envelope)
+    }
+
+    fn from_envelope(
+        envelope: ThresholdKeyShareEnvelope<P>,
+    ) -> Result<Self, KeyShareDecodingError> {
+        let ThresholdKeyShareEnvelope {
+            header: CheckedKeyShareHeader(header, _),
+            index,
+            secret_share,
+            public_shares,
+            secret_aux,
+            public_aux,
+            init_id,
+            share_set_id,
+            epoch,
+        } = envelope;
+
+        // Assembled before the checks so that the secrets are zeroized if any of them fails.
+        let key_share = Self {
+            index,
+            threshold: header.threshold,
+            secret_share,
+            public_shares,
+            secret_aux,
+            public_aux,
+            init_id,
+            share_set_id,
+            epoch,
+        };
+
+        let num_shares = key_share.public_shares.len();
+        let threshold = key_share.threshold as usize;
+        if threshold == 0 || threshold > num_shares || header.num_parties as usize != num_shares {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+        if !key_share
+            .public_shares
+            .keys()
+            .eq(key_share.public_aux.keys())
+        {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+        let public_share = key_share
+            .public_shares
+            .get(&key_share.index)
+            .ok_or(KeyShareDecodingError::Inconsistent)?;
+        if key_share.secret_share.mul_by_generator() != *public_share {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+        if header.verifying_key != key_share.verifying_key_as_point()
+            || header.verifying_key == Point::IDENTITY
+        {
+            return Err(KeyShareDecodingError::Inconsistent);
+        }
+
+        Ok(key_share)
+    }
+
+    /// Updates a threshold key share with a change obtained from the threshold KeyRefresh protocol.
+    ///
+    /// The verifying key and the threshold stay the same, and the epoch is incremented.
+    pub fn update(self, change: ThresholdKeyShareChange<P>) -> Self {
+        // TODO (#68): check that the share index and the set of share indices are the same
+        let secret_share = self.secret_share + change.secret_share_change;
+        let public_shares = self
+            .public_shares
+            .iter()
+            .map(|(idx, public_share)| (*idx, *public_share + change.public_share_changes[idx]))
+            .collect::<BTreeMap<_, _>>();
+        let epoch = self.epoch + 1;
+
+        // TODO (#20): see the comment in `new_centralized()`.
+        let public_shares_vec = public_shares.values().cloned().collect::<Vec<_>>();
+        let public_aux_vec = self.public_aux.values().cloned().collect::<Vec<_>>();
+        let share_set_id =
+            KeyShare::make_share_set_id(&self.init_id, epoch, &public_shares_vec, &public_aux_vec);
+
+        Self {
+            index: change.index,
+            threshold: self.threshold,
+            secret_share,
+            public_shares,
+            secret_aux: self.secret_aux.clone(),
+            public_aux: self.public_aux.clone(),
+            init_id: self.init_id.clone(),
+            share_set_id,
+            epoch,
+        }
+    }
+
+    /// Returns the key share for the non-hardened BIP32 child key number `child_number`
+    /// along with its chain code, given the chain code of this share's verifying key.
+    ///
+    /// All the parties must use the same `chain_code` and `child_number`
+    /// to obtain a consistent set of child key shares.
+    pub fn derive_child(
+        &self,
+        chain_code: &ChainCode,
+        child_number: u32,
+    ) -> Result<(Self, ChainCode), DeriveChildError> {
+        let (tweak, child_chain_code) =
+            derive_tweak(&self.verifying_key_as_point(), chain_code, child_number)?;
+
+        // Since the interpolation coefficients sum up to 1,
+        // adding the tweak to every share adds it to the shared secret.
+        let secret_share = self.secret_share + tweak;
+        let public_tweak = tweak.mul_by_generator();
+        let public_shares = self
+            .public_shares
+            .iter()
+            .map(|(idx, public_share)| (*idx, *public_share + public_tweak))
+            .collect::<BTreeMap<_, _>>();
+
+        // TODO (#20): see the comment in `new_centralized()`.
+        let public_shares_vec = public_shares.values().cloned().collect::<Vec<_>>();
+        let public_aux_vec = self.public_aux.values().cloned().collect::<Vec<_>>();
+        let share_set_id = KeyShare::make_share_set_id(
+            &self.init_id,
+            self.epoch,
+            &public_shares_vec,
+            &public_aux_vec,
+        );
+
+        let key_share = Self {
+            index: self.index,
+            threshold: self.threshold,
+            secret_share,
+            public_shares,
+            secret_aux: self.secret_aux.clone(),
+            public_aux: self.public_aux.clone(),
+            init_id: self.init_id.clone(),
+            share_set_id,
+            epoch: self.epoch,
+        };
+        Ok((key_share, child_chain_code))
+    }
+
     /// Converts a t-of-n key share into a t-of-t key share
     /// (for the `t` share indices supplied as `share_idxs`)
     /// that can be used in the presigning/signing protocols.
+    ///
+    /// `share_idxs` must be listed in the order of the verifiers of the parties holding them
+    /// (as given by `Ord`).
     pub fn to_key_share(&self, share_idxs: &[ShareIdx]) -> KeyShare<P> {
         debug_assert!(share_idxs.len() == self.threshold as usize);
         // TODO (#68): assert that all indices are distinct
@@ -215,7 +522,381 @@ impl<P: SchemeParams> ThresholdKeyShare<P> {
             public_aux,
             init_id: self.init_id.clone(),
             share_set_id: self.share_set_id,
+            epoch: self.epoch,
+        }
+    }
+
+    /// Selects the parties for retrying a session after some of the holders of the share set
+    /// have been blamed for an abort, and converts this share for them
+    /// (see [`to_key_share`](Self::to_key_share)).
+    ///
+    /// `holders` maps the verifiers of all the holders of the share set to their share indices,
+    /// and `blamed` are the parties to exclude
+    /// (e.g. accumulated from [`Error::blamed_parties`](crate::sessions::Error::blamed_parties)
+    /// over the previous attempts).
+    /// The quorum consists of the first `threshold` remaining holders in the order of their verifiers,
+    /// so all the parties calling this method with the same arguments arrive at the same quorum.
+    pub fn retry_quorum<Verifier: Clone + Ord>(
+        &self,
+        holders: &BTreeMap<Verifier, ShareIdx>,
+        blamed: &BTreeSet<Verifier>,
+    ) -> Result<RetryQuorum<P, Verifier>, RetryQuorumError> {
+        let share_idxs = holders.values().collect::<BTreeSet<_>>();
+        if share_idxs.len() != holders.len()
+            || share_idxs
+                .iter()
+                .any(|idx| !self.public_shares.contains_key(idx))
+        {
+            return Err(RetryQuorumError::InvalidShareIdxs);
+        }
+        if !share_idxs.contains(&self.index) {
+            return Err(RetryQuorumError::NotAHolder);
+        }
+
+        let threshold = self.threshold as usize;
+        let remaining = holders
+            .iter()
+            .filter(|(verifier, _idx)| !blamed.contains(verifier))
+            .collect::<Vec<_>>();
+        if remaining.len() < threshold {
+            return Err(RetryQuorumError::NotEnoughParties {
+                available: remaining.len(),
+                threshold,
+            });
+        }
+
+        let quorum = &remaining[..threshold];
+        let quorum_share_idxs = quorum
+            .iter()
+            .map(|(_verifier, idx)| **idx)
+            .collect::<Vec<_>>();
+        let key_share = if quorum_share_idxs.contains(&self.index) {
+            Some(self.to_key_share(&quorum_share_idxs))
+        } else {
+            None
+        };
+
+        Ok(RetryQuorum {
+            verifiers: quorum
+                .iter()
+                .map(|(verifier, _idx)| (*verifier).clone())
+                .collect(),
+            key_share,
+        })
+    }
+
+    /// Converts a t-of-t key share into a t-of-t threshold key share,
+    /// with the share indices `1..=t` assigned in the order of the parties.
+    ///
+    /// This is the inverse of [`to_key_share`](Self::to_key_share) for the full set of share indices,
+    /// and can be used, e.g., to reshare a key created by KeyGen into a t-of-n one.
+    pub fn from_key_share(key_share: &KeyShare<P>) -> Self {
+        let share_idxs = shamir_evaluation_points(key_share.num_parties());
+        // The additive shares are the Shamir shares multiplied by the interpolation coefficients,
+        // so we just need to divide by them.
+        let inv_coeff = |idx: &ShareIdx| interpolation_coeff(&share_idxs, idx).invert().unwrap();
+
+        let index = share_idxs[key_share.party_index()];
+        let secret_share = key_share.secret_share * inv_coeff(&index);
+        let public_shares = share_idxs
+            .iter()
+            .zip(key_share.public_shares.iter())
+            .map(|(share_idx, public_share)| (*share_idx, *public_share * inv_coeff(share_idx)))
+            .collect();
+        let public_aux = share_idxs
+            .iter()
+            .zip(key_share.public_aux.iter())
+            .map(|(share_idx, public_aux)| (*share_idx, public_aux.clone()))
+            .collect();
+
+        Self {
+            index,
+            threshold: share_idxs.len() as u32,
+            secret_share,
+            public_shares,
+            secret_aux: key_share.secret_aux.clone(),
+            public_aux,
+            init_id: key_share.init_id.clone(),
+            share_set_id: key_share.share_set_id,
+            epoch: key_share.epoch,
+        }
+    }
+
+    /// Converts several t-of-n key shares held by a single party into a key share
+    /// that can be used in the presigning/signing protocols,
+    /// for the case when some parties hold more than one share (e.g. for weighted voting).
+    ///
+    /// `parties` lists the share indices held by each party participating in the protocol,
+    /// in the order of their verifiers (as given by `Ord`);
+    /// together they must constitute exactly `threshold` distinct shares of the set.
+    /// `shares` must be the shares of this party, in the same order as they are listed in `parties`.
+    /// The auxiliary information of the first share in each list is used for the corresponding party.
+    pub fn combine_into_key_share(
+        shares: &[Self],
+        parties: &[Vec<ShareIdx>],
+    ) -> Result<KeyShare<P>, CombineKeySharesError> {
+        let some_share = shares.first().ok_or(CombineKeySharesError::NoShares)?;
+        let verifying_key = some_share.verifying_key_as_point();
+        if shares.iter().any(|share| {
+            share.threshold != some_share.threshold
+                || share.verifying_key_as_point() != verifying_key
+        }) {
+            return Err(CombineKeySharesError::SharesMismatch);
+        }
+
+        let share_idxs = parties.iter().flatten().cloned().collect::<Vec<_>>();
+        let unique_idxs = share_idxs.iter().collect::<BTreeSet<_>>();
+        if unique_idxs.len() != share_idxs.len()
+            || share_idxs.len() != some_share.threshold as usize
+            || !unique_idxs.iter().all(|idx| {
+                some_share.public_shares.contains_key(idx)
+                    && some_share.public_aux.contains_key(idx)
+            })
+            || parties.iter().any(|idxs| idxs.is_empty())
+        {
+            return Err(CombineKeySharesError::InvalidShareIdxs);
+        }
+
+        let my_idxs = shares.iter().map(|share| share.index).collect::<Vec<_>>();
+        let my_idx_position = parties
+            .iter()
+            .position(|party_idxs| party_idxs == &my_idxs)
+            .ok_or(CombineKeySharesError::NotAParticipant)?;
+
+        let secret_share = shares
+            .iter()
+            .map(|share| share.secret_share * interpolation_coeff(&share_idxs, &share.index))
+            .sum();
+        let public_shares = parties
+            .iter()
+            .map(|party_idxs| {
+                party_idxs
+                    .iter()
+                    .map(|share_idx| {
+                        some_share.public_shares[share_idx]
+                            * interpolation_coeff(&share_idxs, share_idx)
+                    })
+                    .sum()
+            })
+            .collect();
+
+        let public_aux = parties
+            .iter()
+            .map(|party_idxs| some_share.public_aux[&party_idxs[0]].clone())
+            .collect();
+
+        Ok(KeyShare {
+            index: PartyIdx::from_usize(my_idx_position),
+            secret_share,
+            public_shares,
+            secret_aux: some_share.secret_aux.clone(),
+            public_aux,
+            init_id: some_share.init_id.clone(),
+            share_set_id: some_share.share_set_id,
+            epoch: some_share.epoch,
+        })
+    }
+}
+
+/// The parties selected to retry a session without the blamed ones
+/// (see [`ThresholdKeyShare::retry_quorum`]).
+#[derive(Debug, Clone)]
+pub struct RetryQuorum<P: SchemeParams, Verifier> {
+    verifiers: BTreeSet<Verifier>,
+    key_share: Option<KeyShare<P>>,
+}
+
+impl<P: SchemeParams, Verifier> RetryQuorum<P, Verifier> {
+    /// Returns the verifiers of the parties to run the new session with.
+    pub fn verifiers(&self) -> &BTreeSet<Verifier> {
+        &self.verifiers
+    }
+
+    /// Returns the key share of this party for the new session,
+    /// or `None` if this party is not a part of the quorum.
+    pub fn key_share(&self) -> Option<&KeyShare<P>> {
+        self.key_share.as_ref()
+    }
+}
+
+/// Possible errors when selecting the parties for a retry with [`ThresholdKeyShare::retry_quorum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum RetryQuorumError {
+    /// Only {available} parties remain after the exclusions, but the threshold is {threshold}.
+    NotEnoughParties {
+        /// The number of holders that are not blamed.
+        available: usize,
+        /// The number of parties required to sign.
+        threshold: usize,
+    },
+    /// A share index is listed twice or does not belong to the set.
+    InvalidShareIdxs,
+    /// The share index of this party is not listed among the holders.
+    NotAHolder,
+}
+
+impl core::error::Error for RetryQuorumError {}
+
+/// Possible errors when combining the shares of a party with [`ThresholdKeyShare::combine_into_key_share`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum CombineKeySharesError {
+    /// No shares were given.
+    NoShares,
+    /// The shares do not belong to the same set (their verifying keys or thresholds differ).
+    SharesMismatch,
+    /// A party has no shares, a share index is listed twice or does not belong to the set,
+    /// or the total number of shares is not equal to the threshold.
+    InvalidShareIdxs,
+    /// The share indices of this party are not listed.
+    NotAParticipant,
+}
+
+impl core::error::Error for CombineKeySharesError {}
+
+/// Possible errors when converting a [`WeightedKeyShare`] into a key share for a signing quorum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum WeightedKeyShareError {
+    /// The total weight of the parties ({weight}) is below the threshold ({threshold}).
+    InsufficientWeight {
+        /// The total weight of the parties.
+        weight: usize,
+        /// The weight required to sign.
+        threshold: usize,
+    },
+    /// There are more parties ({num_parties}) than the threshold ({threshold}),
+    /// so some of them would not hold any shares.
+    TooManyParties {
+        /// The number of parties.
+        num_parties: usize,
+        /// The weight required to sign.
+        threshold: usize,
+    },
+    /// A party has no shares, or a share index is listed twice or does not belong to the set.
+    InvalidShareIdxs,
+    /// The share indices of this party are not listed.
+    NotAParticipant,
+    /// Failed to combine the shares: {0}.
+    Combine(CombineKeySharesError),
+}
+
+impl core::error::Error for WeightedKeyShareError {}
+
+/// The threshold key shares of a party with an integer weight,
+/// holding as many shares of the set as its weight (e.g. for weighted voting).
+///
+/// A signing quorum needs the total weight of at least `threshold`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "ThresholdKeyShare<P>: Serialize"))]
+#[serde(bound(deserialize = "ThresholdKeyShare<P>: for <'x> Deserialize<'x>"))]
+pub struct WeightedKeyShare<P: SchemeParams> {
+    shares: Box<[ThresholdKeyShare<P>]>,
+}
+
+impl<P: SchemeParams> WeightedKeyShare<P> {
+    /// Returns the random self-consistent key shares of the parties with the given `weights`,
+    /// any of whom with the total weight of at least `threshold` can sign.
+    ///
+    /// The party `i` gets the share indices following those of the party `i - 1`,
+    /// starting from 1.
+    #[cfg(any(test, feature = "key-refresh"))]
+    pub fn new_centralized(
+        rng: &mut impl CryptoRngCore,
+        threshold: usize,
+        weights: &[usize],
+        signing_key: Option<&SigningKey>,
+    ) -> Box<[Self]> {
+        // TODO (#68): make the method fallible
+        debug_assert!(weights.iter().all(|weight| *weight > 0));
+        debug_assert!(threshold <= weights.iter().sum());
+        ThresholdKeyShare::new_centralized_weighted(rng, threshold, weights, signing_key)
+            .into_iter()
+            .map(|shares| Self {
+                shares: shares.into(),
+            })
+            .collect()
+    }
+
+    /// Returns the weight of this party (the number of shares it holds).
+    pub fn weight(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Returns the total weight required to sign.
+    pub fn threshold(&self) -> usize {
+        self.shares[0].threshold as usize
+    }
+
+    /// Returns the indices of the shares held by this party.
+    pub fn share_idxs(&self) -> Vec<ShareIdx> {
+        self.shares.iter().map(|share| share.index).collect()
+    }
+
+    /// Return the verifying key to which this set of shares corresponds.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.shares[0].verifying_key()
+    }
+
+    /// Returns the number of refreshes applied to this share set since its creation.
+    pub fn epoch(&self) -> u64 {
+        self.shares[0].epoch
+    }
+
+    /// Converts the shares into a key share that can be used in the presigning/signing protocols
+    /// by the parties of a quorum.
+    ///
+    /// `parties` lists the share indices (as returned by [`share_idxs`](Self::share_idxs))
+    /// of each party participating in the protocol
+    /// (including this one), in the order of their verifiers (as given by `Ord`).
+    /// Their total weight must be at least the threshold; if it is greater,
+    /// every party keeps one share, and then the remaining ones are assigned
+    /// in the order of the parties, so that exactly `threshold` shares are used.
+    pub fn to_key_share(
+        &self,
+        parties: &[Vec<ShareIdx>],
+    ) -> Result<KeyShare<P>, WeightedKeyShareError> {
+        let threshold = self.threshold();
+        let num_parties = parties.len();
+        let weight = parties.iter().map(|idxs| idxs.len()).sum();
+        if weight < threshold {
+            return Err(WeightedKeyShareError::InsufficientWeight { weight, threshold });
+        }
+        if num_parties > threshold {
+            return Err(WeightedKeyShareError::TooManyParties {
+                num_parties,
+                threshold,
+            });
         }
+
+        let some_share = &self.shares[0];
+        let unique_idxs = parties.iter().flatten().collect::<BTreeSet<_>>();
+        if unique_idxs.len() != weight
+            || !unique_idxs
+                .iter()
+                .all(|idx| some_share.public_shares.contains_key(idx))
+            || parties.iter().any(|idxs| idxs.is_empty())
+        {
+            return Err(WeightedKeyShareError::InvalidShareIdxs);
+        }
+
+        let my_idxs = self.share_idxs();
+        let my_position = parties
+            .iter()
+            .position(|idxs| idxs == &my_idxs)
+            .ok_or(WeightedKeyShareError::NotAParticipant)?;
+
+        let mut extra_shares = threshold - num_parties;
+        let quorum = parties
+            .iter()
+            .map(|idxs| {
+                let num_shares = 1 + core::cmp::min(extra_shares, idxs.len() - 1);
+                extra_shares -= num_shares - 1;
+                idxs[..num_shares].to_vec()
+            })
+            .collect::<Vec<_>>();
+
+        let my_shares = &self.shares[..quorum[my_position].len()];
+        ThresholdKeyShare::combine_into_key_share(my_shares, &quorum)
+            .map_err(WeightedKeyShareError::Combine)
     }
 }
 
@@ -226,6 +907,7 @@ impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for ThresholdKeyShare<
             f,
             concat![
                 "KeyShare {{",
+                "fingerprint: {}, ",
                 "index: {:?}, ",
                 "threshold: {:?} ",
                 "secret_share: <...>, ",
@@ -234,19 +916,55 @@ impl<P: SchemeParams + core::fmt::Debug> core::fmt::Debug for ThresholdKeyShare<
                 "public_aux: {:?} ",
                 "}}"
             ],
-            self.index, self.threshold, self.public_shares, self.secret_aux, self.public_aux
+            self.fingerprint(),
+            self.index,
+            self.threshold,
+            self.public_shares,
+            self.secret_aux,
+            self.public_aux
         )
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for ThresholdKeyShareSeed<P> {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for ThresholdKeyShare<P> {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: SchemeParams> Drop for ThresholdKeyShareChange<P> {
+    fn drop(&mut self) {
+        self.secret_share_change.zeroize();
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use k256::ecdsa::SigningKey;
+    use alloc::collections::{BTreeMap, BTreeSet};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
     use rand_core::OsRng;
 
-    use super::ThresholdKeyShare;
-    use crate::cggmp21::TestParams;
-    use crate::curve::Scalar;
+    use super::{
+        CombineKeySharesError, RetryQuorumError, ThresholdKeyShare, WeightedKeyShare,
+        WeightedKeyShareError,
+    };
+    use crate::bip32::{derive_tweak, ChainCode};
+    use crate::cggmp21::{ProductionParams, TestParams};
+    use crate::common::{KeyShare, KeyShareDecodingError};
+    use crate::curve::{Scalar, SigningKey};
+    use crate::rounds::test_utils::quorum_key_shares;
+    use crate::tools::sss::ShareIdx;
 
     #[test]
     fn threshold_key_share_centralized() {
@@ -259,9 +977,8 @@ mod tests {
 
         assert_eq!(&shares[0].verifying_key(), sk.verifying_key());
 
-        let share_idxs = [shares[2].index(), shares[0].index()];
-        let nt_share0 = shares[0].to_key_share(&share_idxs);
-        let nt_share1 = shares[2].to_key_share(&share_idxs);
+        let nt_shares = quorum_key_shares(&shares, &[2, 0]);
+        let (nt_share0, nt_share1) = (&nt_shares[1], &nt_shares[0]);
 
         assert_eq!(&nt_share0.verifying_key(), sk.verifying_key());
         assert_eq!(&nt_share1.verifying_key(), sk.verifying_key());
@@ -270,4 +987,313 @@ mod tests {
             Scalar::from(sk.as_nonzero_scalar())
         );
     }
+
+    #[test]
+    fn threshold_key_share_bytes_roundtrip() {
+        let shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 2, 3, None);
+        let share = &shares[0];
+        let bytes = share.to_bytes();
+
+        // The same header as for `KeyShare`, with the actual threshold.
+        assert_eq!(bytes[0], 2);
+        assert_eq!(&bytes[9..23], b"synedrion-test");
+        assert_eq!(bytes[23..27], 3u32.to_le_bytes());
+        assert_eq!(bytes[27..31], 2u32.to_le_bytes());
+        assert_eq!(
+            bytes[39..72],
+            share.verifying_key_as_point().to_compressed_array()[..]
+        );
+
+        let decoded = ThresholdKeyShare::<TestParams>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.verifying_key(), share.verifying_key());
+        assert_eq!(decoded.index(), share.index());
+
+        let decoded: ThresholdKeyShare<TestParams> =
+            bincode::deserialize(&bincode::serialize(share).unwrap()).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        assert_eq!(
+            ThresholdKeyShare::<ProductionParams>::from_bytes(&bytes).unwrap_err(),
+            KeyShareDecodingError::SchemeParamsMismatch {
+                expected: "synedrion-production",
+                found: "synedrion-test".into(),
+            }
+        );
+
+        // The threshold must be between 1 and the number of shares.
+        for threshold in [0, 4] {
+            let mut wrong_threshold = bytes.to_vec();
+            wrong_threshold[27] = threshold;
+            assert_eq!(
+                ThresholdKeyShare::<TestParams>::from_bytes(&wrong_threshold).unwrap_err(),
+                KeyShareDecodingError::Inconsistent
+            );
+        }
+
+        // Claim the share has another index (the last byte of the big-endian scalar).
+        let mut wrong_index = bytes.to_vec();
+        wrong_index[111] = 2;
+        assert_eq!(
+            ThresholdKeyShare::<TestParams>::from_bytes(&wrong_index).unwrap_err(),
+            KeyShareDecodingError::Inconsistent
+        );
+    }
+
+    #[test]
+    fn retry_quorum() {
+        let sk = SigningKey::random(&mut OsRng);
+        let shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 3, 5, Some(&sk));
+
+        // The verifiers are listed in the reverse order of the share indices
+        // to check that the quorum follows the order of the verifiers.
+        let holders = shares
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| (10 - idx as u8, share.index()))
+            .collect::<BTreeMap<_, _>>();
+
+        let blamed = BTreeSet::from([8u8]);
+        let retries = shares
+            .iter()
+            .map(|share| share.retry_quorum(&holders, &blamed).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(retries[0].verifiers(), &BTreeSet::from([6, 7, 9]));
+
+        // The parties outside of the quorum do not take part,
+        // and the key shares of the others add up to the secret key.
+        assert!(retries[0].key_share().is_none());
+        assert!(retries[2].key_share().is_none());
+        let key_shares =
+            [&retries[4], &retries[3], &retries[1]].map(|retry| retry.key_share().unwrap().clone());
+        for (position, key_share) in key_shares.iter().enumerate() {
+            assert_eq!(key_share.party_index(), position);
+            assert_eq!(&key_share.verifying_key(), sk.verifying_key());
+        }
+        assert_eq!(
+            key_shares
+                .iter()
+                .map(|key_share| key_share.secret_share)
+                .sum::<Scalar>(),
+            Scalar::from(sk.as_nonzero_scalar())
+        );
+
+        let blamed = BTreeSet::from([6, 8, 10]);
+        assert_eq!(
+            shares[0].retry_quorum(&holders, &blamed).unwrap_err(),
+            RetryQuorumError::NotEnoughParties {
+                available: 2,
+                threshold: 3
+            }
+        );
+
+        let mut partial_holders = holders.clone();
+        partial_holders.remove(&10);
+        assert_eq!(
+            shares[0]
+                .retry_quorum(&partial_holders, &BTreeSet::new())
+                .unwrap_err(),
+            RetryQuorumError::NotAHolder
+        );
+
+        let mut duplicate_holders = holders.clone();
+        duplicate_holders.insert(10, shares[1].index());
+        assert_eq!(
+            shares[1]
+                .retry_quorum(&duplicate_holders, &BTreeSet::new())
+                .unwrap_err(),
+            RetryQuorumError::InvalidShareIdxs
+        );
+    }
+
+    #[test]
+    fn from_key_share() {
+        let sk = SigningKey::random(&mut OsRng);
+        let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, Some(&sk));
+
+        let threshold_shares = key_shares
+            .iter()
+            .map(ThresholdKeyShare::from_key_share)
+            .collect::<Vec<_>>();
+        assert_eq!(&threshold_shares[0].verifying_key(), sk.verifying_key());
+
+        let share_idxs = threshold_shares
+            .iter()
+            .map(|share| share.index())
+            .collect::<Vec<_>>();
+        for (key_share, threshold_share) in key_shares.iter().zip(threshold_shares.iter()) {
+            let key_share_back = threshold_share.to_key_share(&share_idxs);
+            assert_eq!(key_share_back.party_index(), key_share.party_index());
+            assert_eq!(key_share_back.secret_share, key_share.secret_share);
+            assert_eq!(key_share_back.public_shares, key_share.public_shares);
+        }
+    }
+
+    #[test]
+    fn multiple_shares_per_party() {
+        let sk = SigningKey::random(&mut OsRng);
+        let shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 3, 4, Some(&sk));
+
+        // The first party holds two shares, the second one holds a single share.
+        let parties = [
+            vec![shares[3].index(), shares[1].index()],
+            vec![shares[2].index()],
+        ];
+        let nt_share0 = ThresholdKeyShare::combine_into_key_share(
+            &[shares[3].clone(), shares[1].clone()],
+            &parties,
+        )
+        .unwrap();
+        let nt_share1 =
+            ThresholdKeyShare::combine_into_key_share(&[shares[2].clone()], &parties).unwrap();
+
+        assert_eq!(nt_share0.num_parties(), 2);
+        assert_eq!(nt_share0.party_index(), 0);
+        assert_eq!(nt_share1.party_index(), 1);
+        assert_eq!(&nt_share0.verifying_key(), sk.verifying_key());
+        assert_eq!(&nt_share1.verifying_key(), sk.verifying_key());
+        assert_eq!(
+            nt_share0.secret_share + nt_share1.secret_share,
+            Scalar::from(sk.as_nonzero_scalar())
+        );
+
+        let combine = |my_shares: &[ThresholdKeyShare<TestParams>], parties: &[Vec<ShareIdx>]| {
+            ThresholdKeyShare::combine_into_key_share(my_shares, parties).unwrap_err()
+        };
+        assert_eq!(combine(&[], &parties), CombineKeySharesError::NoShares);
+        let other_shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 3, 4, None);
+        assert_eq!(
+            combine(&[shares[3].clone(), other_shares[1].clone()], &parties),
+            CombineKeySharesError::SharesMismatch
+        );
+        // Not enough shares
+        assert_eq!(
+            combine(&[shares[2].clone()], &parties[1..]),
+            CombineKeySharesError::InvalidShareIdxs
+        );
+        // A share listed twice
+        assert_eq!(
+            combine(
+                &[shares[2].clone()],
+                &[
+                    vec![shares[3].index(), shares[3].index()],
+                    vec![shares[2].index()]
+                ]
+            ),
+            CombineKeySharesError::InvalidShareIdxs
+        );
+        // A party without shares
+        assert_eq!(
+            combine(
+                &[shares[2].clone()],
+                &[
+                    vec![shares[3].index(), shares[1].index(), shares[0].index()],
+                    vec![],
+                    vec![shares[2].index()]
+                ]
+            ),
+            CombineKeySharesError::InvalidShareIdxs
+        );
+        // This party's shares are not listed as they are given
+        assert_eq!(
+            combine(&[shares[1].clone(), shares[3].clone()], &parties),
+            CombineKeySharesError::NotAParticipant
+        );
+        assert_eq!(
+            combine(&[shares[0].clone()], &parties),
+            CombineKeySharesError::NotAParticipant
+        );
+    }
+
+    #[test]
+    fn weighted_key_shares() {
+        // Two custodians with two votes each, and three officers with one vote each.
+        let sk = SigningKey::random(&mut OsRng);
+        let shares = WeightedKeyShare::<TestParams>::new_centralized(
+            &mut OsRng,
+            4,
+            &[2, 2, 1, 1, 1],
+            Some(&sk),
+        );
+        assert_eq!(shares[0].weight(), 2);
+        assert_eq!(shares[4].weight(), 1);
+        assert_eq!(&shares[3].verifying_key(), sk.verifying_key());
+
+        let check_quorum = |members: &[usize]| {
+            let parties = members
+                .iter()
+                .map(|idx| shares[*idx].share_idxs())
+                .collect::<Vec<_>>();
+            let key_shares = members
+                .iter()
+                .map(|idx| shares[*idx].to_key_share(&parties).unwrap())
+                .collect::<Vec<_>>();
+            for (position, key_share) in key_shares.iter().enumerate() {
+                assert_eq!(key_share.party_index(), position);
+                assert_eq!(key_share.num_parties(), members.len());
+                assert_eq!(&key_share.verifying_key(), sk.verifying_key());
+            }
+            assert_eq!(
+                key_shares
+                    .iter()
+                    .map(|key_share| key_share.secret_share)
+                    .sum::<Scalar>(),
+                Scalar::from(sk.as_nonzero_scalar())
+            );
+        };
+
+        check_quorum(&[0, 1]);
+        check_quorum(&[1, 2, 4]);
+        // The total weight is 5, so one of the shares of the custodian is left out.
+        check_quorum(&[2, 0, 3, 4]);
+
+        let parties = [shares[0].share_idxs(), shares[2].share_idxs()];
+        assert_eq!(
+            shares[0].to_key_share(&parties).unwrap_err(),
+            WeightedKeyShareError::InsufficientWeight {
+                weight: 3,
+                threshold: 4
+            }
+        );
+        let parties = [shares[0].share_idxs(), shares[0].share_idxs()];
+        assert_eq!(
+            shares[0].to_key_share(&parties).unwrap_err(),
+            WeightedKeyShareError::InvalidShareIdxs
+        );
+        let parties = [shares[0].share_idxs(), shares[1].share_idxs()];
+        assert_eq!(
+            shares[2].to_key_share(&parties).unwrap_err(),
+            WeightedKeyShareError::NotAParticipant
+        );
+    }
+
+    #[test]
+    fn derive_child() {
+        let sk = SigningKey::random(&mut OsRng);
+        let chain_code = ChainCode([1; 32]);
+        let shares = ThresholdKeyShare::<TestParams>::new_centralized(&mut OsRng, 2, 3, Some(&sk));
+
+        let (tweak, _) = derive_tweak(
+            &Scalar::from(sk.as_nonzero_scalar()).mul_by_generator(),
+            &chain_code,
+            5,
+        )
+        .unwrap();
+        let child_sk = Scalar::from(sk.as_nonzero_scalar()) + tweak;
+
+        let children = shares
+            .iter()
+            .map(|share| share.derive_child(&chain_code, 5).unwrap().0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            children[1].verifying_key_as_point(),
+            child_sk.mul_by_generator()
+        );
+
+        let share_idxs = [children[1].index(), children[2].index()];
+        let nt_share0 = children[1].to_key_share(&share_idxs);
+        let nt_share1 = children[2].to_key_share(&share_idxs);
+        assert_eq!(nt_share0.secret_share + nt_share1.secret_share, child_sk);
+    }
 }
@@ -1,5 +1,10 @@
 pub(crate) mod bitvec;
 pub(crate) mod collections;
+#[cfg(all(test, feature = "dudect"))]
+mod dudect;
+pub(crate) mod ecies;
 pub(crate) mod hashing;
+pub(crate) mod parallel;
 pub(crate) mod serde_bytes;
+pub(crate) mod serialized_size;
 pub(crate) mod sss;
@@ -7,17 +7,31 @@ use serde::{Deserialize, Serialize};
 
 use crate::tools::hashing::{Chain, Hashable};
 use crate::tools::serde_bytes;
+use crate::tools::serialized_size::bytes_size;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) struct BitVec(#[serde(with = "serde_bytes::as_base64")] Box<[u8]>);
 
 impl BitVec {
+    // The minimum number of bytes containing `min_bits` bits.
+    fn num_bytes(min_bits: usize) -> usize {
+        (min_bits - 1) / 8 + 1
+    }
+
     pub fn random(rng: &mut impl CryptoRngCore, min_bits: usize) -> Self {
-        let len = (min_bits - 1) / 8 + 1; // minimum number of bytes containing `min_bits` bits.
-        let mut bytes = vec![0; len];
+        let mut bytes = vec![0; Self::num_bytes(min_bits)];
         rng.fill_bytes(&mut bytes);
         Self(bytes.into())
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Returns the size of a serialized random vector created with the given `min_bits`.
+    pub fn serialized_size(min_bits: usize) -> usize {
+        bytes_size(Self::num_bytes(min_bits))
+    }
 }
 
 impl Hashable for BitVec {
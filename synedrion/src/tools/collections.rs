@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
 
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 #[derive(Clone, Debug)]
 pub(crate) struct HoleVecAccum<T> {
@@ -134,6 +135,12 @@ pub(crate) struct HoleVec<T> {
     hole_at: u16,
 }
 
+impl<T: Zeroize> Zeroize for HoleVec<T> {
+    fn zeroize(&mut self) {
+        self.elems.zeroize();
+    }
+}
+
 impl<T> HoleVec<T> {
     pub fn hole_at(&self) -> usize {
         self.hole_at.into()
@@ -0,0 +1,182 @@
+//! A minimal timing leakage test harness, following the approach of
+//! Reparaz, Balasch and Verbauwhede, "Dude, is my code constant time?" (2017).
+//!
+//! An operation is executed on inputs randomly drawn from two classes
+//! (a fixed secret and random secrets), and Welch's t-test is applied
+//! to the distributions of the measured execution times.
+//! If the operation is constant-time with respect to the secret, the distributions coincide.
+//!
+//! The measurements are noisy, so these tests are only compiled with the `dudect` feature,
+//! and are meant to be run in release mode on an otherwise idle machine:
+//! `cargo test --release --features dudect dudect`.
+
+use alloc::vec::Vec;
+use core::hint::black_box;
+use std::time::Instant;
+
+use rand_core::{OsRng, RngCore};
+
+use crate::cggmp21::{SchemeParams, TestParams};
+use crate::curve::{Point, Scalar};
+use crate::paillier::{CiphertextMod, PaillierParams, SecretKeyPaillier};
+use crate::tools::sss::{shamir_join_scalars, ShareIdx};
+use crate::uint::{NonZero, RandomMod, Signed, UintModLike};
+
+type Paillier = <TestParams as SchemeParams>::Paillier;
+
+const NUM_SAMPLES: usize = 20000;
+
+/// Measurements above this percentile are discarded,
+/// since they are most likely caused by interrupts or context switches.
+const CROP_PERCENTILE: f64 = 0.9;
+
+/// The value of the t-statistic above which the timing is considered to depend on the secret.
+/// The original paper uses 4.5 as a sign of a possible leak and 10 as a definite one;
+/// we use the latter to avoid spurious failures on noisy machines.
+const T_THRESHOLD: f64 = 10.0;
+
+/// Running mean and variance (Welford's algorithm).
+#[derive(Default)]
+struct Stats {
+    count: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Stats {
+    fn push(&mut self, value: f64) {
+        self.count += 1.;
+        let delta = value - self.mean;
+        self.mean += delta / self.count;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.count - 1.)
+    }
+}
+
+/// Welch's t-statistic for the two samples.
+fn t_statistic(fixed: &Stats, random: &Stats) -> f64 {
+    let denominator = (fixed.variance() / fixed.count + random.variance() / random.count).sqrt();
+    (fixed.mean - random.mean) / denominator
+}
+
+/// Measures `op` on a fixed input and on inputs created by `make_random`,
+/// and returns the absolute value of the t-statistic of the execution times.
+fn measure_leakage<I, R>(
+    fixed: I,
+    make_random: impl Fn(&mut OsRng) -> I,
+    op: impl Fn(&I) -> R,
+) -> f64
+where
+    I: Clone,
+{
+    // Create all the inputs beforehand so that the generation is not measured.
+    let inputs = (0..NUM_SAMPLES)
+        .map(|_| {
+            let is_fixed = OsRng.next_u32() & 1 == 1;
+            let input = if is_fixed {
+                fixed.clone()
+            } else {
+                make_random(&mut OsRng)
+            };
+            (is_fixed, input)
+        })
+        .collect::<Vec<_>>();
+
+    let timings = inputs
+        .iter()
+        .map(|(is_fixed, input)| {
+            let start = Instant::now();
+            black_box(op(black_box(input)));
+            (*is_fixed, start.elapsed().as_nanos() as f64)
+        })
+        .collect::<Vec<_>>();
+
+    let mut sorted = timings.iter().map(|(_, time)| *time).collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let cutoff = sorted[(sorted.len() as f64 * CROP_PERCENTILE) as usize];
+
+    let mut fixed_stats = Stats::default();
+    let mut random_stats = Stats::default();
+    for (is_fixed, time) in timings.into_iter().filter(|(_, time)| *time <= cutoff) {
+        if is_fixed {
+            fixed_stats.push(time);
+        } else {
+            random_stats.push(time);
+        }
+    }
+
+    t_statistic(&fixed_stats, &random_stats).abs()
+}
+
+fn assert_constant_time(t: f64) {
+    assert!(
+        t < T_THRESHOLD,
+        "The execution time depends on the secret (|t| = {t})"
+    );
+}
+
+#[test]
+fn dudect_scalar_mul() {
+    let point = Point::GENERATOR * Scalar::random(&mut OsRng);
+    let t = measure_leakage(Scalar::ONE, Scalar::random, |scalar| point * scalar);
+    assert_constant_time(t);
+}
+
+#[test]
+fn dudect_scalar_split() {
+    let t = measure_leakage(Scalar::ONE, Scalar::random, |scalar| {
+        scalar.split(&mut OsRng, 3)
+    });
+    assert_constant_time(t);
+}
+
+#[test]
+fn dudect_interpolation() {
+    let share_idxs = [ShareIdx::new(1), ShareIdx::new(3), ShareIdx::new(4)];
+    let t = measure_leakage(
+        [Scalar::ONE; 3],
+        |rng| {
+            [
+                Scalar::random(rng),
+                Scalar::random(rng),
+                Scalar::random(rng),
+            ]
+        },
+        |shares| shamir_join_scalars(share_idxs.iter().zip(shares.iter())),
+    );
+    assert_constant_time(t);
+}
+
+#[test]
+fn dudect_pow_signed() {
+    let sk = SecretKeyPaillier::<Paillier>::random(&mut OsRng).to_precomputed();
+    let pk = sk.public_key();
+    let base = pk.random_invertible_group_elem(&mut OsRng);
+    let bound = Paillier::MODULUS_BITS - 2;
+    let t = measure_leakage(
+        Signed::new_positive(<Paillier as PaillierParams>::Uint::ONE, bound as u32).unwrap(),
+        |rng| Signed::random_bounded_bits(rng, bound),
+        |exponent| base.pow_signed(exponent),
+    );
+    assert_constant_time(t);
+}
+
+#[test]
+fn dudect_paillier_decryption() {
+    let sk = SecretKeyPaillier::<Paillier>::random(&mut OsRng).to_precomputed();
+    let pk = sk.public_key();
+    let modulus = NonZero::new(*pk.modulus()).unwrap();
+    let encrypt = |plaintext| CiphertextMod::<Paillier>::new(&mut OsRng, pk, &plaintext);
+    let t = measure_leakage(
+        encrypt(<Paillier as PaillierParams>::Uint::ONE),
+        |rng| {
+            let plaintext = <Paillier as PaillierParams>::Uint::random_mod(rng, &modulus);
+            encrypt(plaintext)
+        },
+        |ciphertext| ciphertext.decrypt_signed(&sk),
+    );
+    assert_constant_time(t);
+}
@@ -0,0 +1,121 @@
+//! ECIES over secp256k1: an ephemeral ECDH key agreement, HKDF-SHA256 and ChaCha20-Poly1305.
+//!
+//! The sender creates an ephemeral key $e$ and derives a ChaCha20-Poly1305 key with HKDF-SHA256
+//! from the shared point $e Q$ (where $Q$ is the recipient's key),
+//! with the domain, $e G$ and $Q$ as the info.
+//! Each derived key encrypts a single message, so the nonce is fixed to zero.
+//! The hash of the associated data is authenticated along with the ciphertext,
+//! and $e G$ is sent followed by the ciphertext and the tag.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305, KeyInit, Nonce, Tag};
+use hkdf::Hkdf;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::curve::{Point, Scalar};
+use crate::tools::hashing::{Chain, Hash, HashOutput, Hashable};
+
+/// The size of the compressed ephemeral point.
+const EPHEMERAL_KEY_SIZE: usize = 33;
+
+/// The size of the ChaCha20-Poly1305 authentication tag.
+const AEAD_TAG_SIZE: usize = 16;
+
+/// The number of bytes the encryption adds to the plaintext.
+pub(crate) const ECIES_OVERHEAD: usize = EPHEMERAL_KEY_SIZE + AEAD_TAG_SIZE;
+
+/// Encrypts `plaintext` to `key`, binding the ciphertext to the domain `dst`
+/// and the associated data.
+pub(crate) fn encrypt(
+    rng: &mut impl CryptoRngCore,
+    dst: &[u8],
+    key: &Point,
+    associated_data: &impl Hashable,
+    plaintext: &[u8],
+) -> Box<[u8]> {
+    let ephemeral_key = Scalar::random_nonzero(rng);
+    let ephemeral_point = ephemeral_key.mul_by_generator();
+    let shared_point = key * &ephemeral_key;
+    let cipher = derive_cipher(dst, &ephemeral_point, key, &shared_point);
+
+    let mut ciphertext = Vec::with_capacity(plaintext.len() + ECIES_OVERHEAD);
+    ciphertext.extend(ephemeral_point.to_compressed_array());
+    ciphertext.extend(plaintext);
+    let tag = cipher
+        .encrypt_in_place_detached(
+            &Nonce::default(),
+            associated_data_hash(dst, associated_data).as_ref(),
+            &mut ciphertext[EPHEMERAL_KEY_SIZE..],
+        )
+        .expect("the plaintext is shorter than the ChaCha20-Poly1305 limit");
+    ciphertext.extend(tag);
+    ciphertext.into()
+}
+
+/// Decrypts a ciphertext created by [`encrypt`] for the same domain and associated data
+/// with the secret key `key`.
+pub(crate) fn decrypt(
+    dst: &[u8],
+    key: &Scalar,
+    associated_data: &impl Hashable,
+    ciphertext: &[u8],
+) -> Result<Box<[u8]>, String> {
+    if ciphertext.len() < ECIES_OVERHEAD {
+        return Err("The ciphertext is too short".into());
+    }
+    let (authenticated, tag) = ciphertext.split_at(ciphertext.len() - AEAD_TAG_SIZE);
+    let (ephemeral_point, encrypted) = authenticated.split_at(EPHEMERAL_KEY_SIZE);
+
+    let ephemeral_point = Point::try_from_compressed_bytes(ephemeral_point)?;
+    let shared_point = ephemeral_point * key;
+    let cipher = derive_cipher(
+        dst,
+        &ephemeral_point,
+        &key.mul_by_generator(),
+        &shared_point,
+    );
+
+    let tag = <[u8; AEAD_TAG_SIZE]>::try_from(tag).expect("the tag has been split off by its size");
+    let mut plaintext = Box::<[u8]>::from(encrypted);
+    cipher
+        .decrypt_in_place_detached(
+            &Nonce::default(),
+            associated_data_hash(dst, associated_data).as_ref(),
+            &mut plaintext,
+            &Tag::from(tag),
+        )
+        .map_err(|_| String::from("Invalid authentication tag"))?;
+    Ok(plaintext)
+}
+
+/// Derives the single-use AEAD key from the shared point.
+fn derive_cipher(
+    dst: &[u8],
+    ephemeral_point: &Point,
+    recipient_key: &Point,
+    shared_point: &Point,
+) -> ChaCha20Poly1305 {
+    let shared_secret = Zeroizing::new(shared_point.to_compressed_array());
+    let hkdf = Hkdf::<Sha256>::new(None, &shared_secret);
+    let mut key = Zeroizing::new([0u8; 32]);
+    hkdf.expand_multi_info(
+        &[
+            dst,
+            &ephemeral_point.to_compressed_array(),
+            &recipient_key.to_compressed_array(),
+        ],
+        key.as_mut(),
+    )
+    .expect("32 bytes is a valid HKDF-SHA256 output length");
+    ChaCha20Poly1305::new(key.as_ref().into())
+}
+
+/// Hashes the associated data into the domain `dst`.
+fn associated_data_hash(dst: &[u8], associated_data: &impl Hashable) -> HashOutput {
+    Hash::new_with_dst(dst).chain(associated_data).finalize()
+}
@@ -2,20 +2,20 @@ use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 
-use digest::{Digest, ExtendableOutput, Update, XofReader};
+use digest::{Digest, ExtendableOutput, Output, Update, XofReader};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use sha3::{Shake256, Shake256Reader};
+use sha3::{Keccak256, Shake256, Shake256Reader};
 
-use crate::curve::Scalar;
 use crate::tools::serde_bytes;
+use crate::tools::serialized_size::{bytes_size, MaxSerializedSize};
 
 /// Encodes the object into bytes for hashing purposes.
 pub(crate) trait HashInto {
     fn from_reader(reader: &mut impl XofReader) -> Self;
 }
 
-/// A digest object that takes byte slices or decomposable ([`Hashable`]) objects.
+/// A digest object that takes byte slices or decomposable objects.
 pub trait Chain: Sized {
     /// Hash raw bytes.
     ///
@@ -36,14 +36,17 @@ pub trait Chain: Sized {
         self.chain_raw_bytes(&len).chain_raw_bytes(bytes.as_ref())
     }
 
+    /// Hash a decomposable object.
     fn chain<T: Hashable>(self, hashable: &T) -> Self {
         hashable.chain(self)
     }
 
+    /// Hash the identifier of a type.
     fn chain_type<T: HashableType>(self) -> Self {
         T::chain_type(self)
     }
 
+    /// Hash a slice of decomposable objects.
     fn chain_slice<T: Hashable>(self, hashable: &[T]) -> Self {
         // Hashing the length too to prevent collisions.
         let len = hashable.len() as u64;
@@ -66,7 +69,7 @@ impl Chain for Hash {
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct HashOutput(
     #[serde(with = "serde_bytes::as_hex")] [u8; 32], // Length of the BackendDigest output. Unfortunately we can't get it in compile-time.
 );
@@ -89,13 +92,12 @@ impl Hash {
     pub(crate) fn finalize(self) -> HashOutput {
         HashOutput(self.0.finalize().into())
     }
-
-    pub fn finalize_to_scalar(self) -> Scalar {
-        Scalar::from_digest(self.0)
-    }
 }
 
 /// Wraps an extendable output hash for easier replacement, and standardizes the use of DST.
+///
+/// This is the default [`FiatShamirTranscript`], based on SHAKE256.
+#[derive(Debug, Clone)]
 pub struct XofHash(Shake256);
 
 impl Chain for XofHash {
@@ -106,20 +108,139 @@ impl Chain for XofHash {
     }
 }
 
-impl XofHash {
-    fn new() -> Self {
-        Self(Shake256::default())
-    }
+/// A Fiat-Shamir transcript used to derive the challenges of the zero-knowledge proofs.
+///
+/// The default one is [`XofHash`], but integrators can supply their own
+/// (e.g. a wrapper around a Merlin transcript, or a hash with a custom domain separation scheme)
+/// via [`SchemeParams::Transcript`](crate::SchemeParams::Transcript).
+/// All the parties of a session must use the same transcript.
+///
+/// Note that [`Chain::chain_raw_bytes`] receives the values already encoded unambiguously
+/// (variable-sized values are prefixed with their length),
+/// so the implementation does not need to add its own framing.
+///
+/// For example, a Merlin-based transcript can be implemented as
+/// ```ignore
+/// struct MerlinTranscript(merlin::Transcript);
+///
+/// impl Chain for MerlinTranscript {
+///     fn chain_raw_bytes(mut self, bytes: &[u8]) -> Self {
+///         self.0.append_message(b"data", bytes);
+///         self
+///     }
+/// }
+///
+/// impl FiatShamirTranscript for MerlinTranscript {
+///     type Reader = MerlinReader;
+///
+///     fn new_with_dst(dst: &[u8]) -> Self {
+///         let mut transcript = merlin::Transcript::new(b"my-application");
+///         transcript.append_message(b"dst", dst);
+///         Self(transcript)
+///     }
+///
+///     fn finalize_to_reader(self) -> Self::Reader {
+///         MerlinReader(self.0)
+///     }
+/// }
+///
+/// struct MerlinReader(merlin::Transcript);
+///
+/// impl XofReader for MerlinReader {
+///     fn read(&mut self, buffer: &mut [u8]) {
+///         self.0.challenge_bytes(b"challenge", buffer);
+///     }
+/// }
+/// ```
+pub trait FiatShamirTranscript: Chain {
+    /// The source of the challenge bytes.
+    type Reader: XofReader;
+
+    /// Creates a new transcript with the given domain separation tag.
+    fn new_with_dst(dst: &[u8]) -> Self;
+
+    /// Finishes absorbing the values and returns the source of the challenge bytes.
+    fn finalize_to_reader(self) -> Self::Reader;
+}
 
-    pub fn new_with_dst(dst: &[u8]) -> Self {
-        Self::new().chain_bytes(dst)
+impl FiatShamirTranscript for XofHash {
+    type Reader = Shake256Reader;
+
+    fn new_with_dst(dst: &[u8]) -> Self {
+        Self(Shake256::default()).chain_bytes(dst)
     }
 
-    pub fn finalize_to_reader(self) -> Shake256Reader {
+    fn finalize_to_reader(self) -> Self::Reader {
         self.0.finalize_xof()
     }
 }
 
+/// A [`FiatShamirTranscript`] based on the fixed output hash `D`,
+/// for the applications whose verifiers expect the proofs to use a specific hash
+/// (see [`Sha256Transcript`] and [`Keccak256Transcript`]).
+///
+/// The challenge bytes are produced in the counter mode:
+/// the `i`-th block of the output is `D(h || i)`, where `h` is the hash of the transcript
+/// and `i` is encoded as a big-endian `u64`.
+#[derive(Debug, Clone)]
+pub struct DigestTranscript<D>(D);
+
+/// A [`DigestTranscript`] based on SHA-256.
+pub type Sha256Transcript = DigestTranscript<Sha256>;
+
+/// A [`DigestTranscript`] based on Keccak-256 (as used in Ethereum, not the standardized SHA3-256).
+pub type Keccak256Transcript = DigestTranscript<Keccak256>;
+
+impl<D: Digest> Chain for DigestTranscript<D> {
+    fn chain_raw_bytes(self, bytes: &[u8]) -> Self {
+        Self(self.0.chain_update(bytes))
+    }
+}
+
+impl<D: Digest> FiatShamirTranscript for DigestTranscript<D> {
+    type Reader = DigestReader<D>;
+
+    fn new_with_dst(dst: &[u8]) -> Self {
+        Self(D::new()).chain_bytes(dst)
+    }
+
+    fn finalize_to_reader(self) -> Self::Reader {
+        let block = Output::<D>::default();
+        DigestReader {
+            hash: self.0.finalize(),
+            counter: 0,
+            position: block.len(),
+            block,
+        }
+    }
+}
+
+/// The source of the challenge bytes of a [`DigestTranscript`].
+#[derive(Debug, Clone)]
+pub struct DigestReader<D: Digest> {
+    hash: Output<D>,
+    counter: u64,
+    block: Output<D>,
+    position: usize,
+}
+
+impl<D: Digest> XofReader for DigestReader<D> {
+    fn read(&mut self, buffer: &mut [u8]) {
+        for byte in buffer.iter_mut() {
+            if self.position == self.block.len() {
+                self.block = D::new()
+                    .chain_update(&self.hash)
+                    .chain_update(self.counter.to_be_bytes())
+                    .finalize();
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+}
+
 /// A trait allowing hashing of types without having access to their instances.
 pub trait HashableType {
     fn chain_type<C: Chain>(digest: C) -> C;
@@ -209,8 +330,44 @@ impl<K: Hashable, V: Hashable> Hashable for BTreeMap<K, V> {
     }
 }
 
+impl MaxSerializedSize for HashOutput {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        bytes_size(32)
+    }
+}
+
 impl Hashable for HashOutput {
     fn chain<C: Chain>(&self, digest: C) -> C {
         digest.chain_constant_sized_bytes(&self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use digest::XofReader;
+
+    use super::{FiatShamirTranscript, Keccak256Transcript, Sha256Transcript};
+
+    fn read_challenge<T: FiatShamirTranscript>(chunks: &[usize]) -> Vec<u8> {
+        let mut reader = T::new_with_dst(b"test")
+            .chain_bytes(b"data")
+            .finalize_to_reader();
+        let mut output = Vec::new();
+        for chunk in chunks {
+            let mut buffer = vec![0; *chunk];
+            reader.read(&mut buffer);
+            output.extend(buffer);
+        }
+        output
+    }
+
+    #[test]
+    fn digest_transcript_output() {
+        // The output does not depend on the way it is read, and extends beyond one block.
+        let whole = read_challenge::<Sha256Transcript>(&[100]);
+        assert_eq!(whole, read_challenge::<Sha256Transcript>(&[1, 31, 33, 35]));
+        assert_ne!(whole[..32], whole[32..64]);
+
+        assert_ne!(whole, read_challenge::<Keccak256Transcript>(&[100]));
+    }
+}
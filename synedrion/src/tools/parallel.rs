@@ -0,0 +1,84 @@
+//! Independent computations executed in parallel with the `rayon` feature.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rayon")]
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// Applies `f` to each of `items`, preserving the order.
+///
+/// With the `rayon` feature enabled, the calls are executed in the threads of the `rayon` pool.
+pub(crate) fn map<T, U, F>(items: Vec<T>, f: F) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    {
+        items.into_par_iter().map(f).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
+/// Applies `f` to each of `items`, preserving the order.
+///
+/// With the `rayon` feature enabled, the calls are executed in the threads of the `rayon` pool,
+/// each with its own RNG seeded from `rng`.
+/// Since the seeds are drawn before the calls are scheduled,
+/// the result is still determined by the state of `rng`.
+pub(crate) fn map_with_rng<T, U, F>(rng: &mut impl CryptoRngCore, items: Vec<T>, f: F) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    F: Fn(&mut dyn CryptoRngCore, T) -> U + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    {
+        let seeded_items = items
+            .into_iter()
+            .map(|item| {
+                let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+                rng.fill_bytes(&mut seed);
+                (seed, item)
+            })
+            .collect::<Vec<_>>();
+
+        seeded_items
+            .into_par_iter()
+            .map(|(seed, item)| f(&mut ChaCha20Rng::from_seed(seed), item))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.into_iter().map(|item| f(rng, item)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand_core::OsRng;
+
+    use super::map_with_rng;
+
+    #[test]
+    fn order_preserved() {
+        let items = (0..100u64).collect::<Vec<_>>();
+        let results = map_with_rng(&mut OsRng, items, |rng, item| (item, rng.next_u64()));
+        for (idx, (item, _)) in results.iter().enumerate() {
+            assert_eq!(*item, idx as u64);
+        }
+        // Every call gets fresh randomness.
+        assert!(results[0].1 != results[1].1);
+    }
+}
@@ -0,0 +1,44 @@
+//! Upper bounds on the sizes of values serialized with `bincode`
+//! (the format used for the message payloads).
+
+use crate::uint::UintLike;
+
+/// The size of the length prefix of a sequence or a byte string.
+pub(crate) const LENGTH_PREFIX_SIZE: usize = 8;
+
+/// The size of the variant index of an enum.
+pub(crate) const VARIANT_INDEX_SIZE: usize = 4;
+
+/// A type with a known upper bound on the size of its serialized values.
+pub(crate) trait MaxSerializedSize {
+    /// Returns the upper bound for a value created in a session with `num_parties` parties.
+    fn max_serialized_size(num_parties: usize) -> usize;
+}
+
+impl MaxSerializedSize for () {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        0
+    }
+}
+
+impl<T1: MaxSerializedSize, T2: MaxSerializedSize> MaxSerializedSize for (T1, T2) {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        T1::max_serialized_size(num_parties) + T2::max_serialized_size(num_parties)
+    }
+}
+
+/// Returns the size of a serialized byte string of the given length.
+pub(crate) const fn bytes_size(len: usize) -> usize {
+    LENGTH_PREFIX_SIZE + len
+}
+
+/// Returns the upper bound on the size of a serialized sequence of `len` elements.
+pub(crate) fn seq_size<T: MaxSerializedSize>(len: usize, num_parties: usize) -> usize {
+    LENGTH_PREFIX_SIZE + len * T::max_serialized_size(num_parties)
+}
+
+/// Returns the size of a serialized integer
+/// (they are serialized as fixed-size arrays, without a length prefix).
+pub(crate) const fn uint_size<T: UintLike>() -> usize {
+    T::BYTES
+}
@@ -4,16 +4,41 @@ use core::ops::{Add, Mul};
 
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use crate::curve::{Point, Scalar};
+use crate::tools::hashing::{Chain, Hashable};
 
+/// The index of a share in a threshold share set (the point at which the sharing polynomial
+/// is evaluated to get the share).
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ShareIdx(Scalar);
 
 impl ShareIdx {
+    /// Creates a share index from a non-zero integer.
+    ///
+    /// The shares created by [`ThresholdKeyShare::new_centralized`](crate::ThresholdKeyShare::new_centralized)
+    /// and [`ThresholdKeyShare::from_key_share`](crate::ThresholdKeyShare::from_key_share)
+    /// have the indices `1..=n`.
     pub fn new(idx: usize) -> Self {
         Self(Scalar::from(idx))
     }
+
+    #[cfg(feature = "tss-lib")]
+    pub(crate) fn from_scalar(idx: Scalar) -> Self {
+        Self(idx)
+    }
+
+    pub(crate) fn to_scalar(self) -> Scalar {
+        self.0
+    }
+}
+
+impl Hashable for ShareIdx {
+    fn chain<C: Chain>(&self, digest: C) -> C {
+        digest.chain(&self.0)
+    }
 }
 
 pub(crate) fn shamir_evaluation_points(num_shares: usize) -> Vec<ShareIdx> {
@@ -24,7 +49,7 @@ pub(crate) fn shamir_evaluation_points(num_shares: usize) -> Vec<ShareIdx> {
         .collect()
 }
 
-fn evaluate_polynomial<T>(coeffs: &[T], x: &Scalar) -> T
+pub(crate) fn evaluate_polynomial<T>(coeffs: &[T], x: &Scalar) -> T
 where
     T: Copy + Add<T, Output = T> + for<'a> Mul<&'a Scalar, Output = T>,
 {
@@ -63,6 +88,13 @@ impl Polynomial {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for Polynomial {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PublicPolynomial(Vec<Point>);
 
@@ -74,6 +106,19 @@ impl PublicPolynomial {
     pub fn coeff0(&self) -> Point {
         self.0[0]
     }
+
+    /// Creates a polynomial with the zero constant term and the given other coefficients.
+    pub fn with_zero_coeff0(higher_coeffs: Vec<Point>) -> Self {
+        let mut coeffs = Vec::with_capacity(higher_coeffs.len() + 1);
+        coeffs.push(Point::IDENTITY);
+        coeffs.extend(higher_coeffs);
+        Self(coeffs)
+    }
+
+    /// Returns all the coefficients except the constant term.
+    pub fn higher_coeffs(&self) -> &[Point] {
+        &self.0[1..]
+    }
 }
 
 pub(crate) fn shamir_split(
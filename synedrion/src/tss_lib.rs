@@ -0,0 +1,470 @@
+//! Import of ECDSA key shares created by [`tss-lib`](https://github.com/bnb-chain/tss-lib)
+//! (a GG18/GG20 implementation), so that the existing keys can be used with this library
+//! without running a new KeyGen.
+//!
+//! Only the key material is imported: the Paillier keys and the ring-Pedersen parameters
+//! of GG20 do not satisfy the requirements of CGGMP'21, and the El Gamal keys are absent.
+//! The migration therefore consists of the following steps:
+//! 1. Each party parses its saved share with [`TssLibKeyShare::from_json`].
+//! 2. The parties that will hold the new shares (at least `threshold + 1` of them)
+//!    convert their shares with [`TssLibKeyShare::to_key_share_seed`].
+//! 3. The same parties run the KeyRefresh protocol
+//!    (see [`make_key_refresh_session`](crate::make_key_refresh_session)),
+//!    and combine the seed with the result with [`KeyShare::new`](crate::KeyShare::new).
+//!
+//! The resulting key shares are n-of-n; a threshold set of shares can be obtained
+//! with [`ThresholdKeyShare::from_key_share`](crate::ThresholdKeyShare::from_key_share)
+//! followed by the KeyResharing protocol.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use displaydoc::Display;
+use serde::Deserialize;
+use serde_json::Number;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::common::KeyShareSeed;
+use crate::curve::{Point, Scalar, VerifyingKey};
+use crate::tools::{
+    bitvec::BitVec,
+    hashing::{Chain, Hash},
+    sss::{interpolation_coeff, ShareIdx},
+};
+
+/// Possible errors when importing a `tss-lib` key share.
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum TssLibImportError {
+    /// Failed to parse the JSON: {0}.
+    InvalidJson(String),
+    /// Invalid value of `{field}`: {reason}.
+    InvalidValue {
+        /// The name of the field in the JSON.
+        field: &'static str,
+        /// The description of the problem.
+        reason: String,
+    },
+    /// The key share is internally inconsistent: {0}.
+    Inconsistent(&'static str),
+    /// Invalid list of signers: {0}.
+    InvalidSigners(&'static str),
+}
+
+impl core::error::Error for TssLibImportError {}
+
+#[derive(Deserialize)]
+struct JsonPoint {
+    #[serde(rename = "Curve")]
+    curve: String,
+    #[serde(rename = "Coords")]
+    coords: [Number; 2],
+}
+
+// The relevant fields of `LocalPartySaveData` in `tss-lib/ecdsa/keygen`.
+// Big integers are serialized by Go as JSON numbers of arbitrary size.
+#[derive(Deserialize)]
+struct JsonSaveData {
+    #[serde(rename = "Xi")]
+    xi: Number,
+    #[serde(rename = "ShareID")]
+    share_id: Number,
+    #[serde(rename = "Ks")]
+    ks: Vec<Number>,
+    #[serde(rename = "BigXj")]
+    big_xj: Vec<JsonPoint>,
+    #[serde(rename = "ECDSAPub")]
+    ecdsa_pub: JsonPoint,
+}
+
+/// Parses a non-negative decimal integer into 32 big-endian bytes.
+fn decimal_to_be_bytes(decimal: &str) -> Option<[u8; 32]> {
+    if decimal.is_empty() {
+        return None;
+    }
+
+    let mut bytes = [0u8; 32];
+    for c in decimal.chars() {
+        let mut carry = c.to_digit(10)?;
+        for byte in bytes.iter_mut().rev() {
+            let value = u32::from(*byte) * 10 + carry;
+            *byte = (value & 0xff) as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(bytes)
+}
+
+fn parse_bytes(field: &'static str, number: &Number) -> Result<[u8; 32], TssLibImportError> {
+    decimal_to_be_bytes(&number.to_string()).ok_or_else(|| TssLibImportError::InvalidValue {
+        field,
+        reason: "expected a non-negative integer below 2^256".into(),
+    })
+}
+
+fn parse_scalar(field: &'static str, number: &Number) -> Result<Scalar, TssLibImportError> {
+    let bytes = parse_bytes(field, number)?;
+    Scalar::try_from_bytes(&bytes)
+        .map_err(|reason| TssLibImportError::InvalidValue { field, reason })
+}
+
+fn parse_point(field: &'static str, point: &JsonPoint) -> Result<Point, TssLibImportError> {
+    if point.curve != "secp256k1" {
+        return Err(TssLibImportError::InvalidValue {
+            field,
+            reason: format!("unsupported curve {}", point.curve),
+        });
+    }
+    let x = parse_bytes(field, &point.coords[0])?;
+    let y = parse_bytes(field, &point.coords[1])?;
+    Point::try_from_affine_coordinates(&x, &y)
+        .map_err(|reason| TssLibImportError::InvalidValue { field, reason })
+}
+
+/// A key share created by the ECDSA KeyGen protocol of `tss-lib`.
+#[derive(Clone)]
+pub struct TssLibKeyShare {
+    index: usize,
+    secret_share: Scalar,
+    share_ids: Vec<Scalar>,
+    public_shares: Vec<Point>,
+    verifying_key: Point,
+}
+
+impl TssLibKeyShare {
+    /// Parses the JSON serialization of `LocalPartySaveData` produced by `tss-lib`.
+    ///
+    /// Only the fields `Xi`, `ShareID`, `Ks`, `BigXj` and `ECDSAPub` are used;
+    /// the rest are ignored.
+    pub fn from_json(json: &str) -> Result<Self, TssLibImportError> {
+        let data: JsonSaveData = serde_json::from_str(json)
+            .map_err(|err| TssLibImportError::InvalidJson(err.to_string()))?;
+
+        let secret_share = parse_scalar("Xi", &data.xi)?;
+        let share_id = parse_scalar("ShareID", &data.share_id)?;
+        let share_ids = data
+            .ks
+            .iter()
+            .map(|k| parse_scalar("Ks", k))
+            .collect::<Result<Vec<_>, _>>()?;
+        let public_shares = data
+            .big_xj
+            .iter()
+            .map(|point| parse_point("BigXj", point))
+            .collect::<Result<Vec<_>, _>>()?;
+        let verifying_key = parse_point("ECDSAPub", &data.ecdsa_pub)?;
+
+        if share_ids.len() != public_shares.len() {
+            return Err(TssLibImportError::Inconsistent(
+                "the lengths of `Ks` and `BigXj` differ",
+            ));
+        }
+        for (i, id) in share_ids.iter().enumerate() {
+            if id == &Scalar::ZERO || share_ids[..i].contains(id) {
+                return Err(TssLibImportError::Inconsistent(
+                    "the share IDs must be distinct and non-zero",
+                ));
+            }
+        }
+        let index = share_ids.iter().position(|id| id == &share_id).ok_or(
+            TssLibImportError::Inconsistent("`ShareID` is not present in `Ks`"),
+        )?;
+        if secret_share.mul_by_generator() != public_shares[index] {
+            return Err(TssLibImportError::Inconsistent(
+                "`Xi` does not correspond to the public share",
+            ));
+        }
+
+        Ok(Self {
+            index,
+            secret_share,
+            share_ids,
+            public_shares,
+            verifying_key,
+        })
+    }
+
+    /// Returns the verifying key of the share set.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        // The point was parsed from affine coordinates, so it cannot be the identity.
+        self.verifying_key.to_verifying_key().unwrap()
+    }
+
+    /// Returns the total number of shares in the share set.
+    pub fn num_parties(&self) -> usize {
+        self.share_ids.len()
+    }
+
+    /// Returns the position of this share's ID in the list of all share IDs (`Ks`).
+    pub fn party_index(&self) -> usize {
+        self.index
+    }
+
+    /// Converts this share into a seed for the n-of-n key share held by `signers`,
+    /// given as positions in the list of share IDs (see [`party_index`](Self::party_index)).
+    ///
+    /// `signers` must include this share, and must be listed in the order of the verifiers
+    /// of the parties holding them (as given by `Ord`), since this is the order
+    /// in which the KeyRefresh protocol assigns the parties to the seed's public shares.
+    /// The seed must then be passed to [`KeyShare::new`](crate::KeyShare::new) along with the result of KeyRefresh.
+    pub fn to_key_share_seed(&self, signers: &[usize]) -> Result<KeyShareSeed, TssLibImportError> {
+        for (i, signer) in signers.iter().enumerate() {
+            if *signer >= self.num_parties() || signers[..i].contains(signer) {
+                return Err(TssLibImportError::InvalidSigners(
+                    "the positions must be distinct and less than the number of shares",
+                ));
+            }
+        }
+        if !signers.contains(&self.index) {
+            return Err(TssLibImportError::InvalidSigners(
+                "this share must be one of the signers",
+            ));
+        }
+
+        let share_idxs = signers
+            .iter()
+            .map(|signer| ShareIdx::from_scalar(self.share_ids[*signer]))
+            .collect::<Vec<_>>();
+        let public_shares = signers
+            .iter()
+            .zip(share_idxs.iter())
+            .map(|(signer, share_idx)| {
+                self.public_shares[*signer] * interpolation_coeff(&share_idxs, share_idx)
+            })
+            .collect::<Vec<_>>();
+
+        if public_shares.iter().sum::<Point>() != self.verifying_key {
+            return Err(TssLibImportError::InvalidSigners(
+                "the shares of the signers do not add up to the verifying key \\
+                (probably there are fewer signers than the threshold requires)",
+            ));
+        }
+
+        let secret_share = self.secret_share
+            * interpolation_coeff(
+                &share_idxs,
+                &ShareIdx::from_scalar(self.share_ids[self.index]),
+            );
+
+        // All the holders of the shares of this set must have the same identifier,
+        // so we derive it from the public data.
+        let init_id = Hash::new_with_dst(b"tss-lib-import")
+            .chain(&self.verifying_key)
+            .chain_slice(&self.share_ids)
+            .chain_slice(&self.public_shares)
+            .finalize();
+
+        Ok(KeyShareSeed {
+            secret_share,
+            public_shares: public_shares.into(),
+            init_id: BitVec::from_bytes(init_id.as_ref()),
+        })
+    }
+}
+
+// A custom Debug impl that skips the secret value
+impl core::fmt::Debug for TssLibKeyShare {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "TssLibKeyShare {{ index: {}, secret_share: <...>, public_shares: {:?} }}",
+            self.index, self.public_shares,
+        )
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for TssLibKeyShare {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use num_bigint::BigUint;
+    use rand_core::{OsRng, RngCore};
+
+    use super::{decimal_to_be_bytes, TssLibImportError, TssLibKeyShare};
+    use crate::cggmp21::{key_refresh, TestParams};
+    use crate::common::KeyShare;
+    use crate::curve::{Point, Scalar, SigningKey};
+    use crate::rounds::{
+        test_utils::{step_next_round, step_result, step_round},
+        FirstRound, PartyIdx,
+    };
+    use crate::tools::sss::{shamir_split, ShareIdx};
+
+    fn decimal(bytes: &[u8]) -> String {
+        BigUint::from_bytes_be(bytes).to_string()
+    }
+
+    fn json_point(point: &Point) -> String {
+        let encoded = point.to_backend().to_affine().to_encoded_point(false);
+        format!(
+            r#"{{"Curve":"secp256k1","Coords":[{},{}]}}"#,
+            decimal(encoded.x().unwrap()),
+            decimal(encoded.y().unwrap())
+        )
+    }
+
+    /// Creates the JSON save data of `tss-lib` for a 2-of-3 sharing of `sk`.
+    fn make_save_data(sk: &SigningKey) -> Vec<String> {
+        // `tss-lib` uses random share IDs.
+        let share_ids = (0..3)
+            .map(|_| Scalar::random_nonzero(&mut OsRng))
+            .collect::<Vec<_>>();
+        let share_idxs = share_ids
+            .iter()
+            .map(|id| ShareIdx::from_scalar(*id))
+            .collect::<Vec<_>>();
+        let secret = Scalar::from(sk.as_nonzero_scalar());
+        let shares = shamir_split(&mut OsRng, &secret, 2, &share_idxs);
+
+        let ks = share_ids
+            .iter()
+            .map(|id| decimal(&id.to_bytes()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let big_xj = share_idxs
+            .iter()
+            .map(|idx| json_point(&shares[idx].mul_by_generator()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let ecdsa_pub = json_point(&secret.mul_by_generator());
+
+        share_idxs
+            .iter()
+            .zip(share_ids.iter())
+            .map(|(idx, id)| {
+                format!(
+                    concat![
+                        r#"{{"PaillierSK":{{"N":12345,"LambdaN":678,"PhiN":9}},"#,
+                        r#""NTildei":1,"H1i":2,"H2i":3,"#,
+                        r#""Xi":{},"ShareID":{},"Ks":[{}],"BigXj":[{}],"ECDSAPub":{}}}"#
+                    ],
+                    decimal(&shares[idx].to_bytes()),
+                    decimal(&id.to_bytes()),
+                    ks,
+                    big_xj,
+                    ecdsa_pub
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_decimal() {
+        let mut expected = [0u8; 32];
+        expected[30..].copy_from_slice(&[0x30, 0x39]);
+        assert_eq!(decimal_to_be_bytes("12345"), Some(expected));
+        assert_eq!(decimal_to_be_bytes(&decimal(&[0xff; 32])), Some([0xff; 32]));
+        let too_large = BigUint::from_bytes_be(&[0xff; 32]) + 1u32;
+        assert_eq!(decimal_to_be_bytes(&too_large.to_string()), None);
+        assert_eq!(decimal_to_be_bytes("-1"), None);
+        assert_eq!(decimal_to_be_bytes(""), None);
+    }
+
+    #[test]
+    fn import_and_refresh() {
+        let sk = SigningKey::random(&mut OsRng);
+        let save_data = make_save_data(&sk);
+
+        let tss_shares = save_data
+            .iter()
+            .map(|json| TssLibKeyShare::from_json(json).unwrap())
+            .collect::<Vec<_>>();
+        for (idx, share) in tss_shares.iter().enumerate() {
+            assert_eq!(&share.verifying_key(), sk.verifying_key());
+            assert_eq!(share.party_index(), idx);
+        }
+
+        // One share is not enough.
+        assert!(matches!(
+            tss_shares[0].to_key_share_seed(&[0]),
+            Err(TssLibImportError::InvalidSigners(_))
+        ));
+        // The party must be one of the signers.
+        assert!(matches!(
+            tss_shares[1].to_key_share_seed(&[0, 2]),
+            Err(TssLibImportError::InvalidSigners(_))
+        ));
+
+        let signers = [2, 0];
+        let seeds = signers
+            .iter()
+            .map(|signer| tss_shares[*signer].to_key_share_seed(&signers).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut shared_randomness = [0u8; 32];
+        OsRng.fill_bytes(&mut shared_randomness);
+        let r1 = (0..signers.len())
+            .map(|idx| {
+                key_refresh::Round1::<TestParams>::new(
+                    &mut OsRng,
+                    &shared_randomness,
+                    signers.len(),
+                    PartyIdx::from_usize(idx),
+                    key_refresh::Inputs::default(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let r1a = step_round(&mut OsRng, r1).unwrap();
+        let r2 = step_next_round(&mut OsRng, r1a).unwrap();
+        let r2a = step_round(&mut OsRng, r2).unwrap();
+        let r3 = step_next_round(&mut OsRng, r2a).unwrap();
+        let r3a = step_round(&mut OsRng, r3).unwrap();
+        let changes = step_result(&mut OsRng, r3a).unwrap();
+
+        let key_shares = seeds
+            .into_iter()
+            .zip(changes)
+            .map(|(seed, change)| KeyShare::<TestParams>::new(seed, change).unwrap())
+            .collect::<Vec<_>>();
+
+        let secret: Scalar = key_shares.iter().map(|share| share.secret_share).sum();
+        assert_eq!(secret, Scalar::from(sk.as_nonzero_scalar()));
+        for share in key_shares.iter() {
+            assert_eq!(&share.verifying_key(), sk.verifying_key());
+            assert_eq!(share.share_set_id, key_shares[0].share_set_id);
+        }
+    }
+
+    #[test]
+    fn reject_inconsistent_share() {
+        let sk = SigningKey::random(&mut OsRng);
+        let save_data = make_save_data(&sk);
+        let other_data = make_save_data(&sk);
+
+        // Take the secret share from a different sharing.
+        let xi_start = save_data[0].find(r#""Xi":"#).unwrap();
+        let xi_end = xi_start + save_data[0][xi_start..].find(',').unwrap();
+        let other_xi_start = other_data[0].find(r#""Xi":"#).unwrap();
+        let other_xi_end = other_xi_start + other_data[0][other_xi_start..].find(',').unwrap();
+        let json = format!(
+            "{}{}{}",
+            &save_data[0][..xi_start],
+            &other_data[0][other_xi_start..other_xi_end],
+            &save_data[0][xi_end..]
+        );
+        assert!(matches!(
+            TssLibKeyShare::from_json(&json),
+            Err(TssLibImportError::Inconsistent(_))
+        ));
+
+        assert!(matches!(
+            TssLibKeyShare::from_json("{}"),
+            Err(TssLibImportError::InvalidJson(_))
+        ));
+    }
+}
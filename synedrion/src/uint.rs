@@ -4,12 +4,15 @@ mod traits;
 
 pub(crate) use crypto_bigint::{
     modular::Retrieve, subtle, CheckedAdd, CheckedMul, CheckedSub, Encoding, Integer, Invert,
-    NonZero, PowBoundedExp, RandomMod, Zero, U1024, U2048, U4096, U512, U8192,
+    NonZero, PowBoundedExp, RandomMod, Zero, U1024, U1536, U16384, U2048, U3072, U4096, U512,
+    U6144, U768, U8192,
 };
-pub(crate) use crypto_primes::RandomPrimeWithRng;
 
 pub(crate) use bounded::Bounded;
 pub(crate) use signed::Signed;
+#[cfg(feature = "key-refresh")]
+pub(crate) use traits::uint_from_be_slice;
 pub(crate) use traits::{
-    upcast_uint, HasWide, U1024Mod, U2048Mod, U4096Mod, U512Mod, UintLike, UintModLike,
+    upcast_uint, HasWide, U1024Mod, U1536Mod, U2048Mod, U3072Mod, U4096Mod, U512Mod, U768Mod,
+    U8192Mod, UintLike, UintModLike,
 };
@@ -3,6 +3,7 @@ use alloc::format;
 use alloc::string::String;
 
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 use super::{
     subtle::{Choice, ConditionallySelectable, ConstantTimeLess, CtOption},
@@ -10,6 +11,7 @@ use super::{
 };
 use crate::tools::hashing::{Chain, Hashable};
 use crate::tools::serde_bytes;
+use crate::tools::serialized_size::{bytes_size, uint_size, MaxSerializedSize};
 
 /// A packed representation for serializing Bounded objects.
 /// Usually they have the bound much lower than the full size of the integer,
@@ -24,7 +26,7 @@ pub(crate) struct PackedBounded {
 impl<T: UintLike> From<Bounded<T>> for PackedBounded {
     fn from(val: Bounded<T>) -> Self {
         let repr = val.as_ref().to_be_bytes();
-        let bound_bytes = (val.bound() + 7) / 8;
+        let bound_bytes = val.bound().div_ceil(8);
         let slice = &repr.as_ref()[(repr.as_ref().len() - bound_bytes as usize)..];
         Self {
             bound: val.bound(),
@@ -46,6 +48,14 @@ impl<T: UintLike> TryFrom<PackedBounded> for Bounded<T> {
                 bytes_len, repr_len
             ));
         }
+        // The packed value has exactly as many bytes as needed for the bound,
+        // so that the serialization is canonical.
+        if bytes_len != (val.bound as usize).div_ceil(8) {
+            return Err(format!(
+                "The bytestring of length {} does not match the bound {}",
+                bytes_len, val.bound
+            ));
+        }
 
         repr.as_mut()[(repr_len - bytes_len)..].copy_from_slice(&val.bytes);
         let abs_value = T::from_be_bytes(repr);
@@ -69,6 +79,19 @@ impl<T: UintLike> Hashable for Bounded<T> {
     }
 }
 
+impl<T: UintLike> MaxSerializedSize for Bounded<T> {
+    fn max_serialized_size(_num_parties: usize) -> usize {
+        // The bound, and at most the full integer as a byte string.
+        4 + bytes_size(uint_size::<T>())
+    }
+}
+
+impl<T: UintLike> Zeroize for Bounded<T> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
 impl<T: UintLike> Bounded<T> {
     pub fn bound(&self) -> u32 {
         self.bound
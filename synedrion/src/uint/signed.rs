@@ -4,6 +4,7 @@ use core::ops::{Add, Mul, Neg, Sub};
 use digest::XofReader;
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 use super::{
     bounded::PackedBounded,
@@ -15,6 +16,7 @@ use super::{
 };
 
 use crate::tools::hashing::{Chain, Hashable};
+use crate::tools::serialized_size::MaxSerializedSize;
 
 /// A packed representation for serializing Signed objects.
 /// Usually they have the bound much lower than the full size of the integer,
@@ -38,6 +40,10 @@ impl<T: UintLike> TryFrom<PackedSigned> for Signed<T> {
     type Error = String;
     fn try_from(val: PackedSigned) -> Result<Self, Self::Error> {
         let abs_value = Bounded::try_from(val.abs_value)?;
+        // Zero is serialized as positive, so that the serialization is canonical.
+        if val.is_negative && abs_value.as_ref() == &T::ZERO {
+            return Err("Negative zero is not a valid signed integer".into());
+        }
         Self::new_from_abs(
             *abs_value.as_ref(),
             abs_value.bound(),
@@ -64,6 +70,19 @@ impl<T: UintLike> Hashable for Signed<T> {
     }
 }
 
+impl<T: UintLike> MaxSerializedSize for Signed<T> {
+    fn max_serialized_size(num_parties: usize) -> usize {
+        // The sign, and the packed absolute value.
+        1 + Bounded::<T>::max_serialized_size(num_parties)
+    }
+}
+
+impl<T: UintLike> Zeroize for Signed<T> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
 impl<T: UintLike> Signed<T> {
     pub fn bound(&self) -> u32 {
         self.bound
@@ -276,7 +295,7 @@ impl<T: UintLike> Neg for Signed<T> {
     }
 }
 
-impl<'a, T: UintLike> Neg for &'a Signed<T> {
+impl<T: UintLike> Neg for &Signed<T> {
     type Output = Signed<T>;
     fn neg(self) -> Self::Output {
         Signed {
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::ops::{Add, Mul, Neg, Sub};
 
 use crypto_bigint::{
@@ -7,11 +8,21 @@ use crypto_bigint::{
     },
     nlimbs,
     subtle::{self, Choice, ConstantTimeLess, CtOption},
-    Encoding, Integer, Invert, NonZero, PowBoundedExp, Random, RandomMod, Uint, Zero, U1024, U2048,
-    U4096, U512, U8192,
+    Encoding, Integer, Invert, NonZero, PowBoundedExp, Random, RandomMod, Uint, Zero, U1024, U1536,
+    U16384, U2048, U3072, U4096, U512, U6144, U768, U8192,
 };
+#[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+use crypto_primes::hazmat::{random_odd_uint, Sieve};
+#[cfg(any(test, feature = "key-refresh"))]
 use crypto_primes::RandomPrimeWithRng;
 use digest::XofReader;
+#[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+#[cfg(any(test, feature = "key-refresh"))]
+use rand_core::CryptoRngCore;
+#[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use zeroize::Zeroize;
 
 use super::{bounded::Bounded, signed::Signed};
 use crate::tools::hashing::{Chain, Hashable};
@@ -27,14 +38,26 @@ pub(crate) const fn upcast_uint<const N1: usize, const N2: usize>(value: Uint<N1
     Uint::from_words(result_words)
 }
 
+/// Creates an integer from big-endian bytes,
+/// returning `None` if it does not fit into `T`.
+#[cfg(feature = "key-refresh")]
+pub(crate) fn uint_from_be_slice<T: UintLike>(bytes: &[u8]) -> Option<T> {
+    let mut repr = T::ZERO.to_be_bytes();
+    let uint_len = repr.as_ref().len();
+    let (leading, bytes) = if bytes.len() > uint_len {
+        bytes.split_at(bytes.len() - uint_len)
+    } else {
+        (&[][..], bytes)
+    };
+    if leading.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    repr.as_mut()[uint_len - bytes.len()..].copy_from_slice(bytes);
+    Some(T::from_be_bytes(repr))
+}
+
 pub trait UintLike:
-    Integer
-    + Encoding
-    + Hashable
-    + RandomPrimeWithRng
-    + RandomMod
-    + Random
-    + subtle::ConditionallySelectable
+    Integer + Encoding + Hashable + RandomMod + Random + subtle::ConditionallySelectable + Zeroize
 {
     type ModUint: UintModLike<RawUint = Self>;
     fn from_xof(reader: &mut impl XofReader, modulus: &NonZero<Self>) -> Self;
@@ -53,6 +76,16 @@ pub trait UintLike:
     fn neg_mod(&self, modulus: &Self) -> Self;
     fn shl_vartime(&self, shift: usize) -> Self;
     fn shr_vartime(&self, shift: usize) -> Self;
+    /// Returns a random safe prime of size `bit_length`.
+    #[cfg(any(test, feature = "key-refresh"))]
+    fn generate_safe_prime(rng: &mut impl CryptoRngCore, bit_length: usize) -> Self;
+    /// Returns a random safe prime of size `bit_length`,
+    /// testing the candidates in the threads of the `rayon` pool.
+    #[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+    fn generate_safe_prime_in_parallel(rng: &mut impl CryptoRngCore, bit_length: usize) -> Self;
+    /// Checks if the number is prime (probabilistically).
+    #[cfg(any(test, feature = "key-refresh"))]
+    fn is_prime(&self, rng: &mut impl CryptoRngCore) -> bool;
     fn to_mod(&self, precomputed: &<Self::ModUint as UintModLike>::Precomputed) -> Self::ModUint {
         Self::ModUint::new(self, precomputed)
     }
@@ -83,7 +116,7 @@ where
         let backend_modulus = modulus.as_ref();
 
         let n_bits = backend_modulus.bits_vartime();
-        let n_bytes = (n_bits + 7) / 8; // ceiling division by 8
+        let n_bytes = n_bits.div_ceil(8);
 
         // If the number of bits is not a multiple of 8,
         // use a mask to zeroize the high bits in the gererated random bytestring,
@@ -167,6 +200,47 @@ where
     fn shr_vartime(&self, shift: usize) -> Self {
         self.shr_vartime(shift)
     }
+
+    #[cfg(any(test, feature = "key-refresh"))]
+    fn generate_safe_prime(rng: &mut impl CryptoRngCore, bit_length: usize) -> Self {
+        Self::generate_safe_prime_with_rng(rng, Some(bit_length))
+    }
+
+    #[cfg(all(feature = "rayon", any(test, feature = "key-refresh")))]
+    fn generate_safe_prime_in_parallel(rng: &mut impl CryptoRngCore, bit_length: usize) -> Self {
+        // Same as `generate_safe_prime_with_rng()`, but the candidates left by the sieve
+        // are tested in batches, and the first safe prime in the order of the sieve is taken.
+        // The randomness of each primality test is derived from a seed drawn from `rng`
+        // and the position of the candidate, so the result is determined by the state of `rng`
+        // and does not depend on the number of threads or the scheduling.
+        let batch_size = 4 * rayon::current_num_threads();
+        loop {
+            let start: Self = random_odd_uint(rng, bit_length);
+            let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+            rng.fill_bytes(&mut seed);
+
+            let mut candidates = Sieve::new(&start, bit_length, true).zip(0u64..);
+            loop {
+                let batch = candidates.by_ref().take(batch_size).collect::<Vec<_>>();
+                if batch.is_empty() {
+                    break;
+                }
+                let prime = batch.into_par_iter().find_map_first(|(num, position)| {
+                    let mut rng = ChaCha20Rng::from_seed(seed);
+                    rng.set_stream(position);
+                    num.is_safe_prime_with_rng(&mut rng).then_some(num)
+                });
+                if let Some(prime) = prime {
+                    return prime;
+                }
+            }
+        }
+    }
+
+    #[cfg(any(test, feature = "key-refresh"))]
+    fn is_prime(&self, rng: &mut impl CryptoRngCore) -> bool {
+        self.is_prime_with_rng(rng)
+    }
 }
 
 impl<const L: usize> Hashable for Uint<L>
@@ -182,6 +256,7 @@ where
 pub trait UintModLike:
     PowBoundedExp<Self::RawUint>
     + Send
+    + Sync
     + core::fmt::Debug
     + Add<Output = Self>
     + Neg<Output = Self>
@@ -196,12 +271,14 @@ pub trait UintModLike:
     + for<'a> Mul<&'a Self, Output = Self>
     + subtle::ConditionallyNegatable
     + subtle::ConditionallySelectable
+    + subtle::ConstantTimeEq
+    + Zeroize
 {
     /// The corresponding regular integer type.
     type RawUint: UintLike<ModUint = Self>;
 
     /// Precomputed data for converting a regular integer to the modulo representation.
-    type Precomputed: Clone + Copy + core::fmt::Debug + PartialEq + Eq + Send;
+    type Precomputed: Clone + Copy + core::fmt::Debug + PartialEq + Eq + Send + Sync;
 
     fn new_precomputed(modulus: &NonZero<Self::RawUint>) -> Self::Precomputed;
     fn new(value: &Self::RawUint, precomputed: &Self::Precomputed) -> Self;
@@ -282,6 +359,55 @@ pub trait UintModLike:
         Self::conditional_select(&abs_result, &inv_result, exponent.is_negative())
     }
 
+    /// Calculates `prod_i base_i^{exponent_i}` with Straus' method
+    /// (the squarings are shared between all the terms, and each base uses a window of 4 bits).
+    ///
+    /// Note: this is variable time in both the bases and the exponents,
+    /// so it must only be used with public values (e.g. when verifying ZK proofs).
+    /// Returns `None` if one of the bases with a negative exponent is not invertible.
+    fn multi_pow_signed_vartime<T: UintLike>(
+        precomputed: &Self::Precomputed,
+        bases_and_exponents: &[(Self, Signed<T>)],
+    ) -> Option<Self> {
+        const WINDOW: usize = 4;
+
+        let mut terms = Vec::with_capacity(bases_and_exponents.len());
+        for (base, exponent) in bases_and_exponents {
+            let base = if exponent.is_negative().into() {
+                Option::<Self>::from(base.invert())?
+            } else {
+                *base
+            };
+            // `base^1, ..., base^{2^WINDOW - 1}`
+            let mut table = [base; (1 << WINDOW) - 1];
+            for i in 1..table.len() {
+                table[i] = table[i - 1] * base;
+            }
+            terms.push((table, exponent.abs()));
+        }
+
+        let bits = terms
+            .iter()
+            .map(|(_table, exponent)| exponent.bits_vartime())
+            .max()
+            .unwrap_or(0);
+
+        let mut result = Self::one(precomputed);
+        for window in (0..bits.div_ceil(WINDOW)).rev() {
+            result = result.pow_2k(WINDOW);
+            for (table, exponent) in terms.iter() {
+                let digit = (0..WINDOW).rev().fold(0, |digit, bit| {
+                    (digit << 1) | usize::from(exponent.bit_vartime(window * WINDOW + bit))
+                });
+                if digit != 0 {
+                    result = result * table[digit - 1];
+                }
+            }
+        }
+
+        Some(result)
+    }
+
     /// Calculates `self^{2^k}`
     fn pow_2k(&self, k: usize) -> Self {
         let mut result = *self;
@@ -314,71 +440,44 @@ where
     }
 }
 
-impl HasWide for U512 {
-    type Wide = U1024;
-    fn mul_wide(&self, other: &Self) -> Self::Wide {
-        self.mul_wide(other).into()
-    }
-    fn square_wide(&self) -> Self::Wide {
-        self.square_wide().into()
-    }
-    fn into_wide(self) -> Self::Wide {
-        (self, Self::ZERO).into()
-    }
-    fn from_wide(value: Self::Wide) -> (Self, Self) {
-        value.into()
-    }
-}
-
-impl HasWide for U1024 {
-    type Wide = U2048;
-    fn mul_wide(&self, other: &Self) -> Self::Wide {
-        self.mul_wide(other).into()
-    }
-    fn square_wide(&self) -> Self::Wide {
-        self.square_wide().into()
-    }
-    fn into_wide(self) -> Self::Wide {
-        (self, Self::ZERO).into()
-    }
-    fn from_wide(value: Self::Wide) -> (Self, Self) {
-        value.into()
-    }
-}
-
-impl HasWide for U2048 {
-    type Wide = U4096;
-    fn mul_wide(&self, other: &Self) -> Self::Wide {
-        self.mul_wide(other).into()
-    }
-    fn square_wide(&self) -> Self::Wide {
-        self.square_wide().into()
-    }
-    fn into_wide(self) -> Self::Wide {
-        (self, Self::ZERO).into()
-    }
-    fn from_wide(value: Self::Wide) -> (Self, Self) {
-        value.into()
-    }
+macro_rules! impl_has_wide {
+    ($($uint:ident => $wide:ident),* $(,)?) => {
+        $(
+            impl HasWide for $uint {
+                type Wide = $wide;
+                fn mul_wide(&self, other: &Self) -> Self::Wide {
+                    self.mul_wide(other).into()
+                }
+                fn square_wide(&self) -> Self::Wide {
+                    self.square_wide().into()
+                }
+                fn into_wide(self) -> Self::Wide {
+                    (self, Self::ZERO).into()
+                }
+                fn from_wide(value: Self::Wide) -> (Self, Self) {
+                    value.into()
+                }
+            }
+        )*
+    };
 }
 
-impl HasWide for U4096 {
-    type Wide = U8192;
-    fn mul_wide(&self, other: &Self) -> Self::Wide {
-        self.mul_wide(other).into()
-    }
-    fn square_wide(&self) -> Self::Wide {
-        self.square_wide().into()
-    }
-    fn into_wide(self) -> Self::Wide {
-        (self, Self::ZERO).into()
-    }
-    fn from_wide(value: Self::Wide) -> (Self, Self) {
-        value.into()
-    }
-}
+impl_has_wide!(
+    U512 => U1024,
+    U768 => U1536,
+    U1024 => U2048,
+    U1536 => U3072,
+    U2048 => U4096,
+    U3072 => U6144,
+    U4096 => U8192,
+    U8192 => U16384,
+);
 
 pub type U512Mod = DynResidue<{ nlimbs!(512) }>;
+pub type U768Mod = DynResidue<{ nlimbs!(768) }>;
 pub type U1024Mod = DynResidue<{ nlimbs!(1024) }>;
+pub type U1536Mod = DynResidue<{ nlimbs!(1536) }>;
 pub type U2048Mod = DynResidue<{ nlimbs!(2048) }>;
+pub type U3072Mod = DynResidue<{ nlimbs!(3072) }>;
 pub type U4096Mod = DynResidue<{ nlimbs!(4096) }>;
+pub type U8192Mod = DynResidue<{ nlimbs!(8192) }>;
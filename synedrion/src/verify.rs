@@ -0,0 +1,37 @@
+//! Everything needed to check the outputs of the protocols without taking part in them.
+//!
+//! This module gathers the types that verify signatures, evidence of misbehavior
+//! and session transcripts using only public data.
+//! None of them perform Paillier secret key operations or generate primes,
+//! so a verifier (e.g. a light client or a constrained device) can depend on the crate
+//! with `default-features = false`, which leaves out the code of all the protocols
+//! and the [`crypto-primes`](https://docs.rs/crypto-primes) dependency,
+//! while keeping the crate `no_std` with `alloc`.
+//!
+//! - [`RecoverableSignature::verify`] and [`SchnorrSignature::verify`] check the signatures
+//!   produced by the signing protocols, and [`AdaptorSignature::verify`] the pre-signatures
+//!   of the adaptor signing protocol;
+//! - [`EquivocationEvidence::verify`] checks that a party signed conflicting messages,
+//!   and [`AbortCertificate::verify`] that a fault was endorsed by a quorum of parties;
+//! - [`SigningCertificate::verify`] checks that a signature was endorsed
+//!   by all the parties of the session that created it;
+//! - [`MessageTranscript::verify`] checks a recorded log of the messages of a session;
+//! - [`EncryptedShare::verify`] checks that an escrowed share matches the party's public share;
+//! - with the `keygen` feature, [`KeyGenTranscript::verify`] checks the broadcasts of a KeyGen
+//!   execution and returns the resulting verifying key.
+//!   The transcript needs the message types of KeyRefresh, so it is not available
+//!   in the verification-only build.
+
+pub use crate::adaptor::AdaptorSignature;
+pub use crate::bip340::SchnorrSignature;
+pub use crate::curve::RecoverableSignature;
+pub use crate::curve::VerifyingKey;
+pub use crate::recovery::EncryptedShare;
+pub use crate::sessions::{
+    AbortCertificate, AbortCertificateError, AbortReport, CombinedMessage, EquivocationEvidence,
+    MessageTranscript, MessageTranscriptError, SessionId, SigningAttestation, SigningCertificate,
+    SigningCertificateError,
+};
+#[cfg(feature = "keygen")]
+pub use crate::sessions::{KeyGenTranscript, TranscriptError};
+pub use crate::PrehashedMessage;
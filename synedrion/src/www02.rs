@@ -31,6 +31,7 @@ use crate::tools::{
 pub struct KeyResharingResult<P: SchemeParams>(PhantomData<P>);
 
 impl<P: SchemeParams> ProtocolResult for KeyResharingResult<P> {
+    const NAME: &'static str = "KeyResharing";
     type Success = Option<ThresholdKeyShareSeed<P>>;
     type ProvableError = KeyResharingError;
     type CorrectnessProof = ();
@@ -324,8 +325,8 @@ impl<P: SchemeParams> FinalizableToResult for Round1<P> {
             .iter()
             .map(|party_idx| {
                 (
-                    payloads[&party_idx].old_share_idx,
-                    payloads[&party_idx].subshare,
+                    payloads[party_idx].old_share_idx,
+                    payloads[party_idx].subshare,
                 )
             })
             .collect::<BTreeMap<_, _>>();
@@ -336,7 +337,7 @@ impl<P: SchemeParams> FinalizableToResult for Round1<P> {
             .new_share_idxs
             .keys()
             .map(|party_idx| {
-                let share_idx = self.new_share_idxs[&party_idx];
+                let share_idx = self.new_share_idxs[party_idx];
                 let public_subshares = payloads
                     .values()
                     .map(|p| (p.old_share_idx, p.public_polynomial.evaluate(&share_idx)))
@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+
+use synedrion::{
+    make_key_gen_session,
+    sessions::{Error, LowLevelMessage, LowLevelOutcome, LowLevelRound},
+    testnet, KeyGenResult, TestParams,
+};
+
+type KeyGenRound = LowLevelRound<KeyGenResult<TestParams>>;
+
+fn make_signers(num_parties: usize) -> (Vec<SigningKey>, BTreeSet<VerifyingKey>) {
+    testnet::make_signers(num_parties, || SigningKey::random(&mut OsRng))
+}
+
+fn make_key_gen_rounds(num_parties: usize) -> Vec<KeyGenRound> {
+    let (signers, verifiers) = make_signers(num_parties);
+    signers
+        .into_iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+            )
+            .unwrap()
+            .into_low_level()
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn keygen_round_by_round() {
+    let num_parties = 3;
+    let mut rounds = make_key_gen_rounds(num_parties);
+
+    let key_shares = loop {
+        // Every party creates its messages, and they are delivered to their destinations.
+        let mut inboxes = vec![Vec::<(usize, LowLevelMessage)>::new(); num_parties];
+        for round in rounds.iter_mut() {
+            for message in round.to_send(&mut OsRng).unwrap() {
+                inboxes[message.destination].push((round.party_index(), message));
+            }
+        }
+
+        for (round, inbox) in rounds.iter_mut().zip(inboxes) {
+            assert_eq!(round.missing_messages().len(), num_parties - 1);
+            for (from, message) in inbox {
+                round.receive(from, &message).unwrap();
+            }
+            assert!(round.can_finalize());
+        }
+
+        let outcomes = rounds
+            .into_iter()
+            .map(|round| round.finalize(&mut OsRng).unwrap())
+            .collect::<Vec<_>>();
+        if let LowLevelOutcome::Success(_) = outcomes[0] {
+            break outcomes
+                .into_iter()
+                .map(|outcome| match outcome {
+                    LowLevelOutcome::Success(key_share) => key_share,
+                    LowLevelOutcome::AnotherRound(_) => panic!("The parties are out of sync"),
+                })
+                .collect::<Vec<_>>();
+        }
+        rounds = outcomes
+            .into_iter()
+            .map(|outcome| match outcome {
+                LowLevelOutcome::AnotherRound(round) => round,
+                LowLevelOutcome::Success(_) => panic!("The parties are out of sync"),
+            })
+            .collect();
+    };
+
+    for (idx, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
+    }
+}
+
+#[test]
+fn messages_checked_on_receive() {
+    let mut rounds = make_key_gen_rounds(3);
+    let messages = rounds[0].to_send(&mut OsRng).unwrap();
+    let message = messages
+        .iter()
+        .find(|message| message.destination == 1)
+        .unwrap();
+
+    // The message is not for this party.
+    assert!(matches!(
+        rounds[2].receive(0, message),
+        Err(Error::Local(_))
+    ));
+
+    // The same party sends a message twice.
+    rounds[1].to_send(&mut OsRng).unwrap();
+    rounds[1].receive(0, message).unwrap();
+    assert!(matches!(
+        rounds[1].receive(0, message),
+        Err(Error::Local(_))
+    ));
+    assert_eq!(rounds[1].missing_messages(), [2]);
+}
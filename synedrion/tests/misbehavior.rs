@@ -0,0 +1,349 @@
+use std::collections::BTreeSet;
+
+use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+
+use synedrion::testnet::{self, Fault, MessageFilter, TestNetwork};
+use synedrion::{
+    make_key_gen_session,
+    sessions::{
+        AbortCertificate, AbortCertificateError, AbortReport, EchoError, EquivocationEvidence,
+        Error, Misbehavior, ProvableError, RemoteError, RemoteErrorEnum,
+    },
+    KeyGenError, KeyGenResult, KeyInitError, ProtocolResult, Session, TestParams,
+};
+
+type KeyGenSession = Session<KeyGenResult<TestParams>, Signature, SigningKey, VerifyingKey>;
+
+fn make_signers(num_parties: usize) -> (Vec<SigningKey>, BTreeSet<VerifyingKey>) {
+    testnet::make_signers(num_parties, || SigningKey::random(&mut OsRng))
+}
+
+fn make_key_gen_sessions_for(
+    signers: &[SigningKey],
+    verifiers: &BTreeSet<VerifyingKey>,
+) -> Vec<KeyGenSession> {
+    signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                verifiers,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn make_key_gen_sessions(num_parties: usize) -> Vec<KeyGenSession> {
+    let (signers, verifiers) = make_signers(num_parties);
+    make_key_gen_sessions_for(&signers, &verifiers)
+}
+
+/// Runs the sessions on a [`TestNetwork`], with the party 0 applying `misbehavior`
+/// to its messages to the parties in `victims` in the given round,
+/// and returns the error reported by the first honest party.
+fn run_with_misbehavior<Res: ProtocolResult>(
+    sessions: Vec<Session<Res, Signature, SigningKey, VerifyingKey>>,
+    round: u8,
+    misbehavior: Misbehavior,
+    victims: &[usize],
+) -> Error<Res, VerifyingKey> {
+    let keys = sessions
+        .iter()
+        .map(|session| session.verifier())
+        .collect::<Vec<_>>();
+    let network = victims
+        .iter()
+        .fold(TestNetwork::new(sessions), |network, victim| {
+            network.with_fault(Fault::Misbehave(
+                MessageFilter::all()
+                    .sent_by(keys[0])
+                    .sent_to(keys[*victim])
+                    .in_round(round),
+                misbehavior,
+            ))
+        });
+    let mut outcomes = network.run(&mut OsRng);
+
+    keys[1..]
+        .iter()
+        .find_map(|key| outcomes.remove(key).unwrap().err())
+        .expect("The misbehavior was not detected")
+}
+
+#[test]
+fn wrong_commitment() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    // The broadcast of Round 1 ends with the hash commitments of KeyInit and KeyRefresh
+    // (8 bytes of the length prefix and 32 bytes of the hash each),
+    // so this corrupts the KeyInit one.
+    let error = run_with_misbehavior(
+        sessions,
+        1,
+        Misbehavior::CorruptBroadcast { position: 40 },
+        &[1, 2],
+    );
+    assert!(matches!(
+        error,
+        Error::Provable {
+            party,
+            error: ProvableError::Protocol(KeyGenError::KeyInit(KeyInitError::R2HashMismatch)),
+            ..
+        } if party == malicious
+    ));
+}
+
+#[test]
+fn corrupted_proof() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    // The broadcast of Round 3 is a Schnorr proof, ending with the response scalar.
+    let error = run_with_misbehavior(
+        sessions,
+        3,
+        Misbehavior::CorruptBroadcast { position: 0 },
+        &[1, 2],
+    );
+    assert!(matches!(
+        error,
+        Error::Provable {
+            party,
+            error: ProvableError::Protocol(KeyGenError::KeyInit(KeyInitError::R3InvalidSchProof)),
+            ..
+        } if party == malicious
+    ));
+}
+
+#[test]
+fn inconsistent_broadcast() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    // Only one of the parties receives the corrupted broadcast.
+    let error = run_with_misbehavior(
+        sessions,
+        1,
+        Misbehavior::CorruptBroadcast { position: 0 },
+        &[1],
+    );
+    assert!(matches!(
+        error,
+        Error::Provable {
+            party,
+            error: ProvableError::Echo(EchoError::ConflictingBroadcasts),
+            ..
+        } if party == malicious
+    ));
+}
+
+#[test]
+fn invalid_signature() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    let error = run_with_misbehavior(sessions, 2, Misbehavior::InvalidSignature, &[1, 2]);
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            party,
+            error: RemoteErrorEnum::InvalidSignature(_),
+            ..
+        }) if party == malicious
+    ));
+}
+
+#[test]
+fn unsupported_version() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    // A party running an incompatible version is rejected in the first round.
+    let error = run_with_misbehavior(
+        sessions,
+        1,
+        Misbehavior::UnsupportedVersion { version: 2 },
+        &[1, 2],
+    );
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            party,
+            error: RemoteErrorEnum::UnsupportedVersion(2),
+            ..
+        }) if party == malicious
+    ));
+}
+
+#[test]
+fn relayed_message() {
+    let sessions = make_key_gen_sessions(3);
+    let keys = sessions
+        .iter()
+        .map(|session| session.verifier())
+        .collect::<Vec<_>>();
+
+    // The party 1 passes off a message of the party 0 as its own.
+    let (message, _artifact) = sessions[0].make_message(&mut OsRng, &keys[2]).unwrap();
+    let mut accum = sessions[2].make_accumulator();
+    let error = sessions[2]
+        .preprocess_message(&mut accum, &keys[1], message)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            party,
+            error: RemoteErrorEnum::UnexpectedSender,
+            ..
+        }) if party == keys[1]
+    ));
+}
+
+#[test]
+fn duplicate_messages() {
+    let sessions = make_key_gen_sessions(3);
+    let keys = sessions
+        .iter()
+        .map(|session| session.verifier())
+        .collect::<Vec<_>>();
+
+    let (message, _artifact) = sessions[0].make_message(&mut OsRng, &keys[1]).unwrap();
+    let mut accum = sessions[1].make_accumulator();
+    let preprocessed = sessions[1]
+        .preprocess_message(&mut accum, &keys[0], message.clone())
+        .unwrap()
+        .unwrap();
+    let processed = sessions[1].process_message(preprocessed).unwrap();
+    accum.add_processed_message(processed).unwrap().unwrap();
+
+    // Identical copies of the message are ignored.
+    assert!(sessions[1]
+        .preprocess_message(&mut accum, &keys[0], message)
+        .unwrap()
+        .is_none());
+
+    // A different message for the same round is an equivocation.
+    let (conflicting, _artifact) = sessions[0]
+        .make_malicious_message(
+            &mut OsRng,
+            &keys[1],
+            Misbehavior::CorruptBroadcast { position: 0 },
+        )
+        .unwrap()
+        .unwrap();
+    let error = sessions[1]
+        .preprocess_message(&mut accum, &keys[0], conflicting)
+        .err()
+        .unwrap();
+    let Error::Provable {
+        party,
+        error: ProvableError::Equivocation(evidence),
+        ..
+    } = error
+    else {
+        panic!("Unexpected error: {error:?}");
+    };
+    assert_eq!(party, keys[0]);
+
+    // The evidence can be checked by a third party knowing the verifier of the offender.
+    let evidence: EquivocationEvidence =
+        bincode::deserialize(&bincode::serialize(&evidence).unwrap()).unwrap();
+    assert!(evidence.verify::<Signature>(&keys[0]));
+    assert!(!evidence.verify::<Signature>(&keys[2]));
+}
+
+#[test]
+fn silence() {
+    let sessions = make_key_gen_sessions(3);
+    let malicious = sessions[0].verifier();
+
+    let error = run_with_misbehavior(sessions, 2, Misbehavior::Silence, &[1, 2]);
+    assert!(matches!(
+        &error,
+        Error::Unresponsive { parties, .. } if parties == &[malicious]
+    ));
+    assert_eq!(error.context().unwrap().round, 2);
+}
+
+#[test]
+fn abort_certificate() {
+    let (signers, verifiers) = make_signers(3);
+    let sessions = make_key_gen_sessions_for(&signers, &verifiers);
+    let session_id = *sessions[0].session_id();
+
+    let error = run_with_misbehavior(
+        sessions,
+        3,
+        Misbehavior::CorruptBroadcast { position: 0 },
+        &[1, 2],
+    );
+    let report = AbortReport::new(&session_id, &error).unwrap();
+    assert_eq!(report.accused, *signers[0].verifying_key());
+    assert_eq!(report.round, 3);
+
+    // The corrupted proof was broadcast, so the other honest party reports the same fault.
+    let accuser = &signers[1];
+    let endorser = &signers[2];
+    let mut certificate =
+        AbortCertificate::<Signature, _>::new(&mut OsRng, accuser, report.clone()).unwrap();
+    assert_eq!(
+        certificate.verify(&verifiers, 2),
+        Err(AbortCertificateError::NotEnoughEndorsements {
+            required: 2,
+            found: 1
+        })
+    );
+
+    // A party can only endorse the report matching its own one.
+    let mut other_report = report.clone();
+    other_report.round = 2;
+    assert!(certificate
+        .endorse(&mut OsRng, endorser, &other_report)
+        .is_err());
+    // The accused party cannot endorse the report.
+    assert!(certificate
+        .endorse(&mut OsRng, &signers[0], &report)
+        .is_err());
+
+    let mut endorsed = certificate.clone();
+    endorsed.endorse(&mut OsRng, endorser, &report).unwrap();
+    certificate.merge(endorsed).unwrap();
+    assert_eq!(certificate.endorsers().len(), 2);
+    certificate.verify(&verifiers, 2).unwrap();
+
+    // The certificate cannot be used for a different set of parties...
+    let (_, other_verifiers) = make_signers(3);
+    assert_eq!(
+        certificate.verify(&other_verifiers, 2),
+        Err(AbortCertificateError::UnknownEndorser)
+    );
+
+    // ...or a different report.
+    let mut forged =
+        AbortCertificate::<Signature, _>::new(&mut OsRng, accuser, other_report).unwrap();
+    assert!(forged.merge(certificate.clone()).is_err());
+
+    let mut serialized = bincode::serialize(&certificate).unwrap();
+    let deserialized: AbortCertificate<Signature, VerifyingKey> =
+        bincode::deserialize(&serialized).unwrap();
+    deserialized.verify(&verifiers, 2).unwrap();
+
+    let reason = report.reason.as_bytes();
+    let position = serialized
+        .windows(reason.len())
+        .position(|window| window == reason)
+        .unwrap();
+    serialized[position] ^= 1;
+    let tampered: AbortCertificate<Signature, VerifyingKey> =
+        bincode::deserialize(&serialized).unwrap();
+    assert_eq!(
+        tampered.verify(&verifiers, 2),
+        Err(AbortCertificateError::InvalidSignature)
+    );
+}
@@ -1,15 +1,47 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::marker::PhantomData;
+use std::sync::Mutex;
 
-use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, SigningKey, VerifyingKey};
-use rand::Rng;
-use rand_core::OsRng;
+use k256::ecdsa::{
+    signature::{hazmat::PrehashVerifier, Keypair, Signer},
+    Signature, SigningKey, VerifyingKey,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use sha2::{Digest, Sha256};
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 
+// The curve of the shared keys, as opposed to the `k256` transport keys above.
+#[cfg(not(feature = "p256"))]
+use k256 as shared_curve;
+use shared_curve::ecdsa as shared_ecdsa;
+#[cfg(feature = "p256")]
+use synedrion::p256 as shared_curve;
 use synedrion::{
-    make_interactive_signing_session, make_key_gen_session, CombinedMessage, FinalizeOutcome,
-    KeyShare, ProtocolResult, Session, TestParams,
+    make_adaptor_signing_session, make_aux_refresh_session, make_batch_interactive_signing_session,
+    make_interactive_signing_session, make_interactive_signing_session_with_precompute,
+    make_interactive_signing_session_with_tweak, make_key_export_session, make_key_gen_session,
+    make_key_gen_session_with_beacon, make_key_import_session, make_key_refresh_session,
+    make_key_refresh_session_with_paillier_key, make_presigning_session,
+    make_presigning_session_with_precompute, make_schnorr_signing_session,
+    make_share_refresh_session, make_signing_session, make_threshold_refresh_session, run_session,
+    sessions::{
+        run_session_with_metrics, run_session_with_observer, run_session_with_recorder,
+        run_session_with_rng_factory, AbortCause, Bincode, DirectMessageEncryption, Error,
+        ErrorContext, FragmentError, KeyGenTranscript, LocalError, MessageDirection, MessageFormat,
+        MessageFragment, MessageLog, MessageTranscript, MessageTranscriptError, OutgoingPart,
+        RemoteError, RemoteErrorEnum, ResumableTransport, RunError, SessionLimits, SessionManager,
+        SessionManagerError, SessionMetrics, SessionObserver, SessionStatus, SigningAttestation,
+        SigningCertificate, SigningCertificateError, TranscriptError, TranscriptRecorder,
+    },
+    testnet::{self, Fault, MessageFilter, TestNetwork},
+    BeaconRandomness, CombinedMessage, FinalizeOutcome, InteractiveSigner, KeyShare, Params1536,
+    PregeneratedPaillierKey, PresigningMode, PresigningPrecompute, ProtocolInfo, ProtocolResult,
+    Session, TaprootTweak, TestParams, ThresholdKeyShare, Transport,
 };
+#[cfg(feature = "ed25519")]
+use synedrion::{make_ed25519_signing_session, Ed25519ThresholdKeyShare, ShareIdx};
 
 type MessageOut = (VerifyingKey, VerifyingKey, CombinedMessage<Signature>);
 type MessageIn = (VerifyingKey, CombinedMessage<Signature>);
@@ -18,7 +50,7 @@ fn key_to_str(key: &VerifyingKey) -> String {
     hex::encode(&key.to_encoded_point(true).as_bytes()[1..5])
 }
 
-async fn run_session<Res: ProtocolResult>(
+async fn run_session_manually<Res: ProtocolResult>(
     tx: mpsc::Sender<MessageOut>,
     rx: mpsc::Receiver<MessageIn>,
     session: Session<Res, Signature, SigningKey, VerifyingKey>,
@@ -109,6 +141,154 @@ async fn run_session<Res: ProtocolResult>(
     }
 }
 
+struct ChannelTransport {
+    key: VerifyingKey,
+    tx: mpsc::Sender<MessageOut>,
+    rx: mpsc::Receiver<MessageIn>,
+}
+
+impl Transport<Signature, VerifyingKey> for ChannelTransport {
+    type Error = String;
+
+    async fn send(
+        &mut self,
+        destination: &VerifyingKey,
+        message: CombinedMessage<Signature>,
+    ) -> Result<(), Self::Error> {
+        self.tx
+            .send((self.key, *destination, message))
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn receive(&mut self) -> Result<MessageIn, Self::Error> {
+        self.rx.recv().await.ok_or("The channel is closed".into())
+    }
+}
+
+/// A transport that fails after receiving a given number of messages, emulating a crash.
+struct CrashingTransport {
+    inner: ChannelTransport,
+    receives_before_crash: Option<usize>,
+}
+
+impl Transport<Signature, VerifyingKey> for CrashingTransport {
+    type Error = String;
+
+    async fn send(
+        &mut self,
+        destination: &VerifyingKey,
+        message: CombinedMessage<Signature>,
+    ) -> Result<(), Self::Error> {
+        self.inner.send(destination, message).await
+    }
+
+    async fn receive(&mut self) -> Result<MessageIn, Self::Error> {
+        if let Some(receives) = self.receives_before_crash.as_mut() {
+            if *receives == 0 {
+                return Err("Crashed".into());
+            }
+            *receives -= 1;
+        }
+        self.inner.receive().await
+    }
+}
+
+/// A transport that records all the sent and received broadcasts in a KeyGen transcript.
+struct RecordingTransport {
+    inner: ChannelTransport,
+    transcript: KeyGenTranscript<TestParams, Signature, VerifyingKey>,
+}
+
+impl Transport<Signature, VerifyingKey> for RecordingTransport {
+    type Error = String;
+
+    async fn send(
+        &mut self,
+        destination: &VerifyingKey,
+        message: CombinedMessage<Signature>,
+    ) -> Result<(), Self::Error> {
+        self.transcript
+            .add_message(&self.inner.key, &message)
+            .map_err(|err| format!("{err:?}"))?;
+        self.inner.send(destination, message).await
+    }
+
+    async fn receive(&mut self) -> Result<MessageIn, Self::Error> {
+        let (from, message) = self.inner.receive().await?;
+        self.transcript
+            .add_message(&from, &message)
+            .map_err(|err| format!("{err:?}"))?;
+        Ok((from, message))
+    }
+}
+
+/// A transport that checks the sizes of the sent messages against the bounds in `ProtocolInfo`.
+struct SizeCheckingTransport {
+    inner: ChannelTransport,
+    info: ProtocolInfo,
+    messages_sent: usize,
+}
+
+impl Transport<Signature, VerifyingKey> for SizeCheckingTransport {
+    type Error = String;
+
+    async fn send(
+        &mut self,
+        destination: &VerifyingKey,
+        message: CombinedMessage<Signature>,
+    ) -> Result<(), Self::Error> {
+        // The driver sends all the messages of a round before moving on to the next one.
+        let round = &self.info.rounds[self.messages_sent / self.info.rounds[0].num_messages];
+        let size = bincode::serialize(&message).unwrap().len();
+        if size > round.max_message_size {
+            return Err(format!(
+                "The message size {size} exceeds the bound in {round:?}"
+            ));
+        }
+        self.messages_sent += 1;
+        self.inner.send(destination, message).await
+    }
+
+    async fn receive(&mut self) -> Result<MessageIn, Self::Error> {
+        self.inner.receive().await
+    }
+}
+
+/// An observer checking that the progress events follow the rounds listed in `ProtocolInfo`.
+struct ProgressObserver {
+    info: ProtocolInfo,
+    // The position of the current round in `info.rounds` and the number of missing messages.
+    current: Option<(usize, usize)>,
+    rounds_finalized: usize,
+}
+
+impl SessionObserver<VerifyingKey> for ProgressObserver {
+    fn on_round_start(&mut self, round: u8, is_echo_round: bool, missing: &[VerifyingKey]) {
+        let position = self.info.round_position(round, is_echo_round).unwrap();
+        assert_eq!(position, self.rounds_finalized);
+        assert!(self.current.is_none());
+        self.current = Some((position, missing.len()));
+    }
+
+    fn on_message_received(&mut self, from: &VerifyingKey, missing: &[VerifyingKey]) {
+        let (position, num_missing) = self.current.unwrap();
+        assert!(!missing.contains(from));
+        assert!(missing.len() < num_missing);
+        self.current = Some((position, missing.len()));
+    }
+
+    fn on_finalize(&mut self, round: u8, is_echo_round: bool) {
+        let (position, num_missing) = self.current.take().unwrap();
+        assert_eq!(
+            self.info.round_position(round, is_echo_round),
+            Some(position)
+        );
+        assert_eq!(num_missing, 0);
+        self.rounds_finalized += 1;
+    }
+}
+
 async fn message_dispatcher(
     txs: BTreeMap<VerifyingKey, mpsc::Sender<MessageIn>>,
     rx: mpsc::Receiver<MessageOut>,
@@ -132,6 +312,12 @@ async fn message_dispatcher(
             let message_idx = rand::thread_rng().gen_range(0..messages.len());
             let (id_from, id_to, message) = messages.swap_remove(message_idx);
 
+            // Deliver the message through its canonical encoding,
+            // checking that it is reproduced exactly after a roundtrip.
+            let bytes = message.canonical_bytes().unwrap();
+            let message: CombinedMessage<Signature> = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(message.canonical_bytes().unwrap(), bytes);
+
             txs[&id_to].send((id_from, message)).await.unwrap();
 
             // Give up execution so that the tasks could process messages.
@@ -144,15 +330,8 @@ async fn message_dispatcher(
     }
 }
 
-fn make_signers(num_parties: usize) -> (Vec<SigningKey>, Vec<VerifyingKey>) {
-    let signers = (0..num_parties)
-        .map(|_| SigningKey::random(&mut OsRng))
-        .collect::<Vec<_>>();
-    let verifiers = signers
-        .iter()
-        .map(|signer| *signer.verifying_key())
-        .collect::<Vec<_>>();
-    (signers, verifiers)
+fn make_signers(num_parties: usize) -> (Vec<SigningKey>, BTreeSet<VerifyingKey>) {
+    testnet::make_signers(num_parties, || SigningKey::random(&mut OsRng))
 }
 
 async fn run_nodes<Res>(
@@ -162,29 +341,124 @@ where
     Res: ProtocolResult + Send + 'static,
     <Res as ProtocolResult>::Success: Send + 'static,
 {
-    let num_parties = sessions.len();
+    run_nodes_with(sessions, |tx, rx, session| {
+        tokio::spawn(run_session_manually(tx, rx, session))
+    })
+    .await
+}
+
+async fn run_nodes_with_driver<Res, Format>(
+    sessions: Vec<Session<Res, Signature, SigningKey, VerifyingKey, Format>>,
+) -> Vec<<Res as ProtocolResult>::Success>
+where
+    Res: ProtocolResult + Send + 'static,
+    <Res as ProtocolResult>::Success: Send + 'static,
+    Format: MessageFormat,
+{
+    let nodes = sessions
+        .into_iter()
+        .map(|session| (session.verifier(), session))
+        .collect();
+    run_custom_nodes(nodes, |tx, rx, session| {
+        let mut transport = ChannelTransport {
+            key: session.verifier(),
+            tx,
+            rx,
+        };
+        tokio::spawn(async move {
+            run_session(&mut OsRng, &mut transport, session)
+                .await
+                .unwrap()
+        })
+    })
+    .await
+}
+
+async fn run_nodes_checking_sizes<Res>(
+    sessions: Vec<Session<Res, Signature, SigningKey, VerifyingKey>>,
+    info: ProtocolInfo,
+) -> Vec<<Res as ProtocolResult>::Success>
+where
+    Res: ProtocolResult + Send + 'static,
+    <Res as ProtocolResult>::Success: Send + 'static,
+{
+    run_nodes_with(sessions, |tx, rx, session| {
+        let mut transport = SizeCheckingTransport {
+            inner: ChannelTransport {
+                key: session.verifier(),
+                tx,
+                rx,
+            },
+            info: info.clone(),
+            messages_sent: 0,
+        };
+        tokio::spawn(async move {
+            let result = run_session(&mut OsRng, &mut transport, session)
+                .await
+                .unwrap();
+            assert_eq!(
+                transport.messages_sent,
+                transport
+                    .info
+                    .rounds
+                    .iter()
+                    .map(|round| round.num_messages)
+                    .sum::<usize>()
+            );
+            result
+        })
+    })
+    .await
+}
+
+async fn run_nodes_with<Res, F>(
+    sessions: Vec<Session<Res, Signature, SigningKey, VerifyingKey>>,
+    spawn_node: F,
+) -> Vec<<Res as ProtocolResult>::Success>
+where
+    F: Fn(
+        mpsc::Sender<MessageOut>,
+        mpsc::Receiver<MessageIn>,
+        Session<Res, Signature, SigningKey, VerifyingKey>,
+    ) -> tokio::task::JoinHandle<<Res as ProtocolResult>::Success>,
+    Res: ProtocolResult + Send + 'static,
+    <Res as ProtocolResult>::Success: Send + 'static,
+{
+    let nodes = sessions
+        .into_iter()
+        .map(|session| (session.verifier(), session))
+        .collect();
+    run_custom_nodes(nodes, spawn_node).await
+}
+
+async fn run_custom_nodes<Node, Res, F>(nodes: Vec<(VerifyingKey, Node)>, spawn_node: F) -> Vec<Res>
+where
+    F: Fn(
+        mpsc::Sender<MessageOut>,
+        mpsc::Receiver<MessageIn>,
+        Node,
+    ) -> tokio::task::JoinHandle<Res>,
+{
+    let num_parties = nodes.len();
 
     let (dispatcher_tx, dispatcher_rx) = mpsc::channel::<MessageOut>(100);
 
     let channels = (0..num_parties).map(|_| mpsc::channel::<MessageIn>(100));
     let (txs, rxs): (Vec<mpsc::Sender<MessageIn>>, Vec<mpsc::Receiver<MessageIn>>) =
         channels.unzip();
-    let tx_map = sessions
+    let tx_map = nodes
         .iter()
-        .map(|session| session.verifier())
-        .zip(txs.into_iter())
+        .map(|(verifier, _node)| *verifier)
+        .zip(txs)
         .collect();
 
     let dispatcher_task = message_dispatcher(tx_map, dispatcher_rx);
     let dispatcher = tokio::spawn(dispatcher_task);
 
-    let handles: Vec<tokio::task::JoinHandle<<Res as ProtocolResult>::Success>> = rxs
+    let handles: Vec<tokio::task::JoinHandle<Res>> = rxs
         .into_iter()
-        .zip(sessions.into_iter())
-        .map(|(rx, session)| {
-            let node_task = run_session(dispatcher_tx.clone(), rx, session);
-            tokio::spawn(node_task)
-        })
+        .zip(nodes)
+        .map(|(rx, (_verifier, node))| spawn_node(dispatcher_tx.clone(), rx, node))
         .collect();
 
     // Drop the last copy of the dispatcher's incoming channel so that it could finish.
@@ -210,7 +484,7 @@ async fn keygen_and_aux() {
     let sessions = signers
         .into_iter()
         .map(|signer| {
-            make_key_gen_session::<TestParams, Signature, _, _>(
+            make_key_gen_session::<TestParams, Signature, _, _, _>(
                 &mut OsRng,
                 shared_randomness,
                 signer,
@@ -229,8 +503,256 @@ async fn keygen_and_aux() {
     }
 }
 
+#[test]
+fn session_parameter_validation() {
+    fn error_message<Res: ProtocolResult>(
+        result: Result<Session<Res, Signature, SigningKey, VerifyingKey>, LocalError>,
+    ) -> String {
+        match result {
+            Ok(_) => panic!("The session should not have been created"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    let shared_randomness = b"1234567890";
+
+    // A single party cannot run a session.
+    let (signers, verifiers) = make_signers(1);
+    let message = error_message(make_key_gen_session::<TestParams, _, _, _, _>(
+        &mut OsRng,
+        shared_randomness,
+        signers[0].clone(),
+        &verifiers,
+    ));
+    assert!(message.contains("Number of verifiers must be between 2"));
+
+    // The signer must be one of the verifiers.
+    let (_signers, verifiers) = make_signers(3);
+    let message = error_message(make_key_gen_session::<TestParams, _, _, _, _>(
+        &mut OsRng,
+        shared_randomness,
+        SigningKey::random(&mut OsRng),
+        &verifiers,
+    ));
+    assert!(message.contains("not among the verifiers"));
+
+    // The signer's position must match the key share.
+    let (signers, verifiers) = make_signers(3);
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, 3, None);
+    let message = error_message(make_aux_refresh_session::<TestParams, _, _, _, _>(
+        &mut OsRng,
+        shared_randomness,
+        signers[0].clone(),
+        &verifiers,
+        &key_shares[1],
+        None,
+    ));
+    assert!(message.contains("must be equal to the party index in the key share"));
+
+    // The number of verifiers must match the key share.
+    let (signers, verifiers) = make_signers(2);
+    let message = error_message(make_aux_refresh_session::<TestParams, _, _, _, _>(
+        &mut OsRng,
+        shared_randomness,
+        signers[0].clone(),
+        &verifiers,
+        &key_shares[0],
+        None,
+    ));
+    assert!(message.contains("must be equal to the number of parties in the key share"));
+}
+
+#[tokio::test]
+async fn keygen_with_beacon() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    // An ECDSA key stands in for the beacon's key.
+    let beacon_key = SigningKey::random(&mut OsRng);
+    let beacon_verifying_key = *beacon_key.verifying_key();
+    let beacon_verifier = |round: u64, signature: &[u8]| {
+        Signature::from_slice(signature).is_ok_and(|signature| {
+            beacon_verifying_key
+                .verify_prehash(&Sha256::digest(round.to_be_bytes()), &signature)
+                .is_ok()
+        })
+    };
+    let beacon_output = |round: u64| {
+        let signature: Signature = beacon_key.sign(&round.to_be_bytes());
+        BeaconRandomness::new(round, &signature.to_bytes())
+    };
+
+    let make_session = |beacon: &BeaconRandomness, signer: SigningKey| {
+        make_key_gen_session_with_beacon::<TestParams, Signature, _, _, _>(
+            &mut OsRng,
+            beacon,
+            &beacon_verifier,
+            99..=101,
+            signer,
+            &verifiers,
+        )
+    };
+
+    // A stale output is rejected before the first round.
+    let err = make_session(&beacon_output(98), signers[0].clone())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("outside of the accepted window"));
+
+    // So is the signature of another round.
+    let signature: Signature = beacon_key.sign(&98u64.to_be_bytes());
+    let forged = BeaconRandomness::new(100, &signature.to_bytes());
+    let err = make_session(&forged, signers[0].clone()).err().unwrap();
+    assert!(err.to_string().contains("signature is invalid"));
+
+    let beacon = beacon_output(100);
+    let sessions = signers
+        .into_iter()
+        .map(|signer| make_session(&beacon, signer).unwrap())
+        .collect();
+    let key_shares = run_nodes(sessions).await;
+    for key_share in key_shares.iter() {
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
+    }
+}
+
 #[tokio::test]
-async fn interactive_signing() {
+async fn keygen_and_aux_with_driver() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let shared_randomness = b"1234567890";
+
+    let sessions = signers
+        .into_iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let key_shares = run_nodes_with_driver(sessions).await;
+
+    for (idx, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(key_share.num_parties(), num_parties);
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
+    }
+}
+
+#[tokio::test]
+async fn keygen_transcript() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let shared_randomness = b"1234567890";
+
+    let nodes = signers
+        .into_iter()
+        .map(|signer| (*signer.verifying_key(), signer))
+        .collect();
+
+    let results = run_custom_nodes(nodes, |tx, rx, signer| {
+        let verifiers = verifiers.clone();
+        tokio::spawn(async move {
+            let key = *signer.verifying_key();
+            let session = make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+            )
+            .unwrap();
+            let mut transport = RecordingTransport {
+                inner: ChannelTransport { key, tx, rx },
+                transcript: KeyGenTranscript::new(shared_randomness, &verifiers).unwrap(),
+            };
+            let key_share = run_session(&mut OsRng, &mut transport, session)
+                .await
+                .unwrap();
+            (key_share, transport.transcript)
+        })
+    })
+    .await;
+
+    for (key_share, transcript) in results {
+        assert_eq!(transcript.verify().unwrap(), key_share.verifying_key());
+    }
+
+    // An incomplete transcript is rejected.
+    let transcript =
+        KeyGenTranscript::<TestParams, Signature, VerifyingKey>::new(shared_randomness, &verifiers)
+            .unwrap();
+    assert!(matches!(
+        transcript.verify(),
+        Err(TranscriptError::MissingMessage { round: 1, .. })
+    ));
+}
+
+#[cfg(feature = "cbor")]
+#[tokio::test]
+async fn keygen_and_aux_with_cbor() {
+    use synedrion::sessions::Cbor;
+
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let shared_randomness = b"1234567890";
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Cbor>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // A party using a different format cannot take part in the session.
+    let bincode_session = make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+        &mut OsRng,
+        shared_randomness,
+        signers[0].clone(),
+        &verifiers,
+    )
+    .unwrap();
+    let (message, _artifact) = bincode_session
+        .make_message(&mut OsRng, &sessions[1].verifier())
+        .unwrap();
+    let mut accum = sessions[1].make_accumulator();
+    let error = sessions[1]
+        .preprocess_message(&mut accum, &bincode_session.verifier(), message)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            error: RemoteErrorEnum::UnexpectedFormat,
+            ..
+        })
+    ));
+
+    let key_shares = run_nodes_with_driver(sessions).await;
+
+    for (idx, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
+    }
+}
+
+#[tokio::test]
+async fn interactive_signing_with_compact_format() {
+    use synedrion::sessions::Compact;
+
     let num_parties = 3;
     let (signers, verifiers) = make_signers(num_parties);
 
@@ -242,7 +764,7 @@ async fn interactive_signing() {
         .iter()
         .zip(signers.into_iter())
         .map(|(key_share, signer)| {
-            make_interactive_signing_session::<_, Signature, _, _>(
+            make_interactive_signing_session::<_, Signature, _, _, Compact>(
                 &mut OsRng,
                 shared_randomness,
                 signer,
@@ -252,19 +774,2347 @@ async fn interactive_signing() {
             )
             .unwrap()
         })
-        .collect();
+        .collect::<Vec<_>>();
 
-    let signatures = run_nodes(sessions).await;
+    let signatures = run_nodes_with_driver(sessions).await;
 
+    let vkey = key_shares[0].verifying_key();
     for signature in signatures {
-        let (sig, rec_id) = signature.to_backend();
-        let vkey = key_shares[0].verifying_key();
-
-        // Check that the signature can be verified
+        let (sig, _rec_id) = signature.to_backend();
         vkey.verify_prehash(message, &sig).unwrap();
+    }
+}
 
-        // Check that the key can be recovered
-        let recovered_key = VerifyingKey::recover_from_prehash(message, &sig, rec_id).unwrap();
-        assert_eq!(recovered_key, vkey);
+#[test]
+fn keygen_and_aux_with_test_network() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let keys = verifiers.iter().cloned().collect::<Vec<_>>();
+
+    let make_sessions = || {
+        signers
+            .iter()
+            .map(|signer| {
+                make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                    &mut OsRng,
+                    b"1234567890",
+                    signer.clone(),
+                    &verifiers,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Reordered and delayed messages do not disrupt the protocol.
+    let outcomes = TestNetwork::new(make_sessions())
+        .with_random_delivery_order()
+        .with_fault(Fault::Delay(MessageFilter::all().sent_by(keys[0])))
+        .run(&mut OsRng);
+    assert_eq!(outcomes.len(), num_parties);
+    let key_shares = outcomes
+        .into_values()
+        .map(|outcome| outcome.unwrap())
+        .collect::<Vec<_>>();
+    for (idx, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
+    }
+
+    // A lost message makes the receiver give up on the sender.
+    let outcomes = TestNetwork::new(make_sessions())
+        .with_fault(Fault::Drop(
+            MessageFilter::all()
+                .sent_by(keys[0])
+                .sent_to(keys[1])
+                .in_round(2),
+        ))
+        .run(&mut OsRng);
+    let error = outcomes[&keys[1]].as_ref().unwrap_err();
+    assert!(matches!(
+        error,
+        Error::Unresponsive { parties, .. } if parties == &[keys[0]]
+    ));
+    assert_eq!(error.context().unwrap().round, 2);
+
+    // A crashed party is blamed by everyone else, and has no outcome itself.
+    let outcomes = TestNetwork::new(make_sessions())
+        .with_fault(Fault::Crash {
+            party: keys[2],
+            round: 1,
+        })
+        .run(&mut OsRng);
+    assert_eq!(outcomes.len(), num_parties - 1);
+    for outcome in outcomes.values() {
+        assert_eq!(outcome.as_ref().unwrap_err().blamed_parties(), [keys[2]]);
+    }
+}
+
+#[tokio::test]
+async fn key_refresh_with_pregenerated_paillier_keys() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+
+    // The keys can be generated in advance and stored until the refresh is needed.
+    let paillier_keys = (0..num_parties)
+        .map(|_| {
+            let key = PregeneratedPaillierKey::<TestParams>::random(&mut OsRng);
+            bincode::serialize(&key).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let sessions = signers
+        .into_iter()
+        .zip(paillier_keys)
+        .map(|(signer, paillier_key)| {
+            make_key_refresh_session_with_paillier_key::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                bincode::deserialize(&paillier_key).unwrap(),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let changes = run_nodes(sessions).await;
+
+    for (key_share, change) in key_shares.into_vec().into_iter().zip(changes) {
+        let verifying_key = key_share.verifying_key();
+        let new_key_share = key_share.try_update(change).unwrap();
+        assert_eq!(new_key_share.verifying_key(), verifying_key);
+        assert_eq!(new_key_share.epoch(), 1);
+    }
+}
+
+#[tokio::test]
+async fn aux_refresh() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_aux_refresh_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                None,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let changes = run_nodes(sessions).await;
+
+    for (key_share, change) in key_shares.into_vec().into_iter().zip(changes) {
+        let new_key_share = key_share.clone().try_update(change).unwrap();
+        assert_eq!(new_key_share.verifying_key(), key_share.verifying_key());
+        assert_eq!(new_key_share.epoch(), 1);
+        assert!(new_key_share.verify_consistency().is_consistent());
+
+        // The auxiliary data is replaced.
+        assert_ne!(new_key_share.to_bytes(), key_share.to_bytes());
+    }
+}
+
+#[tokio::test]
+async fn share_refresh() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_share_refresh_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let changes = run_nodes(sessions).await;
+
+    for (key_share, change) in key_shares.into_vec().into_iter().zip(changes) {
+        let new_key_share = key_share.clone().try_update(change).unwrap();
+        assert_eq!(new_key_share.verifying_key(), key_share.verifying_key());
+        assert_eq!(new_key_share.epoch(), 1);
+        assert!(new_key_share.verify_consistency().is_consistent());
+    }
+}
+
+#[tokio::test]
+async fn key_import_and_refresh() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let shared_randomness = b"1234567890";
+
+    // The key of an existing single-key wallet, held by one of the parties.
+    let imported_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+    let dealer = *signers[1].verifying_key();
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            let signing_key = (signer.verifying_key() == &dealer).then_some(&imported_key);
+            make_key_import_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+                &dealer,
+                signing_key,
+                Some(imported_key.verifying_key()),
+            )
+            .unwrap()
+        })
+        .collect();
+    let info = ProtocolInfo::key_import::<TestParams>(num_parties, signature_size);
+    let seeds = run_nodes_checking_sizes(sessions, info).await;
+
+    // The dealer knows all the shares, so they have to be refreshed before use.
+    let sessions = signers
+        .into_iter()
+        .map(|signer| {
+            make_key_refresh_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect();
+    let changes = run_nodes(sessions).await;
+
+    for (idx, (seed, change)) in seeds.into_iter().zip(changes).enumerate() {
+        let key_share = KeyShare::new(seed, change).unwrap();
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(&key_share.verifying_key(), imported_key.verifying_key());
+    }
+}
+
+#[tokio::test]
+async fn key_export() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let shared_randomness = b"1234567890";
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_key_export_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                recipient_key.verifying_key(),
+            )
+            .unwrap()
+        })
+        .collect();
+    let info = ProtocolInfo::key_export::<TestParams>(num_parties, signature_size);
+    let exported_keys = run_nodes_checking_sizes(sessions, info).await;
+
+    for exported_key in exported_keys {
+        let secret_key = exported_key.decrypt(&recipient_key).unwrap();
+        assert_eq!(secret_key.verifying_key(), &key_shares[0].verifying_key());
+    }
+}
+
+#[tokio::test]
+async fn session_observer() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let info = ProtocolInfo::key_export::<TestParams>(num_parties, signature_size);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let nodes = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            let session = make_key_export_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                recipient_key.verifying_key(),
+            )
+            .unwrap();
+            (session.verifier(), session)
+        })
+        .collect();
+    let observers = run_custom_nodes(nodes, |tx, rx, session| {
+        let mut transport = ChannelTransport {
+            key: session.verifier(),
+            tx,
+            rx,
+        };
+        let mut observer = ProgressObserver {
+            info: info.clone(),
+            current: None,
+            rounds_finalized: 0,
+        };
+        tokio::spawn(async move {
+            run_session_with_observer(&mut OsRng, &mut transport, session, &mut observer)
+                .await
+                .unwrap();
+            observer
+        })
+    })
+    .await;
+
+    for observer in observers {
+        assert_eq!(observer.rounds_finalized, info.rounds.len());
+    }
+}
+
+#[derive(Default)]
+struct CountingMetrics {
+    messages_sent: usize,
+    bytes_sent: usize,
+    messages_received: usize,
+    bytes_received: usize,
+    rounds_completed: usize,
+    sessions_completed: usize,
+    aborts: Vec<AbortCause>,
+}
+
+impl SessionMetrics for CountingMetrics {
+    fn on_message_sent(&mut self, protocol: &'static str, bytes: usize) {
+        assert_eq!(protocol, "KeyExport");
+        self.messages_sent += 1;
+        self.bytes_sent += bytes;
+    }
+
+    fn on_message_received(&mut self, _protocol: &'static str, bytes: usize) {
+        self.messages_received += 1;
+        self.bytes_received += bytes;
+    }
+
+    fn on_round_completed(&mut self, _protocol: &'static str, _round: u8, _is_echo_round: bool) {
+        self.rounds_completed += 1;
+    }
+
+    fn on_session_completed(&mut self, _protocol: &'static str) {
+        self.sessions_completed += 1;
+    }
+
+    fn on_abort(&mut self, _protocol: &'static str, cause: AbortCause) {
+        self.aborts.push(cause);
+    }
+}
+
+#[tokio::test]
+async fn session_metrics() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let info = ProtocolInfo::key_export::<TestParams>(num_parties, 64);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let nodes = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            let session = make_key_export_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                recipient_key.verifying_key(),
+            )
+            .unwrap();
+            (session.verifier(), session)
+        })
+        .collect();
+    let all_metrics = run_custom_nodes(nodes, |tx, rx, session| {
+        let mut transport = ChannelTransport {
+            key: session.verifier(),
+            tx,
+            rx,
+        };
+        let mut metrics = CountingMetrics::default();
+        tokio::spawn(async move {
+            run_session_with_metrics(&mut OsRng, &mut transport, session, &mut (), &mut metrics)
+                .await
+                .unwrap();
+            metrics
+        })
+    })
+    .await;
+
+    for metrics in all_metrics.iter() {
+        assert_eq!(metrics.messages_sent, num_parties - 1);
+        assert_eq!(metrics.messages_received, num_parties - 1);
+        assert!(metrics.bytes_sent > 0);
+        assert!(metrics.bytes_sent <= (num_parties - 1) * info.max_message_size());
+        assert_eq!(metrics.rounds_completed, info.rounds.len());
+        assert_eq!(metrics.sessions_completed, 1);
+        assert!(metrics.aborts.is_empty());
+    }
+    assert_eq!(
+        all_metrics.iter().map(|m| m.bytes_sent).sum::<usize>(),
+        all_metrics.iter().map(|m| m.bytes_received).sum::<usize>()
+    );
+}
+
+/// An RNG that cannot be moved between threads, like a handle of a hardware device.
+struct DeviceRng {
+    rng: StdRng,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl RngCore for DeviceRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for DeviceRng {}
+
+#[tokio::test]
+async fn rng_factory() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let info = ProtocolInfo::key_export::<TestParams>(num_parties, signature_size);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let nodes = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            let session = make_key_export_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                recipient_key.verifying_key(),
+            )
+            .unwrap();
+            (session.verifier(), session)
+        })
+        .collect();
+    let rngs_made = run_custom_nodes(nodes, |tx, rx, session| {
+        let mut transport = ChannelTransport {
+            key: session.verifier(),
+            tx,
+            rx,
+        };
+        // The task would not be `Send` if the RNG was held across an await.
+        tokio::spawn(async move {
+            let mut rngs_made = 0;
+            let mut rng_factory = || {
+                rngs_made += 1;
+                DeviceRng {
+                    rng: StdRng::from_entropy(),
+                    _not_send: PhantomData,
+                }
+            };
+            run_session_with_rng_factory(&mut rng_factory, &mut transport, session)
+                .await
+                .unwrap();
+            rngs_made
+        })
+    })
+    .await;
+
+    // One RNG to create the messages of each round, and one to finalize it.
+    for rngs_made in rngs_made {
+        assert_eq!(rngs_made, 2 * info.rounds.len());
+    }
+}
+
+#[tokio::test]
+async fn transcript_recorder() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let nodes = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            let session = make_key_export_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                recipient_key.verifying_key(),
+            )
+            .unwrap();
+            (session.verifier(), session)
+        })
+        .collect();
+    let transcripts = run_custom_nodes(nodes, |tx, rx, session| {
+        let mut transport = ChannelTransport {
+            key: session.verifier(),
+            tx,
+            rx,
+        };
+        let mut recorder = TranscriptRecorder::new(&session.verifier()).unwrap();
+        tokio::spawn(async move {
+            run_session_with_recorder(&mut OsRng, &mut transport, session, &mut (), &mut recorder)
+                .await
+                .unwrap();
+            recorder.finish()
+        })
+    })
+    .await;
+
+    for transcript in transcripts {
+        transcript.verify::<Signature>().unwrap();
+
+        let entries = transcript.entries();
+        assert!(entries
+            .iter()
+            .any(|entry| entry.direction() == MessageDirection::Sent));
+        assert!(entries
+            .iter()
+            .any(|entry| entry.direction() == MessageDirection::Received));
+        for entry in entries {
+            assert!(verifiers.contains(entry.counterparty()));
+            assert!(entry.counterparty() != transcript.party());
+        }
+        assert_eq!(
+            &transcript.head().unwrap()[..],
+            entries.last().unwrap().hash()
+        );
+
+        // The transcript survives serialization, but any modification breaks the hash chain.
+        let mut bytes = bincode::serialize(&transcript).unwrap();
+        let restored: MessageTranscript<VerifyingKey> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored.head().unwrap(), transcript.head().unwrap());
+        restored.verify::<Signature>().unwrap();
+
+        *bytes.last_mut().unwrap() ^= 1;
+        let tampered: MessageTranscript<VerifyingKey> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(
+            tampered.verify::<Signature>(),
+            Err(MessageTranscriptError::BrokenChain {
+                index: entries.len() - 1
+            })
+        );
+    }
+}
+
+#[tokio::test]
+async fn threshold_refresh() {
+    let num_parties = 3;
+    let threshold = 2;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let shared_randomness = b"1234567890";
+
+    let signing_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+    let key_shares = ThresholdKeyShare::<TestParams>::new_centralized(
+        &mut OsRng,
+        threshold,
+        num_parties,
+        Some(&signing_key),
+    );
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .map(|(key_share, signer)| {
+            make_threshold_refresh_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+                key_share,
+            )
+            .unwrap()
+        })
+        .collect();
+    let info = ProtocolInfo::threshold_refresh::<TestParams>(num_parties, signature_size);
+    let changes = run_nodes_checking_sizes(sessions, info).await;
+
+    let new_key_shares = key_shares
+        .iter()
+        .cloned()
+        .zip(changes)
+        .map(|(key_share, change)| key_share.update(change))
+        .collect::<Vec<_>>();
+    for new_key_share in new_key_shares.iter() {
+        assert_eq!(&new_key_share.verifying_key(), signing_key.verifying_key());
+        assert_eq!(new_key_share.epoch(), 1);
+    }
+
+    // Any `threshold` of the refreshed shares can still be used together.
+    let quorum = [0, 2];
+    let share_idxs = quorum
+        .iter()
+        .map(|idx| new_key_shares[*idx].index())
+        .collect::<Vec<_>>();
+    let quorum_verifiers = quorum
+        .iter()
+        .map(|idx| *signers[*idx].verifying_key())
+        .collect::<BTreeSet<_>>();
+    let recipient_key = shared_ecdsa::SigningKey::random(&mut OsRng);
+    let sessions = quorum
+        .iter()
+        .map(|idx| {
+            make_key_export_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signers[*idx].clone(),
+                &quorum_verifiers,
+                &new_key_shares[*idx].to_key_share(&share_idxs),
+                recipient_key.verifying_key(),
+            )
+            .unwrap()
+        })
+        .collect();
+    let exported_keys = run_nodes(sessions).await;
+    let secret_key = exported_keys[0].decrypt(&recipient_key).unwrap();
+    assert_eq!(secret_key.to_bytes(), signing_key.to_bytes());
+}
+
+#[tokio::test]
+async fn interactive_signing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures {
+        let (sig, rec_id) = signature.to_backend();
+        let vkey = key_shares[0].verifying_key();
+
+        // Check that the signature can be verified
+        vkey.verify_prehash(message, &sig).unwrap();
+
+        // Check that the key can be recovered
+        let recovered_key =
+            shared_ecdsa::VerifyingKey::recover_from_prehash(message, &sig, rec_id).unwrap();
+        assert_eq!(recovered_key, vkey);
+    }
+}
+
+#[tokio::test]
+async fn signing_certificate() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let vkey = key_shares[0].verifying_key();
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let session_id = *sessions[0].session_id();
+    let signatures = run_nodes(sessions).await;
+
+    // Each party attests to the result of its own session.
+    let attestations = signatures
+        .iter()
+        .map(|signature| {
+            SigningAttestation::new(&session_id, &verifiers, &vkey, message, signature).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        SigningAttestation::new(&session_id, &verifiers, &vkey, &[0; 32], &signatures[0]).is_err()
+    );
+
+    let mut certificate =
+        SigningCertificate::<Signature, _>::new(&mut OsRng, &signers[0], attestations[0].clone())
+            .unwrap();
+    certificate
+        .endorse(&mut OsRng, &signers[1], &attestations[1])
+        .unwrap();
+    assert_eq!(
+        certificate.verify(),
+        Err(SigningCertificateError::MissingEndorsements { missing: 1 })
+    );
+
+    let other =
+        SigningCertificate::<Signature, _>::new(&mut OsRng, &signers[2], attestations[2].clone())
+            .unwrap();
+    certificate.merge(other).unwrap();
+    certificate.verify().unwrap();
+
+    // The certificate can be checked by a third party.
+    let serialized = bincode::serialize(&certificate).unwrap();
+    let certificate: SigningCertificate<Signature, VerifyingKey> =
+        bincode::deserialize(&serialized).unwrap();
+    certificate.verify().unwrap();
+    assert_eq!(certificate.attestation().verifying_key(), Some(vkey));
+    assert_eq!(
+        certificate.attestation().participants(),
+        verifiers.iter().cloned().collect::<Vec<_>>()
+    );
+    assert!(certificate.endorsers().contains(signers[1].verifying_key()));
+
+    // An outsider cannot endorse the attestation.
+    let outsider = SigningKey::random(&mut OsRng);
+    assert!(SigningCertificate::<Signature, _>::new(
+        &mut OsRng,
+        &outsider,
+        attestations[0].clone()
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn interactive_signing_with_precompute() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    // Done before the message is known.
+    let mut precomputes = key_shares
+        .iter()
+        .map(|key_share| PresigningPrecompute::new(&mut OsRng, key_share))
+        .collect::<Vec<_>>();
+
+    // The precomputed values must be made for the signer's key share.
+    assert!(
+        make_interactive_signing_session_with_precompute::<_, Signature, _, _, Bincode>(
+            &mut OsRng,
+            shared_randomness,
+            signers[0].clone(),
+            &verifiers,
+            PresigningPrecompute::new(&mut OsRng, &key_shares[1]),
+            message,
+        )
+        .is_err()
+    );
+
+    // The parties without precomputed values are compatible with the ones having them.
+    let last_signer = signers[num_parties - 1].clone();
+    let mut sessions = signers[..num_parties - 1]
+        .iter()
+        .zip(precomputes.drain(..num_parties - 1))
+        .map(|(signer, precompute)| {
+            make_interactive_signing_session_with_precompute::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+                precompute,
+                message,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    sessions.push(
+        make_interactive_signing_session::<_, Signature, _, _, _>(
+            &mut OsRng,
+            shared_randomness,
+            last_signer,
+            &verifiers,
+            &key_shares[num_parties - 1],
+            message,
+        )
+        .unwrap(),
+    );
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures {
+        let (sig, _rec_id) = signature.to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .unwrap();
+    }
+
+    // Same for the standalone Presigning protocol.
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_presigning_session_with_precompute::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                PresigningPrecompute::new(&mut OsRng, key_share),
+                PresigningMode::IdentifiableAbort,
+            )
+            .unwrap()
+        })
+        .collect();
+    let presigning_datas = run_nodes(sessions).await;
+    assert_eq!(presigning_datas.len(), num_parties);
+}
+
+#[cfg(feature = "p256")]
+#[tokio::test]
+async fn p256_keygen_and_signing() {
+    use shared_ecdsa::signature::Verifier;
+
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let shared_randomness = b"1234567890";
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect();
+    let key_shares = run_nodes(sessions).await;
+
+    let message = b"message to sign";
+    let prehash: [u8; 32] = Sha256::digest(message).into();
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                &prehash,
+            )
+            .unwrap()
+        })
+        .collect();
+    let signatures = run_nodes(sessions).await;
+
+    // A regular P-256 ECDSA verifier (hashing the message with SHA-256) accepts the signatures.
+    let vkey: shared_ecdsa::VerifyingKey = key_shares[0].verifying_key();
+    for signature in signatures {
+        let (sig, _rec_id) = signature.to_backend();
+        vkey.verify(message, &sig).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn interactive_signing_with_tweak() {
+    use shared_curve::elliptic_curve::Field;
+
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+    let tweak = shared_curve::Scalar::random(&mut OsRng);
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session_with_tweak::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                &tweak,
+                message,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    // The signatures verify under the tweaked key, and the key shares are unchanged.
+    let tweaked_point =
+        shared_curve::ProjectivePoint::from(*key_shares[0].verifying_key().as_affine())
+            + shared_curve::ProjectivePoint::GENERATOR * tweak;
+    let tweaked_key = shared_ecdsa::VerifyingKey::from_affine(tweaked_point.to_affine()).unwrap();
+    for signature in signatures {
+        let (sig, rec_id) = signature.to_backend();
+        tweaked_key.verify_prehash(message, &sig).unwrap();
+        let recovered_key =
+            shared_ecdsa::VerifyingKey::recover_from_prehash(message, &sig, rec_id).unwrap();
+        assert_eq!(recovered_key, tweaked_key);
+        assert!(key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .is_err());
+    }
+}
+
+#[tokio::test]
+async fn adaptor_signing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+    let adaptor_secret = shared_ecdsa::SigningKey::random(&mut OsRng);
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_adaptor_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                message,
+                adaptor_secret.verifying_key(),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let info = ProtocolInfo::adaptor_signing::<TestParams>(num_parties, signature_size);
+    let adaptor_signatures = run_nodes_checking_sizes(sessions, info).await;
+
+    let vkey = key_shares[0].verifying_key();
+    for adaptor_signature in adaptor_signatures {
+        assert_eq!(
+            &adaptor_signature.adaptor_point(),
+            adaptor_secret.verifying_key()
+        );
+        assert!(adaptor_signature.verify(&vkey, message));
+
+        let signature = adaptor_signature
+            .complete(&vkey, message, &adaptor_secret)
+            .unwrap();
+        let (sig, _rec_id) = signature.to_backend();
+        vkey.verify_prehash(message, &sig).unwrap();
+
+        assert_eq!(
+            adaptor_signature
+                .recover_adaptor_secret(&signature)
+                .unwrap(),
+            adaptor_secret
+        );
+    }
+}
+
+#[tokio::test]
+async fn encrypted_direct_messages() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let make_session = |key_share: &KeyShare<TestParams>, signer: &SigningKey| {
+        make_interactive_signing_session::<_, Signature, _, _, Bincode>(
+            &mut OsRng,
+            shared_randomness,
+            signer.clone(),
+            &verifiers,
+            key_share,
+            message,
+        )
+        .unwrap()
+    };
+
+    // The encryption keys are separate from the signing keys.
+    let decryption_keys = (0..num_parties)
+        .map(|_| shared_ecdsa::SigningKey::random(&mut OsRng))
+        .collect::<Vec<_>>();
+    let encryption_keys = signers
+        .iter()
+        .zip(decryption_keys.iter())
+        .map(|(signer, key)| (*signer.verifying_key(), *key.verifying_key()))
+        .collect::<BTreeMap<_, _>>();
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .zip(decryption_keys)
+        .map(|((key_share, signer), key)| {
+            make_session(key_share, signer)
+                .with_direct_message_encryption(DirectMessageEncryption::new(
+                    key,
+                    encryption_keys.clone(),
+                ))
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // The direct part of the first round is encrypted.
+    let (message_to_1, _artifact) = sessions[0]
+        .make_message(&mut OsRng, &sessions[1].verifier())
+        .unwrap();
+    let CombinedMessage::Both { broadcast, direct } = &message_to_1 else {
+        panic!("Expected a broadcast and a direct message");
+    };
+    assert!(!broadcast.is_encrypted());
+    assert!(direct.is_encrypted());
+
+    // A party that does not encrypt its direct messages cannot take part in the session.
+    let plain_session = make_session(&key_shares[0], &signers[0]);
+    let (plain_message, _artifact) = plain_session
+        .make_message(&mut OsRng, &sessions[1].verifier())
+        .unwrap();
+    let mut accum = sessions[1].make_accumulator();
+    let error = sessions[1]
+        .preprocess_message(&mut accum, &plain_session.verifier(), plain_message)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            error: RemoteErrorEnum::UnexpectedEncryption,
+            ..
+        })
+    ));
+
+    // The encrypted message cannot be processed by a party that does not decrypt it.
+    let mut accum = plain_session.make_accumulator();
+    let error = make_session(&key_shares[1], &signers[1])
+        .preprocess_message(&mut accum, &sessions[0].verifier(), message_to_1)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            error: RemoteErrorEnum::UnexpectedEncryption,
+            ..
+        })
+    ));
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures {
+        let (sig, _rec_id) = signature.to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn interactive_signing_with_digest() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"a message of an arbitrary length";
+
+    let interactive_signers = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            InteractiveSigner::new(key_share.clone(), signer, verifiers.clone()).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // Hashing the message with the digest of the scheme parameters gives the same prehash.
+    let sessions = interactive_signers
+        .iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            assert_eq!(signer.verifying_key(), key_shares[0].verifying_key());
+            if idx == 0 {
+                signer
+                    .sign_message_session(&mut OsRng, shared_randomness, message)
+                    .unwrap()
+            } else {
+                signer
+                    .sign_digest_session(
+                        &mut OsRng,
+                        shared_randomness,
+                        Sha256::new_with_prefix(message),
+                    )
+                    .unwrap()
+            }
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    let prehash = Sha256::digest(message);
+    for signature in signatures {
+        let (sig, _rec_id) = signature.to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(&prehash, &sig)
+            .unwrap();
+    }
+
+    // The prehash must be of the correct size.
+    assert!(interactive_signers[0]
+        .sign_prehash_session::<Signature, Bincode>(&mut OsRng, shared_randomness, &prehash[..31])
+        .is_err());
+}
+
+#[test]
+fn receive_and_maybe_finalize() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+    let num_rounds = ProtocolInfo::interactive_signing::<TestParams>(num_parties, 64)
+        .rounds
+        .len();
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(sessions.iter().all(|session| !session.is_last_round()));
+
+    // `TestNetwork` drives each party by the messages it receives
+    // with `receive_and_maybe_finalize`, finalizing the rounds when they are done.
+    let (outcomes, sent) = TestNetwork::new(sessions).run_and_record(&mut OsRng);
+
+    let rounds = sent
+        .iter()
+        .map(|sent| (sent.round, sent.is_echo_round))
+        .collect::<BTreeSet<_>>();
+    assert_eq!(rounds.len(), num_rounds);
+    assert_eq!(outcomes.len(), num_parties);
+    for outcome in outcomes.into_values() {
+        let (sig, _rec_id) = outcome.unwrap().to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .unwrap();
+    }
+}
+
+#[test]
+fn session_manager() {
+    let num_parties = 3;
+    let num_sessions = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let prehash = |idx: usize| Sha256::digest([idx as u8]);
+
+    let limits = SessionLimits {
+        max_sessions: num_sessions,
+        ..Default::default()
+    };
+
+    let mut managers = Vec::new();
+    let mut messages = Vec::new();
+    let mut session_ids = Vec::new();
+    for (from, (key_share, signer)) in key_shares.iter().zip(signers).enumerate() {
+        let signer = InteractiveSigner::new(key_share.clone(), signer, verifiers.clone()).unwrap();
+        let make_session = |idx: usize| {
+            signer
+                .sign_prehash_session::<Signature, Bincode>(&mut OsRng, &[idx as u8], &prehash(idx))
+                .unwrap()
+        };
+
+        let mut manager = SessionManager::new(limits);
+        let mut ids = Vec::new();
+        for idx in 0..num_sessions {
+            let (session_id, outgoing) =
+                manager.add_session(&mut OsRng, make_session(idx)).unwrap();
+            messages.extend(
+                outgoing
+                    .into_iter()
+                    .map(|(to, message)| (from, to, message)),
+            );
+            ids.push(session_id);
+        }
+
+        // The sessions are identified by their parameters, and their number is limited.
+        assert_eq!(
+            manager.add_session(&mut OsRng, make_session(0)).err(),
+            Some(SessionManagerError::DuplicateSession(ids[0]))
+        );
+        assert_eq!(
+            manager
+                .add_session(&mut OsRng, make_session(num_sessions))
+                .err(),
+            Some(SessionManagerError::TooManySessions)
+        );
+
+        managers.push(manager);
+        session_ids = ids;
+    }
+
+    // Deliver the messages of all the sessions in a random order.
+    let keys = verifiers.iter().cloned().collect::<Vec<_>>();
+    let replayed = messages[0].clone();
+    while !messages.is_empty() {
+        let (from, to, message) = messages.swap_remove(OsRng.gen_range(0..messages.len()));
+        let to = keys.iter().position(|key| key == &to).unwrap();
+        let outgoing = managers[to]
+            .receive_message(&mut OsRng, &keys[from], message)
+            .unwrap();
+        messages.extend(
+            outgoing
+                .into_iter()
+                .map(|(dest, message)| (to, dest, message)),
+        );
+    }
+
+    for manager in managers.iter_mut() {
+        for session_id in session_ids.iter() {
+            assert_eq!(manager.status(session_id), Some(SessionStatus::Finished));
+        }
+        assert_eq!(
+            manager
+                .receive_message(&mut OsRng, &keys[replayed.0], replayed.2.clone())
+                .err(),
+            Some(SessionManagerError::Inactive(session_ids[0]))
+        );
+
+        let outcomes = manager.collect_garbage();
+        assert!(manager.is_empty());
+        assert_eq!(outcomes.len(), num_sessions);
+        for (session_id, outcome) in outcomes {
+            let idx = session_ids.iter().position(|id| id == &session_id).unwrap();
+            let (sig, _rec_id) = outcome.unwrap().to_backend();
+            key_shares[0]
+                .verifying_key()
+                .verify_prehash(&prehash(idx), &sig)
+                .unwrap();
+        }
+    }
+
+    // The messages of the collected sessions are not routed anymore.
+    let (from, to, message) = replayed;
+    let to = keys.iter().position(|key| key == &to).unwrap();
+    assert_eq!(
+        managers[to]
+            .receive_message(&mut OsRng, &keys[from], message)
+            .err(),
+        Some(SessionManagerError::UnknownSession(session_ids[0]))
+    );
+}
+
+#[tokio::test]
+async fn interactive_signing_with_a_smaller_modulus() {
+    let num_parties = 2;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    // The bounds of this preset differ from those of `ProductionParams`,
+    // so check that the values used in the protocol still fit in the modulus.
+    let key_shares = KeyShare::<Params1536>::new_centralized(&mut OsRng, num_parties, None);
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers)
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                b"1234567890",
+                signer,
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    for signature in run_nodes(sessions).await {
+        let (sig, _rec_id) = signature.to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn schnorr_signing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_schnorr_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                message,
+                Some(TaprootTweak::KeyPathOnly),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures.iter() {
+        assert_eq!(signature, &signatures[0]);
+        assert!(signature.verify(message));
+    }
+}
+
+#[cfg(feature = "ed25519")]
+#[tokio::test]
+async fn ed25519_signing() {
+    let (threshold, num_parties) = (2, 3);
+    let threshold_key_shares =
+        Ed25519ThresholdKeyShare::new_centralized(&mut OsRng, threshold, num_parties, None);
+
+    // The holders of the first and the last shares sign.
+    let (signers, verifiers) = make_signers(threshold);
+    let share_idxs = [ShareIdx::new(1), ShareIdx::new(3)];
+    let key_shares = [&threshold_key_shares[0], &threshold_key_shares[2]]
+        .map(|key_share| key_share.to_key_share(&share_idxs));
+
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let shared_randomness = b"1234567890";
+    let message = b"a message of an arbitrary length";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_ed25519_signing_session::<Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let info = ProtocolInfo::ed25519_signing(threshold, signature_size);
+    let signatures = run_nodes_checking_sizes(sessions, info).await;
+
+    for signature in signatures.iter() {
+        assert_eq!(signature, &signatures[0]);
+        assert_eq!(
+            signature.verifying_key_bytes(),
+            threshold_key_shares[0].verifying_key_bytes()
+        );
+        assert!(signature.verify(message));
+    }
+}
+
+#[tokio::test]
+async fn presigning_and_signing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .map(|(key_share, signer)| {
+            make_presigning_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+                key_share,
+                PresigningMode::Fast,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let presigning_datas = run_nodes(sessions).await;
+
+    // The presigning data can be stored until a message to sign arrives.
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .zip(presigning_datas.into_iter())
+        .map(|((key_share, signer), presigning_data)| {
+            let session = make_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                presigning_data,
+                message,
+            )
+            .unwrap();
+            // Signing consists of a single round.
+            assert!(session.is_last_round());
+            session
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures {
+        let (sig, rec_id) = signature.to_backend();
+        let vkey = key_shares[0].verifying_key();
+
+        // Check that the signature can be verified
+        vkey.verify_prehash(message, &sig).unwrap();
+
+        // Check that the key can be recovered
+        let recovered_key =
+            shared_ecdsa::VerifyingKey::recover_from_prehash(message, &sig, rec_id).unwrap();
+        assert_eq!(recovered_key, vkey);
+    }
+}
+
+#[tokio::test]
+async fn presigning_with_identifiable_abort() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .map(|(key_share, signer)| {
+            make_presigning_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+                key_share,
+                PresigningMode::IdentifiableAbort,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let info = ProtocolInfo::presigning::<TestParams>(
+        num_parties,
+        signature_size,
+        PresigningMode::IdentifiableAbort,
+    );
+    // Four rounds, the first and the third ones followed by echo rounds.
+    assert_eq!(info.rounds.len(), 6);
+    let presigning_datas = run_nodes_checking_sizes(sessions, info).await;
+
+    // The presigning data is the same as the one produced by the fast protocol.
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .zip(presigning_datas.into_iter())
+        .map(|((key_share, signer), presigning_data)| {
+            make_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                presigning_data,
+                message,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let signatures = run_nodes(sessions).await;
+
+    for signature in signatures {
+        let (sig, _rec_id) = signature.to_backend();
+        key_shares[0]
+            .verifying_key()
+            .verify_prehash(message, &sig)
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn batch_interactive_signing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let shared_randomness = b"1234567890";
+    let messages = [
+        *b"abcdefghijklmnopqrstuvwxyz123456",
+        *b"123456abcdefghijklmnopqrstuvwxyz",
+    ];
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_batch_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                &messages,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let signature_batches = run_nodes(sessions).await;
+
+    let vkey = key_shares[0].verifying_key();
+    for signatures in signature_batches {
+        assert_eq!(signatures.len(), messages.len());
+        for (signature, message) in signatures.into_iter().zip(messages.iter()) {
+            let (sig, _rec_id) = signature.to_backend();
+            vkey.verify_prehash(message, &sig).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn message_size_bounds() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let signature: Signature = signers[0].sign(b"");
+    let signature_size = bincode::serialize(&signature).unwrap().len();
+    let shared_randomness = b"1234567890";
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect();
+    let info = ProtocolInfo::key_gen::<TestParams>(num_parties, signature_size);
+    let key_shares = run_nodes_checking_sizes(sessions, info).await;
+
+    let messages = [
+        *b"abcdefghijklmnopqrstuvwxyz123456",
+        *b"123456abcdefghijklmnopqrstuvwxyz",
+    ];
+    let sessions = key_shares
+        .iter()
+        .zip(signers.into_iter())
+        .map(|(key_share, signer)| {
+            make_batch_interactive_signing_session::<_, Signature, _, _, _>(
+                &mut OsRng,
+                shared_randomness,
+                signer,
+                &verifiers,
+                key_share,
+                &messages,
+            )
+            .unwrap()
+        })
+        .collect();
+    let info = ProtocolInfo::batch_interactive_signing::<TestParams>(
+        num_parties,
+        signature_size,
+        messages.len(),
+    );
+    run_nodes_checking_sizes(sessions, info).await;
+}
+
+#[test]
+fn message_fragments() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let sender = sessions[0].verifier();
+    let receiver = &sessions[1];
+    let (message, _artifact) = sessions[0]
+        .make_message(&mut OsRng, &receiver.verifier())
+        .unwrap();
+    let message_bytes = bincode::serialize(&message).unwrap();
+
+    let mut fragments = sessions[0]
+        .make_fragments(&mut OsRng, &message, 32)
+        .unwrap();
+    assert_eq!(fragments.len(), message_bytes.len().div_ceil(32));
+
+    // A fragment with modified contents is rejected.
+    let mut tampered = bincode::serialize(&fragments[0]).unwrap();
+    // The first byte of the data, following the session ID, the message hash,
+    // the fragment index and count, and the data length.
+    tampered[96] ^= 1;
+    let tampered: MessageFragment<Signature> = bincode::deserialize(&tampered).unwrap();
+    let mut reassembler = receiver.make_reassembler();
+    assert!(matches!(
+        reassembler.add_fragment(&sender, tampered),
+        Err(FragmentError::InvalidSignature(_))
+    ));
+
+    // The fragments can arrive in any order.
+    fragments.reverse();
+    let last = fragments.pop().unwrap();
+    for fragment in fragments {
+        assert!(reassembler
+            .add_fragment(&sender, fragment)
+            .unwrap()
+            .is_none());
+    }
+    assert_eq!(reassembler.pending_messages(), 1);
+    let reassembled = reassembler.add_fragment(&sender, last).unwrap().unwrap();
+    assert_eq!(reassembler.pending_messages(), 0);
+    assert_eq!(bincode::serialize(&reassembled).unwrap(), message_bytes);
+
+    let mut accum = receiver.make_accumulator();
+    assert!(receiver
+        .preprocess_message(&mut accum, &sender, reassembled)
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn resending_messages() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let sender = &sessions[0];
+    let receiver = &sessions[1];
+
+    let mut sender_accum = sender.make_accumulator();
+    let state = sender
+        .make_sending_state(&mut OsRng, &mut sender_accum)
+        .unwrap();
+    assert_eq!(state.messages().len(), num_parties - 1);
+
+    // The artifacts were added to the accumulator, so they cannot be added again.
+    let (_message, artifact) = sender
+        .make_message(&mut OsRng, &receiver.verifier())
+        .unwrap();
+    assert!(sender_accum.add_artifact(artifact).is_err());
+
+    // The same message is returned each time, so it can be retransmitted.
+    let serialize = |message: &CombinedMessage<Signature>| bincode::serialize(message).unwrap();
+    let message = state.message(&receiver.verifier()).unwrap();
+    assert_eq!(
+        serialize(message),
+        serialize(state.message(&receiver.verifier()).unwrap())
+    );
+    assert!(state.message(&sender.verifier()).is_none());
+
+    // The receiver processes the first copy and ignores the retransmitted one.
+    let mut accum = receiver.make_accumulator();
+    let preprocessed = receiver
+        .preprocess_message(&mut accum, &sender.verifier(), message.clone())
+        .unwrap()
+        .unwrap();
+    let processed = receiver.process_message(preprocessed).unwrap();
+    accum.add_processed_message(processed).unwrap().unwrap();
+    assert!(receiver
+        .preprocess_message(&mut accum, &sender.verifier(), message.clone())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn split_message_parts() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+    let message = b"abcdefghijklmnopqrstuvwxyz123456";
+
+    let sessions = key_shares
+        .iter()
+        .zip(signers.iter())
+        .map(|(key_share, signer)| {
+            make_interactive_signing_session::<_, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+                key_share,
+                message,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let sender = &sessions[0];
+    let mut sender_accum = sender.make_accumulator();
+    let state = sender
+        .make_sending_state(&mut OsRng, &mut sender_accum)
+        .unwrap();
+
+    // The first round has a broadcast part, sent once to all the destinations,
+    // followed by a direct part for each destination.
+    let parts = state.parts().collect::<Vec<_>>();
+    assert_eq!(parts.len(), num_parties);
+    let OutgoingPart::Broadcast {
+        destinations,
+        message: broadcast,
+    } = parts[0]
+    else {
+        panic!("Expected the broadcast part first");
+    };
+    assert_eq!(
+        destinations.iter().collect::<BTreeSet<_>>(),
+        verifiers
+            .iter()
+            .filter(|verifier| **verifier != sender.verifier())
+            .collect()
+    );
+    assert_eq!(state.broadcast(), Some(broadcast));
+    for (part, destination) in parts[1..].iter().zip(destinations) {
+        assert!(matches!(
+            part,
+            OutgoingPart::Direct { destination: to, .. } if *to == destination
+        ));
+    }
+    for (_destination, message) in state.messages() {
+        let CombinedMessage::Both {
+            broadcast: message_broadcast,
+            ..
+        } = message
+        else {
+            panic!("Expected a broadcast and a direct message");
+        };
+        assert_eq!(message_broadcast, broadcast);
+    }
+
+    // The receiver puts the parts delivered separately back together.
+    let receiver = &sessions[1];
+    let (_, direct) = state
+        .direct_messages()
+        .find(|(destination, _)| *destination == &receiver.verifier())
+        .unwrap();
+    let combined =
+        CombinedMessage::from_parts(Some(broadcast.clone()), Some(direct.clone())).unwrap();
+    let mut accum = receiver.make_accumulator();
+    let preprocessed = receiver
+        .preprocess_message(&mut accum, &sender.verifier(), combined)
+        .unwrap()
+        .unwrap();
+    let processed = receiver.process_message(preprocessed).unwrap();
+    accum.add_processed_message(processed).unwrap().unwrap();
+
+    // A message with a missing part is rejected.
+    let mut accum = receiver.make_accumulator();
+    let partial = CombinedMessage::from_parts(Some(broadcast.clone()), None).unwrap();
+    let preprocessed = receiver
+        .preprocess_message(&mut accum, &sender.verifier(), partial)
+        .unwrap()
+        .unwrap();
+    assert!(receiver.process_message(preprocessed).is_err());
+    assert!(CombinedMessage::<Signature>::from_parts(None, None).is_none());
+}
+
+#[test]
+fn cancellation() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let mut sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let sender = sessions.remove(0);
+    let sender_verifier = sender.verifier();
+    let state = sender.cancel(&mut OsRng, "rejected by policy").unwrap();
+
+    // The same notification is sent to all the other parties.
+    assert_eq!(state.messages().len(), num_parties - 1);
+    assert_eq!(state.direct_messages().count(), 0);
+    let notification = state.broadcast().unwrap();
+
+    for receiver in sessions.iter() {
+        let message = state.message(&receiver.verifier()).unwrap();
+        let mut accum = receiver.make_accumulator();
+        let error = receiver
+            .preprocess_message(&mut accum, &sender_verifier, message.clone())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            Error::Remote(RemoteError {
+                party,
+                error: RemoteErrorEnum::Cancelled(reason),
+                ..
+            }) if party == sender_verifier && reason == "rejected by policy"
+        ));
+    }
+
+    // A notification cannot be attributed to another party.
+    let receiver = &sessions[0];
+    let mut accum = receiver.make_accumulator();
+    let error = receiver
+        .preprocess_message(
+            &mut accum,
+            &sessions[1].verifier(),
+            CombinedMessage::One(notification.clone()),
+        )
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            error: RemoteErrorEnum::UnexpectedSender,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn parallel_message_creation() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let session = make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+        &mut OsRng,
+        b"1234567890",
+        signers[0].clone(),
+        &verifiers,
+    )
+    .unwrap();
+
+    // The session can be shared between threads to create the messages in parallel,
+    // with the artifacts collected in the accumulator afterwards.
+    let mut accum = session.make_accumulator();
+    let destinations = session.message_destinations();
+    let results = std::thread::scope(|scope| {
+        let handles = destinations
+            .iter()
+            .map(|destination| {
+                let session = &session;
+                scope.spawn(move || session.make_message(&mut OsRng, destination).unwrap())
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(results.len(), num_parties - 1);
+    for (_message, artifact) in results {
+        accum.add_artifact(artifact).unwrap();
+    }
+}
+
+#[test]
+fn batch_message_processing() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let sessions = signers
+        .iter()
+        .map(|signer| {
+            make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                &mut OsRng,
+                b"1234567890",
+                signer.clone(),
+                &verifiers,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let receiver = &sessions[0];
+    let mut accum = receiver.make_accumulator();
+    receiver.make_sending_state(&mut OsRng, &mut accum).unwrap();
+
+    let preprocessed = sessions[1..]
+        .iter()
+        .map(|sender| {
+            let (message, _artifact) = sender
+                .make_message(&mut OsRng, &receiver.verifier())
+                .unwrap();
+            receiver
+                .preprocess_message(&mut accum, &sender.verifier(), message)
+                .unwrap()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // The messages are verified at once (in parallel with the `rayon` feature).
+    let results = receiver.process_messages(preprocessed);
+    assert_eq!(results.len(), num_parties - 1);
+    for processed in results {
+        accum
+            .add_processed_message(processed.unwrap())
+            .unwrap()
+            .unwrap();
+    }
+    assert!(receiver.can_finalize(&accum).unwrap());
+}
+
+#[tokio::test]
+async fn deterministic_sessions() {
+    let num_parties = 3;
+    let mut idx = 0u8;
+    let (signers, verifiers) = testnet::make_signers(num_parties, || {
+        idx += 1;
+        SigningKey::from_slice(&[idx; 32]).unwrap()
+    });
+
+    let make_sessions = || {
+        signers
+            .iter()
+            .enumerate()
+            .map(|(idx, signer)| {
+                let mut rng = StdRng::from_seed([idx as u8; 32]);
+                make_key_gen_session::<TestParams, Signature, _, _, _>(
+                    &mut rng,
+                    b"1234567890",
+                    signer.clone(),
+                    &verifiers,
+                )
+                .unwrap()
+                .deterministic()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The messages do not depend on the order of their creation
+    // or on the RNG passed to `make_message()`.
+    let sessions = make_sessions();
+    let destinations = sessions[0].message_destinations();
+    let messages = destinations
+        .iter()
+        .map(|destination| sessions[0].make_message(&mut OsRng, destination).unwrap().0)
+        .collect::<Vec<_>>();
+    let sessions = make_sessions();
+    let reversed = destinations
+        .iter()
+        .rev()
+        .map(|destination| sessions[0].make_message(&mut OsRng, destination).unwrap().0)
+        .collect::<Vec<_>>();
+    let serialize = |message: &CombinedMessage<Signature>| bincode::serialize(message).unwrap();
+    for (message, reversed) in messages.iter().zip(reversed.iter().rev()) {
+        assert_eq!(serialize(message), serialize(reversed));
+    }
+
+    // Creating all the messages at once (in parallel with the `rayon` feature)
+    // gives the same messages as well.
+    let sessions = make_sessions();
+    let mut accum = sessions[0].make_accumulator();
+    let state = sessions[0]
+        .make_sending_state(&mut OsRng, &mut accum)
+        .unwrap();
+    for ((_destination, message), expected) in state.messages().iter().zip(messages.iter()) {
+        assert_eq!(serialize(message), serialize(expected));
+    }
+
+    // The messages are delivered in a random order, and the parties use `OsRng`,
+    // but the results are the same.
+    let key_shares1 = run_nodes(make_sessions()).await;
+    let key_shares2 = run_nodes(make_sessions()).await;
+    for (share1, share2) in key_shares1.iter().zip(key_shares2.iter()) {
+        assert_eq!(share1.to_bytes(), share2.to_bytes());
+    }
+}
+
+#[test]
+fn error_provenance() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let make_session = |signer: &SigningKey, shared_randomness: &[u8]| {
+        make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+            &mut OsRng,
+            shared_randomness,
+            signer.clone(),
+            &verifiers,
+        )
+        .unwrap()
+    };
+
+    // The parties were given different shared randomness, so the session IDs do not match.
+    let sender = make_session(&signers[0], b"1234567890");
+    let receiver = make_session(&signers[1], b"0987654321");
+
+    let (message, _artifact) = sender
+        .make_message(&mut OsRng, &receiver.verifier())
+        .unwrap();
+    let mut accum = receiver.make_accumulator();
+    let error = receiver
+        .preprocess_message(&mut accum, &sender.verifier(), message)
+        .err()
+        .unwrap();
+
+    let expected_context = ErrorContext {
+        protocol: "KeyGen",
+        round: 1,
+        is_echo_round: false,
+    };
+    assert_eq!(error.context(), Some(&expected_context));
+    assert_eq!(error.party(), Some(&sender.verifier()));
+    assert_eq!(error.blamed_parties(), [sender.verifier()]);
+    assert!(matches!(
+        error,
+        Error::Remote(RemoteError {
+            error: RemoteErrorEnum::UnexpectedSessionId,
+            ..
+        })
+    ));
+    assert!(error.to_string().contains("KeyGen, round 1"));
+}
+
+#[test]
+fn replayed_messages_rejected() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+    let shared_randomness = b"1234567890";
+
+    let sender = make_key_gen_session::<TestParams, Signature, _, _, _>(
+        &mut OsRng,
+        shared_randomness,
+        signers[0].clone(),
+        &verifiers,
+    )
+    .unwrap();
+
+    fn assert_rejected<Res1: ProtocolResult, Res2: ProtocolResult>(
+        sender: &Session<Res1, Signature, SigningKey, VerifyingKey>,
+        receiver: Session<Res2, Signature, SigningKey, VerifyingKey>,
+    ) {
+        let (message, _artifact) = sender
+            .make_message(&mut OsRng, &receiver.verifier())
+            .unwrap();
+        let mut accum = receiver.make_accumulator();
+        let error = receiver
+            .preprocess_message(&mut accum, &sender.verifier(), message)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            Error::Remote(RemoteError {
+                error: RemoteErrorEnum::UnexpectedSessionId,
+                ..
+            })
+        ));
+    }
+
+    // The same shared randomness is reused in a session of the same protocol
+    // with a different set of participants...
+    let mut other_verifiers = verifiers.clone();
+    other_verifiers.remove(signers[2].verifying_key());
+    other_verifiers.insert(*SigningKey::random(&mut OsRng).verifying_key());
+    assert_rejected(
+        &sender,
+        make_key_gen_session::<TestParams, Signature, _, _, _>(
+            &mut OsRng,
+            shared_randomness,
+            signers[1].clone(),
+            &other_verifiers,
+        )
+        .unwrap(),
+    );
+
+    // ...and in a session of a different protocol with the same participants.
+    assert_rejected(
+        &sender,
+        make_key_refresh_session::<TestParams, Signature, _, _, _>(
+            &mut OsRng,
+            shared_randomness,
+            signers[1].clone(),
+            &verifiers,
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn finalize_with_absent_parties() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let make_session = |signer: &SigningKey| {
+        make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+            &mut OsRng,
+            b"1234567890",
+            signer.clone(),
+            &verifiers,
+        )
+        .unwrap()
+    };
+
+    let sender = make_session(&signers[0]);
+    let absent = signers[2].verifying_key();
+
+    // The receiver has created its own messages, but only received the message from `sender`.
+    let make_receiver = || {
+        let receiver = make_session(&signers[1]);
+        let mut accum = receiver.make_accumulator();
+        for destination in receiver.message_destinations() {
+            let (_message, artifact) = receiver.make_message(&mut OsRng, &destination).unwrap();
+            accum.add_artifact(artifact).unwrap();
+        }
+
+        let (message, _artifact) = sender
+            .make_message(&mut OsRng, &receiver.verifier())
+            .unwrap();
+        let preprocessed = receiver
+            .preprocess_message(&mut accum, &sender.verifier(), message)
+            .unwrap()
+            .unwrap();
+        let processed = receiver.process_message(preprocessed).unwrap();
+        accum.add_processed_message(processed).unwrap().unwrap();
+        (receiver, accum)
+    };
+
+    let (receiver, accum) = make_receiver();
+    assert_eq!(receiver.missing_messages(&accum).unwrap(), [*absent]);
+
+    // Only the parties whose messages are missing can be declared absent.
+    let result = receiver.finalize_with_absent(&mut OsRng, accum, &[sender.verifier()]);
+    assert!(matches!(result, Err(Error::Local(_))));
+
+    // The round requires messages from all the parties, so it cannot be finalized.
+    let (receiver, accum) = make_receiver();
+    let error = receiver
+        .finalize_with_absent(&mut OsRng, accum, &[*absent])
+        .err()
+        .unwrap();
+    assert!(matches!(
+        &error,
+        Error::Unresponsive { parties, .. } if parties == &[*absent]
+    ));
+    assert_eq!(error.context().unwrap().round, 1);
+    assert_eq!(error.blamed_parties(), [*absent]);
+}
+
+#[test]
+fn mismatched_key_share() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let key_shares = KeyShare::<TestParams>::new_centralized(&mut OsRng, num_parties, None);
+
+    // The key share of party 1 cannot be used by the signer at position 0.
+    let result = make_presigning_session::<_, Signature, _, _, Bincode>(
+        &mut OsRng,
+        b"1234567890",
+        signers[0].clone(),
+        &verifiers,
+        &key_shares[1],
+        PresigningMode::Fast,
+    );
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn keygen_and_aux_with_restart() {
+    let num_parties = 3;
+    let (signers, verifiers) = make_signers(num_parties);
+
+    let shared_randomness = b"1234567890";
+
+    // The first node will crash after receiving 3 messages (that is, in the second round)
+    let nodes = signers
+        .into_iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            let crash = if idx == 0 { Some(3) } else { None };
+            (*signer.verifying_key(), (idx as u64, signer, crash))
+        })
+        .collect();
+
+    let key_shares = run_custom_nodes(nodes, |tx, rx, (seed, signer, crash)| {
+        let verifiers = verifiers.clone();
+        tokio::spawn(async move {
+            let key = *signer.verifying_key();
+            let make_session = |rng: &mut StdRng| {
+                make_key_gen_session::<TestParams, Signature, _, _, Bincode>(
+                    rng,
+                    shared_randomness,
+                    signer.clone(),
+                    &verifiers,
+                )
+                .unwrap()
+            };
+
+            // In production usage, the log will be serialized and saved to a disk.
+            let saved_log = Mutex::new(MessageLog::new());
+            let save_log = |log: &MessageLog<Signature, VerifyingKey>| {
+                *saved_log.lock().unwrap() = log.clone();
+            };
+
+            let mut rng = StdRng::seed_from_u64(seed);
+            let session = make_session(&mut rng);
+            let mut transport = ResumableTransport::new(
+                CrashingTransport {
+                    inner: ChannelTransport { key, tx, rx },
+                    receives_before_crash: crash,
+                },
+                save_log,
+            );
+
+            match run_session(&mut rng, &mut transport, session).await {
+                Ok(key_share) => key_share,
+                Err(RunError::Transport(_)) => {
+                    let (mut inner, log) = transport.into_parts();
+                    assert_eq!(log.received(), 3);
+                    inner.receives_before_crash = None;
+
+                    // Restore the session from scratch using the same seed and the saved log.
+                    let log = saved_log.lock().unwrap().clone();
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let session = make_session(&mut rng);
+                    let mut transport = ResumableTransport::restore(inner, log, |_log| {});
+                    run_session(&mut rng, &mut transport, session)
+                        .await
+                        .unwrap()
+                }
+                Err(_) => panic!("Unexpected session error"),
+            }
+        })
+    })
+    .await;
+
+    for (idx, key_share) in key_shares.iter().enumerate() {
+        assert_eq!(key_share.party_index(), idx);
+        assert_eq!(key_share.num_parties(), num_parties);
+        assert_eq!(key_share.verifying_key(), key_shares[0].verifying_key());
     }
 }
@@ -0,0 +1,87 @@
+//! Checks that the current code reproduces the pinned transcripts of the ceremonies.
+//!
+//! If a change of the messages or the stored results is intended,
+//! the vectors can be regenerated with
+//! `cargo test --features test-vectors --test test_vectors -- --ignored regenerate`.
+//!
+//! The vectors are created without the `rayon` feature, which changes the use of the randomness,
+//! and on secp256k1, so they are skipped with the `p256` feature.
+
+#![cfg(not(any(feature = "rayon", feature = "p256")))]
+
+use std::path::PathBuf;
+
+use synedrion::{
+    test_vectors::{Ceremony, TestVector, TestVectorError},
+    TestParams,
+};
+
+const SEED: [u8; 32] = [7; 32];
+
+fn vector_path(ceremony: Ceremony) -> PathBuf {
+    let name = match ceremony {
+        Ceremony::KeyGen => "key_gen",
+        Ceremony::KeyRefresh => "key_refresh",
+        Ceremony::InteractiveSigning => "interactive_signing",
+    };
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("vectors")
+        .join(format!("{name}.json"))
+}
+
+fn check_pinned(ceremony: Ceremony) {
+    let json = std::fs::read_to_string(vector_path(ceremony)).unwrap();
+    let vector = TestVector::from_json(&json).unwrap();
+    assert_eq!(vector.ceremony, ceremony);
+    assert_eq!(vector.seed, SEED);
+    vector.check::<TestParams>().unwrap();
+}
+
+#[test]
+fn key_gen() {
+    check_pinned(Ceremony::KeyGen);
+}
+
+#[test]
+fn key_refresh() {
+    check_pinned(Ceremony::KeyRefresh);
+}
+
+#[test]
+fn interactive_signing() {
+    check_pinned(Ceremony::InteractiveSigning);
+}
+
+#[test]
+fn mismatch_detected() {
+    let json = std::fs::read_to_string(vector_path(Ceremony::KeyGen)).unwrap();
+    let mut vector = TestVector::from_json(&json).unwrap();
+    let message = &mut vector.messages[4];
+    let (round, from, to) = (message.round, message.from, message.to);
+    let last = message.message.len() - 1;
+    message.message[last] ^= 1;
+    assert_eq!(
+        vector.check::<TestParams>(),
+        Err(TestVectorError::MessageMismatch {
+            index: 4,
+            round,
+            from,
+            to
+        })
+    );
+}
+
+#[test]
+#[ignore]
+fn regenerate() {
+    for ceremony in [
+        Ceremony::KeyGen,
+        Ceremony::KeyRefresh,
+        Ceremony::InteractiveSigning,
+    ] {
+        let vector = TestVector::generate::<TestParams>(ceremony, &SEED).unwrap();
+        std::fs::create_dir_all(vector_path(ceremony).parent().unwrap()).unwrap();
+        std::fs::write(vector_path(ceremony), vector.to_json() + "\n").unwrap();
+    }
+}